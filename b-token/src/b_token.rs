@@ -0,0 +1,195 @@
+use crate::{
+    dependencies::PoolClient,
+    errors::BTokenError,
+    events::BTokenEvents,
+    storage::{self, AllowanceValue, BTokenMeta},
+};
+use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, Env, String};
+
+/// ### BToken
+///
+/// A SEP-41 token representing the non-collateral supply of a single pool reserve. The pool
+/// deploys a bToken for a reserve and remains the source of truth for all balances, so
+/// transfers move supply positions directly within the pool.
+#[contract]
+pub struct BTokenContract;
+
+#[contractimpl]
+impl BTokenContract {
+    /// Construct the bToken contract
+    ///
+    /// ### Arguments
+    /// * `pool` - The pool that issues the bTokens
+    /// * `asset` - The underlying asset of the reserve
+    /// * `name` - The name of the bToken
+    /// * `symbol` - The symbol of the bToken
+    pub fn __constructor(e: Env, pool: Address, asset: Address, name: String, symbol: String) {
+        let decimals = token::Client::new(&e, &asset).decimals();
+        storage::set_meta(
+            &e,
+            &BTokenMeta {
+                pool,
+                asset,
+                decimals,
+                name,
+                symbol,
+            },
+        );
+    }
+
+    /// Fetch the allowance "from" has given "spender"
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the owner of the bTokens
+    /// * `spender` - The address of the spender
+    pub fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_allowance(&e, &from, &spender).amount
+    }
+
+    /// Allow "spender" to spend up to "amount" of the bTokens of "from"
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the owner of the bTokens
+    /// * `spender` - The address of the spender
+    /// * `amount` - The amount of bTokens "spender" can spend
+    /// * `expiration_ledger` - The ledger the allowance expires at
+    ///
+    /// ### Panics
+    /// If the amount is negative or the expiration ledger has passed for a non-zero amount
+    pub fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+        storage::extend_instance(&e);
+
+        if amount < 0 {
+            panic_with_error!(&e, BTokenError::NegativeAmountError);
+        }
+        if amount > 0 && expiration_ledger < e.ledger().sequence() {
+            panic_with_error!(&e, BTokenError::InvalidExpirationLedger);
+        }
+        storage::set_allowance(
+            &e,
+            &from,
+            &spender,
+            &AllowanceValue {
+                amount,
+                expiration_ledger,
+            },
+        );
+
+        BTokenEvents::approve(&e, from, spender, amount, expiration_ledger);
+    }
+
+    /// Fetch the bToken balance of "id"
+    ///
+    /// ### Arguments
+    /// * `id` - The address to fetch the balance of
+    pub fn balance(e: Env, id: Address) -> i128 {
+        storage::extend_instance(&e);
+        let meta = storage::get_meta(&e);
+        PoolClient::new(&e, &meta.pool).b_token_balance(&meta.asset, &id)
+    }
+
+    /// Fetch the total amount of bTokens issued by the pool for the reserve, including
+    /// bTokens held as collateral
+    pub fn total_supply(e: Env) -> i128 {
+        storage::extend_instance(&e);
+        let meta = storage::get_meta(&e);
+        PoolClient::new(&e, &meta.pool).b_token_supply(&meta.asset)
+    }
+
+    /// Transfer "amount" bTokens from "from" to "to"
+    ///
+    /// ### Arguments
+    /// * `from` - The address sending the bTokens
+    /// * `to` - The address receiving the bTokens
+    /// * `amount` - The amount of bTokens to transfer
+    ///
+    /// ### Panics
+    /// If "from" does not hold enough non-collateral supply or the pool rejects the transfer
+    pub fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        storage::extend_instance(&e);
+
+        transfer_b_tokens(&e, from, to, amount);
+    }
+
+    /// Transfer "amount" bTokens from "from" to "to", spending the allowance of "spender"
+    ///
+    /// ### Arguments
+    /// * `spender` - The address spending the bTokens
+    /// * `from` - The address sending the bTokens
+    /// * `to` - The address receiving the bTokens
+    /// * `amount` - The amount of bTokens to transfer
+    ///
+    /// ### Panics
+    /// If "spender" does not have enough allowance, or the transfer fails
+    pub fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        storage::extend_instance(&e);
+
+        spend_allowance(&e, &from, &spender, amount);
+        transfer_b_tokens(&e, from, to, amount);
+    }
+
+    /// Not supported, as bTokens can only be burnt by withdrawing from the pool
+    pub fn burn(e: Env, _from: Address, _amount: i128) {
+        panic_with_error!(&e, BTokenError::NotSupported);
+    }
+
+    /// Not supported, as bTokens can only be burnt by withdrawing from the pool
+    pub fn burn_from(e: Env, _spender: Address, _from: Address, _amount: i128) {
+        panic_with_error!(&e, BTokenError::NotSupported);
+    }
+
+    /// Fetch the decimals of the bToken, which match the underlying asset
+    pub fn decimals(e: Env) -> u32 {
+        storage::get_meta(&e).decimals
+    }
+
+    /// Fetch the name of the bToken
+    pub fn name(e: Env) -> String {
+        storage::get_meta(&e).name
+    }
+
+    /// Fetch the symbol of the bToken
+    pub fn symbol(e: Env) -> String {
+        storage::get_meta(&e).symbol
+    }
+
+    /// Fetch the pool and underlying asset of the bToken
+    pub fn pool(e: Env) -> (Address, Address) {
+        let meta = storage::get_meta(&e);
+        (meta.pool, meta.asset)
+    }
+}
+
+/// Move "amount" of non-collateral supply from "from" to "to" in the pool
+fn transfer_b_tokens(e: &Env, from: Address, to: Address, amount: i128) {
+    if amount < 0 {
+        panic_with_error!(e, BTokenError::NegativeAmountError);
+    }
+    let meta = storage::get_meta(e);
+    PoolClient::new(e, &meta.pool).b_token_transfer(&meta.asset, &from, &to, &amount);
+
+    BTokenEvents::transfer(e, from, to, amount);
+}
+
+/// Spend "amount" of the allowance "from" has given "spender"
+fn spend_allowance(e: &Env, from: &Address, spender: &Address, amount: i128) {
+    let allowance = storage::get_allowance(e, from, spender);
+    if allowance.amount < amount {
+        panic_with_error!(e, BTokenError::AllowanceError);
+    }
+    if amount > 0 {
+        storage::set_allowance(
+            e,
+            from,
+            spender,
+            &AllowanceValue {
+                amount: allowance.amount - amount,
+                expiration_ledger: allowance.expiration_ledger,
+            },
+        );
+    }
+}
@@ -0,0 +1,2 @@
+mod pool;
+pub use pool::PoolClient;
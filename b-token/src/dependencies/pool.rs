@@ -0,0 +1,14 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// The bToken accounting exposed by the pool that deployed the bToken
+#[contractclient(name = "PoolClient")]
+pub trait Pool {
+    /// Fetch the amount of bTokens "id" holds as non-collateral supply of "asset"
+    fn b_token_balance(e: Env, asset: Address, id: Address) -> i128;
+
+    /// Fetch the total amount of bTokens issued for "asset"
+    fn b_token_supply(e: Env, asset: Address) -> i128;
+
+    /// Transfer non-collateral supply of "asset" from "from" to "to"
+    fn b_token_transfer(e: Env, asset: Address, from: Address, to: Address, amount: i128);
+}
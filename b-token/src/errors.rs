@@ -0,0 +1,19 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+/// Error codes for the bToken contract. Common errors are codes that match up with the built-in
+/// contracts error reporting. bToken specific errors start at 1400.
+pub enum BTokenError {
+    // Common Errors
+    InternalError = 1,
+    AlreadyInitializedError = 3,
+
+    NegativeAmountError = 8,
+    AllowanceError = 9,
+
+    // bToken
+    InvalidExpirationLedger = 1400,
+    NotSupported = 1401,
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+
+mod b_token;
+mod dependencies;
+mod errors;
+mod events;
+mod storage;
+
+pub use b_token::*;
+pub use errors::BTokenError;
+pub use storage::{AllowanceDataKey, AllowanceValue, BTokenDataKey, BTokenMeta};
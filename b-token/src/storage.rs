@@ -0,0 +1,110 @@
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, String, Symbol};
+
+/********** Ledger Thresholds **********/
+
+const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5s a ledger
+
+const LEDGER_THRESHOLD_INSTANCE: u32 = ONE_DAY_LEDGERS * 30; // ~ 30 days
+const LEDGER_BUMP_INSTANCE: u32 = LEDGER_THRESHOLD_INSTANCE + ONE_DAY_LEDGERS; // ~ 31 days
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceDataKey {
+    pub from: Address,
+    pub spender: Address,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum BTokenDataKey {
+    Allowance(AllowanceDataKey),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct BTokenMeta {
+    pub pool: Address,  // the pool that issues the bTokens
+    pub asset: Address, // the underlying asset of the reserve
+    pub decimals: u32,
+    pub name: String,
+    pub symbol: String,
+}
+
+/// Bump the instance rent for the contract
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LEDGER_THRESHOLD_INSTANCE, LEDGER_BUMP_INSTANCE);
+}
+
+/// Fetch the bToken metadata
+pub fn get_meta(e: &Env) -> BTokenMeta {
+    e.storage()
+        .instance()
+        .get::<Symbol, BTokenMeta>(&Symbol::new(e, "Meta"))
+        .unwrap_optimized()
+}
+
+/// Set the bToken metadata
+///
+/// ### Arguments
+/// * `meta` - The metadata of the bToken
+pub fn set_meta(e: &Env, meta: &BTokenMeta) {
+    e.storage()
+        .instance()
+        .set::<Symbol, BTokenMeta>(&Symbol::new(e, "Meta"), meta)
+}
+
+/// Fetch the allowance "from" has given "spender"
+///
+/// Returns an empty allowance if none exists or it has expired
+///
+/// ### Arguments
+/// * `from` - The address of the owner of the tokens
+/// * `spender` - The address of the spender
+pub fn get_allowance(e: &Env, from: &Address, spender: &Address) -> AllowanceValue {
+    let key = BTokenDataKey::Allowance(AllowanceDataKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    match e
+        .storage()
+        .temporary()
+        .get::<BTokenDataKey, AllowanceValue>(&key)
+    {
+        Some(allowance) if allowance.expiration_ledger >= e.ledger().sequence() => allowance,
+        _ => AllowanceValue {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+/// Set the allowance "from" has given "spender"
+///
+/// ### Arguments
+/// * `from` - The address of the owner of the tokens
+/// * `spender` - The address of the spender
+/// * `allowance` - The allowance
+pub fn set_allowance(e: &Env, from: &Address, spender: &Address, allowance: &AllowanceValue) {
+    let key = BTokenDataKey::Allowance(AllowanceDataKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    e.storage()
+        .temporary()
+        .set::<BTokenDataKey, AllowanceValue>(&key, allowance);
+    if allowance.amount > 0 {
+        let ledgers_to_live = allowance.expiration_ledger - e.ledger().sequence();
+        e.storage()
+            .temporary()
+            .extend_ttl(&key, ledgers_to_live, ledgers_to_live);
+    }
+}
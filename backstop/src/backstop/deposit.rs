@@ -2,12 +2,39 @@ use crate::{contract::require_nonnegative, emissions, storage, BackstopError};
 use sep_41_token::TokenClient;
 use soroban_sdk::{panic_with_error, Address, Env};
 
-use super::require_is_from_pool_factory;
+use super::{load_pool_backstop_token, require_is_from_pool_factory};
 
 /// Perform a deposit into the backstop module
 pub fn execute_deposit(e: &Env, from: &Address, pool_address: &Address, amount: i128) -> i128 {
+    deposit(e, from, from, pool_address, amount, false)
+}
+
+/// Perform a deposit into the backstop module for `depositor`, where the backstop tokens are
+/// pulled from `spender` with `transfer_from`
+pub fn execute_deposit_from(
+    e: &Env,
+    spender: &Address,
+    depositor: &Address,
+    pool_address: &Address,
+    amount: i128,
+) -> i128 {
+    deposit(e, spender, depositor, pool_address, amount, true)
+}
+
+fn deposit(
+    e: &Env,
+    spender: &Address,
+    from: &Address,
+    pool_address: &Address,
+    amount: i128,
+    use_allowance: bool,
+) -> i128 {
     require_nonnegative(e, amount);
-    if from == pool_address || from == &e.current_contract_address() {
+    let backstop = e.current_contract_address();
+    if from == pool_address || from == &backstop {
+        panic_with_error!(e, &BackstopError::BadRequest)
+    }
+    if spender == pool_address || spender == &backstop {
         panic_with_error!(e, &BackstopError::BadRequest)
     }
     let mut pool_balance = storage::get_pool_balance(e, pool_address);
@@ -16,8 +43,12 @@ pub fn execute_deposit(e: &Env, from: &Address, pool_address: &Address, amount:
 
     emissions::update_emissions(e, pool_address, &pool_balance, from, &user_balance);
 
-    let backstop_token_client = TokenClient::new(e, &storage::get_backstop_token(e));
-    backstop_token_client.transfer(from, &e.current_contract_address(), &amount);
+    let backstop_token_client = TokenClient::new(e, &load_pool_backstop_token(e, pool_address));
+    if use_allowance {
+        backstop_token_client.transfer_from(&backstop, spender, &backstop, &amount);
+    } else {
+        backstop_token_client.transfer(spender, &backstop, &amount);
+    }
 
     let to_mint = pool_balance.convert_to_shares(amount);
     if to_mint == 0 {
@@ -107,6 +138,52 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_execute_deposit_from() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let backstop_address = create_backstop(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool_0_id = Address::generate(&e);
+
+        let (_, backstop_token_client) = create_backstop_token(&e, &backstop_address, &bombadil);
+        backstop_token_client.mint(&samwise, &100_0000000);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        backstop_token_client.approve(
+            &samwise,
+            &backstop_address,
+            &60_0000000,
+            &e.ledger().sequence(),
+        );
+        e.as_contract(&backstop_address, || {
+            let shares = execute_deposit_from(&e, &samwise, &frodo, &pool_0_id, 60_0000000);
+            assert_eq!(shares, 60_0000000);
+
+            let new_pool_balance = storage::get_pool_balance(&e, &pool_0_id);
+            assert_eq!(new_pool_balance.shares, 60_0000000);
+            assert_eq!(new_pool_balance.tokens, 60_0000000);
+
+            let frodo_balance = storage::get_user_balance(&e, &pool_0_id, &frodo);
+            assert_eq!(frodo_balance.shares, 60_0000000);
+            let samwise_balance = storage::get_user_balance(&e, &pool_0_id, &samwise);
+            assert_eq!(samwise_balance.shares, 0);
+
+            assert_eq!(backstop_token_client.balance(&backstop_address), 60_0000000);
+            assert_eq!(backstop_token_client.balance(&samwise), 40_0000000);
+            assert_eq!(
+                backstop_token_client.allowance(&samwise, &backstop_address),
+                0
+            );
+        });
+    }
+
     #[test]
     #[should_panic]
     fn test_execute_deposit_too_many_tokens() {
@@ -4,9 +4,14 @@ use crate::{
 };
 use sep_41_token::TokenClient;
 use soroban_fixed_point_math::FixedPoint;
-use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env};
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    panic_with_error,
+    unwrap::UnwrapOptimized,
+    vec, Address, Env, IntoVal, Symbol, Val, Vec,
+};
 
-use super::require_is_from_pool_factory;
+use super::{load_pool_backstop_token, require_is_from_pool_factory};
 
 /// Perform a draw from a pool's backstop
 ///
@@ -19,10 +24,60 @@ pub fn execute_draw(e: &Env, pool_address: &Address, amount: i128, to: &Address)
     pool_balance.withdraw(e, amount, 0);
     storage::set_pool_balance(e, pool_address, &pool_balance);
 
-    let backstop_token = TokenClient::new(e, &storage::get_backstop_token(e));
+    let backstop_token = TokenClient::new(e, &load_pool_backstop_token(e, pool_address));
     backstop_token.transfer(&e.current_contract_address(), to, &amount);
 }
 
+/// Perform a draw of USDC from a pool's backstop by exiting the backstop tokens required to
+/// withdraw `amount` USDC from the LP. Returns the amount of backstop tokens burned.
+///
+/// The backstop tokens burned are capped at `max_tokens_in`, which can be at most 140% of the
+/// value of the USDC drawn at the LP's current spot value, to limit the slippage of the single
+/// sided exit.
+///
+/// `pool_address` MUST be authenticated before calling
+pub fn execute_draw_usdc(
+    e: &Env,
+    pool_address: &Address,
+    amount: i128,
+    to: &Address,
+    max_tokens_in: i128,
+) -> i128 {
+    require_nonnegative(e, amount);
+    require_nonnegative(e, max_tokens_in);
+
+    let mut pool_balance = storage::get_pool_balance(e, pool_address);
+
+    // each backstop token is 20% USDC by value, so the USDC value of a token is 5x its USDC
+    let backstop_token = load_pool_backstop_token(e, pool_address);
+    let usdc_token = storage::get_usdc_token(e);
+    let (_, usdc_per_tkn) =
+        load_comet_token_value(e, &backstop_token, &storage::get_blnd_token(e), &usdc_token);
+    let spot_max_tokens_in = amount
+        .fixed_div_ceil(usdc_per_tkn * 5, SCALAR_7)
+        .unwrap_optimized()
+        .fixed_mul_ceil(1_4000000, SCALAR_7)
+        .unwrap_optimized();
+    if max_tokens_in > spot_max_tokens_in {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+    let max_tokens_in = max_tokens_in.min(pool_balance.tokens);
+
+    let tokens_in = CometClient::new(e, &backstop_token)
+        .wdr_tokn_amt_out_get_lp_tokns_in(
+            &usdc_token,
+            &amount,
+            &max_tokens_in,
+            &e.current_contract_address(),
+        );
+
+    pool_balance.withdraw(e, tokens_in, 0);
+    storage::set_pool_balance(e, pool_address, &pool_balance);
+
+    TokenClient::new(e, &usdc_token).transfer(&e.current_contract_address(), to, &amount);
+    tokens_in
+}
+
 /// Perform a donation to a pool's backstop
 pub fn execute_donate(e: &Env, from: &Address, pool_address: &Address, amount: i128) {
     require_nonnegative(e, amount);
@@ -33,7 +88,7 @@ pub fn execute_donate(e: &Env, from: &Address, pool_address: &Address, amount: i
     let mut pool_balance = storage::get_pool_balance(e, pool_address);
     require_is_from_pool_factory(e, pool_address, pool_balance.shares);
 
-    let backstop_token = TokenClient::new(e, &storage::get_backstop_token(e));
+    let backstop_token = TokenClient::new(e, &load_pool_backstop_token(e, pool_address));
     backstop_token.transfer_from(
         &e.current_contract_address(),
         from,
@@ -45,6 +100,86 @@ pub fn execute_donate(e: &Env, from: &Address, pool_address: &Address, amount: i
     storage::set_pool_balance(e, pool_address, &pool_balance);
 }
 
+/// Perform a donation of BLND or USDC to a pool's backstop by joining the backstop token LP with
+/// the donated tokens. Returns the amount of backstop tokens minted.
+///
+/// The backstop tokens minted must be at least `min_tokens_out`, which must be at least 1 / 1.4 of
+/// the value of the donated tokens at the LP's current spot value, to limit the slippage of the
+/// single sided join.
+///
+/// `pool_address` MUST be authenticated before calling
+pub fn execute_donate_underlying(
+    e: &Env,
+    pool_address: &Address,
+    token: &Address,
+    amount: i128,
+    min_tokens_out: i128,
+) -> i128 {
+    require_nonnegative(e, amount);
+
+    let mut pool_balance = storage::get_pool_balance(e, pool_address);
+    require_is_from_pool_factory(e, pool_address, pool_balance.shares);
+
+    // each backstop token is 80% BLND and 20% USDC by value
+    let backstop_token = load_pool_backstop_token(e, pool_address);
+    let blnd_token = storage::get_blnd_token(e);
+    let usdc_token = storage::get_usdc_token(e);
+    let (blnd_per_tkn, usdc_per_tkn) =
+        load_comet_token_value(e, &backstop_token, &blnd_token, &usdc_token);
+    let token_per_tkn = if token == &blnd_token {
+        blnd_per_tkn * 5 / 4
+    } else if token == &usdc_token {
+        usdc_per_tkn * 5
+    } else {
+        panic_with_error!(e, BackstopError::BadRequest);
+    };
+    let spot_min_tokens_out = amount
+        .fixed_div_floor(token_per_tkn, SCALAR_7)
+        .unwrap_optimized()
+        .fixed_div_floor(1_4000000, SCALAR_7)
+        .unwrap_optimized();
+    if min_tokens_out < spot_min_tokens_out {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+
+    TokenClient::new(e, token).transfer_from(
+        &e.current_contract_address(),
+        pool_address,
+        &e.current_contract_address(),
+        &amount,
+    );
+
+    let approval_ledger = e.ledger().sequence();
+    let args: Vec<Val> = vec![
+        e,
+        (&e.current_contract_address()).into_val(e),
+        (&backstop_token).into_val(e),
+        (&amount).into_val(e),
+        (&approval_ledger).into_val(e),
+    ];
+    e.authorize_as_current_contract(vec![
+        e,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: token.clone(),
+                fn_name: Symbol::new(e, "approve"),
+                args,
+            },
+            sub_invocations: vec![e],
+        }),
+    ]);
+    let tokens_out = CometClient::new(e, &backstop_token).dep_tokn_amt_in_get_lp_tokns_out(
+        token,
+        &amount,
+        &min_tokens_out,
+        &e.current_contract_address(),
+    );
+
+    pool_balance.deposit(tokens_out, 0);
+    storage::set_pool_balance(e, pool_address, &pool_balance);
+    tokens_out
+}
+
 /// Perform an update to the Comet LP token underlying value
 pub fn execute_update_comet_token_value(
     e: &Env,
@@ -52,9 +187,26 @@ pub fn execute_update_comet_token_value(
     blnd_token: &Address,
     usdc_token: &Address,
 ) -> (i128, i128) {
-    let total_comet_shares = CometClient::new(e, backstop_token).get_total_supply();
-    let total_blnd = TokenClient::new(e, &blnd_token).balance(backstop_token);
-    let total_usdc = TokenClient::new(e, &usdc_token).balance(backstop_token);
+    let lp_token_val = load_comet_token_value(e, backstop_token, blnd_token, usdc_token);
+    storage::set_lp_token_val(e, &lp_token_val);
+    lp_token_val
+}
+
+/// Calculate the BLND and USDC held per LP token of a comet pool
+///
+/// ### Arguments
+/// * `comet` - The address of the comet pool
+/// * `blnd_token` - The address of the BLND token
+/// * `usdc_token` - The address of the USDC token
+pub fn load_comet_token_value(
+    e: &Env,
+    comet: &Address,
+    blnd_token: &Address,
+    usdc_token: &Address,
+) -> (i128, i128) {
+    let total_comet_shares = CometClient::new(e, comet).get_total_supply();
+    let total_blnd = TokenClient::new(e, &blnd_token).balance(comet);
+    let total_usdc = TokenClient::new(e, &usdc_token).balance(comet);
 
     // underlying per LP token
     let blnd_per_tkn = total_blnd
@@ -64,9 +216,7 @@ pub fn execute_update_comet_token_value(
         .fixed_div_floor(total_comet_shares, SCALAR_7)
         .unwrap_optimized();
 
-    let lp_token_val = (blnd_per_tkn, usdc_per_tkn);
-    storage::set_lp_token_val(e, &lp_token_val);
-    lp_token_val
+    (blnd_per_tkn, usdc_per_tkn)
 }
 
 #[cfg(test)]
@@ -74,10 +224,10 @@ mod tests {
     use soroban_sdk::{testutils::Address as _, Address};
 
     use crate::{
-        backstop::execute_deposit,
+        backstop::{execute_deposit, PoolBalance},
         testutils::{
             create_backstop, create_backstop_token, create_blnd_token, create_comet_lp_pool,
-            create_mock_pool_factory, create_usdc_token,
+            create_mock_pool_factory, create_token, create_usdc_token,
         },
     };
 
@@ -326,6 +476,229 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_execute_draw_usdc() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_id = create_backstop(&e);
+        let pool_0_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (usdc_token, usdc_token_client) = create_usdc_token(&e, &backstop_id, &bombadil);
+        let (blnd_token, _) = create_blnd_token(&e, &backstop_id, &bombadil);
+        let (comet_id, comet_client) =
+            create_comet_lp_pool(&e, &bombadil, &blnd_token, &usdc_token);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_id);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        e.as_contract(&backstop_id, || {
+            storage::set_backstop_token(&e, &comet_id);
+            storage::set_usdc_token(&e, &usdc_token);
+            execute_update_comet_token_value(&e, &comet_id, &blnd_token, &usdc_token);
+        });
+
+        // initialize pool 0 with funds
+        e.as_contract(&backstop_id, || {
+            execute_deposit(&e, &bombadil, &pool_0_id, 50_0000000);
+        });
+
+        e.as_contract(&backstop_id, || {
+            let tokens_in = execute_draw_usdc(&e, &pool_0_id, 1_0000000, &samwise, 1_0000000);
+
+            // 1 USDC is worth 0.8 backstop tokens before slippage and fees
+            assert!(tokens_in > 0_8000000);
+            assert!(tokens_in <= 1_0000000);
+
+            let new_pool_balance = storage::get_pool_balance(&e, &pool_0_id);
+            assert_eq!(new_pool_balance.shares, 50_0000000);
+            assert_eq!(new_pool_balance.tokens, 50_0000000 - tokens_in);
+            assert_eq!(comet_client.balance(&backstop_id), 50_0000000 - tokens_in);
+            assert_eq!(usdc_token_client.balance(&samwise), 1_0000000);
+        });
+    }
+
+    #[test]
+    fn test_execute_donate_underlying() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_id = create_backstop(&e);
+        let pool_0_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+
+        let (usdc_token, usdc_token_client) = create_usdc_token(&e, &backstop_id, &bombadil);
+        let (blnd_token, _) = create_blnd_token(&e, &backstop_id, &bombadil);
+        let (comet_id, comet_client) =
+            create_comet_lp_pool(&e, &bombadil, &blnd_token, &usdc_token);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_id);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        e.as_contract(&backstop_id, || {
+            storage::set_backstop_token(&e, &comet_id);
+            execute_update_comet_token_value(&e, &comet_id, &blnd_token, &usdc_token);
+        });
+
+        // initialize pool 0 with funds
+        e.as_contract(&backstop_id, || {
+            execute_deposit(&e, &bombadil, &pool_0_id, 50_0000000);
+        });
+
+        usdc_token_client.mint(&pool_0_id, &1_0000000);
+        usdc_token_client.approve(&pool_0_id, &backstop_id, &1_0000000, &e.ledger().sequence());
+        e.as_contract(&backstop_id, || {
+            let tokens_out =
+                execute_donate_underlying(&e, &pool_0_id, &usdc_token, 1_0000000, 0_7000000);
+
+            // 1 USDC is worth 0.8 backstop tokens before slippage and fees
+            assert!(tokens_out > 0_7000000);
+            assert!(tokens_out < 0_8000000);
+
+            let new_pool_balance = storage::get_pool_balance(&e, &pool_0_id);
+            assert_eq!(new_pool_balance.shares, 50_0000000);
+            assert_eq!(new_pool_balance.tokens, 50_0000000 + tokens_out);
+            assert_eq!(comet_client.balance(&backstop_id), 50_0000000 + tokens_out);
+            assert_eq!(usdc_token_client.balance(&pool_0_id), 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_donate_underlying_not_lp_asset() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_id = create_backstop(&e);
+        let pool_0_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+
+        let (usdc_token, _) = create_usdc_token(&e, &backstop_id, &bombadil);
+        let (blnd_token, _) = create_blnd_token(&e, &backstop_id, &bombadil);
+        let (comet_id, _) = create_comet_lp_pool(&e, &bombadil, &blnd_token, &usdc_token);
+        let (other_token, _) = create_token(&e, &bombadil);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_id);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        e.as_contract(&backstop_id, || {
+            storage::set_backstop_token(&e, &comet_id);
+            execute_update_comet_token_value(&e, &comet_id, &blnd_token, &usdc_token);
+            execute_deposit(&e, &bombadil, &pool_0_id, 50_0000000);
+            execute_donate_underlying(&e, &pool_0_id, &other_token, 1_0000000, 0_7000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_donate_underlying_min_tokens_out_under_spot_value() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_id = create_backstop(&e);
+        let pool_0_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+
+        let (usdc_token, usdc_token_client) = create_usdc_token(&e, &backstop_id, &bombadil);
+        let (blnd_token, _) = create_blnd_token(&e, &backstop_id, &bombadil);
+        let (comet_id, _) = create_comet_lp_pool(&e, &bombadil, &blnd_token, &usdc_token);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_id);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        usdc_token_client.mint(&pool_0_id, &1_0000000);
+        usdc_token_client.approve(&pool_0_id, &backstop_id, &1_0000000, &e.ledger().sequence());
+        e.as_contract(&backstop_id, || {
+            storage::set_backstop_token(&e, &comet_id);
+            execute_update_comet_token_value(&e, &comet_id, &blnd_token, &usdc_token);
+            execute_deposit(&e, &bombadil, &pool_0_id, 50_0000000);
+
+            // 1 USDC is worth 0.8 backstop tokens, so at least 0.57 tokens must be minted
+            execute_donate_underlying(&e, &pool_0_id, &usdc_token, 1_0000000, 0_5000000);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_execute_draw_usdc_over_pool_balance() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_id = create_backstop(&e);
+        let pool_0_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (usdc_token, _) = create_usdc_token(&e, &backstop_id, &bombadil);
+        let (blnd_token, _) = create_blnd_token(&e, &backstop_id, &bombadil);
+        let (comet_id, comet_client) =
+            create_comet_lp_pool(&e, &bombadil, &blnd_token, &usdc_token);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_id);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        e.as_contract(&backstop_id, || {
+            storage::set_backstop_token(&e, &comet_id);
+            storage::set_usdc_token(&e, &usdc_token);
+            execute_update_comet_token_value(&e, &comet_id, &blnd_token, &usdc_token);
+        });
+
+        // the backstop holds more tokens than are credited to pool 0
+        comet_client.transfer(&bombadil, &backstop_id, &50_0000000);
+        e.as_contract(&backstop_id, || {
+            storage::set_pool_balance(
+                &e,
+                &pool_0_id,
+                &PoolBalance {
+                    shares: 0_5000000,
+                    tokens: 0_5000000,
+                    q4w: 0,
+                },
+            );
+        });
+
+        e.as_contract(&backstop_id, || {
+            execute_draw_usdc(&e, &pool_0_id, 1_0000000, &samwise, 1_1000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_draw_usdc_max_tokens_in_over_spot_value() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_id = create_backstop(&e);
+        let pool_0_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (usdc_token, _) = create_usdc_token(&e, &backstop_id, &bombadil);
+        let (blnd_token, _) = create_blnd_token(&e, &backstop_id, &bombadil);
+        let (comet_id, _) = create_comet_lp_pool(&e, &bombadil, &blnd_token, &usdc_token);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_id);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        e.as_contract(&backstop_id, || {
+            storage::set_backstop_token(&e, &comet_id);
+            storage::set_usdc_token(&e, &usdc_token);
+            execute_update_comet_token_value(&e, &comet_id, &blnd_token, &usdc_token);
+            execute_deposit(&e, &bombadil, &pool_0_id, 50_0000000);
+
+            // 1 USDC is worth 0.8 backstop tokens, so at most 1.12 tokens can be burned
+            execute_draw_usdc(&e, &pool_0_id, 1_0000000, &samwise, 2_0000000);
+        });
+    }
+
     #[test]
     fn test_execute_update_comet_token_value() {
         let e = Env::default();
@@ -0,0 +1,371 @@
+use sep_41_token::TokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Vec};
+
+use crate::{
+    constants::SCALAR_7, dependencies::CometClient, emissions, errors::BackstopError, storage,
+};
+
+use super::{load_comet_token_value, require_is_from_pool_factory};
+
+/// Set the backstop token that pool deposits can be migrated to. The new backstop token must be
+/// a comet pool of BLND and USDC.
+///
+/// ### Panics
+/// If a new backstop token is already set or the token is invalid
+pub fn execute_set_backstop_token_v2(e: &Env, token: &Address) {
+    if storage::get_backstop_token_v2(e).is_some() {
+        panic_with_error!(e, BackstopError::InvalidMigration);
+    }
+    let blnd_token = storage::get_blnd_token(e);
+    let usdc_token = storage::get_usdc_token(e);
+    if token == &storage::get_backstop_token(e) || token == &blnd_token || token == &usdc_token {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+    storage::set_backstop_token_v2(e, token);
+    storage::set_lp_token_v2_val(e, &load_comet_token_value(e, token, &blnd_token, &usdc_token));
+}
+
+/// Migrate a pool's backstop deposits to the new backstop token. The backstop tokens held for
+/// the pool are converted in kind, by exiting the original backstop token's comet pool and
+/// joining the new backstop token's comet pool with the BLND and USDC received, so every
+/// depositor's share of the pool's backstop keeps the value of its underlying.
+///
+/// Shares, Q4W entries and emission indexes are not modified.
+///
+/// Returns the amount of new backstop tokens held for the pool
+///
+/// ### Arguments
+/// * `pool_address` - The address of the pool
+/// * `min_tokens_out` - The minimum amount of new backstop tokens the conversion must result in
+///
+/// ### Panics
+/// If no new backstop token is set, the pool's deposits have already been migrated, or the
+/// conversion results in less than `min_tokens_out`
+pub fn execute_migrate_deposit(e: &Env, pool_address: &Address, min_tokens_out: i128) -> i128 {
+    let token_v2 = match storage::get_backstop_token_v2(e) {
+        Some(token_v2) => token_v2,
+        None => panic_with_error!(e, BackstopError::InvalidMigration),
+    };
+    if storage::get_pool_migration_rate(e, pool_address).is_some() {
+        panic_with_error!(e, BackstopError::InvalidMigration);
+    }
+    let mut pool_balance = storage::get_pool_balance(e, pool_address);
+    require_is_from_pool_factory(e, pool_address, pool_balance.shares);
+
+    // accrue the pool's reward zone emissions before the pool's token units change
+    emissions::update_rz_emis_data(e, pool_address, false);
+
+    let tokens_v1 = pool_balance.tokens;
+    let (tokens_v2, rate) = if tokens_v1 > 0 {
+        let tokens_v2 = convert_backstop_tokens(e, &token_v2, tokens_v1);
+        let rate = tokens_v2
+            .fixed_div_floor(tokens_v1, SCALAR_7)
+            .unwrap_optimized();
+        (tokens_v2, rate)
+    } else {
+        (0, load_join_rate(e))
+    };
+    if tokens_v2 < min_tokens_out || rate <= 0 {
+        panic_with_error!(e, BackstopError::InvalidMigration);
+    }
+
+    pool_balance.tokens = tokens_v2;
+    storage::set_pool_balance(e, pool_address, &pool_balance);
+    storage::set_pool_migration_rate(e, pool_address, rate);
+
+    tokens_v2
+}
+
+/// Convert original backstop tokens held by the backstop to the new backstop token. The
+/// original tokens are exited from their comet pool, the BLND and USDC received are joined into
+/// the new comet pool proportionally, and any remainder the proportional join can't use is
+/// deposited on its own.
+///
+/// Returns the amount of new backstop tokens received
+fn convert_backstop_tokens(e: &Env, token_v2: &Address, tokens_v1: i128) -> i128 {
+    let backstop = e.current_contract_address();
+    let blnd_client = TokenClient::new(e, &storage::get_blnd_token(e));
+    let usdc_client = TokenClient::new(e, &storage::get_usdc_token(e));
+    let token_v2_client = TokenClient::new(e, token_v2);
+    let pre_blnd = blnd_client.balance(&backstop);
+    let pre_usdc = usdc_client.balance(&backstop);
+    let pre_tokens_v2 = token_v2_client.balance(&backstop);
+
+    CometClient::new(e, &storage::get_backstop_token(e)).exit_pool(
+        &tokens_v1,
+        &vec![e, 0, 0],
+        &backstop,
+    );
+    let blnd_out = blnd_client.balance(&backstop) - pre_blnd;
+    let usdc_out = usdc_client.balance(&backstop) - pre_usdc;
+
+    // join with one unit less than the ratio both amounts cover to absorb rounding in the join
+    let comet_v2 = CometClient::new(e, token_v2);
+    let blnd_ratio = blnd_out
+        .fixed_div_floor(comet_v2.get_balance(&blnd_client.address), SCALAR_7)
+        .unwrap_optimized();
+    let usdc_ratio = usdc_out
+        .fixed_div_floor(comet_v2.get_balance(&usdc_client.address), SCALAR_7)
+        .unwrap_optimized();
+    let ratio = blnd_ratio.min(usdc_ratio) - 1;
+    if ratio > 0 {
+        let pool_amount_out = ratio
+            .fixed_mul_floor(comet_v2.get_total_supply(), SCALAR_7)
+            .unwrap_optimized();
+        let mut max_amounts_in: Vec<i128> = Vec::new(e);
+        for token in comet_v2.get_tokens().iter() {
+            if token == blnd_client.address {
+                max_amounts_in.push_back(blnd_out);
+            } else {
+                max_amounts_in.push_back(usdc_out);
+            }
+        }
+        comet_v2.join_pool(&pool_amount_out, &max_amounts_in, &backstop);
+    }
+
+    for (client, pre_balance) in [(&blnd_client, pre_blnd), (&usdc_client, pre_usdc)] {
+        let remainder = client.balance(&backstop) - pre_balance;
+        if remainder > 0 {
+            comet_v2.dep_tokn_amt_in_get_lp_tokns_out(&client.address, &remainder, &0, &backstop);
+        }
+    }
+    token_v2_client.balance(&backstop) - pre_tokens_v2
+}
+
+/// Fetch the amount of new backstop tokens a proportional join would result in for each original
+/// backstop token, based on the last updated value of both LP tokens
+fn load_join_rate(e: &Env) -> i128 {
+    let (blnd_per_tkn, usdc_per_tkn) = storage::get_lp_token_val(e);
+    let (blnd_per_tkn_v2, usdc_per_tkn_v2) = storage::get_lp_token_v2_val(e);
+    let blnd_rate = blnd_per_tkn
+        .fixed_div_floor(blnd_per_tkn_v2, SCALAR_7)
+        .unwrap_optimized();
+    let usdc_rate = usdc_per_tkn
+        .fixed_div_floor(usdc_per_tkn_v2, SCALAR_7)
+        .unwrap_optimized();
+    blnd_rate.min(usdc_rate)
+}
+
+/// Fetch the backstop token a pool's deposits are held in
+pub fn load_pool_backstop_token(e: &Env, pool_address: &Address) -> Address {
+    match storage::get_pool_migration_rate(e, pool_address) {
+        Some(_) => storage::get_backstop_token_v2(e).unwrap_optimized(),
+        None => storage::get_backstop_token(e),
+    }
+}
+
+/// Fetch the BLND and USDC per backstop token for the token a pool's deposits are held in
+pub fn load_pool_lp_token_val(e: &Env, pool_address: &Address) -> (i128, i128) {
+    match storage::get_pool_migration_rate(e, pool_address) {
+        Some(_) => storage::get_lp_token_v2_val(e),
+        None => storage::get_lp_token_val(e),
+    }
+}
+
+/// Convert an amount of the token a pool's deposits are held in to original backstop tokens
+pub fn to_original_tokens(e: &Env, pool_address: &Address, tokens: i128) -> i128 {
+    match storage::get_pool_migration_rate(e, pool_address) {
+        Some(rate) => tokens.fixed_div_floor(rate, SCALAR_7).unwrap_optimized(),
+        None => tokens,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use soroban_sdk::{testutils::Address as _, Address};
+
+    use crate::{
+        backstop::{execute_deposit, execute_queue_withdrawal},
+        testutils::{
+            create_backstop, create_blnd_token, create_comet_lp_pool, create_mock_pool_factory,
+            create_usdc_token,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_execute_migrate_deposit() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let backstop_address = create_backstop(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool_0_id = Address::generate(&e);
+
+        let (blnd, blnd_client) = create_blnd_token(&e, &backstop_address, &bombadil);
+        let (usdc, usdc_client) = create_usdc_token(&e, &backstop_address, &bombadil);
+        let (token_v1, token_v1_client) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (token_v2, token_v2_client) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        token_v1_client.transfer(&bombadil, &samwise, &50_0000000);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        let tokens_v2 = e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &token_v1);
+            storage::set_lp_token_val(&e, &(10_0000000, 0_2500000));
+            execute_deposit(&e, &samwise, &pool_0_id, 50_0000000);
+            execute_queue_withdrawal(&e, &samwise, &pool_0_id, 10_0000000);
+
+            execute_set_backstop_token_v2(&e, &token_v2);
+            assert_eq!(storage::get_lp_token_v2_val(&e), (10_0000000, 0_2500000));
+            let tokens_v2 = execute_migrate_deposit(&e, &pool_0_id, 49_9000000);
+
+            // both comet pools hold the same underlying per LP token
+            assert!(tokens_v2 >= 49_9000000 && tokens_v2 <= 50_0000000);
+            let rate = tokens_v2
+                .fixed_div_floor(50_0000000, SCALAR_7)
+                .unwrap_optimized();
+            assert_eq!(storage::get_pool_migration_rate(&e, &pool_0_id), Some(rate));
+            assert_eq!(load_pool_backstop_token(&e, &pool_0_id), token_v2);
+            assert_eq!(
+                load_pool_lp_token_val(&e, &pool_0_id),
+                (10_0000000, 0_2500000)
+            );
+
+            let pool_balance = storage::get_pool_balance(&e, &pool_0_id);
+            assert_eq!(pool_balance.tokens, tokens_v2);
+            assert_eq!(pool_balance.shares, 50_0000000);
+            assert_eq!(pool_balance.q4w, 10_0000000);
+
+            let user_balance = storage::get_user_balance(&e, &pool_0_id, &samwise);
+            assert_eq!(user_balance.shares, 40_0000000);
+            assert_eq!(user_balance.q4w.len(), 1);
+            tokens_v2
+        });
+        assert_eq!(token_v1_client.balance(&backstop_address), 0);
+        assert_eq!(token_v2_client.balance(&backstop_address), tokens_v2);
+        assert_eq!(blnd_client.balance(&backstop_address), 0);
+        assert_eq!(usdc_client.balance(&backstop_address), 0);
+    }
+
+    #[test]
+    fn test_execute_migrate_deposit_empty_pool() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let backstop_address = create_backstop(&e);
+        let bombadil = Address::generate(&e);
+        let pool_0_id = Address::generate(&e);
+
+        let (blnd, _) = create_blnd_token(&e, &backstop_address, &bombadil);
+        let (usdc, _) = create_usdc_token(&e, &backstop_address, &bombadil);
+        let (token_v1, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (token_v2, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &token_v1);
+            storage::set_lp_token_val(&e, &(20_0000000, 0_5000000));
+
+            execute_set_backstop_token_v2(&e, &token_v2);
+            let tokens_v2 = execute_migrate_deposit(&e, &pool_0_id, 0);
+            assert_eq!(tokens_v2, 0);
+
+            // the rate is based on the value of both LP tokens
+            assert_eq!(
+                storage::get_pool_migration_rate(&e, &pool_0_id),
+                Some(2_0000000)
+            );
+            assert_eq!(load_pool_backstop_token(&e, &pool_0_id), token_v2);
+            assert_eq!(to_original_tokens(&e, &pool_0_id, 10_0000000), 5_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1012)")]
+    fn test_execute_migrate_deposit_under_min_tokens_out() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let backstop_address = create_backstop(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool_0_id = Address::generate(&e);
+
+        let (blnd, _) = create_blnd_token(&e, &backstop_address, &bombadil);
+        let (usdc, _) = create_usdc_token(&e, &backstop_address, &bombadil);
+        let (token_v1, token_v1_client) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (token_v2, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        token_v1_client.transfer(&bombadil, &samwise, &50_0000000);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &token_v1);
+            execute_deposit(&e, &samwise, &pool_0_id, 50_0000000);
+
+            execute_set_backstop_token_v2(&e, &token_v2);
+            execute_migrate_deposit(&e, &pool_0_id, 50_0000001);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1012)")]
+    fn test_execute_migrate_deposit_not_set() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_0_id = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            execute_migrate_deposit(&e, &pool_0_id, 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1012)")]
+    fn test_execute_migrate_deposit_twice() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let backstop_address = create_backstop(&e);
+        let bombadil = Address::generate(&e);
+        let pool_0_id = Address::generate(&e);
+
+        let (blnd, _) = create_blnd_token(&e, &backstop_address, &bombadil);
+        let (usdc, _) = create_usdc_token(&e, &backstop_address, &bombadil);
+        let (token_v1, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (token_v2, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_0_id);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &token_v1);
+            storage::set_lp_token_val(&e, &(10_0000000, 0_2500000));
+            execute_set_backstop_token_v2(&e, &token_v2);
+            execute_migrate_deposit(&e, &pool_0_id, 0);
+            execute_migrate_deposit(&e, &pool_0_id, 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_set_backstop_token_v2_original_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let bombadil = Address::generate(&e);
+        let (blnd, _) = create_blnd_token(&e, &backstop_address, &bombadil);
+        let (usdc, _) = create_usdc_token(&e, &backstop_address, &bombadil);
+        let (token_v1, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &token_v1);
+            execute_set_backstop_token_v2(&e, &token_v1);
+        });
+    }
+}
@@ -1,16 +1,34 @@
 mod deposit;
-pub use deposit::execute_deposit;
+pub use deposit::{execute_deposit, execute_deposit_from};
 
 mod fund_management;
-pub use fund_management::{execute_donate, execute_draw, execute_update_comet_token_value};
+pub use fund_management::{
+    execute_donate, execute_donate_underlying, execute_draw, execute_draw_usdc,
+    execute_update_comet_token_value, load_comet_token_value,
+};
+
+mod migration;
+pub use migration::{
+    execute_migrate_deposit, execute_set_backstop_token_v2, load_pool_backstop_token,
+    load_pool_lp_token_val, to_original_tokens,
+};
 
 mod withdrawal;
-pub use withdrawal::{execute_dequeue_withdrawal, execute_queue_withdrawal, execute_withdraw};
+pub use withdrawal::{
+    execute_dequeue_withdrawal, execute_dequeue_withdrawal_partial, execute_queue_withdrawal,
+    execute_withdraw,
+};
 
 mod pool;
 pub use pool::{
-    load_pool_backstop_data, require_is_from_pool_factory, require_pool_above_threshold,
-    PoolBackstopData, PoolBalance,
+    check_pool_bad_debt, execute_set_q4w_lock, load_pool_backstop_data,
+    require_is_from_pool_factory, require_pool_above_threshold, PoolBackstopData, PoolBalance,
+};
+
+mod share_token;
+pub use share_token::{
+    execute_deploy_share_token, execute_share_token_transfer, load_share_token_balance,
+    load_share_token_supply,
 };
 
 mod user;
@@ -1,7 +1,14 @@
 use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{contracttype, panic_with_error, unwrap::UnwrapOptimized, Address, Env};
 
-use crate::{constants::SCALAR_7, dependencies::PoolFactoryClient, errors::BackstopError, storage};
+use crate::{
+    constants::{MAX_Q4W_LOCK_TIME, Q4W_LOCK_TIME, SCALAR_7},
+    dependencies::{PoolClient, PoolFactoryClient},
+    errors::BackstopError,
+    storage,
+};
+
+use super::{load_pool_backstop_token, load_pool_lp_token_val};
 
 /// The pool's backstop data
 #[derive(Clone)]
@@ -24,7 +31,7 @@ pub fn load_pool_backstop_data(e: &Env, address: &Address) -> PoolBackstopData {
         0
     };
 
-    let (blnd_per_tkn, usdc_per_tkn) = storage::get_lp_token_val(e);
+    let (blnd_per_tkn, usdc_per_tkn) = load_pool_lp_token_val(e, address);
     let blnd = pool_balance
         .tokens
         .fixed_mul_floor(blnd_per_tkn, SCALAR_7)
@@ -42,6 +49,18 @@ pub fn load_pool_backstop_data(e: &Env, address: &Address) -> PoolBackstopData {
     }
 }
 
+/// Let the pool start a bad debt auction now that its backstop changed, in case its bad debt
+/// exceeds the pool's bad debt threshold and the backstop holds tokens to auction. Failures are
+/// ignored, so a pool can't block deposits into or withdrawals from its backstop.
+///
+/// ### Arguments
+/// * `pool` - The address of the pool
+pub fn check_pool_bad_debt(e: &Env, pool: &Address) {
+    let pool_backstop_data = load_pool_backstop_data(e, pool);
+    let backstop_token = load_pool_backstop_token(e, pool);
+    let _ = PoolClient::new(e, pool).try_check_bad_debt(&backstop_token, &pool_backstop_data);
+}
+
 /// Verify the pool address was deployed by the Pool Factory.
 ///
 /// If the pool has an outstanding balance, it is assumed that it was verified before.
@@ -61,6 +80,21 @@ pub fn require_is_from_pool_factory(e: &Env, address: &Address, balance: i128) {
     }
 }
 
+/// Set the time in seconds that new Q4W entries for a pool are locked for
+///
+/// ### Arguments
+/// * `pool_address` - The address of the pool
+/// * `lock_time` - The lock time in seconds
+///
+/// ### Panics
+/// If the lock time is not within the default and max Q4W lock time
+pub fn execute_set_q4w_lock(e: &Env, pool_address: &Address, lock_time: u64) {
+    if !(Q4W_LOCK_TIME..=MAX_Q4W_LOCK_TIME).contains(&lock_time) {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+    storage::set_q4w_lock(e, pool_address, &lock_time);
+}
+
 /// Calculate the threshold for the pool's backstop balance
 ///
 /// Returns true if the pool's backstop balance is above the threshold
@@ -234,6 +268,49 @@ mod tests {
         });
     }
 
+    /********** execute_set_q4w_lock **********/
+
+    #[test]
+    fn test_execute_set_q4w_lock() {
+        let e = Env::default();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            assert_eq!(storage::get_q4w_lock(&e, &pool_address), 21 * 24 * 60 * 60);
+
+            execute_set_q4w_lock(&e, &pool_address, 30 * 24 * 60 * 60);
+            assert_eq!(storage::get_q4w_lock(&e, &pool_address), 30 * 24 * 60 * 60);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_set_q4w_lock_under_default() {
+        let e = Env::default();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            execute_set_q4w_lock(&e, &pool_address, 21 * 24 * 60 * 60 - 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_set_q4w_lock_over_max() {
+        let e = Env::default();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            execute_set_q4w_lock(&e, &pool_address, 61 * 24 * 60 * 60);
+        });
+    }
+
     /********** require_is_from_pool_factory **********/
 
     #[test]
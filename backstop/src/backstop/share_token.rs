@@ -0,0 +1,314 @@
+use soroban_sdk::{panic_with_error, Address, Bytes, Env, String};
+
+use crate::{contract::require_nonnegative, emissions, errors::BackstopError, storage};
+
+use super::require_is_from_pool_factory;
+
+/// Deploy a share token contract for a pool's backstop, which exposes the backstop shares of the
+/// pool, excluding shares queued for withdrawal, as a transferable SEP-41 token. The share token
+/// is deployed from the wasm hash set by governance.
+///
+/// Returns the address of the share token contract
+///
+/// ### Arguments
+/// * `pool_address` - The address of the pool
+/// * `name` - The name of the share token
+/// * `symbol` - The symbol of the share token
+///
+/// ### Panics
+/// If the pool was not deployed by the pool factory, already has a share token, or governance
+/// has not set the share token wasm hash
+pub fn execute_deploy_share_token(
+    e: &Env,
+    pool_address: &Address,
+    name: &String,
+    symbol: &String,
+) -> Address {
+    if storage::get_share_token(e, pool_address).is_some() {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+    let wasm_hash = match storage::get_share_token_wasm(e) {
+        Some(wasm_hash) => wasm_hash,
+        None => panic_with_error!(e, BackstopError::BadRequest),
+    };
+    let pool_balance = storage::get_pool_balance(e, pool_address);
+    require_is_from_pool_factory(e, pool_address, pool_balance.shares);
+
+    let mut as_u8s: [u8; 56] = [0; 56];
+    pool_address.to_string().copy_into_slice(&mut as_u8s);
+    let salt = e.crypto().keccak256(&Bytes::from_array(e, &as_u8s));
+    let share_token = e.deployer().with_current_contract(salt).deploy_v2(
+        wasm_hash,
+        (
+            e.current_contract_address(),
+            pool_address.clone(),
+            name.clone(),
+            symbol.clone(),
+        ),
+    );
+    storage::set_share_token(e, pool_address, &share_token);
+    share_token
+}
+
+/// Transfer backstop shares of a pool between users on behalf of the pool's share token. The
+/// emissions of both users are accrued before their shares change.
+///
+/// ### Arguments
+/// * `pool_address` - The address of the pool
+/// * `from` - The address sending the shares
+/// * `to` - The address receiving the shares
+/// * `amount` - The amount of shares to transfer
+///
+/// ### Panics
+/// If the pool has no share token, the share token did not authorize the transfer, "from" does
+/// not hold enough shares that are not queued for withdrawal, or "to" is the pool or backstop
+pub fn execute_share_token_transfer(
+    e: &Env,
+    pool_address: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+) {
+    let share_token = match storage::get_share_token(e, pool_address) {
+        Some(share_token) => share_token,
+        None => panic_with_error!(e, BackstopError::BadRequest),
+    };
+    share_token.require_auth();
+    require_nonnegative(e, amount);
+    if from == to || to == pool_address || to == &e.current_contract_address() {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+    if amount == 0 {
+        return;
+    }
+
+    let pool_balance = storage::get_pool_balance(e, pool_address);
+    let mut from_balance = storage::get_user_balance(e, pool_address, from);
+    let mut to_balance = storage::get_user_balance(e, pool_address, to);
+    emissions::update_emissions(e, pool_address, &pool_balance, from, &from_balance);
+    emissions::update_emissions(e, pool_address, &pool_balance, to, &to_balance);
+
+    if from_balance.shares < amount {
+        panic_with_error!(e, BackstopError::BalanceError);
+    }
+    from_balance.shares -= amount;
+    to_balance.add_shares(amount);
+
+    storage::set_user_balance(e, pool_address, from, &from_balance);
+    storage::set_user_balance(e, pool_address, to, &to_balance);
+}
+
+/// Fetch the amount of backstop shares a user holds for a pool, excluding shares queued for
+/// withdrawal
+///
+/// ### Arguments
+/// * `pool_address` - The address of the pool
+/// * `id` - The address of the user
+pub fn load_share_token_balance(e: &Env, pool_address: &Address, id: &Address) -> i128 {
+    storage::get_user_balance(e, pool_address, id).shares
+}
+
+/// Fetch the total amount of backstop shares issued for a pool, excluding shares queued for
+/// withdrawal
+///
+/// ### Arguments
+/// * `pool_address` - The address of the pool
+pub fn load_share_token_supply(e: &Env, pool_address: &Address) -> i128 {
+    let pool_balance = storage::get_pool_balance(e, pool_address);
+    pool_balance.shares - pool_balance.q4w
+}
+
+#[cfg(test)]
+mod tests {
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger, LedgerInfo},
+        unwrap::UnwrapOptimized,
+        vec, BytesN,
+    };
+
+    use crate::{
+        backstop::{PoolBalance, UserBalance},
+        storage::{BackstopEmissionData, UserEmissionData},
+        testutils::{create_backstop, create_mock_pool_factory},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_execute_share_token_transfer() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1713139200 + 1234;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+        let share_token = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_share_token(&e, &pool_address, &share_token);
+            storage::set_backstop_emis_data(
+                &e,
+                &pool_address,
+                &BackstopEmissionData {
+                    expiration: 1713139200 + 7 * 24 * 60 * 60,
+                    eps: 0_10000000000000,
+                    index: 222220000000,
+                    last_time: 1713139200,
+                },
+            );
+            storage::set_user_emis_data(
+                &e,
+                &pool_address,
+                &samwise,
+                &UserEmissionData {
+                    index: 111110000000,
+                    accrued: 3,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_address,
+                &PoolBalance {
+                    shares: 160_0000000,
+                    tokens: 200_0000000,
+                    q4w: 10_0000000,
+                },
+            );
+            storage::set_user_balance(
+                &e,
+                &pool_address,
+                &samwise,
+                &UserBalance {
+                    shares: 9_0000000,
+                    q4w: vec![&e],
+                },
+            );
+
+            execute_share_token_transfer(&e, &pool_address, &samwise, &frodo, 4_0000000);
+
+            assert_eq!(load_share_token_balance(&e, &pool_address, &samwise), 5_0000000);
+            assert_eq!(load_share_token_balance(&e, &pool_address, &frodo), 4_0000000);
+            assert_eq!(load_share_token_supply(&e, &pool_address), 150_0000000);
+
+            // emissions are accrued on the balances before the transfer
+            let backstop_data =
+                storage::get_backstop_emis_data(&e, &pool_address).unwrap_optimized();
+            assert_eq!(backstop_data.index, 82488886666666);
+            let samwise_data =
+                storage::get_user_emis_data(&e, &pool_address, &samwise).unwrap_optimized();
+            assert_eq!(samwise_data.accrued, 7_4140001);
+            assert_eq!(samwise_data.index, 82488886666666);
+            let frodo_data =
+                storage::get_user_emis_data(&e, &pool_address, &frodo).unwrap_optimized();
+            assert_eq!(frodo_data.accrued, 0);
+            assert_eq!(frodo_data.index, 82488886666666);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_execute_share_token_transfer_queued_shares() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+        let share_token = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_share_token(&e, &pool_address, &share_token);
+            storage::set_pool_balance(
+                &e,
+                &pool_address,
+                &PoolBalance {
+                    shares: 10_0000000,
+                    tokens: 10_0000000,
+                    q4w: 5_0000000,
+                },
+            );
+            let mut user_balance = UserBalance::env_default(&e);
+            user_balance.add_shares(10_0000000);
+            user_balance.queue_shares_for_withdrawal(&e, 5_0000000, 1000);
+            storage::set_user_balance(&e, &pool_address, &samwise, &user_balance);
+
+            execute_share_token_transfer(&e, &pool_address, &samwise, &frodo, 5_0000001);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_share_token_transfer_to_pool() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+        let share_token = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_share_token(&e, &pool_address, &share_token);
+
+            execute_share_token_transfer(&e, &pool_address, &samwise, &pool_address, 1_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_deploy_share_token_already_deployed() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_address);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_share_token_wasm(&e, &BytesN::from_array(&e, &[0; 32]));
+            storage::set_share_token(&e, &pool_address, &Address::generate(&e));
+
+            execute_deploy_share_token(
+                &e,
+                &pool_address,
+                &String::from_str(&e, "Blend Backstop Share"),
+                &String::from_str(&e, "bBLP"),
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_deploy_share_token_no_wasm() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_address);
+
+        e.as_contract(&backstop_address, || {
+            execute_deploy_share_token(
+                &e,
+                &pool_address,
+                &String::from_str(&e, "Blend Backstop Share"),
+                &String::from_str(&e, "bBLP"),
+            );
+        });
+    }
+}
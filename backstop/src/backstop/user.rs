@@ -1,9 +1,6 @@
 use soroban_sdk::{contracttype, panic_with_error, vec, Env, Vec};
 
-use crate::{
-    constants::{MAX_Q4W_SIZE, Q4W_LOCK_TIME},
-    errors::BackstopError,
-};
+use crate::{constants::MAX_Q4W_SIZE, errors::BackstopError};
 
 /// A deposit that is queued for withdrawal
 #[derive(Clone)]
@@ -47,10 +44,11 @@ impl UserBalance {
     ///
     /// ### Arguments
     /// * `to_q` - The amount of new shares to queue for withdraw
+    /// * `lock_time` - The time in seconds the shares are locked for
     ///
     /// ### Errors
     /// If the amount to queue is greater than the available shares
-    pub fn queue_shares_for_withdrawal(&mut self, e: &Env, to_q: i128) {
+    pub fn queue_shares_for_withdrawal(&mut self, e: &Env, to_q: i128, lock_time: u64) {
         if self.shares < to_q {
             panic_with_error!(e, BackstopError::BalanceError);
         }
@@ -62,11 +60,23 @@ impl UserBalance {
         // user has enough tokens to withdrawal, add Q4W
         let new_q4w = Q4W {
             amount: to_q,
-            exp: e.ledger().timestamp() + Q4W_LOCK_TIME,
+            exp: e.ledger().timestamp() + lock_time,
         };
         self.q4w.push_back(new_q4w.clone());
     }
 
+    /// Unlock all queued withdrawals so they can be withdrawn immediately
+    pub fn unlock_q4w(&mut self, e: &Env) {
+        let now = e.ledger().timestamp();
+        for index in 0..self.q4w.len() {
+            let mut cur_q4w = self.q4w.get_unchecked(index);
+            if cur_q4w.exp > now {
+                cur_q4w.exp = now;
+                self.q4w.set(index, cur_q4w);
+            }
+        }
+    }
+
     /// Withdraw shares from the withdrawal queue
     ///
     /// ### Arguments
@@ -140,11 +150,36 @@ impl UserBalance {
             panic_with_error!(e, BackstopError::BalanceError);
         }
     }
+
+    /// Dequeue shares from a single entry in the withdrawal queue. The remainder of the entry
+    /// keeps its expiration.
+    ///
+    /// ### Arguments
+    /// * `index` - The index of the entry in the withdrawal queue
+    /// * `to_dequeue` - The amount of shares to dequeue from the entry
+    ///
+    /// ### Errors
+    /// If the entry does not exist or does not have enough queued shares to dequeue
+    #[allow(clippy::comparison_chain)]
+    pub fn dequeue_shares_from_entry(&mut self, e: &Env, index: u32, to_dequeue: i128) {
+        let mut entry = match self.q4w.get(index) {
+            Some(entry) => entry,
+            None => panic_with_error!(e, BackstopError::BadRequest),
+        };
+        if entry.amount < to_dequeue {
+            panic_with_error!(e, BackstopError::BalanceError);
+        } else if entry.amount == to_dequeue {
+            self.q4w.remove(index);
+        } else {
+            entry.amount -= to_dequeue;
+            self.q4w.set(index, entry);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::testutils::assert_eq_vec_q4w;
+    use crate::{constants::Q4W_LOCK_TIME, testutils::assert_eq_vec_q4w};
 
     use super::*;
     use soroban_sdk::{
@@ -192,7 +227,7 @@ mod tests {
         });
 
         let to_queue = 500;
-        user.queue_shares_for_withdrawal(&e, to_queue);
+        user.queue_shares_for_withdrawal(&e, to_queue, Q4W_LOCK_TIME);
         assert_eq_vec_q4w(
             &user.q4w,
             &vec![
@@ -233,7 +268,7 @@ mod tests {
         });
 
         let to_queue = 500;
-        user.queue_shares_for_withdrawal(&e, to_queue);
+        user.queue_shares_for_withdrawal(&e, to_queue, Q4W_LOCK_TIME);
         cur_q4w.push_back(Q4W {
             amount: to_queue,
             exp: 11000000 + 21 * 24 * 60 * 60,
@@ -269,7 +304,7 @@ mod tests {
         });
 
         let to_queue = 500;
-        user.queue_shares_for_withdrawal(&e, to_queue);
+        user.queue_shares_for_withdrawal(&e, to_queue, Q4W_LOCK_TIME);
         cur_q4w.push_back(Q4W {
             amount: to_queue,
             exp: 11000000 + 21 * 24 * 60 * 60,
@@ -307,7 +342,7 @@ mod tests {
         });
 
         let to_queue = 500;
-        user.queue_shares_for_withdrawal(&e, to_queue);
+        user.queue_shares_for_withdrawal(&e, to_queue, Q4W_LOCK_TIME);
     }
 
     #[test]
@@ -339,7 +374,7 @@ mod tests {
         });
 
         let to_queue = 801;
-        user.queue_shares_for_withdrawal(&e, to_queue);
+        user.queue_shares_for_withdrawal(&e, to_queue, Q4W_LOCK_TIME);
     }
 
     // withdraw_shares
@@ -771,4 +806,105 @@ mod tests {
         let to_dequeue = 376;
         user.dequeue_shares(&e, to_dequeue);
     }
+    #[test]
+    fn test_dequeue_shares_from_entry() {
+        let e = Env::default();
+
+        let cur_q4w = vec![
+            &e,
+            Q4W {
+                amount: 125,
+                exp: 10000000,
+            },
+            Q4W {
+                amount: 200,
+                exp: 12592000,
+            },
+            Q4W {
+                amount: 50,
+                exp: 19592000,
+            },
+        ];
+        let mut user = UserBalance {
+            shares: 1000,
+            q4w: cur_q4w.clone(),
+        };
+
+        user.dequeue_shares_from_entry(&e, 0, 100);
+        let expected_q4w = vec![
+            &e,
+            Q4W {
+                amount: 25,
+                exp: 10000000,
+            },
+            Q4W {
+                amount: 200,
+                exp: 12592000,
+            },
+            Q4W {
+                amount: 50,
+                exp: 19592000,
+            },
+        ];
+        assert_eq_vec_q4w(&user.q4w, &expected_q4w);
+
+        user.dequeue_shares_from_entry(&e, 1, 200);
+        let expected_q4w = vec![
+            &e,
+            Q4W {
+                amount: 25,
+                exp: 10000000,
+            },
+            Q4W {
+                amount: 50,
+                exp: 19592000,
+            },
+        ];
+        assert_eq_vec_q4w(&user.q4w, &expected_q4w);
+        assert_eq!(user.shares, 1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_dequeue_shares_from_entry_over_entry() {
+        let e = Env::default();
+
+        let cur_q4w = vec![
+            &e,
+            Q4W {
+                amount: 125,
+                exp: 10000000,
+            },
+            Q4W {
+                amount: 200,
+                exp: 12592000,
+            },
+        ];
+        let mut user = UserBalance {
+            shares: 1000,
+            q4w: cur_q4w.clone(),
+        };
+
+        user.dequeue_shares_from_entry(&e, 0, 126);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_dequeue_shares_from_entry_missing_entry() {
+        let e = Env::default();
+
+        let cur_q4w = vec![
+            &e,
+            Q4W {
+                amount: 125,
+                exp: 10000000,
+            },
+        ];
+        let mut user = UserBalance {
+            shares: 1000,
+            q4w: cur_q4w.clone(),
+        };
+
+        user.dequeue_shares_from_entry(&e, 1, 100);
+    }
 }
@@ -2,7 +2,7 @@ use crate::{contract::require_nonnegative, emissions, storage, BackstopError};
 use sep_41_token::TokenClient;
 use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env};
 
-use super::Q4W;
+use super::{load_pool_backstop_token, Q4W};
 
 /// Perform a queue for withdraw from the backstop module
 pub fn execute_queue_withdrawal(
@@ -19,7 +19,13 @@ pub fn execute_queue_withdrawal(
     // update emissions
     emissions::update_emissions(e, pool_address, &pool_balance, from, &user_balance);
 
-    user_balance.queue_shares_for_withdrawal(e, amount);
+    // withdrawals from a decommissioned pool are not locked
+    let lock_time = if storage::get_pool_decommissioned(e, pool_address) {
+        0
+    } else {
+        storage::get_q4w_lock(e, pool_address)
+    };
+    user_balance.queue_shares_for_withdrawal(e, amount, lock_time);
     pool_balance.queue_for_withdraw(amount);
 
     storage::set_user_balance(e, pool_address, from, &user_balance);
@@ -46,6 +52,30 @@ pub fn execute_dequeue_withdrawal(e: &Env, from: &Address, pool_address: &Addres
     storage::set_pool_balance(e, pool_address, &pool_balance);
 }
 
+/// Perform a dequeue of part of a single queued for withdraw entry from the backstop module
+pub fn execute_dequeue_withdrawal_partial(
+    e: &Env,
+    from: &Address,
+    pool_address: &Address,
+    index: u32,
+    amount: i128,
+) {
+    require_nonnegative(e, amount);
+
+    let mut pool_balance = storage::get_pool_balance(e, pool_address);
+    let mut user_balance = storage::get_user_balance(e, pool_address, from);
+
+    // update emissions
+    emissions::update_emissions(e, pool_address, &pool_balance, from, &user_balance);
+
+    user_balance.dequeue_shares_from_entry(e, index, amount);
+    user_balance.add_shares(amount);
+    pool_balance.dequeue_q4w(e, amount);
+
+    storage::set_user_balance(e, pool_address, from, &user_balance);
+    storage::set_pool_balance(e, pool_address, &pool_balance);
+}
+
 /// Perform a withdraw from the backstop module
 pub fn execute_withdraw(e: &Env, from: &Address, pool_address: &Address, amount: i128) -> i128 {
     require_nonnegative(e, amount);
@@ -53,6 +83,9 @@ pub fn execute_withdraw(e: &Env, from: &Address, pool_address: &Address, amount:
     let mut pool_balance = storage::get_pool_balance(e, pool_address);
     let mut user_balance = storage::get_user_balance(e, pool_address, from);
 
+    if storage::get_pool_decommissioned(e, pool_address) {
+        user_balance.unlock_q4w(e);
+    }
     user_balance.withdraw_shares(e, amount);
 
     let to_return = pool_balance.convert_to_tokens(amount);
@@ -64,7 +97,7 @@ pub fn execute_withdraw(e: &Env, from: &Address, pool_address: &Address, amount:
     storage::set_user_balance(e, pool_address, from, &user_balance);
     storage::set_pool_balance(e, pool_address, &pool_balance);
 
-    let backstop_token_client = TokenClient::new(e, &storage::get_backstop_token(e));
+    let backstop_token_client = TokenClient::new(e, &load_pool_backstop_token(e, pool_address));
     backstop_token_client.transfer(&e.current_contract_address(), from, &to_return);
 
     to_return
@@ -145,6 +178,66 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_execute_queue_withdrawal_pool_lock_time() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (_, backstop_token_client) = create_backstop_token(&e, &backstop_address, &bombadil);
+        backstop_token_client.mint(&samwise, &100_0000000);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_address);
+
+        // setup pool with deposits
+        e.as_contract(&backstop_address, || {
+            execute_deposit(&e, &samwise, &pool_address, 100_0000000);
+            storage::set_q4w_lock(&e, &pool_address, &(30 * 24 * 60 * 60));
+        });
+
+        e.ledger().set(LedgerInfo {
+            protocol_version: 22,
+            sequence_number: 200,
+            timestamp: 10000,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        e.as_contract(&backstop_address, || {
+            execute_queue_withdrawal(&e, &samwise, &pool_address, 42_0000000);
+
+            let new_user_balance = storage::get_user_balance(&e, &pool_address, &samwise);
+            assert_eq!(new_user_balance.shares, 58_0000000);
+            let expected_q4w = vec![
+                &e,
+                Q4W {
+                    amount: 42_0000000,
+                    exp: 10000 + 30 * 24 * 60 * 60,
+                },
+            ];
+            assert_eq_vec_q4w(&new_user_balance.q4w, &expected_q4w);
+
+            let new_pool_balance = storage::get_pool_balance(&e, &pool_address);
+            assert_eq!(new_pool_balance.q4w, 42_0000000);
+            assert_eq!(new_pool_balance.shares, 100_0000000);
+            assert_eq!(new_pool_balance.tokens, 100_0000000);
+
+            assert_eq!(
+                backstop_token_client.balance(&backstop_address),
+                100_0000000
+            );
+            assert_eq!(backstop_token_client.balance(&samwise), 0);
+        });
+    }
+
     #[test]
     #[should_panic(expected = "Error(Contract, #8)")]
     fn test_execute_queue_withdrawal_negative_amount() {
@@ -266,6 +359,88 @@ mod tests {
         });
     }
     #[test]
+    fn test_execute_dequeue_withdrawal_partial() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (_, backstop_token_client) = create_backstop_token(&e, &backstop_address, &bombadil);
+        backstop_token_client.mint(&samwise, &100_0000000);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_address);
+
+        // queue shares for withdraw
+        e.as_contract(&backstop_address, || {
+            execute_deposit(&e, &samwise, &pool_address, 75_0000000);
+
+            e.ledger().set(LedgerInfo {
+                protocol_version: 22,
+                sequence_number: 100,
+                timestamp: 10000,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 10,
+                min_persistent_entry_ttl: 10,
+                max_entry_ttl: 3110400,
+            });
+
+            execute_queue_withdrawal(&e, &samwise, &pool_address, 25_0000000);
+
+            e.ledger().set(LedgerInfo {
+                protocol_version: 22,
+                sequence_number: 100,
+                timestamp: 20000,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 10,
+                min_persistent_entry_ttl: 10,
+                max_entry_ttl: 3110400,
+            });
+
+            execute_queue_withdrawal(&e, &samwise, &pool_address, 40_0000000);
+        });
+
+        e.ledger().set(LedgerInfo {
+            protocol_version: 22,
+            sequence_number: 200,
+            timestamp: 30000,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        e.as_contract(&backstop_address, || {
+            execute_dequeue_withdrawal_partial(&e, &samwise, &pool_address, 0, 10_0000000);
+
+            let new_user_balance = storage::get_user_balance(&e, &pool_address, &samwise);
+            assert_eq!(new_user_balance.shares, 20_0000000);
+            let expected_q4w = vec![
+                &e,
+                Q4W {
+                    amount: 15_0000000,
+                    exp: 10000 + 21 * 24 * 60 * 60,
+                },
+                Q4W {
+                    amount: 40_0000000,
+                    exp: 20000 + 21 * 24 * 60 * 60,
+                },
+            ];
+            assert_eq_vec_q4w(&new_user_balance.q4w, &expected_q4w);
+
+            let new_pool_balance = storage::get_pool_balance(&e, &pool_address);
+            assert_eq!(new_pool_balance.q4w, 55_0000000);
+            assert_eq!(new_pool_balance.shares, 75_0000000);
+            assert_eq!(new_pool_balance.tokens, 75_0000000);
+        });
+    }
+    #[test]
     #[should_panic(expected = "Error(Contract, #8)")]
     fn test_execute_dequeue_withdrawal_negative_amount() {
         let e = Env::default();
@@ -389,6 +564,57 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_execute_withdrawal_decommissioned() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let backstop_address = create_backstop(&e);
+        let pool_address = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (_, backstop_token_client) = create_backstop_token(&e, &backstop_address, &bombadil);
+        backstop_token_client.mint(&samwise, &100_0000000);
+
+        let (_, mock_pool_factory_client) = create_mock_pool_factory(&e, &backstop_address);
+        mock_pool_factory_client.set_pool(&pool_address);
+
+        e.ledger().set(LedgerInfo {
+            protocol_version: 22,
+            sequence_number: 200,
+            timestamp: 10000,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        e.as_contract(&backstop_address, || {
+            execute_deposit(&e, &samwise, &pool_address, 100_0000000);
+            execute_queue_withdrawal(&e, &samwise, &pool_address, 42_0000000);
+
+            // queued withdrawals made before and after decommissioning are unlocked
+            storage::set_pool_decommissioned(&e, &pool_address);
+            let q4w = execute_queue_withdrawal(&e, &samwise, &pool_address, 8_0000000);
+            assert_eq!(q4w.exp, 10000);
+
+            let tokens = execute_withdraw(&e, &samwise, &pool_address, 50_0000000);
+            assert_eq!(tokens, 50_0000000);
+
+            let new_user_balance = storage::get_user_balance(&e, &pool_address, &samwise);
+            assert_eq!(new_user_balance.shares, 50_0000000);
+            assert_eq!(new_user_balance.q4w.len(), 0);
+
+            let new_pool_balance = storage::get_pool_balance(&e, &pool_address);
+            assert_eq!(new_pool_balance.q4w, 0);
+            assert_eq!(new_pool_balance.shares, 50_0000000);
+            assert_eq!(new_pool_balance.tokens, 50_0000000);
+            assert_eq!(backstop_token_client.balance(&samwise), 50_0000000);
+        });
+    }
+
     #[test]
     #[should_panic(expected = "Error(Contract, #8)")]
     fn test_execute_withdrawal_negative_amount() {
@@ -8,12 +8,32 @@ pub const SCALAR_14: i128 = 1_0000000_0000000;
 pub const MAX_RZ_SIZE: u32 = 50;
 
 /// The maximum amount of active Q4W entries that a user can have against a single backstop.
-/// Set such that a user can create a maximum of 1 entry per day over the 21 day lock period.
+/// Set such that a user can create a maximum of 1 entry per day over the default 21 day lock
+/// period. Pools with a longer lock period allow fewer entries per day.
 pub const MAX_Q4W_SIZE: u32 = 21;
 
-/// The time in seconds that a Q4W entry is locked for (21 days).
+/// The default time in seconds that a Q4W entry is locked for (21 days).
 pub const Q4W_LOCK_TIME: u64 = 21 * 24 * 60 * 60;
 
+/// The maximum time in seconds that governance can lock a pool's Q4W entries for (60 days).
+pub const MAX_Q4W_LOCK_TIME: u64 = 60 * 24 * 60 * 60;
+
+/// The maximum amount of additional reward tokens that can be emitted to a pool's backstop.
+pub const MAX_REWARD_TOKENS: u32 = 5;
+
+/// The minimum time in seconds a reward token can be emitted over (1 day).
+pub const MIN_REWARD_DURATION: u64 = 24 * 60 * 60;
+
+/// The maximum time in seconds a reward token can be emitted over (365 days).
+pub const MAX_REWARD_DURATION: u64 = 365 * 24 * 60 * 60;
+
+/// The time in seconds after a reward token's emissions end before it can be removed (30 days).
+pub const REWARD_CLAIM_PERIOD: u64 = 30 * 24 * 60 * 60;
+
 /// The maximum amount of backfilled emissions that can be emitted.
 /// Represents between 3-4 months worth of token emissions.
 pub const MAX_BACKFILLED_EMISSIONS: i128 = 10_000_000 * SCALAR_7;
+
+/// The emission boost for each pool risk tier, applied to the pool's backstop deposits when
+/// splitting reward zone emissions (7 decimals). Tier 0 is the lowest risk and unboosted.
+pub const RISK_TIER_BOOSTS: [i128; 4] = [1_0000000, 1_1000000, 1_2500000, 1_5000000];
@@ -5,9 +5,11 @@ use crate::{
     emissions,
     errors::BackstopError,
     events::BackstopEvents,
-    storage,
+    storage::{self, BackstopEmissionConfig},
+};
+use soroban_sdk::{
+    contract, contractclient, contractimpl, panic_with_error, Address, BytesN, Env, String, Vec,
 };
-use soroban_sdk::{contract, contractclient, contractimpl, panic_with_error, Address, Env, Vec};
 
 /// ### Backstop
 ///
@@ -19,7 +21,8 @@ pub struct BackstopContract;
 pub trait Backstop {
     /********** Core **********/
 
-    /// Deposit backstop tokens from "from" into the backstop of a pool
+    /// Deposit backstop tokens from "from" into the backstop of a pool. The pool is then checked
+    /// for bad debt over its bad debt threshold, which starts a bad debt auction.
     ///
     /// Returns the number of backstop pool shares minted
     ///
@@ -29,6 +32,26 @@ pub trait Backstop {
     /// * `amount` - The amount of tokens to deposit
     fn deposit(e: Env, from: Address, pool_address: Address, amount: i128) -> i128;
 
+    /// Deposit backstop tokens from "spender" into the backstop of a pool for "depositor". The
+    /// tokens are pulled from "spender" USING transfer_from, so "spender" must approve the
+    /// backstop for at least "amount" first. The pool is then checked for bad debt over its bad
+    /// debt threshold, which starts a bad debt auction.
+    ///
+    /// Returns the number of backstop pool shares minted
+    ///
+    /// ### Arguments
+    /// * `spender` - The address sending the backstop tokens
+    /// * `depositor` - The address receiving the backstop pool shares
+    /// * `pool_address` - The address of the pool
+    /// * `amount` - The amount of tokens to deposit
+    fn deposit_from(
+        e: Env,
+        spender: Address,
+        depositor: Address,
+        pool_address: Address,
+        amount: i128,
+    ) -> i128;
+
     /// Queue deposited pool shares from "from" for withdraw from a backstop of a pool
     ///
     /// Returns the created queue for withdrawal
@@ -47,7 +70,24 @@ pub trait Backstop {
     /// * `amount` - The amount of shares to dequeue
     fn dequeue_withdrawal(e: Env, from: Address, pool_address: Address, amount: i128);
 
-    /// Withdraw shares from "from"s withdraw queue for a backstop of a pool
+    /// Dequeue part of a single queued pool share withdraw for "from" from the backstop of a pool.
+    /// The remainder of the queued withdraw keeps its expiration.
+    ///
+    /// ### Arguments
+    /// * `from` - The address whose deposits are being dequeued
+    /// * `pool_address` - The address of the pool
+    /// * `index` - The index of the queued withdraw to dequeue from
+    /// * `amount` - The amount of shares to dequeue
+    fn dequeue_withdrawal_partial(
+        e: Env,
+        from: Address,
+        pool_address: Address,
+        index: u32,
+        amount: i128,
+    );
+
+    /// Withdraw shares from "from"s withdraw queue for a backstop of a pool. The pool is then
+    /// checked for bad debt over its bad debt threshold, which starts a bad debt auction.
     ///
     /// Returns the amount of tokens returned
     ///
@@ -72,12 +112,56 @@ pub trait Backstop {
     /// * `pool_address` - The address of the pool
     fn pool_data(e: Env, pool: Address) -> PoolBackstopData;
 
+    /// Fetch the time in seconds that new queued withdrawals for the pool are locked for
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    fn q4w_lock(e: Env, pool: Address) -> u64;
+
+    /// Fetch if the pool is decommissioned. Queued withdrawals from a decommissioned pool's
+    /// backstop are not locked.
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    fn decommissioned(e: Env, pool: Address) -> bool;
+
+    /// Fetch the emission config for the pool's backstop, including the pool's risk tier and
+    /// emission boost
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    fn emission_config(e: Env, pool: Address) -> BackstopEmissionConfig;
+
+    /// Fetch the additional reward tokens emitted to the backstop depositors of a pool
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    fn reward_tokens(e: Env, pool: Address) -> Vec<Address>;
+
+    /// Fetch the pools in the reward zone
+    fn reward_zone(e: Env) -> Vec<Address>;
+
     /// Fetch the backstop token for the backstop
     fn backstop_token(e: Env) -> Address;
 
+    /// Fetch the backstop token a pool's deposits are held in, which is the new backstop token
+    /// once the pool's deposits are migrated
+    ///
+    /// ### Arguments
+    /// * `pool` - The address of the pool
+    fn pool_backstop_token(e: Env, pool: Address) -> Address;
+
+    /// Fetch the USDC token held in the backstop token's LP
+    fn usdc_token(e: Env) -> Address;
+
+    /// Fetch the pool factory the backstop verifies pools with
+    fn pool_factory(e: Env) -> Address;
+
     /********** Emissions **********/
 
-    /// Update the backstop with new emissions for all reward zone pools
+    /// Update the backstop with new emissions for all reward zone pools. A pool that was added to
+    /// the reward zone during the distribution period earns emissions pro-rata from the time it
+    /// was added.
     ///
     /// Returns the amount of new emissions for all reward zone pools
     fn distribute(e: Env) -> i128;
@@ -112,19 +196,66 @@ pub trait Backstop {
     /// If the pool is not below the threshold or if the pool is not in the reward zone
     fn remove_reward(e: Env, to_remove: Address);
 
-    /// Claim backstop deposit emissions from a list of pools for `from`
+    /// Claim backstop deposit emissions from a list of pools for `from`. The claimed emissions
+    /// are deposited into each pool's backstop for `to`, which can differ from `from`, so a
+    /// multisig depositor can route its emissions to another wallet. Only `from` must authorize
+    /// the claim.
     ///
     /// Returns the amount of BLND emissions claimed
     ///
     /// ### Arguments
     /// * `from` - The address of the user claiming emissions
     /// * `pool_addresses` - The Vec of addresses to claim backstop deposit emissions from
-    /// * `to` - The Address to send to emissions to
+    /// * `to` - The address the claimed emissions are deposited for
     ///
     /// ### Errors
     /// If an invalid pool address is included
     fn claim(e: Env, from: Address, pool_addresses: Vec<Address>, to: Address) -> i128;
 
+    /// Claim backstop deposit emissions from a list of pools for `from`, and deposit all of the
+    /// claimed emissions into the backstop of `pool_address` for `from`
+    ///
+    /// Returns the number of backstop pool shares minted
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user claiming emissions
+    /// * `pool_addresses` - The Vec of addresses to claim backstop deposit emissions from
+    /// * `pool_address` - The address of the pool to deposit the claimed emissions into
+    /// * `min_lp_tokens_out` - The minimum amount of backstop tokens to receive for the claimed
+    ///                         emissions
+    ///
+    /// ### Errors
+    /// If an invalid pool address is included, or if the LP join returns less than
+    /// `min_lp_tokens_out`
+    fn claim_and_deposit(
+        e: Env,
+        from: Address,
+        pool_addresses: Vec<Address>,
+        pool_address: Address,
+        min_lp_tokens_out: i128,
+    ) -> i128;
+
+    /// Claim an additional reward token emitted to backstop depositors from a list of pools
+    /// for `from`
+    ///
+    /// Returns the amount of the reward token claimed
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user claiming rewards
+    /// * `pool_addresses` - The Vec of addresses to claim rewards from
+    /// * `token` - The address of the reward token
+    /// * `to` - The Address to send the rewards to
+    ///
+    /// ### Errors
+    /// If no pool addresses are included
+    fn claim_reward_token(
+        e: Env,
+        from: Address,
+        pool_addresses: Vec<Address>,
+        token: Address,
+        to: Address,
+    ) -> i128;
+
     /// Drop initial BLND to a list of addresses through the emitter
     fn drop(e: Env);
 
@@ -143,6 +274,29 @@ pub trait Backstop {
     /// not authorize the call
     fn draw(e: Env, pool_address: Address, amount: i128, to: Address);
 
+    /// (Only Pool) Take USDC from a pools backstop by exiting backstop tokens from the LP
+    ///
+    /// Returns the amount of backstop tokens burned
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `amount` - The amount of USDC to draw
+    /// * `to` - The address to send the USDC to
+    /// * `max_tokens_in` - The maximum amount of backstop tokens to burn. Can be at most 140% of
+    ///                     the value of the USDC drawn at the LP's current spot value.
+    ///
+    /// ### Errors
+    /// If the pool does not have enough backstop tokens, if the exit burns more than
+    /// `max_tokens_in`, if `max_tokens_in` is over the allowed slippage, or if the pool does not
+    /// authorize the call
+    fn draw_usdc(
+        e: Env,
+        pool_address: Address,
+        amount: i128,
+        to: Address,
+        max_tokens_in: i128,
+    ) -> i128;
+
     /// (Only Pool) Sends backstop tokens from "from" to a pools backstop
     ///
     /// NOTE: This is not a deposit, and "from" will permanently lose access to the funds
@@ -157,7 +311,32 @@ pub trait Backstop {
     /// authorize the call
     fn donate(e: Env, from: Address, pool_address: Address, amount: i128);
 
-    /// Updates the underlying value of 1 backstop token
+    /// (Only Pool) Donates BLND or USDC from a pool to its backstop by joining the backstop token LP
+    ///
+    /// Returns the amount of backstop tokens minted
+    ///
+    /// NOTE: This is not a deposit, and the pool will permanently lose access to the funds
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `token` - The address of the token to donate, either BLND or USDC
+    /// * `amount` - The amount of tokens to donate
+    /// * `min_tokens_out` - The minimum amount of backstop tokens to mint. Must be at least 1 / 1.4
+    ///                      of the value of the donated tokens at the LP's current spot value.
+    ///
+    /// ### Errors
+    /// If the `pool_address` is not valid, the token is not BLND or USDC, backstop does not have
+    /// sufficient allowance from the pool, the join mints less than `min_tokens_out`,
+    /// `min_tokens_out` is under the allowed slippage, or if the pool does not authorize the call
+    fn donate_underlying(
+        e: Env,
+        pool_address: Address,
+        token: Address,
+        amount: i128,
+        min_tokens_out: i128,
+    ) -> i128;
+
+    /// Updates the underlying value of 1 backstop token, and of 1 new backstop token if one is set
     ///
     /// ### Returns
     /// A tuple of (blnd_per_tkn, usdc_per_tkn) of underlying value per backstop token
@@ -165,6 +344,187 @@ pub trait Backstop {
     /// ### Errors
     /// If the underlying value is unable to be computed
     fn update_tkn_val(e: Env) -> (i128, i128);
+
+    /// (Only Governance) Set the time in seconds that new queued withdrawals for the pool are
+    /// locked for. The lock can only be extended beyond the default 21 days, so depositors are
+    /// always given at least the default time to react to a pool's risk.
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `q4w_lock_seconds` - The lock time in seconds
+    ///
+    /// ### Errors
+    /// If the lock time is outside of the allowed bounds, or governance does not authorize the call
+    fn set_q4w_lock(e: Env, pool_address: Address, q4w_lock_seconds: u64);
+
+    /// (Only Governance) Set the risk tier of the pool. Riskier tiers boost the pool's share of
+    /// reward zone emissions, so backstop depositors insuring riskier pools earn more BLND per
+    /// share.
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `tier` - The risk tier of the pool
+    ///
+    /// ### Errors
+    /// If the risk tier does not exist, or governance does not authorize the call
+    fn set_risk_tier(e: Env, pool_address: Address, tier: u32);
+
+    /// (Only Pool) Decommission the pool. Queued withdrawals from the pool's backstop are no
+    /// longer locked, so backstop depositors can withdraw immediately. Decommissioning is
+    /// permanent.
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    ///
+    /// ### Errors
+    /// If the pool does not authorize the call
+    fn decommission_pool(e: Env, pool_address: Address);
+
+    /// (Only Pool) Fund a reward token from "from" to be emitted to the backstop depositors
+    /// of a pool over `duration` seconds. Any rewards left over from the current emissions of
+    /// the token are included in the new emissions.
+    ///
+    /// ### Arguments
+    /// * `from` - The address funding the rewards
+    /// * `pool_address` - The address of the pool
+    /// * `token` - The address of the reward token
+    /// * `amount` - The amount of the reward token to emit
+    /// * `duration` - The duration in seconds to emit the rewards over
+    ///
+    /// ### Errors
+    /// If the token is BLND or the backstop token, the duration is outside of the allowed bounds,
+    /// the token was removed from the pool, the pool has too many reward tokens, or the pool does
+    /// not authorize the call
+    fn fund_reward_token(
+        e: Env,
+        from: Address,
+        pool_address: Address,
+        token: Address,
+        amount: i128,
+        duration: u64,
+    );
+
+    /// (Only Pool) Remove a reward token from the backstop depositors of a pool, 30 days after its
+    /// emissions end. Depositors can still claim rewards accrued before the token is removed, and
+    /// the token can't be funded for the pool again.
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `token` - The address of the reward token
+    ///
+    /// ### Errors
+    /// If the token is not a reward token of the pool, its emissions ended less than 30 days ago,
+    /// or the pool does not authorize the call
+    fn remove_reward_token(e: Env, pool_address: Address, token: Address);
+
+    /// Fetch the backstop token pool deposits can be migrated to, if set
+    fn backstop_token_v2(e: Env) -> Option<Address>;
+
+    /// (Governance only) Set the backstop token pool deposits can be migrated to. The new token
+    /// must be a comet pool of BLND and USDC. Can only be set once.
+    ///
+    /// ### Arguments
+    /// * `token` - The address of the new backstop token
+    ///
+    /// ### Errors
+    /// If the caller is not governance, a new backstop token is already set, or the token is
+    /// invalid
+    fn set_backstop_token_v2(e: Env, token: Address);
+
+    /// (Governance only) Migrate a pool's backstop deposits to the new backstop token. The
+    /// backstop tokens held for the pool are converted in kind through their BLND and USDC, so
+    /// every depositor's share keeps the value of its underlying. Depositor shares, Q4W entries
+    /// and emissions are preserved.
+    ///
+    /// Returns the amount of new backstop tokens held for the pool
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `min_tokens_out` - The minimum amount of new backstop tokens the conversion must result in
+    ///
+    /// ### Errors
+    /// If the caller is not governance, no new backstop token is set, the pool's deposits were
+    /// already migrated, or the conversion results in less than `min_tokens_out`
+    fn migrate_deposit(e: Env, pool_address: Address, min_tokens_out: i128) -> i128;
+
+    /********** Share Token **********/
+
+    /// (Governance only) Set the hash of the share token contract wasm that share tokens are
+    /// deployed from. Share tokens that are already deployed are not changed.
+    ///
+    /// ### Arguments
+    /// * `wasm_hash` - The hash of the share token contract wasm
+    ///
+    /// ### Errors
+    /// If the caller is not governance
+    fn set_share_token_wasm(e: Env, wasm_hash: BytesN<32>);
+
+    /// (Only Pool) Deploy a share token for the pool's backstop, which exposes the pool's backstop
+    /// shares, excluding shares queued for withdrawal, as a transferable SEP-41 token. The share
+    /// token is deployed from the wasm hash set by governance.
+    ///
+    /// Returns the address of the share token contract
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `name` - The name of the share token
+    /// * `symbol` - The symbol of the share token
+    ///
+    /// ### Errors
+    /// If the pool already has a share token, the share token wasm hash is not set, or the pool
+    /// does not authorize the call
+    fn deploy_share_token(e: Env, pool_address: Address, name: String, symbol: String) -> Address;
+
+    /// Fetch the share token of the pool's backstop, if it has been deployed
+    ///
+    /// ### Arguments
+    /// * `pool` - The address of the pool
+    fn share_token(e: Env, pool: Address) -> Option<Address>;
+
+    /// Fetch the amount of shares 'id' holds in the pool's backstop, excluding shares queued for
+    /// withdrawal
+    ///
+    /// ### Arguments
+    /// * `pool` - The address of the pool
+    /// * `id` - The address of the user
+    fn share_token_balance(e: Env, pool: Address, id: Address) -> i128;
+
+    /// Fetch the total amount of shares issued by the pool's backstop, excluding shares queued
+    /// for withdrawal
+    ///
+    /// ### Arguments
+    /// * `pool` - The address of the pool
+    fn share_token_supply(e: Env, pool: Address) -> i128;
+
+    /// (Share token only) Transfer shares of the pool's backstop from 'from' to 'to'. Only
+    /// callable by the pool's share token, which is responsible for authorizing 'from'. The
+    /// emissions of both users are updated before the transfer.
+    ///
+    /// ### Arguments
+    /// * `pool` - The address of the pool
+    /// * `from` - The address sending the shares
+    /// * `to` - The address receiving the shares
+    /// * `amount` - The amount of shares to transfer
+    ///
+    /// ### Errors
+    /// If the caller is not the pool's share token, 'from' does not hold enough shares that are
+    /// not queued for withdrawal, or 'to' is the pool or the backstop
+    fn share_token_transfer(e: Env, pool: Address, from: Address, to: Address, amount: i128);
+
+    /********** bToken **********/
+
+    /// (Governance only) Set the hash of the bToken contract wasm that pools deploy bTokens from.
+    /// bTokens that are already deployed are not changed.
+    ///
+    /// ### Arguments
+    /// * `wasm_hash` - The hash of the bToken contract wasm
+    ///
+    /// ### Errors
+    /// If the caller is not governance
+    fn set_b_token_wasm(e: Env, wasm_hash: BytesN<32>);
+
+    /// Fetch the hash of the bToken contract wasm that pools deploy bTokens from, if set
+    fn b_token_wasm(e: Env) -> Option<BytesN<32>>;
 }
 
 #[contractimpl]
@@ -178,6 +538,8 @@ impl BackstopContract {
     /// * `usdc_token` - The USDC token ID
     /// * `pool_factory` - The pool factory ID
     /// * `drop_list` - The list of addresses to distribute initial BLND to and the percent of the distribution they should receive
+    /// * `governance` - The address that can migrate the backstop token
+    #[allow(clippy::too_many_arguments)]
     pub fn __constructor(
         e: Env,
         backstop_token: Address,
@@ -186,8 +548,10 @@ impl BackstopContract {
         usdc_token: Address,
         pool_factory: Address,
         drop_list: Vec<(Address, i128)>,
+        governance: Address,
     ) {
         storage::set_backstop_token(&e, &backstop_token);
+        storage::set_governance(&e, &governance);
         storage::set_blnd_token(&e, &blnd_token);
         storage::set_usdc_token(&e, &usdc_token);
         storage::set_pool_factory(&e, &pool_factory);
@@ -215,11 +579,30 @@ impl Backstop for BackstopContract {
         from.require_auth();
 
         let to_mint = backstop::execute_deposit(&e, &from, &pool_address, amount);
+        backstop::check_pool_bad_debt(&e, &pool_address);
 
         BackstopEvents::deposit(&e, pool_address, from, amount, to_mint);
         to_mint
     }
 
+    fn deposit_from(
+        e: Env,
+        spender: Address,
+        depositor: Address,
+        pool_address: Address,
+        amount: i128,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        spender.require_auth();
+
+        let to_mint =
+            backstop::execute_deposit_from(&e, &spender, &depositor, &pool_address, amount);
+        backstop::check_pool_bad_debt(&e, &pool_address);
+
+        BackstopEvents::deposit(&e, pool_address, depositor, amount, to_mint);
+        to_mint
+    }
+
     fn queue_withdrawal(e: Env, from: Address, pool_address: Address, amount: i128) -> Q4W {
         storage::extend_instance(&e);
         from.require_auth();
@@ -239,11 +622,27 @@ impl Backstop for BackstopContract {
         BackstopEvents::dequeue_withdrawal(&e, pool_address, from, amount);
     }
 
+    fn dequeue_withdrawal_partial(
+        e: Env,
+        from: Address,
+        pool_address: Address,
+        index: u32,
+        amount: i128,
+    ) {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        backstop::execute_dequeue_withdrawal_partial(&e, &from, &pool_address, index, amount);
+
+        BackstopEvents::dequeue_withdrawal(&e, pool_address, from, amount);
+    }
+
     fn withdraw(e: Env, from: Address, pool_address: Address, amount: i128) -> i128 {
         storage::extend_instance(&e);
         from.require_auth();
 
         let to_withdraw = backstop::execute_withdraw(&e, &from, &pool_address, amount);
+        backstop::check_pool_bad_debt(&e, &pool_address);
 
         BackstopEvents::withdraw(&e, pool_address, from, amount, to_withdraw);
         to_withdraw
@@ -257,10 +656,42 @@ impl Backstop for BackstopContract {
         load_pool_backstop_data(&e, &pool)
     }
 
+    fn q4w_lock(e: Env, pool: Address) -> u64 {
+        storage::get_q4w_lock(&e, &pool)
+    }
+
+    fn decommissioned(e: Env, pool: Address) -> bool {
+        storage::get_pool_decommissioned(&e, &pool)
+    }
+
+    fn emission_config(e: Env, pool: Address) -> BackstopEmissionConfig {
+        storage::get_backstop_emis_config(&e, &pool)
+    }
+
+    fn reward_tokens(e: Env, pool: Address) -> Vec<Address> {
+        storage::get_reward_tokens(&e, &pool)
+    }
+
+    fn reward_zone(e: Env) -> Vec<Address> {
+        storage::get_reward_zone(&e)
+    }
+
     fn backstop_token(e: Env) -> Address {
         storage::get_backstop_token(&e)
     }
 
+    fn pool_backstop_token(e: Env, pool: Address) -> Address {
+        backstop::load_pool_backstop_token(&e, &pool)
+    }
+
+    fn usdc_token(e: Env) -> Address {
+        storage::get_usdc_token(&e)
+    }
+
+    fn pool_factory(e: Env) -> Address {
+        storage::get_pool_factory(&e)
+    }
+
     /********** Emissions **********/
 
     fn distribute(e: Env) -> i128 {
@@ -304,6 +735,47 @@ impl Backstop for BackstopContract {
         amount
     }
 
+    fn claim_and_deposit(
+        e: Env,
+        from: Address,
+        pool_addresses: Vec<Address>,
+        pool_address: Address,
+        min_lp_tokens_out: i128,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        let (claimed, tokens_in, to_mint) = emissions::execute_claim_and_deposit(
+            &e,
+            &from,
+            &pool_addresses,
+            &pool_address,
+            min_lp_tokens_out,
+        );
+
+        BackstopEvents::claim(&e, from.clone(), claimed);
+        if to_mint > 0 {
+            BackstopEvents::deposit(&e, pool_address, from, tokens_in, to_mint);
+        }
+        to_mint
+    }
+
+    fn claim_reward_token(
+        e: Env,
+        from: Address,
+        pool_addresses: Vec<Address>,
+        token: Address,
+        to: Address,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        let amount = emissions::execute_claim_reward_token(&e, &from, &pool_addresses, &token, &to);
+
+        BackstopEvents::claim_reward_token(&e, from, token, amount);
+        amount
+    }
+
     fn drop(e: Env) {
         let mut drop_list = storage::get_drop_list(&e);
         let backfilled_emissions = storage::get_backfill_emissions(&e);
@@ -323,6 +795,22 @@ impl Backstop for BackstopContract {
         BackstopEvents::draw(&e, pool_address, to, amount);
     }
 
+    fn draw_usdc(
+        e: Env,
+        pool_address: Address,
+        amount: i128,
+        to: Address,
+        max_tokens_in: i128,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        pool_address.require_auth();
+
+        let tokens_in = backstop::execute_draw_usdc(&e, &pool_address, amount, &to, max_tokens_in);
+
+        BackstopEvents::draw_usdc(&e, pool_address, to, amount, tokens_in);
+        tokens_in
+    }
+
     fn donate(e: Env, from: Address, pool_address: Address, amount: i128) {
         storage::extend_instance(&e);
         from.require_auth();
@@ -333,6 +821,28 @@ impl Backstop for BackstopContract {
         BackstopEvents::donate(&e, pool_address, from, amount);
     }
 
+    fn donate_underlying(
+        e: Env,
+        pool_address: Address,
+        token: Address,
+        amount: i128,
+        min_tokens_out: i128,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        pool_address.require_auth();
+
+        let tokens_out = backstop::execute_donate_underlying(
+            &e,
+            &pool_address,
+            &token,
+            amount,
+            min_tokens_out,
+        );
+
+        BackstopEvents::donate_underlying(&e, pool_address, token, amount, tokens_out);
+        tokens_out
+    }
+
     fn update_tkn_val(e: Env) -> (i128, i128) {
         storage::extend_instance(&e);
 
@@ -340,8 +850,143 @@ impl Backstop for BackstopContract {
         let blnd_token = storage::get_blnd_token(&e);
         let usdc_token = storage::get_usdc_token(&e);
 
+        if let Some(token_v2) = storage::get_backstop_token_v2(&e) {
+            let lp_token_v2_val =
+                backstop::load_comet_token_value(&e, &token_v2, &blnd_token, &usdc_token);
+            storage::set_lp_token_v2_val(&e, &lp_token_v2_val);
+        }
         backstop::execute_update_comet_token_value(&e, &backstop_token, &blnd_token, &usdc_token)
     }
+
+    fn set_q4w_lock(e: Env, pool_address: Address, q4w_lock_seconds: u64) {
+        storage::extend_instance(&e);
+        storage::get_governance(&e).require_auth();
+
+        backstop::execute_set_q4w_lock(&e, &pool_address, q4w_lock_seconds);
+
+        BackstopEvents::set_q4w_lock(&e, pool_address, q4w_lock_seconds);
+    }
+
+    fn set_risk_tier(e: Env, pool_address: Address, tier: u32) {
+        storage::extend_instance(&e);
+        storage::get_governance(&e).require_auth();
+
+        let config = emissions::execute_set_risk_tier(&e, &pool_address, tier);
+
+        BackstopEvents::set_risk_tier(&e, pool_address, config.tier, config.boost);
+    }
+
+    fn decommission_pool(e: Env, pool_address: Address) {
+        storage::extend_instance(&e);
+        pool_address.require_auth();
+
+        storage::set_pool_decommissioned(&e, &pool_address);
+
+        BackstopEvents::decommission_pool(&e, pool_address);
+    }
+
+    fn fund_reward_token(
+        e: Env,
+        from: Address,
+        pool_address: Address,
+        token: Address,
+        amount: i128,
+        duration: u64,
+    ) {
+        storage::extend_instance(&e);
+        from.require_auth();
+        pool_address.require_auth();
+
+        emissions::execute_fund_reward_token(&e, &from, &pool_address, &token, amount, duration);
+
+        BackstopEvents::fund_reward_token(&e, pool_address, token, from, amount, duration);
+    }
+
+    fn remove_reward_token(e: Env, pool_address: Address, token: Address) {
+        storage::extend_instance(&e);
+        pool_address.require_auth();
+
+        emissions::execute_remove_reward_token(&e, &pool_address, &token);
+
+        BackstopEvents::remove_reward_token(&e, pool_address, token);
+    }
+
+    fn backstop_token_v2(e: Env) -> Option<Address> {
+        storage::get_backstop_token_v2(&e)
+    }
+
+    fn set_backstop_token_v2(e: Env, token: Address) {
+        storage::extend_instance(&e);
+        storage::get_governance(&e).require_auth();
+
+        backstop::execute_set_backstop_token_v2(&e, &token);
+
+        BackstopEvents::set_backstop_token_v2(&e, token);
+    }
+
+    fn migrate_deposit(e: Env, pool_address: Address, min_tokens_out: i128) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_governance(&e).require_auth();
+
+        let tokens_v2 = backstop::execute_migrate_deposit(&e, &pool_address, min_tokens_out);
+
+        BackstopEvents::migrate_deposit(&e, pool_address, tokens_v2);
+        tokens_v2
+    }
+
+    /********** Share Token **********/
+
+    fn set_share_token_wasm(e: Env, wasm_hash: BytesN<32>) {
+        storage::extend_instance(&e);
+        storage::get_governance(&e).require_auth();
+
+        storage::set_share_token_wasm(&e, &wasm_hash);
+
+        BackstopEvents::set_share_token_wasm(&e, wasm_hash);
+    }
+
+    fn deploy_share_token(e: Env, pool_address: Address, name: String, symbol: String) -> Address {
+        storage::extend_instance(&e);
+        pool_address.require_auth();
+
+        let share_token = backstop::execute_deploy_share_token(&e, &pool_address, &name, &symbol);
+
+        BackstopEvents::deploy_share_token(&e, pool_address, share_token.clone());
+        share_token
+    }
+
+    fn share_token(e: Env, pool: Address) -> Option<Address> {
+        storage::get_share_token(&e, &pool)
+    }
+
+    fn share_token_balance(e: Env, pool: Address, id: Address) -> i128 {
+        backstop::load_share_token_balance(&e, &pool, &id)
+    }
+
+    fn share_token_supply(e: Env, pool: Address) -> i128 {
+        backstop::load_share_token_supply(&e, &pool)
+    }
+
+    fn share_token_transfer(e: Env, pool: Address, from: Address, to: Address, amount: i128) {
+        storage::extend_instance(&e);
+
+        backstop::execute_share_token_transfer(&e, &pool, &from, &to, amount);
+    }
+
+    /********** bToken **********/
+
+    fn set_b_token_wasm(e: Env, wasm_hash: BytesN<32>) {
+        storage::extend_instance(&e);
+        storage::get_governance(&e).require_auth();
+
+        storage::set_b_token_wasm(&e, &wasm_hash);
+
+        BackstopEvents::set_b_token_wasm(&e, wasm_hash);
+    }
+
+    fn b_token_wasm(e: Env) -> Option<BytesN<32>> {
+        storage::get_b_token_wasm(&e)
+    }
 }
 
 /// Require that an incoming amount is not negative
@@ -9,3 +9,6 @@ pub use comet::WASM as COMET_WASM;
 
 mod emitter;
 pub use emitter::Client as EmitterClient;
+
+mod pool;
+pub use pool::PoolClient;
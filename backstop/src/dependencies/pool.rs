@@ -0,0 +1,11 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+use crate::backstop::PoolBackstopData;
+
+/// The bad debt automation exposed by the pools the backstop insures
+#[contractclient(name = "PoolClient")]
+pub trait Pool {
+    /// (Backstop only) Start a bad debt auction for the backstop's bad debt in the pool if its
+    /// value exceeds the pool's bad debt threshold, using the pool's backstop data
+    fn check_bad_debt(e: Env, backstop_token: Address, pool_backstop_data: PoolBackstopData);
+}
@@ -1,65 +1,59 @@
-use crate::{dependencies::CometClient, errors::BackstopError, events::BackstopEvents, storage};
+use crate::{
+    backstop::{load_pool_backstop_token, require_is_from_pool_factory},
+    dependencies::CometClient,
+    errors::BackstopError,
+    events::BackstopEvents,
+    require_nonnegative, storage,
+};
 use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{
     auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
     panic_with_error, vec, Address, Env, IntoVal, Map, Symbol, Val, Vec,
 };
 
-use super::distributor::claim_emissions;
+use super::distributor::{claim_emissions, update_emissions};
 
 /// Perform a claim for backstop deposit emissions by a user from the backstop module
 pub fn execute_claim(e: &Env, from: &Address, pool_addresses: &Vec<Address>, to: &Address) -> i128 {
-    if pool_addresses.is_empty() {
-        panic_with_error!(e, BackstopError::BadRequest);
-    }
-
-    let mut claimed: i128 = 0;
-    let mut claims: Map<Address, i128> = Map::new(e);
-    for pool_id in pool_addresses.iter() {
-        let pool_balance = storage::get_pool_balance(e, &pool_id);
-        let user_balance = storage::get_user_balance(e, &pool_id, from);
-        let claim_amt = claim_emissions(e, &pool_id, &pool_balance, from, &user_balance);
-
-        claimed += claim_amt;
-        claims.set(pool_id, claim_amt);
-    }
+    let (claimed, claims) = claim_from_pools(e, from, pool_addresses);
 
     if claimed > 0 {
-        let blnd_id = storage::get_blnd_token(e);
-        let lp_id = storage::get_backstop_token(e);
-        let approval_ledger = (e.ledger().sequence() / 100000 + 1) * 100000;
-        let args: Vec<Val> = vec![
-            e,
-            (&e.current_contract_address()).into_val(e),
-            (&lp_id).into_val(e),
-            (&claimed).into_val(e),
-            (&approval_ledger).into_val(e),
-        ];
-        e.authorize_as_current_contract(vec![
-            &e,
-            InvokerContractAuthEntry::Contract(SubContractInvocation {
-                context: ContractContext {
-                    contract: blnd_id.clone(),
-                    fn_name: Symbol::new(e, "approve"),
-                    args: args.clone(),
-                },
-                sub_invocations: vec![e],
-            }),
-        ]);
-        let lp_tokens_out = CometClient::new(e, &lp_id).dep_tokn_amt_in_get_lp_tokns_out(
-            &blnd_id,
-            &claimed,
-            &0,
-            &e.current_contract_address(),
-        );
+        // pools whose deposits were migrated hold a different backstop token, so join each
+        // backstop token with the BLND claimed from the pools that hold it
+        let mut claimed_by_token: Map<Address, i128> = Map::new(e);
+        for pool_id in pool_addresses.iter() {
+            let token = load_pool_backstop_token(e, &pool_id);
+            let claim_amount = claims.get(pool_id.clone()).unwrap();
+            let token_claimed = claimed_by_token.get(token.clone()).unwrap_or(0);
+            claimed_by_token.set(token, token_claimed + claim_amount);
+        }
+        let mut lp_tokens_by_token: Map<Address, i128> = Map::new(e);
+        for (token, token_claimed) in claimed_by_token.iter() {
+            if token_claimed > 0 {
+                let lp_tokens_out = join_backstop_token(e, &token, token_claimed, 0);
+                lp_tokens_by_token.set(token, lp_tokens_out);
+            }
+        }
+
         for pool_id in pool_addresses.iter() {
+            let token = load_pool_backstop_token(e, &pool_id);
             let claim_amount = claims.get(pool_id.clone()).unwrap();
-            let deposit_amount = lp_tokens_out
-                .fixed_mul_floor(claim_amount, claimed)
-                .unwrap();
+            let token_claimed = claimed_by_token.get(token.clone()).unwrap();
+            let deposit_amount = if token_claimed > 0 {
+                lp_tokens_by_token
+                    .get(token)
+                    .unwrap()
+                    .fixed_mul_floor(claim_amount, token_claimed)
+                    .unwrap()
+            } else {
+                0
+            };
             let mut pool_balance = storage::get_pool_balance(e, &pool_id);
             let mut user_balance = storage::get_user_balance(e, &pool_id, to);
 
+            // "to" may already hold shares, so accrue its emissions before its balance changes
+            update_emissions(e, &pool_id, &pool_balance, to, &user_balance);
+
             // Deposit LP tokens into pool backstop
             let to_mint = pool_balance.convert_to_shares(deposit_amount);
             pool_balance.deposit(deposit_amount, to_mint);
@@ -75,6 +69,106 @@ pub fn execute_claim(e: &Env, from: &Address, pool_addresses: &Vec<Address>, to:
     claimed
 }
 
+/// Perform a claim for backstop deposit emissions by a user from the backstop module, and deposit
+/// all of the claimed emissions into the backstop of a single pool for the user
+///
+/// Returns a tuple of (BLND claimed, backstop tokens deposited, backstop shares minted)
+///
+/// ### Panics
+/// If the pool is not from the pool factory, or the join returns less than `min_lp_tokens_out`
+pub fn execute_claim_and_deposit(
+    e: &Env,
+    from: &Address,
+    pool_addresses: &Vec<Address>,
+    pool_address: &Address,
+    min_lp_tokens_out: i128,
+) -> (i128, i128, i128) {
+    require_nonnegative(e, min_lp_tokens_out);
+    let (claimed, _) = claim_from_pools(e, from, pool_addresses);
+    if claimed == 0 {
+        return (0, 0, 0);
+    }
+
+    let backstop_token = load_pool_backstop_token(e, pool_address);
+    let lp_tokens_out = join_backstop_token(e, &backstop_token, claimed, min_lp_tokens_out);
+
+    let mut pool_balance = storage::get_pool_balance(e, pool_address);
+    require_is_from_pool_factory(e, pool_address, pool_balance.shares);
+    let mut user_balance = storage::get_user_balance(e, pool_address, from);
+
+    // the pool may not have been claimed from, so accrue emissions before the balance changes
+    update_emissions(e, pool_address, &pool_balance, from, &user_balance);
+
+    let to_mint = pool_balance.convert_to_shares(lp_tokens_out);
+    if to_mint == 0 {
+        panic_with_error!(e, BackstopError::InvalidShareMintAmount);
+    }
+    pool_balance.deposit(lp_tokens_out, to_mint);
+    user_balance.add_shares(to_mint);
+
+    storage::set_pool_balance(e, pool_address, &pool_balance);
+    storage::set_user_balance(e, pool_address, from, &user_balance);
+
+    (claimed, lp_tokens_out, to_mint)
+}
+
+/// Claim the emissions of `from` for each pool
+///
+/// Returns the total amount of BLND claimed and the amount claimed from each pool
+fn claim_from_pools(
+    e: &Env,
+    from: &Address,
+    pool_addresses: &Vec<Address>,
+) -> (i128, Map<Address, i128>) {
+    if pool_addresses.is_empty() {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+
+    let mut claimed: i128 = 0;
+    let mut claims: Map<Address, i128> = Map::new(e);
+    for pool_id in pool_addresses.iter() {
+        let pool_balance = storage::get_pool_balance(e, &pool_id);
+        let user_balance = storage::get_user_balance(e, &pool_id, from);
+        let claim_amt = claim_emissions(e, &pool_id, &pool_balance, from, &user_balance);
+
+        claimed += claim_amt;
+        claims.set(pool_id, claim_amt);
+    }
+    (claimed, claims)
+}
+
+/// Join the backstop token `lp_id`'s LP with `amount` of BLND held by the backstop
+///
+/// Returns the amount of backstop tokens minted to the backstop
+fn join_backstop_token(e: &Env, lp_id: &Address, amount: i128, min_lp_tokens_out: i128) -> i128 {
+    let blnd_id = storage::get_blnd_token(e);
+    let approval_ledger = (e.ledger().sequence() / 100000 + 1) * 100000;
+    let args: Vec<Val> = vec![
+        e,
+        (&e.current_contract_address()).into_val(e),
+        lp_id.into_val(e),
+        (&amount).into_val(e),
+        (&approval_ledger).into_val(e),
+    ];
+    e.authorize_as_current_contract(vec![
+        &e,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: blnd_id.clone(),
+                fn_name: Symbol::new(e, "approve"),
+                args: args.clone(),
+            },
+            sub_invocations: vec![e],
+        }),
+    ]);
+    CometClient::new(e, lp_id).dep_tokn_amt_in_get_lp_tokns_out(
+        &blnd_id,
+        &amount,
+        &min_lp_tokens_out,
+        &e.current_contract_address(),
+    )
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -272,6 +366,128 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_claim_to_existing_depositor() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1500000000 + 12345;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_address = create_backstop(&e);
+        let pool_1_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let (blnd_address, blnd_token_client) = create_blnd_token(&e, &backstop_address, &bombadil);
+        let (usdc_address, _) = create_usdc_token(&e, &backstop_address, &bombadil);
+        blnd_token_client.mint(&backstop_address, &100_0000000);
+
+        let (lp_address, _) = create_comet_lp_pool(&e, &bombadil, &blnd_address, &usdc_address);
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_emis_data(
+                &e,
+                &pool_1_id,
+                &BackstopEmissionData {
+                    expiration: 1500000000 + 7 * 24 * 60 * 60,
+                    eps: 0_10000000000000,
+                    index: 222220000000,
+                    last_time: 1500000000,
+                },
+            );
+            storage::set_user_emis_data(
+                &e,
+                &pool_1_id,
+                &samwise,
+                &UserEmissionData {
+                    index: 111110000000,
+                    accrued: 1_2345678,
+                },
+            );
+            storage::set_user_emis_data(
+                &e,
+                &pool_1_id,
+                &frodo,
+                &UserEmissionData {
+                    index: 111110000000,
+                    accrued: 0,
+                },
+            );
+            storage::set_backstop_token(&e, &lp_address);
+            storage::set_blnd_token(&e, &blnd_address);
+            storage::set_rz_emission_index(&e, &1_00000000000000);
+            storage::set_rz_emis_data(
+                &e,
+                &pool_1_id,
+                &RzEmissionData {
+                    index: 0,
+                    accrued: 0,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_1_id,
+                &PoolBalance {
+                    shares: 150_0000000,
+                    tokens: 200_0000000,
+                    q4w: 2_0000000,
+                },
+            );
+            storage::set_user_balance(
+                &e,
+                &pool_1_id,
+                &samwise,
+                &UserBalance {
+                    shares: 9_0000000,
+                    q4w: vec![&e],
+                },
+            );
+            storage::set_user_balance(
+                &e,
+                &pool_1_id,
+                &frodo,
+                &UserBalance {
+                    shares: 1_0000000,
+                    q4w: vec![&e],
+                },
+            );
+
+            let result = execute_claim(&e, &samwise, &vec![&e, pool_1_id.clone()], &frodo);
+            assert_eq!(result, 76_3155136);
+
+            // the claimed emissions are deposited for frodo, and samwise's accrual is reset
+            let pool_balance = storage::get_pool_balance(&e, &pool_1_id);
+            let sam_balance = storage::get_user_balance(&e, &pool_1_id, &samwise);
+            assert_eq!(sam_balance.shares, 9_0000000);
+            let frodo_balance = storage::get_user_balance(&e, &pool_1_id, &frodo);
+            assert!(frodo_balance.shares > 1_0000000);
+            assert_eq!(
+                frodo_balance.shares - 1_0000000,
+                pool_balance.shares - 150_0000000
+            );
+            let sam_data =
+                storage::get_user_emis_data(&e, &pool_1_id, &samwise).unwrap_optimized();
+            assert_eq!(sam_data.accrued, 0);
+            assert_eq!(sam_data.index, 834343841621621);
+
+            // frodo accrues emissions on the shares held before the deposit
+            let frodo_data =
+                storage::get_user_emis_data(&e, &pool_1_id, &frodo).unwrap_optimized();
+            assert_eq!(frodo_data.accrued, 8_3423273);
+            assert_eq!(frodo_data.index, 834343841621621);
+        });
+    }
+
     #[test]
     fn test_claim_twice() {
         let e = Env::default();
@@ -491,6 +707,278 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_claim_and_deposit() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1500000000 + 12345;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_address = create_backstop(&e);
+        let pool_1_id = Address::generate(&e);
+        let pool_2_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (blnd_address, blnd_token_client) = create_blnd_token(&e, &backstop_address, &bombadil);
+        let (usdc_address, _) = create_usdc_token(&e, &backstop_address, &bombadil);
+        blnd_token_client.mint(&backstop_address, &100_0000000);
+
+        let backstop_1_emissions_data = BackstopEmissionData {
+            expiration: 1500000000 + 7 * 24 * 60 * 60,
+            eps: 0_10000000000000,
+            index: 222220000000,
+            last_time: 1500000000,
+        };
+        let user_1_emissions_data = UserEmissionData {
+            index: 111110000000,
+            accrued: 1_2345678,
+        };
+
+        let backstop_2_emissions_data = BackstopEmissionData {
+            expiration: 1500000000 + 7 * 24 * 60 * 60,
+            eps: 0_02000000000000,
+            index: 0,
+            last_time: 1500010000,
+        };
+        let user_2_emissions_data = UserEmissionData {
+            index: 0,
+            accrued: 0,
+        };
+        let (lp_address, lp_client) =
+            create_comet_lp_pool(&e, &bombadil, &blnd_address, &usdc_address);
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_emis_data(&e, &pool_1_id, &backstop_1_emissions_data);
+            storage::set_user_emis_data(&e, &pool_1_id, &samwise, &user_1_emissions_data);
+            storage::set_backstop_emis_data(&e, &pool_2_id, &backstop_2_emissions_data);
+            storage::set_user_emis_data(&e, &pool_2_id, &samwise, &user_2_emissions_data);
+            storage::set_backstop_token(&e, &lp_address);
+            storage::set_blnd_token(&e, &blnd_address);
+            storage::set_rz_emission_index(&e, &1_00000000000000);
+            storage::set_rz_emis_data(
+                &e,
+                &pool_1_id,
+                &RzEmissionData {
+                    index: 0,
+                    accrued: 0,
+                },
+            );
+            storage::set_rz_emis_data(
+                &e,
+                &pool_2_id,
+                &RzEmissionData {
+                    index: 0,
+                    accrued: 0,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_1_id,
+                &PoolBalance {
+                    shares: 150_0000000,
+                    tokens: 200_0000000,
+                    q4w: 2_0000000,
+                },
+            );
+            storage::set_user_balance(
+                &e,
+                &pool_1_id,
+                &samwise,
+                &UserBalance {
+                    shares: 9_0000000,
+                    q4w: vec![&e],
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_2_id,
+                &PoolBalance {
+                    shares: 70_0000000,
+                    tokens: 75_0000000,
+                    q4w: 3_5000000,
+                },
+            );
+            storage::set_user_balance(
+                &e,
+                &pool_2_id,
+                &samwise,
+                &UserBalance {
+                    shares: 7_5000000,
+                    q4w: vec![&e],
+                },
+            );
+            let backstop_lp_balance = lp_client.balance(&backstop_address);
+            let (claimed, tokens_in, to_mint) = execute_claim_and_deposit(
+                &e,
+                &samwise,
+                &vec![&e, pool_1_id.clone(), pool_2_id.clone()],
+                &pool_1_id,
+                0,
+            );
+            assert_eq!(claimed, 76_3155136 + 5_2894736);
+            assert_eq!(tokens_in, 6_4729327);
+            assert_eq!(to_mint, 4_8546995);
+            assert_eq!(
+                lp_client.balance(&backstop_address),
+                backstop_lp_balance + 6_4729327
+            );
+            assert_eq!(
+                blnd_token_client.balance(&backstop_address),
+                100_0000000 - (76_3155136 + 5_2894736)
+            );
+            let sam_balance_1 = storage::get_user_balance(&e, &pool_1_id, &samwise);
+            assert_eq!(sam_balance_1.shares, 9_0000000 + 4_8546995);
+            let sam_balance_2 = storage::get_user_balance(&e, &pool_2_id, &samwise);
+            assert_eq!(sam_balance_2.shares, 7_5000000);
+
+            let pool_balance_1 = storage::get_pool_balance(&e, &pool_1_id);
+            assert_eq!(pool_balance_1.tokens, 200_0000000 + 6_4729327);
+            assert_eq!(pool_balance_1.shares, 150_0000000 + 4_8546995);
+            let pool_balance_2 = storage::get_pool_balance(&e, &pool_2_id);
+            assert_eq!(pool_balance_2.tokens, 75_0000000);
+            assert_eq!(pool_balance_2.shares, 70_0000000);
+
+            let new_user_1_data =
+                storage::get_user_emis_data(&e, &pool_1_id, &samwise).unwrap_optimized();
+            assert_eq!(new_user_1_data.accrued, 0);
+            assert_eq!(new_user_1_data.index, 834343841621621);
+            let new_user_2_data =
+                storage::get_user_emis_data(&e, &pool_2_id, &samwise).unwrap_optimized();
+            assert_eq!(new_user_2_data.accrued, 0);
+            assert_eq!(new_user_2_data.index, 70526315789473);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_claim_and_deposit_min_lp_tokens_out() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let block_timestamp = 1500000000 + 12345;
+        e.ledger().set(LedgerInfo {
+            timestamp: block_timestamp,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        e.cost_estimate().budget().reset_unlimited();
+
+        let backstop_address = create_backstop(&e);
+        let pool_1_id = Address::generate(&e);
+        let pool_2_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (blnd_address, blnd_token_client) = create_blnd_token(&e, &backstop_address, &bombadil);
+        let (usdc_address, _) = create_usdc_token(&e, &backstop_address, &bombadil);
+        blnd_token_client.mint(&backstop_address, &100_0000000);
+
+        let backstop_1_emissions_data = BackstopEmissionData {
+            expiration: 1500000000 + 7 * 24 * 60 * 60,
+            eps: 0_10000000000000,
+            index: 222220000000,
+            last_time: 1500000000,
+        };
+        let user_1_emissions_data = UserEmissionData {
+            index: 111110000000,
+            accrued: 1_2345678,
+        };
+
+        let backstop_2_emissions_data = BackstopEmissionData {
+            expiration: 1500000000 + 7 * 24 * 60 * 60,
+            eps: 0_02000000000000,
+            index: 0,
+            last_time: 1500010000,
+        };
+        let user_2_emissions_data = UserEmissionData {
+            index: 0,
+            accrued: 0,
+        };
+        let (lp_address, _) = create_comet_lp_pool(&e, &bombadil, &blnd_address, &usdc_address);
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_emis_data(&e, &pool_1_id, &backstop_1_emissions_data);
+            storage::set_user_emis_data(&e, &pool_1_id, &samwise, &user_1_emissions_data);
+            storage::set_backstop_emis_data(&e, &pool_2_id, &backstop_2_emissions_data);
+            storage::set_user_emis_data(&e, &pool_2_id, &samwise, &user_2_emissions_data);
+            storage::set_backstop_token(&e, &lp_address);
+            storage::set_blnd_token(&e, &blnd_address);
+            storage::set_rz_emission_index(&e, &1_00000000000000);
+            storage::set_rz_emis_data(
+                &e,
+                &pool_1_id,
+                &RzEmissionData {
+                    index: 0,
+                    accrued: 0,
+                },
+            );
+            storage::set_rz_emis_data(
+                &e,
+                &pool_2_id,
+                &RzEmissionData {
+                    index: 0,
+                    accrued: 0,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_1_id,
+                &PoolBalance {
+                    shares: 150_0000000,
+                    tokens: 200_0000000,
+                    q4w: 2_0000000,
+                },
+            );
+            storage::set_user_balance(
+                &e,
+                &pool_1_id,
+                &samwise,
+                &UserBalance {
+                    shares: 9_0000000,
+                    q4w: vec![&e],
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_2_id,
+                &PoolBalance {
+                    shares: 70_0000000,
+                    tokens: 75_0000000,
+                    q4w: 3_5000000,
+                },
+            );
+            storage::set_user_balance(
+                &e,
+                &pool_2_id,
+                &samwise,
+                &UserBalance {
+                    shares: 7_5000000,
+                    q4w: vec![&e],
+                },
+            );
+            execute_claim_and_deposit(
+                &e,
+                &samwise,
+                &vec![&e, pool_1_id.clone(), pool_2_id.clone()],
+                &pool_1_id,
+                6_4729328,
+            );
+        });
+    }
+
     #[test]
     fn test_claim_no_deposits() {
         let e = Env::default();
@@ -4,7 +4,7 @@ use cast::i128;
 use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{unwrap::UnwrapOptimized, Address, Env};
 
-use super::update_rz_emis_data;
+use super::{update_rewards, update_rz_emis_data};
 use crate::{
     backstop::{PoolBalance, UserBalance},
     constants::{SCALAR_14, SCALAR_7},
@@ -24,6 +24,7 @@ pub fn update_emissions(
     if let Some(emis_data) = update_emission_data(e, pool_id, pool_balance) {
         update_user_emissions(e, pool_id, user_id, &emis_data, user_balance, false);
     }
+    update_rewards(e, pool_id, pool_balance, user_id, user_balance);
 }
 
 /// Update for claiming emissions for a user and pool
@@ -56,41 +57,54 @@ pub fn update_emission_data(
 ) -> Option<BackstopEmissionData> {
     match storage::get_backstop_emis_data(e, pool_id) {
         Some(emis_data) => {
-            if emis_data.last_time >= emis_data.expiration
-                || e.ledger().timestamp() == emis_data.last_time
-                || emis_data.eps == 0
-                || pool_balance.shares == 0
-            {
+            if let Some(new_data) = accrue_emission_index(e, &emis_data, pool_balance) {
+                storage::set_backstop_emis_data(e, pool_id, &new_data);
+                Some(new_data)
+            } else {
                 // emis_data already updated or expired
-                return Some(emis_data);
+                Some(emis_data)
             }
-
-            let max_timestamp = if e.ledger().timestamp() > emis_data.expiration {
-                emis_data.expiration
-            } else {
-                e.ledger().timestamp()
-            };
-
-            let unqueued_shares = pool_balance.shares - pool_balance.q4w;
-            require_nonnegative(e, unqueued_shares);
-            // Eps is in 14 decimals and needs to be converted to 7 decimals to match emission token decimals
-            let additional_idx = (i128(max_timestamp - emis_data.last_time) * i128(emis_data.eps))
-                .fixed_div_floor(unqueued_shares, SCALAR_7)
-                .unwrap_optimized();
-            let new_data = BackstopEmissionData {
-                eps: emis_data.eps,
-                expiration: emis_data.expiration,
-                index: additional_idx + emis_data.index,
-                last_time: e.ledger().timestamp(),
-            };
-
-            storage::set_backstop_emis_data(e, pool_id, &new_data);
-            Some(new_data)
         }
         None => return None, // no emission exist, no update is required
     }
 }
 
+/// Accrue the emissions index of a backstop's emission data to the current timestamp
+///
+/// Returns the updated emission data, or None if the emission data is already updated or expired
+pub(super) fn accrue_emission_index(
+    e: &Env,
+    emis_data: &BackstopEmissionData,
+    pool_balance: &PoolBalance,
+) -> Option<BackstopEmissionData> {
+    if emis_data.last_time >= emis_data.expiration
+        || e.ledger().timestamp() == emis_data.last_time
+        || emis_data.eps == 0
+        || pool_balance.shares == 0
+    {
+        return None;
+    }
+
+    let max_timestamp = if e.ledger().timestamp() > emis_data.expiration {
+        emis_data.expiration
+    } else {
+        e.ledger().timestamp()
+    };
+
+    let unqueued_shares = pool_balance.shares - pool_balance.q4w;
+    require_nonnegative(e, unqueued_shares);
+    // Eps is in 14 decimals and needs to be converted to 7 decimals to match emission token decimals
+    let additional_idx = (i128(max_timestamp - emis_data.last_time) * i128(emis_data.eps))
+        .fixed_div_floor(unqueued_shares, SCALAR_7)
+        .unwrap_optimized();
+    Some(BackstopEmissionData {
+        eps: emis_data.eps,
+        expiration: emis_data.expiration,
+        index: additional_idx + emis_data.index,
+        last_time: e.ledger().timestamp(),
+    })
+}
+
 /// Update the user's emissions. If `to_claim` is true, the user's accrued emissions will be returned and
 /// a value of zero will be stored to the ledger.
 ///
@@ -104,31 +118,62 @@ fn update_user_emissions(
     user_balance: &UserBalance,
     to_claim: bool,
 ) -> i128 {
-    if let Some(user_data) = storage::get_user_emis_data(e, pool, user) {
-        if user_data.index != emis_data.index || to_claim {
-            let mut accrual = user_data.accrued;
-            if user_balance.shares != 0 {
-                let delta_index = emis_data.index - user_data.index;
-                require_nonnegative(e, delta_index);
-                let to_accrue = (user_balance.shares)
-                    .fixed_mul_floor(delta_index, SCALAR_14)
-                    .unwrap_optimized();
-                accrual += to_accrue;
-            }
-            return set_user_emissions(e, pool, user, emis_data.index, accrual, to_claim);
+    let user_data = storage::get_user_emis_data(e, pool, user);
+    match accrue_user_emissions(e, &user_data, emis_data, user_balance) {
+        Some(new_data) => {
+            set_user_emissions(e, pool, user, new_data.index, new_data.accrued, to_claim)
+        }
+        None if to_claim => {
+            let user_data = user_data.unwrap_optimized();
+            set_user_emissions(e, pool, user, user_data.index, user_data.accrued, to_claim)
         }
         // no accrual occured and no claim requested
-        return 0;
+        None => 0,
+    }
+}
+
+/// Accrue the user's emissions to the emissions index of the backstop
+///
+/// Returns the updated user emission data, or None if the user is already accrued to the index
+pub(super) fn accrue_user_emissions(
+    e: &Env,
+    user_data: &Option<UserEmissionData>,
+    emis_data: &BackstopEmissionData,
+    user_balance: &UserBalance,
+) -> Option<UserEmissionData> {
+    if let Some(user_data) = user_data {
+        if user_data.index == emis_data.index {
+            return None;
+        }
+        let mut accrual = user_data.accrued;
+        if user_balance.shares != 0 {
+            let delta_index = emis_data.index - user_data.index;
+            require_nonnegative(e, delta_index);
+            let to_accrue = (user_balance.shares)
+                .fixed_mul_floor(delta_index, SCALAR_14)
+                .unwrap_optimized();
+            accrual += to_accrue;
+        }
+        Some(UserEmissionData {
+            index: emis_data.index,
+            accrued: accrual,
+        })
     } else if user_balance.shares == 0 {
         // first time the user registered an action with the asset since emissions were added
-        return set_user_emissions(e, pool, user, emis_data.index, 0, to_claim);
+        Some(UserEmissionData {
+            index: emis_data.index,
+            accrued: 0,
+        })
     } else {
         // user had tokens before emissions began, they are due any historical emissions
         let to_accrue = user_balance
             .shares
             .fixed_mul_floor(emis_data.index, SCALAR_14)
             .unwrap_optimized();
-        return set_user_emissions(e, pool, user, emis_data.index, to_accrue, to_claim);
+        Some(UserEmissionData {
+            index: emis_data.index,
+            accrued: to_accrue,
+        })
     }
 }
 
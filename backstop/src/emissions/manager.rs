@@ -1,14 +1,14 @@
 use cast::{i128, u64};
 use sep_41_token::TokenClient;
 use soroban_fixed_point_math::FixedPoint;
-use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env, Vec};
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Vec};
 
 use crate::{
-    backstop::{load_pool_backstop_data, require_pool_above_threshold},
-    constants::{MAX_BACKFILLED_EMISSIONS, MAX_RZ_SIZE, SCALAR_14, SCALAR_7},
+    backstop::{load_pool_backstop_data, require_pool_above_threshold, to_original_tokens},
+    constants::{MAX_BACKFILLED_EMISSIONS, MAX_RZ_SIZE, RISK_TIER_BOOSTS, SCALAR_14, SCALAR_7},
     dependencies::EmitterClient,
     errors::BackstopError,
-    storage::{self, BackstopEmissionData, RzEmissionData},
+    storage::{self, BackstopEmissionConfig, BackstopEmissionData, RzEmissionData},
     PoolBalance,
 };
 
@@ -59,6 +59,8 @@ pub fn add_to_reward_zone(e: &Env, to_add: Address, to_remove: Option<Address>)
     } else {
         set_rz_emissions(e, &to_add, rz_emission_index, 0, false);
     }
+    // record when the pool qualified so its first distribution is pro-rated
+    storage::set_rz_qualified_time(e, &to_add, &e.ledger().timestamp());
     storage::set_reward_zone(e, &reward_zone);
 }
 
@@ -93,6 +95,7 @@ fn remove_pool(e: &Env, reward_zone: &mut Vec<Address>, to_remove: &Address) {
             // set emission index to i128::MAX to prevent further emissions
             let to_remove_emis_data = storage::get_rz_emis_data(e, &to_remove).unwrap_optimized();
             set_rz_emissions(e, &to_remove, i128::MAX, to_remove_emis_data.accrued, false);
+            storage::del_rz_qualified_time(e, to_remove);
 
             reward_zone.remove(idx);
         }
@@ -172,18 +175,62 @@ pub fn distribute(e: &Env) -> i128 {
     storage::set_last_distribution_time(e, &emitter_last_distribution);
     let prev_index = storage::get_rz_emission_index(e);
 
-    // fetch total tokens of BLND in the reward zone
+    // fetch total boosted tokens of BLND in the reward zone. Pools that qualified for the reward
+    // zone during the distribution period are weighted by the portion of the period since they
+    // qualified.
     let mut total_non_queued_tokens: i128 = 0;
+    let mut total_prorated_tokens: i128 = 0;
+    let mut prorated_pools: Vec<(Address, i128)> = vec![e];
     for rz_pool_index in 0..rz_len {
         let rz_pool = reward_zone.get(rz_pool_index).unwrap_optimized();
         let pool_balance = storage::get_pool_balance(e, &rz_pool);
-        total_non_queued_tokens += pool_balance.non_queued_tokens();
+        let config = storage::get_backstop_emis_config(e, &rz_pool);
+        let pool_tokens = boosted_non_queued_tokens(e, &rz_pool, &pool_balance, &config);
+        let mut prorated_tokens = pool_tokens;
+        if let Some(qualified_time) = storage::get_rz_qualified_time(e, &rz_pool) {
+            if qualified_time > last_distribution {
+                prorated_tokens = prorate_tokens(
+                    pool_tokens,
+                    qualified_time,
+                    last_distribution,
+                    emitter_last_distribution,
+                );
+                prorated_pools.push_back((rz_pool.clone(), prorated_tokens));
+            }
+            // a pool that qualified after the period keeps its timestamp for the next period
+            if qualified_time < emitter_last_distribution {
+                storage::del_rz_qualified_time(e, &rz_pool);
+            }
+        }
+        total_non_queued_tokens += pool_tokens;
+        total_prorated_tokens += prorated_tokens;
     }
 
+    // if no pool was in the reward zone for the whole period, the pools share the period's
+    // emissions by their full tokens, as the emissions would otherwise not be distributed
+    let is_prorated = prorated_pools.len() < rz_len;
+    if is_prorated {
+        total_non_queued_tokens = total_prorated_tokens;
+    }
     let additional_index = new_emissions
         .fixed_div_floor(total_non_queued_tokens, SCALAR_14)
         .unwrap_optimized();
     let new_index = prev_index + additional_index;
+
+    // accrue the emissions of pro-rated pools directly, as their share of the period is less
+    // than their boosted tokens
+    for (rz_pool, prorated_tokens) in prorated_pools.iter() {
+        if is_prorated {
+            update_rz_emis_data(e, &rz_pool, false);
+            let emis_data = storage::get_rz_emis_data(e, &rz_pool).unwrap_optimized();
+            let pool_emissions = prorated_tokens
+                .fixed_mul_floor(additional_index, SCALAR_14)
+                .unwrap_optimized();
+            set_rz_emissions(e, &rz_pool, new_index, emis_data.accrued + pool_emissions, false);
+        } else {
+            storage::del_rz_qualified_time(e, &rz_pool);
+        }
+    }
     storage::set_rz_emission_index(e, &new_index);
 
     return new_emissions;
@@ -221,14 +268,28 @@ pub fn gulp_emissions(e: &Env, pool: &Address) -> (i128, i128) {
 }
 
 pub fn update_rz_emis_data(e: &Env, pool: &Address, to_gulp: bool) -> i128 {
+    let config = storage::get_backstop_emis_config(e, pool);
+    update_emission_data_with_config(e, pool, &config, to_gulp)
+}
+
+/// Accrue the pool's share of reward zone emissions, with the pool's backstop deposits boosted by
+/// the emission boost of `config`
+///
+/// Returns the emissions accrued to the pool if `to_gulp` is true, otherwise 0
+pub fn update_emission_data_with_config(
+    e: &Env,
+    pool: &Address,
+    config: &BackstopEmissionConfig,
+    to_gulp: bool,
+) -> i128 {
     if let Some(emission_data) = storage::get_rz_emis_data(e, pool) {
         let pool_balance = storage::get_pool_balance(e, pool);
         let gulp_index = storage::get_rz_emission_index(e);
         let mut accrued = emission_data.accrued;
         if emission_data.index < gulp_index || to_gulp {
-            if pool_balance.non_queued_tokens() > 0 {
-                let new_emissions = pool_balance
-                    .non_queued_tokens()
+            let boosted_tokens = boosted_non_queued_tokens(e, pool, &pool_balance, config);
+            if boosted_tokens > 0 {
+                let new_emissions = boosted_tokens
                     .fixed_mul_floor(gulp_index - emission_data.index, SCALAR_14)
                     .unwrap_optimized();
                 accrued += new_emissions;
@@ -241,6 +302,50 @@ pub fn update_rz_emis_data(e: &Env, pool: &Address, to_gulp: bool) -> i128 {
     return 0;
 }
 
+/// Scale a pool's boosted tokens by the portion of the distribution period since the pool
+/// qualified for the reward zone
+fn prorate_tokens(tokens: i128, qualified_time: u64, period_start: u64, period_end: u64) -> i128 {
+    if qualified_time >= period_end {
+        return 0;
+    }
+    tokens
+        .fixed_mul_floor(i128(period_end - qualified_time), i128(period_end - period_start))
+        .unwrap_optimized()
+}
+
+/// Fetch the non-queued tokens of a pool's backstop, in original backstop tokens, multiplied by
+/// the pool's emission boost
+fn boosted_non_queued_tokens(
+    e: &Env,
+    pool: &Address,
+    pool_balance: &PoolBalance,
+    config: &BackstopEmissionConfig,
+) -> i128 {
+    to_original_tokens(e, pool, pool_balance.non_queued_tokens())
+        .fixed_mul_floor(config.boost, SCALAR_7)
+        .unwrap_optimized()
+}
+
+/// Set the risk tier of a pool, which boosts the pool's share of reward zone emissions so
+/// depositors insuring riskier pools earn more emissions per share. The pool's accrued reward
+/// zone emissions are updated before the boost changes.
+///
+/// Returns the new emission config for the pool
+///
+/// ### Panics
+/// If the risk tier does not exist
+pub fn execute_set_risk_tier(e: &Env, pool: &Address, tier: u32) -> BackstopEmissionConfig {
+    let boost = match RISK_TIER_BOOSTS.get(tier as usize) {
+        Some(boost) => *boost,
+        None => panic_with_error!(e, BackstopError::BadRequest),
+    };
+    update_rz_emis_data(e, pool, false);
+
+    let config = BackstopEmissionConfig { tier, boost };
+    storage::set_backstop_emis_config(e, pool, &config);
+    config
+}
+
 fn set_rz_emissions(e: &Env, pool_id: &Address, index: i128, accrued: i128, to_gulp: bool) -> i128 {
     if to_gulp {
         storage::set_rz_emis_data(e, pool_id, &RzEmissionData { index, accrued: 0 });
@@ -549,6 +654,165 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_distribute_prorates_new_pool() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 1713139200,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let backstop = create_backstop(&e);
+        let emitter_distro_time = 1713139200 - 10;
+        create_emitter(
+            &e,
+            &backstop,
+            &Address::generate(&e),
+            &Address::generate(&e),
+            emitter_distro_time,
+        );
+
+        let pool_1 = Address::generate(&e);
+        let pool_2 = Address::generate(&e);
+        let pool_3 = Address::generate(&e);
+        let reward_zone: Vec<Address> = vec![&e, pool_1.clone(), pool_2.clone(), pool_3.clone()];
+
+        e.as_contract(&backstop, || {
+            storage::set_last_distribution_time(&e, &(emitter_distro_time - (60 * 60 * 24)));
+            storage::set_reward_zone(&e, &reward_zone);
+            storage::set_pool_balance(
+                &e,
+                &pool_1,
+                &PoolBalance {
+                    tokens: 300_000_0000000,
+                    shares: 200_000_0000000,
+                    q4w: 0,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_2,
+                &PoolBalance {
+                    tokens: 200_000_0000000,
+                    shares: 150_000_0000000,
+                    q4w: 0,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_3,
+                &PoolBalance {
+                    tokens: 500_000_0000000,
+                    shares: 600_000_0000000,
+                    q4w: 0,
+                },
+            );
+            storage::set_rz_emis_data(
+                &e,
+                &pool_1,
+                &RzEmissionData {
+                    index: 0,
+                    accrued: 0,
+                },
+            );
+            // pool 3 qualified with 6 hours left in the period
+            storage::set_rz_emis_data(
+                &e,
+                &pool_3,
+                &RzEmissionData {
+                    index: 0,
+                    accrued: 0,
+                },
+            );
+            storage::set_rz_qualified_time(&e, &pool_3, &(emitter_distro_time - 6 * 60 * 60));
+
+            distribute(&e);
+
+            // pool 3 is weighted as 125k tokens
+            let gulp_index = storage::get_rz_emission_index(&e);
+            assert_eq!(gulp_index, 13824000000000);
+            let pool_3_data = storage::get_rz_emis_data(&e, &pool_3).unwrap_optimized();
+            assert_eq!(pool_3_data.index, 13824000000000);
+            assert_eq!(pool_3_data.accrued, 17280_0000000);
+            assert_eq!(storage::get_rz_qualified_time(&e, &pool_3), None);
+
+            // established pools earn the remaining emissions
+            assert_eq!(update_rz_emis_data(&e, &pool_1, true), 41472_0000000);
+        });
+    }
+
+    #[test]
+    fn test_distribute_only_new_pools_not_prorated() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 1713139200,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let backstop = create_backstop(&e);
+        let emitter_distro_time = 1713139200 - 10;
+        create_emitter(
+            &e,
+            &backstop,
+            &Address::generate(&e),
+            &Address::generate(&e),
+            emitter_distro_time,
+        );
+
+        let pool_1 = Address::generate(&e);
+        let pool_2 = Address::generate(&e);
+        let reward_zone: Vec<Address> = vec![&e, pool_1.clone(), pool_2.clone()];
+
+        e.as_contract(&backstop, || {
+            storage::set_last_distribution_time(&e, &(emitter_distro_time - (60 * 60 * 24)));
+            storage::set_reward_zone(&e, &reward_zone);
+            storage::set_pool_balance(
+                &e,
+                &pool_1,
+                &PoolBalance {
+                    tokens: 300_000_0000000,
+                    shares: 200_000_0000000,
+                    q4w: 0,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool_2,
+                &PoolBalance {
+                    tokens: 200_000_0000000,
+                    shares: 150_000_0000000,
+                    q4w: 0,
+                },
+            );
+            // pool 1 qualified during the period and pool 2 after it
+            storage::set_rz_qualified_time(&e, &pool_1, &(emitter_distro_time - 6 * 60 * 60));
+            storage::set_rz_qualified_time(&e, &pool_2, &(emitter_distro_time + 5));
+
+            distribute(&e);
+
+            let gulp_index = storage::get_rz_emission_index(&e);
+            assert_eq!(gulp_index, 17280000000000);
+            assert_eq!(storage::get_rz_qualified_time(&e, &pool_1), None);
+            assert_eq!(storage::get_rz_qualified_time(&e, &pool_2), None);
+        });
+    }
+
     #[test]
     #[should_panic(expected = "Error(Contract, #1000)")]
     fn test_distribute_empty_rz() {
@@ -1191,8 +1455,9 @@ mod tests {
 
             add_to_reward_zone(&e, to_add.clone(), None);
             let actual_rz = storage::get_reward_zone(&e);
-            let expected_rz: Vec<Address> = vec![&e, to_add];
+            let expected_rz: Vec<Address> = vec![&e, to_add.clone()];
             assert_eq!(actual_rz, expected_rz);
+            assert_eq!(storage::get_rz_qualified_time(&e, &to_add), Some(1713139200));
         });
     }
 
@@ -2207,4 +2472,174 @@ mod tests {
             assert!(actual_data.is_none());
         });
     }
+
+    #[test]
+    fn test_update_rz_emis_data_boosted() {
+        let e = Env::default();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1713139200,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let backstop_id = create_backstop(&e);
+        let pool = Address::generate(&e);
+
+        e.as_contract(&backstop_id, || {
+            storage::set_rz_emission_index(&e, &22_00000000000000);
+            storage::set_rz_emis_data(
+                &e,
+                &pool,
+                &RzEmissionData {
+                    index: 11_00000000000000,
+                    accrued: 100_0000000,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool,
+                &PoolBalance {
+                    shares: 150_0000000,
+                    tokens: 200_0000000,
+                    q4w: 2_0000000,
+                },
+            );
+            storage::set_backstop_emis_config(
+                &e,
+                &pool,
+                &BackstopEmissionConfig {
+                    tier: 3,
+                    boost: 1_5000000,
+                },
+            );
+            let result = update_rz_emis_data(&e, &pool, false);
+            let actual_data = storage::get_rz_emis_data(&e, &pool).unwrap_optimized();
+            assert_eq!(result, 0);
+            assert_eq!(actual_data.index, 22_00000000000000);
+            assert_eq!(actual_data.accrued, 3356_0000011);
+        });
+    }
+
+    #[test]
+    fn test_update_emission_data_with_config() {
+        let e = Env::default();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1713139200,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let backstop_id = create_backstop(&e);
+        let pool = Address::generate(&e);
+
+        e.as_contract(&backstop_id, || {
+            storage::set_rz_emission_index(&e, &22_00000000000000);
+            storage::set_rz_emis_data(
+                &e,
+                &pool,
+                &RzEmissionData {
+                    index: 11_00000000000000,
+                    accrued: 100_0000000,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool,
+                &PoolBalance {
+                    shares: 150_0000000,
+                    tokens: 200_0000000,
+                    q4w: 2_0000000,
+                },
+            );
+            // the boost of the given config is applied, not the stored config
+            let config = BackstopEmissionConfig {
+                tier: 3,
+                boost: 1_5000000,
+            };
+            let result = update_emission_data_with_config(&e, &pool, &config, false);
+            let actual_data = storage::get_rz_emis_data(&e, &pool).unwrap_optimized();
+            assert_eq!(result, 0);
+            assert_eq!(actual_data.index, 22_00000000000000);
+            assert_eq!(actual_data.accrued, 3356_0000011);
+        });
+    }
+
+    /********** execute_set_risk_tier **********/
+
+    #[test]
+    fn test_execute_set_risk_tier() {
+        let e = Env::default();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1713139200,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let backstop_id = create_backstop(&e);
+        let pool = Address::generate(&e);
+
+        e.as_contract(&backstop_id, || {
+            storage::set_rz_emission_index(&e, &22_00000000000000);
+            storage::set_rz_emis_data(
+                &e,
+                &pool,
+                &RzEmissionData {
+                    index: 11_00000000000000,
+                    accrued: 100_0000000,
+                },
+            );
+            storage::set_pool_balance(
+                &e,
+                &pool,
+                &PoolBalance {
+                    shares: 150_0000000,
+                    tokens: 200_0000000,
+                    q4w: 2_0000000,
+                },
+            );
+
+            let config = execute_set_risk_tier(&e, &pool, 3);
+            assert_eq!(config.tier, 3);
+            assert_eq!(config.boost, 1_5000000);
+            let stored_config = storage::get_backstop_emis_config(&e, &pool);
+            assert_eq!(stored_config.tier, 3);
+            assert_eq!(stored_config.boost, 1_5000000);
+
+            // emissions before the tier change accrue unboosted
+            let rz_data = storage::get_rz_emis_data(&e, &pool).unwrap_optimized();
+            assert_eq!(rz_data.index, 22_00000000000000);
+            assert_eq!(rz_data.accrued, 2270_6666674);
+
+            // emissions after the tier change accrue boosted
+            storage::set_rz_emission_index(&e, &33_00000000000000);
+            update_rz_emis_data(&e, &pool, false);
+            let rz_data = storage::get_rz_emis_data(&e, &pool).unwrap_optimized();
+            assert_eq!(rz_data.index, 33_00000000000000);
+            assert_eq!(rz_data.accrued, 5526_6666685);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_execute_set_risk_tier_invalid_tier() {
+        let e = Env::default();
+        let backstop_id = create_backstop(&e);
+        let pool = Address::generate(&e);
+
+        e.as_contract(&backstop_id, || {
+            execute_set_risk_tier(&e, &pool, 4);
+        });
+    }
 }
@@ -1,10 +1,17 @@
 mod claim;
-pub use claim::execute_claim;
+pub use claim::{execute_claim, execute_claim_and_deposit};
 
 mod distributor;
 pub use distributor::update_emissions;
 
 mod manager;
 pub use manager::{
-    add_to_reward_zone, distribute, gulp_emissions, remove_from_reward_zone, update_rz_emis_data,
+    add_to_reward_zone, distribute, execute_set_risk_tier, gulp_emissions, remove_from_reward_zone,
+    update_rz_emis_data,
+};
+
+mod rewards;
+pub use rewards::{
+    execute_claim_reward_token, execute_fund_reward_token, execute_remove_reward_token,
+    update_rewards,
 };
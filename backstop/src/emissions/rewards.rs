@@ -0,0 +1,675 @@
+//! Methods for distributing additional reward tokens to backstop depositors
+
+use cast::{i128, u64};
+use sep_41_token::TokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env, Vec};
+
+use super::distributor::{accrue_emission_index, accrue_user_emissions};
+use crate::{
+    backstop::{require_is_from_pool_factory, PoolBalance, UserBalance},
+    constants::{
+        MAX_REWARD_DURATION, MAX_REWARD_TOKENS, MIN_REWARD_DURATION, REWARD_CLAIM_PERIOD, SCALAR_7,
+    },
+    errors::BackstopError,
+    require_nonnegative,
+    storage::{self, BackstopEmissionData},
+};
+
+/// Fund a reward token to be emitted to the backstop depositors of a pool over `duration`
+/// seconds. Any tokens left over from the token's current emissions are rolled into the
+/// new emissions.
+///
+/// Returns the new emission data for the reward token
+///
+/// ### Panics
+/// If the token is BLND or the backstop token, the duration is invalid, the token was removed
+/// from the pool, or the pool already has the max number of reward tokens
+pub fn execute_fund_reward_token(
+    e: &Env,
+    from: &Address,
+    pool_address: &Address,
+    token: &Address,
+    amount: i128,
+    duration: u64,
+) -> BackstopEmissionData {
+    require_nonnegative(e, amount);
+    if token == &storage::get_blnd_token(e)
+        || token == &storage::get_backstop_token(e)
+        || storage::get_backstop_token_v2(e).as_ref() == Some(token)
+        || !(MIN_REWARD_DURATION..=MAX_REWARD_DURATION).contains(&duration)
+    {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+    let pool_balance = storage::get_pool_balance(e, pool_address);
+    require_is_from_pool_factory(e, pool_address, pool_balance.shares);
+
+    let emis_data = storage::get_reward_emis_data(e, pool_address, token);
+    let mut reward_tokens = storage::get_reward_tokens(e, pool_address);
+    if !reward_tokens.contains(token) {
+        // depositor balances are no longer tracked for removed tokens, so they can't be re-added
+        if emis_data.is_some() {
+            panic_with_error!(e, BackstopError::BadRequest);
+        }
+        if reward_tokens.len() >= MAX_REWARD_TOKENS {
+            panic_with_error!(e, BackstopError::TooManyRewardTokens);
+        }
+        reward_tokens.push_back(token.clone());
+        storage::set_reward_tokens(e, pool_address, &reward_tokens);
+    }
+
+    let now = e.ledger().timestamp();
+    let mut tokens_to_emit = amount;
+    let index = match emis_data {
+        Some(emis_data) => {
+            let emis_data = accrue_emission_index(e, &emis_data, &pool_balance)
+                .unwrap_or(emis_data);
+            if emis_data.expiration > now {
+                // Eps is scaled by 14 decimals
+                tokens_to_emit += i128(emis_data.eps)
+                    .fixed_mul_floor(i128(emis_data.expiration - now), SCALAR_7)
+                    .unwrap_optimized();
+            }
+            emis_data.index
+        }
+        None => 0,
+    };
+
+    // Scale eps by 14 decimal places to reduce rounding errors
+    let eps = u64(tokens_to_emit * SCALAR_7 / i128(duration)).unwrap_optimized();
+    let new_data = BackstopEmissionData {
+        expiration: now + duration,
+        eps,
+        index,
+        last_time: now,
+    };
+    storage::set_reward_emis_data(e, pool_address, token, &new_data);
+
+    TokenClient::new(e, token).transfer(from, &e.current_contract_address(), &amount);
+
+    new_data
+}
+
+/// Remove a reward token from the pool's backstop once its emissions have ended and the claim
+/// period has passed. Rewards accrued to depositors before the token is removed can still be
+/// claimed, but depositors no longer accrue the token's rewards.
+///
+/// ### Panics
+/// If the token is not a reward token of the pool, or its emissions or claim period have not
+/// ended
+pub fn execute_remove_reward_token(e: &Env, pool_address: &Address, token: &Address) {
+    let mut reward_tokens = storage::get_reward_tokens(e, pool_address);
+    let index = match reward_tokens.first_index_of(token) {
+        Some(index) => index,
+        None => panic_with_error!(e, BackstopError::BadRequest),
+    };
+    let emis_data = storage::get_reward_emis_data(e, pool_address, token).unwrap_optimized();
+    if emis_data.expiration + REWARD_CLAIM_PERIOD > e.ledger().timestamp() {
+        panic_with_error!(e, BackstopError::NotExpired);
+    }
+    reward_tokens.remove(index);
+    storage::set_reward_tokens(e, pool_address, &reward_tokens);
+}
+
+/// Update the reward token emission indexes for the user and pool
+pub fn update_rewards(
+    e: &Env,
+    pool_id: &Address,
+    pool_balance: &PoolBalance,
+    user_id: &Address,
+    user_balance: &UserBalance,
+) {
+    for token in storage::get_reward_tokens(e, pool_id).iter() {
+        update_user_rewards(e, pool_id, &token, pool_balance, user_id, user_balance, false);
+    }
+}
+
+/// Perform a claim for a reward token emitted to backstop depositors by a user
+///
+/// Returns the amount of the reward token claimed
+pub fn execute_claim_reward_token(
+    e: &Env,
+    from: &Address,
+    pool_addresses: &Vec<Address>,
+    token: &Address,
+    to: &Address,
+) -> i128 {
+    if pool_addresses.is_empty() {
+        panic_with_error!(e, BackstopError::BadRequest);
+    }
+
+    let mut claimed: i128 = 0;
+    for pool_id in pool_addresses.iter() {
+        if storage::get_reward_tokens(e, &pool_id).contains(token) {
+            let pool_balance = storage::get_pool_balance(e, &pool_id);
+            let user_balance = storage::get_user_balance(e, &pool_id, from);
+            claimed +=
+                update_user_rewards(e, &pool_id, token, &pool_balance, from, &user_balance, true);
+        } else {
+            claimed += claim_accrued_rewards(e, &pool_id, token, from);
+        }
+    }
+
+    if claimed > 0 {
+        TokenClient::new(e, token).transfer(&e.current_contract_address(), to, &claimed);
+    }
+    claimed
+}
+
+/// Claim the rewards a user accrued for a reward token removed from the pool. The user's balance
+/// is no longer tracked for the token, so only rewards accrued before it was removed are paid.
+///
+/// ### Returns
+/// The number of reward tokens the caller needs to send to the user
+fn claim_accrued_rewards(e: &Env, pool_id: &Address, token: &Address, user_id: &Address) -> i128 {
+    match storage::get_user_reward_emis_data(e, pool_id, token, user_id) {
+        Some(mut user_data) if user_data.accrued > 0 => {
+            let accrued = user_data.accrued;
+            user_data.accrued = 0;
+            storage::set_user_reward_emis_data(e, pool_id, token, user_id, &user_data);
+            accrued
+        }
+        _ => 0,
+    }
+}
+
+/// Update the emissions of a reward token for the user and pool. If `to_claim` is true, the
+/// user's accrued rewards will be returned and a value of zero will be stored to the ledger.
+///
+/// ### Returns
+/// The number of reward tokens the caller needs to send to the user
+fn update_user_rewards(
+    e: &Env,
+    pool_id: &Address,
+    token: &Address,
+    pool_balance: &PoolBalance,
+    user_id: &Address,
+    user_balance: &UserBalance,
+    to_claim: bool,
+) -> i128 {
+    let emis_data = match storage::get_reward_emis_data(e, pool_id, token) {
+        Some(emis_data) => match accrue_emission_index(e, &emis_data, pool_balance) {
+            Some(new_data) => {
+                storage::set_reward_emis_data(e, pool_id, token, &new_data);
+                new_data
+            }
+            None => emis_data,
+        },
+        None => return 0,
+    };
+
+    let user_data = storage::get_user_reward_emis_data(e, pool_id, token, user_id);
+    let mut new_user_data = match accrue_user_emissions(e, &user_data, &emis_data, user_balance) {
+        Some(new_user_data) => new_user_data,
+        None if to_claim => user_data.unwrap_optimized(),
+        None => return 0,
+    };
+    let claimed = if to_claim {
+        let accrued = new_user_data.accrued;
+        new_user_data.accrued = 0;
+        accrued
+    } else {
+        0
+    };
+    storage::set_user_reward_emis_data(e, pool_id, token, user_id, &new_user_data);
+    claimed
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        storage::UserEmissionData,
+        testutils::{create_backstop, create_blnd_token, create_token},
+    };
+
+    use super::*;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger, LedgerInfo},
+        vec,
+    };
+
+    /********** execute_fund_reward_token **********/
+
+    #[test]
+    fn test_fund_reward_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1500000000,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        create_blnd_token(&e, &backstop_address, &bombadil);
+        let (token, token_client) = create_token(&e, &bombadil);
+        token_client.mint(&frodo, &2000_0000000);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &Address::generate(&e));
+            storage::set_pool_balance(
+                &e,
+                &pool_id,
+                &PoolBalance {
+                    shares: 100_0000000,
+                    tokens: 150_0000000,
+                    q4w: 0,
+                },
+            );
+
+            let emis_data =
+                execute_fund_reward_token(&e, &frodo, &pool_id, &token, 1000_0000000, 1000000);
+            assert_eq!(emis_data.expiration, 1500000000 + 1000000);
+            assert_eq!(emis_data.eps, 0_00100000000000);
+            assert_eq!(emis_data.index, 0);
+            assert_eq!(emis_data.last_time, 1500000000);
+            assert_eq!(storage::get_reward_tokens(&e, &pool_id), vec![&e, token.clone()]);
+            assert_eq!(token_client.balance(&backstop_address), 1000_0000000);
+
+            // half of the emissions are left, which get rolled into the new emissions
+            e.ledger().set(LedgerInfo {
+                timestamp: 1500000000 + 500000,
+                protocol_version: 22,
+                sequence_number: 0,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 10,
+                min_persistent_entry_ttl: 10,
+                max_entry_ttl: 3110400,
+            });
+            let emis_data =
+                execute_fund_reward_token(&e, &frodo, &pool_id, &token, 1000_0000000, 1000000);
+            assert_eq!(emis_data.expiration, 1500000000 + 500000 + 1000000);
+            assert_eq!(emis_data.eps, 0_00150000000000);
+            assert_eq!(emis_data.index, 5_00000000000000);
+            assert_eq!(emis_data.last_time, 1500000000 + 500000);
+            assert_eq!(storage::get_reward_tokens(&e, &pool_id).len(), 1);
+            assert_eq!(token_client.balance(&backstop_address), 2000_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_fund_reward_token_blnd_panics() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let (blnd, blnd_client) = create_blnd_token(&e, &backstop_address, &bombadil);
+        blnd_client.mint(&frodo, &1000_0000000);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &Address::generate(&e));
+            execute_fund_reward_token(&e, &frodo, &pool_id, &blnd, 1000_0000000, 1000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_fund_reward_token_invalid_duration() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        create_blnd_token(&e, &backstop_address, &bombadil);
+        let (token, token_client) = create_token(&e, &bombadil);
+        token_client.mint(&frodo, &1000_0000000);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &Address::generate(&e));
+            execute_fund_reward_token(&e, &frodo, &pool_id, &token, 1000_0000000, 60 * 60);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1011)")]
+    fn test_fund_reward_token_too_many_tokens() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        create_blnd_token(&e, &backstop_address, &bombadil);
+        let (token, token_client) = create_token(&e, &bombadil);
+        token_client.mint(&frodo, &1000_0000000);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &Address::generate(&e));
+            storage::set_pool_balance(
+                &e,
+                &pool_id,
+                &PoolBalance {
+                    shares: 100_0000000,
+                    tokens: 150_0000000,
+                    q4w: 0,
+                },
+            );
+            let mut reward_tokens = vec![&e];
+            for _ in 0..MAX_REWARD_TOKENS {
+                reward_tokens.push_back(Address::generate(&e));
+            }
+            storage::set_reward_tokens(&e, &pool_id, &reward_tokens);
+
+            execute_fund_reward_token(&e, &frodo, &pool_id, &token, 1000_0000000, 1000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_fund_reward_token_removed_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        create_blnd_token(&e, &backstop_address, &bombadil);
+        let (token, token_client) = create_token(&e, &bombadil);
+        token_client.mint(&frodo, &1000_0000000);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_backstop_token(&e, &Address::generate(&e));
+            storage::set_pool_balance(
+                &e,
+                &pool_id,
+                &PoolBalance {
+                    shares: 100_0000000,
+                    tokens: 150_0000000,
+                    q4w: 0,
+                },
+            );
+            storage::set_reward_emis_data(
+                &e,
+                &pool_id,
+                &token,
+                &BackstopEmissionData {
+                    expiration: 1000,
+                    eps: 0_10000000000000,
+                    index: 0,
+                    last_time: 0,
+                },
+            );
+
+            execute_fund_reward_token(&e, &frodo, &pool_id, &token, 1000_0000000, 1000000);
+        });
+    }
+
+    /********** execute_remove_reward_token **********/
+
+    #[test]
+    fn test_remove_reward_token() {
+        let e = Env::default();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1500000000 + REWARD_CLAIM_PERIOD,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let token = Address::generate(&e);
+        let other_token = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_reward_tokens(&e, &pool_id, &vec![&e, other_token.clone(), token.clone()]);
+            storage::set_reward_emis_data(
+                &e,
+                &pool_id,
+                &token,
+                &BackstopEmissionData {
+                    expiration: 1500000000,
+                    eps: 0_10000000000000,
+                    index: 1_25000000000000,
+                    last_time: 1500000000,
+                },
+            );
+
+            execute_remove_reward_token(&e, &pool_id, &token);
+            assert_eq!(storage::get_reward_tokens(&e, &pool_id), vec![&e, other_token]);
+            assert!(storage::get_reward_emis_data(&e, &pool_id, &token).is_some());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1001)")]
+    fn test_remove_reward_token_claim_period_not_passed() {
+        let e = Env::default();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1500000000 + REWARD_CLAIM_PERIOD - 1,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_reward_tokens(&e, &pool_id, &vec![&e, token.clone()]);
+            storage::set_reward_emis_data(
+                &e,
+                &pool_id,
+                &token,
+                &BackstopEmissionData {
+                    expiration: 1500000000,
+                    eps: 0_10000000000000,
+                    index: 1_25000000000000,
+                    last_time: 1500000000,
+                },
+            );
+
+            execute_remove_reward_token(&e, &pool_id, &token);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1000)")]
+    fn test_remove_reward_token_not_reward_token() {
+        let e = Env::default();
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let token = Address::generate(&e);
+
+        e.as_contract(&backstop_address, || {
+            execute_remove_reward_token(&e, &pool_id, &token);
+        });
+    }
+
+    /********** update_rewards / execute_claim_reward_token **********/
+
+    #[test]
+    fn test_update_and_claim_rewards() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1500000000 + 1000,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let (token, token_client) = create_token(&e, &bombadil);
+        token_client.mint(&backstop_address, &1000_0000000);
+
+        let pool_balance = PoolBalance {
+            shares: 100_0000000,
+            tokens: 150_0000000,
+            q4w: 20_0000000,
+        };
+        let user_balance = UserBalance {
+            shares: 10_0000000,
+            q4w: vec![&e],
+        };
+        e.as_contract(&backstop_address, || {
+            storage::set_pool_balance(&e, &pool_id, &pool_balance);
+            storage::set_user_balance(&e, &pool_id, &samwise, &user_balance);
+            storage::set_reward_tokens(&e, &pool_id, &vec![&e, token.clone()]);
+            storage::set_reward_emis_data(
+                &e,
+                &pool_id,
+                &token,
+                &BackstopEmissionData {
+                    expiration: 1500000000 + 1000000,
+                    eps: 0_10000000000000,
+                    index: 0,
+                    last_time: 1500000000,
+                },
+            );
+            storage::set_user_reward_emis_data(
+                &e,
+                &pool_id,
+                &token,
+                &samwise,
+                &UserEmissionData {
+                    index: 0,
+                    accrued: 0,
+                },
+            );
+
+            // 1000 seconds * 0.1 tokens per second / 80 unqueued shares
+            update_rewards(&e, &pool_id, &pool_balance, &samwise, &user_balance);
+            let emis_data = storage::get_reward_emis_data(&e, &pool_id, &token).unwrap();
+            assert_eq!(emis_data.index, 1_25000000000000);
+            assert_eq!(emis_data.last_time, 1500000000 + 1000);
+            let user_data =
+                storage::get_user_reward_emis_data(&e, &pool_id, &token, &samwise).unwrap();
+            assert_eq!(user_data.index, 1_25000000000000);
+            assert_eq!(user_data.accrued, 12_5000000);
+
+            let pools = vec![&e, pool_id.clone()];
+            let claimed = execute_claim_reward_token(&e, &samwise, &pools, &token, &frodo);
+            assert_eq!(claimed, 12_5000000);
+            assert_eq!(token_client.balance(&frodo), 12_5000000);
+            assert_eq!(token_client.balance(&backstop_address), 987_5000000);
+            let user_data =
+                storage::get_user_reward_emis_data(&e, &pool_id, &token, &samwise).unwrap();
+            assert_eq!(user_data.index, 1_25000000000000);
+            assert_eq!(user_data.accrued, 0);
+        });
+    }
+
+    #[test]
+    fn test_claim_rewards_removed_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1500000000 + REWARD_CLAIM_PERIOD + 1000,
+            protocol_version: 22,
+            sequence_number: 0,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let backstop_address = create_backstop(&e);
+        let pool_id = Address::generate(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let (token, token_client) = create_token(&e, &bombadil);
+        token_client.mint(&backstop_address, &1000_0000000);
+
+        e.as_contract(&backstop_address, || {
+            storage::set_pool_balance(
+                &e,
+                &pool_id,
+                &PoolBalance {
+                    shares: 100_0000000,
+                    tokens: 150_0000000,
+                    q4w: 20_0000000,
+                },
+            );
+            storage::set_user_balance(
+                &e,
+                &pool_id,
+                &samwise,
+                &UserBalance {
+                    shares: 10_0000000,
+                    q4w: vec![&e],
+                },
+            );
+            storage::set_reward_tokens(&e, &pool_id, &vec![&e]);
+            storage::set_reward_emis_data(
+                &e,
+                &pool_id,
+                &token,
+                &BackstopEmissionData {
+                    expiration: 1500000000,
+                    eps: 0_10000000000000,
+                    index: 0,
+                    last_time: 1500000000 - 1000,
+                },
+            );
+            storage::set_user_reward_emis_data(
+                &e,
+                &pool_id,
+                &token,
+                &samwise,
+                &UserEmissionData {
+                    index: 0,
+                    accrued: 5_0000000,
+                },
+            );
+
+            // only the rewards accrued before the token was removed are paid
+            let pools = vec![&e, pool_id.clone()];
+            let claimed = execute_claim_reward_token(&e, &samwise, &pools, &token, &frodo);
+            assert_eq!(claimed, 5_0000000);
+            assert_eq!(token_client.balance(&frodo), 5_0000000);
+            let user_data =
+                storage::get_user_reward_emis_data(&e, &pool_id, &token, &samwise).unwrap();
+            assert_eq!(user_data.index, 0);
+            assert_eq!(user_data.accrued, 0);
+
+            let claimed = execute_claim_reward_token(&e, &samwise, &pools, &token, &frodo);
+            assert_eq!(claimed, 0);
+            assert_eq!(token_client.balance(&frodo), 5_0000000);
+        });
+    }
+}
@@ -28,4 +28,6 @@ pub enum BackstopError {
     NotInRewardZone = 1008,
     RewardZoneFull = 1009,
     MaxBackfillEmissions = 1010,
+    TooManyRewardTokens = 1011,
+    InvalidMigration = 1012,
 }
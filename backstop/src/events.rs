@@ -1,4 +1,4 @@
-use soroban_sdk::{Address, Env, Symbol};
+use soroban_sdk::{Address, BytesN, Env, Symbol};
 
 pub struct BackstopEvents {}
 
@@ -158,6 +158,21 @@ impl BackstopEvents {
         e.events().publish(topics, (to, amount));
     }
 
+    /// Emitted when USDC is drawn from the backstop
+    ///
+    /// - topics - `["draw_usdc", pool_address: Address]`
+    /// - data - `[to: Address, amount: i128, tokens_in: i128]`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `to` - The address receiving the drawn USDC
+    /// * `amount` - The amount of USDC drawn
+    /// * `tokens_in` - The amount of backstop tokens burned
+    pub fn draw_usdc(e: &Env, pool_address: Address, to: Address, amount: i128, tokens_in: i128) {
+        let topics = (Symbol::new(e, "draw_usdc"), pool_address);
+        e.events().publish(topics, (to, amount, tokens_in));
+    }
+
     /// Emitted when tokens are donated to the backstop
     ///
     /// - topics - `["donate", pool_address: Address, from: Address]`
@@ -171,4 +186,176 @@ impl BackstopEvents {
         let topics = (Symbol::new(e, "donate"), pool_address, from);
         e.events().publish(topics, amount);
     }
+
+    /// Emitted when BLND or USDC is donated to the backstop
+    ///
+    /// - topics - `["donate_underlying", pool_address: Address, token: Address]`
+    /// - data - `[amount: i128, tokens_out: i128]`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `token` - The address of the donated token
+    /// * `amount` - The amount of tokens donated
+    /// * `tokens_out` - The amount of backstop tokens minted
+    pub fn donate_underlying(
+        e: &Env,
+        pool_address: Address,
+        token: Address,
+        amount: i128,
+        tokens_out: i128,
+    ) {
+        let topics = (Symbol::new(e, "donate_underlying"), pool_address, token);
+        e.events().publish(topics, (amount, tokens_out));
+    }
+
+    /// Emitted when the Q4W lock time of a pool is updated
+    ///
+    /// - topics - `["set_q4w_lock", pool_address: Address]`
+    /// - data - `q4w_lock_seconds: u64`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `q4w_lock_seconds` - The new lock time in seconds
+    pub fn set_q4w_lock(e: &Env, pool_address: Address, q4w_lock_seconds: u64) {
+        let topics = (Symbol::new(e, "set_q4w_lock"), pool_address);
+        e.events().publish(topics, q4w_lock_seconds);
+    }
+
+    /// Emitted when a pool is decommissioned
+    ///
+    /// - topics - `["decommission_pool", pool_address: Address]`
+    /// - data - `()`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    pub fn decommission_pool(e: &Env, pool_address: Address) {
+        let topics = (Symbol::new(e, "decommission_pool"), pool_address);
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when a share token is deployed for a pool's backstop
+    ///
+    /// - topics - `["deploy_share_token", pool_address: Address]`
+    /// - data - `share_token: Address`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `share_token` - The address of the share token
+    pub fn deploy_share_token(e: &Env, pool_address: Address, share_token: Address) {
+        let topics = (Symbol::new(e, "deploy_share_token"), pool_address);
+        e.events().publish(topics, share_token);
+    }
+
+    /// Emitted when the risk tier of a pool is updated
+    ///
+    /// - topics - `["set_risk_tier", pool_address: Address]`
+    /// - data - `[tier: u32, boost: i128]`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `tier` - The new risk tier
+    /// * `boost` - The emission boost of the risk tier
+    pub fn set_risk_tier(e: &Env, pool_address: Address, tier: u32, boost: i128) {
+        let topics = (Symbol::new(e, "set_risk_tier"), pool_address);
+        e.events().publish(topics, (tier, boost));
+    }
+
+    /// Emitted when a reward token is funded for the backstop depositors of a pool
+    ///
+    /// - topics - `["fund_reward_token", pool_address: Address, token: Address]`
+    /// - data - `[from: Address, amount: i128, duration: u64]`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `token` - The address of the reward token
+    /// * `from` - The address funding the rewards
+    /// * `amount` - The amount of the reward token funded
+    /// * `duration` - The duration in seconds the rewards are emitted over
+    pub fn fund_reward_token(
+        e: &Env,
+        pool_address: Address,
+        token: Address,
+        from: Address,
+        amount: i128,
+        duration: u64,
+    ) {
+        let topics = (Symbol::new(e, "fund_reward_token"), pool_address, token);
+        e.events().publish(topics, (from, amount, duration));
+    }
+
+    /// Emitted when a reward token is removed from the backstop depositors of a pool
+    ///
+    /// - topics - `["remove_reward_token", pool_address: Address, token: Address]`
+    /// - data - `()`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `token` - The address of the reward token
+    pub fn remove_reward_token(e: &Env, pool_address: Address, token: Address) {
+        let topics = (Symbol::new(e, "remove_reward_token"), pool_address, token);
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when a reward token is claimed by a backstop depositor
+    ///
+    /// - topics - `["claim_reward_token", from: Address, token: Address]`
+    /// - data - `amount: i128`
+    ///
+    /// ### Arguments
+    /// * `from` - The address claiming the rewards
+    /// * `token` - The address of the reward token
+    /// * `amount` - The amount of the reward token claimed
+    pub fn claim_reward_token(e: &Env, from: Address, token: Address, amount: i128) {
+        let topics = (Symbol::new(e, "claim_reward_token"), from, token);
+        e.events().publish(topics, amount);
+    }
+
+    /// Emitted when governance sets the share token contract wasm
+    ///
+    /// - topics - `["set_share_token_wasm"]`
+    /// - data - `wasm_hash: BytesN<32>`
+    ///
+    /// ### Arguments
+    /// * `wasm_hash` - The hash of the share token contract wasm
+    pub fn set_share_token_wasm(e: &Env, wasm_hash: BytesN<32>) {
+        let topics = (Symbol::new(e, "set_share_token_wasm"),);
+        e.events().publish(topics, wasm_hash);
+    }
+
+    /// Emitted when governance sets the bToken contract wasm
+    ///
+    /// - topics - `["set_b_token_wasm"]`
+    /// - data - `wasm_hash: BytesN<32>`
+    ///
+    /// ### Arguments
+    /// * `wasm_hash` - The hash of the bToken contract wasm
+    pub fn set_b_token_wasm(e: &Env, wasm_hash: BytesN<32>) {
+        let topics = (Symbol::new(e, "set_b_token_wasm"),);
+        e.events().publish(topics, wasm_hash);
+    }
+
+    /// Emitted when governance sets the backstop token pool deposits can be migrated to
+    ///
+    /// - topics - `["set_backstop_token_v2"]`
+    /// - data - `token: Address`
+    ///
+    /// ### Arguments
+    /// * `token` - The address of the new backstop token
+    pub fn set_backstop_token_v2(e: &Env, token: Address) {
+        let topics = (Symbol::new(e, "set_backstop_token_v2"),);
+        e.events().publish(topics, token);
+    }
+
+    /// Emitted when a pool's backstop deposits are migrated to the new backstop token
+    ///
+    /// - topics - `["migrate_deposit", pool_address: Address]`
+    /// - data - `tokens: i128`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `tokens` - The amount of new backstop tokens held for the pool
+    pub fn migrate_deposit(e: &Env, pool_address: Address, tokens: i128) {
+        let topics = (Symbol::new(e, "migrate_deposit"), pool_address);
+        e.events().publish(topics, tokens);
+    }
 }
@@ -16,4 +16,6 @@ mod testutils;
 pub use backstop::{PoolBackstopData, PoolBalance, UserBalance, Q4W};
 pub use contract::*;
 pub use errors::BackstopError;
-pub use storage::{BackstopDataKey, BackstopEmissionData, PoolUserKey, UserEmissionData};
+pub use storage::{
+    BackstopDataKey, BackstopEmissionConfig, BackstopEmissionData, PoolUserKey, UserEmissionData,
+};
@@ -1,8 +1,12 @@
 use soroban_sdk::{
-    contracttype, unwrap::UnwrapOptimized, vec, Address, Env, IntoVal, Symbol, TryFromVal, Val, Vec,
+    contracttype, unwrap::UnwrapOptimized, vec, Address, BytesN, Env, IntoVal, Symbol, TryFromVal,
+    Val, Vec,
 };
 
-use crate::backstop::{PoolBalance, UserBalance};
+use crate::{
+    backstop::{PoolBalance, UserBalance},
+    constants::{Q4W_LOCK_TIME, SCALAR_7},
+};
 
 /********** Ledger Thresholds **********/
 
@@ -41,6 +45,16 @@ pub struct BackstopEmissionData {
     pub last_time: u64,
 }
 
+// The emission config for a pool's backstop
+#[derive(Clone)]
+#[contracttype]
+pub struct BackstopEmissionConfig {
+    // The risk tier of the pool
+    pub tier: u32,
+    // The multiplier applied to the pool's backstop deposits when splitting emissions (7 decimals)
+    pub boost: i128,
+}
+
 /// The user emission data for the reserve b or d token
 #[derive(Clone)]
 #[contracttype]
@@ -61,9 +75,14 @@ const LAST_DISTRO_KEY: &str = "LastDist";
 const REWARD_ZONE_KEY: &str = "RZ";
 const DROP_LIST_KEY: &str = "DropList";
 const LP_TOKEN_VAL_KEY: &str = "LPTknVal";
+const LP_TOKEN_V2_VAL_KEY: &str = "LPTknV2Val";
 const RZ_EMISSION_INDEX_KEY: &str = "RZEmissionIndex";
 const BACKFILL_EMISSIONS_KEY: &str = "BackfillEmis";
 const BACKFILL_STATUS_KEY: &str = "Backfill";
+const GOVERNANCE_KEY: &str = "Gov";
+const BACKSTOP_TOKEN_V2_KEY: &str = "BTokenV2";
+const SHARE_TOKEN_WASM_KEY: &str = "ShareWasm";
+const B_TOKEN_WASM_KEY: &str = "ResBTokenWasm";
 
 #[derive(Clone)]
 #[contracttype]
@@ -72,6 +91,21 @@ pub struct PoolUserKey {
     user: Address,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolRewardKey {
+    pool: Address,
+    token: Address,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolUserRewardKey {
+    pool: Address,
+    token: Address,
+    user: Address,
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum BackstopDataKey {
@@ -79,8 +113,17 @@ pub enum BackstopDataKey {
     PoolBalance(Address),
     PoolUSDC(Address),
     RzEmisData(Address),
+    RzQualified(Address),
     BEmisData(Address),
     UEmisData(PoolUserKey),
+    Q4WLock(Address),
+    EmisConfig(Address),
+    Migrated(Address),
+    Decomm(Address),
+    RewardTokens(Address),
+    REmisData(PoolRewardKey),
+    UREmisData(PoolUserRewardKey),
+    ShareToken(Address),
 }
 
 /****************************
@@ -204,6 +247,75 @@ pub fn set_backstop_token(e: &Env, backstop_token_id: &Address) {
         .set::<Symbol, Address>(&Symbol::new(e, BACKSTOP_TOKEN_KEY), backstop_token_id);
 }
 
+/// Fetch the governance address
+pub fn get_governance(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&Symbol::new(e, GOVERNANCE_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the governance address
+///
+/// ### Arguments
+/// * `governance` - The governance address
+pub fn set_governance(e: &Env, governance: &Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, GOVERNANCE_KEY), governance);
+}
+
+/// Fetch the backstop token deposits can be migrated to, if one is set
+pub fn get_backstop_token_v2(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&Symbol::new(e, BACKSTOP_TOKEN_V2_KEY))
+}
+
+/// Set the backstop token deposits can be migrated to
+///
+/// ### Arguments
+/// * `token_v2` - The new backstop token
+pub fn set_backstop_token_v2(e: &Env, token_v2: &Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, BACKSTOP_TOKEN_V2_KEY), token_v2);
+}
+
+/// Fetch the hash of the share token contract wasm, if set
+pub fn get_share_token_wasm(e: &Env) -> Option<BytesN<32>> {
+    e.storage()
+        .instance()
+        .get::<Symbol, BytesN<32>>(&Symbol::new(e, SHARE_TOKEN_WASM_KEY))
+}
+
+/// Set the hash of the share token contract wasm
+///
+/// ### Arguments
+/// * `wasm_hash` - The hash of the share token contract wasm
+pub fn set_share_token_wasm(e: &Env, wasm_hash: &BytesN<32>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, BytesN<32>>(&Symbol::new(e, SHARE_TOKEN_WASM_KEY), wasm_hash);
+}
+
+/// Fetch the hash of the bToken contract wasm, if set
+pub fn get_b_token_wasm(e: &Env) -> Option<BytesN<32>> {
+    e.storage()
+        .instance()
+        .get::<Symbol, BytesN<32>>(&Symbol::new(e, B_TOKEN_WASM_KEY))
+}
+
+/// Set the hash of the bToken contract wasm
+///
+/// ### Arguments
+/// * `wasm_hash` - The hash of the bToken contract wasm
+pub fn set_b_token_wasm(e: &Env, wasm_hash: &BytesN<32>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, BytesN<32>>(&Symbol::new(e, B_TOKEN_WASM_KEY), wasm_hash);
+}
+
 /********** User Shares **********/
 
 /// Fetch the balance's for a given user
@@ -280,6 +392,159 @@ pub fn set_pool_balance(e: &Env, pool: &Address, balance: &PoolBalance) {
         .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
 }
 
+/// Fetch the time in seconds that Q4W entries for a pool are locked for
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn get_q4w_lock(e: &Env, pool: &Address) -> u64 {
+    let key = BackstopDataKey::Q4WLock(pool.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || Q4W_LOCK_TIME,
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the time in seconds that Q4W entries for a pool are locked for
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `lock_time` - The lock time in seconds
+pub fn set_q4w_lock(e: &Env, pool: &Address, lock_time: &u64) {
+    let key = BackstopDataKey::Q4WLock(pool.clone());
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, u64>(&key, lock_time);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Fetch the emission config for a pool's backstop
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn get_backstop_emis_config(e: &Env, pool: &Address) -> BackstopEmissionConfig {
+    let key = BackstopDataKey::EmisConfig(pool.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || BackstopEmissionConfig {
+            tier: 0,
+            boost: SCALAR_7,
+        },
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the emission config for a pool's backstop
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `config` - The emission config
+pub fn set_backstop_emis_config(e: &Env, pool: &Address, config: &BackstopEmissionConfig) {
+    let key = BackstopDataKey::EmisConfig(pool.clone());
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, BackstopEmissionConfig>(&key, config);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Fetch the amount of the new backstop token each original backstop token converted to when a
+/// pool's deposits were migrated, if they have been migrated
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn get_pool_migration_rate(e: &Env, pool: &Address) -> Option<i128> {
+    let key = BackstopDataKey::Migrated(pool.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || None,
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set a pool's deposits as migrated to the new backstop token
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `rate` - The amount of the new backstop token each original backstop token converted to
+pub fn set_pool_migration_rate(e: &Env, pool: &Address, rate: i128) {
+    let key = BackstopDataKey::Migrated(pool.clone());
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, i128>(&key, &rate);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Fetch if a pool has been decommissioned
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn get_pool_decommissioned(e: &Env, pool: &Address) -> bool {
+    let key = BackstopDataKey::Decomm(pool.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || false,
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set a pool as decommissioned
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn set_pool_decommissioned(e: &Env, pool: &Address) {
+    let key = BackstopDataKey::Decomm(pool.clone());
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, bool>(&key, &true);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Fetch the share token of a pool's backstop, if it has been deployed
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn get_share_token(e: &Env, pool: &Address) -> Option<Address> {
+    let key = BackstopDataKey::ShareToken(pool.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || None,
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the share token of a pool's backstop
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `share_token` - The contract address of the share token
+pub fn set_share_token(e: &Env, pool: &Address, share_token: &Address) {
+    let key = BackstopDataKey::ShareToken(pool.clone());
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, Address>(&key, share_token);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
 /********** Distribution / Reward Zone **********/
 
 /// Get the timestamp of when the next emission cycle begins
@@ -448,6 +713,46 @@ pub fn set_rz_emis_data(e: &Env, pool: &Address, emis_data: &RzEmissionData) {
         .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
 }
 
+/// Get the timestamp a pool qualified for the reward zone, if the pool has not yet been in the
+/// reward zone for a full distribution period
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn get_rz_qualified_time(e: &Env, pool: &Address) -> Option<u64> {
+    let key = BackstopDataKey::RzQualified(pool.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || None,
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the timestamp a pool qualified for the reward zone
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `timestamp` - The timestamp the pool was added to the reward zone
+pub fn set_rz_qualified_time(e: &Env, pool: &Address, timestamp: &u64) {
+    let key = BackstopDataKey::RzQualified(pool.clone());
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, u64>(&key, timestamp);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Remove the timestamp a pool qualified for the reward zone
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn del_rz_qualified_time(e: &Env, pool: &Address) {
+    let key = BackstopDataKey::RzQualified(pool.clone());
+    e.storage().persistent().remove(&key);
+}
+
 /// Get the pool's backstop emissions data
 ///
 /// ### Arguments
@@ -509,6 +814,132 @@ pub fn set_user_emis_data(
         .set::<BackstopDataKey, UserEmissionData>(&key, user_emis_data);
 }
 
+/********** Backstop Depositor Reward Tokens **********/
+
+/// Get the additional reward tokens emitted to the pool's backstop depositors
+///
+/// ### Arguments
+/// * `pool` - The pool
+pub fn get_reward_tokens(e: &Env, pool: &Address) -> Vec<Address> {
+    let key = BackstopDataKey::RewardTokens(pool.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || vec![e],
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the additional reward tokens emitted to the pool's backstop depositors
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `tokens` - The reward tokens
+pub fn set_reward_tokens(e: &Env, pool: &Address, tokens: &Vec<Address>) {
+    let key = BackstopDataKey::RewardTokens(pool.clone());
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, Vec<Address>>(&key, tokens);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Get the pool's backstop emissions data for a reward token
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `token` - The reward token
+pub fn get_reward_emis_data(
+    e: &Env,
+    pool: &Address,
+    token: &Address,
+) -> Option<BackstopEmissionData> {
+    let key = BackstopDataKey::REmisData(PoolRewardKey {
+        pool: pool.clone(),
+        token: token.clone(),
+    });
+    get_persistent_default(
+        e,
+        &key,
+        || None,
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the pool's backstop emissions data for a reward token
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `token` - The reward token
+/// * `emis_data` - The new emission data for the reward token
+pub fn set_reward_emis_data(
+    e: &Env,
+    pool: &Address,
+    token: &Address,
+    emis_data: &BackstopEmissionData,
+) {
+    let key = BackstopDataKey::REmisData(PoolRewardKey {
+        pool: pool.clone(),
+        token: token.clone(),
+    });
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, BackstopEmissionData>(&key, emis_data);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Get the user's backstop emissions data for a reward token
+///
+/// ### Arguments
+/// * `pool` - The pool whose backstop the user's emissions are for
+/// * `token` - The reward token
+/// * `user` - The user's address
+pub fn get_user_reward_emis_data(
+    e: &Env,
+    pool: &Address,
+    token: &Address,
+    user: &Address,
+) -> Option<UserEmissionData> {
+    let key = BackstopDataKey::UREmisData(PoolUserRewardKey {
+        pool: pool.clone(),
+        token: token.clone(),
+        user: user.clone(),
+    });
+    get_persistent_default(e, &key, || None, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set the user's backstop emissions data for a reward token
+///
+/// ### Arguments
+/// * `pool` - The pool whose backstop the user's emissions are for
+/// * `token` - The reward token
+/// * `user` - The user's address
+/// * `user_emis_data` - The new emission data for the user
+pub fn set_user_reward_emis_data(
+    e: &Env,
+    pool: &Address,
+    token: &Address,
+    user: &Address,
+    user_emis_data: &UserEmissionData,
+) {
+    let key = BackstopDataKey::UREmisData(PoolUserRewardKey {
+        pool: pool.clone(),
+        token: token.clone(),
+        user: user.clone(),
+    });
+    e.storage()
+        .persistent()
+        .set::<BackstopDataKey, UserEmissionData>(&key, user_emis_data);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
 /********** Drop Emissions **********/
 
 /// Get the current pool addresses that are in the drop list and the amount of the initial distribution they receive
@@ -563,3 +994,31 @@ pub fn set_lp_token_val(e: &Env, share_val: &(i128, i128)) {
         LEDGER_BUMP_SHARED,
     );
 }
+
+/// Get the last updated token value for the new backstop token's LP pool
+pub fn get_lp_token_v2_val(e: &Env) -> (i128, i128) {
+    e.storage().persistent().extend_ttl(
+        &Symbol::new(&e, LP_TOKEN_V2_VAL_KEY),
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    );
+    e.storage()
+        .persistent()
+        .get::<Symbol, (i128, i128)>(&Symbol::new(&e, LP_TOKEN_V2_VAL_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the token value for the new backstop token's LP pool
+///
+/// ### Arguments
+/// * `share_val` - A tuple of (blnd_per_share, usdc_per_share)
+pub fn set_lp_token_v2_val(e: &Env, share_val: &(i128, i128)) {
+    e.storage()
+        .persistent()
+        .set::<Symbol, (i128, i128)>(&Symbol::new(&e, LP_TOKEN_V2_VAL_KEY), share_val);
+    e.storage().persistent().extend_ttl(
+        &Symbol::new(&e, LP_TOKEN_V2_VAL_KEY),
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    );
+}
@@ -32,6 +32,7 @@ pub(crate) fn create_backstop(e: &Env) -> Address {
             Address::generate(e),
             Address::generate(e),
             Vec::<(Address, i128)>::new(e),
+            Address::generate(e),
         ),
     )
 }
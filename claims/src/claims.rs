@@ -0,0 +1,117 @@
+use crate::{
+    dependencies::{BackstopClient, PoolClient},
+    errors::ClaimsError,
+    events::ClaimsEvents,
+    storage::{self, ClaimsMeta},
+};
+use sep_41_token::TokenClient;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, panic_with_error, vec, Address, Env, Map, Vec,
+};
+
+/// The claims to make from a single pool
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolClaim {
+    pub pool: Address,                 // the pool to claim from
+    pub reserve_token_ids: Vec<u32>,   // the reserve token ids to claim emissions for
+    pub referral_assets: Vec<Address>, // the reserve assets to claim referral fees for
+}
+
+/// ### Claims
+///
+/// Claims pool emissions, referral fees, and backstop emissions for a user across many pools in
+/// a single call. Pool emissions and referral fees are claimed to the contract and sent to the
+/// user with a single transfer per token. Backstop emissions are deposited into the user's
+/// backstop positions by the backstop, as with a direct claim.
+#[contract]
+pub struct ClaimsContract;
+
+#[contractimpl]
+impl ClaimsContract {
+    /// Construct the claims contract
+    ///
+    /// ### Arguments
+    /// * `backstop` - The backstop module
+    /// * `blnd_token` - The token pool emissions are paid in
+    pub fn __constructor(e: Env, backstop: Address, blnd_token: Address) {
+        storage::set_meta(
+            &e,
+            &ClaimsMeta {
+                backstop,
+                blnd_token,
+            },
+        );
+    }
+
+    /// Fetch the backstop module
+    pub fn backstop(e: Env) -> Address {
+        storage::get_meta(&e).backstop
+    }
+
+    /// Claim pool emissions, referral fees, and backstop emissions for "user"
+    ///
+    /// Returns the amount of each token transferred to "user"
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user claiming
+    /// * `pools` - The claims to make from each pool
+    /// * `claim_backstop` - If backstop emissions are claimed for the user's deposits in the
+    ///                      backstops of `pools`
+    ///
+    /// ### Panics
+    /// If no pools are given, or any claim fails
+    pub fn claim_all(
+        e: Env,
+        user: Address,
+        pools: Vec<PoolClaim>,
+        claim_backstop: bool,
+    ) -> Map<Address, i128> {
+        user.require_auth();
+        storage::extend_instance(&e);
+        if pools.is_empty() {
+            panic_with_error!(&e, ClaimsError::InvalidClaim);
+        }
+
+        let meta = storage::get_meta(&e);
+        let claims = e.current_contract_address();
+        let mut to_transfer: Map<Address, i128> = Map::new(&e);
+        let mut pool_addresses: Vec<Address> = vec![&e];
+        for pool_claim in pools.iter() {
+            let pool_client = PoolClient::new(&e, &pool_claim.pool);
+            if !pool_claim.reserve_token_ids.is_empty() {
+                let emitted = pool_client.claim(&user, &pool_claim.reserve_token_ids, &claims);
+                add_amount(&mut to_transfer, &meta.blnd_token, emitted);
+            }
+            if !pool_claim.referral_assets.is_empty() {
+                let fees =
+                    pool_client.claim_referral_fees(&user, &pool_claim.referral_assets, &claims);
+                for (asset, fee) in pool_claim.referral_assets.iter().zip(fees.iter()) {
+                    add_amount(&mut to_transfer, &asset, fee);
+                }
+            }
+            pool_addresses.push_back(pool_claim.pool);
+        }
+
+        let backstop_claimed = if claim_backstop {
+            BackstopClient::new(&e, &meta.backstop).claim(&user, &pool_addresses, &user)
+        } else {
+            0
+        };
+
+        for (token, amount) in to_transfer.iter() {
+            TokenClient::new(&e, &token).transfer(&claims, &user, &amount);
+        }
+
+        ClaimsEvents::claim_all(&e, user, to_transfer.clone(), backstop_claimed);
+        to_transfer
+    }
+}
+
+/// Add a claimed amount of a token to the amounts to transfer. Zero amounts are skipped.
+fn add_amount(to_transfer: &mut Map<Address, i128>, token: &Address, amount: i128) {
+    if amount > 0 {
+        let total = to_transfer.get(token.clone()).unwrap_or(0);
+        to_transfer.set(token.clone(), total + amount);
+    }
+}
@@ -0,0 +1,4 @@
+mod backstop;
+pub use backstop::Client as BackstopClient;
+mod pool;
+pub use pool::Client as PoolClient;
@@ -0,0 +1,14 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+/// Error codes for the claims contract. Common errors are codes that match up with the built-in
+/// contracts error reporting. Claims specific errors start at 1600.
+pub enum ClaimsError {
+    // Common Errors
+    InternalError = 1,
+
+    // Claims
+    InvalidClaim = 1600,
+}
@@ -0,0 +1,25 @@
+use soroban_sdk::{Address, Env, Map, Symbol};
+
+pub struct ClaimsEvents {}
+
+impl ClaimsEvents {
+    /// Emitted when a user claims across pools and the backstop
+    ///
+    /// - topics - `["claim_all", user: Address]`
+    /// - data - `[transferred: Map<Address, i128>, backstop_claimed: i128]`
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user claiming
+    /// * `transferred` - The amount of each token transferred to the user
+    /// * `backstop_claimed` - The amount of backstop emissions claimed into the user's backstop
+    ///                        deposits
+    pub fn claim_all(
+        e: &Env,
+        user: Address,
+        transferred: Map<Address, i128>,
+        backstop_claimed: i128,
+    ) {
+        let topics = (Symbol::new(e, "claim_all"), user);
+        e.events().publish(topics, (transferred, backstop_claimed));
+    }
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+
+mod claims;
+mod dependencies;
+mod errors;
+mod events;
+mod storage;
+
+pub use claims::*;
+pub use errors::ClaimsError;
+pub use storage::ClaimsMeta;
@@ -0,0 +1,42 @@
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, Symbol};
+
+/********** Ledger Thresholds **********/
+
+const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5s a ledger
+
+const LEDGER_THRESHOLD_INSTANCE: u32 = ONE_DAY_LEDGERS * 30; // ~ 30 days
+const LEDGER_BUMP_INSTANCE: u32 = LEDGER_THRESHOLD_INSTANCE + ONE_DAY_LEDGERS; // ~ 31 days
+
+const META_KEY: &str = "Meta";
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ClaimsMeta {
+    pub backstop: Address,   // the backstop module
+    pub blnd_token: Address, // the token pool emissions are paid in
+}
+
+/// Bump the instance rent for the contract
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LEDGER_THRESHOLD_INSTANCE, LEDGER_BUMP_INSTANCE);
+}
+
+/// Fetch the claims contract metadata
+pub fn get_meta(e: &Env) -> ClaimsMeta {
+    e.storage()
+        .instance()
+        .get::<Symbol, ClaimsMeta>(&Symbol::new(e, META_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the claims contract metadata
+///
+/// ### Arguments
+/// * `meta` - The metadata of the claims contract
+pub fn set_meta(e: &Env, meta: &ClaimsMeta) {
+    e.storage()
+        .instance()
+        .set::<Symbol, ClaimsMeta>(&Symbol::new(e, META_KEY), meta)
+}
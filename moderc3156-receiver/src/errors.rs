@@ -0,0 +1,16 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+/// Error codes for the flash loan receiver contract. Common errors are codes that match up with the
+/// built-in contracts error reporting. Flash loan receiver specific errors start at 1700.
+pub enum FlashLoanReceiverError {
+    // Common Errors
+    InternalError = 1,
+
+    // Flash Loan Receiver
+    InvalidAmount = 1700,
+    InsufficientFunds = 1701,
+    RepaymentNotApproved = 1702,
+}
@@ -0,0 +1,12 @@
+#![no_std]
+
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+
+mod errors;
+mod receiver;
+mod storage;
+pub mod testutils;
+
+pub use errors::FlashLoanReceiverError;
+pub use receiver::*;
@@ -0,0 +1,198 @@
+use crate::{errors::FlashLoanReceiverError, storage};
+use sep_41_token::TokenClient;
+use soroban_sdk::{contract, contractimpl, panic_with_error, Address, Env};
+
+/// Validate a flash loan sent to the receiver by the pool. Must be called before the receiver
+/// operates on the flash loaned funds.
+///
+/// ### Arguments
+/// * `caller` - The address of the user that took the flash loan
+/// * `token` - The flash loaned token
+/// * `amount` - The flash loaned amount
+/// * `fee` - The flash loan fee the pool charges the caller
+///
+/// ### Panics
+/// If the pool did not invoke the receiver, the caller did not authorize the flash loan, the
+/// amount or fee is invalid, or the receiver does not hold the flash loaned amount
+pub fn validate_flash_loan(e: &Env, caller: &Address, token: &Address, amount: i128, fee: i128) {
+    // only the pool can invoke the receiver, otherwise anyone could move the receiver's funds
+    storage::get_pool(e).require_auth();
+    caller.require_auth();
+
+    if amount <= 0 || fee < 0 {
+        panic_with_error!(e, FlashLoanReceiverError::InvalidAmount);
+    }
+    let balance = TokenClient::new(e, token).balance(&e.current_contract_address());
+    if balance < amount {
+        panic_with_error!(e, FlashLoanReceiverError::InsufficientFunds);
+    }
+}
+
+/// Return the flash loaned amount to the caller. The pool pulls any repayment and the fee from
+/// the caller, not the receiver.
+///
+/// ### Arguments
+/// * `caller` - The address of the user that took the flash loan
+/// * `token` - The flash loaned token
+/// * `amount` - The amount to return to the caller
+pub fn return_to_caller(e: &Env, caller: &Address, token: &Address, amount: i128) {
+    TokenClient::new(e, token).transfer(&e.current_contract_address(), caller, &amount);
+}
+
+/// Verify the pool can pull `repayment` of the flash loaned token from the caller once the
+/// receiver returns. Catches missing approvals in the receiver instead of after the flash loan.
+///
+/// ### Arguments
+/// * `caller` - The address of the user that took the flash loan
+/// * `token` - The flash loaned token
+/// * `repayment` - The amount the pool will pull from the caller, at least the fee
+///
+/// ### Panics
+/// If the caller has not approved the pool for `repayment` or does not hold it
+pub fn require_repayment_approved(e: &Env, caller: &Address, token: &Address, repayment: i128) {
+    let token_client = TokenClient::new(e, token);
+    if token_client.allowance(caller, &storage::get_pool(e)) < repayment {
+        panic_with_error!(e, FlashLoanReceiverError::RepaymentNotApproved);
+    }
+    if token_client.balance(caller) < repayment {
+        panic_with_error!(e, FlashLoanReceiverError::InsufficientFunds);
+    }
+}
+
+/// ### FlashLoanReceiver
+///
+/// A reference flash loan receiver for the pool's `flash_loan` entrypoint. It validates the flash
+/// loan, returns the flash loaned funds to the caller and verifies the pool can pull the fee.
+/// Integrators perform their operations between validating and returning the funds.
+#[contract]
+pub struct FlashLoanReceiver;
+
+#[contractimpl]
+impl FlashLoanReceiver {
+    /// Construct the flash loan receiver
+    ///
+    /// ### Arguments
+    /// * `pool` - The pool the receiver accepts flash loans from
+    pub fn __constructor(e: Env, pool: Address) {
+        storage::set_pool(&e, &pool);
+    }
+
+    /// Fetch the pool the receiver accepts flash loans from
+    pub fn pool(e: Env) -> Address {
+        storage::get_pool(&e)
+    }
+
+    /// Execute an operation on a flash loan. Called by the pool after the flash loaned amount
+    /// is transferred to the receiver.
+    ///
+    /// ### Arguments
+    /// * `caller` - The address of the user that took the flash loan
+    /// * `token` - The flash loaned token
+    /// * `amount` - The flash loaned amount
+    /// * `fee` - The flash loan fee the pool charges the caller
+    ///
+    /// ### Panics
+    /// If the flash loan is invalid or the pool can't pull the fee from the caller
+    pub fn exec_op(e: Env, caller: Address, token: Address, amount: i128, fee: i128) {
+        storage::extend_instance(&e);
+        validate_flash_loan(&e, &caller, &token, amount, fee);
+
+        // operations on the flash loaned funds go here
+
+        return_to_caller(&e, &caller, &token, amount);
+        require_repayment_approved(&e, &caller, &token, fee);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutils::{create_flash_loan_receiver, exec_op_as_pool};
+
+    use super::*;
+    use soroban_sdk::{testutils::Address as _, token::StellarAssetClient};
+
+    #[test]
+    fn test_exec_op() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = Address::generate(&e);
+        let token = e
+            .register_stellar_asset_contract_v2(bombadil)
+            .address();
+        let token_admin = StellarAssetClient::new(&e, &token);
+        let token_client = TokenClient::new(&e, &token);
+
+        let (receiver, receiver_client) = create_flash_loan_receiver(&e, &pool);
+        assert_eq!(receiver_client.pool(), pool);
+
+        token_admin.mint(&receiver, &1_000_0000000);
+        token_admin.mint(&samwise, &1_0000000);
+        let approval_ledger = e.ledger().sequence() + 100;
+        token_client.approve(&samwise, &pool, &1_0000000, &approval_ledger);
+
+        exec_op_as_pool(&e, &pool, &receiver, &samwise, &token, 1_000_0000000, 1_0000000);
+
+        assert_eq!(token_client.balance(&receiver), 0);
+        assert_eq!(token_client.balance(&samwise), 1_001_0000000);
+    }
+
+    #[test]
+    fn test_exec_op_not_from_pool() {
+        let e = Env::default();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = Address::generate(&e);
+        let token = e
+            .register_stellar_asset_contract_v2(bombadil)
+            .address();
+
+        let (_, receiver_client) = create_flash_loan_receiver(&e, &pool);
+
+        let result = receiver_client.try_exec_op(&samwise, &token, &1_000_0000000, &0);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1701)")]
+    fn test_exec_op_funds_not_received() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = Address::generate(&e);
+        let token = e
+            .register_stellar_asset_contract_v2(bombadil)
+            .address();
+
+        let (receiver, _) = create_flash_loan_receiver(&e, &pool);
+        StellarAssetClient::new(&e, &token).mint(&receiver, &999_0000000);
+
+        exec_op_as_pool(&e, &pool, &receiver, &samwise, &token, 1_000_0000000, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1702)")]
+    fn test_exec_op_fee_not_approved() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = Address::generate(&e);
+        let token = e
+            .register_stellar_asset_contract_v2(bombadil)
+            .address();
+        let token_admin = StellarAssetClient::new(&e, &token);
+
+        let (receiver, _) = create_flash_loan_receiver(&e, &pool);
+        token_admin.mint(&receiver, &1_000_0000000);
+        token_admin.mint(&samwise, &1_0000000);
+
+        exec_op_as_pool(&e, &pool, &receiver, &samwise, &token, 1_000_0000000, 1_0000000);
+    }
+}
@@ -0,0 +1,45 @@
+#![cfg(any(test, feature = "testutils"))]
+
+use crate::{FlashLoanReceiver, FlashLoanReceiverClient};
+use soroban_sdk::{vec, Address, Env, IntoVal, Symbol};
+
+/// Deploy the reference flash loan receiver for `pool`
+pub fn create_flash_loan_receiver<'a>(
+    e: &Env,
+    pool: &Address,
+) -> (Address, FlashLoanReceiverClient<'a>) {
+    let contract_id = e.register(FlashLoanReceiver {}, (pool.clone(),));
+    (
+        contract_id.clone(),
+        FlashLoanReceiverClient::new(e, &contract_id),
+    )
+}
+
+/// Call `exec_op` on any flash loan receiver with `pool` as the invoker, as the pool does during
+/// a flash loan. The flash loaned amount must be sent to the receiver beforehand.
+///
+/// ### Panics
+/// If the receiver panics
+pub fn exec_op_as_pool(
+    e: &Env,
+    pool: &Address,
+    receiver: &Address,
+    caller: &Address,
+    token: &Address,
+    amount: i128,
+    fee: i128,
+) {
+    e.as_contract(pool, || {
+        e.invoke_contract::<()>(
+            receiver,
+            &Symbol::new(e, "exec_op"),
+            vec![
+                e,
+                caller.into_val(e),
+                token.into_val(e),
+                amount.into_val(e),
+                fee.into_val(e),
+            ],
+        );
+    });
+}
@@ -0,0 +1,8 @@
+use soroban_sdk::{contractclient, Address, Env, Vec};
+
+/// The reward zone exposed by the backstop pools are deployed with
+#[contractclient(name = "BackstopClient")]
+pub trait Backstop {
+    /// Fetch the pools in the reward zone
+    fn reward_zone(e: Env) -> Vec<Address>;
+}
@@ -0,0 +1,5 @@
+mod backstop;
+pub use backstop::BackstopClient;
+
+mod pool;
+pub use pool::{InterestRateConfig, PoolClient, PoolConfig, ReserveConfig, ReserveEmissionMetadata};
@@ -0,0 +1,92 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env, Vec};
+
+/// The configuration of a pool reserve. Mirrors `ReserveConfig` of the pool contract.
+#[derive(Clone)]
+#[contracttype]
+pub struct ReserveConfig {
+    pub index: u32,
+    pub decimals: u32,
+    pub c_factor: u32,
+    pub l_factor: u32,
+    pub util: u32,
+    pub max_util: u32,
+    pub r_base: u32,
+    pub r_one: u32,
+    pub r_two: u32,
+    pub r_three: u32,
+    pub reactivity: u32,
+    pub collateral_cap: i128,
+    pub debt_cap: i128,
+    pub enabled: bool,
+    pub min_supply_ledgers: u32,
+    pub isolated: bool,
+    pub debt_ceiling: i128,
+    pub category: u32,
+}
+
+/// A custom interest rate curve for a reserve. Mirrors `InterestRateConfig` of the pool contract.
+#[derive(Clone)]
+#[contracttype]
+pub struct InterestRateConfig {
+    pub breakpoints: Vec<u32>,
+    pub slopes: Vec<u32>,
+}
+
+/// The emissions share of a reserve token. Mirrors `ReserveEmissionMetadata` of the pool contract.
+#[derive(Clone)]
+#[contracttype]
+pub struct ReserveEmissionMetadata {
+    pub res_index: u32,
+    pub res_type: u32,
+    pub share: u64,
+}
+
+/// The configuration of a pool. Mirrors `PoolConfig` of the pool contract.
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolConfig {
+    pub oracle: Address,
+    pub bstop_rate: u32,
+    pub status: u32,
+    pub max_positions: u32,
+    pub flash_loan_fee: u32,
+    pub hf_strategy: u32,
+    pub fallback_oracle: Option<Address>,
+    pub min_health_factor: u32,
+    pub max_account_leverage: u32,
+}
+
+/// The subset of the pool interface used by the factory to initialize and index its pools
+#[contractclient(name = "PoolClient")]
+pub trait Pool {
+    /// (Admin only) Queue setting data for a reserve in the pool
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset to add as a reserve
+    /// * `metadata` - The ReserveConfig for the reserve
+    /// * `ir_config` - The custom interest rate curve for the reserve, if any
+    fn queue_set_reserve(
+        e: Env,
+        asset: Address,
+        metadata: ReserveConfig,
+        ir_config: Option<InterestRateConfig>,
+    );
+
+    /// Execute a queued reserve initialization for the pool
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset to add as a reserve
+    fn set_reserve(e: Env, asset: Address) -> u32;
+
+    /// (Admin only) Set the emission configuration for the pool
+    ///
+    /// ### Arguments
+    /// * `res_emission_metadata` - A vector of ReserveEmissionMetadata to update metadata to
+    fn set_emissions_config(e: Env, res_emission_metadata: Vec<ReserveEmissionMetadata>);
+
+    /// Fetch the pool configuration
+    fn get_config(e: Env) -> PoolConfig;
+
+    /// Fetch the underlying assets of the pool's reserves, ordered by reserve index
+    fn get_reserve_list(e: Env) -> Vec<Address>;
+}
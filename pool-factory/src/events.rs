@@ -15,4 +15,31 @@ impl PoolFactoryEvents {
         let topics = (Symbol::new(e, "deploy"),);
         e.events().publish(topics, pool_address);
     }
+
+    /// Emitted when a pool deployed by the factory is initialized with its reserves and emissions
+    ///
+    /// - topics - `["init", pool_address: Address]`
+    /// - data - `reserve_count: u32`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `reserve_count` - The number of reserves added to the pool
+    pub fn init(e: &Env, pool_address: Address, reserve_count: u32) {
+        let topics = (Symbol::new(e, "init"), pool_address);
+        e.events().publish(topics, reserve_count);
+    }
+
+    /// Emitted when the registry entry of a pool is refreshed
+    ///
+    /// - topics - `["refresh_pool", pool_address: Address]`
+    /// - data - `[status: u32, verified: bool]`
+    ///
+    /// ### Arguments
+    /// * `pool_address` - The address of the pool
+    /// * `status` - The status of the pool
+    /// * `verified` - Whether the pool is in the backstop's reward zone
+    pub fn refresh_pool(e: &Env, pool_address: Address, status: u32, verified: bool) {
+        let topics = (Symbol::new(e, "refresh_pool"), pool_address);
+        e.events().publish(topics, (status, verified));
+    }
 }
@@ -3,12 +3,14 @@
 #[cfg(any(test, feature = "testutils"))]
 extern crate std;
 
+mod dependencies;
 mod errors;
 mod events;
 mod pool_factory;
 mod storage;
 mod test;
 
+pub use dependencies::{InterestRateConfig, ReserveConfig, ReserveEmissionMetadata};
 pub use errors::PoolFactoryError;
 pub use pool_factory::*;
-pub use storage::{PoolFactoryDataKey, PoolInitMeta};
+pub use storage::{PoolFactoryDataKey, PoolInitMeta, PoolMetadata, PoolRegistryEntry};
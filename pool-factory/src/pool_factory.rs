@@ -1,14 +1,27 @@
 use crate::{
+    dependencies::{
+        BackstopClient, InterestRateConfig, PoolClient, ReserveConfig, ReserveEmissionMetadata,
+    },
     errors::PoolFactoryError,
     events::PoolFactoryEvents,
-    storage::{self, PoolInitMeta},
+    storage::{self, PoolInitMeta, PoolMetadata, PoolRegistryEntry},
 };
 use soroban_sdk::{
-    contract, contractclient, contractimpl, panic_with_error, Address, Bytes, BytesN, Env, IntoVal,
-    String,
+    contract, contractclient, contractimpl, contracttype, panic_with_error,
+    unwrap::UnwrapOptimized, Address, Bytes, BytesN, Env, IntoVal, String, Vec,
 };
 
 const SCALAR_7: u32 = 1_0000000;
+const MAX_POOL_PAGE: u32 = 25;
+
+/// A reserve to add to a pool when it is deployed
+#[derive(Clone)]
+#[contracttype]
+pub struct ReserveInit {
+    pub asset: Address,                        // the underlying asset of the reserve
+    pub config: ReserveConfig,                 // the configuration of the reserve
+    pub ir_config: Option<InterestRateConfig>, // the custom interest rate curve, if any
+}
 
 #[contract]
 pub struct PoolFactoryContract;
@@ -24,6 +37,8 @@ pub trait PoolFactory {
     /// * `oracle` - The oracle address for the pool
     /// * `backstop_take_rate` - The backstop take rate for the pool (7 decimals)
     /// * `max_positions` - The maximum user positions supported by the pool
+    /// * `hf_strategy` - The strategy used to calculate user health factors (0 = standard,
+    ///                   1 = e-mode, 2 = portfolio margin)
     fn deploy(
         e: Env,
         admin: Address,
@@ -32,8 +47,77 @@ pub trait PoolFactory {
         oracle: Address,
         backstop_take_rate: u32,
         max_positions: u32,
+        hf_strategy: u32,
     ) -> Address;
 
+    /// Deploys and initializes a lending pool, then adds its initial reserves and sets its
+    /// emissions config in the same call. The pool is left in the setup status.
+    ///
+    /// The admin must authorize the reserve and emissions configuration calls made against the
+    /// new pool, whose address is determined by the salt and admin.
+    ///
+    /// The pool is linked to the factory's backstop when it is constructed, but no backstop
+    /// deposit is made. The backstop verifies a pool without deposits by calling `is_pool` on
+    /// this factory, and contracts can't be re-entered, so the first deposit must be made to the
+    /// backstop in a separate call once the pool is deployed.
+    ///
+    /// ### Arguments
+    /// * `admin` - The admin address for the pool
+    /// * `name` - The name of the pool
+    /// * `salt` - The salt for the pool address
+    /// * `oracle` - The oracle address for the pool
+    /// * `backstop_take_rate` - The backstop take rate for the pool (7 decimals)
+    /// * `max_positions` - The maximum user positions supported by the pool
+    /// * `hf_strategy` - The strategy used to calculate user health factors (0 = standard,
+    ///                   1 = e-mode, 2 = portfolio margin)
+    /// * `reserves` - The reserves to add to the pool, in index order
+    /// * `emissions` - The emissions share of each reserve token, or empty for no emissions
+    fn deploy_and_init(
+        e: Env,
+        admin: Address,
+        name: String,
+        salt: BytesN<32>,
+        oracle: Address,
+        backstop_take_rate: u32,
+        max_positions: u32,
+        hf_strategy: u32,
+        reserves: Vec<ReserveInit>,
+        emissions: Vec<ReserveEmissionMetadata>,
+    ) -> Address;
+
+    /// Fetch the metadata a pool was deployed with
+    ///
+    /// ### Arguments
+    /// * `pool_id` - The contract address of the pool
+    ///
+    /// ### Panics
+    /// If the pool was not deployed by the factory
+    fn get_pool_metadata(e: Env, pool_id: Address) -> PoolMetadata;
+
+    /// Fetch the number of pools deployed by the factory
+    fn get_pool_count(e: Env) -> u32;
+
+    /// Fetch a page of the pool registry, ordered by deployment. Each entry includes the pool's
+    /// oracle, reserve list, status, and verification as of its last refresh.
+    ///
+    /// ### Arguments
+    /// * `start` - The index of the first pool to fetch
+    /// * `limit` - The maximum number of pools to fetch (at most 25)
+    fn get_pools(e: Env, start: u32, limit: u32) -> Vec<PoolRegistryEntry>;
+
+    /// Refresh the registry entry of a pool with the pool's current oracle, reserve list, and
+    /// status. The pool is verified if it is in the backstop's reward zone. Anyone can refresh
+    /// an entry.
+    ///
+    /// Returns the refreshed entry
+    ///
+    /// ### Arguments
+    /// * `index` - The index of the pool, in deployment order
+    ///
+    /// ### Panics
+    /// If no pool was deployed at the index
+    fn refresh_pool(e: Env, index: u32) -> PoolRegistryEntry;
+
     /// Checks if contract address was deployed by the factory
     ///
     /// Returns true if pool was deployed by factory and false otherwise
@@ -64,48 +148,198 @@ impl PoolFactory for PoolFactoryContract {
         oracle: Address,
         backstop_take_rate: u32,
         max_positions: u32,
+        hf_strategy: u32,
     ) -> Address {
         admin.require_auth();
         storage::extend_instance(&e);
-        let pool_init_meta = storage::get_pool_init_meta(&e);
 
-        // verify backstop take rate is within [0,1) with 7 decimals
-        if backstop_take_rate >= SCALAR_7 {
-            panic_with_error!(&e, PoolFactoryError::InvalidPoolInitArgs);
-        }
+        deploy_pool(
+            &e,
+            admin,
+            name,
+            salt,
+            oracle,
+            backstop_take_rate,
+            max_positions,
+            hf_strategy,
+        )
+    }
 
-        // verify max positions is at least 2
-        if max_positions < 2 {
-            panic_with_error!(&e, PoolFactoryError::InvalidPoolInitArgs);
-        }
+    fn deploy_and_init(
+        e: Env,
+        admin: Address,
+        name: String,
+        salt: BytesN<32>,
+        oracle: Address,
+        backstop_take_rate: u32,
+        max_positions: u32,
+        hf_strategy: u32,
+        reserves: Vec<ReserveInit>,
+        emissions: Vec<ReserveEmissionMetadata>,
+    ) -> Address {
+        admin.require_auth();
+        storage::extend_instance(&e);
 
-        let mut as_u8s: [u8; 56] = [0; 56];
-        admin.to_string().copy_into_slice(&mut as_u8s);
-        let mut salt_as_bytes: Bytes = salt.into_val(&e);
-        salt_as_bytes.extend_from_array(&as_u8s);
-        let new_salt = e.crypto().keccak256(&salt_as_bytes);
-
-        let pool_address = e.deployer().with_current_contract(new_salt).deploy_v2(
-            pool_init_meta.pool_hash,
-            (
-                admin,
-                name,
-                oracle,
-                backstop_take_rate,
-                max_positions,
-                pool_init_meta.backstop,
-                pool_init_meta.blnd_id,
-            ),
+        let pool_address = deploy_pool(
+            &e,
+            admin,
+            name,
+            salt,
+            oracle,
+            backstop_take_rate,
+            max_positions,
+            hf_strategy,
         );
 
-        storage::set_deployed(&e, &pool_address);
+        // the pool is in the setup status, so reserves are set without a timelock
+        let pool_client = PoolClient::new(&e, &pool_address);
+        let index = storage::get_pool_count(&e) - 1;
+        let mut entry = storage::get_pool_entry(&e, index).unwrap_optimized();
+        for reserve in reserves.iter() {
+            pool_client.queue_set_reserve(&reserve.asset, &reserve.config, &reserve.ir_config);
+            pool_client.set_reserve(&reserve.asset);
+            entry.reserves.push_back(reserve.asset);
+        }
+        storage::set_pool_entry(&e, index, &entry);
+        if !emissions.is_empty() {
+            pool_client.set_emissions_config(&emissions);
+        }
 
-        PoolFactoryEvents::deploy(&e, pool_address.clone());
+        PoolFactoryEvents::init(&e, pool_address.clone(), reserves.len());
         pool_address
     }
 
+    fn get_pool_metadata(e: Env, pool_id: Address) -> PoolMetadata {
+        storage::extend_instance(&e);
+        match storage::get_pool_metadata(&e, &pool_id) {
+            Some(metadata) => metadata,
+            None => panic_with_error!(&e, PoolFactoryError::PoolNotFound),
+        }
+    }
+
+    fn get_pool_count(e: Env) -> u32 {
+        storage::extend_instance(&e);
+        storage::get_pool_count(&e)
+    }
+
+    fn get_pools(e: Env, start: u32, limit: u32) -> Vec<PoolRegistryEntry> {
+        storage::extend_instance(&e);
+        let end = start
+            .saturating_add(limit.min(MAX_POOL_PAGE))
+            .min(storage::get_pool_count(&e));
+        let mut entries = Vec::new(&e);
+        for index in start..end {
+            if let Some(entry) = storage::get_pool_entry(&e, index) {
+                entries.push_back(entry);
+            }
+        }
+        entries
+    }
+
+    fn refresh_pool(e: Env, index: u32) -> PoolRegistryEntry {
+        storage::extend_instance(&e);
+        let mut entry = match storage::get_pool_entry(&e, index) {
+            Some(entry) => entry,
+            None => panic_with_error!(&e, PoolFactoryError::PoolNotFound),
+        };
+
+        let pool_client = PoolClient::new(&e, &entry.pool);
+        let config = pool_client.get_config();
+        entry.oracle = config.oracle;
+        entry.reserves = pool_client.get_reserve_list();
+        entry.status = config.status;
+        entry.verified = BackstopClient::new(&e, &entry.backstop)
+            .reward_zone()
+            .contains(&entry.pool);
+        storage::set_pool_entry(&e, index, &entry);
+
+        PoolFactoryEvents::refresh_pool(&e, entry.pool.clone(), entry.status, entry.verified);
+        entry
+    }
+
     fn is_pool(e: Env, pool_address: Address) -> bool {
         storage::extend_instance(&e);
         storage::is_deployed(&e, &pool_address)
     }
 }
+
+/// Validate the pool init args and deploy the pool, registering it and its metadata
+#[allow(clippy::too_many_arguments)]
+fn deploy_pool(
+    e: &Env,
+    admin: Address,
+    name: String,
+    salt: BytesN<32>,
+    oracle: Address,
+    backstop_take_rate: u32,
+    max_positions: u32,
+    hf_strategy: u32,
+) -> Address {
+    let pool_init_meta = storage::get_pool_init_meta(e);
+
+    // verify backstop take rate is within [0,1) with 7 decimals
+    if backstop_take_rate >= SCALAR_7 {
+        panic_with_error!(e, PoolFactoryError::InvalidPoolInitArgs);
+    }
+
+    // verify max positions is at least 2
+    if max_positions < 2 {
+        panic_with_error!(e, PoolFactoryError::InvalidPoolInitArgs);
+    }
+
+    // verify the health strategy is standard, e-mode, or portfolio margin
+    if hf_strategy > 2 {
+        panic_with_error!(e, PoolFactoryError::InvalidPoolInitArgs);
+    }
+
+    let mut as_u8s: [u8; 56] = [0; 56];
+    admin.to_string().copy_into_slice(&mut as_u8s);
+    let mut salt_as_bytes: Bytes = salt.into_val(e);
+    salt_as_bytes.extend_from_array(&as_u8s);
+    let new_salt = e.crypto().keccak256(&salt_as_bytes);
+
+    let metadata = PoolMetadata {
+        admin: admin.clone(),
+        name: name.clone(),
+        oracle: oracle.clone(),
+        backstop_take_rate,
+        max_positions,
+        hf_strategy,
+    };
+    let pool_address = e.deployer().with_current_contract(new_salt).deploy_v2(
+        pool_init_meta.pool_hash,
+        (
+            admin,
+            name,
+            oracle,
+            backstop_take_rate,
+            max_positions,
+            hf_strategy,
+            pool_init_meta.backstop.clone(),
+            pool_init_meta.blnd_id,
+        ),
+    );
+
+    storage::set_deployed(e, &pool_address);
+    storage::set_pool_metadata(e, &pool_address, &metadata);
+    let pool_count = storage::get_pool_count(e);
+    storage::set_pool_entry(
+        e,
+        pool_count,
+        &PoolRegistryEntry {
+            pool: pool_address.clone(),
+            index: pool_count,
+            name: metadata.name,
+            oracle: metadata.oracle,
+            backstop: pool_init_meta.backstop,
+            reserves: Vec::new(e),
+            // pools are deployed in the setup status
+            status: 6,
+            verified: false,
+        },
+    );
+    storage::set_pool_count(e, pool_count + 1);
+
+    PoolFactoryEvents::deploy(e, pool_address.clone());
+    pool_address
+}
@@ -1,4 +1,6 @@
-use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, BytesN, Env, Symbol};
+use soroban_sdk::{
+    contracttype, unwrap::UnwrapOptimized, Address, BytesN, Env, String, Symbol, Vec,
+};
 
 /********** Ledger Thresholds **********/
 
@@ -14,6 +16,8 @@ const LEDGER_BUMP_USER: u32 = LEDGER_THRESHOLD_USER + 20 * ONE_DAY_LEDGERS; // ~
 #[contracttype]
 pub enum PoolFactoryDataKey {
     Contracts(Address),
+    Metadata(Address),
+    PoolIndex(u32),
 }
 
 #[derive(Clone)]
@@ -24,6 +28,33 @@ pub struct PoolInitMeta {
     pub blnd_id: Address,
 }
 
+/// The metadata a pool was deployed with
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolMetadata {
+    pub admin: Address,          // the admin the pool was deployed with
+    pub name: String,            // the name of the pool
+    pub oracle: Address,         // the oracle the pool was deployed with
+    pub backstop_take_rate: u32, // the backstop take rate the pool was deployed with (7 decimals)
+    pub max_positions: u32,      // the maximum user positions the pool was deployed with
+    pub hf_strategy: u32,        // the health factor strategy the pool was deployed with
+}
+
+/// A pool deployed by the factory, as listed by the pool registry. The oracle, reserves, status,
+/// and verification are as of the last time the entry was refreshed.
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolRegistryEntry {
+    pub pool: Address,          // the address of the pool
+    pub index: u32,             // the index of the pool, in deployment order
+    pub name: String,           // the name of the pool
+    pub oracle: Address,        // the oracle of the pool
+    pub backstop: Address,      // the backstop the pool was deployed with
+    pub reserves: Vec<Address>, // the underlying assets of the pool's reserves, by reserve index
+    pub status: u32,            // the status of the pool, 6 if the pool is still in setup
+    pub verified: bool,         // whether the pool is in the backstop's reward zone
+}
+
 /// Bump the instance rent for the contract
 pub fn extend_instance(e: &Env) {
     e.storage()
@@ -82,3 +113,88 @@ pub fn set_deployed(e: &Env, contract_id: &Address) {
         .persistent()
         .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
+
+/// Fetch the metadata a pool was deployed with, or None if it was not deployed by the factory
+///
+/// ### Arguments
+/// * `contract_id` - The contract_id of the pool
+pub fn get_pool_metadata(e: &Env, contract_id: &Address) -> Option<PoolMetadata> {
+    let key = PoolFactoryDataKey::Metadata(contract_id.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolFactoryDataKey, PoolMetadata>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
+}
+
+/// Set the metadata a pool was deployed with
+///
+/// ### Arguments
+/// * `contract_id` - The contract_id of the pool
+/// * `metadata` - The metadata the pool was deployed with
+pub fn set_pool_metadata(e: &Env, contract_id: &Address, metadata: &PoolMetadata) {
+    let key = PoolFactoryDataKey::Metadata(contract_id.clone());
+    e.storage()
+        .persistent()
+        .set::<PoolFactoryDataKey, PoolMetadata>(&key, metadata);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Fetch the number of pools deployed by the factory
+pub fn get_pool_count(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get::<Symbol, u32>(&Symbol::new(e, "PoolCount"))
+        .unwrap_or(0)
+}
+
+/// Set the number of pools deployed by the factory
+///
+/// ### Arguments
+/// * `count` - The number of pools deployed
+pub fn set_pool_count(e: &Env, count: u32) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, "PoolCount"), &count);
+}
+
+/// Fetch the registry entry of the pool deployed at an index, or None if no pool was deployed
+/// at the index
+///
+/// ### Arguments
+/// * `index` - The index of the pool, in deployment order
+pub fn get_pool_entry(e: &Env, index: u32) -> Option<PoolRegistryEntry> {
+    let key = PoolFactoryDataKey::PoolIndex(index);
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolFactoryDataKey, PoolRegistryEntry>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
+}
+
+/// Set the registry entry of the pool deployed at an index
+///
+/// ### Arguments
+/// * `index` - The index of the pool, in deployment order
+/// * `entry` - The registry entry of the pool
+pub fn set_pool_entry(e: &Env, index: u32, entry: &PoolRegistryEntry) {
+    let key = PoolFactoryDataKey::PoolIndex(index);
+    e.storage()
+        .persistent()
+        .set::<PoolFactoryDataKey, PoolRegistryEntry>(&key, entry);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
@@ -1,16 +1,39 @@
 #![cfg(test)]
 
 use soroban_sdk::{
+    contract, contractimpl,
     testutils::{Address as _, BytesN as _, Events},
-    vec, Address, BytesN, Env, IntoVal, String, Symbol,
+    vec, Address, BytesN, Env, IntoVal, String, Symbol, Vec,
 };
 
-use crate::{PoolFactoryClient, PoolFactoryContract, PoolInitMeta};
+use crate::{
+    PoolFactoryClient, PoolFactoryContract, PoolInitMeta, ReserveConfig, ReserveEmissionMetadata,
+    ReserveInit,
+};
 
 mod pool {
     soroban_sdk::contractimport!(file = "../target/wasm32-unknown-unknown/optimized/pool.wasm");
 }
 
+#[contract]
+struct MockBackstop;
+
+#[contractimpl]
+impl MockBackstop {
+    pub fn reward_zone(e: Env) -> Vec<Address> {
+        e.storage()
+            .instance()
+            .get(&Symbol::new(&e, "RZ"))
+            .unwrap_or(vec![&e])
+    }
+
+    pub fn set_reward_zone(e: Env, reward_zone: Vec<Address>) {
+        e.storage()
+            .instance()
+            .set(&Symbol::new(&e, "RZ"), &reward_zone);
+    }
+}
+
 #[test]
 fn test_pool_factory() {
     let e = Env::default();
@@ -46,6 +69,7 @@ fn test_pool_factory() {
         &oracle,
         &backstop_rate,
         &max_positions,
+        &0,
     );
 
     let event = vec![&e, e.events().all().last_unchecked()];
@@ -69,6 +93,7 @@ fn test_pool_factory() {
         &oracle,
         &backstop_rate,
         &max_positions,
+        &0,
     );
 
     e.as_contract(&deployed_pool_address_1, || {
@@ -95,7 +120,12 @@ fn test_pool_factory() {
                 oracle: oracle,
                 bstop_rate: backstop_rate,
                 status: 6,
-                max_positions: 6
+                max_positions: 6,
+                flash_loan_fee: 0,
+                hf_strategy: 0,
+                fallback_oracle: None,
+                min_health_factor: 1_0000100,
+                max_account_leverage: 0
             }
         );
         assert_eq!(
@@ -147,6 +177,7 @@ fn test_pool_factory_invalid_pool_init_args_backstop_rate() {
         &oracle,
         &backstop_rate,
         &max_positions,
+        &0,
     );
 }
 
@@ -184,6 +215,7 @@ fn test_pool_factory_invalid_pool_init_args_max_positions() {
         &oracle,
         &backstop_rate,
         &max_positions,
+        &0,
     );
 }
 
@@ -225,6 +257,7 @@ fn test_pool_factory_frontrun_protection() {
         &oracle,
         &backstop_rate,
         &max_positions,
+        &0,
     );
 
     let deployed_pool_address_bombadil = pool_factory_client.deploy(
@@ -234,9 +267,275 @@ fn test_pool_factory_frontrun_protection() {
         &oracle,
         &backstop_rate,
         &max_positions,
+        &0,
     );
 
     assert!(deployed_pool_address_sauron != deployed_pool_address_bombadil);
     assert!(pool_factory_client.is_pool(&deployed_pool_address_sauron));
     assert!(pool_factory_client.is_pool(&deployed_pool_address_bombadil));
 }
+
+#[test]
+fn test_pool_factory_deploy_and_init() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths_allowing_non_root_auth();
+
+    let wasm_hash = e.deployer().upload_contract_wasm(pool::WASM);
+
+    let bombadil = Address::generate(&e);
+
+    let oracle = Address::generate(&e);
+    let backstop_id = Address::generate(&e);
+    let backstop_rate: u32 = 0_1000000;
+    let max_positions: u32 = 6;
+    let blnd_id = Address::generate(&e);
+
+    let pool_init_meta = PoolInitMeta {
+        backstop: backstop_id.clone(),
+        pool_hash: wasm_hash.clone(),
+        blnd_id: blnd_id.clone(),
+    };
+    let pool_factory_address = e.register(PoolFactoryContract {}, (pool_init_meta,));
+    let pool_factory_client = PoolFactoryClient::new(&e, &pool_factory_address);
+
+    let name = String::from_str(&e, "pool1");
+    let salt = BytesN::<32>::random(&e);
+    let asset_0 = Address::generate(&e);
+    let asset_1 = Address::generate(&e);
+    let reserve_config = ReserveConfig {
+        index: 0,
+        decimals: 7,
+        c_factor: 0_7500000,
+        l_factor: 0_7500000,
+        util: 0_7500000,
+        max_util: 0_9500000,
+        r_base: 0_0100000,
+        r_one: 0_0500000,
+        r_two: 0_5000000,
+        r_three: 1_5000000,
+        reactivity: 0_0000020,
+        collateral_cap: 1000000000000000000,
+        debt_cap: 1000000000000000000,
+        enabled: true,
+        min_supply_ledgers: 0,
+        isolated: false,
+        debt_ceiling: 0,
+        category: 0,
+    };
+    let reserves = vec![
+        &e,
+        ReserveInit {
+            asset: asset_0.clone(),
+            config: reserve_config.clone(),
+            ir_config: None,
+        },
+        ReserveInit {
+            asset: asset_1.clone(),
+            config: reserve_config,
+            ir_config: None,
+        },
+    ];
+    let emissions = vec![
+        &e,
+        ReserveEmissionMetadata {
+            res_index: 1,
+            res_type: 0,
+            share: 1_0000000,
+        },
+    ];
+
+    let pool_address = pool_factory_client.deploy_and_init(
+        &bombadil,
+        &name,
+        &salt,
+        &oracle,
+        &backstop_rate,
+        &max_positions,
+        &0,
+        &reserves,
+        &emissions,
+    );
+
+    let event = vec![&e, e.events().all().last_unchecked()];
+    assert_eq!(
+        event,
+        vec![
+            &e,
+            (
+                pool_factory_address.clone(),
+                (Symbol::new(&e, "init"), pool_address.clone()).into_val(&e),
+                2u32.into_val(&e)
+            )
+        ]
+    );
+
+    let pool_client = pool::Client::new(&e, &pool_address);
+    assert_eq!(pool_client.get_config().status, 6);
+    assert_eq!(pool_client.get_reserve(&asset_0).index, 0);
+    assert_eq!(pool_client.get_reserve(&asset_1).index, 1);
+    assert!(pool_factory_client.is_pool(&pool_address));
+
+    let metadata = pool_factory_client.get_pool_metadata(&pool_address);
+    assert_eq!(metadata.admin, bombadil);
+    assert_eq!(metadata.name, name);
+    assert_eq!(metadata.oracle, oracle);
+    assert_eq!(metadata.backstop_take_rate, backstop_rate);
+    assert_eq!(metadata.max_positions, max_positions);
+    assert_eq!(metadata.hf_strategy, 0);
+
+    let entry = pool_factory_client.get_pools(&0, &1).get_unchecked(0);
+    assert_eq!(entry.pool, pool_address);
+    assert_eq!(entry.reserves, vec![&e, asset_0, asset_1]);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1301)")]
+fn test_pool_factory_get_pool_metadata_not_found() {
+    let e = Env::default();
+    e.mock_all_auths_allowing_non_root_auth();
+
+    let pool_init_meta = PoolInitMeta {
+        backstop: Address::generate(&e),
+        pool_hash: BytesN::<32>::random(&e),
+        blnd_id: Address::generate(&e),
+    };
+    let pool_factory_address = e.register(PoolFactoryContract {}, (pool_init_meta,));
+    let pool_factory_client = PoolFactoryClient::new(&e, &pool_factory_address);
+
+    pool_factory_client.get_pool_metadata(&Address::generate(&e));
+}
+
+#[test]
+fn test_pool_factory_registry() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths_allowing_non_root_auth();
+
+    let wasm_hash = e.deployer().upload_contract_wasm(pool::WASM);
+
+    let bombadil = Address::generate(&e);
+    let oracle = Address::generate(&e);
+    let backstop_id = Address::generate(&e);
+    let blnd_id = Address::generate(&e);
+
+    let pool_init_meta = PoolInitMeta {
+        backstop: backstop_id.clone(),
+        pool_hash: wasm_hash.clone(),
+        blnd_id: blnd_id.clone(),
+    };
+    let pool_factory_address = e.register(PoolFactoryContract {}, (pool_init_meta,));
+    let pool_factory_client = PoolFactoryClient::new(&e, &pool_factory_address);
+    assert_eq!(pool_factory_client.get_pool_count(), 0);
+    assert_eq!(pool_factory_client.get_pools(&0, &10).len(), 0);
+
+    let mut pools = vec![&e];
+    for name in ["pool1", "pool2", "pool3"] {
+        let pool_address = pool_factory_client.deploy(
+            &bombadil,
+            &String::from_str(&e, name),
+            &BytesN::<32>::random(&e),
+            &oracle,
+            &0_1000000,
+            &4,
+            &0,
+        );
+        pools.push_back(pool_address);
+    }
+    assert_eq!(pool_factory_client.get_pool_count(), 3);
+
+    let page = pool_factory_client.get_pools(&1, &10);
+    assert_eq!(page.len(), 2);
+    let entry = page.get_unchecked(0);
+    assert_eq!(entry.pool, pools.get_unchecked(1));
+    assert_eq!(entry.index, 1);
+    assert_eq!(entry.name, String::from_str(&e, "pool2"));
+    assert_eq!(entry.oracle, oracle);
+    assert_eq!(entry.backstop, backstop_id);
+    assert_eq!(entry.reserves.len(), 0);
+    assert_eq!(entry.status, 6);
+    assert!(!entry.verified);
+    assert_eq!(page.get_unchecked(1).pool, pools.get_unchecked(2));
+
+    let page = pool_factory_client.get_pools(&0, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get_unchecked(0).pool, pools.get_unchecked(0));
+
+    let page = pool_factory_client.get_pools(&3, &10);
+    assert_eq!(page.len(), 0);
+}
+
+#[test]
+fn test_pool_factory_refresh_pool() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths_allowing_non_root_auth();
+
+    let wasm_hash = e.deployer().upload_contract_wasm(pool::WASM);
+
+    let bombadil = Address::generate(&e);
+    let oracle = Address::generate(&e);
+    let backstop_id = e.register(MockBackstop {}, ());
+    let backstop_client = MockBackstopClient::new(&e, &backstop_id);
+
+    let pool_init_meta = PoolInitMeta {
+        backstop: backstop_id.clone(),
+        pool_hash: wasm_hash.clone(),
+        blnd_id: Address::generate(&e),
+    };
+    let pool_factory_address = e.register(PoolFactoryContract {}, (pool_init_meta,));
+    let pool_factory_client = PoolFactoryClient::new(&e, &pool_factory_address);
+
+    let pool_address = pool_factory_client.deploy(
+        &bombadil,
+        &String::from_str(&e, "pool1"),
+        &BytesN::<32>::random(&e),
+        &oracle,
+        &0_1000000,
+        &4,
+        &0,
+    );
+
+    let entry = pool_factory_client.refresh_pool(&0);
+    assert_eq!(entry.pool, pool_address);
+    assert_eq!(entry.oracle, oracle);
+    assert_eq!(entry.status, 6);
+    assert!(!entry.verified);
+
+    backstop_client.set_reward_zone(&vec![&e, pool_address.clone()]);
+    let entry = pool_factory_client.refresh_pool(&0);
+    assert!(entry.verified);
+
+    let event = vec![&e, e.events().all().last_unchecked()];
+    assert_eq!(
+        event,
+        vec![
+            &e,
+            (
+                pool_factory_address.clone(),
+                (Symbol::new(&e, "refresh_pool"), pool_address.clone()).into_val(&e),
+                (6u32, true).into_val(&e)
+            )
+        ]
+    );
+
+    let entry = pool_factory_client.get_pools(&0, &1).get_unchecked(0);
+    assert_eq!(entry.pool, pool_address);
+    assert!(entry.verified);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #1301)")]
+fn test_pool_factory_refresh_pool_not_found() {
+    let e = Env::default();
+
+    let pool_init_meta = PoolInitMeta {
+        backstop: Address::generate(&e),
+        pool_hash: BytesN::<32>::random(&e),
+        blnd_id: Address::generate(&e),
+    };
+    let pool_factory_address = e.register(PoolFactoryContract {}, (pool_init_meta,));
+    let pool_factory_client = PoolFactoryClient::new(&e, &pool_factory_address);
+
+    pool_factory_client.refresh_pool(&0);
+}
@@ -1,21 +1,39 @@
+use crate::storage::AuctionConfig;
+use soroban_sdk::{contracttype, Address, Map};
+
+#[cfg(feature = "auctions")]
 use crate::{
-    constants::SCALAR_7,
+    constants::{MAX_KEEPER_BOUNTY, MAX_TWAP_RECORDS, SCALAR_7},
+    dependencies::PoolBackstopData,
     errors::PoolError,
-    pool::{Pool, User},
-    storage,
+    events::PoolEvents,
+    pool::{Pool, PositionData, User},
+    storage::{self, LiquidationConfig},
 };
+#[cfg(feature = "auctions")]
 use cast::i128;
+#[cfg(feature = "auctions")]
+use sep_41_token::TokenClient;
+#[cfg(feature = "auctions")]
 use soroban_fixed_point_math::FixedPoint;
-use soroban_sdk::{
-    contracttype, map, panic_with_error, unwrap::UnwrapOptimized, Address, Env, Map, Vec,
-};
+#[cfg(feature = "auctions")]
+use soroban_sdk::{map, panic_with_error, unwrap::UnwrapOptimized, vec, Env, Vec};
 
+#[cfg(feature = "auctions")]
 use super::{
     backstop_interest_auction::{create_interest_auction_data, fill_interest_auction},
-    bad_debt_auction::{create_bad_debt_auction_data, fill_bad_debt_auction},
-    user_liquidation_auction::{create_user_liq_auction_data, fill_user_liq_auction},
+    bad_debt_auction::{
+        build_bad_debt_auction_data, create_bad_debt_auction_data, fill_bad_debt_auction,
+    },
+    user_liquidation_auction::{
+        create_user_liq_auction_data, fill_user_liq_auction, require_fill_above_floor,
+    },
 };
 
+/// The maximum number of auctions returned by a single page of the auction queue
+#[cfg(feature = "auctions")]
+const MAX_AUCTION_QUEUE_PAGE: u32 = 50;
+
 #[derive(Clone, PartialEq)]
 #[repr(u32)]
 pub enum AuctionType {
@@ -24,6 +42,7 @@ pub enum AuctionType {
     InterestAuction = 2,
 }
 
+#[cfg(feature = "auctions")]
 impl AuctionType {
     pub fn from_u32(e: &Env, value: u32) -> Self {
         match value {
@@ -57,6 +76,43 @@ pub struct AuctionData {
     /// The block the auction begins on. This is used to determine how the auction
     /// should be scaled based on the number of blocks that have passed since the auction began.
     pub block: u32,
+    /// The ID of the sub-account being liquidated for a UserLiquidation, otherwise 0
+    pub account_id: u32,
+    /// The auction config of the pool when the auction was created. Fills are priced and
+    /// validated against this config, so config changes only apply to new auctions.
+    pub config: AuctionConfig,
+}
+
+/// The data of an auction, as stored before auctions recorded their sub-account and config
+#[derive(Clone)]
+#[contracttype]
+pub struct AuctionDataV1 {
+    pub bid: Map<Address, i128>, // the assets being bid on and the amount being bid
+    pub lot: Map<Address, i128>, // the assets being auctioned off and the amount being auctioned
+    pub block: u32,              // the block the auction begins on
+}
+
+impl AuctionDataV1 {
+    /// Migrate the auction to the current layout. The auction is for the user's primary account
+    /// and uses the default auction curve it was created with.
+    pub fn migrate(self) -> AuctionData {
+        AuctionData {
+            bid: self.bid,
+            lot: self.lot,
+            block: self.block,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        }
+    }
+}
+
+/// An open auction in the auction queue
+#[derive(Clone)]
+#[contracttype]
+pub struct QueuedAuction {
+    pub auction_type: u32, // the type of auction
+    pub user: Address,     // the Address involved in the auction
+    pub data: AuctionData, // the auction data
 }
 
 /// Create a new auction. Stores the resulting auction to the ledger to begin on the next block.
@@ -74,6 +130,7 @@ pub struct AuctionData {
 /// * If the max positions are exceeded
 /// * If the user and percent are invalid for the auction type
 /// * If the auction is unable to be created
+#[cfg(feature = "auctions")]
 pub fn create_auction(
     e: &Env,
     auction_type: u32,
@@ -85,11 +142,54 @@ pub fn create_auction(
     // panics if auction_type parameter is not valid
     let auction_type_enum = AuctionType::from_u32(e, auction_type);
     let auction_data = match auction_type_enum {
-        AuctionType::UserLiquidation => create_user_liq_auction_data(e, user, bid, lot, percent),
+        AuctionType::UserLiquidation => {
+            return create_account_liquidation(e, user, 0, bid, lot, percent);
+        }
         AuctionType::BadDebtAuction => create_bad_debt_auction_data(e, user, bid, lot, percent),
         AuctionType::InterestAuction => create_interest_auction_data(e, user, bid, lot, percent),
     };
     storage::set_auction(e, &auction_type, user, &auction_data);
+    storage::set_auction_keeper(e, &auction_type, user, &None);
+    queue_auction(e, auction_type, user);
+    auction_data
+}
+
+/// Create a new liquidation auction for one of the user's sub-accounts. Stores the resulting
+/// auction to the ledger to begin on the next block.
+///
+/// If the user registered a liquidation protection contract, it is called first and can supply
+/// collateral for the user. If that restores the sub-account's health, no auction is created.
+///
+/// Returns the AuctionData object created, or an AuctionData with an empty bid and lot if no
+/// auction was created
+///
+/// ### Arguments
+/// * `user` - The user being liquidated
+/// * `account_id` - The ID of the sub-account being liquidated
+/// * `bid` - The assets being bid on
+/// * `lot` - The assets being auctioned off
+/// * `percent` - The percentage of the sub-account's positions being liquidated
+///
+/// ### Panics
+/// If the auction is unable to be created
+#[cfg(feature = "auctions")]
+pub fn create_account_liquidation(
+    e: &Env,
+    user: &Address,
+    account_id: u32,
+    bid: &Vec<Address>,
+    lot: &Vec<Address>,
+    percent: u32,
+) -> AuctionData {
+    let auction_data = create_user_liq_auction_data(e, user, account_id, bid, lot, percent);
+    if auction_data.bid.is_empty() {
+        // the user's liquidation protection restored their health
+        return auction_data;
+    }
+    let auction_type = AuctionType::UserLiquidation as u32;
+    storage::set_auction(e, &auction_type, user, &auction_data);
+    storage::set_auction_keeper(e, &auction_type, user, &None);
+    queue_auction(e, auction_type, user);
     auction_data
 }
 
@@ -98,18 +198,238 @@ pub fn create_auction(
 /// NOTE: Does not verify if the user's positions are healthy. This must be done before calling.
 ///
 /// ### Arguments
-/// * `auction_type` - The type of auction being created
+/// * `user` - The user being liquidated
+/// * `account_id` - The ID of the sub-account of the user
+///
+/// ### Panics
+/// If no auction exists for the sub-account of the user
+#[cfg(feature = "auctions")]
+pub fn delete_liquidation(e: &Env, user: &Address, account_id: u32) {
+    let auction_type = AuctionType::UserLiquidation as u32;
+    if !storage::has_auction(e, &auction_type, user)
+        || storage::get_auction(e, &auction_type, user).account_id != account_id
+    {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::del_auction(e, &auction_type, user);
+    storage::set_auction_keeper(e, &auction_type, user, &None);
+    dequeue_auction(e, auction_type, user);
+}
+
+/// Load a page of the open auctions, ordered from oldest to newest. Filling or deleting an auction
+/// removes it from the queue, which shifts the position of every newer auction down by one.
+///
+/// ### Arguments
+/// * `start` - The position in the queue of the first auction to load
+/// * `limit` - The maximum number of auctions to load
+#[cfg(feature = "auctions")]
+pub fn load_auction_queue(e: &Env, start: u32, limit: u32) -> Vec<QueuedAuction> {
+    let queue = storage::get_auction_queue(e);
+    let end = start
+        .saturating_add(limit.min(MAX_AUCTION_QUEUE_PAGE))
+        .min(queue.len());
+    let mut auctions = vec![e];
+    for index in start..end {
+        let (auction_type, user) = queue.get_unchecked(index);
+        // auctions are kept in temporary storage, and can expire without being removed
+        if storage::has_auction(e, &auction_type, &user) {
+            auctions.push_back(QueuedAuction {
+                data: storage::get_auction(e, &auction_type, &user),
+                auction_type,
+                user,
+            });
+        }
+    }
+    auctions
+}
+
+/// Add an auction to the end of the auction queue, if it is not already in it. Auctions are kept
+/// in temporary storage and can expire without being dequeued, so the queue is pruned of expired
+/// auctions whenever an auction is queued.
+#[cfg(feature = "auctions")]
+fn queue_auction(e: &Env, auction_type: u32, user: &Address) {
+    let mut queued = false;
+    let mut queue = vec![e];
+    for (queued_type, queued_user) in storage::get_auction_queue(e).iter() {
+        if storage::has_auction(e, &queued_type, &queued_user) {
+            queued |= queued_type == auction_type && &queued_user == user;
+            queue.push_back((queued_type, queued_user));
+        }
+    }
+    if !queued {
+        queue.push_back((auction_type, user.clone()));
+    }
+    storage::set_auction_queue(e, &queue);
+}
+
+/// Remove an auction from the auction queue
+#[cfg(feature = "auctions")]
+fn dequeue_auction(e: &Env, auction_type: u32, user: &Address) {
+    let mut queue = storage::get_auction_queue(e);
+    if let Some(index) = queue.first_index_of(&(auction_type, user.clone())) {
+        queue.remove(index);
+        storage::set_auction_queue(e, &queue);
+    }
+}
+
+/// Set the share of a filled auction's value paid to the keeper that created the auction
+///
+/// ### Arguments
+/// * `bounty` - The share of the filled value (7 decimals)
+///
+/// ### Panics
+/// If the bounty is over the max
+#[cfg(feature = "auctions")]
+pub fn execute_set_keeper_bounty(e: &Env, bounty: u32) {
+    if bounty > MAX_KEEPER_BOUNTY {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_keeper_bounty(e, &bounty);
+}
+
+/// Set the value of backstop bad debt that triggers a bad debt auction automatically
+///
+/// ### Arguments
+/// * `threshold` - The value of bad debt in the oracle's base asset, or 0 to disable
+///
+/// ### Panics
+/// If the threshold is negative
+#[cfg(feature = "auctions")]
+pub fn execute_set_bad_debt_threshold(e: &Env, threshold: i128) {
+    if threshold < 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_bad_debt_threshold(e, &threshold);
+}
+
+/// Require that "liquidator" can create or fill user liquidation auctions. If user liquidations
+/// are restricted to the pool's liquidator allowlist, the liquidator must be on it, and user
+/// liquidation auctions can't be created without a liquidator.
+///
+/// ### Arguments
+/// * `liquidator` - The address creating or filling the auction, if any
+///
+/// ### Panics
+/// If user liquidations are restricted and the liquidator is not on the liquidator allowlist
+#[cfg(feature = "auctions")]
+pub fn require_allowed_liquidator(e: &Env, liquidator: Option<&Address>) {
+    if storage::get_liquidator_allowlist(e)
+        && !liquidator.is_some_and(|liquidator| storage::get_allowed_liquidator(e, liquidator))
+    {
+        panic_with_error!(e, PoolError::LiquidatorNotAllowed);
+    }
+}
+
+/// Create a bad debt auction for the backstop's bad debt if the value of the bad debt exceeds
+/// the pool's bad debt threshold, the backstop holds tokens to auction, and no bad debt auction
+/// is in progress. This lets bad debt be auctioned without waiting on a keeper.
+///
+/// This is checked when the backstop's deposits for the pool change, so it fails soft. If a price
+/// can't be loaded, no auction is created instead of reverting the backstop action.
+///
+/// Returns the created auction, if any
+///
+/// ### Arguments
+/// * `backstop_token` - The backstop token the pool's backstop holds
+/// * `pool_backstop_data` - The pool's backstop data
+#[cfg(feature = "auctions")]
+pub fn try_create_bad_debt_auction(
+    e: &Env,
+    backstop_token: &Address,
+    pool_backstop_data: &PoolBackstopData,
+) -> Option<AuctionData> {
+    let threshold = storage::get_bad_debt_threshold(e);
+    if threshold == 0 {
+        return None;
+    }
+    let backstop = storage::get_backstop(e);
+    let auction_type = AuctionType::BadDebtAuction as u32;
+    if storage::has_auction(e, &auction_type, &backstop) {
+        return None;
+    }
+
+    // the auction cannot bid on more positions than a user can hold
+    let mut pool = Pool::load(e);
+    let reserve_list = storage::get_res_list(e);
+    let backstop_positions = storage::get_user_positions(e, &backstop);
+    let mut bid = vec![e];
+    for (reserve_index, liability_balance) in backstop_positions.liabilities.iter() {
+        if bid.len() >= pool.config.max_positions {
+            break;
+        }
+        if liability_balance > 0 {
+            bid.push_back(reserve_list.get(reserve_index)?);
+        }
+    }
+
+    let (auction_data, debt_value) = build_bad_debt_auction_data(
+        e,
+        &mut pool,
+        &bid,
+        backstop_token,
+        pool_backstop_data,
+    )
+    .ok()?;
+    if debt_value < threshold {
+        return None;
+    }
+
+    storage::set_auction(e, &auction_type, &backstop, &auction_data);
+    storage::set_auction_keeper(e, &auction_type, &backstop, &None);
+    queue_auction(e, auction_type, &backstop);
+    Some(auction_data)
+}
+
+/// Set the dutch auction curve used to price auctions
+///
+/// ### Arguments
+/// * `config` - The auction curve config
+///
+/// ### Panics
+/// If the starting lot is not under 100%, the step is not between 10 and 1000 blocks, the max
+/// premium is not greater than 0% and at most 50%, the floor is not under 100%, the min fill
+/// percent is over 100%, or the min fill value is negative
+#[cfg(feature = "auctions")]
+pub fn execute_set_auction_config(e: &Env, config: &AuctionConfig) {
+    if i128(config.lot_start) >= SCALAR_7
+        || config.step_blocks < 10
+        || config.step_blocks > 1000
+        || config.max_premium == 0
+        || i128(config.max_premium) > SCALAR_7 / 2
+        || i128(config.floor) >= SCALAR_7
+        || config.min_fill_percent > 100
+        || config.min_fill_value < 0
+    {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_auction_config(e, config);
+}
+
+/// Set the close factor bounds used when creating user liquidations
+///
+/// ### Arguments
+/// * `config` - The liquidation config
 ///
 /// ### Panics
-/// If no auction exists for the user
-pub fn delete_liquidation(e: &Env, user: &Address) {
-    if !storage::has_auction(e, &(AuctionType::UserLiquidation as u32), user) {
+/// If the minimum close factor is over 100%, the max health factor is not greater than 1.03
+/// and at most 2, the number of TWAP records is over the max, or the target health factor is
+/// under 1.03 or over the max health factor
+#[cfg(feature = "auctions")]
+pub fn execute_set_liquidation_config(e: &Env, config: &LiquidationConfig) {
+    if config.min_close_factor > 100
+        || i128(config.max_hf) <= 1_0300000
+        || i128(config.max_hf) > 2_0000000
+        || config.twap_records > MAX_TWAP_RECORDS
+        || i128(config.target_hf) < 1_0300000
+        || config.target_hf > config.max_hf
+    {
         panic_with_error!(e, PoolError::BadRequest);
     }
-    storage::del_auction(e, &(AuctionType::UserLiquidation as u32), user);
+    storage::set_liquidation_config(e, config);
 }
 
-/// Fills the auction from the invoker.
+/// Fills the auction from the invoker. User liquidation fills are valued with the pool's
+/// liquidation TWAP, the same prices used to create them.
 ///
 /// ### Arguments
 /// * `pool` - The pool
@@ -119,8 +439,10 @@ pub fn delete_liquidation(e: &Env, user: &Address) {
 /// * `percent_filled` - The percentage being filled as a number (i.e. 15 => 15%)
 ///
 /// ### Panics
-/// If the auction does not exist, or if the pool is unable to fulfill either side
-/// of the auction quote
+/// If the auction does not exist, if the pool is unable to fulfill either side
+/// of the auction quote, if a partial fill is under the pool's minimum fill size, or if a user
+/// liquidation is filled below the pool's floor
+#[cfg(feature = "auctions")]
 pub fn fill(
     e: &Env,
     pool: &mut Pool,
@@ -133,7 +455,27 @@ pub fn fill(
         panic_with_error!(e, PoolError::InvalidLiquidation);
     }
     let auction_data = storage::get_auction(e, &auction_type, user);
-    let (to_fill_auction, remaining_auction) = scale_auction(e, &auction_data, percent_filled);
+    let auction_config = auction_data.config.clone();
+    let (to_fill_auction, remaining_auction) =
+        scale_auction(e, &auction_data, &auction_config, percent_filled);
+    let is_user_liquidation = auction_type == AuctionType::UserLiquidation as u32;
+    if is_user_liquidation {
+        // value the fill with the same prices used to create the auction
+        pool.set_twap_records(e, storage::get_liquidation_config(e).twap_records);
+    }
+    require_min_fill(
+        e,
+        pool,
+        auction_type,
+        &to_fill_auction,
+        &auction_config,
+        percent_filled,
+    );
+    if is_user_liquidation {
+        require_fill_above_floor(e, pool, &to_fill_auction, auction_config.floor);
+        // the filler's health is checked with spot prices
+        pool.set_twap_records(e, 0);
+    }
     match AuctionType::from_u32(e, auction_type) {
         AuctionType::UserLiquidation => {
             fill_user_liq_auction(e, pool, &to_fill_auction, user, filler_state)
@@ -146,20 +488,260 @@ pub fn fill(
         }
     };
 
+    let keeper = storage::get_auction_keeper(e, &auction_type, user)
+        .filter(|keeper| keeper != user && keeper != &filler_state.address);
     if let Some(auction_to_store) = remaining_auction {
         storage::set_auction(e, &auction_type, user, &auction_to_store);
     } else {
         storage::del_auction(e, &auction_type, user);
+        storage::set_auction_keeper(e, &auction_type, user, &None);
+        dequeue_auction(e, auction_type, user);
+    }
+    if let Some(keeper) = keeper {
+        pay_keeper_bounty(e, pool, auction_type, user, &keeper, &to_fill_auction);
     }
 
     to_fill_auction
 }
 
+/// Preview the fill of an auction at a block. Returns the bid and lot the filler would spend and
+/// receive, as calculated by `fill`. Nothing is written to the ledger.
+///
+/// ### Arguments
+/// * `auction_type` - The type of auction to preview
+/// * `user` - The user involved in the auction
+/// * `percent_filled` - The percentage being filled as a number (i.e. 15 => 15%)
+/// * `at_block` - The block the fill would occur on
+///
+/// ### Panics
+/// If the auction does not exist, the block is before the auction began, or the percent filled
+/// is greater than 100 or less than 0
+#[cfg(feature = "auctions")]
+pub fn preview_fill(
+    e: &Env,
+    auction_type: u32,
+    user: &Address,
+    percent_filled: u64,
+    at_block: u32,
+) -> AuctionData {
+    let auction_data = storage::get_auction(e, &auction_type, user);
+    if at_block < auction_data.block {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let auction_config = auction_data.config.clone();
+    let (to_fill_auction, _) =
+        scale_auction_at(e, &auction_data, &auction_config, percent_filled, at_block);
+    to_fill_auction
+}
+
+/// Calculate the smallest percent of a user liquidation auction that restores the user to the
+/// pool's target health factor if filled at the current block, clamped to `max_percent`. Returns
+/// `max_percent` if filling it does not restore the user to the target. The user is valued with the
+/// pool's liquidation TWAP, the same prices used to create the auction.
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `user` - The user being liquidated
+/// * `max_percent` - The max percentage the filler is willing to fill (i.e. 15 => 15%)
+///
+/// ### Panics
+/// If the auction does not exist, or if the max percent is greater than 100 or less than 0
+#[cfg(feature = "auctions")]
+pub fn calc_target_fill_percent(
+    e: &Env,
+    pool: &mut Pool,
+    user: &Address,
+    max_percent: u64,
+) -> u64 {
+    let auction_data = storage::get_auction(e, &(AuctionType::UserLiquidation as u32), user);
+    let auction_config = auction_data.config.clone();
+    let liq_config = storage::get_liquidation_config(e);
+    let target_hf = i128(liq_config.target_hf);
+    let user_state = User::load_account(e, user, auction_data.account_id);
+    let emode = user_state.emode(e);
+    // value the user with the same prices used to create the auction
+    pool.set_twap_records(e, liq_config.twap_records);
+
+    // the health factor can only increase with the percent filled, so search for the smallest
+    // percent that reaches the target
+    let mut restores_target = |percent: u64| -> bool {
+        let (to_fill_auction, _) = scale_auction(e, &auction_data, &auction_config, percent);
+        let mut positions = user_state.positions.clone();
+        for (asset, amount) in to_fill_auction.lot.iter() {
+            let index = pool.load_reserve(e, &asset, false).index;
+            let balance = positions.collateral.get(index).unwrap_or(0) - amount;
+            if balance > 0 {
+                positions.collateral.set(index, balance);
+            } else {
+                positions.collateral.remove(index);
+            }
+        }
+        for (asset, amount) in to_fill_auction.bid.iter() {
+            let index = pool.load_reserve(e, &asset, false).index;
+            let balance = positions.liabilities.get(index).unwrap_or(0) - amount;
+            if balance > 0 {
+                positions.liabilities.set(index, balance);
+            } else {
+                positions.liabilities.remove(index);
+            }
+        }
+        let new_data = PositionData::calculate_from_positions(e, pool, &positions, emode);
+        !new_data.is_hf_under(target_hf)
+    };
+    let percent = if !restores_target(max_percent) {
+        max_percent
+    } else {
+        let mut low = 1;
+        let mut high = max_percent;
+        while low < high {
+            let mid = (low + high) / 2;
+            if restores_target(mid) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        low
+    };
+    pool.set_twap_records(e, 0);
+    percent
+}
+
+/// Require a partial fill of an auction to take at least the pool's minimum percent of the
+/// remaining auction and to be worth at least the pool's minimum fill value, so fillers cannot
+/// delay an auction with many small fills. Filling the rest of an auction is always allowed.
+///
+/// The value of a fill is the value of its reserve positions at oracle prices, which is the bid
+/// of user liquidations and bad debt auctions, and the lot of interest auctions.
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `auction_type` - The type of auction being filled
+/// * `to_fill_auction` - The scaled auction being filled
+/// * `auction_config` - The auction config the auction was created with
+/// * `percent_filled` - The percentage being filled as a number (i.e. 15 => 15%)
+///
+/// ### Panics
+/// If the fill is partial and under the minimum percent or value
+#[cfg(feature = "auctions")]
+fn require_min_fill(
+    e: &Env,
+    pool: &mut Pool,
+    auction_type: u32,
+    to_fill_auction: &AuctionData,
+    auction_config: &AuctionConfig,
+    percent_filled: u64,
+) {
+    if percent_filled >= 100 {
+        return;
+    }
+    if percent_filled < u64::from(auction_config.min_fill_percent) {
+        panic_with_error!(e, PoolError::AuctionFillTooSmall);
+    }
+    if auction_config.min_fill_value == 0 {
+        return;
+    }
+    let mut fill_base = 0;
+    if auction_type == AuctionType::InterestAuction as u32 {
+        for (asset, amount) in to_fill_auction.lot.iter() {
+            let reserve = pool.load_reserve(e, &asset, false);
+            let price = pool.load_price(e, &asset);
+            fill_base += amount
+                .fixed_mul_floor(price, reserve.scalar)
+                .unwrap_optimized();
+        }
+    } else {
+        for (asset, amount) in to_fill_auction.bid.iter() {
+            let reserve = pool.load_reserve(e, &asset, false);
+            let price = pool.load_price(e, &asset);
+            fill_base += reserve
+                .to_asset_from_d_token(amount)
+                .fixed_mul_floor(price, reserve.scalar)
+                .unwrap_optimized();
+        }
+    }
+    if fill_base < auction_config.min_fill_value {
+        panic_with_error!(e, PoolError::AuctionFillTooSmall);
+    }
+}
+
+/// Pay the keeper bounty of an auction fill to the keeper that created the auction. The bounty is
+/// a share of the value filled, paid in the underlying tokens of the filled reserves out of their
+/// backstop credit, so the filler receives the full lot. Credit committed to the lot of a running
+/// interest auction is not used, and the bounty is capped at the remaining credit.
+///
+/// The value filled is the bid of user liquidations and bad debt auctions, and the lot of
+/// interest auctions.
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `auction_type` - The type of auction being filled
+/// * `user` - The user involved in the auction
+/// * `keeper` - The keeper that created the auction
+/// * `filled_auction` - The scaled auction that was filled
+#[cfg(feature = "auctions")]
+fn pay_keeper_bounty(
+    e: &Env,
+    pool: &mut Pool,
+    auction_type: u32,
+    user: &Address,
+    keeper: &Address,
+    filled_auction: &AuctionData,
+) {
+    let bounty_rate = i128(storage::get_keeper_bounty(e));
+    if bounty_rate == 0 {
+        return;
+    }
+    let interest_auction_type = AuctionType::InterestAuction as u32;
+    let backstop = storage::get_backstop(e);
+    let committed_credit = if storage::has_auction(e, &interest_auction_type, &backstop) {
+        storage::get_auction(e, &interest_auction_type, &backstop).lot
+    } else {
+        map![e]
+    };
+    let is_interest_auction = auction_type == interest_auction_type;
+    let filled = if is_interest_auction {
+        &filled_auction.lot
+    } else {
+        &filled_auction.bid
+    };
+
+    let mut bounty: Map<Address, i128> = map![e];
+    for (asset, amount) in filled.iter() {
+        let mut reserve = pool.load_reserve(e, &asset, true);
+        let filled_underlying = if is_interest_auction {
+            amount
+        } else {
+            reserve.to_asset_from_d_token(amount)
+        };
+        let available_credit =
+            reserve.backstop_credit - committed_credit.get(asset.clone()).unwrap_or(0);
+        let bounty_amount = filled_underlying
+            .fixed_mul_floor(bounty_rate, SCALAR_7)
+            .unwrap_optimized()
+            .min(available_credit);
+        if bounty_amount > 0 {
+            reserve.backstop_credit -= bounty_amount;
+            TokenClient::new(e, &asset).transfer(
+                &e.current_contract_address(),
+                keeper,
+                &bounty_amount,
+            );
+            bounty.set(asset, bounty_amount);
+        }
+        pool.cache_reserve(reserve);
+    }
+    if !bounty.is_empty() {
+        PoolEvents::keeper_bounty(e, auction_type, user.clone(), keeper.clone(), bounty);
+    }
+}
+
 /// Scale the auction based on the percent being filled and the amount of blocks that have passed
 /// since the auction began.
 ///
 /// ### Arguments
 /// * `auction_data` - The auction data to scale
+/// * `auction_config` - The auction curve config the auction was created with
 /// * `percent_filled` - The percentage being filled as a number (i.e. 15 => 15%)
 ///
 /// Returns the (Scaled Auction, Remaining Auction) such that:
@@ -168,11 +750,26 @@ pub fn fill(
 ///
 /// ### Panics
 /// If the percent filled is greater than 100 or less than 0
-#[allow(clippy::zero_prefixed_literal)]
+#[cfg(feature = "auctions")]
 fn scale_auction(
     e: &Env,
     auction_data: &AuctionData,
+    auction_config: &AuctionConfig,
+    percent_filled: u64,
+) -> (AuctionData, Option<AuctionData>) {
+    let block = e.ledger().sequence();
+    scale_auction_at(e, auction_data, auction_config, percent_filled, block)
+}
+
+/// Scale the auction based on the percent being filled and the amount of blocks that have passed
+/// between the start of the auction and `block`.
+#[cfg(feature = "auctions")]
+fn scale_auction_at(
+    e: &Env,
+    auction_data: &AuctionData,
+    auction_config: &AuctionConfig,
     percent_filled: u64,
+    block: u32,
 ) -> (AuctionData, Option<AuctionData>) {
     if percent_filled > 100 || percent_filled == 0 {
         panic_with_error!(e, PoolError::BadRequest);
@@ -182,29 +779,34 @@ fn scale_auction(
         bid: map![e],
         lot: map![e],
         block: auction_data.block,
+        account_id: auction_data.account_id,
+        config: auction_data.config.clone(),
     };
     let mut remaining_auction = AuctionData {
         bid: map![e],
         lot: map![e],
         block: auction_data.block,
+        account_id: auction_data.account_id,
+        config: auction_data.config.clone(),
     };
 
     // determine block based auction modifiers
     let bid_modifier: i128;
     let lot_modifier: i128;
-    let per_block_scalar: i128 = 0_0050000; // modifier moves 0.5% every block
-    let block_dif = i128(e.ledger().sequence() - auction_data.block);
-    if block_dif > 200 {
+    let step_blocks = i128(auction_config.step_blocks);
+    let block_dif = i128(block - auction_data.block);
+    if block_dif > step_blocks {
         // lot 100%, bid scaling down from 100% to 0%
         lot_modifier = SCALAR_7;
-        if block_dif < 400 {
-            bid_modifier = SCALAR_7 - (block_dif - 200) * per_block_scalar;
+        if block_dif < 2 * step_blocks {
+            bid_modifier = SCALAR_7 - (block_dif - step_blocks) * SCALAR_7 / step_blocks;
         } else {
             bid_modifier = 0;
         }
     } else {
-        // lot scaling from 0% to 100%, bid 100%
-        lot_modifier = block_dif * per_block_scalar;
+        // lot scaling from the starting lot to 100%, bid 100%
+        let lot_start = i128(auction_config.lot_start);
+        lot_modifier = lot_start + (SCALAR_7 - lot_start) * block_dif / step_blocks;
         bid_modifier = SCALAR_7;
     }
 
@@ -254,7 +856,7 @@ fn scale_auction(
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "auctions"))]
 mod tests {
     use crate::{
         pool::Positions,
@@ -270,6 +872,17 @@ mod tests {
         vec, Symbol,
     };
 
+    fn default_auction_config() -> AuctionConfig {
+        AuctionConfig {
+            lot_start: 0,
+            step_blocks: 200,
+            max_premium: 0_5000000,
+            floor: 0,
+            min_fill_percent: 0,
+            min_fill_value: 0,
+        }
+    }
+
     #[test]
     fn test_create_bad_debt_auction() {
         let e = Env::default();
@@ -314,7 +927,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -327,7 +940,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -340,7 +953,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -388,9 +1001,17 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
+        let mut auction_config = default_auction_config();
+        auction_config.step_blocks = 100;
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
+            storage::set_auction_config(&e, &auction_config);
             storage::set_user_positions(&e, &backstop_address, &positions);
 
             create_auction(
@@ -402,6 +1023,8 @@ mod tests {
                 100,
             );
             assert!(storage::has_auction(&e, &1, &backstop_address));
+            let auction = storage::get_auction(&e, &1, &backstop_address);
+            assert_eq!(auction.config.step_blocks, 100);
         });
     }
 
@@ -467,7 +1090,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -498,6 +1121,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -541,7 +1169,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -555,7 +1183,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -610,6 +1238,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_backstop(&e, &Address::generate(&e));
@@ -626,6 +1259,7 @@ mod tests {
                 liq_pct,
             );
             assert!(storage::has_auction(&e, &0, &samwise));
+            assert_eq!(storage::get_auction_queue(&e), vec![&e, (0, samwise.clone())]);
         });
     }
 
@@ -654,7 +1288,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -668,7 +1302,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -723,6 +1357,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_backstop(&e, &Address::generate(&e));
@@ -766,7 +1405,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -780,7 +1419,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -835,6 +1474,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_backstop(&e, &backstop);
@@ -916,6 +1560,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -943,6 +1592,8 @@ mod tests {
             bid: map![&e],
             lot: map![&e],
             block: 100,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         e.as_contract(&pool_id, || {
             storage::set_auction(
@@ -952,7 +1603,7 @@ mod tests {
                 &auction_data,
             );
 
-            delete_liquidation(&e, &samwise);
+            delete_liquidation(&e, &samwise, 0);
             assert!(!storage::has_auction(
                 &e,
                 &(AuctionType::UserLiquidation as u32),
@@ -971,7 +1622,7 @@ mod tests {
         let samwise = Address::generate(&e);
 
         e.as_contract(&pool_id, || {
-            delete_liquidation(&e, &samwise);
+            delete_liquidation(&e, &samwise, 0);
         });
     }
 
@@ -1043,12 +1694,19 @@ mod tests {
                 (underlying_1.clone(), 1_5395739)
             ],
             block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1063,6 +1721,7 @@ mod tests {
             storage::set_user_positions(&e, &samwise, &positions);
             storage::set_pool_config(&e, &pool_config);
             storage::set_auction(&e, &0, &samwise, &auction_data);
+            queue_auction(&e, 0, &samwise);
 
             e.ledger().set(LedgerInfo {
                 timestamp: 12345 + 200 * 5,
@@ -1080,18 +1739,78 @@ mod tests {
             fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 100);
             let has_auction = storage::has_auction(&e, &0, &samwise);
             assert_eq!(has_auction, false);
+            assert_eq!(storage::get_auction_queue(&e).len(), 0);
         });
     }
 
     #[test]
-    fn test_partial_fill() {
+    fn test_load_auction_queue() {
+        let e = Env::default();
+        let pool_address = create_pool(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let backstop = Address::generate(&e);
+
+        let auction_data = AuctionData {
+            bid: map![&e, (Address::generate(&e), 10_0000000)],
+            lot: map![&e, (Address::generate(&e), 20_0000000)],
+            block: 100,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_auction(&e, &0, &samwise, &auction_data);
+            queue_auction(&e, 0, &samwise);
+            storage::set_auction(&e, &2, &backstop, &auction_data);
+            queue_auction(&e, 2, &backstop);
+            storage::set_auction(&e, &0, &frodo, &auction_data);
+            queue_auction(&e, 0, &frodo);
+            // an auction is only queued once
+            queue_auction(&e, 0, &samwise);
+            assert_eq!(storage::get_auction_queue(&e).len(), 3);
+
+            let page = load_auction_queue(&e, 0, 2);
+            assert_eq!(page.len(), 2);
+            assert_eq!(page.get_unchecked(0).auction_type, 0);
+            assert_eq!(page.get_unchecked(0).user, samwise);
+            assert_eq!(page.get_unchecked(0).data.block, 100);
+            assert_eq!(page.get_unchecked(1).auction_type, 2);
+            assert_eq!(page.get_unchecked(1).user, backstop);
+
+            // removed auctions shift newer auctions down the queue
+            storage::del_auction(&e, &0, &samwise);
+            dequeue_auction(&e, 0, &samwise);
+            let page = load_auction_queue(&e, 1, 2);
+            assert_eq!(page.len(), 1);
+            assert_eq!(page.get_unchecked(0).user, frodo);
+
+            // auctions no longer in storage are skipped
+            storage::del_auction(&e, &2, &backstop);
+            let page = load_auction_queue(&e, 0, 10);
+            assert_eq!(page.len(), 1);
+            assert_eq!(page.get_unchecked(0).user, frodo);
+
+            assert_eq!(load_auction_queue(&e, 5, 10).len(), 0);
+
+            // expired auctions are pruned when an auction is queued
+            storage::set_auction(&e, &0, &samwise, &auction_data);
+            queue_auction(&e, 0, &samwise);
+            assert_eq!(
+                storage::get_auction_queue(&e),
+                vec![&e, (0, frodo.clone()), (0, samwise.clone())]
+            );
+        });
+    }
+
+    #[test]
+    fn test_calc_target_fill_percent() {
         let e = Env::default();
 
         e.mock_all_auths();
         e.ledger().set(LedgerInfo {
             timestamp: 12345,
             protocol_version: 22,
-            sequence_number: 175,
+            sequence_number: 300,
             network_id: Default::default(),
             base_reserve: 10,
             min_temp_entry_ttl: 172800,
@@ -1101,17 +1820,17 @@ mod tests {
 
         let bombadil = Address::generate(&e);
         let samwise = Address::generate(&e);
-        let frodo = Address::generate(&e);
 
         let pool_address = create_pool(&e);
 
-        let (oracle_address, _) = testutils::create_mock_oracle(&e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
 
         // creating reserves for a pool exhausts the budget
         e.cost_estimate().budget().reset_unlimited();
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
-        let (mut reserve_config_0, reserve_data_0) = testutils::default_reserve_meta();
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_config_0.index = 0;
+        reserve_data_0.last_time = 12345;
         testutils::create_reserve(
             &e,
             &pool_address,
@@ -1121,8 +1840,9 @@ mod tests {
         );
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
-        let (mut reserve_config_1, reserve_data_1) = testutils::default_reserve_meta();
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_config_1.index = 1;
+        reserve_data_1.last_time = 12345;
         testutils::create_reserve(
             &e,
             &pool_address,
@@ -1130,36 +1850,334 @@ mod tests {
             &reserve_config_1,
             &reserve_data_1,
         );
-
-        let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
-        let (mut reserve_config_2, reserve_data_2) = testutils::default_reserve_meta();
-        reserve_config_2.index = 2;
-        testutils::create_reserve(
-            &e,
-            &pool_address,
-            &underlying_2,
-            &reserve_config_2,
-            &reserve_data_2,
-        );
         e.cost_estimate().budget().reset_unlimited();
 
-        let auction_data = AuctionData {
-            bid: map![&e, (underlying_2.clone(), 1_2375000)],
-            lot: map![
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
                 &e,
-                (underlying_0.clone(), 30_5595329),
-                (underlying_1.clone(), 1_5395739)
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
             ],
-            block: 176,
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 1_0000000]);
+
+        // the lot is fully scaled in and the bid is not yet scaled down
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_1.clone(), 30_0000000)],
+            lot: map![&e, (underlying_0.clone(), 33_0000000)],
+            block: 100,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
-            collateral: map![
+            collateral: map![&e, (reserve_config_0.index, 100_0000000)],
+            liabilities: map![&e, (reserve_config_1.index, 60_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_auction(&e, &0, &samwise, &auction_data);
+
+            let mut pool = Pool::load(&e);
+            // filling 67% leaves the user at a 1.098 health factor and 68% at 1.102
+            assert_eq!(calc_target_fill_percent(&e, &mut pool, &samwise, 100), 68);
+            // the filler's percent is not exceeded
+            assert_eq!(calc_target_fill_percent(&e, &mut pool, &samwise, 50), 50);
+        });
+    }
+
+    #[test]
+    fn test_fill_pays_keeper_bounty() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 175,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+
+        let (oracle_address, _) = testutils::create_mock_oracle(&e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, reserve_data_1) = testutils::default_reserve_meta();
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        let (underlying_2, underlying_2_client) =
+            testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
+        reserve_config_2.index = 2;
+        reserve_data_2.last_time = 12345 + 200 * 5;
+        reserve_data_2.backstop_credit = 1_0000000;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+        e.cost_estimate().budget().reset_unlimited();
+
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_2.clone(), 1_2375000)],
+            lot: map![
+                &e,
+                (underlying_0.clone(), 30_5595329),
+                (underlying_1.clone(), 1_5395739)
+            ],
+            block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 90_9100000),
+                (reserve_config_1.index, 04_5800000),
+            ],
+            liabilities: map![&e, (reserve_config_2.index, 02_7500000),],
+            supply: map![&e],
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_auction(&e, &0, &samwise, &auction_data);
+            storage::set_auction_keeper(&e, &0, &samwise, &Some(merry.clone()));
+            execute_set_keeper_bounty(&e, 0_0500000);
+
+            e.ledger().set(LedgerInfo {
+                timestamp: 12345 + 200 * 5,
+                protocol_version: 22,
+                sequence_number: 176 + 200,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 172800,
+                min_persistent_entry_ttl: 172800,
+                max_entry_ttl: 9999999,
+            });
+            e.cost_estimate().budget().reset_unlimited();
+            let mut pool = Pool::load(&e);
+            let mut frodo_state = User::load(&e, &frodo);
+            let filled = fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 100);
+            // the filler receives the full lot
+            assert_eq!(filled.lot.get_unchecked(underlying_0.clone()), 30_5595329);
+            assert_eq!(filled.lot.get_unchecked(underlying_1.clone()), 1_5395739);
+            assert_eq!(frodo_state.get_collateral(0), 30_5595329);
+            assert_eq!(frodo_state.get_collateral(1), 1_5395739);
+            // the keeper receives 5% of the filled bid in underlying from the backstop credit
+            assert_eq!(underlying_2_client.balance(&merry), 0_0618750);
+            let reserve_2 = pool.load_reserve(&e, &underlying_2, false);
+            assert_eq!(reserve_2.backstop_credit, 1_0000000 - 0_0618750);
+            let merry_positions = storage::get_user_positions(&e, &merry);
+            assert!(merry_positions.collateral.is_empty());
+            let samwise_positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(samwise_positions.collateral.get_unchecked(0), 60_3504671);
+            assert_eq!(samwise_positions.collateral.get_unchecked(1), 3_0404261);
+
+            assert!(!storage::has_auction(&e, &0, &samwise));
+            assert_eq!(storage::get_auction_keeper(&e, &0, &samwise), None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_keeper_bounty_too_high() {
+        let e = Env::default();
+        let pool_address = create_pool(&e);
+
+        e.as_contract(&pool_address, || {
+            execute_set_keeper_bounty(&e, MAX_KEEPER_BOUNTY + 1);
+        });
+    }
+
+    #[test]
+    fn test_require_allowed_liquidator() {
+        let e = Env::default();
+        let pool_address = create_pool(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&pool_address, || {
+            // anyone can liquidate while the allowlist is disabled
+            require_allowed_liquidator(&e, None);
+            require_allowed_liquidator(&e, Some(&frodo));
+
+            storage::set_liquidator_allowlist(&e, true);
+            storage::set_allowed_liquidator(&e, &samwise, true);
+            require_allowed_liquidator(&e, Some(&samwise));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1245)")]
+    fn test_require_allowed_liquidator_not_allowed() {
+        let e = Env::default();
+        let pool_address = create_pool(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&pool_address, || {
+            storage::set_liquidator_allowlist(&e, true);
+            storage::set_allowed_liquidator(&e, &samwise, true);
+            require_allowed_liquidator(&e, Some(&frodo));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1245)")]
+    fn test_require_allowed_liquidator_no_liquidator() {
+        let e = Env::default();
+        let pool_address = create_pool(&e);
+
+        e.as_contract(&pool_address, || {
+            storage::set_liquidator_allowlist(&e, true);
+            require_allowed_liquidator(&e, None);
+        });
+    }
+
+    #[test]
+    fn test_partial_fill() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 175,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+
+        let (oracle_address, _) = testutils::create_mock_oracle(&e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, reserve_data_1) = testutils::default_reserve_meta();
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, reserve_data_2) = testutils::default_reserve_meta();
+        reserve_config_2.index = 2;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+        e.cost_estimate().budget().reset_unlimited();
+
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_2.clone(), 1_2375000)],
+            lot: map![
+                &e,
+                (underlying_0.clone(), 30_5595329),
+                (underlying_1.clone(), 1_5395739)
+            ],
+            block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions: Positions = Positions {
+            collateral: map![
                 &e,
                 (reserve_config_0.index, 90_9100000),
                 (reserve_config_1.index, 04_5800000),
@@ -1195,6 +2213,8 @@ mod tests {
                     (underlying_1.clone(), 1_1546805)
                 ],
                 block: 176,
+                account_id: 0,
+                config: AuctionConfig::default(),
             };
             let new_auction = storage::get_auction(&e, &0, &samwise);
             assert_eq!(new_auction.bid, expected_new_auction_data.bid);
@@ -1272,12 +2292,19 @@ mod tests {
                 (underlying_1.clone(), 1_000_0000)
             ],
             block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1316,6 +2343,8 @@ mod tests {
                     (underlying_1.clone(), 750_0000)
                 ],
                 block: 176,
+                account_id: 0,
+                config: AuctionConfig::default(),
             };
 
             // Partial fill 2 - 66% @ 100% mods
@@ -1336,66 +2365,437 @@ mod tests {
             });
             let mut pool = Pool::load(&e);
             let mut frodo_state = User::load(&e, &frodo);
-            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 67);
-
-            let expected_new_auction_data = AuctionData {
-                bid: map![&e, (underlying_2.clone(), 24_7500000)],
-                lot: map![
-                    &e,
-                    (underlying_0.clone(), 2_4750000),
-                    (underlying_1.clone(), 0_2475000)
-                ],
-                block: 176,
-            };
+            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 67);
+
+            let expected_new_auction_data = AuctionData {
+                bid: map![&e, (underlying_2.clone(), 24_7500000)],
+                lot: map![
+                    &e,
+                    (underlying_0.clone(), 2_4750000),
+                    (underlying_1.clone(), 0_2475000)
+                ],
+                block: 176,
+                account_id: 0,
+                config: AuctionConfig::default(),
+            };
+            let new_auction = storage::get_auction(&e, &0, &samwise);
+            assert_eq!(new_auction.bid, expected_new_auction_data.bid);
+            assert_eq!(new_auction.lot, expected_new_auction_data.lot);
+            assert_eq!(new_auction.block, expected_new_auction_data.block);
+
+            // full fill at 50% bid mod
+            e.ledger().set(LedgerInfo {
+                timestamp: 12345 + 300 * 5,
+                protocol_version: 22,
+                sequence_number: 176 + 300,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 172800,
+                min_persistent_entry_ttl: 172800,
+                max_entry_ttl: 9999999,
+            });
+            let mut pool = Pool::load(&e);
+            let mut frodo_state = User::load(&e, &frodo);
+            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 100);
+            let new_auction = storage::has_auction(&e, &0, &samwise);
+            assert_eq!(new_auction, false);
+            let samwise_positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(
+                samwise_positions
+                    .collateral
+                    .get(reserve_config_0.index)
+                    .unwrap_optimized(),
+                30_000_0000 - 1_250_0000 - 5_000_0002 - 2_499_9998
+            );
+            assert_eq!(
+                samwise_positions
+                    .collateral
+                    .get(reserve_config_1.index)
+                    .unwrap_optimized(),
+                3_000_0000 - 125_0000 - 500_0000 - 250_0000
+            );
+            assert_eq!(
+                samwise_positions
+                    .liabilities
+                    .get(reserve_config_2.index)
+                    .unwrap_optimized(),
+                200_000_0000 - 25_000_0000 - 50_000_0025 - 12_6249975
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_fill_fails_pct_too_large() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 175,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+
+        let (oracle_address, _) = testutils::create_mock_oracle(&e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, reserve_data_1) = testutils::default_reserve_meta();
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, reserve_data_2) = testutils::default_reserve_meta();
+        reserve_config_2.index = 2;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_2.clone(), 1_2375000)],
+            lot: map![
+                &e,
+                (underlying_0.clone(), 30_5595329),
+                (underlying_1.clone(), 1_5395739)
+            ],
+            block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 90_9100000),
+                (reserve_config_1.index, 04_5800000),
+            ],
+            liabilities: map![&e, (reserve_config_2.index, 02_7500000),],
+            supply: map![&e],
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_auction(&e, &0, &samwise, &auction_data);
+
+            e.ledger().set(LedgerInfo {
+                timestamp: 12345 + 200 * 5,
+                protocol_version: 22,
+                sequence_number: 176 + 200,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 172800,
+                min_persistent_entry_ttl: 172800,
+                max_entry_ttl: 9999999,
+            });
+            e.cost_estimate().budget().reset_unlimited();
+            let mut pool = Pool::load(&e);
+            let mut frodo_state = User::load(&e, &frodo);
+            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 101);
+
+            let expected_new_auction_data = AuctionData {
+                bid: map![&e, (underlying_2.clone(), 9281250)],
+                lot: map![
+                    &e,
+                    (underlying_0.clone(), 22_9196497),
+                    (underlying_1.clone(), 1_1546805)
+                ],
+                block: 176,
+                account_id: 0,
+                config: AuctionConfig::default(),
+            };
+            let new_auction = storage::get_auction(&e, &0, &samwise);
+            assert_eq!(new_auction.bid, expected_new_auction_data.bid);
+            assert_eq!(new_auction.lot, expected_new_auction_data.lot);
+            assert_eq!(new_auction.block, expected_new_auction_data.block);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1243)")]
+    fn test_fill_fails_under_min_fill_percent() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 175,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+
+        let (oracle_address, _) = testutils::create_mock_oracle(&e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, reserve_data_1) = testutils::default_reserve_meta();
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, reserve_data_2) = testutils::default_reserve_meta();
+        reserve_config_2.index = 2;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+        e.cost_estimate().budget().reset_unlimited();
+
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_2.clone(), 1_2375000)],
+            lot: map![
+                &e,
+                (underlying_0.clone(), 30_5595329),
+                (underlying_1.clone(), 1_5395739)
+            ],
+            block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 90_9100000),
+                (reserve_config_1.index, 04_5800000),
+            ],
+            liabilities: map![&e, (reserve_config_2.index, 02_7500000),],
+            supply: map![&e],
+        };
+        let mut auction_config = default_auction_config();
+        auction_config.min_fill_percent = 60;
+        let auction_data = AuctionData {
+            config: auction_config.clone(),
+            ..auction_data
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_auction_config(&e, &auction_config);
+            storage::set_auction(&e, &0, &samwise, &auction_data);
+
+            e.ledger().set(LedgerInfo {
+                timestamp: 12345 + 200 * 5,
+                protocol_version: 22,
+                sequence_number: 176 + 200,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 172800,
+                min_persistent_entry_ttl: 172800,
+                max_entry_ttl: 9999999,
+            });
+            e.cost_estimate().budget().reset_unlimited();
+            let mut pool = Pool::load(&e);
+            let mut frodo_state = User::load(&e, &frodo);
+            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 50);
+        });
+    }
+
+    #[test]
+    fn test_fill_ignores_auction_config_changes() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 175,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+
+        let (oracle_address, _) = testutils::create_mock_oracle(&e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, reserve_data_1) = testutils::default_reserve_meta();
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, reserve_data_2) = testutils::default_reserve_meta();
+        reserve_config_2.index = 2;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+        e.cost_estimate().budget().reset_unlimited();
+
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_2.clone(), 1_2375000)],
+            lot: map![
+                &e,
+                (underlying_0.clone(), 30_5595329),
+                (underlying_1.clone(), 1_5395739)
+            ],
+            block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 90_9100000),
+                (reserve_config_1.index, 04_5800000),
+            ],
+            liabilities: map![&e, (reserve_config_2.index, 02_7500000),],
+            supply: map![&e],
+        };
+        // the pool's config changes after the auction was created
+        let mut auction_config = default_auction_config();
+        auction_config.min_fill_percent = 60;
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_auction_config(&e, &auction_config);
+            storage::set_auction(&e, &0, &samwise, &auction_data);
+
+            e.ledger().set(LedgerInfo {
+                timestamp: 12345 + 200 * 5,
+                protocol_version: 22,
+                sequence_number: 176 + 200,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 172800,
+                min_persistent_entry_ttl: 172800,
+                max_entry_ttl: 9999999,
+            });
+            e.cost_estimate().budget().reset_unlimited();
+            let mut pool = Pool::load(&e);
+            let mut frodo_state = User::load(&e, &frodo);
+            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 50);
             let new_auction = storage::get_auction(&e, &0, &samwise);
-            assert_eq!(new_auction.bid, expected_new_auction_data.bid);
-            assert_eq!(new_auction.lot, expected_new_auction_data.lot);
-            assert_eq!(new_auction.block, expected_new_auction_data.block);
-
-            // full fill at 50% bid mod
-            e.ledger().set(LedgerInfo {
-                timestamp: 12345 + 300 * 5,
-                protocol_version: 22,
-                sequence_number: 176 + 300,
-                network_id: Default::default(),
-                base_reserve: 10,
-                min_temp_entry_ttl: 172800,
-                min_persistent_entry_ttl: 172800,
-                max_entry_ttl: 9999999,
-            });
-            let mut pool = Pool::load(&e);
-            let mut frodo_state = User::load(&e, &frodo);
-            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 100);
-            let new_auction = storage::has_auction(&e, &0, &samwise);
-            assert_eq!(new_auction, false);
-            let samwise_positions = storage::get_user_positions(&e, &samwise);
-            assert_eq!(
-                samwise_positions
-                    .collateral
-                    .get(reserve_config_0.index)
-                    .unwrap_optimized(),
-                30_000_0000 - 1_250_0000 - 5_000_0002 - 2_499_9998
-            );
-            assert_eq!(
-                samwise_positions
-                    .collateral
-                    .get(reserve_config_1.index)
-                    .unwrap_optimized(),
-                3_000_0000 - 125_0000 - 500_0000 - 250_0000
-            );
-            assert_eq!(
-                samwise_positions
-                    .liabilities
-                    .get(reserve_config_2.index)
-                    .unwrap_optimized(),
-                200_000_0000 - 25_000_0000 - 50_000_0025 - 12_6249975
-            );
+            assert_eq!(new_auction.config.min_fill_percent, 0);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1200)")]
-    fn test_fill_fails_pct_too_large() {
+    #[should_panic(expected = "Error(Contract, #1243)")]
+    fn test_fill_fails_under_min_fill_value() {
         let e = Env::default();
 
         e.mock_all_auths();
@@ -1416,7 +2816,7 @@ mod tests {
 
         let pool_address = create_pool(&e);
 
-        let (oracle_address, _) = testutils::create_mock_oracle(&e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
 
         // creating reserves for a pool exhausts the budget
         e.cost_estimate().budget().reset_unlimited();
@@ -1452,6 +2852,21 @@ mod tests {
             &reserve_config_2,
             &reserve_data_2,
         );
+        e.cost_estimate().budget().reset_unlimited();
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+                Asset::Stellar(underlying_2.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000, 100_0000000]);
 
         let auction_data = AuctionData {
             bid: map![&e, (underlying_2.clone(), 1_2375000)],
@@ -1461,12 +2876,19 @@ mod tests {
                 (underlying_1.clone(), 1_5395739)
             ],
             block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1477,9 +2899,17 @@ mod tests {
             liabilities: map![&e, (reserve_config_2.index, 02_7500000),],
             supply: map![&e],
         };
+        // the bid of a 50% fill is worth ~61.9
+        let mut auction_config = default_auction_config();
+        auction_config.min_fill_value = 100_0000000_0000000;
+        let auction_data = AuctionData {
+            config: auction_config.clone(),
+            ..auction_data
+        };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
             storage::set_pool_config(&e, &pool_config);
+            storage::set_auction_config(&e, &auction_config);
             storage::set_auction(&e, &0, &samwise, &auction_data);
 
             e.ledger().set(LedgerInfo {
@@ -1495,21 +2925,7 @@ mod tests {
             e.cost_estimate().budget().reset_unlimited();
             let mut pool = Pool::load(&e);
             let mut frodo_state = User::load(&e, &frodo);
-            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 101);
-
-            let expected_new_auction_data = AuctionData {
-                bid: map![&e, (underlying_2.clone(), 9281250)],
-                lot: map![
-                    &e,
-                    (underlying_0.clone(), 22_9196497),
-                    (underlying_1.clone(), 1_1546805)
-                ],
-                block: 176,
-            };
-            let new_auction = storage::get_auction(&e, &0, &samwise);
-            assert_eq!(new_auction.bid, expected_new_auction_data.bid);
-            assert_eq!(new_auction.lot, expected_new_auction_data.lot);
-            assert_eq!(new_auction.block, expected_new_auction_data.block);
+            fill(&e, &mut pool, 0, &samwise, &mut frodo_state, 50);
         });
     }
 
@@ -1583,12 +2999,19 @@ mod tests {
                 (underlying_1.clone(), 1_5395739)
             ],
             block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1627,6 +3050,8 @@ mod tests {
                     (underlying_1.clone(), 1_1546805)
                 ],
                 block: 176,
+                account_id: 0,
+                config: AuctionConfig::default(),
             };
             let new_auction = storage::get_auction(&e, &0, &samwise);
             assert_eq!(new_auction.bid, expected_new_auction_data.bid);
@@ -1646,6 +3071,8 @@ mod tests {
             bid: map![&e, (underlying_0.clone(), 100_0000000)],
             lot: map![&e, (underlying_1.clone(), 100_0000000)],
             block: 1000,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
 
         // 0 blocks
@@ -1659,7 +3086,8 @@ mod tests {
             min_persistent_entry_ttl: 172800,
             max_entry_ttl: 9999999,
         });
-        let (scaled_auction, remaining_auction) = scale_auction(&e, &base_auction_data, 100);
+        let (scaled_auction, remaining_auction) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 100);
         assert_eq!(
             scaled_auction.bid.get_unchecked(underlying_0.clone()),
             100_0000000
@@ -1678,7 +3106,8 @@ mod tests {
             min_persistent_entry_ttl: 172800,
             max_entry_ttl: 9999999,
         });
-        let (scaled_auction, remaining_auction) = scale_auction(&e, &base_auction_data, 100);
+        let (scaled_auction, remaining_auction) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 100);
         assert_eq!(
             scaled_auction.bid.get_unchecked(underlying_0.clone()),
             100_0000000
@@ -1700,7 +3129,8 @@ mod tests {
             min_persistent_entry_ttl: 172800,
             max_entry_ttl: 9999999,
         });
-        let (scaled_auction, remaining_auction) = scale_auction(&e, &base_auction_data, 100);
+        let (scaled_auction, remaining_auction) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 100);
         assert_eq!(
             scaled_auction.bid.get_unchecked(underlying_0.clone()),
             100_0000000
@@ -1722,7 +3152,8 @@ mod tests {
             min_persistent_entry_ttl: 172800,
             max_entry_ttl: 9999999,
         });
-        let (scaled_auction, remaining_auction) = scale_auction(&e, &base_auction_data, 100);
+        let (scaled_auction, remaining_auction) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 100);
         assert_eq!(
             scaled_auction.bid.get_unchecked(underlying_0.clone()),
             50_0000000
@@ -1744,7 +3175,8 @@ mod tests {
             min_persistent_entry_ttl: 172800,
             max_entry_ttl: 9999999,
         });
-        let (scaled_auction, remaining_auction) = scale_auction(&e, &base_auction_data, 100);
+        let (scaled_auction, remaining_auction) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 100);
         assert_eq!(scaled_auction.bid.len(), 0);
         assert_eq!(
             scaled_auction.lot.get_unchecked(underlying_1.clone()),
@@ -1821,12 +3253,19 @@ mod tests {
                 (underlying_1.clone(), 1_5395739)
             ],
             block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1871,6 +3310,8 @@ mod tests {
             bid: map![&e, (underlying_0.clone(), 25_0000005)],
             lot: map![&e, (underlying_1.clone(), 25_0000005)],
             block: 1000,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
 
         // 0 blocks
@@ -1884,7 +3325,8 @@ mod tests {
             min_persistent_entry_ttl: 172800,
             max_entry_ttl: 9999999,
         });
-        let (scaled_auction, remaining_auction_option) = scale_auction(&e, &base_auction_data, 50);
+        let (scaled_auction, remaining_auction_option) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 50);
         let remaining_auction = remaining_auction_option.unwrap();
         assert_eq!(
             scaled_auction.bid.get_unchecked(underlying_0.clone()),
@@ -1912,7 +3354,8 @@ mod tests {
             max_entry_ttl: 9999999,
         });
 
-        let (scaled_auction, remaining_auction_option) = scale_auction(&e, &base_auction_data, 60);
+        let (scaled_auction, remaining_auction_option) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 60);
         let remaining_auction = remaining_auction_option.unwrap();
         assert_eq!(
             scaled_auction.bid.get_unchecked(underlying_0.clone()),
@@ -1943,7 +3386,8 @@ mod tests {
             max_entry_ttl: 9999999,
         });
 
-        let (scaled_auction, remaining_auction_option) = scale_auction(&e, &base_auction_data, 60);
+        let (scaled_auction, remaining_auction_option) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 60);
         let remaining_auction = remaining_auction_option.unwrap();
         assert_eq!(
             scaled_auction.bid.get_unchecked(underlying_0.clone()),
@@ -1973,7 +3417,8 @@ mod tests {
             min_persistent_entry_ttl: 172800,
             max_entry_ttl: 9999999,
         });
-        let (scaled_auction, remaining_auction_option) = scale_auction(&e, &base_auction_data, 50);
+        let (scaled_auction, remaining_auction_option) =
+            scale_auction(&e, &base_auction_data, &default_auction_config(), 50);
         let remaining_auction = remaining_auction_option.unwrap();
         assert_eq!(scaled_auction.bid.len(), 0);
         assert_eq!(
@@ -2001,6 +3446,8 @@ mod tests {
             bid: map![&e, (underlying_0.clone(), 25_0000005)],
             lot: map![&e, (underlying_1.clone(), 25_0000005)],
             block: 1000,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
 
         // 0 blocks
@@ -2015,7 +3462,7 @@ mod tests {
             max_entry_ttl: 9999999,
         });
 
-        let (_, _) = scale_auction(&e, &base_auction_data, 0);
+        let (_, _) = scale_auction(&e, &base_auction_data, &default_auction_config(), 0);
     }
 
     #[test]
@@ -2029,6 +3476,45 @@ mod tests {
             bid: map![&e, (underlying_0.clone(), 25_0000005)],
             lot: map![&e, (underlying_1.clone(), 25_0000005)],
             block: 1000,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+
+        // 0 blocks
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 1000,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+
+        let (_, _) = scale_auction(&e, &base_auction_data, &default_auction_config(), 101);
+    }
+
+    #[test]
+    fn test_scale_auction_custom_config() {
+        let e = Env::default();
+        let underlying_0 = Address::generate(&e);
+        let underlying_1 = Address::generate(&e);
+
+        let base_auction_data = AuctionData {
+            bid: map![&e, (underlying_0.clone(), 100_0000000)],
+            lot: map![&e, (underlying_1.clone(), 100_0000000)],
+            block: 1000,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        let auction_config = AuctionConfig {
+            lot_start: 0_5000000,
+            step_blocks: 100,
+            max_premium: 0_2000000,
+            floor: 0,
+            min_fill_percent: 0,
+            min_fill_value: 0,
         };
 
         // 0 blocks
@@ -2042,7 +3528,244 @@ mod tests {
             min_persistent_entry_ttl: 172800,
             max_entry_ttl: 9999999,
         });
+        let (scaled_auction, _) = scale_auction(&e, &base_auction_data, &auction_config, 100);
+        assert_eq!(scaled_auction.bid.get_unchecked(underlying_0.clone()), 100_0000000);
+        assert_eq!(scaled_auction.lot.get_unchecked(underlying_1.clone()), 50_0000000);
 
-        let (_, _) = scale_auction(&e, &base_auction_data, 101);
+        // 50 blocks
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 1050,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+        let (scaled_auction, _) = scale_auction(&e, &base_auction_data, &auction_config, 100);
+        assert_eq!(scaled_auction.bid.get_unchecked(underlying_0.clone()), 100_0000000);
+        assert_eq!(scaled_auction.lot.get_unchecked(underlying_1.clone()), 75_0000000);
+
+        // 150 blocks
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 1150,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+        let (scaled_auction, _) = scale_auction(&e, &base_auction_data, &auction_config, 100);
+        assert_eq!(scaled_auction.bid.get_unchecked(underlying_0.clone()), 50_0000000);
+        assert_eq!(scaled_auction.lot.get_unchecked(underlying_1.clone()), 100_0000000);
+
+        // 200 blocks
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 1200,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+        let (scaled_auction, _) = scale_auction(&e, &base_auction_data, &auction_config, 100);
+        assert_eq!(scaled_auction.bid.len(), 0);
+        assert_eq!(scaled_auction.lot.get_unchecked(underlying_1.clone()), 100_0000000);
+    }
+
+    #[test]
+    fn test_preview_fill() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 1050,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 172800,
+            min_persistent_entry_ttl: 172800,
+            max_entry_ttl: 9999999,
+        });
+
+        let pool_id = create_pool(&e);
+        let samwise = Address::generate(&e);
+        let underlying_0 = Address::generate(&e);
+        let underlying_1 = Address::generate(&e);
+
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_0.clone(), 25_0000005)],
+            lot: map![&e, (underlying_1.clone(), 25_0000005)],
+            block: 1000,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        e.as_contract(&pool_id, || {
+            storage::set_auction(
+                &e,
+                &(AuctionType::UserLiquidation as u32),
+                &samwise,
+                &auction_data,
+            );
+
+            // 100 blocks in, the lot is scaled to 50% and the bid is 100%
+            let preview = preview_fill(&e, 0, &samwise, 50, 1100);
+            assert_eq!(preview.bid.get_unchecked(underlying_0.clone()), 12_5000003);
+            assert_eq!(preview.lot.get_unchecked(underlying_1.clone()), 6_2500001);
+            assert_eq!(preview.block, 1000);
+
+            // 300 blocks in, the lot is 100% and the bid is scaled to 50%
+            let preview = preview_fill(&e, 0, &samwise, 100, 1300);
+            assert_eq!(preview.bid.get_unchecked(underlying_0.clone()), 12_5000003);
+            assert_eq!(preview.lot.get_unchecked(underlying_1.clone()), 25_0000005);
+
+            // nothing is written to the ledger
+            let stored = storage::get_auction(&e, &0, &samwise);
+            assert_eq!(stored.bid.get_unchecked(underlying_0.clone()), 25_0000005);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_preview_fill_before_auction_block() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let pool_id = create_pool(&e);
+        let samwise = Address::generate(&e);
+
+        let auction_data = AuctionData {
+            bid: map![&e],
+            lot: map![&e],
+            block: 1000,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        e.as_contract(&pool_id, || {
+            storage::set_auction(
+                &e,
+                &(AuctionType::UserLiquidation as u32),
+                &samwise,
+                &auction_data,
+            );
+
+            preview_fill(&e, 0, &samwise, 50, 999);
+        });
+    }
+
+    #[test]
+    fn test_set_auction_config() {
+        let e = Env::default();
+        let pool = create_pool(&e);
+
+        let auction_config = AuctionConfig {
+            lot_start: 0_1000000,
+            step_blocks: 100,
+            max_premium: 0_2000000,
+            floor: 0_9000000,
+            min_fill_percent: 10,
+            min_fill_value: 100_0000000,
+        };
+        e.as_contract(&pool, || {
+            let default_config = storage::get_auction_config(&e);
+            assert_eq!(default_config.lot_start, 0);
+            assert_eq!(default_config.step_blocks, 200);
+            assert_eq!(default_config.max_premium, 0_5000000);
+            assert_eq!(default_config.floor, 0);
+
+            execute_set_auction_config(&e, &auction_config);
+            let new_config = storage::get_auction_config(&e);
+            assert_eq!(new_config.lot_start, 0_1000000);
+            assert_eq!(new_config.step_blocks, 100);
+            assert_eq!(new_config.max_premium, 0_2000000);
+            assert_eq!(new_config.floor, 0_9000000);
+            assert_eq!(new_config.min_fill_percent, 10);
+            assert_eq!(new_config.min_fill_value, 100_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_auction_config_validates() {
+        let e = Env::default();
+        let pool = create_pool(&e);
+
+        let auction_config = AuctionConfig {
+            lot_start: 0,
+            step_blocks: 5,
+            max_premium: 0_2000000,
+            floor: 0,
+            min_fill_percent: 0,
+            min_fill_value: 0,
+        };
+        e.as_contract(&pool, || {
+            execute_set_auction_config(&e, &auction_config);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_liquidation_config_target_over_max_hf() {
+        let e = Env::default();
+        let pool = create_pool(&e);
+
+        let liquidation_config = LiquidationConfig {
+            min_close_factor: 25,
+            max_hf: 1_1500000,
+            twap_records: 0,
+            target_hf: 1_2000000,
+        };
+        e.as_contract(&pool, || {
+            execute_set_liquidation_config(&e, &liquidation_config);
+        });
+    }
+
+    #[test]
+    fn test_set_liquidation_config() {
+        let e = Env::default();
+        let pool = create_pool(&e);
+
+        let liquidation_config = LiquidationConfig {
+            min_close_factor: 25,
+            max_hf: 1_2000000,
+            twap_records: 6,
+            target_hf: 1_0500000,
+        };
+        e.as_contract(&pool, || {
+            let default_config = storage::get_liquidation_config(&e);
+            assert_eq!(default_config.min_close_factor, 0);
+            assert_eq!(default_config.max_hf, 1_1500000);
+            assert_eq!(default_config.twap_records, 0);
+            assert_eq!(default_config.target_hf, 1_1000000);
+
+            execute_set_liquidation_config(&e, &liquidation_config);
+            let new_config = storage::get_liquidation_config(&e);
+            assert_eq!(new_config.min_close_factor, 25);
+            assert_eq!(new_config.max_hf, 1_2000000);
+            assert_eq!(new_config.twap_records, 6);
+            assert_eq!(new_config.target_hf, 1_0500000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_liquidation_config_validates() {
+        let e = Env::default();
+        let pool = create_pool(&e);
+
+        let liquidation_config = LiquidationConfig {
+            min_close_factor: 25,
+            max_hf: 1_0200000,
+            twap_records: 0,
+            target_hf: 1_1000000,
+        };
+        e.as_contract(&pool, || {
+            execute_set_liquidation_config(&e, &liquidation_config);
+        });
     }
 }
@@ -1,5 +1,9 @@
 use crate::{
-    constants::SCALAR_7, dependencies::BackstopClient, errors::PoolError, pool::Pool, storage,
+    constants::{PRICE_DECIMALS, SCALAR_7},
+    dependencies::BackstopClient,
+    errors::PoolError,
+    pool::Pool,
+    storage,
 };
 use cast::i128;
 use sep_41_token::TokenClient;
@@ -30,15 +34,17 @@ pub fn create_interest_auction_data(
     if pool.config.max_positions < lot.len() {
         panic_with_error!(e, PoolError::MaxPositionsExceeded);
     }
-    let oracle_scalar = 10i128.pow(pool.load_price_decimals(e));
+    let oracle_scalar = 10i128.pow(PRICE_DECIMALS);
     let mut auction_data = AuctionData {
         lot: map![e],
         bid: map![e],
         block: e.ledger().sequence() + 1,
+        account_id: 0,
+        config: storage::get_auction_config(e),
     };
 
     // validate and create lot auction data
-    let mut interest_value = 0; // expressed in the oracle's decimals
+    let mut interest_value = 0; // expressed in the pool's price decimals
     for lot_asset in lot {
         // don't store updated reserve data back to ledger. This will occur on the the auction's fill.
         // `load_reserve` will panic if the reserve does not exist
@@ -57,13 +63,13 @@ pub fn create_interest_auction_data(
     }
 
     // Ensure that the interest value is at least 200 USDC
-    if interest_value <= (200 * 10i128.pow(pool.load_price_decimals(e))) {
+    if interest_value <= (200 * oracle_scalar) {
         panic_with_error!(e, PoolError::InterestTooSmall);
     }
 
     // validate and create bid auction data
     let backstop_client = BackstopClient::new(e, &backstop);
-    let backstop_token = backstop_client.backstop_token();
+    let backstop_token = backstop_client.pool_backstop_token(&e.current_contract_address());
     if bid.len() != 1 || bid.get_unchecked(0) != backstop_token {
         panic_with_error!(e, PoolError::InvalidBid);
     }
@@ -98,7 +104,8 @@ pub fn fill_interest_auction(
         panic_with_error!(e, PoolError::BadRequest);
     }
     let backstop_client = BackstopClient::new(&e, &backstop);
-    let backstop_token: Address = backstop_client.backstop_token();
+    let backstop_token: Address =
+        backstop_client.pool_backstop_token(&e.current_contract_address());
     let backstop_token_bid_amount = auction_data.bid.get(backstop_token).unwrap_or(0);
 
     backstop_client.donate(
@@ -124,7 +131,7 @@ pub fn fill_interest_auction(
 mod tests {
     use crate::{
         auctions::auction::AuctionType,
-        storage::{self, PoolConfig},
+        storage::{self, AuctionConfig, PoolConfig},
         testutils::{self, create_comet_lp_pool, create_pool},
     };
 
@@ -158,6 +165,8 @@ mod tests {
             bid: map![&e],
             lot: map![&e],
             block: 50,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         e.as_contract(&pool_address, || {
             storage::set_backstop(&e, &backstop_address);
@@ -196,6 +205,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -237,6 +251,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -279,6 +298,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -327,7 +351,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_data_0.backstop_credit = 10_0000000;
         reserve_config_0.index = 0;
@@ -341,7 +365,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_100_000_000;
+        reserve_data_1.b_rate = 1_100_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_data_1.backstop_credit = 2_5000000;
         reserve_config_1.index = 1;
@@ -355,7 +379,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -386,6 +410,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -469,6 +498,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -547,6 +581,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -625,6 +664,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -733,6 +777,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -844,6 +893,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -962,6 +1016,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -1075,6 +1134,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 3,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -1145,7 +1209,7 @@ mod tests {
 
         let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_data_0.b_supply = 200_000_0000000;
         reserve_data_0.d_supply = 100_000_0000000;
         reserve_data_0.last_time = 12345;
@@ -1162,7 +1226,7 @@ mod tests {
 
         let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_100_000_000;
+        reserve_data_1.b_rate = 1_100_000_000_000;
         reserve_data_0.b_supply = 10_000_0000000;
         reserve_data_0.b_supply = 7_000_0000000;
         reserve_data_1.last_time = 12345;
@@ -1182,6 +1246,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let mut auction_data = AuctionData {
             bid: map![&e, (backstop_token_id.clone(), 75_0000000)],
@@ -1191,6 +1260,8 @@ mod tests {
                 (underlying_1.clone(), 25_0000000)
             ],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
 
         backstop_token_client.approve(
@@ -1273,7 +1344,7 @@ mod tests {
 
         let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_data_0.b_supply = 200_000_0000000;
         reserve_data_0.d_supply = 100_000_0000000;
         reserve_data_0.last_time = 12345;
@@ -1290,7 +1361,7 @@ mod tests {
 
         let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_100_000_000;
+        reserve_data_1.b_rate = 1_100_000_000_000;
         reserve_data_0.b_supply = 10_000_0000000;
         reserve_data_0.b_supply = 7_000_0000000;
         reserve_data_1.last_time = 12345;
@@ -1310,6 +1381,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let mut auction_data = AuctionData {
             bid: map![&e],
@@ -1319,6 +1395,8 @@ mod tests {
                 (underlying_1.clone(), 25_0000000)
             ],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         e.as_contract(&pool_address, || {
             e.mock_all_auths_allowing_non_root_auth();
@@ -1411,6 +1489,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let mut auction_data = AuctionData {
             bid: map![&e, (usdc_id.clone(), 95_0000000)],
@@ -1420,6 +1503,8 @@ mod tests {
                 (underlying_1.clone(), 25_0000000)
             ],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         usdc_client.mint(&samwise, &100_0000000);
         e.as_contract(&pool_address, || {
@@ -1,6 +1,6 @@
 use crate::{
-    constants::SCALAR_7,
-    dependencies::BackstopClient,
+    constants::{PRICE_DECIMALS, SCALAR_7},
+    dependencies::{BackstopClient, PoolBackstopData},
     errors::PoolError,
     events::PoolEvents,
     pool::{calc_pool_backstop_threshold, Pool, User},
@@ -30,70 +30,102 @@ pub fn create_bad_debt_auction_data(
         panic_with_error!(e, PoolError::AuctionInProgress);
     }
 
+    let mut pool = Pool::load(e);
+    if pool.config.max_positions < bid.len() {
+        panic_with_error!(e, PoolError::MaxPositionsExceeded);
+    }
+
+    let backstop_client = BackstopClient::new(e, &backstop);
+    let backstop_token = backstop_client.pool_backstop_token(&e.current_contract_address());
+    if lot.len() != 1 || lot.get_unchecked(0) != backstop_token {
+        panic_with_error!(e, PoolError::InvalidLot);
+    }
+    let pool_backstop_data = backstop_client.pool_data(&e.current_contract_address());
+
+    match build_bad_debt_auction_data(e, &mut pool, bid, &backstop_token, &pool_backstop_data) {
+        Ok((auction_data, _)) => auction_data,
+        Err(error) => panic_with_error!(e, error),
+    }
+}
+
+/// Build a bad debt auction for the backstop's liabilities in the `bid` reserves. The lot is the
+/// backstop tokens needed to cover the value of the liabilities with a 40% premium, or all of the
+/// backstop tokens if there are not enough.
+///
+/// Returns the auction data and the value of the bid in the oracle's base asset
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `bid` - The reserves of the backstop's liabilities to auction
+/// * `backstop_token` - The backstop token the pool's backstop holds
+/// * `pool_backstop_data` - The pool's backstop data
+///
+/// ### Errors
+/// If the backstop has no liabilities in a bid reserve, a price can't be loaded, or the backstop
+/// tokens have no value
+pub fn build_bad_debt_auction_data(
+    e: &Env,
+    pool: &mut Pool,
+    bid: &Vec<Address>,
+    backstop_token: &Address,
+    pool_backstop_data: &PoolBackstopData,
+) -> Result<(AuctionData, i128), PoolError> {
     let mut auction_data = AuctionData {
         bid: map![e],
         lot: map![e],
         block: e.ledger().sequence() + 1,
+        account_id: 0,
+        config: storage::get_auction_config(e),
     };
 
     // validate and create bid auction data
-    let mut pool = Pool::load(e);
-    if pool.config.max_positions < bid.len() {
-        panic_with_error!(e, PoolError::MaxPositionsExceeded);
-    }
-    let oracle_scalar = 10i128.pow(pool.load_price_decimals(e));
-    let backstop_positions = storage::get_user_positions(e, &backstop);
-    let mut debt_value = 0;
+    let backstop_positions = storage::get_user_positions(e, &storage::get_backstop(e));
+    let mut debt_value: i128 = 0;
     for bid_asset in bid {
         let reserve = pool.load_reserve(e, &bid_asset, false);
         let liability_balance = backstop_positions
             .liabilities
             .get(reserve.index)
             .unwrap_or(0);
-        if liability_balance > 0 {
-            let asset_to_base = pool.load_price(e, &reserve.asset);
-            let asset_balance = reserve.to_asset_from_d_token(liability_balance);
-            debt_value += i128(asset_to_base)
-                .fixed_mul_floor(asset_balance, reserve.scalar)
-                .unwrap_optimized();
-            auction_data.bid.set(reserve.asset, liability_balance);
-        } else {
-            panic_with_error!(e, PoolError::InvalidBid);
+        if liability_balance <= 0 {
+            return Err(PoolError::InvalidBid);
         }
+        let asset_to_base = pool.try_load_price(e, &reserve.asset)?;
+        let asset_balance = reserve.to_asset_from_d_token(liability_balance);
+        debt_value = asset_to_base
+            .fixed_mul_floor(asset_balance, reserve.scalar)
+            .and_then(|asset_value| debt_value.checked_add(asset_value))
+            .ok_or(PoolError::OverflowError)?;
+        auction_data.bid.set(reserve.asset, liability_balance);
     }
-
     if auction_data.bid.is_empty() || debt_value == 0 {
-        panic_with_error!(e, PoolError::InvalidBid);
+        return Err(PoolError::InvalidBid);
     }
 
-    // validate and create lot auction data
-    let backstop_client = BackstopClient::new(e, &backstop);
-    let backstop_token = backstop_client.backstop_token();
-    if lot.len() != 1 || lot.get_unchecked(0) != backstop_token {
-        panic_with_error!(e, PoolError::InvalidLot);
-    }
-
-    // get value of backstop_token (BLND-USDC LP token) to base
-    let pool_backstop_data = backstop_client.pool_data(&e.current_contract_address());
-    let backstop_value_base = pool_backstop_data
+    // get value of backstop_token (BLND-USDC LP token) to base. Since the backstop LP token is an
+    // 80/20 split of BLND/USDC, the value of its USDC is multiplied by 5 to get its full value.
+    let backstop_token_to_base = pool_backstop_data
         .usdc
-        .fixed_mul_floor(oracle_scalar, SCALAR_7)
-        .unwrap_optimized() // adjust for oracle scalar
-        * 5; // Since the backstop LP token is an 80/20 split of USDC/BLND, we multiply by 5 to get the value of the BLND portion
-    let backstop_token_to_base = backstop_value_base
-        .fixed_div_floor(pool_backstop_data.tokens, SCALAR_7)
-        .unwrap_optimized();
+        .fixed_mul_floor(10i128.pow(PRICE_DECIMALS), SCALAR_7)
+        .and_then(|usdc_value| usdc_value.checked_mul(5))
+        .and_then(|backstop_value| {
+            backstop_value.fixed_div_floor(pool_backstop_data.tokens, SCALAR_7)
+        })
+        .unwrap_or(0);
+    if backstop_token_to_base <= 0 {
+        return Err(PoolError::InvalidLot);
+    }
 
     // determine lot amount of backstop tokens needed to safely cover bad debt, or post
     // all backstop tokens if there isn't enough to cover the bad debt
-    let mut lot_amount = debt_value
+    let lot_amount = debt_value
         .fixed_mul_floor(1_4000000, SCALAR_7)
-        .unwrap_optimized()
-        .fixed_div_floor(backstop_token_to_base, SCALAR_7)
-        .unwrap_optimized();
-    lot_amount = pool_backstop_data.tokens.min(lot_amount);
-    auction_data.lot.set(backstop_token, lot_amount);
+        .and_then(|lot_value| lot_value.fixed_div_floor(backstop_token_to_base, SCALAR_7))
+        .ok_or(PoolError::OverflowError)?;
     auction_data
+        .lot
+        .set(backstop_token.clone(), pool_backstop_data.tokens.min(lot_amount));
+    Ok((auction_data, debt_value))
 }
 
 #[allow(clippy::inconsistent_digit_grouping)]
@@ -114,7 +146,7 @@ pub fn fill_bad_debt_auction(
     filler_state.add_positions(e, pool, map![e], auction_data.bid.clone());
 
     let backstop_client = BackstopClient::new(e, &backstop_address);
-    let backstop_token_id = backstop_client.backstop_token();
+    let backstop_token_id = backstop_client.pool_backstop_token(&e.current_contract_address());
     let lot_amount = auction_data.lot.get(backstop_token_id).unwrap_or(0);
     backstop_client.draw(
         &e.current_contract_address(),
@@ -146,9 +178,9 @@ pub fn fill_bad_debt_auction(
 mod tests {
 
     use crate::{
-        auctions::auction::AuctionType,
+        auctions::auction::{try_create_bad_debt_auction, AuctionType},
         pool::Positions,
-        storage::PoolConfig,
+        storage::{AuctionConfig, PoolConfig},
         testutils::{self, create_pool},
     };
 
@@ -206,6 +238,8 @@ mod tests {
             bid: map![&e],
             lot: map![&e],
             block: 50,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         e.as_contract(&pool_address, || {
             storage::set_auction(
@@ -318,6 +352,8 @@ mod tests {
             bid: map![&e],
             lot: map![&e],
             block: 50,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         e.as_contract(&pool_address, || {
             storage::set_auction(
@@ -383,7 +419,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -418,6 +454,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -478,7 +519,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -526,6 +567,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -586,7 +632,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -621,6 +667,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -681,7 +732,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -716,6 +767,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -776,7 +832,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -789,7 +845,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -802,7 +858,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -844,6 +900,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -864,6 +925,185 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_try_create_bad_debt_auction() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited(); // setup exhausts budget
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 50,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool_address = create_pool(&e);
+
+        let (blnd, blnd_client) = testutils::create_blnd_token(&e, &pool_address, &bombadil);
+        let (usdc, usdc_client) = testutils::create_token_contract(&e, &bombadil);
+        let (lp_token, lp_token_client) =
+            testutils::create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (backstop_address, backstop_client) =
+            testutils::create_backstop(&e, &pool_address, &lp_token, &usdc, &blnd);
+        // mint lp tokens
+        blnd_client.mint(&samwise, &500_001_0000000);
+        blnd_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        usdc_client.mint(&samwise, &12_501_0000000);
+        usdc_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        lp_token_client.join_pool(
+            &50_000_0000000,
+            &vec![&e, 500_001_0000000, 12_501_0000000],
+            &samwise,
+        );
+        backstop_client.deposit(&samwise, &pool_address, &50_000_0000000);
+        backstop_client.update_tkn_val();
+
+        let (oracle_id, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.d_rate = 1_100_000_000_000;
+        reserve_data_0.last_time = 12345;
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.d_rate = 1_200_000_000_000;
+        reserve_data_1.last_time = 12345;
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
+        reserve_data_2.b_rate = 1_100_000_000_000;
+        reserve_data_2.last_time = 12345;
+        reserve_config_2.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+                Asset::Stellar(underlying_2),
+                Asset::Stellar(usdc),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000, 100_0000000, 1_0000000]);
+
+        let positions: Positions = Positions {
+            collateral: map![&e],
+            liabilities: map![
+                &e,
+                (reserve_config_0.index, 10_0000000),
+                (reserve_config_1.index, 2_5000000)
+            ],
+            supply: map![&e],
+        };
+
+        let pool_config = PoolConfig {
+            oracle: oracle_id,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let pool_backstop_data = backstop_client.pool_data(&pool_address);
+        e.as_contract(&pool_address, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &backstop_address, &positions);
+
+            // below the threshold
+            storage::set_bad_debt_threshold(&e, &34_0000000_0000001);
+            assert!(try_create_bad_debt_auction(&e, &lp_token, &pool_backstop_data).is_none());
+            assert!(!storage::has_auction(&e, &1, &backstop_address));
+
+            // a stale price doesn't start an auction or revert
+            storage::set_bad_debt_threshold(&e, &34_0000000_0000000);
+            e.ledger().set_timestamp(12345 + 24 * 60 * 60 + 1);
+            assert!(try_create_bad_debt_auction(&e, &lp_token, &pool_backstop_data).is_none());
+            assert!(!storage::has_auction(&e, &1, &backstop_address));
+            e.ledger().set_timestamp(12345);
+
+            storage::set_bad_debt_threshold(&e, &34_0000000_0000000);
+            let result = try_create_bad_debt_auction(&e, &lp_token, &pool_backstop_data).unwrap();
+            assert_eq!(result.block, 51);
+            assert_eq!(result.bid.get_unchecked(underlying_0), 10_0000000);
+            assert_eq!(result.bid.get_unchecked(underlying_1), 2_5000000);
+            assert_eq!(result.bid.len(), 2);
+            assert_eq!(result.lot.get_unchecked(lp_token.clone()), 38_0800000);
+            assert_eq!(result.lot.len(), 1);
+            assert!(storage::has_auction(&e, &1, &backstop_address));
+
+            // an auction is already in progress
+            assert!(try_create_bad_debt_auction(&e, &lp_token, &pool_backstop_data).is_none());
+        });
+    }
+
+    #[test]
+    fn test_try_create_bad_debt_auction_disabled() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let pool_address = create_pool(&e);
+        let backstop_address = Address::generate(&e);
+        let positions: Positions = Positions {
+            collateral: map![&e],
+            liabilities: map![&e, (0, 10_0000000)],
+            supply: map![&e],
+        };
+        let pool_backstop_data = PoolBackstopData {
+            tokens: 1_000_0000000,
+            q4w_pct: 0,
+            blnd: 10_000_0000000,
+            usdc: 250_0000000,
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_backstop(&e, &backstop_address);
+            storage::set_user_positions(&e, &backstop_address, &positions);
+
+            let backstop_token = Address::generate(&e);
+            assert!(
+                try_create_bad_debt_auction(&e, &backstop_token, &pool_backstop_data).is_none()
+            );
+        });
+    }
+
+
     #[test]
     fn test_create_bad_debt_auction() {
         let e = Env::default();
@@ -908,7 +1148,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -921,7 +1161,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -934,7 +1174,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -975,6 +1215,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -1041,7 +1286,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -1054,7 +1299,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -1067,7 +1312,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -1114,6 +1359,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -1180,7 +1430,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -1193,7 +1443,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -1206,7 +1456,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -1247,6 +1497,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -1314,7 +1569,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 11845;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -1327,7 +1582,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 11845;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -1340,7 +1595,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 11845;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -1381,6 +1636,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -1399,7 +1659,7 @@ mod tests {
             assert_eq!(result.bid.get_unchecked(underlying_0), 10_0000000);
             assert_eq!(result.bid.get_unchecked(underlying_1), 2_5000000);
             assert_eq!(result.bid.len(), 2);
-            assert_eq!(result.lot.get_unchecked(lp_token), 38_0801894);
+            assert_eq!(result.lot.get_unchecked(lp_token), 38_0801895);
             assert_eq!(result.lot.len(), 1);
         });
     }
@@ -1448,7 +1708,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -1461,7 +1721,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -1474,7 +1734,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -1515,6 +1775,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -1579,7 +1844,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -1592,7 +1857,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -1605,7 +1870,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -1620,11 +1885,18 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let mut auction_data = AuctionData {
             bid: map![&e, (underlying_0, 10_0000000), (underlying_1, 2_5000000)],
             lot: map![&e, (lp_token.clone(), 47_6000000)],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let positions: Positions = Positions {
             collateral: map![&e],
@@ -1716,7 +1988,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -1729,7 +2001,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -1742,7 +2014,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -1757,6 +2029,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let mut auction_data = AuctionData {
             bid: map![
@@ -1766,6 +2043,8 @@ mod tests {
             ],
             lot: map![&e, (lp_token.clone(), 47_6000000)],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let positions: Positions = Positions {
             collateral: map![&e],
@@ -1876,7 +2155,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -1889,7 +2168,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -1902,7 +2181,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -1917,6 +2196,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let mut auction_data = AuctionData {
             bid: map![
@@ -1926,6 +2210,8 @@ mod tests {
             ],
             lot: map![&e, (lp_token.clone(), 47_6000000)],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let positions: Positions = Positions {
             collateral: map![&e],
@@ -2046,7 +2332,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -2059,7 +2345,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -2072,7 +2358,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -2087,11 +2373,18 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let mut auction_data = AuctionData {
             bid: map![&e],
             lot: map![&e, (lp_token.clone(), 47_6000000)],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let positions: Positions = Positions {
             collateral: map![&e],
@@ -2185,7 +2478,7 @@ mod tests {
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.d_rate = 1_100_000_000;
+        reserve_data_0.d_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -2198,7 +2491,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.d_rate = 1_200_000_000;
+        reserve_data_1.d_rate = 1_200_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -2211,7 +2504,7 @@ mod tests {
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -2226,11 +2519,18 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let mut auction_data = AuctionData {
             bid: map![&e, (underlying_0, 10_0000000), (underlying_1, 2_5000000)],
             lot: map![&e, (lp_token.clone(), 47_6000000)],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let positions: Positions = Positions {
             collateral: map![&e],
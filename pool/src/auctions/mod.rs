@@ -1,6 +1,16 @@
 mod auction;
+#[cfg(feature = "auctions")]
 mod backstop_interest_auction;
+#[cfg(feature = "auctions")]
 mod bad_debt_auction;
+#[cfg(feature = "auctions")]
+mod risk_bot;
+#[cfg(feature = "auctions")]
 mod user_liquidation_auction;
 
 pub use auction::*;
+#[cfg(feature = "auctions")]
+pub use risk_bot::{
+    execute_batch_liquidations, execute_queue_unstake_risk_bot, execute_stake_risk_bot,
+    execute_unstake_risk_bot, LiquidationRequest,
+};
@@ -0,0 +1,442 @@
+use sep_41_token::TokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{contracttype, panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Vec};
+
+use crate::{
+    constants::{MIN_RISK_BOT_STAKE, RISK_BOT_SLASH_RATE, RISK_BOT_UNSTAKE_DELAY, SCALAR_7},
+    errors::PoolError,
+    events::PoolEvents,
+    pool::{settle_fixed_loans, Pool, PositionData, User},
+    storage::{self, RiskBotUnstake},
+};
+
+use super::{create_auction, require_allowed_liquidator, AuctionType};
+
+/// A user liquidation auction requested by a risk bot
+#[derive(Clone)]
+#[contracttype]
+pub struct LiquidationRequest {
+    pub user: Address,     // the user being liquidated
+    pub bid: Vec<Address>, // the liability assets to include in the auction bid
+    pub lot: Vec<Address>, // the collateral assets to include in the auction lot
+    pub percent: u32,      // the percent of the user's positions to liquidate
+}
+
+/// Stake BLND for a risk bot. Returns the bot's new stake.
+///
+/// ### Arguments
+/// * `bot` - The address of the risk bot
+/// * `amount` - The amount of BLND to stake
+///
+/// ### Panics
+/// If the amount is not positive
+pub fn execute_stake_risk_bot(e: &Env, bot: &Address, amount: i128) -> i128 {
+    if amount <= 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    TokenClient::new(e, &storage::get_blnd_token(e)).transfer(
+        bot,
+        &e.current_contract_address(),
+        &amount,
+    );
+    let stake = storage::get_risk_bot_stake(e, bot) + amount;
+    storage::set_risk_bot_stake(e, bot, stake);
+    stake
+}
+
+/// Queue BLND for a risk bot to unstake once the unstake delay has passed. Queued BLND stays
+/// staked, and can be slashed, until it is unstaked. Queuing more BLND restarts the delay.
+/// Returns the bot's queued unstake.
+///
+/// ### Arguments
+/// * `bot` - The address of the risk bot
+/// * `amount` - The amount of BLND to queue
+///
+/// ### Panics
+/// If the amount is not positive or the total queued is greater than the bot's stake
+pub fn execute_queue_unstake_risk_bot(e: &Env, bot: &Address, amount: i128) -> RiskBotUnstake {
+    let stake = storage::get_risk_bot_stake(e, bot);
+    let queued = storage::get_risk_bot_unstake(e, bot).map_or(0, |unstake| unstake.amount);
+    if amount <= 0 || queued + amount > stake {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let unstake = RiskBotUnstake {
+        amount: queued + amount,
+        unlock_time: e.ledger().timestamp() + RISK_BOT_UNSTAKE_DELAY,
+    };
+    storage::set_risk_bot_unstake(e, bot, &Some(unstake.clone()));
+    unstake
+}
+
+/// Unstake queued BLND for a risk bot once the unstake delay has passed. Returns the bot's new
+/// stake.
+///
+/// ### Arguments
+/// * `bot` - The address of the risk bot
+/// * `amount` - The amount of BLND to unstake
+///
+/// ### Panics
+/// If the amount is not positive or is greater than the bot's queued BLND or stake, or if the
+/// unstake delay has not passed
+pub fn execute_unstake_risk_bot(e: &Env, bot: &Address, amount: i128) -> i128 {
+    let mut unstake = storage::get_risk_bot_unstake(e, bot)
+        .unwrap_or_else(|| panic_with_error!(e, PoolError::BadRequest));
+    let stake = storage::get_risk_bot_stake(e, bot);
+    if amount <= 0 || amount > unstake.amount || amount > stake {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    if e.ledger().timestamp() < unstake.unlock_time {
+        panic_with_error!(e, PoolError::RiskBotStakeLocked);
+    }
+    unstake.amount -= amount;
+    if unstake.amount > 0 {
+        storage::set_risk_bot_unstake(e, bot, &Some(unstake));
+    } else {
+        storage::set_risk_bot_unstake(e, bot, &None);
+    }
+    let stake = stake - amount;
+    storage::set_risk_bot_stake(e, bot, stake);
+    TokenClient::new(e, &storage::get_blnd_token(e)).transfer(
+        &e.current_contract_address(),
+        bot,
+        &amount,
+    );
+    stake
+}
+
+/// Create user liquidation auctions for a batch of users on behalf of a staked risk bot.
+///
+/// Each user's health factor is re-verified. Requests for healthy users are skipped and
+/// slash a portion of the bot's stake, which is sent to the backstop. All other requests
+/// must create a valid auction.
+///
+/// Returns the users an auction was created for
+///
+/// ### Arguments
+/// * `bot` - The address of the risk bot
+/// * `requests` - The liquidation auctions to create
+///
+/// ### Panics
+/// If the bot's stake is under the minimum or any auction for an unhealthy user
+/// is unable to be created
+pub fn execute_batch_liquidations(
+    e: &Env,
+    bot: &Address,
+    requests: &Vec<LiquidationRequest>,
+) -> Vec<Address> {
+    let mut stake = storage::get_risk_bot_stake(e, bot);
+    if stake < MIN_RISK_BOT_STAKE {
+        panic_with_error!(e, PoolError::InsufficientRiskBotStake);
+    }
+    require_allowed_liquidator(e, Some(bot));
+
+    let mut pool = Pool::load(e);
+    pool.skip_price_deviation_check();
+    let mut total_slashed = 0;
+    let mut created = vec![e];
+    for request in requests.iter() {
+        let mut user_state = User::load(e, &request.user);
+        if settle_fixed_loans(e, &mut pool, &mut user_state) {
+            pool.store_cached_reserves(e);
+            user_state.store(e);
+        }
+        let emode = user_state.emode(e);
+        let position_data =
+            PositionData::calculate_from_positions(e, &mut pool, &user_state.positions, emode);
+        if position_data.liability_base < position_data.collateral_base {
+            let slashed = stake
+                .fixed_mul_floor(RISK_BOT_SLASH_RATE, SCALAR_7)
+                .unwrap_optimized();
+            stake -= slashed;
+            total_slashed += slashed;
+            PoolEvents::slash_risk_bot(e, bot.clone(), request.user.clone(), slashed);
+            continue;
+        }
+
+        let auction_data = create_auction(
+            e,
+            AuctionType::UserLiquidation as u32,
+            &request.user,
+            &request.bid,
+            &request.lot,
+            request.percent,
+        );
+        if auction_data.bid.is_empty() {
+            // the user's liquidation protection restored their health
+            continue;
+        }
+        PoolEvents::new_auction(
+            e,
+            AuctionType::UserLiquidation as u32,
+            request.user.clone(),
+            request.percent,
+            auction_data,
+        );
+        created.push_back(request.user);
+    }
+
+    if total_slashed > 0 {
+        storage::set_risk_bot_stake(e, bot, stake);
+        TokenClient::new(e, &storage::get_blnd_token(e)).transfer(
+            &e.current_contract_address(),
+            &storage::get_backstop(e),
+            &total_slashed,
+        );
+    }
+    created
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pool::Positions,
+        storage::PoolConfig,
+        testutils::{self, create_pool},
+    };
+    use sep_40_oracle::testutils::Asset;
+    use soroban_sdk::{
+        map,
+        testutils::{Address as _, Ledger, LedgerInfo},
+        Symbol,
+    };
+
+    #[test]
+    fn test_stake_and_unstake_risk_bot() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let bot = Address::generate(&e);
+        let pool = create_pool(&e);
+        let (_, blnd_client) = testutils::create_blnd_token(&e, &pool, &bombadil);
+        blnd_client.mint(&bot, &2_000_0000000);
+
+        e.as_contract(&pool, || {
+            let stake = execute_stake_risk_bot(&e, &bot, 1_500_0000000);
+            assert_eq!(stake, 1_500_0000000);
+            assert_eq!(storage::get_risk_bot_stake(&e, &bot), 1_500_0000000);
+            assert_eq!(blnd_client.balance(&bot), 500_0000000);
+
+            let unstake = execute_queue_unstake_risk_bot(&e, &bot, 300_0000000);
+            assert_eq!(unstake.amount, 300_0000000);
+            assert_eq!(unstake.unlock_time, RISK_BOT_UNSTAKE_DELAY);
+            e.ledger().set_timestamp(100);
+            let unstake = execute_queue_unstake_risk_bot(&e, &bot, 200_0000000);
+            assert_eq!(unstake.amount, 500_0000000);
+            assert_eq!(unstake.unlock_time, 100 + RISK_BOT_UNSTAKE_DELAY);
+            // queued BLND stays staked
+            assert_eq!(storage::get_risk_bot_stake(&e, &bot), 1_500_0000000);
+
+            e.ledger().set_timestamp(100 + RISK_BOT_UNSTAKE_DELAY);
+            let stake = execute_unstake_risk_bot(&e, &bot, 400_0000000);
+            assert_eq!(stake, 1_100_0000000);
+            assert_eq!(storage::get_risk_bot_stake(&e, &bot), 1_100_0000000);
+            assert_eq!(blnd_client.balance(&bot), 900_0000000);
+            let unstake = storage::get_risk_bot_unstake(&e, &bot).unwrap();
+            assert_eq!(unstake.amount, 100_0000000);
+
+            let stake = execute_unstake_risk_bot(&e, &bot, 100_0000000);
+            assert_eq!(stake, 1_000_0000000);
+            assert_eq!(blnd_client.balance(&bot), 1_000_0000000);
+            assert!(storage::get_risk_bot_unstake(&e, &bot).is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1248)")]
+    fn test_unstake_risk_bot_before_delay() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let bot = Address::generate(&e);
+        let pool = create_pool(&e);
+        let (_, blnd_client) = testutils::create_blnd_token(&e, &pool, &bombadil);
+        blnd_client.mint(&bot, &2_000_0000000);
+
+        e.as_contract(&pool, || {
+            execute_stake_risk_bot(&e, &bot, 1_000_0000000);
+            execute_queue_unstake_risk_bot(&e, &bot, 500_0000000);
+            e.ledger().set_timestamp(RISK_BOT_UNSTAKE_DELAY - 1);
+            execute_unstake_risk_bot(&e, &bot, 500_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_queue_unstake_risk_bot_over_stake() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let bot = Address::generate(&e);
+        let pool = create_pool(&e);
+        let (_, blnd_client) = testutils::create_blnd_token(&e, &pool, &bombadil);
+        blnd_client.mint(&bot, &2_000_0000000);
+
+        e.as_contract(&pool, || {
+            execute_stake_risk_bot(&e, &bot, 1_000_0000000);
+            execute_queue_unstake_risk_bot(&e, &bot, 600_0000000);
+            execute_queue_unstake_risk_bot(&e, &bot, 400_0000001);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1226)")]
+    fn test_batch_liquidations_requires_stake() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let bot = Address::generate(&e);
+        let pool = create_pool(&e);
+        let (_, blnd_client) = testutils::create_blnd_token(&e, &pool, &bombadil);
+        blnd_client.mint(&bot, &2_000_0000000);
+
+        e.as_contract(&pool, || {
+            execute_stake_risk_bot(&e, &bot, MIN_RISK_BOT_STAKE - 1);
+            execute_batch_liquidations(&e, &bot, &vec![&e]);
+        });
+    }
+
+    #[test]
+    fn test_batch_liquidations() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.cost_estimate().budget().reset_unlimited();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 50,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let bot = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
+        let backstop_address = Address::generate(&e);
+        let (_, blnd_client) = testutils::create_blnd_token(&e, &pool_address, &bombadil);
+        blnd_client.mint(&bot, &2_000_0000000);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.last_time = 12345;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
+        reserve_config_0.c_factor = 0_8500000;
+        reserve_config_0.l_factor = 0_9000000;
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 12345;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
+        reserve_config_1.c_factor = 0_8000000;
+        reserve_config_1.l_factor = 0_7500000;
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
+
+        let unhealthy_positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 50_0000000),
+                (reserve_config_1.index, 30_0000000),
+            ],
+            liabilities: map![
+                &e,
+                (reserve_config_0.index, 30_0000000),
+                (reserve_config_1.index, 20_0000000),
+            ],
+            supply: map![&e],
+        };
+        let healthy_positions = Positions {
+            collateral: map![&e, (reserve_config_0.index, 50_0000000)],
+            liabilities: map![&e, (reserve_config_1.index, 5_0000000)],
+            supply: map![&e],
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &unhealthy_positions);
+            storage::set_user_positions(&e, &frodo, &healthy_positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_backstop(&e, &backstop_address);
+
+            execute_stake_risk_bot(&e, &bot, 2_000_0000000);
+
+            let requests = vec![
+                &e,
+                LiquidationRequest {
+                    user: frodo.clone(),
+                    bid: vec![&e, underlying_1.clone()],
+                    lot: vec![&e, underlying_0.clone()],
+                    percent: 50,
+                },
+                LiquidationRequest {
+                    user: samwise.clone(),
+                    bid: vec![&e, underlying_0.clone()],
+                    lot: vec![&e, underlying_1.clone()],
+                    percent: 85,
+                },
+            ];
+            let created = execute_batch_liquidations(&e, &bot, &requests);
+            assert_eq!(created, vec![&e, samwise.clone()]);
+
+            // healthy user is skipped and the bot is slashed
+            assert!(!storage::has_auction(&e, &0, &frodo));
+            assert_eq!(storage::get_risk_bot_stake(&e, &bot), 1_800_0000000);
+            assert_eq!(blnd_client.balance(&backstop_address), 200_0000000);
+
+            let auction = storage::get_auction(&e, &0, &samwise);
+            assert_eq!(auction.block, 51);
+            assert_eq!(auction.bid.get_unchecked(underlying_0.clone()), 25_5000000);
+            assert_eq!(auction.lot.get_unchecked(underlying_1.clone()), 13_9293750);
+        });
+    }
+}
@@ -1,10 +1,17 @@
 use cast::i128;
+use sep_41_token::TokenClient;
 use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::unwrap::UnwrapOptimized;
 use soroban_sdk::{map, panic_with_error, Address, Env, Vec};
 
 use crate::auctions::auction::AuctionData;
-use crate::pool::{Pool, PositionData, User};
+use crate::constants::SCALAR_7;
+use crate::dependencies::LiquidationProtectionClient;
+use crate::events::PoolEvents;
+use crate::pool::{
+    remove_liquidated_isolated_debt, settle_fixed_loans, Pool, PositionData, User,
+};
+use crate::RequestType;
 use crate::Positions;
 use crate::{errors::PoolError, storage};
 
@@ -13,6 +20,7 @@ use super::AuctionType;
 pub fn create_user_liq_auction_data(
     e: &Env,
     user: &Address,
+    account_id: u32,
     bid: &Vec<Address>,
     lot: &Vec<Address>,
     percent: u32,
@@ -26,26 +34,54 @@ pub fn create_user_liq_auction_data(
     if percent > 100 || percent == 0 {
         panic_with_error!(e, PoolError::InvalidLiquidation);
     }
+    let liq_config = storage::get_liquidation_config(e);
+    if percent < liq_config.min_close_factor {
+        panic_with_error!(e, PoolError::InvalidLiqTooSmall);
+    }
+    let max_hf = i128(liq_config.max_hf);
+    let auction_config = storage::get_auction_config(e);
+    if protect_from_liquidation(e, user, account_id) {
+        return AuctionData {
+            bid: map![e],
+            lot: map![e],
+            block: e.ledger().sequence(),
+            account_id,
+            config: auction_config,
+        };
+    }
 
     let mut liquidation_quote = AuctionData {
         bid: map![e],
         lot: map![e],
         block: e.ledger().sequence() + 1,
+        account_id,
+        config: auction_config.clone(),
     };
     let mut full_liquidation_quote = AuctionData {
         bid: map![e],
         lot: map![e],
         block: e.ledger().sequence() + 1,
+        account_id,
+        config: auction_config.clone(),
     };
     let mut pool = Pool::load(e);
     if pool.config.max_positions < (lot.len() + bid.len()) {
         panic_with_error!(e, PoolError::MaxPositionsExceeded);
     }
-
-    // this is used for checking the liquidation percent and should NOT be set
-    let mut user_state = User::load(e, user);
+    pool.set_twap_records(e, liq_config.twap_records);
+    pool.skip_price_deviation_check();
+
+    // this is used for checking the liquidation percent and should NOT be set once the
+    // fixed-rate loans are settled, so the liabilities are auctioned at their fixed value
+    let mut user_state = User::load_account(e, user, account_id);
+    if settle_fixed_loans(e, &mut pool, &mut user_state) {
+        pool.store_cached_reserves(e);
+        user_state.store(e);
+    }
     let reserve_list = storage::get_res_list(e);
-    let position_data = PositionData::calculate_from_positions(e, &mut pool, &user_state.positions);
+    let emode = user_state.emode(e);
+    let position_data =
+        PositionData::calculate_from_positions(e, &mut pool, &user_state.positions, emode);
 
     // ensure the user has less collateral than liabilities
     if position_data.liability_base < position_data.collateral_base {
@@ -85,7 +121,7 @@ pub fn create_user_liq_auction_data(
         panic_with_error!(e, PoolError::InvalidLot);
     }
     let position_data_inc =
-        PositionData::calculate_from_positions(e, &mut pool, &positions_auctioned);
+        PositionData::calculate_from_positions(e, &mut pool, &positions_auctioned, emode);
     let is_all_collateral = position_data_inc.collateral_raw == position_data.collateral_raw;
     let is_all_positions =
         is_all_collateral && position_data_inc.liability_raw == position_data.liability_raw;
@@ -118,6 +154,12 @@ pub fn create_user_liq_auction_data(
     .fixed_div_ceil(2 * position_data_inc.scalar, position_data_inc.scalar)
     .unwrap_optimized()
         + position_data_inc.scalar;
+    // cap the incentive at the pool's max lot premium
+    let max_incentive = i128(auction_config.max_premium)
+        .fixed_mul_floor(position_data_inc.scalar, SCALAR_7)
+        .unwrap_optimized()
+        + position_data_inc.scalar;
+    let est_incentive = est_incentive.min(max_incentive);
 
     let est_withdrawn_collateral = position_data_inc
         .liability_raw
@@ -168,7 +210,8 @@ pub fn create_user_liq_auction_data(
         liquidation_quote.lot.clone(),
         liquidation_quote.bid.clone(),
     );
-    let new_data = PositionData::calculate_from_positions(e, &mut pool, &user_state.positions);
+    let new_data =
+        PositionData::calculate_from_positions(e, &mut pool, &user_state.positions, emode);
 
     if is_full_liquidation {
         // A full user liquidation was requested, validate that a full liquidation is not too large.
@@ -176,14 +219,14 @@ pub fn create_user_liq_auction_data(
         // 95% liquidation is not too large. That is, if a user can be liquidated to 95%, they can
         // be liquidated fully. This helps prevent edge cases due to liquidation percentages
         // being harder to calculate between as it approaches 100.
-        if est_withdrawn_collateral < position_data.collateral_raw && new_data.is_hf_over(1_1500000)
-        {
+        if est_withdrawn_collateral < position_data.collateral_raw && new_data.is_hf_over(max_hf) {
             panic_with_error!(e, PoolError::InvalidLiqTooLarge)
         };
         full_liquidation_quote
     } else {
-        // Post-liq health factor must be under 1.15
-        if new_data.is_hf_over(1_1500000) {
+        // Post-liq health factor must be under the max, unless only the minimum close factor
+        // is being liquidated
+        if percent > liq_config.min_close_factor && new_data.is_hf_over(max_hf) {
             panic_with_error!(e, PoolError::InvalidLiqTooLarge)
         };
 
@@ -195,6 +238,118 @@ pub fn create_user_liq_auction_data(
     }
 }
 
+/// Give the liquidation protection contract registered by the user, if any, one chance to
+/// supply collateral for the user's sub-account before a liquidation auction is created. Failed
+/// callbacks and collateral that can't be pulled from the protection contract are ignored.
+///
+/// Returns true if the user's sub-account is no longer eligible for liquidation
+fn protect_from_liquidation(e: &Env, user: &Address, account_id: u32) -> bool {
+    let protection = match storage::get_liquidation_protection(e, user) {
+        Some(protection) => protection,
+        None => return false,
+    };
+    // top ups are supplied as collateral, which siloed users have not opted in to
+    if storage::get_user_siloed(e, user) {
+        return false;
+    }
+
+    let mut pool = Pool::load(e);
+    let mut user_state = User::load_account(e, user, account_id);
+    let emode = user_state.emode(e);
+    let position_data =
+        PositionData::calculate_from_positions(e, &mut pool, &user_state.positions, emode);
+    if position_data.liability_base < position_data.collateral_base {
+        return false;
+    }
+    let shortfall = position_data.liability_base - position_data.collateral_base;
+
+    let pool_address = e.current_contract_address();
+    let top_ups = match LiquidationProtectionClient::new(e, &protection)
+        .try_on_liquidation_warning(&pool_address, user, &shortfall)
+    {
+        Ok(Ok(top_ups)) => top_ups,
+        _ => return false,
+    };
+    for (asset, amount) in top_ups.iter() {
+        if amount <= 0 || !storage::has_res(e, &asset) {
+            continue;
+        }
+        let mut reserve = pool.load_reserve(e, &asset, true);
+        let supply_type = RequestType::SupplyCollateral as u32;
+        if reserve.check_action_allowed(e, supply_type).is_err() {
+            continue;
+        }
+        let pulled = TokenClient::new(e, &asset).try_transfer_from(
+            &pool_address,
+            &protection,
+            &pool_address,
+            &amount,
+        );
+        if !matches!(pulled, Ok(Ok(()))) {
+            continue;
+        }
+        let b_tokens_minted = reserve.to_b_token_down(amount);
+        user_state.add_collateral(e, &mut reserve, b_tokens_minted);
+        reserve.record_supply(e, &user_state);
+        pool.cache_reserve(reserve);
+        PoolEvents::supply_collateral(e, asset, user.clone(), amount, b_tokens_minted);
+    }
+    pool.store_cached_reserves(e);
+    user_state.store(e);
+
+    let new_data =
+        PositionData::calculate_from_positions(e, &mut pool, &user_state.positions, emode);
+    let is_protected = new_data.liability_base < new_data.collateral_base;
+    PoolEvents::liquidation_warning(e, user.clone(), account_id, shortfall, is_protected);
+    is_protected
+}
+
+/// Require the bid of a user liquidation fill to be worth at least `floor` of its lot at oracle
+/// prices. Protects users from being liquidated at an extreme discount when the auction is left
+/// unfilled deep into the bid phase, i.e. during network congestion.
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `auction_data` - The scaled auction being filled
+/// * `floor` - The min value of the bid over the lot (7 decimals), or 0 for no floor
+///
+/// ### Panics
+/// If the bid is worth less than `floor` of the lot
+pub fn require_fill_above_floor(
+    e: &Env,
+    pool: &mut Pool,
+    auction_data: &AuctionData,
+    floor: u32,
+) {
+    if floor == 0 {
+        return;
+    }
+    let mut bid_base = 0;
+    for (asset, amount) in auction_data.bid.iter() {
+        let reserve = pool.load_reserve(e, &asset, false);
+        let price = pool.load_price(e, &asset);
+        bid_base += reserve
+            .to_asset_from_d_token(amount)
+            .fixed_mul_floor(price, reserve.scalar)
+            .unwrap_optimized();
+    }
+    let mut lot_base = 0;
+    for (asset, amount) in auction_data.lot.iter() {
+        let reserve = pool.load_reserve(e, &asset, false);
+        let price = pool.load_price(e, &asset);
+        lot_base += reserve
+            .to_asset_from_b_token(amount)
+            .fixed_mul_ceil(price, reserve.scalar)
+            .unwrap_optimized();
+    }
+    let min_bid_base = lot_base
+        .fixed_mul_ceil(i128(floor), SCALAR_7)
+        .unwrap_optimized();
+    if bid_base < min_bid_base {
+        panic_with_error!(e, PoolError::AuctionBelowFloor);
+    }
+}
+
 pub fn fill_user_liq_auction(
     e: &Env,
     pool: &mut Pool,
@@ -202,8 +357,10 @@ pub fn fill_user_liq_auction(
     user: &Address,
     filler_state: &mut User,
 ) {
-    let mut user_state = User::load(e, user);
+    let mut user_state = User::load_account(e, user, auction_data.account_id);
+    let prior_user_state = user_state.clone();
     user_state.rm_positions(e, pool, auction_data.lot.clone(), auction_data.bid.clone());
+    remove_liquidated_isolated_debt(e, pool, &prior_user_state, &user_state, &auction_data.bid);
     filler_state.add_positions(e, pool, auction_data.lot.clone(), auction_data.bid.clone());
     user_state.store(e);
 }
@@ -214,17 +371,51 @@ mod tests {
     use crate::{
         auctions::auction::AuctionType,
         pool::Positions,
-        storage::{self, PoolConfig},
+        storage::{self, AuctionConfig, FixedLoan, LiquidationConfig, PoolConfig},
         testutils::{self, create_pool},
     };
 
     use super::*;
     use sep_40_oracle::testutils::Asset;
     use soroban_sdk::{
+        contract, contractimpl,
         testutils::{Address as AddressTestTrait, Ledger, LedgerInfo},
         vec, Symbol,
     };
 
+    /// A liquidation protection contract that always offers the same top up
+    #[contract]
+    struct MockProtection;
+
+    #[contractimpl]
+    impl MockProtection {
+        pub fn set_top_up(e: Env, asset: Address, amount: i128) {
+            e.storage()
+                .instance()
+                .set(&Symbol::new(&e, "TopUp"), &(asset, amount));
+        }
+
+        pub fn on_liquidation_warning(
+            e: Env,
+            pool: Address,
+            _user: Address,
+            _shortfall: i128,
+        ) -> Vec<(Address, i128)> {
+            let (asset, amount): (Address, i128) = e
+                .storage()
+                .instance()
+                .get(&Symbol::new(&e, "TopUp"))
+                .unwrap();
+            TokenClient::new(&e, &asset).approve(
+                &e.current_contract_address(),
+                &pool,
+                &amount,
+                &e.ledger().sequence(),
+            );
+            vec![&e, (asset, amount)]
+        }
+    }
+
     #[test]
     #[should_panic(expected = "Error(Contract, #1212)")]
     fn test_create_liquidation_already_in_progress() {
@@ -254,12 +445,19 @@ mod tests {
             bid: map![&e],
             lot: map![&e],
             block: 50,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
@@ -270,7 +468,7 @@ mod tests {
                 &samwise,
                 &auction_data,
             );
-            create_user_liq_auction_data(&e, &samwise, &vec![&e], &vec![&e], liq_pct);
+            create_user_liq_auction_data(&e, &samwise, 0, &vec![&e], &vec![&e], liq_pct);
         });
     }
 
@@ -301,11 +499,16 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
             storage::set_backstop(&e, &backstop_address);
-            create_user_liq_auction_data(&e, &pool_address, &vec![&e], &vec![&e], liq_pct);
+            create_user_liq_auction_data(&e, &pool_address, 0, &vec![&e], &vec![&e], liq_pct);
         });
     }
 
@@ -336,11 +539,16 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
             storage::set_backstop(&e, &backstop_address);
-            create_user_liq_auction_data(&e, &backstop_address, &vec![&e], &vec![&e], liq_pct);
+            create_user_liq_auction_data(&e, &backstop_address, 0, &vec![&e], &vec![&e], liq_pct);
         });
     }
 
@@ -371,11 +579,16 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
             storage::set_backstop(&e, &backstop_address);
-            create_user_liq_auction_data(&e, &backstop_address, &vec![&e], &vec![&e], liq_pct);
+            create_user_liq_auction_data(&e, &backstop_address, 0, &vec![&e], &vec![&e], liq_pct);
         });
     }
 
@@ -406,11 +619,16 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_pool_config(&e, &pool_config);
             storage::set_backstop(&e, &backstop_address);
-            create_user_liq_auction_data(&e, &backstop_address, &vec![&e], &vec![&e], liq_pct);
+            create_user_liq_auction_data(&e, &backstop_address, 0, &vec![&e], &vec![&e], liq_pct);
         });
     }
 
@@ -443,8 +661,8 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -459,8 +677,8 @@ mod tests {
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
         reserve_config_1.c_factor = 0_8000000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_config_1.index = 1;
@@ -496,6 +714,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -505,6 +728,7 @@ mod tests {
             create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e],
                 &vec![&e, underlying_0.clone()],
                 liq_pct,
@@ -541,8 +765,8 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -557,8 +781,8 @@ mod tests {
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
         reserve_config_1.c_factor = 0_8000000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_config_1.index = 1;
@@ -594,6 +818,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -603,6 +832,7 @@ mod tests {
             create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_0.clone()],
                 &vec![&e, underlying_0.clone()],
                 liq_pct,
@@ -639,8 +869,8 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -655,8 +885,8 @@ mod tests {
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
         reserve_config_1.c_factor = 0_8000000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_config_1.index = 1;
@@ -692,6 +922,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -701,6 +936,7 @@ mod tests {
             create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_1.clone()],
                 &vec![&e],
                 liq_pct,
@@ -737,8 +973,8 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -753,8 +989,8 @@ mod tests {
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
         reserve_config_1.c_factor = 0_8000000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_config_1.index = 1;
@@ -790,6 +1026,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -799,6 +1040,7 @@ mod tests {
             create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_1.clone()],
                 &vec![&e, underlying_1.clone()],
                 liq_pct,
@@ -835,7 +1077,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -849,7 +1091,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -904,6 +1146,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -913,6 +1160,7 @@ mod tests {
             create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_2.clone()],
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 liq_pct,
@@ -948,7 +1196,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -962,7 +1210,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -1017,6 +1265,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -1026,6 +1279,7 @@ mod tests {
             let result = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_2.clone()],
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 liq_pct,
@@ -1067,7 +1321,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_000_206_159;
+        reserve_data_0.b_rate = 1_000_206_159_000;
         reserve_config_0.c_factor = 0_9000000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1084,7 +1338,7 @@ mod tests {
         reserve_config_1.c_factor = 0_0000000;
         reserve_config_1.l_factor = 0_9000000;
         reserve_config_1.index = 1;
-        reserve_data_1.d_rate = 1000201748;
+        reserve_data_1.d_rate = 1000201748000;
         testutils::create_reserve(
             &e,
             &pool_address,
@@ -1117,6 +1371,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -1126,6 +1385,7 @@ mod tests {
             let result = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_1.clone()],
                 &vec![&e, underlying_0.clone()],
                 liq_pct,
@@ -1133,7 +1393,7 @@ mod tests {
             assert_eq!(result.block, 51);
             assert_eq!(result.bid.get_unchecked(underlying_1), 731_0913452);
             assert_eq!(result.bid.len(), 1);
-            assert_eq!(result.lot.get_unchecked(underlying_0), 5791_1010751);
+            assert_eq!(result.lot.get_unchecked(underlying_0), 5791_1022489);
             assert_eq!(result.lot.len(), 1);
         });
     }
@@ -1166,7 +1426,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_000_206_159;
+        reserve_data_0.b_rate = 1_000_206_159_000;
         reserve_config_0.c_factor = 0_9000000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1184,7 +1444,7 @@ mod tests {
         reserve_config_1.l_factor = 0_9000000;
         reserve_config_1.index = 1;
         reserve_config_1.decimals = 6;
-        reserve_data_1.d_rate = 1000201748;
+        reserve_data_1.d_rate = 1000201748000;
         testutils::create_reserve(
             &e,
             &pool_address,
@@ -1217,6 +1477,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -1226,6 +1491,7 @@ mod tests {
             let result = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_1.clone()],
                 &vec![&e, underlying_0.clone()],
                 liq_pct,
@@ -1264,7 +1530,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_000_206_159;
+        reserve_data_0.b_rate = 1_000_206_159_000;
         reserve_config_0.c_factor = 0_9000000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1281,7 +1547,7 @@ mod tests {
         reserve_config_1.c_factor = 0_5000000;
         reserve_config_1.l_factor = 0_8000000;
         reserve_config_1.index = 1;
-        reserve_data_1.d_rate = 1_050_001_748;
+        reserve_data_1.d_rate = 1_050_001_748_000;
         testutils::create_reserve(
             &e,
             &pool_address,
@@ -1323,6 +1589,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -1332,6 +1603,7 @@ mod tests {
             let result = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 liq_pct,
@@ -1372,8 +1644,8 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1388,8 +1660,8 @@ mod tests {
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
         reserve_config_1.c_factor = 0_8000000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_config_1.index = 1;
@@ -1433,6 +1705,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -1443,6 +1720,7 @@ mod tests {
             let result_95 = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 95,
@@ -1471,6 +1749,7 @@ mod tests {
             let result_100 = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 100,
@@ -1527,7 +1806,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1541,7 +1820,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -1587,6 +1866,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1605,6 +1889,7 @@ mod tests {
             create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_2.clone()],
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 liq_pct,
@@ -1642,7 +1927,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1656,7 +1941,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -1702,6 +1987,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1720,6 +2010,7 @@ mod tests {
             create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_2.clone()],
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 liq_pct,
@@ -1728,8 +2019,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1214)")]
-    fn test_create_user_liquidation_auction_too_small() {
+    fn test_create_user_liquidation_auction_min_close_factor_skips_max_hf() {
         let e = Env::default();
 
         e.mock_all_auths();
@@ -1757,7 +2047,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1771,7 +2061,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -1806,17 +2096,22 @@ mod tests {
                 Asset::Stellar(underlying_1.clone()),
                 Asset::Stellar(underlying_2.clone()),
             ],
-            &5,
+            &6,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 2_00000, 4_00000, 50_00000]);
+        oracle_client.set_price_stable(&vec![&e, 2_000000, 4_000000, 50_000000]);
 
-        let liq_pct = 25;
+        let liq_pct = 46;
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1831,23 +2126,35 @@ mod tests {
             storage::set_user_positions(&e, &samwise, &positions);
             storage::set_pool_config(&e, &pool_config);
             storage::set_backstop(&e, &backstop_address);
+            storage::set_liquidation_config(
+                &e,
+                &LiquidationConfig {
+                    min_close_factor: 46,
+                    max_hf: 1_1500000,
+                    twap_records: 0,
+                    target_hf: 1_1000000,
+                },
+            );
 
-            create_user_liq_auction_data(
+            let result = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_2.clone()],
                 &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 liq_pct,
             );
+            assert_eq!(result.bid.len(), 1);
+            assert_eq!(result.lot.len(), 2);
         });
     }
 
     #[test]
-    fn test_create_user_liquidation_partial() {
+    #[should_panic(expected = "Error(Contract, #1214)")]
+    fn test_create_user_liquidation_auction_under_min_close_factor() {
         let e = Env::default();
-        e.mock_all_auths();
-        e.cost_estimate().budget().reset_unlimited();
 
+        e.mock_all_auths();
         e.ledger().set(LedgerInfo {
             timestamp: 12345,
             protocol_version: 22,
@@ -1863,14 +2170,16 @@ mod tests {
         let samwise = Address::generate(&e);
 
         let pool_address = create_pool(&e);
+
         let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
         let backstop_address = Address::generate(&e);
 
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1884,11 +2193,10 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
-        reserve_config_1.c_factor = 0_8000000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
+        reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
             &e,
@@ -1898,6 +2206,19 @@ mod tests {
             &reserve_data_1,
         );
 
+        let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, reserve_data_2) = testutils::default_reserve_meta();
+        reserve_config_2.c_factor = 0_0000000;
+        reserve_config_2.l_factor = 0_7000000;
+        reserve_config_2.index = 2;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+
         oracle_client.set_data(
             &bombadil,
             &Asset::Other(Symbol::new(&e, "USD")),
@@ -1905,59 +2226,65 @@ mod tests {
                 &e,
                 Asset::Stellar(underlying_0.clone()),
                 Asset::Stellar(underlying_1.clone()),
+                Asset::Stellar(underlying_2.clone()),
             ],
-            &7,
+            &6,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
+        oracle_client.set_price_stable(&vec![&e, 2_000000, 4_000000, 50_000000]);
 
-        let liq_pct = 85;
-        let positions: Positions = Positions {
-            collateral: map![
-                &e,
-                (reserve_config_0.index, 50_0000000),
-                (reserve_config_1.index, 30_0000000),
-            ],
-            liabilities: map![
-                &e,
-                (reserve_config_0.index, 30_0000000),
-                (reserve_config_1.index, 20_0000000),
-            ],
-            supply: map![&e],
-        };
+        let liq_pct = 20;
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 90_9100000),
+                (reserve_config_1.index, 04_5800000),
+            ],
+            liabilities: map![&e, (reserve_config_2.index, 02_7500000),],
+            supply: map![&e],
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
             storage::set_pool_config(&e, &pool_config);
             storage::set_backstop(&e, &backstop_address);
+            storage::set_liquidation_config(
+                &e,
+                &LiquidationConfig {
+                    min_close_factor: 30,
+                    max_hf: 1_1500000,
+                    twap_records: 0,
+                    target_hf: 1_1000000,
+                },
+            );
 
-            let result = create_user_liq_auction_data(
+            create_user_liq_auction_data(
                 &e,
                 &samwise,
-                &vec![&e, underlying_0.clone()],
-                &vec![&e, underlying_1.clone()],
+                0,
+                &vec![&e, underlying_2.clone()],
+                &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 liq_pct,
             );
-
-            assert_eq!(result.block, 51);
-            assert_eq!(result.bid.get_unchecked(underlying_0.clone()), 25_5000000);
-            assert_eq!(result.bid.len(), 1);
-            assert_eq!(result.lot.get_unchecked(underlying_1.clone()), 13_9293750);
-            assert_eq!(result.lot.len(), 1);
         });
     }
 
     #[test]
-    fn test_create_user_liquidation_partial_100() {
+    #[should_panic(expected = "Error(Contract, #1214)")]
+    fn test_create_user_liquidation_auction_too_small() {
         let e = Env::default();
-        e.mock_all_auths();
-        e.cost_estimate().budget().reset_unlimited();
 
+        e.mock_all_auths();
         e.ledger().set(LedgerInfo {
             timestamp: 12345,
             protocol_version: 22,
@@ -1973,14 +2300,16 @@ mod tests {
         let samwise = Address::generate(&e);
 
         let pool_address = create_pool(&e);
+
         let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
         let backstop_address = Address::generate(&e);
 
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1994,11 +2323,10 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
-        reserve_config_1.c_factor = 0_8000000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
+        reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
             &e,
@@ -2008,6 +2336,19 @@ mod tests {
             &reserve_data_1,
         );
 
+        let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, reserve_data_2) = testutils::default_reserve_meta();
+        reserve_config_2.c_factor = 0_0000000;
+        reserve_config_2.l_factor = 0_7000000;
+        reserve_config_2.index = 2;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+
         oracle_client.set_data(
             &bombadil,
             &Asset::Other(Symbol::new(&e, "USD")),
@@ -2015,55 +2356,52 @@ mod tests {
                 &e,
                 Asset::Stellar(underlying_0.clone()),
                 Asset::Stellar(underlying_1.clone()),
+                Asset::Stellar(underlying_2.clone()),
             ],
-            &7,
+            &5,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
+        oracle_client.set_price_stable(&vec![&e, 2_00000, 4_00000, 50_00000]);
 
-        // liquidation can be safely filled by just liquidating a single liability
-        // validate the collateral is auctioned correctly to create a fair liquidation
-        // -> including the full position results in an ~60% liquidation, and a slightly larger
-        //    liquidation overall due to the higher liability factor of reserve 1
-        let liq_pct = 100;
-        let positions: Positions = Positions {
-            collateral: map![&e, (reserve_config_0.index, 100_0000000),],
-            liabilities: map![
-                &e,
-                (reserve_config_0.index, 40_0000000),
-                (reserve_config_1.index, 15_0000000),
-            ],
-            supply: map![&e],
-        };
+        let liq_pct = 25;
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 90_9100000),
+                (reserve_config_1.index, 04_5800000),
+            ],
+            liabilities: map![&e, (reserve_config_2.index, 02_7500000),],
+            supply: map![&e],
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
             storage::set_pool_config(&e, &pool_config);
             storage::set_backstop(&e, &backstop_address);
 
-            let result = create_user_liq_auction_data(
+            create_user_liq_auction_data(
                 &e,
                 &samwise,
-                &vec![&e, underlying_1.clone()],
-                &vec![&e, underlying_0.clone()],
+                0,
+                &vec![&e, underlying_2.clone()],
+                &vec![&e, underlying_0.clone(), underlying_1.clone()],
                 liq_pct,
             );
-
-            assert_eq!(result.block, 51);
-            assert_eq!(result.bid.get_unchecked(underlying_1.clone()), 15_0000000);
-            assert_eq!(result.bid.len(), 1);
-            assert_eq!(result.lot.get_unchecked(underlying_0.clone()), 41_8806900);
-            assert_eq!(result.lot.len(), 1);
         });
     }
 
     #[test]
-    fn test_create_user_liquidation_partial_0_cf_lot() {
+    fn test_create_user_liquidation_partial() {
         let e = Env::default();
         e.mock_all_auths();
         e.cost_estimate().budget().reset_unlimited();
@@ -2089,8 +2427,8 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -2105,9 +2443,9 @@ mod tests {
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
-        reserve_config_1.c_factor = 0;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
+        reserve_config_1.c_factor = 0_8000000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -2131,16 +2469,18 @@ mod tests {
         );
         oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
 
-        // validate a liquidation of only 0 CF assets is valid
-        // -> asset 1 has 0 CF
-        let liq_pct = 15;
+        let liq_pct = 85;
         let positions: Positions = Positions {
             collateral: map![
                 &e,
-                (reserve_config_0.index, 100_0000000),
-                (reserve_config_1.index, 100_0000000),
+                (reserve_config_0.index, 50_0000000),
+                (reserve_config_1.index, 30_0000000),
+            ],
+            liabilities: map![
+                &e,
+                (reserve_config_0.index, 30_0000000),
+                (reserve_config_1.index, 20_0000000),
             ],
-            liabilities: map![&e, (reserve_config_0.index, 80_0000000),],
             supply: map![&e],
         };
         let pool_config = PoolConfig {
@@ -2148,6 +2488,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -2157,22 +2502,22 @@ mod tests {
             let result = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_0.clone()],
                 &vec![&e, underlying_1.clone()],
                 liq_pct,
             );
 
             assert_eq!(result.block, 51);
-            assert_eq!(result.bid.get_unchecked(underlying_0.clone()), 12_0000000);
+            assert_eq!(result.bid.get_unchecked(underlying_0.clone()), 25_5000000);
             assert_eq!(result.bid.len(), 1);
-            assert_eq!(result.lot.get_unchecked(underlying_1.clone()), 8_6250000);
+            assert_eq!(result.lot.get_unchecked(underlying_1.clone()), 13_9293750);
             assert_eq!(result.lot.len(), 1);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1211)")]
-    fn test_create_user_liquidation_partial_exclude_collateral_when_required_panics() {
+    fn test_create_user_liquidation_partial_100() {
         let e = Env::default();
         e.mock_all_auths();
         e.cost_estimate().budget().reset_unlimited();
@@ -2198,8 +2543,8 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -2214,8 +2559,8 @@ mod tests {
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
         reserve_config_1.c_factor = 0_8000000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_config_1.index = 1;
@@ -2240,17 +2585,18 @@ mod tests {
         );
         oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
 
-        // user holds most collateral in asset 0
-        // liquidator attempts to create a bad liquidation by excluding asset 0
-        // causing excess liabilities to be included in the bid
-        let liq_pct = 40;
+        // liquidation can be safely filled by just liquidating a single liability
+        // validate the collateral is auctioned correctly to create a fair liquidation
+        // -> including the full position results in an ~60% liquidation, and a slightly larger
+        //    liquidation overall due to the higher liability factor of reserve 1
+        let liq_pct = 100;
         let positions: Positions = Positions {
-            collateral: map![
+            collateral: map![&e, (reserve_config_0.index, 100_0000000),],
+            liabilities: map![
                 &e,
-                (reserve_config_0.index, 60_0000000),
-                (reserve_config_1.index, 10_0000000),
+                (reserve_config_0.index, 40_0000000),
+                (reserve_config_1.index, 15_0000000),
             ],
-            liabilities: map![&e, (reserve_config_1.index, 25_0000000),],
             supply: map![&e],
         };
         let pool_config = PoolConfig {
@@ -2258,25 +2604,36 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
             storage::set_pool_config(&e, &pool_config);
             storage::set_backstop(&e, &backstop_address);
 
-            create_user_liq_auction_data(
+            let result = create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_1.clone()],
-                &vec![&e, underlying_1.clone()],
+                &vec![&e, underlying_0.clone()],
                 liq_pct,
             );
+
+            assert_eq!(result.block, 51);
+            assert_eq!(result.bid.get_unchecked(underlying_1.clone()), 15_0000000);
+            assert_eq!(result.bid.len(), 1);
+            assert_eq!(result.lot.get_unchecked(underlying_0.clone()), 41_8806900);
+            assert_eq!(result.lot.len(), 1);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1214)")]
-    fn test_create_user_liquidation_partial_exclude_liabilities_when_required_too_small() {
+    fn test_create_user_liquidation_partial_0_cf_lot() {
         let e = Env::default();
         e.mock_all_auths();
         e.cost_estimate().budget().reset_unlimited();
@@ -2302,8 +2659,8 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
-        reserve_data_0.d_rate = 1_150_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -2318,9 +2675,9 @@ mod tests {
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
         reserve_data_1.last_time = 12345;
-        reserve_data_1.b_rate = 1_200_000_000;
-        reserve_data_1.d_rate = 1_300_000_000;
-        reserve_config_1.c_factor = 0_8000000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
+        reserve_config_1.c_factor = 0;
         reserve_config_1.l_factor = 0_7500000;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -2344,9 +2701,234 @@ mod tests {
         );
         oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
 
-        // user holds most liabilities in asset 1
-        // liquidator attempts to create a bad liquidation by excluding asset 1 from bid
-        let liq_pct = 100;
+        // validate a liquidation of only 0 CF assets is valid
+        // -> asset 1 has 0 CF
+        let liq_pct = 15;
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 100_0000000),
+                (reserve_config_1.index, 100_0000000),
+            ],
+            liabilities: map![&e, (reserve_config_0.index, 80_0000000),],
+            supply: map![&e],
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_backstop(&e, &backstop_address);
+
+            let result = create_user_liq_auction_data(
+                &e,
+                &samwise,
+                0,
+                &vec![&e, underlying_0.clone()],
+                &vec![&e, underlying_1.clone()],
+                liq_pct,
+            );
+
+            assert_eq!(result.block, 51);
+            assert_eq!(result.bid.get_unchecked(underlying_0.clone()), 12_0000000);
+            assert_eq!(result.bid.len(), 1);
+            assert_eq!(result.lot.get_unchecked(underlying_1.clone()), 8_6250000);
+            assert_eq!(result.lot.len(), 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1211)")]
+    fn test_create_user_liquidation_partial_exclude_collateral_when_required_panics() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.cost_estimate().budget().reset_unlimited();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 50,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
+        let backstop_address = Address::generate(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.last_time = 12345;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
+        reserve_config_0.c_factor = 0_8500000;
+        reserve_config_0.l_factor = 0_9000000;
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 12345;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
+        reserve_config_1.c_factor = 0_8000000;
+        reserve_config_1.l_factor = 0_7500000;
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
+
+        // user holds most collateral in asset 0
+        // liquidator attempts to create a bad liquidation by excluding asset 0
+        // causing excess liabilities to be included in the bid
+        let liq_pct = 40;
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 60_0000000),
+                (reserve_config_1.index, 10_0000000),
+            ],
+            liabilities: map![&e, (reserve_config_1.index, 25_0000000),],
+            supply: map![&e],
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_backstop(&e, &backstop_address);
+
+            create_user_liq_auction_data(
+                &e,
+                &samwise,
+                0,
+                &vec![&e, underlying_1.clone()],
+                &vec![&e, underlying_1.clone()],
+                liq_pct,
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1214)")]
+    fn test_create_user_liquidation_partial_exclude_liabilities_when_required_too_small() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.cost_estimate().budget().reset_unlimited();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 50,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
+        let backstop_address = Address::generate(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.last_time = 12345;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.d_rate = 1_150_000_000_000;
+        reserve_config_0.c_factor = 0_8500000;
+        reserve_config_0.l_factor = 0_9000000;
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 12345;
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_data_1.d_rate = 1_300_000_000_000;
+        reserve_config_1.c_factor = 0_8000000;
+        reserve_config_1.l_factor = 0_7500000;
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
+
+        // user holds most liabilities in asset 1
+        // liquidator attempts to create a bad liquidation by excluding asset 1 from bid
+        let liq_pct = 100;
         let positions: Positions = Positions {
             collateral: map![&e, (reserve_config_0.index, 100_0000000),],
             liabilities: map![
@@ -2361,6 +2943,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
@@ -2370,6 +2957,7 @@ mod tests {
             create_user_liq_auction_data(
                 &e,
                 &samwise,
+                0,
                 &vec![&e, underlying_0.clone()],
                 &vec![&e, underlying_0.clone()],
                 liq_pct,
@@ -2406,7 +2994,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -2420,7 +3008,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -2471,12 +3059,19 @@ mod tests {
                 (underlying_1.clone(), 1_5395739)
             ],
             block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -2552,7 +3147,7 @@ mod tests {
     }
 
     #[test]
-    fn test_fill_user_liquidation_auction_hits_target() {
+    fn test_fill_user_liquidation_auction_sub_account() {
         let e = Env::default();
 
         e.mock_all_auths();
@@ -2580,7 +3175,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -2594,7 +3189,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -2645,12 +3240,19 @@ mod tests {
                 (underlying_1.clone(), 1_5395739)
             ],
             block: 176,
+            account_id: 2,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -2662,9 +3264,9 @@ mod tests {
             supply: map![&e],
         };
         e.as_contract(&pool_address, || {
-            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_account_positions(&e, &samwise, 2, &positions);
             storage::set_pool_config(&e, &pool_config);
-            //scale up modifiers
+
             e.ledger().set(LedgerInfo {
                 timestamp: 12345 + 200 * 5,
                 protocol_version: 22,
@@ -2678,16 +3280,50 @@ mod tests {
             let mut pool = Pool::load(&e);
             let mut frodo_state = User::load(&e, &frodo);
             fill_user_liq_auction(&e, &mut pool, &mut auction_data, &samwise, &mut frodo_state);
-            let samwise_positions = storage::get_user_positions(&e, &samwise);
-            let samwise_hf =
-                PositionData::calculate_from_positions(&e, &mut pool, &samwise_positions)
-                    .as_health_factor();
-            assert_eq!(samwise_hf, 1_1458977);
+            let frodo_positions = frodo_state.positions;
+            assert_eq!(
+                frodo_positions
+                    .collateral
+                    .get(reserve_config_0.index)
+                    .unwrap_optimized(),
+                30_5595329
+            );
+            assert_eq!(
+                frodo_positions
+                    .collateral
+                    .get(reserve_config_1.index)
+                    .unwrap_optimized(),
+                1_5395739
+            );
+            assert_eq!(
+                frodo_positions
+                    .liabilities
+                    .get(reserve_config_2.index)
+                    .unwrap_optimized(),
+                1_2375000
+            );
+            // the fill is taken from the sub-account, not the main account
+            assert!(storage::get_user_positions(&e, &samwise).collateral.is_empty());
+            let samwise_positions = storage::get_account_positions(&e, &samwise, 2);
+            assert_eq!(
+                samwise_positions
+                    .collateral
+                    .get(reserve_config_0.index)
+                    .unwrap_optimized(),
+                90_9100000 - 30_5595329
+            );
+            assert_eq!(
+                samwise_positions
+                    .liabilities
+                    .get(reserve_config_2.index)
+                    .unwrap_optimized(),
+                02_7500000 - 1_2375000
+            );
         });
     }
 
     #[test]
-    fn test_fill_user_liquidation_auction_empty_bid() {
+    fn test_fill_user_liquidation_auction_hits_target() {
         let e = Env::default();
 
         e.mock_all_auths();
@@ -2715,7 +3351,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -2729,7 +3365,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -2773,19 +3409,26 @@ mod tests {
         reserve_2_asset.approve(&frodo, &pool_address, &i128::MAX, &1000000);
 
         let mut auction_data = AuctionData {
-            bid: map![&e],
+            bid: map![&e, (underlying_2.clone(), 1_2375000)],
             lot: map![
                 &e,
                 (underlying_0.clone(), 30_5595329),
                 (underlying_1.clone(), 1_5395739)
             ],
             block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -2799,7 +3442,7 @@ mod tests {
         e.as_contract(&pool_address, || {
             storage::set_user_positions(&e, &samwise, &positions);
             storage::set_pool_config(&e, &pool_config);
-
+            //scale up modifiers
             e.ledger().set(LedgerInfo {
                 timestamp: 12345 + 200 * 5,
                 protocol_version: 22,
@@ -2813,7 +3456,149 @@ mod tests {
             let mut pool = Pool::load(&e);
             let mut frodo_state = User::load(&e, &frodo);
             fill_user_liq_auction(&e, &mut pool, &mut auction_data, &samwise, &mut frodo_state);
-            let frodo_positions = frodo_state.positions;
+            let samwise_positions = storage::get_user_positions(&e, &samwise);
+            let samwise_hf =
+                PositionData::calculate_from_positions(&e, &mut pool, &samwise_positions, 0)
+                    .as_health_factor();
+            assert_eq!(samwise_hf, 1_1458977);
+        });
+    }
+
+    #[test]
+    fn test_fill_user_liquidation_auction_empty_bid() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 175,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 17280,
+            min_persistent_entry_ttl: 17280,
+            max_entry_ttl: 9999999,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.last_time = 12345;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_config_0.c_factor = 0_8500000;
+        reserve_config_0.l_factor = 0_9000000;
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.b_rate = 1_200_000_000_000;
+        reserve_config_1.c_factor = 0_7500000;
+        reserve_config_1.l_factor = 0_7500000;
+        reserve_data_1.last_time = 12345;
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        let (underlying_2, reserve_2_asset) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_2, reserve_data_2) = testutils::default_reserve_meta();
+        reserve_config_2.c_factor = 0_0000000;
+        reserve_config_2.l_factor = 0_7000000;
+        reserve_config_2.index = 2;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_2,
+            &reserve_config_2,
+            &reserve_data_2,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+                Asset::Stellar(underlying_2.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000, 50_0000000]);
+
+        reserve_2_asset.mint(&frodo, &0_8000000);
+        reserve_2_asset.approve(&frodo, &pool_address, &i128::MAX, &1000000);
+
+        let mut auction_data = AuctionData {
+            bid: map![&e],
+            lot: map![
+                &e,
+                (underlying_0.clone(), 30_5595329),
+                (underlying_1.clone(), 1_5395739)
+            ],
+            block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions: Positions = Positions {
+            collateral: map![
+                &e,
+                (reserve_config_0.index, 90_9100000),
+                (reserve_config_1.index, 04_5800000),
+            ],
+            liabilities: map![&e, (reserve_config_2.index, 02_7500000),],
+            supply: map![&e],
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+
+            e.ledger().set(LedgerInfo {
+                timestamp: 12345 + 200 * 5,
+                protocol_version: 22,
+                sequence_number: 176 + 200,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 17280,
+                min_persistent_entry_ttl: 17280,
+                max_entry_ttl: 9999999,
+            });
+            let mut pool = Pool::load(&e);
+            let mut frodo_state = User::load(&e, &frodo);
+            fill_user_liq_auction(&e, &mut pool, &mut auction_data, &samwise, &mut frodo_state);
+            let frodo_positions = frodo_state.positions;
             assert_eq!(
                 frodo_positions
                     .collateral
@@ -2853,4 +3638,463 @@ mod tests {
             );
         });
     }
+
+    fn setup_floor_test(e: &Env) -> (Address, Address, Address, PoolConfig) {
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 500,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 17280,
+            min_persistent_entry_ttl: 17280,
+            max_entry_ttl: 9999999,
+        });
+
+        let bombadil = Address::generate(e);
+        let pool_address = create_pool(e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(e, &bombadil);
+        let (reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_data_0.last_time = 12345;
+        testutils::create_reserve(
+            e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_config_1.index = 1;
+        reserve_data_1.last_time = 12345;
+        testutils::create_reserve(
+            e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(e, "USD")),
+            &vec![
+                e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![e, 2_0000000, 1_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        (pool_address, underlying_0, underlying_1, pool_config)
+    }
+
+    #[test]
+    fn test_require_fill_above_floor() {
+        let e = Env::default();
+        let (pool_address, underlying_0, underlying_1, pool_config) = setup_floor_test(&e);
+
+        // lot is worth 22 and bid is worth 20
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_1.clone(), 20_0000000)],
+            lot: map![&e, (underlying_0.clone(), 10_0000000)],
+            block: 400,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        let empty_bid = AuctionData {
+            bid: map![&e],
+            lot: map![&e, (underlying_0.clone(), 10_0000000)],
+            block: 400,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_pool_config(&e, &pool_config);
+            let mut pool = Pool::load(&e);
+
+            require_fill_above_floor(&e, &mut pool, &auction_data, 0_9000000);
+            // no floor allows the bid to reach 0
+            require_fill_above_floor(&e, &mut pool, &empty_bid, 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1240)")]
+    fn test_require_fill_above_floor_under_floor() {
+        let e = Env::default();
+        let (pool_address, underlying_0, underlying_1, pool_config) = setup_floor_test(&e);
+
+        // lot is worth 22 and bid is worth 20
+        let auction_data = AuctionData {
+            bid: map![&e, (underlying_1.clone(), 20_0000000)],
+            lot: map![&e, (underlying_0.clone(), 10_0000000)],
+            block: 400,
+            account_id: 0,
+            config: AuctionConfig::default(),
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_pool_config(&e, &pool_config);
+            let mut pool = Pool::load(&e);
+
+            require_fill_above_floor(&e, &mut pool, &auction_data, 0_9500000);
+        });
+    }
+
+    #[test]
+    fn test_protect_from_liquidation_skips_unprotected_and_healthy_users() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 50,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let protection = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.last_time = 12345;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_config_0.c_factor = 0_8500000;
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 12345;
+        reserve_data_1.d_rate = 1_300_000_000_000;
+        reserve_config_1.l_factor = 0_7500000;
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
+
+        let unhealthy_positions: Positions = Positions {
+            collateral: map![&e, (reserve_config_0.index, 100_0000000),],
+            liabilities: map![&e, (reserve_config_1.index, 30_0000000),],
+            supply: map![&e],
+        };
+        let healthy_positions: Positions = Positions {
+            collateral: map![&e, (reserve_config_0.index, 100_0000000),],
+            liabilities: map![&e, (reserve_config_1.index, 10_0000000),],
+            supply: map![&e],
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &unhealthy_positions);
+            storage::set_user_positions(&e, &frodo, &healthy_positions);
+
+            // no protection registered
+            assert!(!protect_from_liquidation(&e, &samwise, 0));
+
+            // protection is only called for users eligible for liquidation
+            storage::set_liquidation_protection(&e, &frodo, &Some(protection.clone()));
+            assert!(!protect_from_liquidation(&e, &frodo, 0));
+            assert_eq!(
+                storage::get_liquidation_protection(&e, &frodo),
+                Some(protection.clone())
+            );
+
+            storage::set_liquidation_protection(&e, &frodo, &None);
+            assert!(storage::get_liquidation_protection(&e, &frodo).is_none());
+        });
+    }
+
+    #[test]
+    fn test_create_user_liquidation_protection_restores_health() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 50,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
+        let backstop_address = Address::generate(&e);
+        let protection = e.register(MockProtection {}, ());
+        let protection_client = MockProtectionClient::new(&e, &protection);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.last_time = 12345;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_config_0.c_factor = 0_8500000;
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 12345;
+        reserve_data_1.d_rate = 1_300_000_000_000;
+        reserve_config_1.l_factor = 0_7500000;
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
+
+        // collateral is worth 187 and liabilities are worth 208, a top up of 30 adds 51
+        underlying_0_client.mint(&protection, &30_0000000);
+        protection_client.set_top_up(&underlying_0, &30_0000000);
+        let pool_balance = underlying_0_client.balance(&pool_address);
+
+        let positions: Positions = Positions {
+            collateral: map![&e, (reserve_config_0.index, 100_0000000),],
+            liabilities: map![&e, (reserve_config_1.index, 30_0000000),],
+            supply: map![&e],
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_backstop(&e, &backstop_address);
+            storage::set_liquidation_protection(&e, &samwise, &Some(protection.clone()));
+
+            let result = create_user_liq_auction_data(
+                &e,
+                &samwise,
+                0,
+                &vec![&e, underlying_1.clone()],
+                &vec![&e, underlying_0.clone()],
+                50,
+            );
+            assert_eq!(result.bid.len(), 0);
+            assert_eq!(result.lot.len(), 0);
+
+            let new_positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(
+                new_positions.collateral.get_unchecked(reserve_config_0.index),
+                100_0000000 + 27_2727272
+            );
+            assert_eq!(underlying_0_client.balance(&protection), 0);
+            assert_eq!(
+                underlying_0_client.balance(&pool_address),
+                pool_balance + 30_0000000
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1211)")]
+    fn test_create_user_liquidation_settles_fixed_rate_loans() {
+        let e = Env::default();
+
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 50,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let pool_address = create_pool(&e);
+        let (oracle_address, oracle_client) = testutils::create_mock_oracle(&e);
+        let backstop_address = Address::generate(&e);
+
+        // creating reserves for a pool exhausts the budget
+        e.cost_estimate().budget().reset_unlimited();
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_data_0.last_time = 12345;
+        reserve_data_0.b_rate = 1_100_000_000_000;
+        reserve_config_0.c_factor = 0_8500000;
+        reserve_config_0.index = 0;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_0,
+            &reserve_config_0,
+            &reserve_data_0,
+        );
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 12345;
+        reserve_data_1.d_rate = 1_300_000_000_000;
+        reserve_config_1.l_factor = 0_7500000;
+        reserve_config_1.index = 1;
+        testutils::create_reserve(
+            &e,
+            &pool_address,
+            &underlying_1,
+            &reserve_config_1,
+            &reserve_data_1,
+        );
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 2_0000000, 4_0000000]);
+
+        // collateral is worth 187 and liabilities are worth 208 at the variable rate, but the
+        // liabilities are under a fixed-rate loan worth 20 tokens, or 106
+        let positions: Positions = Positions {
+            collateral: map![&e, (reserve_config_0.index, 100_0000000),],
+            liabilities: map![&e, (reserve_config_1.index, 30_0000000),],
+            supply: map![&e],
+        };
+        let loan = FixedLoan {
+            d_tokens: 30_0000000,
+            value: 20_0000000,
+            rate: 0,
+            last_time: 12345,
+            maturity: 12345 + 1000,
+        };
+        let pool_config = PoolConfig {
+            oracle: oracle_address,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_backstop(&e, &backstop_address);
+            storage::set_user_fixed_loan(&e, &samwise, reserve_config_1.index, &Some(loan));
+
+            create_user_liq_auction_data(
+                &e,
+                &samwise,
+                0,
+                &vec![&e, underlying_1.clone()],
+                &vec![&e, underlying_0.clone()],
+                50,
+            );
+        });
+    }
 }
@@ -1,13 +1,79 @@
 /********** Numbers **********/
 
+/// Fixed-point scalar for 12 decimal numbers
+pub const SCALAR_12: i128 = 1_000_000_000_000;
+
 /// Fixed-point scalar for 9 decimal numbers
 pub const SCALAR_9: i128 = 1_000_000_000;
 
 /// Fixed-point scalar for 7 decimal numbers
 pub const SCALAR_7: i128 = 1_0000000;
 
+/// The decimals oracle prices are normalized to, regardless of the decimals of the feed. This
+/// matches the 14 decimals of the standard price feeds, so their prices keep full precision.
+pub const PRICE_DECIMALS: u32 = 14;
+
 // seconds per year
 pub const SECONDS_PER_YEAR: i128 = 31536000;
 
 // approximate week in blocks assuming 5 seconds per block
 pub const SECONDS_PER_WEEK: u64 = 604800;
+
+// the minimum amount of BLND a risk bot must stake to create liquidation auctions
+#[cfg(feature = "auctions")]
+pub const MIN_RISK_BOT_STAKE: i128 = 1_000_0000000;
+
+// the portion of a risk bot's stake slashed for each auction requested for a healthy user
+#[cfg(feature = "auctions")]
+pub const RISK_BOT_SLASH_RATE: i128 = 0_1000000;
+
+// the delay in seconds before BLND queued by a risk bot can be unstaked (7 days)
+#[cfg(feature = "auctions")]
+pub const RISK_BOT_UNSTAKE_DELAY: u64 = 7 * 24 * 60 * 60;
+
+// the maximum number of oracle price records used for a time weighted average price
+#[cfg(feature = "auctions")]
+pub const MAX_TWAP_RECORDS: u32 = 24;
+
+// the maximum number of interest accrual snapshots kept for each reserve
+pub const MAX_ACCRUAL_SNAPSHOTS: u32 = 24;
+
+// the maximum delay in seconds before a proposed admin can accept the admin role (30 days)
+pub const MAX_ADMIN_DELAY: u64 = 30 * 24 * 60 * 60;
+
+// the delay in seconds before a proposed admin can accept the admin role, if none is set (7 days)
+pub const DEFAULT_ADMIN_DELAY: u64 = 7 * 24 * 60 * 60;
+
+// the lowest minimum health factor a pool can require after an action, to prevent rounding errors
+pub const MIN_HEALTH_FACTOR_FLOOR: u32 = 1_0000100;
+
+// the highest minimum health factor a pool can require after an action
+pub const MIN_HEALTH_FACTOR_CEILING: u32 = 1_5000000;
+
+// the maximum number of seconds after borrowing a repayment can receive an interest rebate (1 day)
+pub const MAX_REPAY_GRACE_WINDOW: u64 = 24 * 60 * 60;
+
+// the maximum share of a filled auction's value paid to the keeper that created the auction (10%)
+#[cfg(feature = "auctions")]
+pub const MAX_KEEPER_BOUNTY: u32 = 0_1000000;
+
+// the maximum amount an e-mode category can boost a reserve's collateral or liability factor
+pub const MAX_EMODE_BOOST: u32 = 0_2000000;
+
+// the maximum number of seconds until a fixed-rate loan's maturity (365 days)
+pub const MAX_FIXED_RATE_TERM: u64 = 365 * 24 * 60 * 60;
+
+// the maximum age in seconds of the last price a new price's deviation is checked against (1 hour)
+pub const MAX_LAST_PRICE_AGE: u64 = 60 * 60;
+
+/********** Pause Flags **********/
+
+pub const PAUSE_BORROW: u32 = 1 << 0;
+pub const PAUSE_SUPPLY: u32 = 1 << 1;
+pub const PAUSE_WITHDRAW: u32 = 1 << 2;
+pub const PAUSE_FLASH_LOAN: u32 = 1 << 3;
+pub const PAUSE_LIQUIDATION: u32 = 1 << 4;
+
+// all valid pause flags
+pub const PAUSE_ALL: u32 =
+    PAUSE_BORROW | PAUSE_SUPPLY | PAUSE_WITHDRAW | PAUSE_FLASH_LOAN | PAUSE_LIQUIDATION;
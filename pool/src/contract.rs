@@ -1,12 +1,34 @@
 use crate::{
-    auctions::{self, AuctionData},
     emissions::{self, ReserveEmissionMetadata},
     events::PoolEvents,
-    pool::{self, FlashLoan, Positions, Request, Reserve},
-    storage::{self, ReserveConfig},
+    pool::{
+        self, AdminOp, HealthFactorData, PositionValue, Positions, PriceAttestation, Request,
+        RequestOptions, Reserve, ReservePreview, ReserveRates, SimulateResult, SubmitResult,
+        SubmitSignature,
+    },
+    storage::{
+        self, AccrualSnapshot, ConfigHistoryEntry, CreditSettlementConfig, EModeCategory,
+        EmissionEpoch, FixedLoan, FixedRateConfig, FixedTranche, InterestRateConfig, OracleConfig,
+        PendingAdmin, PendingAdminDelay, PositionSnapshot, QueuedEModeCategory, QueuedReserveInit,
+        RateBounds, RepayGraceConfig, ReserveConfig, SelfRepayConfig,
+    },
     PoolConfig, ReserveEmissionData, UserEmissionData,
 };
-use soroban_sdk::{contract, contractclient, contractimpl, Address, Env, String, Vec};
+use soroban_sdk::{
+    contract, contractclient, contractimpl, Address, BytesN, Env, Map, String, Vec,
+};
+
+#[cfg(feature = "auctions")]
+use crate::{
+    auctions::{self, AuctionData, LiquidationRequest, QueuedAuction},
+    dependencies::{BackstopClient, PoolBackstopData},
+    storage::{AuctionConfig, LiquidationConfig, RiskBotUnstake},
+};
+#[cfg(feature = "flash-loans")]
+use crate::{
+    pool::{CollateralSwap, FlashLoan},
+    storage::{FeeTier, UserFeeVolume},
+};
 
 /// ### Pool
 ///
@@ -16,14 +38,47 @@ pub struct PoolContract;
 
 #[contractclient(name = "PoolClient")]
 pub trait Pool {
-    /// (Admin only) Set a new address as the admin of this pool
+    /// (Admin only) Propose a new address as the admin of this pool. The proposed admin must
+    /// accept the admin role with `accept_admin` once the admin delay has passed. Replaces any
+    /// pending proposal.
     ///
     /// ### Arguments
-    /// * `new_admin` - The new admin address
+    /// * `new_admin` - The proposed admin address
     ///
     /// ### Panics
     /// If the caller is not the admin
-    fn set_admin(e: Env, new_admin: Address);
+    fn propose_admin(e: Env, new_admin: Address);
+
+    /// (Admin only) Cancel the pending admin proposal
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or there is no pending admin proposal
+    fn cancel_admin_proposal(e: Env);
+
+    /// (Proposed admin only) Accept the admin role of this pool
+    ///
+    /// ### Panics
+    /// If the caller is not the proposed admin, or the admin delay has not passed
+    fn accept_admin(e: Env);
+
+    /// (Admin only) Set the delay before a proposed admin can accept the admin role. The new
+    /// delay applies once the current delay has passed.
+    ///
+    /// ### Arguments
+    /// * `delay` - The delay in seconds
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the delay exceeds 30 days
+    fn set_admin_delay(e: Env, delay: u64);
+
+    /// Fetch the pending admin proposal, if any
+    fn get_pending_admin(e: Env) -> Option<PendingAdmin>;
+
+    /// Fetch the pending admin delay change, if any
+    fn get_pending_admin_delay(e: Env) -> Option<PendingAdminDelay>;
+
+    /// Fetch the delay in seconds before a proposed admin can accept the admin role
+    fn get_admin_delay(e: Env) -> u64;
 
     /// (Admin only) Update the pool
     ///
@@ -35,405 +90,2851 @@ pub trait Pool {
     /// If the caller is not the admin
     fn update_pool(e: Env, backstop_take_rate: u32, max_positions: u32);
 
-    /// (Admin only) Queues setting data for a reserve in the pool
+    /// (Admin only) Execute a batch of admin operations atomically. Each operation behaves the
+    /// same as its standalone admin function.
     ///
     /// ### Arguments
-    /// * `asset` - The underlying asset to add as a reserve
-    /// * `config` - The ReserveConfig for the reserve
+    /// * `ops` - The admin operations to execute, in order
     ///
     /// ### Panics
-    /// If the caller is not the admin
-    fn queue_set_reserve(e: Env, asset: Address, metadata: ReserveConfig);
+    /// If the caller is not the admin or any operation fails
+    fn admin_batch(e: Env, ops: Vec<AdminOp>);
 
-    /// (Admin only) Cancels the queued set of a reserve in the pool
+    /// (Admin only) Deploy a share token for the pool's backstop, which exposes the pool's
+    /// backstop shares, excluding shares queued for withdrawal, as a transferable SEP-41 token.
+    /// The share token is deployed from the wasm hash set by the backstop's governance.
+    ///
+    /// Returns the address of the share token contract
     ///
     /// ### Arguments
-    /// * `asset` - The underlying asset to add as a reserve
+    /// * `name` - The name of the share token
+    /// * `symbol` - The symbol of the share token
     ///
     /// ### Panics
-    /// If the caller is not the admin or the reserve is not queued for initialization
-    fn cancel_set_reserve(e: Env, asset: Address);
+    /// If the caller is not the admin or the pool's backstop already has a share token
+    fn deploy_backstop_share_token(e: Env, name: String, symbol: String) -> Address;
 
-    /// (Admin only) Executes the queued set of a reserve in the pool
+    /// (Admin only) Fund a reward token from the admin to be emitted to the pool's backstop
+    /// depositors over `duration` seconds
     ///
     /// ### Arguments
-    /// * `asset` - The underlying asset to add as a reserve
+    /// * `token` - The address of the reward token
+    /// * `amount` - The amount of the reward token to emit
+    /// * `duration` - The duration in seconds to emit the rewards over
     ///
     /// ### Panics
-    /// If the reserve is not queued for initialization
-    /// or is already setup
-    /// or has invalid metadata
-    fn set_reserve(e: Env, asset: Address) -> u32;
+    /// If the caller is not the admin or the backstop rejects the reward token or duration
+    fn fund_backstop_reward(e: Env, token: Address, amount: i128, duration: u64);
 
-    /// Fetch the pool configuration
-    fn get_config(e: Env) -> PoolConfig;
+    /// (Admin only) Remove a reward token from the pool's backstop after its emissions have ended
+    /// and the claim period has passed
+    ///
+    /// ### Arguments
+    /// * `token` - The address of the reward token
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the backstop rejects the removal
+    fn remove_backstop_reward(e: Env, token: Address);
 
-    /// Fetch the admin address of the pool
-    fn get_admin(e: Env) -> Address;
+    /// (Admin only) Set the fallback oracle of the pool. Prices are loaded from the fallback
+    /// oracle when the pool's oracle price is missing or stale.
+    ///
+    /// ### Arguments
+    /// * `fallback_oracle` - The address of the fallback oracle, or None to remove it
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, or the fallback oracle is the pool's oracle or does not
+    /// use the same decimals
+    fn set_fallback_oracle(e: Env, fallback_oracle: Option<Address>);
 
-    /// Fetch information about a reserve
+    /// (Admin only) Set the bounds on the borrow rate of a reserve. The rate from the reserve's
+    /// interest rate curve is clamped to the bounds when interest accrues.
     ///
     /// ### Arguments
-    /// * `asset` - The address of the reserve asset
-    fn get_reserve(e: Env, asset: Address) -> Reserve;
+    /// * `asset` - The underlying asset of the reserve
+    /// * `rate_bounds` - The min and max borrow rate (7 decimals), or None to remove the bounds
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the reserve does not exist, or the min rate is over the
+    /// max rate
+    fn set_rate_bounds(e: Env, asset: Address, rate_bounds: Option<RateBounds>);
 
-    /// Fetch the positions for an address
+    /// (Admin only) Set the half-life of the decay of a reserve's rate modifier towards 1. While
+    /// the reserve is under its target utilization, the modifier halves its distance from 1 every
+    /// half-life, so it does not linger far from 1 after a long period of low demand.
     ///
     /// ### Arguments
-    /// * `address` - The address to fetch positions for
-    fn get_positions(e: Env, address: Address) -> Positions;
+    /// * `asset` - The underlying asset of the reserve
+    /// * `half_life` - The half-life of the decay in seconds, or None to remove the decay
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the reserve does not exist, or the half-life is zero or
+    /// over a year
+    fn set_ir_mod_half_life(e: Env, asset: Address, half_life: Option<u64>);
 
-    /// Submit a set of requests to the pool where 'from' takes on the position, 'sender' sends any
-    /// required tokens to the pool and 'to' receives any tokens sent from the pool
+    /// (Admin only) Set the minimum health factor a user's positions must have after an action
     ///
-    /// Returns the new positions for 'from'
+    /// ### Arguments
+    /// * `min_health_factor` - The minimum health factor (7 decimals)
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, or the minimum health factor is under 1.0000100 or over 1.5
+    fn set_min_health_factor(e: Env, min_health_factor: u32);
+
+    /// (Admin only) Set the maximum leverage a user's positions can have after an action, as
+    /// gross exposure (collateral plus liabilities) over net equity. Submits that exceed it are
+    /// rejected even if the health factor is sufficient.
     ///
     /// ### Arguments
-    /// * `from` - The address of the user whose positions are being modified
-    /// * `spender` - The address of the user who is sending tokens to the pool
-    /// * `to` - The address of the user who is receiving tokens from the pool
-    /// * `requests` - A vec of requests to be processed
+    /// * `max_account_leverage` - The maximum leverage (7 decimals), or 0 for no limit
     ///
     /// ### Panics
-    /// If the request is not able to be completed for cases like insufficient funds or invalid health factor
-    fn submit(
-        e: Env,
-        from: Address,
-        spender: Address,
-        to: Address,
-        requests: Vec<Request>,
-    ) -> Positions;
+    /// If the caller is not the admin, or the maximum leverage is not 0 and is under 1x
+    fn set_max_account_leverage(e: Env, max_account_leverage: u32);
 
-    /// Submit a set of requests to the pool where 'from' takes on the position, 'sender' sends any
-    /// required tokens to the pool and 'to' receives any tokens sent from the pool
+    /// (Admin only) Set the health factor under which a submit emits a `health_warning` event
+    /// for the user, so monitoring services can alert users without polling every account
     ///
-    /// Returns the new positions for 'from'
+    /// ### Arguments
+    /// * `threshold` - The health factor (7 decimals), or 0 to disable health warnings
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, or the threshold is not 0 and is under 1.0
+    fn set_health_warning_threshold(e: Env, threshold: u32);
+
+    /// Fetch the health factor under which a submit emits a health warning, or 0 if disabled
+    fn get_health_warning_threshold(e: Env) -> u32;
+
+    /// (Admin only) Set the minimum value of a collateral or liability position left by a
+    /// request. A collateral withdrawal that would leave a smaller position withdraws the entire
+    /// position, and any other request that would leave a smaller position is rejected.
+    /// Liquidations are not affected.
     ///
     /// ### Arguments
-    /// * `from` - The address of the user whose positions are being modified and also the address of
-    /// the user who is sending and receiving the tokens to the pool.
-    /// * `flash_loan` - Arguments relative to the flash loan: receiver contract, asset and borroed amount.
-    /// * `requests` - A vec of requests to be processed
+    /// * `value` - The value in the oracle's base asset with 14 decimals, or 0 for no minimum
     ///
     /// ### Panics
-    /// If the request is not able to be completed for cases like insufficient funds or invalid health factor
-    fn flash_loan(
-        e: Env,
-        from: Address,
-        flash_loan: FlashLoan,
-        requests: Vec<Request>,
-    ) -> Positions;
+    /// If the caller is not the admin, or the value is negative
+    fn set_min_position_value(e: Env, value: i128);
 
-    /// Submit a set of requests to the pool where 'from' takes on the position, 'spender' sends any
-    /// required tokens to the pool USING transfer_from and 'to' receives any tokens sent from the pool.
+    /// Fetch the minimum value of a collateral or liability position, or 0 if there is no minimum
+    fn get_min_position_value(e: Env) -> i128;
+
+    /// (Admin only) Declare a ledger at which the positions of every user's accounts are
+    /// recorded, so airdrops can be distributed proportionally to historical pool usage.
+    /// Positions are recorded lazily the first time an account's positions change at or after
+    /// the ledger, and reflect the positions at the start of the ledger. Earlier snapshots are
+    /// kept, and a new snapshot can only be declared once the previous snapshot ledger is reached.
     ///
-    /// Returns the new positions for 'from'
+    /// Returns the ID of the snapshot
     ///
     /// ### Arguments
-    /// * `from` - The address of the user whose positions are being modified
-    /// * `spender` - The address of the user who is sending tokens to the pool
-    /// * `to` - The address of the user who is receiving tokens from the pool
-    /// * `requests` - A vec of requests to be processed
+    /// * `ledger` - The ledger sequence to record positions at
     ///
     /// ### Panics
-    /// If the request is not able to be completed for cases like insufficient funds, insufficient allowance, or invalid health factor
-    fn submit_with_allowance(
-        e: Env,
-        from: Address,
-        spender: Address,
-        to: Address,
-        requests: Vec<Request>,
-    ) -> Positions;
-    /// Manage bad debt. Debt is considered "bad" if there is no longer has any collateral posted.
-    ///
-    /// To manage a user's bad debt, all collateralized reserves for the user must be liquidated
-    /// before debt can be transferred to the backstop.
+    /// If the caller is not the admin, the ledger has already passed, or the previous snapshot
+    /// ledger has not been reached
+    fn declare_snapshot(e: Env, ledger: u32) -> u32;
+
+    /// Fetch the most recently declared position snapshot, if any
+    fn get_snapshot(e: Env) -> Option<PositionSnapshot>;
+
+    /// Fetch the positions of a user's sub-account at a snapshot ledger, or None if the snapshot
+    /// does not exist or its ledger has not been reached
     ///
-    /// To manage a backstop's bad debt, the backstop module must be below a critical threshold
-    /// to allow bad debt to be burnt.
+    /// ### Arguments
+    /// * `id` - The ID of the snapshot
+    /// * `user` - The address of the user
+    /// * `account_id` - The ID of the sub-account, where 0 is the user's main account
+    fn get_snapshot_positions(e: Env, id: u32, user: Address, account_id: u32) -> Option<Positions>;
+
+    /// (Admin only) Set the bounds on prices loaded from the pool's oracle. Prices older than
+    /// the max price age, or that change more than the max deviation from the last price loaded
+    /// for the asset in the last hour, are rejected. Liquidations are not bound by the max
+    /// deviation.
     ///
     /// ### Arguments
-    /// * `user` - The user who currently possesses bad debt
+    /// * `config` - The max price age in seconds, and the max deviation (7 decimals)
     ///
     /// ### Panics
-    /// If the user has collateral posted
-    fn bad_debt(e: Env, user: Address);
+    /// If the caller is not the admin or the config is invalid
+    fn set_oracle_config(e: Env, config: OracleConfig);
 
-    /// Update the pool status based on the backstop state - backstop triggered status' are odd numbers
-    /// * 1 = backstop active - if the minimum backstop deposit has been reached
-    ///                and 30% of backstop deposits are not queued for withdrawal
-    ///                then all pool operations are permitted
-    /// * 3 = backstop on-ice - if the minimum backstop deposit has not been reached
-    ///                or 30% of backstop deposits are queued for withdrawal and admin active isn't set
-    ///                or 50% of backstop deposits are queued for withdrawal
-    ///                then borrowing and cancelling liquidations are not permitted
-    /// * 5 = backstop frozen - if 60% of backstop deposits are queued for withdrawal and admin on-ice isn't set
-    ///                or 75% of backstop deposits are queued for withdrawal
-    ///                then all borrowing, cancelling liquidations, and supplying are not permitted
+    /// Fetch the bounds on prices loaded from the pool's oracle
+    fn get_oracle_config(e: Env) -> OracleConfig;
+
+    /// (Admin only) Set the fee charged on flash loans. The fee is paid by the flash loan
+    /// borrower in addition to the borrowed amount and is credited to the backstop.
+    ///
+    /// ### Arguments
+    /// * `flash_loan_fee` - The new flash loan fee (7 decimals)
     ///
     /// ### Panics
-    /// If the pool is currently on status 4, "admin-freeze", where only the admin
-    /// can perform a status update via `set_status`
-    fn update_status(e: Env) -> u32;
+    /// If the caller is not the admin or the fee is not within [0, 1)
+    #[cfg(feature = "flash-loans")]
+    fn set_flash_loan_fee(e: Env, flash_loan_fee: u32);
 
-    /// (Admin only) Pool status is changed to "pool_status"
-    /// * 0 = admin active - requires that the backstop threshold is met
-    ///                 and less than 50% of backstop deposits are queued for withdrawal
-    /// * 2 = admin on-ice - requires that less than 75% of backstop deposits are queued for withdrawal
-    /// * 4 = admin frozen - can always be set
+    /// (Admin only) Set the volume tiers for the flash loan fee. Users whose transaction count
+    /// and volume within the current weekly fee epoch reach a tier receive the tier's discount
+    /// on the flash loan fee.
     ///
     /// ### Arguments
-    /// * 'pool_status' - The pool status to be set
+    /// * `fee_tiers` - The fee tiers, ordered from lowest to highest
     ///
     /// ### Panics
-    /// If the caller is not the admin
-    /// If the specified conditions are not met for the status to be set
-    fn set_status(e: Env, pool_status: u32);
+    /// If the caller is not the admin or the tiers are invalid
+    #[cfg(feature = "flash-loans")]
+    fn set_fee_tiers(e: Env, fee_tiers: Vec<FeeTier>);
 
-    /// Update the reserve's bToken rate based on the pool's balance. This is useful for tokens where
-    ///  a holder's balance can increase outside of a direct transfer.
+    /// (Admin only) Queues setting data for a reserve in the pool
     ///
     /// ### Arguments
-    /// * `asset` - The address of the asset to gulp
+    /// * `asset` - The underlying asset to add as a reserve
+    /// * `config` - The ReserveConfig for the reserve
+    /// * `ir_config` - The custom interest rate curve for the reserve, or None to use the
+    ///                 curve defined by the ReserveConfig
     ///
-    /// Returns the amount of tokens gulped
-    fn gulp(e: Env, asset: Address) -> i128;
+    /// ### Panics
+    /// If the caller is not the admin, or the config or interest rate curve is invalid
+    fn queue_set_reserve(
+        e: Env,
+        asset: Address,
+        metadata: ReserveConfig,
+        ir_config: Option<InterestRateConfig>,
+    );
 
-    /********* Emission Functions **********/
+    /// (Admin only) Cancels the queued set of a reserve in the pool
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset to add as a reserve
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the reserve is not queued for initialization
+    fn cancel_set_reserve(e: Env, asset: Address);
 
-    /// Consume emissions from the backstop and distribute to the reserves based
-    /// on the reserve emission configuration.
+    /// (Admin only) Executes the queued set of a reserve in the pool
     ///
-    /// Returns amount of new tokens emitted
-    fn gulp_emissions(e: Env) -> i128;
+    /// ### Arguments
+    /// * `asset` - The underlying asset to add as a reserve
+    ///
+    /// ### Panics
+    /// If the reserve is not queued for initialization
+    /// or is already setup
+    /// or has invalid metadata
+    fn set_reserve(e: Env, asset: Address) -> u32;
 
-    /// (Admin only) Set the emission configuration for the pool
+    /// Fetch the queued set of a reserve in the pool, if any
     ///
-    /// Changes will be applied in the next pool `update_emissions`, and affect the next emission cycle
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    fn get_queued_reserve(e: Env, asset: Address) -> Option<QueuedReserveInit>;
+
+    /// (Admin only) Remove a reserve without any supply, liabilities or backstop credit. The
+    /// reserve is disabled and keeps its index until every reserve after it is removed, after
+    /// which it is dropped from the reserve list. Setting a removed reserve that is still in the
+    /// reserve list restores it.
     ///
     /// ### Arguments
-    /// * `res_emission_metadata` - A vector of ReserveEmissionMetadata to update metadata to
+    /// * `asset` - The underlying asset of the reserve
     ///
     /// ### Panics
-    /// * If the caller is not the admin
-    /// * If the sum of ReserveEmissionMetadata shares is greater than 1
-    fn set_emissions_config(e: Env, res_emission_metadata: Vec<ReserveEmissionMetadata>);
+    /// If the caller is not the admin, the reserve does not exist or is not empty, or an interest
+    /// auction is in progress
+    fn remove_reserve(e: Env, asset: Address);
 
-    /// Claims outstanding emissions for the caller for the given reserve's
+    /// (Admin only) Withdraw the supply and collateral of a user's account from a disabled
+    /// reserve to the user, so dust positions don't prevent the reserve from being removed
     ///
-    /// Returns the number of tokens claimed
+    /// Returns the amount of underlying tokens sent to the user
     ///
     /// ### Arguments
-    /// * `from` - The address claiming
-    /// * `reserve_token_ids` - Vector of reserve token ids
-    /// * `to` - The Address to send the claimed tokens to
-    fn claim(e: Env, from: Address, reserve_token_ids: Vec<u32>, to: Address) -> i128;
+    /// * `asset` - The underlying asset of the reserve
+    /// * `user` - The address of the user
+    /// * `account_id` - The sub-account of the user
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the reserve is enabled, the account has liabilities in
+    /// the reserve or the account is unhealthy after the withdrawal
+    fn clear_reserve_dust(e: Env, asset: Address, user: Address, account_id: u32) -> i128;
 
-    /// Get the emissions data for a reserve
+    /// (Admin only) Queues a debt rebate for a set of accounts in a reserve. The rebate forgives
+    /// a portion of each account's liabilities once the timelock has passed.
     ///
     /// ### Arguments
-    /// * `reserve_token_id` - The reserve token id. This is a unique identifier for the type of position in a pool. For
-    ///                        dTokens, a reserve token id (reserve_index * 2). For bTokens, a reserve token id (reserve_index * 2) + 1.
-    fn get_reserve_emissions(e: Env, reserve_token_id: u32) -> ReserveEmissionData;
+    /// * `asset` - The underlying asset of the reserve
+    /// * `accounts` - The accounts receiving the rebate
+    /// * `rebate` - The portion of each account's liabilities to forgive (7 decimals)
+    /// * `funder` - The address that transfers the forgiven tokens to the pool, or None to
+    ///              fund the rebate from the reserve's backstop credit
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, a rebate is already queued for the reserve, an account is
+    /// included more than once, or the arguments are invalid
+    fn queue_debt_rebate(
+        e: Env,
+        asset: Address,
+        accounts: Vec<Address>,
+        rebate: i128,
+        funder: Option<Address>,
+    );
 
-    /// Get the emissions data for a user
+    /// (Admin only) Cancels the queued debt rebate for a reserve
     ///
     /// ### Arguments
-    /// * `user` - The address of the user
-    /// * `reserve_token_id` - The reserve token id. This is a unique identifier for the type of position in a pool. For
-    ///                        dTokens, a reserve token id (reserve_index * 2). For bTokens, a reserve token id (reserve_index * 2) + 1.
-    fn get_user_emissions(e: Env, user: Address, reserve_token_id: u32) -> UserEmissionData;
+    /// * `asset` - The underlying asset of the reserve
+    ///
+    /// ### Panics
+    /// If the caller is not the admin
+    fn cancel_debt_rebate(e: Env, asset: Address);
 
-    /***** Auction / Liquidation Functions *****/
+    /// Executes the queued debt rebate for a reserve
+    ///
+    /// Returns the total amount of underlying tokens forgiven
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    ///
+    /// ### Panics
+    /// If the rebate is not queued or unlocked, the funder does not authorize the transfer,
+    /// or the reserve's backstop credit cannot cover the rebate
+    fn debt_rebate(e: Env, asset: Address) -> i128;
 
-    /// Create a new auction. Auctions are used to process liquidations, bad debt, and interest.
+    /// (Admin only) Set the anomaly detection thresholds for a reserve. If the net outflow from the reserve
+    /// within a single ledger exceeds a threshold, the reserve is restricted from new borrows pending admin
+    /// review. Flash loans that would exceed a threshold are rejected.
     ///
     /// ### Arguments
-    /// * `auction_type` - The type of auction, 0 for liquidation auction, 1 for bad debt auction, and 2 for interest auction
-    /// * `user` - The Address involved in the auction. This is generally the source of the assets being auctioned.
-    ///            For bad debt and interest auctions, this is expected to be the backstop address.
-    /// * `bid` - The set of assets to include in the auction bid, or what the filler spends when filling the auction.
-    /// * `lot` - The set of assets to include in the auction lot, or what the filler receives when filling the auction.
-    /// * `percent` - The percent of the assets to be auctioned off as a percentage (15 => 15%). For bad debt and interest auctions.
-    ///               this is expected to be 100.
-    fn new_auction(
+    /// * `asset` - The underlying asset of the reserve
+    /// * `max_b_supply_delta` - The maximum net decrease in b_supply within a ledger, as a portion of the b_supply (7 decimals)
+    /// * `max_d_supply_delta` - The maximum net increase in d_supply within a ledger, as a portion of the d_supply (7 decimals)
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the reserve does not exist, or a threshold is not positive
+    fn set_anomaly_thresholds(
         e: Env,
-        auction_type: u32,
-        user: Address,
-        bid: Vec<Address>,
-        lot: Vec<Address>,
-        percent: u32,
-    ) -> AuctionData;
+        asset: Address,
+        max_b_supply_delta: i128,
+        max_d_supply_delta: i128,
+    );
 
-    /// Fetch an auction from the ledger. Returns a quote based on the current block.
+    /// (Admin only) Lift the restriction placed on a reserve after a supply anomaly was detected
     ///
     /// ### Arguments
-    /// * `auction_type` - The type of auction, 0 for liquidation auction, 1 for bad debt auction, and 2 for interest auction
-    /// * `user` - The Address involved in the auction
+    /// * `asset` - The underlying asset of the reserve
     ///
     /// ### Panics
-    /// If the auction does not exist
-    fn get_auction(e: Env, auction_type: u32, user: Address) -> AuctionData;
-}
+    /// If the caller is not the admin or the reserve is not restricted
+    fn lift_restriction(e: Env, asset: Address);
 
-#[contractimpl]
-impl PoolContract {
-    /// Initialize the pool
+    /// (Admin only) Set the pause guardian of the pool. The pause guardian can pause individual
+    /// pool actions without changing the pool status.
     ///
     /// ### Arguments
-    /// Creator supplied:
-    /// * `admin` - The Address for the admin
-    /// * `name` - The name of the pool
-    /// * `oracle` - The contract address of the oracle
-    /// * `backstop_take_rate` - The take rate for the backstop (7 decimals)
-    /// * `max_positions` - The maximum number of positions a user is permitted to have
+    /// * `guardian` - The address of the pause guardian, or None to remove the pause guardian
     ///
-    /// Pool Factory supplied:
-    /// * `backstop_id` - The contract address of the pool's backstop module
-    /// * `blnd_id` - The contract ID of the BLND token
-    pub fn __constructor(
-        e: Env,
+    /// ### Panics
+    /// If the caller is not the admin
+    fn set_pause_guardian(e: Env, guardian: Option<Address>);
+
+    /// (Admin only) Set the utilization hook of the pool. The hook is called with the reserve's
+    /// utilization and rate modifier when a reserve that accrued interest is stored, so an external
+    /// controller can tune the reserve's interest rate config through `queue_set_reserve`. Hook
+    /// failures are ignored.
+    ///
+    /// ### Arguments
+    /// * `hook` - The address of the hook contract, or None to remove the hook
+    ///
+    /// ### Panics
+    /// If the caller is not the admin
+    fn set_util_hook(e: Env, hook: Option<Address>);
+
+    /// (Admin only) Set the share of the backstop take rate paid to referrers of borrowers
+    ///
+    /// ### Arguments
+    /// * `rate` - The share of the backstop take rate (7 decimals)
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the rate is over 100%
+    fn set_referral_rate(e: Env, rate: u32);
+
+    /// Fetch the share of the backstop take rate paid to referrers of borrowers (7 decimals)
+    fn get_referral_rate(e: Env) -> u32;
+
+    /// (Admin only) Restrict borrowing from the pool to the borrower allowlist. Supplying is not
+    /// restricted. Existing liabilities of users not on the allowlist can still be repaid.
+    ///
+    /// ### Arguments
+    /// * `enabled` - If borrowing is restricted to the borrower allowlist
+    ///
+    /// ### Panics
+    /// If the caller is not the admin
+    fn set_borrow_allowlist(e: Env, enabled: bool);
+
+    /// Check if borrowing from the pool is restricted to the borrower allowlist
+    fn get_borrow_allowlist(e: Env) -> bool;
+
+    /// (Admin only) Add or remove a user from the borrower allowlist
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `allowed` - If the user is on the borrower allowlist
+    ///
+    /// ### Panics
+    /// If the caller is not the admin
+    fn set_allowed_borrower(e: Env, user: Address, allowed: bool);
+
+    /// Check if a user is on the borrower allowlist
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    fn get_allowed_borrower(e: Env, user: Address) -> bool;
+
+    /// (Admin only) Set the interest rebate for repaying a reserve shortly after borrowing. A
+    /// repayment within "window" seconds of the user's most recent borrow from the reserve is
+    /// refunded "rebate" of the interest accrued on the repaid debt, funded from the backstop
+    /// credit.
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    /// * `window` - The number of seconds after borrowing a repayment receives the rebate, or 0 to
+    ///              remove the rebate
+    /// * `rebate` - The share of the accrued interest refunded (7 decimals)
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the reserve does not exist, the window is over 1 day, or the
+    /// rebate is over 100%
+    fn set_repay_grace(e: Env, asset: Address, window: u64, rebate: u32);
+
+    /// Fetch the interest rebate for repaying a reserve shortly after borrowing, if it exists
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    fn get_repay_grace(e: Env, asset: Address) -> Option<RepayGraceConfig>;
+
+    /// (Admin only) Set the fixed-rate borrowing config for a reserve. Existing fixed-rate loans
+    /// keep their fixed rate.
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    /// * `spread` - The spread added to the current borrow rate for fixed rates (7 decimals)
+    /// * `max_term` - The maximum number of seconds until a fixed-rate loan's maturity, or 0 to
+    ///                stop new fixed-rate loans
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the reserve does not exist, the spread is over 100%, or
+    /// the max term is under 1 week or over 365 days
+    fn set_fixed_rate_config(e: Env, asset: Address, spread: u32, max_term: u64);
+
+    /// Fetch the fixed-rate borrowing config for a reserve, if it supports fixed-rate loans
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    fn get_fixed_rate_config(e: Env, asset: Address) -> Option<FixedRateConfig>;
+
+    /// (Admin or pause guardian only) Pause pool actions. Actions already paused remain paused.
+    ///
+    /// Returns the bitmask of paused pool actions
+    ///
+    /// ### Arguments
+    /// * `caller` - The address of the admin or pause guardian
+    /// * `actions` - The bitmask of actions to pause
+    ///               (1 = borrow, 2 = supply, 4 = withdraw, 8 = flash loan, 16 = liquidation)
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or pause guardian, or the bitmask is invalid
+    fn pause_actions(e: Env, caller: Address, actions: u32) -> u32;
+
+    /// (Admin only) Unpause pool actions
+    ///
+    /// Returns the bitmask of paused pool actions
+    ///
+    /// ### Arguments
+    /// * `actions` - The bitmask of actions to unpause
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the bitmask is invalid
+    fn unpause_actions(e: Env, actions: u32) -> u32;
+
+    /// Fetch the pause guardian of the pool, if one is set
+    fn get_pause_guardian(e: Env) -> Option<Address>;
+
+    /// Fetch the utilization hook of the pool, if one is set
+    fn get_util_hook(e: Env) -> Option<Address>;
+
+    /// Fetch the bitmask of paused pool actions
+    fn get_paused_actions(e: Env) -> u32;
+
+    /// (Admin only) Set the keys allowed to sign price attestations for the pool. Signed
+    /// attestations can be submitted with `submit_with_prices` in place of the pool's oracle.
+    ///
+    /// ### Arguments
+    /// * `signers` - The ed25519 public keys allowed to sign price attestations
+    /// * `max_age` - The maximum age of an attestation in seconds
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the max age is zero, greater than one day, or greater
+    /// than the resolution of the pool's oracle
+    fn set_price_signers(e: Env, signers: Vec<BytesN<32>>, max_age: u64);
+
+    /// Fetch the pool configuration
+    fn get_config(e: Env) -> PoolConfig;
+
+    /// Fetch the admin address of the pool
+    fn get_admin(e: Env) -> Address;
+
+    /// Fetch a page of the pool's configuration history, ordered from oldest to newest. Each entry
+    /// records a new pool or reserve configuration, the ledger it was set in, and the address that
+    /// set it. Only the most recent 256 entries are retained.
+    ///
+    /// ### Arguments
+    /// * `start` - The version of the first entry to fetch
+    /// * `limit` - The maximum number of entries to fetch (at most 50)
+    fn get_config_history(e: Env, start: u32, limit: u32) -> Vec<ConfigHistoryEntry>;
+
+    /// Fetch the number of configuration history entries recorded by the pool
+    fn get_config_version(e: Env) -> u32;
+
+    /// Fetch information about a reserve
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    fn get_reserve(e: Env, asset: Address) -> Reserve;
+
+    /// Fetch the underlying assets of the pool's reserves, ordered by reserve index
+    fn get_reserve_list(e: Env) -> Vec<Address>;
+
+    /// Fetch the underlying assets of removed reserves that are still in the reserve list
+    fn get_removed_reserves(e: Env) -> Vec<Address>;
+
+    /// Fetch the most recent interest accrual snapshots of a reserve, oldest first. Snapshots are
+    /// kept in temporary storage and only cover recently active reserves.
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    fn get_accrual_history(e: Env, asset: Address) -> Vec<AccrualSnapshot>;
+
+    /// Fetch a preview of a reserve accrued to the current ledger timestamp, including the
+    /// current borrow and supply rates. Nothing is written to the ledger other than the
+    /// reserve's accrual snapshot.
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    fn get_reserve_preview(e: Env, asset: Address) -> ReservePreview;
+
+    /// Fetch the current borrow and supply rates of a reserve, as both APR and APY
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    fn get_reserve_rates(e: Env, asset: Address) -> ReserveRates;
+
+    /// Fetch the total underlying tokens ever credited to the backstop by each reserve, including
+    /// interest accrued to the current ledger timestamp. Unlike a reserve's `backstop_credit`, the
+    /// accrued fees are not reduced when the credit is auctioned or settled to the backstop.
+    fn get_accrued_fees(e: Env) -> Map<Address, i128>;
+
+    /// Fetch the amount of underlying tokens that can be supplied to a reserve before reaching
+    /// its collateral cap, including interest accrued to the current ledger timestamp
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    fn get_remaining_collateral_capacity(e: Env, asset: Address) -> i128;
+
+    /// Fetch the borrow rate bounds of a reserve, if they exist
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    fn get_rate_bounds(e: Env, asset: Address) -> Option<RateBounds>;
+
+    /// Fetch the half-life in seconds of the decay of a reserve's rate modifier, if any
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    fn get_ir_mod_half_life(e: Env, asset: Address) -> Option<u64>;
+
+    /// Fetch the custom interest rate curve of a reserve, if it exists
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    fn get_reserve_ir_config(e: Env, asset: Address) -> Option<InterestRateConfig>;
+
+    /// Fetch the positions for an address
+    ///
+    /// ### Arguments
+    /// * `address` - The address to fetch positions for
+    fn get_positions(e: Env, address: Address) -> Positions;
+
+    /// Fetch the positions of a sub-account of an address. Account 0 is the address's main account.
+    ///
+    /// ### Arguments
+    /// * `address` - The address to fetch positions for
+    /// * `account_id` - The ID of the sub-account
+    fn get_account_positions(e: Env, address: Address, account_id: u32) -> Positions;
+
+    /// Fetch the health factor for an address, including the effective collateral and effective
+    /// liabilities denominated in the oracle's base asset
+    ///
+    /// ### Arguments
+    /// * `address` - The address to fetch the health factor for
+    fn get_user_health_factor(e: Env, address: Address) -> HealthFactorData;
+
+    /// Fetch the value of the positions for an address in each reserve, denominated in the
+    /// oracle's base asset. Includes the raw and effective (factor-adjusted) collateral and
+    /// liability values, valued the same way as the address's health factor.
+    ///
+    /// Returns a map of reserve index to the value of the positions in the reserve
+    ///
+    /// ### Arguments
+    /// * `address` - The address to fetch the positions value for
+    fn get_positions_value(e: Env, address: Address) -> Map<u32, PositionValue>;
+
+    /// Fetch a compact JSON description of the positions for an address, including the underlying
+    /// amount of each position, the collateral and liability values in the oracle's base asset, and the
+    /// health factor band, so wallets and explorers can display lending positions
+    ///
+    /// ### Arguments
+    /// * `address` - The address to fetch the positions uri for
+    fn get_position_uri(e: Env, address: Address) -> String;
+
+    /// Fetch the volume tiers for the flash loan fee
+    #[cfg(feature = "flash-loans")]
+    fn get_fee_tiers(e: Env) -> Vec<FeeTier>;
+
+    /// Fetch the fee paying transaction count and volume for an address in the current fee epoch
+    ///
+    /// ### Arguments
+    /// * `address` - The address to fetch the fee volume for
+    #[cfg(feature = "flash-loans")]
+    fn get_user_fee_volume(e: Env, address: Address) -> UserFeeVolume;
+
+    /// Submit a set of requests to the pool where 'from' takes on the position, 'sender' sends any
+    /// required tokens to the pool and 'to' receives any tokens sent from the pool
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified
+    /// * `spender` - The address of the user who is sending tokens to the pool
+    /// * `to` - The address of the user who is receiving tokens from the pool
+    /// * `requests` - A vec of requests to be processed
+    ///
+    /// ### Panics
+    /// If the request is not able to be completed for cases like insufficient funds or invalid health factor
+    fn submit(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> Positions;
+
+    /// Submit a set of requests to the pool where 'from' takes on the position, 'sender' sends any
+    /// required tokens to the pool and 'to' receives any tokens sent from the pool
+    ///
+    /// Returns the new positions for 'from' and the result of each request, including the tokens
+    /// moved, the b_tokens or d_tokens minted or burnt, and the rate each request executed at
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified
+    /// * `spender` - The address of the user who is sending tokens to the pool
+    /// * `to` - The address of the user who is receiving tokens from the pool
+    /// * `requests` - A vec of requests to be processed
+    ///
+    /// ### Panics
+    /// If the request is not able to be completed for cases like insufficient funds or invalid health factor
+    fn submit_with_result(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> SubmitResult;
+
+    /// Submit a set of requests to the pool with options for each request, where 'from' takes on
+    /// the position, 'spender' sends any required tokens to the pool and 'to' receives any tokens
+    /// sent from the pool
+    ///
+    /// The options of a request can set its own `spender` and `to`, which are used in place of
+    /// 'spender' and 'to' for the tokens of that request. A request's spender must authorize its
+    /// transfer. All requests must act on the same sub-account.
+    ///
+    /// Returns the new positions for 'from' and the result of each request
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified
+    /// * `spender` - The address of the user who is sending tokens to the pool
+    /// * `to` - The address of the user who is receiving tokens from the pool
+    /// * `requests` - A vec of requests to be processed
+    /// * `options` - The options of each request, in the order of the requests
+    ///
+    /// ### Panics
+    /// If the request is not able to be completed for cases like insufficient funds or invalid health factor,
+    /// or the options don't match the requests
+    fn submit_with_options(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+        options: Vec<RequestOptions>,
+    ) -> SubmitResult;
+
+    /// Simulate a set of requests for 'from' without submitting them. The requests are processed
+    /// and health checked the same as `submit`, but nothing is written to the ledger and no
+    /// balances are changed.
+    ///
+    /// Returns the projected positions for 'from', the result of each request, the tokens the
+    /// spender would send to the pool, the tokens the pool would send to 'to', and the health
+    /// factor after the requests
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions would be modified
+    /// * `requests` - A vec of requests to be simulated
+    ///
+    /// ### Panics
+    /// If `submit` would fail for the requests, or a request fills or deletes an auction
+    fn simulate_submit(e: Env, from: Address, requests: Vec<Request>) -> SimulateResult;
+
+    /// Supply native XLM to the pool through the Stellar Asset Contract, where 'from' sends the XLM
+    /// and takes on the position. The XLM is transferred directly, so no approval is required.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user supplying the XLM
+    /// * `amount` - The amount of XLM to supply
+    /// * `as_collateral` - If the XLM should be supplied as collateral
+    ///
+    /// ### Panics
+    /// If native XLM is not a reserve of the pool or the supply can't be completed
+    fn supply_native(e: Env, from: Address, amount: i128, as_collateral: bool) -> Positions;
+
+    /// Repay native XLM liabilities through the Stellar Asset Contract, where 'from' sends the XLM
+    /// and their liabilities are repaid. The XLM is transferred directly, so no approval is required.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user repaying the XLM
+    /// * `amount` - The amount of XLM to repay, i128::MAX repays the entire position
+    ///
+    /// ### Panics
+    /// If native XLM is not a reserve of the pool or the repayment can't be completed
+    fn repay_native(e: Env, from: Address, amount: i128) -> Positions;
+
+    /// Borrow from a reserve at a fixed rate until a maturity, where 'from' takes on the position
+    /// and receives the borrowed tokens. The fixed rate is the reserve's borrow rate after the
+    /// borrow plus the reserve's fixed-rate spread.
+    ///
+    /// The debt is held as dTokens, and is settled to the fixed value of the loan on repayment or
+    /// with `settle_fixed_loan`. The fixed rate applies until the loan is settled after maturity.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user borrowing
+    /// * `asset` - The underlying asset of the reserve
+    /// * `amount` - The amount of underlying tokens to borrow
+    /// * `maturity` - The maturity of the loan, a multiple of a week. Must match the maturity of
+    ///                any existing fixed-rate loan of 'from' from the reserve.
+    ///
+    /// ### Panics
+    /// If the reserve does not support fixed-rate loans, the maturity is invalid, or the borrow
+    /// can't be completed
+    fn borrow_fixed(
+        e: Env,
+        from: Address,
+        asset: Address,
+        amount: i128,
+        maturity: u64,
+    ) -> Positions;
+
+    /// Settle a user's fixed-rate loan, minting or burning dTokens so the loan's dTokens are worth
+    /// the fixed value of the loan. Anyone can settle a loan after its maturity, which converts
+    /// it to a variable rate loan.
+    ///
+    /// Returns the dTokens minted (positive) or burnt (negative) for the user
+    ///
+    /// ### Arguments
+    /// * `caller` - The address settling the loan
+    /// * `user` - The address of the user with the loan
+    /// * `asset` - The underlying asset of the reserve
+    ///
+    /// ### Panics
+    /// If the user has no fixed-rate loan, or the caller is not the user and the loan is not
+    /// mature
+    fn settle_fixed_loan(e: Env, caller: Address, user: Address, asset: Address) -> i128;
+
+    /// Fetch a user's fixed-rate loan from a reserve, if any
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `asset` - The underlying asset of the reserve
+    fn get_fixed_loan(e: Env, user: Address, asset: Address) -> Option<FixedLoan>;
+
+    /// Fetch the fixed-rate tranche of a reserve
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    fn get_fixed_tranche(e: Env, asset: Address) -> FixedTranche;
+
+    /// Submit a set of requests to the pool where 'from' takes on the position, 'sender' sends any
+    /// required tokens to the pool and 'to' receives any tokens sent from the pool
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified and also the address of
+    /// the user who is sending and receiving the tokens to the pool.
+    /// * `flash_loan` - Arguments relative to the flash loan: receiver contract, asset and borroed amount.
+    /// * `requests` - A vec of requests to be processed
+    ///
+    /// ### Panics
+    /// If the request is not able to be completed for cases like insufficient funds or invalid health factor
+    #[cfg(feature = "flash-loans")]
+    fn flash_loan(
+        e: Env,
+        from: Address,
+        flash_loan: FlashLoan,
+        requests: Vec<Request>,
+    ) -> Positions;
+
+    /// Submit a set of requests to the pool after multiple flash loans, where 'from' takes on the position
+    /// and sends and receives any tokens. Every flash loaned asset is transferred to its receiver contract
+    /// before any receiver contract is called.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified and also the address of
+    /// the user who is sending and receiving the tokens to the pool.
+    /// * `flash_loans` - A vec of flash loans, each with a receiver contract, asset and borrowed amount.
+    /// * `requests` - A vec of requests to be processed
+    ///
+    /// ### Panics
+    /// If no flash loans are included or the request is not able to be completed for cases like insufficient
+    /// funds or invalid health factor
+    #[cfg(feature = "flash-loans")]
+    fn flash_loans(
+        e: Env,
+        from: Address,
+        flash_loans: Vec<FlashLoan>,
+        requests: Vec<Request>,
+    ) -> Positions;
+
+    /// Submit a set of requests to the pool after swapping collateral through a swap adapter, where
+    /// 'from' takes on the position and sends and receives any tokens. The new collateral is supplied
+    /// for 'from' and the old collateral is withdrawn and sent to the swap adapter, which must return
+    /// the new collateral to the pool.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified and also the address of
+    /// the user who is sending and receiving the tokens to the pool.
+    /// * `swap` - The swap adapter, the collateral being swapped from, and the collateral being swapped to
+    /// * `requests` - A vec of requests to be processed
+    ///
+    /// ### Panics
+    /// If the swap is invalid, the swap adapter does not return the new collateral, or the request is not
+    /// able to be completed for cases like insufficient funds or invalid health factor
+    #[cfg(feature = "flash-loans")]
+    fn swap_collateral(
+        e: Env,
+        from: Address,
+        swap: CollateralSwap,
+        requests: Vec<Request>,
+    ) -> Positions;
+
+    /// Deleverage the position of 'from' by repaying a liability with collateral swapped through a
+    /// swap adapter. The liability is repaid and the collateral is withdrawn and sent to the swap
+    /// adapter, which must return the debt asset to the pool. Any debt asset returned beyond the
+    /// outstanding liability is sent to 'from'.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified
+    /// * `swap` - The swap adapter, the collateral being swapped, and the debt asset being repaid
+    ///
+    /// ### Panics
+    /// If the swap is invalid, the swap adapter does not return the debt asset, or the resulting
+    /// position has an invalid health factor
+    #[cfg(feature = "flash-loans")]
+    fn deleverage(e: Env, from: Address, swap: CollateralSwap) -> Positions;
+
+    /// Migrate the positions of 'from' from another pool to this pool. The debt of 'from' is
+    /// borrowed in this pool and used to repay the source pool, and the collateral and supply of
+    /// 'from' are withdrawn from the source pool and supplied to this pool, atomically. 'from'
+    /// must also authorize the `submit_with_allowance` call made to the source pool.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being migrated
+    /// * `source_pool` - The address of the pool to migrate the positions from
+    /// * `assets` - The underlying assets of the positions to migrate
+    ///
+    /// ### Panics
+    /// If the source pool was not deployed by the pool factory, no positions are migrated, an asset
+    /// is not a reserve of both pools, the source pool does not send the withdrawn tokens, or the
+    /// resulting position has an invalid health factor
+    fn migrate_position(
+        e: Env,
+        from: Address,
+        source_pool: Address,
+        assets: Vec<Address>,
+    ) -> Positions;
+
+    /// Submit a set of requests to the pool for multiple users, where each user takes on the position
+    /// for their requests, the user's spender sends any required tokens to the pool and the user's
+    /// 'to' receives any tokens sent from the pool. All users share a single pool load.
+    ///
+    /// Returns the new positions for each user
+    ///
+    /// ### Arguments
+    /// * `batch` - A vec of (user, spender, to, requests) tuples to be processed
+    ///
+    /// ### Panics
+    /// If any request is not able to be completed for cases like insufficient funds or invalid health factor,
+    /// or a user is included more than once
+    fn submit_batch(
+        e: Env,
+        batch: Vec<(Address, Address, Address, Vec<Request>)>,
+    ) -> Vec<Positions>;
+
+    /// Submit a set of requests to the pool where 'from' takes on the position, 'spender' sends any
+    /// required tokens to the pool and 'to' receives any tokens sent from the pool. Prices for the
+    /// assets in 'prices' are taken from the signed attestations instead of the pool's oracle.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified
+    /// * `spender` - The address of the user who is sending tokens to the pool
+    /// * `to` - The address of the user who is receiving tokens from the pool
+    /// * `requests` - A vec of requests to be processed
+    /// * `prices` - A vec of price attestations signed by the pool's price signers
+    ///
+    /// ### Panics
+    /// If the request is not able to be completed for cases like insufficient funds, invalid health factor,
+    /// or an invalid price attestation
+    fn submit_with_prices(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+        prices: Vec<PriceAttestation>,
+    ) -> Positions;
+
+    /// Submit a set of requests signed off-chain by a Stellar account. Any relayer can submit the
+    /// signed requests. The account owning the signing key takes on the position, sends any
+    /// required tokens to the pool USING transfer_from, and receives any tokens sent from the pool.
+    ///
+    /// The signature is over the SHA-256 hash of the XDR encoded pool address, the SHA-256 hash of
+    /// the XDR encoded requests, the nonce, and the expiration. Each nonce can only be used once.
+    ///
+    /// Returns the new positions for the signer
+    ///
+    /// ### Arguments
+    /// * `requests` - A vec of requests to be processed
+    /// * `signature` - The signature of the account authorizing the requests
+    ///
+    /// ### Panics
+    /// If the signature is expired, reused, or invalid, or if the request is not able to be
+    /// completed for cases like insufficient funds or invalid health factor
+    fn submit_with_signature(
+        e: Env,
+        requests: Vec<Request>,
+        signature: SubmitSignature,
+    ) -> Positions;
+
+    /// Fetch the next nonce of an address for signed submits
+    ///
+    /// ### Arguments
+    /// * `address` - The address to fetch the nonce for
+    fn get_nonce(e: Env, address: Address) -> u64;
+
+    /// Allow 'delegatee' to borrow up to 'amount' of 'asset' on behalf of 'from'. The borrowed
+    /// liabilities are added to the positions of 'from', and must be supported by its collateral.
+    /// Setting a new amount replaces any existing credit delegation.
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the delegator whose positions take on the liabilities
+    /// * `delegatee` - The address allowed to borrow
+    /// * `asset` - The underlying asset that can be borrowed
+    /// * `amount` - The amount of the asset that can be borrowed
+    ///
+    /// ### Panics
+    /// If the amount is negative, 'from' is the delegatee, or the reserve does not exist
+    fn delegate_credit(e: Env, from: Address, delegatee: Address, asset: Address, amount: i128);
+
+    /// Fetch the remaining amount of an asset a delegatee can borrow on behalf of a delegator
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the delegator
+    /// * `delegatee` - The address allowed to borrow
+    /// * `asset` - The underlying asset that can be borrowed
+    fn get_credit_delegation(e: Env, from: Address, delegatee: Address, asset: Address) -> i128;
+
+    /// Submit a set of requests to the pool on behalf of 'from' using credit delegation, where
+    /// 'from' takes on the position, 'delegatee' sends any required tokens to the pool and 'to'
+    /// receives any tokens sent from the pool. Only borrow and repay requests are allowed, and
+    /// each borrow spends the delegatee's credit delegation for the borrowed asset.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the delegator whose positions are being modified
+    /// * `delegatee` - The address of the delegatee submitting the requests
+    /// * `to` - The address of the user who is receiving tokens from the pool
+    /// * `requests` - A vec of requests to be processed
+    ///
+    /// ### Panics
+    /// If a request is not a borrow or repay, a borrow exceeds the credit delegation, or the
+    /// request is not able to be completed for cases like insufficient funds or invalid health factor
+    fn submit_with_delegation(
+        e: Env,
+        from: Address,
+        delegatee: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> Positions;
+
+    /// Submit a set of requests to the pool where 'from' takes on the position, 'spender' sends any
+    /// required tokens to the pool USING transfer_from and 'to' receives any tokens sent from the pool.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified
+    /// * `spender` - The address of the user who is sending tokens to the pool
+    /// * `to` - The address of the user who is receiving tokens from the pool
+    /// * `requests` - A vec of requests to be processed
+    ///
+    /// ### Panics
+    /// If the request is not able to be completed for cases like insufficient funds, insufficient allowance, or invalid health factor
+    fn submit_with_allowance(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> Positions;
+
+    /// Submit a set of requests to the pool through a referrer. The first referrer a user submits
+    /// through is attributed to the user, and earns a share of the backstop take rate on the
+    /// interest the user pays.
+    ///
+    /// Returns the new positions for 'from'
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user whose positions are being modified
+    /// * `spender` - The address of the user who is sending tokens to the pool
+    /// * `to` - The address of the user who is receiving tokens from the pool
+    /// * `requests` - A vec of requests to be processed
+    /// * `referrer` - The address that referred the user
+    ///
+    /// ### Panics
+    /// If the request is not able to be completed, or 'from' is the referrer
+    fn submit_with_referral(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+        referrer: Address,
+    ) -> Positions;
+
+    /// Claim the referral fees owed to a referrer
+    ///
+    /// Returns the amount claimed for each asset
+    ///
+    /// ### Arguments
+    /// * `referrer` - The address of the referrer
+    /// * `assets` - The underlying assets to claim fees for
+    /// * `to` - The Address to send the claimed fees to
+    fn claim_referral_fees(
+        e: Env,
+        referrer: Address,
+        assets: Vec<Address>,
+        to: Address,
+    ) -> Vec<i128>;
+
+    /// Fetch the referrer attributed to a user, if one exists
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    fn get_referrer(e: Env, user: Address) -> Option<Address>;
+
+    /// Fetch the referral fees owed to a referrer for a reserve
+    ///
+    /// ### Arguments
+    /// * `referrer` - The address of the referrer
+    /// * `asset` - The underlying asset of the reserve
+    fn get_referral_fees(e: Env, referrer: Address, asset: Address) -> i128;
+
+    /// Manage bad debt. Debt is considered "bad" if there is no longer has any collateral posted.
+    ///
+    /// To manage a user's bad debt, all collateralized reserves for the user must be liquidated
+    /// before debt can be transferred to the backstop.
+    ///
+    /// To manage a backstop's bad debt, the backstop module must be below a critical threshold
+    /// to allow bad debt to be burnt.
+    ///
+    /// ### Arguments
+    /// * `user` - The user who currently possesses bad debt
+    ///
+    /// ### Panics
+    /// If the user has collateral posted
+    fn bad_debt(e: Env, user: Address);
+
+    /// Manage bad debt of a sub-account. Debt is considered "bad" if there is no longer has any
+    /// collateral posted in the sub-account.
+    ///
+    /// ### Arguments
+    /// * `user` - The user who currently possesses bad debt
+    /// * `account_id` - The ID of the sub-account that possesses bad debt
+    ///
+    /// ### Panics
+    /// If the sub-account has collateral posted
+    fn bad_debt_account(e: Env, user: Address, account_id: u32);
+
+    /// (Admin only) Repay the backstop's bad debt in the pool's reserve for the backstop's USDC
+    /// directly with USDC exited from the backstop's LP tokens, bypassing the bad debt auction.
+    ///
+    /// Returns the amount of USDC repaid
+    ///
+    /// ### Arguments
+    /// * `max_tokens_in` - The maximum amount of backstop tokens the backstop can burn to exit
+    ///                     the USDC
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the backstop does not hold bad debt in the USDC reserve, a
+    /// bad debt auction is in progress, or the backstop is unable to cover the debt within
+    /// `max_tokens_in`
+    fn recapitalize(e: Env, max_tokens_in: i128) -> i128;
+
+    /// (Admin only) Set the schedule for settling small amounts of backstop credit directly into
+    /// backstop deposits with `settle_backstop_credit`
+    ///
+    /// ### Arguments
+    /// * `interval` - The minimum number of seconds between settlements
+    /// * `threshold` - The maximum value of backstop credit that can be settled, denominated in the
+    ///                 oracle's base asset
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the interval is zero, or the threshold is not positive
+    fn set_credit_settlement(e: Env, interval: u64, threshold: i128);
+
+    /// (Admin only) Settle a reserve's backstop credit by donating it to the backstop as backstop
+    /// tokens, without an interest auction. Only BLND and USDC credit valued under the settlement
+    /// threshold can be settled, at most once per settlement interval.
+    ///
+    /// Returns the amount of backstop credit settled
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    /// * `min_tokens_out` - The minimum amount of backstop tokens the backstop must mint with the
+    ///                      credit
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, settlements are not configured, the settlement interval has
+    /// not passed, an interest auction is in progress, the backstop mints less than
+    /// `min_tokens_out`, or the reserve's backstop credit can't be settled
+    fn settle_backstop_credit(e: Env, asset: Address, min_tokens_out: i128) -> i128;
+
+    /// (Admin only) Queue setting the boosted collateral and liability factors of an e-mode
+    /// category. Reserves are assigned to a category through their reserve config. The factors
+    /// must be between the factors of each reserve in the category and `MAX_EMODE_BOOST` above
+    /// them.
+    ///
+    /// ### Arguments
+    /// * `category` - The e-mode category
+    /// * `config` - The collateral and liability factors of the category
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, a change is already queued, the category is 0, or the
+    /// factors are invalid
+    fn queue_set_emode_category(e: Env, category: u32, config: EModeCategory);
+
+    /// (Admin only) Cancel the queued change to an e-mode category
+    ///
+    /// ### Arguments
+    /// * `category` - The e-mode category
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or no change is queued for the category
+    fn cancel_set_emode_category(e: Env, category: u32);
+
+    /// Execute the queued change to an e-mode category once its timelock has passed
+    ///
+    /// Returns the new config of the category
+    ///
+    /// ### Arguments
+    /// * `category` - The e-mode category
+    ///
+    /// ### Panics
+    /// If no change is queued for the category, the change is still locked, or the factors are
+    /// no longer valid for the reserves in the category
+    fn set_emode_category(e: Env, category: u32) -> EModeCategory;
+
+    /// Fetch the queued change to an e-mode category, if any
+    ///
+    /// ### Arguments
+    /// * `category` - The e-mode category
+    fn get_queued_emode_category(e: Env, category: u32) -> Option<QueuedEModeCategory>;
+
+    /// Fetch the config of an e-mode category, if it exists
+    ///
+    /// ### Arguments
+    /// * `category` - The e-mode category
+    fn get_emode_category(e: Env, category: u32) -> Option<EModeCategory>;
+
+    /// Enter an e-mode category for 'user', or leave e-mode with category 0. While all of the
+    /// user's collateral and liabilities are in reserves of the category, the category's
+    /// collateral and liability factors are used for the user's health factor.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `category` - The e-mode category to enter, or 0 to leave e-mode
+    ///
+    /// ### Panics
+    /// If the category does not exist or the user's health factor is too low after the change
+    fn set_user_emode(e: Env, user: Address, category: u32);
+
+    /// Fetch the e-mode category a user has entered, or 0 if the user has not entered one
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    fn get_user_emode(e: Env, user: Address) -> u32;
+
+    /// Silo or unsilo a user's supplied assets. While siloed, nothing can be added to the user's
+    /// collateral positions, so supplied assets can't be exposed to liquidation without the user
+    /// explicitly unsiloing them first. Existing collateral is not affected.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `siloed` - If the user's supplied assets are siloed
+    fn set_siloed_deposits(e: Env, user: Address, siloed: bool);
+
+    /// Check if a user's supplied assets are siloed and can't be used as collateral
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    fn get_siloed_deposits(e: Env, user: Address) -> bool;
+
+    /// Allow or disallow a depositor to supply collateral on behalf of a user with a
+    /// `SupplyCollateralFor` request. Deposits add to the user's positions and count towards the
+    /// pool's max positions, so they require the user's opt-in.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `depositor` - The address of the depositor
+    /// * `allowed` - If the depositor can supply collateral on behalf of the user
+    fn allow_deposits_from(e: Env, user: Address, depositor: Address, allowed: bool);
+
+    /// Check if a depositor can supply collateral on behalf of a user
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `depositor` - The address of the depositor
+    fn get_deposits_allowed(e: Env, user: Address, depositor: Address) -> bool;
+
+    /// Opt in or out of a self-repaying loan. While opted in, the yield earned on the user's
+    /// collateral in the reserve repays the user's liability in the same reserve each time the
+    /// user submits requests, or when anyone calls `self_repay`. Yield accrued under a previous
+    /// config is used to repay before the config changes.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `asset` - The underlying asset of the reserve, or None to opt out
+    ///
+    /// ### Panics
+    /// If the reserve does not exist
+    fn set_self_repay(e: Env, user: Address, asset: Option<Address>);
+
+    /// Repay a user's liability with the yield accrued on their collateral, if the user has opted
+    /// in to a self-repaying loan. Permissionless, as it can only improve the user's position.
+    ///
+    /// Returns the amount of underlying tokens repaid
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    fn self_repay(e: Env, user: Address) -> i128;
+
+    /// Fetch a user's self-repaying loan config, or None if the user has not opted in
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    fn get_self_repay(e: Env, user: Address) -> Option<SelfRepayConfig>;
+
+    /// (Admin only) Deploy a bToken contract for a reserve. The bToken is a SEP-41 token that
+    /// represents the non-collateral supply of the reserve, with balances tracked by the pool.
+    /// The bToken is deployed from the wasm hash set by governance on the backstop.
+    ///
+    /// Returns the address of the bToken contract
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    /// * `name` - The name of the bToken
+    /// * `symbol` - The symbol of the bToken
+    ///
+    /// ### Panics
+    /// If the caller is not the admin, the reserve does not exist, the reserve already has a
+    /// bToken, or the bToken wasm hash is not set
+    fn deploy_b_token(e: Env, asset: Address, name: String, symbol: String) -> Address;
+
+    /// Fetch the bToken contract of a reserve, if it has been deployed
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    fn get_b_token(e: Env, asset: Address) -> Option<Address>;
+
+    /// Fetch the amount of bTokens 'id' holds as non-collateral supply of a reserve
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    /// * `id` - The address of the user
+    fn b_token_balance(e: Env, asset: Address, id: Address) -> i128;
+
+    /// Fetch the total amount of bTokens issued for a reserve
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    fn b_token_supply(e: Env, asset: Address) -> i128;
+
+    /// (bToken only) Transfer non-collateral supply of a reserve from 'from' to 'to'. Only
+    /// callable by the reserve's bToken, which is responsible for authorizing 'from'.
+    ///
+    /// ### Arguments
+    /// * `asset` - The underlying asset of the reserve
+    /// * `from` - The address sending the bTokens
+    /// * `to` - The address receiving the bTokens
+    /// * `amount` - The amount of bTokens to transfer
+    ///
+    /// ### Panics
+    /// If the caller is not the reserve's bToken, 'from' does not hold enough supply, or 'to'
+    /// would exceed the max positions
+    fn b_token_transfer(e: Env, asset: Address, from: Address, to: Address, amount: i128);
+
+    /// Update the pool status based on the backstop state - backstop triggered status' are odd numbers
+    /// * 1 = backstop active - if the minimum backstop deposit has been reached
+    ///                and 30% of backstop deposits are not queued for withdrawal
+    ///                then all pool operations are permitted
+    /// * 3 = backstop on-ice - if the minimum backstop deposit has not been reached
+    ///                or 30% of backstop deposits are queued for withdrawal and admin active isn't set
+    ///                or 50% of backstop deposits are queued for withdrawal
+    ///                then borrowing and cancelling liquidations are not permitted
+    /// * 5 = backstop frozen - if 60% of backstop deposits are queued for withdrawal and admin on-ice isn't set
+    ///                or 75% of backstop deposits are queued for withdrawal
+    ///                then all borrowing, cancelling liquidations, and supplying are not permitted
+    ///
+    /// ### Panics
+    /// If the pool is currently on status 4, "admin-freeze", where only the admin
+    /// can perform a status update via `set_status`, or status 7, "decommissioned"
+    fn update_status(e: Env) -> u32;
+
+    /// (Admin only) Pool status is changed to "pool_status"
+    /// * 0 = admin active - requires that the backstop threshold is met
+    ///                 and less than 50% of backstop deposits are queued for withdrawal
+    /// * 2 = admin on-ice - requires that less than 75% of backstop deposits are queued for withdrawal
+    /// * 4 = admin frozen - can always be set
+    /// * 7 = decommissioned - requires that the pool is frozen and has no outstanding liabilities.
+    ///                 Backstop depositors can withdraw immediately. The status is permanent.
+    ///
+    /// ### Arguments
+    /// * 'pool_status' - The pool status to be set
+    ///
+    /// ### Panics
+    /// If the caller is not the admin
+    /// If the pool is decommissioned
+    /// If the specified conditions are not met for the status to be set
+    fn set_status(e: Env, pool_status: u32);
+
+    /// Update the reserve's bToken rate based on the pool's balance. This is useful for tokens where
+    ///  a holder's balance can increase outside of a direct transfer.
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the asset to gulp
+    ///
+    /// Returns the amount of tokens gulped
+    fn gulp(e: Env, asset: Address) -> i128;
+
+    /// Accrue interest on a reserve up to the current ledger timestamp and store it, booking the
+    /// backstop credit earned since the last accrual. Anyone can call this to keep idle reserves
+    /// up to date. Emits an `accrue_interest` event if interest accrued.
+    ///
+    /// Returns the accrued reserve
+    ///
+    /// ### Arguments
+    /// * `asset` - The address of the reserve asset
+    ///
+    /// ### Panics
+    /// If the reserve does not exist
+    fn accrue(e: Env, asset: Address) -> Reserve;
+
+    /********* Emission Functions **********/
+
+    /// Consume emissions from the backstop and distribute to the reserves based
+    /// on the reserve emission configuration.
+    ///
+    /// Returns amount of new tokens emitted
+    fn gulp_emissions(e: Env) -> i128;
+
+    /// (Admin only) Set the emission configuration for the pool
+    ///
+    /// Changes will be applied when the next emission epoch starts with `gulp_emissions`. Past
+    /// epochs keep the config they were started with.
+    ///
+    /// ### Arguments
+    /// * `res_emission_metadata` - A vector of ReserveEmissionMetadata to update metadata to
+    ///
+    /// ### Panics
+    /// * If the caller is not the admin
+    /// * If the sum of ReserveEmissionMetadata shares is greater than 1
+    fn set_emissions_config(e: Env, res_emission_metadata: Vec<ReserveEmissionMetadata>);
+
+    /// (Admin only) Set if reserve emissions are weighted by the time weighted utilization each
+    /// reserve realized since the last `gulp_emissions`, in addition to the configured shares
+    ///
+    /// ### Arguments
+    /// * `enabled` - True if reserve emissions are weighted by utilization
+    ///
+    /// ### Panics
+    /// * If the caller is not the admin
+    fn set_util_weighted_emissions(e: Env, enabled: bool);
+
+    /// Fetch if reserve emissions are weighted by utilization
+    fn get_util_weighted_emissions(e: Env) -> bool;
+
+    /// Claims outstanding emissions for the caller for the given reserve's
+    ///
+    /// Returns the number of tokens claimed
+    ///
+    /// ### Arguments
+    /// * `from` - The address claiming
+    /// * `reserve_token_ids` - Vector of reserve token ids
+    /// * `to` - The Address to send the claimed tokens to
+    fn claim(e: Env, from: Address, reserve_token_ids: Vec<u32>, to: Address) -> i128;
+
+    /// Get the emissions data for a reserve
+    ///
+    /// ### Arguments
+    /// * `reserve_token_id` - The reserve token id. This is a unique identifier for the type of position in a pool. For
+    ///                        dTokens, a reserve token id (reserve_index * 2). For bTokens, a reserve token id (reserve_index * 2) + 1.
+    fn get_reserve_emissions(e: Env, reserve_token_id: u32) -> ReserveEmissionData;
+
+    /// Get the emissions data for a user
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `reserve_token_id` - The reserve token id. This is a unique identifier for the type of position in a pool. For
+    ///                        dTokens, a reserve token id (reserve_index * 2). For bTokens, a reserve token id (reserve_index * 2) + 1.
+    fn get_user_emissions(e: Env, user: Address, reserve_token_id: u32) -> UserEmissionData;
+
+    /// Get an emission epoch, if it exists. An epoch starts each time emissions are gulped from
+    /// the backstop, and records the emission config applied and the tokens distributed to each
+    /// reserve token for the epoch.
+    ///
+    /// ### Arguments
+    /// * `epoch` - The index of the epoch
+    fn get_emission_epoch(e: Env, epoch: u32) -> Option<EmissionEpoch>;
+
+    /// Get the number of emission epochs recorded for the pool
+    fn get_emission_epoch_count(e: Env) -> u32;
+
+    /***** Auction / Liquidation Functions *****/
+
+    /// Create a new auction. Auctions are used to process liquidations, bad debt, and interest.
+    ///
+    /// ### Arguments
+    /// * `auction_type` - The type of auction, 0 for liquidation auction, 1 for bad debt auction, and 2 for interest auction
+    /// * `user` - The Address involved in the auction. This is generally the source of the assets being auctioned.
+    ///            For bad debt and interest auctions, this is expected to be the backstop address.
+    /// * `bid` - The set of assets to include in the auction bid, or what the filler spends when filling the auction.
+    /// * `lot` - The set of assets to include in the auction lot, or what the filler receives when filling the auction.
+    /// * `percent` - The percent of the assets to be auctioned off as a percentage (15 => 15%). For bad debt and interest auctions.
+    ///               this is expected to be 100.
+    ///
+    /// If the user of a liquidation auction registered a liquidation protection contract and the
+    /// contract restores their health, no auction is created and the bid and lot are empty.
+    ///
+    /// If the pool restricts user liquidations to the liquidator allowlist, liquidation auctions
+    /// must be created with `new_auction_with_keeper` by a liquidator on the allowlist.
+    #[cfg(feature = "auctions")]
+    fn new_auction(
+        e: Env,
+        auction_type: u32,
+        user: Address,
+        bid: Vec<Address>,
+        lot: Vec<Address>,
+        percent: u32,
+    ) -> AuctionData;
+
+    /// Create a new auction as "keeper". When the auction is filled by anyone other than the
+    /// keeper, the keeper is paid the keeper bounty's share of the filled value in underlying
+    /// tokens from the pool's backstop credit, capped at the credit available.
+    ///
+    /// ### Arguments
+    /// * `keeper` - The Address creating the auction
+    /// * `auction_type` - The type of auction, 0 for liquidation auction, 1 for bad debt auction, and 2 for interest auction
+    /// * `user` - The Address involved in the auction
+    /// * `bid` - The set of assets to include in the auction bid, or what the filler spends when filling the auction.
+    /// * `lot` - The set of assets to include in the auction lot, or what the filler receives when filling the auction.
+    /// * `percent` - The percent of the assets to be auctioned off as a percentage (15 => 15%)
+    #[cfg(feature = "auctions")]
+    fn new_auction_with_keeper(
+        e: Env,
+        keeper: Address,
+        auction_type: u32,
+        user: Address,
+        bid: Vec<Address>,
+        lot: Vec<Address>,
+        percent: u32,
+    ) -> AuctionData;
+
+    /// (Admin only) Set the share of a filled auction's value paid to the keeper that created the
+    /// auction
+    ///
+    /// ### Arguments
+    /// * `bounty` - The share of the filled value (7 decimals)
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the bounty is over 10%
+    #[cfg(feature = "auctions")]
+    fn set_keeper_bounty(e: Env, bounty: u32);
+
+    /// Set or remove the liquidation protection contract for 'user'. Before a liquidation auction
+    /// is created for the user, the contract's `on_liquidation_warning` is called with the user's
+    /// shortfall and can return collateral for the pool to pull and supply for the user.
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    /// * `protection` - The address of the protection contract, or None to remove it
+    #[cfg(feature = "auctions")]
+    fn set_liquidation_protection(e: Env, user: Address, protection: Option<Address>);
+
+    /// Fetch the liquidation protection contract registered by a user, if any
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    #[cfg(feature = "auctions")]
+    fn get_liquidation_protection(e: Env, user: Address) -> Option<Address>;
+
+    /// Fetch the share of a filled auction's value paid to the keeper that created the auction
+    /// (7 decimals)
+    #[cfg(feature = "auctions")]
+    fn get_keeper_bounty(e: Env) -> u32;
+
+    /// (Admin only) Set the value of backstop bad debt that starts a bad debt auction
+    /// automatically. Once exceeded, the auction is started by the next backstop deposit or
+    /// withdrawal for the pool, or by `draw_on_bad_debt`.
+    ///
+    /// ### Arguments
+    /// * `threshold` - The value of bad debt in the oracle's base asset with 14 decimals, or 0 to
+    ///   disable
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the threshold is negative
+    #[cfg(feature = "auctions")]
+    fn set_bad_debt_threshold(e: Env, threshold: i128);
+
+    /// Fetch the value of backstop bad debt that starts a bad debt auction automatically, or 0
+    /// if disabled
+    #[cfg(feature = "auctions")]
+    fn get_bad_debt_threshold(e: Env) -> i128;
+
+    /// (Admin only) Restrict user liquidations in the pool to the liquidator allowlist. While
+    /// restricted, only liquidators on the allowlist can create user liquidation auctions, as a
+    /// keeper, risk bot or sub-account liquidator, and fill them. Bad debt and interest auctions
+    /// are not restricted.
+    ///
+    /// ### Arguments
+    /// * `enabled` - If user liquidations are restricted to the liquidator allowlist
+    ///
+    /// ### Panics
+    /// If the caller is not the admin
+    #[cfg(feature = "auctions")]
+    fn set_liquidator_allowlist(e: Env, enabled: bool);
+
+    /// Check if user liquidations in the pool are restricted to the liquidator allowlist
+    #[cfg(feature = "auctions")]
+    fn get_liquidator_allowlist(e: Env) -> bool;
+
+    /// (Admin only) Add or remove an address from the liquidator allowlist
+    ///
+    /// ### Arguments
+    /// * `liquidator` - The address of the liquidator
+    /// * `allowed` - If the liquidator is on the liquidator allowlist
+    ///
+    /// ### Panics
+    /// If the caller is not the admin
+    #[cfg(feature = "auctions")]
+    fn set_allowed_liquidator(e: Env, liquidator: Address, allowed: bool);
+
+    /// Check if an address is on the liquidator allowlist
+    ///
+    /// ### Arguments
+    /// * `liquidator` - The address of the liquidator
+    #[cfg(feature = "auctions")]
+    fn get_allowed_liquidator(e: Env, liquidator: Address) -> bool;
+
+    /// Start a bad debt auction for the backstop's bad debt if its value exceeds the bad debt
+    /// threshold, the backstop holds tokens to auction, and no bad debt auction is in progress
+    ///
+    /// Returns the created auction, or None if no auction was started
+    #[cfg(feature = "auctions")]
+    fn draw_on_bad_debt(e: Env) -> Option<AuctionData>;
+
+    /// (Backstop only) Start a bad debt auction for the backstop's bad debt if its value exceeds
+    /// the bad debt threshold. Called by the backstop when its deposits for the pool change.
+    ///
+    /// ### Arguments
+    /// * `backstop_token` - The backstop token the pool's backstop holds
+    /// * `pool_backstop_data` - The pool's backstop data
+    ///
+    /// ### Panics
+    /// If the caller is not the backstop
+    #[cfg(feature = "auctions")]
+    fn check_bad_debt(e: Env, backstop_token: Address, pool_backstop_data: PoolBackstopData);
+
+    /// Create a new liquidation auction for a sub-account of a user
+    ///
+    /// ### Arguments
+    /// * `liquidator` - The Address creating the auction. If the pool restricts user liquidations,
+    ///                  it must authorize the call and be on the liquidator allowlist.
+    /// * `user` - The Address being liquidated
+    /// * `account_id` - The ID of the sub-account being liquidated
+    /// * `bid` - The set of assets to include in the auction bid, or what the filler spends when filling the auction.
+    /// * `lot` - The set of assets to include in the auction lot, or what the filler receives when filling the auction.
+    /// * `percent` - The percent of the assets to be auctioned off as a percentage (15 => 15%).
+    #[cfg(feature = "auctions")]
+    fn new_account_liquidation(
+        e: Env,
+        liquidator: Address,
+        user: Address,
+        account_id: u32,
+        bid: Vec<Address>,
+        lot: Vec<Address>,
+        percent: u32,
+    ) -> AuctionData;
+
+    /// Stake BLND to register 'bot' as a risk bot. Risk bots with at least the minimum stake can create
+    /// liquidation auctions in batches.
+    ///
+    /// Returns the bot's new stake
+    ///
+    /// ### Arguments
+    /// * `bot` - The address of the risk bot
+    /// * `amount` - The amount of BLND to stake
+    ///
+    /// ### Panics
+    /// If the amount is not positive or the bot does not authorize the transfer
+    #[cfg(feature = "auctions")]
+    fn stake_risk_bot(e: Env, bot: Address, amount: i128) -> i128;
+
+    /// Queue BLND for a risk bot to unstake after a 7 day delay. Queued BLND stays staked, and can
+    /// be slashed, until it is unstaked. Queuing more BLND restarts the delay.
+    ///
+    /// Returns the bot's queued unstake
+    ///
+    /// ### Arguments
+    /// * `bot` - The address of the risk bot
+    /// * `amount` - The amount of BLND to queue
+    ///
+    /// ### Panics
+    /// If the amount is not positive or the total queued is greater than the bot's stake
+    #[cfg(feature = "auctions")]
+    fn queue_unstake_risk_bot(e: Env, bot: Address, amount: i128) -> RiskBotUnstake;
+
+    /// Unstake queued BLND for a risk bot once the unstake delay has passed
+    ///
+    /// Returns the bot's new stake
+    ///
+    /// ### Arguments
+    /// * `bot` - The address of the risk bot
+    /// * `amount` - The amount of BLND to unstake
+    ///
+    /// ### Panics
+    /// If the amount is not positive, is greater than the bot's queued BLND or stake, or the
+    /// unstake delay has not passed
+    #[cfg(feature = "auctions")]
+    fn unstake_risk_bot(e: Env, bot: Address, amount: i128) -> i128;
+
+    /// (Risk bot only) Create user liquidation auctions for a batch of users. Each user's health
+    /// factor is re-verified, and requests for healthy users are skipped and slash a portion of the
+    /// bot's stake to the backstop.
+    ///
+    /// Returns the users an auction was created for
+    ///
+    /// ### Arguments
+    /// * `bot` - The address of the risk bot
+    /// * `requests` - The liquidation auctions to create
+    ///
+    /// ### Panics
+    /// If the bot's stake is under the minimum or an auction for an unhealthy user is unable to be created
+    #[cfg(feature = "auctions")]
+    fn new_liquidation_auctions(
+        e: Env,
+        bot: Address,
+        requests: Vec<LiquidationRequest>,
+    ) -> Vec<Address>;
+
+    /// Fetch an auction from the ledger. Returns a quote based on the current block.
+    ///
+    /// ### Arguments
+    /// * `auction_type` - The type of auction, 0 for liquidation auction, 1 for bad debt auction, and 2 for interest auction
+    /// * `user` - The Address involved in the auction
+    ///
+    /// ### Panics
+    /// If the auction does not exist
+    #[cfg(feature = "auctions")]
+    fn get_auction(e: Env, auction_type: u32, user: Address) -> AuctionData;
+
+    /// Fetch a page of the open auctions, ordered from oldest to newest. Filling or deleting an
+    /// auction removes it from the queue, shifting the position of every newer auction.
+    ///
+    /// ### Arguments
+    /// * `start` - The position in the queue of the first auction to fetch
+    /// * `limit` - The maximum number of auctions to fetch (at most 50)
+    #[cfg(feature = "auctions")]
+    fn get_auction_queue(e: Env, start: u32, limit: u32) -> Vec<QueuedAuction>;
+
+    /// Preview the fill of an auction at a block. Returns the bid the filler would spend and the
+    /// lot they would receive, as calculated when filling the auction.
+    ///
+    /// ### Arguments
+    /// * `auction_type` - The type of auction, 0 for liquidation auction, 1 for bad debt auction, and 2 for interest auction
+    /// * `user` - The Address involved in the auction
+    /// * `percent_filled` - The percentage being filled as a number (i.e. 15 => 15%)
+    /// * `at_block` - The block the fill would occur on
+    ///
+    /// ### Panics
+    /// If the auction does not exist, the block is before the auction began, or the percent filled
+    /// is invalid
+    #[cfg(feature = "auctions")]
+    fn preview_auction_fill(
+        e: Env,
+        auction_type: u32,
+        user: Address,
+        percent_filled: u64,
+        at_block: u32,
+    ) -> AuctionData;
+
+    /// (Admin only) Set the dutch auction curve used to price auctions. Auctions keep the curve
+    /// they were created with, so the new curve only applies to auctions created afterwards.
+    ///
+    /// ### Arguments
+    /// * `config` - The starting lot percent, the blocks for each phase of the curve, the max
+    ///              premium of a user liquidation's lot over its bid, and the floor on the oracle
+    ///              value of a user liquidation fill's bid over its lot. Fills under the floor
+    ///              revert. A floor of 0 disables it. Also sets the minimum percent and oracle
+    ///              value of a partial fill, so an auction can't be delayed by many small fills.
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the config is invalid
+    #[cfg(feature = "auctions")]
+    fn set_auction_config(e: Env, config: AuctionConfig);
+
+    /// Fetch the dutch auction curve used to price auctions
+    #[cfg(feature = "auctions")]
+    fn get_auction_config(e: Env) -> AuctionConfig;
+
+    /// (Admin only) Set the close factor bounds and pricing mode used when creating and filling
+    /// user liquidations
+    ///
+    /// ### Arguments
+    /// * `config` - The minimum percent of a user's position that can be liquidated, the max
+    ///              health factor a partial liquidation can leave the user at, the number of
+    ///              oracle records used to value the user with a TWAP (0 to use spot prices), and
+    ///              the health factor fills to target restore the user to
+    ///
+    /// ### Panics
+    /// If the caller is not the admin or the config is invalid
+    #[cfg(feature = "auctions")]
+    fn set_liquidation_config(e: Env, config: LiquidationConfig);
+
+    /// Fetch the close factor bounds and pricing mode used when creating user liquidations
+    #[cfg(feature = "auctions")]
+    fn get_liquidation_config(e: Env) -> LiquidationConfig;
+}
+
+#[contractimpl]
+impl PoolContract {
+    /// Initialize the pool
+    ///
+    /// ### Arguments
+    /// Creator supplied:
+    /// * `admin` - The Address for the admin
+    /// * `name` - The name of the pool
+    /// * `oracle` - The contract address of the oracle
+    /// * `backstop_take_rate` - The take rate for the backstop (7 decimals)
+    /// * `max_positions` - The maximum number of positions a user is permitted to have
+    /// * `hf_strategy` - The strategy used to calculate user health factors (0 = standard,
+    ///                   1 = e-mode, 2 = portfolio margin)
+    ///
+    /// Pool Factory supplied:
+    /// * `backstop_id` - The contract address of the pool's backstop module
+    /// * `blnd_id` - The contract ID of the BLND token
+    pub fn __constructor(
+        e: Env,
         admin: Address,
         name: String,
         oracle: Address,
         bstop_rate: u32,
         max_positions: u32,
+        hf_strategy: u32,
         backstop_id: Address,
         blnd_id: Address,
     ) {
         admin.require_auth();
 
-        pool::execute_initialize(
-            &e,
-            &admin,
-            &name,
-            &oracle,
-            &bstop_rate,
-            &max_positions,
-            &backstop_id,
-            &blnd_id,
-        );
+        pool::execute_initialize(
+            &e,
+            &admin,
+            &name,
+            &oracle,
+            &bstop_rate,
+            &max_positions,
+            &hf_strategy,
+            &backstop_id,
+            &blnd_id,
+        );
+        pool::record_pool_config(&e, &admin);
+    }
+}
+
+#[contractimpl]
+impl Pool for PoolContract {
+    fn propose_admin(e: Env, new_admin: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let pending_admin = pool::execute_propose_admin(&e, &new_admin);
+
+        PoolEvents::propose_admin(&e, admin, new_admin, pending_admin.unlock_time);
+    }
+
+    fn cancel_admin_proposal(e: Env) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let new_admin = pool::execute_cancel_admin_proposal(&e);
+
+        PoolEvents::cancel_admin_proposal(&e, admin, new_admin);
+    }
+
+    fn accept_admin(e: Env) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        if let Some(pending_admin) = storage::get_pending_admin(&e) {
+            pending_admin.new_admin.require_auth();
+        }
+
+        let new_admin = pool::execute_accept_admin(&e);
+
+        PoolEvents::set_admin(&e, admin, new_admin);
+    }
+
+    fn set_admin_delay(e: Env, delay: u64) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let pending_delay = pool::execute_set_admin_delay(&e, delay);
+
+        PoolEvents::set_admin_delay(&e, admin, delay, pending_delay.unlock_time);
+    }
+
+    fn get_pending_admin(e: Env) -> Option<PendingAdmin> {
+        storage::get_pending_admin(&e)
+    }
+
+    fn get_pending_admin_delay(e: Env) -> Option<PendingAdminDelay> {
+        storage::get_pending_admin_delay(&e)
+    }
+
+    fn get_admin_delay(e: Env) -> u64 {
+        pool::load_admin_delay(&e)
+    }
+
+    fn update_pool(e: Env, backstop_take_rate: u32, max_positions: u32) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(
+            &e,
+            &admin,
+            AdminOp::UpdatePool(backstop_take_rate, max_positions),
+        );
+    }
+
+    fn admin_batch(e: Env, ops: Vec<AdminOp>) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_batch(&e, &admin, ops);
+    }
+
+    fn deploy_backstop_share_token(e: Env, name: String, symbol: String) -> Address {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let share_token = pool::execute_deploy_backstop_share_token(&e, &name, &symbol);
+
+        PoolEvents::deploy_backstop_share_token(&e, admin, share_token.clone());
+        share_token
+    }
+
+    fn fund_backstop_reward(e: Env, token: Address, amount: i128, duration: u64) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_fund_backstop_reward(&e, &admin, &token, amount, duration);
+
+        PoolEvents::fund_backstop_reward(&e, admin, token, amount, duration);
+    }
+
+    fn remove_backstop_reward(e: Env, token: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_remove_backstop_reward(&e, &token);
+
+        PoolEvents::remove_backstop_reward(&e, admin, token);
+    }
+
+    fn set_fallback_oracle(e: Env, fallback_oracle: Option<Address>) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(&e, &admin, AdminOp::SetFallbackOracle(fallback_oracle));
+    }
+
+    fn set_rate_bounds(e: Env, asset: Address, rate_bounds: Option<RateBounds>) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_rate_bounds(&e, &asset, &rate_bounds);
+
+        PoolEvents::set_rate_bounds(&e, admin, asset, rate_bounds);
+    }
+
+    fn set_ir_mod_half_life(e: Env, asset: Address, half_life: Option<u64>) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_ir_mod_half_life(&e, &asset, &half_life);
+
+        PoolEvents::set_ir_mod_half_life(&e, admin, asset, half_life);
+    }
+
+    fn set_min_health_factor(e: Env, min_health_factor: u32) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(&e, &admin, AdminOp::SetMinHealthFactor(min_health_factor));
+    }
+
+    fn set_max_account_leverage(e: Env, max_account_leverage: u32) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(
+            &e,
+            &admin,
+            AdminOp::SetMaxAccountLeverage(max_account_leverage),
+        );
+    }
+
+    fn set_health_warning_threshold(e: Env, threshold: u32) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_health_warning_threshold(&e, threshold);
+
+        PoolEvents::set_health_warning_threshold(&e, admin, threshold);
+    }
+
+    fn get_health_warning_threshold(e: Env) -> u32 {
+        storage::get_health_warning_threshold(&e)
+    }
+
+    fn set_min_position_value(e: Env, value: i128) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_min_position_value(&e, value);
+
+        PoolEvents::set_min_position_value(&e, admin, value);
+    }
+
+    fn get_min_position_value(e: Env) -> i128 {
+        storage::get_min_position_value(&e)
+    }
+
+    fn declare_snapshot(e: Env, ledger: u32) -> u32 {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let id = pool::execute_declare_snapshot(&e, ledger);
+
+        PoolEvents::declare_snapshot(&e, admin, id, ledger);
+        id
+    }
+
+    fn get_snapshot(e: Env) -> Option<PositionSnapshot> {
+        storage::get_position_snapshot(&e)
+    }
+
+    fn get_snapshot_positions(
+        e: Env,
+        id: u32,
+        user: Address,
+        account_id: u32,
+    ) -> Option<Positions> {
+        pool::load_snapshot_positions(&e, id, &user, account_id)
+    }
+
+    fn set_oracle_config(e: Env, config: OracleConfig) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(&e, &admin, AdminOp::SetOracleConfig(config));
+    }
+
+    fn get_oracle_config(e: Env) -> OracleConfig {
+        storage::get_oracle_config(&e)
+    }
+
+    #[cfg(feature = "flash-loans")]
+    fn set_flash_loan_fee(e: Env, flash_loan_fee: u32) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_flash_loan_fee(&e, flash_loan_fee);
+        pool::record_pool_config(&e, &admin);
+
+        PoolEvents::set_flash_loan_fee(&e, admin, flash_loan_fee);
+    }
+
+    #[cfg(feature = "flash-loans")]
+    fn set_fee_tiers(e: Env, fee_tiers: Vec<FeeTier>) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_fee_tiers(&e, &fee_tiers);
+
+        PoolEvents::set_fee_tiers(&e, admin, fee_tiers);
+    }
+
+    fn queue_set_reserve(
+        e: Env,
+        asset: Address,
+        metadata: ReserveConfig,
+        ir_config: Option<InterestRateConfig>,
+    ) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(
+            &e,
+            &admin,
+            AdminOp::QueueSetReserve(asset, metadata, ir_config),
+        );
+    }
+
+    fn cancel_set_reserve(e: Env, asset: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(&e, &admin, AdminOp::CancelSetReserve(asset));
+    }
+
+    fn set_reserve(e: Env, asset: Address) -> u32 {
+        let index = pool::execute_set_reserve(&e, &asset);
+        pool::record_reserve_config(&e, &storage::get_admin(&e), &asset);
+
+        PoolEvents::set_reserve(&e, asset, index);
+        index
+    }
+
+    fn get_queued_reserve(e: Env, asset: Address) -> Option<QueuedReserveInit> {
+        if storage::has_queued_reserve_set(&e, &asset) {
+            Some(storage::get_queued_reserve_set(&e, &asset))
+        } else {
+            None
+        }
+    }
+
+    fn remove_reserve(e: Env, asset: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_remove_reserve(&e, &asset);
+
+        PoolEvents::remove_reserve(&e, admin, asset);
+    }
+
+    fn clear_reserve_dust(e: Env, asset: Address, user: Address, account_id: u32) -> i128 {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let amount = pool::execute_clear_reserve_dust(&e, &asset, &user, account_id);
+
+        PoolEvents::clear_reserve_dust(&e, admin, asset, user, account_id, amount);
+        amount
+    }
+
+    fn queue_debt_rebate(
+        e: Env,
+        asset: Address,
+        accounts: Vec<Address>,
+        rebate: i128,
+        funder: Option<Address>,
+    ) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_queue_debt_rebate(&e, &asset, &accounts, rebate, &funder);
+
+        PoolEvents::queue_debt_rebate(&e, admin, asset, accounts, rebate, funder);
+    }
+
+    fn cancel_debt_rebate(e: Env, asset: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_cancel_queued_debt_rebate(&e, &asset);
+
+        PoolEvents::cancel_debt_rebate(&e, admin, asset);
+    }
+
+    fn debt_rebate(e: Env, asset: Address) -> i128 {
+        storage::extend_instance(&e);
+        pool::execute_debt_rebate(&e, &asset)
+    }
+
+    fn set_anomaly_thresholds(
+        e: Env,
+        asset: Address,
+        max_b_supply_delta: i128,
+        max_d_supply_delta: i128,
+    ) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(
+            &e,
+            &admin,
+            AdminOp::SetAnomalyThresholds(asset, max_b_supply_delta, max_d_supply_delta),
+        );
+    }
+
+    fn lift_restriction(e: Env, asset: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(&e, &admin, AdminOp::LiftRestriction(asset));
+    }
+
+    fn set_pause_guardian(e: Env, guardian: Option<Address>) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(&e, &admin, AdminOp::SetPauseGuardian(guardian));
+    }
+
+    fn set_util_hook(e: Env, hook: Option<Address>) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        storage::set_util_hook(&e, &hook);
+
+        PoolEvents::set_util_hook(&e, admin, hook);
+    }
+
+    fn set_referral_rate(e: Env, rate: u32) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_referral_rate(&e, rate);
+
+        PoolEvents::set_referral_rate(&e, admin, rate);
+    }
+
+    fn get_referral_rate(e: Env) -> u32 {
+        storage::get_referral_rate(&e)
+    }
+
+    fn set_borrow_allowlist(e: Env, enabled: bool) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        storage::set_borrow_allowlist(&e, enabled);
+
+        PoolEvents::set_borrow_allowlist(&e, admin, enabled);
+    }
+
+    fn get_borrow_allowlist(e: Env) -> bool {
+        storage::get_borrow_allowlist(&e)
+    }
+
+    fn set_allowed_borrower(e: Env, user: Address, allowed: bool) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        storage::set_allowed_borrower(&e, &user, allowed);
+
+        PoolEvents::set_allowed_borrower(&e, admin, user, allowed);
+    }
+
+    fn get_allowed_borrower(e: Env, user: Address) -> bool {
+        storage::get_allowed_borrower(&e, &user)
+    }
+
+    fn set_repay_grace(e: Env, asset: Address, window: u64, rebate: u32) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_repay_grace(&e, &asset, window, rebate);
+
+        PoolEvents::set_repay_grace(&e, admin, asset, window, rebate);
+    }
+
+    fn get_repay_grace(e: Env, asset: Address) -> Option<RepayGraceConfig> {
+        storage::get_repay_grace_config(&e, &asset)
+    }
+
+    fn set_fixed_rate_config(e: Env, asset: Address, spread: u32, max_term: u64) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_fixed_rate_config(&e, &asset, spread, max_term);
+
+        PoolEvents::set_fixed_rate_config(&e, admin, asset, spread, max_term);
+    }
+
+    fn get_fixed_rate_config(e: Env, asset: Address) -> Option<FixedRateConfig> {
+        storage::get_fixed_rate_config(&e, &asset)
+    }
+
+    fn pause_actions(e: Env, caller: Address, actions: u32) -> u32 {
+        storage::extend_instance(&e);
+        caller.require_auth();
+
+        let paused_actions = pool::execute_pause_actions(&e, &caller, actions);
+
+        PoolEvents::pause_actions(&e, caller, actions, paused_actions);
+        paused_actions
+    }
+
+    fn unpause_actions(e: Env, actions: u32) -> u32 {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(&e, &admin, AdminOp::UnpauseActions(actions));
+        storage::get_paused_actions(&e)
+    }
+
+    fn get_pause_guardian(e: Env) -> Option<Address> {
+        storage::get_pause_guardian(&e)
+    }
+
+    fn get_util_hook(e: Env) -> Option<Address> {
+        storage::get_util_hook(&e)
+    }
+
+    fn get_paused_actions(e: Env) -> u32 {
+        storage::get_paused_actions(&e)
+    }
+
+    fn set_price_signers(e: Env, signers: Vec<BytesN<32>>, max_age: u64) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_set_price_signers(&e, &signers, max_age);
+
+        PoolEvents::set_price_signers(&e, admin, signers, max_age);
+    }
+
+    fn get_config(e: Env) -> PoolConfig {
+        storage::get_pool_config(&e)
+    }
+
+    fn get_admin(e: Env) -> Address {
+        storage::get_admin(&e)
+    }
+
+    fn get_config_history(e: Env, start: u32, limit: u32) -> Vec<ConfigHistoryEntry> {
+        pool::load_config_history(&e, start, limit)
+    }
+
+    fn get_config_version(e: Env) -> u32 {
+        storage::get_config_version(&e)
+    }
+
+    fn get_reserve(e: Env, asset: Address) -> Reserve {
+        let pool_config = storage::get_pool_config(&e);
+        Reserve::load(&e, &pool_config, &asset)
+    }
+
+    fn get_reserve_list(e: Env) -> Vec<Address> {
+        storage::get_res_list(&e)
+    }
+
+    fn get_removed_reserves(e: Env) -> Vec<Address> {
+        storage::get_removed_res(&e)
+    }
+
+    fn get_accrual_history(e: Env, asset: Address) -> Vec<AccrualSnapshot> {
+        storage::get_accrual_history(&e, &asset)
+    }
+
+    fn get_reserve_preview(e: Env, asset: Address) -> ReservePreview {
+        pool::load_reserve_preview(&e, &asset)
+    }
+
+    fn get_reserve_rates(e: Env, asset: Address) -> ReserveRates {
+        pool::load_reserve_rates(&e, &asset)
+    }
+
+    fn get_accrued_fees(e: Env) -> Map<Address, i128> {
+        pool::load_accrued_fees(&e)
+    }
+
+    fn get_remaining_collateral_capacity(e: Env, asset: Address) -> i128 {
+        let pool_config = storage::get_pool_config(&e);
+        Reserve::load(&e, &pool_config, &asset).remaining_collateral_capacity()
+    }
+
+    fn get_rate_bounds(e: Env, asset: Address) -> Option<RateBounds> {
+        storage::get_res_rate_bounds(&e, &asset)
+    }
+
+    fn get_ir_mod_half_life(e: Env, asset: Address) -> Option<u64> {
+        storage::get_res_ir_mod_half_life(&e, &asset)
+    }
+
+    fn get_reserve_ir_config(e: Env, asset: Address) -> Option<InterestRateConfig> {
+        storage::get_res_ir_config(&e, &asset)
+    }
+
+    fn get_positions(e: Env, address: Address) -> Positions {
+        storage::get_user_positions(&e, &address)
+    }
+
+    fn get_account_positions(e: Env, address: Address, account_id: u32) -> Positions {
+        storage::get_account_positions(&e, &address, account_id)
+    }
+
+    fn get_user_health_factor(e: Env, address: Address) -> HealthFactorData {
+        pool::load_user_health_factor(&e, &address)
+    }
+
+    fn get_positions_value(e: Env, address: Address) -> Map<u32, PositionValue> {
+        pool::load_user_positions_value(&e, &address)
+    }
+
+    fn get_position_uri(e: Env, address: Address) -> String {
+        pool::build_position_uri(&e, &address)
+    }
+
+    #[cfg(feature = "flash-loans")]
+    fn get_fee_tiers(e: Env) -> Vec<FeeTier> {
+        storage::get_fee_tiers(&e)
+    }
+
+    #[cfg(feature = "flash-loans")]
+    fn get_user_fee_volume(e: Env, address: Address) -> UserFeeVolume {
+        pool::load_user_fee_volume(&e, &address)
+    }
+
+    fn submit(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        if from != spender {
+            from.require_auth();
+        }
+
+        pool::execute_submit(&e, &from, &spender, &to, requests, false, None)
+            .positions
+    }
+
+    fn submit_with_result(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> SubmitResult {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        if from != spender {
+            from.require_auth();
+        }
+
+        pool::execute_submit(&e, &from, &spender, &to, requests, false, None)
+    }
+
+    fn submit_with_options(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+        options: Vec<RequestOptions>,
+    ) -> SubmitResult {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        if from != spender {
+            from.require_auth();
+        }
+
+        pool::execute_submit_with_options(&e, &from, &spender, &to, requests, options)
+    }
+
+    fn simulate_submit(e: Env, from: Address, requests: Vec<Request>) -> SimulateResult {
+        pool::execute_simulate_submit(&e, &from, requests)
+    }
+
+    fn supply_native(e: Env, from: Address, amount: i128, as_collateral: bool) -> Positions {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_supply_native(&e, &from, amount, as_collateral)
+    }
+
+    fn repay_native(e: Env, from: Address, amount: i128) -> Positions {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_repay_native(&e, &from, amount)
+    }
+
+    fn borrow_fixed(
+        e: Env,
+        from: Address,
+        asset: Address,
+        amount: i128,
+        maturity: u64,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_borrow_fixed(&e, &from, &asset, amount, maturity)
+    }
+
+    fn settle_fixed_loan(e: Env, caller: Address, user: Address, asset: Address) -> i128 {
+        storage::extend_instance(&e);
+        caller.require_auth();
+
+        pool::execute_settle_fixed_loan(&e, &caller, &user, &asset)
+    }
+
+    fn get_fixed_loan(e: Env, user: Address, asset: Address) -> Option<FixedLoan> {
+        let reserve_index = storage::get_res_config(&e, &asset).index;
+        storage::get_user_fixed_loan(&e, &user, reserve_index)
+    }
+
+    fn get_fixed_tranche(e: Env, asset: Address) -> FixedTranche {
+        storage::get_fixed_tranche(&e, &asset)
+    }
+
+    #[cfg(feature = "flash-loans")]
+    fn flash_loan(
+        e: Env,
+        from: Address,
+        flash_loan: FlashLoan,
+        requests: Vec<Request>,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_submit_with_flash_loan(&e, &from, flash_loan, requests)
+    }
+
+    #[cfg(feature = "flash-loans")]
+    fn flash_loans(
+        e: Env,
+        from: Address,
+        flash_loans: Vec<FlashLoan>,
+        requests: Vec<Request>,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_submit_with_flash_loans(&e, &from, flash_loans, requests)
+    }
+
+    #[cfg(feature = "flash-loans")]
+    fn swap_collateral(
+        e: Env,
+        from: Address,
+        swap: CollateralSwap,
+        requests: Vec<Request>,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_submit_with_collateral_swap(&e, &from, swap, requests)
+    }
+
+    #[cfg(feature = "flash-loans")]
+    fn deleverage(e: Env, from: Address, swap: CollateralSwap) -> Positions {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_deleverage(&e, &from, swap)
+    }
+
+    fn migrate_position(
+        e: Env,
+        from: Address,
+        source_pool: Address,
+        assets: Vec<Address>,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_migrate_position(&e, &from, &source_pool, assets)
+    }
+
+    fn submit_batch(
+        e: Env,
+        batch: Vec<(Address, Address, Address, Vec<Request>)>,
+    ) -> Vec<Positions> {
+        storage::extend_instance(&e);
+        // a spender can send tokens for multiple users, so each address only authorizes once
+        let mut authorized: Vec<Address> = Vec::new(&e);
+        for (from, spender, _, _) in batch.iter() {
+            for address in [from, spender] {
+                if !authorized.contains(&address) {
+                    address.require_auth();
+                    authorized.push_back(address);
+                }
+            }
+        }
+
+        pool::execute_submit_batch(&e, batch)
+    }
+
+    fn submit_with_prices(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+        prices: Vec<PriceAttestation>,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        if from != spender {
+            from.require_auth();
+        }
+
+        pool::execute_submit_with_prices(&e, &from, &spender, &to, requests, false, &prices)
+    }
+
+    fn submit_with_signature(
+        e: Env,
+        requests: Vec<Request>,
+        signature: SubmitSignature,
+    ) -> Positions {
+        storage::extend_instance(&e);
+
+        pool::execute_submit_with_signature(&e, requests, &signature)
+    }
+
+    fn get_nonce(e: Env, address: Address) -> u64 {
+        storage::get_user_nonce(&e, &address)
+    }
+
+    fn delegate_credit(e: Env, from: Address, delegatee: Address, asset: Address, amount: i128) {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        pool::execute_delegate_credit(&e, &from, &delegatee, &asset, amount);
+
+        PoolEvents::delegate_credit(&e, asset, from, delegatee, amount);
+    }
+
+    fn get_credit_delegation(e: Env, from: Address, delegatee: Address, asset: Address) -> i128 {
+        storage::get_credit_delegation(&e, &from, &delegatee, &asset)
+    }
+
+    fn submit_with_delegation(
+        e: Env,
+        from: Address,
+        delegatee: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        delegatee.require_auth();
+
+        pool::execute_submit_with_delegation(&e, &from, &delegatee, &to, requests)
+    }
+
+    fn submit_with_allowance(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        if from != spender {
+            from.require_auth();
+        }
+
+        pool::execute_submit(&e, &from, &spender, &to, requests, true, None)
+            .positions
+    }
+
+    fn submit_with_referral(
+        e: Env,
+        from: Address,
+        spender: Address,
+        to: Address,
+        requests: Vec<Request>,
+        referrer: Address,
+    ) -> Positions {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        if from != spender {
+            from.require_auth();
+        }
+
+        pool::execute_submit(&e, &from, &spender, &to, requests, false, Some(&referrer))
+            .positions
+    }
+
+    fn claim_referral_fees(
+        e: Env,
+        referrer: Address,
+        assets: Vec<Address>,
+        to: Address,
+    ) -> Vec<i128> {
+        storage::extend_instance(&e);
+        referrer.require_auth();
+
+        pool::execute_claim_referral_fees(&e, &referrer, assets, &to)
     }
-}
 
-#[contractimpl]
-impl Pool for PoolContract {
-    fn set_admin(e: Env, new_admin: Address) {
+    fn get_referrer(e: Env, user: Address) -> Option<Address> {
+        storage::get_referrer(&e, &user)
+    }
+
+    fn get_referral_fees(e: Env, referrer: Address, asset: Address) -> i128 {
+        storage::get_referral_fees(&e, &referrer, &asset)
+    }
+
+    fn bad_debt(e: Env, user: Address) {
+        pool::transfer_bad_debt_to_backstop(&e, &user, 0);
+    }
+
+    fn bad_debt_account(e: Env, user: Address, account_id: u32) {
+        pool::transfer_bad_debt_to_backstop(&e, &user, account_id);
+    }
+
+    fn recapitalize(e: Env, max_tokens_in: i128) -> i128 {
         storage::extend_instance(&e);
         let admin = storage::get_admin(&e);
         admin.require_auth();
-        new_admin.require_auth();
 
-        storage::set_admin(&e, &new_admin);
-
-        PoolEvents::set_admin(&e, admin, new_admin);
+        pool::execute_recapitalize_bad_debt(&e, max_tokens_in)
     }
 
-    fn update_pool(e: Env, backstop_take_rate: u32, max_positions: u32) {
+    fn set_credit_settlement(e: Env, interval: u64, threshold: i128) {
         storage::extend_instance(&e);
         let admin = storage::get_admin(&e);
         admin.require_auth();
 
-        pool::execute_update_pool(&e, backstop_take_rate, max_positions);
-
-        PoolEvents::update_pool(&e, admin, backstop_take_rate, max_positions);
+        let config = CreditSettlementConfig {
+            interval,
+            threshold,
+        };
+        pool::execute_admin_op(&e, &admin, AdminOp::SetCreditSettlement(config));
     }
 
-    fn queue_set_reserve(e: Env, asset: Address, metadata: ReserveConfig) {
+    fn settle_backstop_credit(e: Env, asset: Address, min_tokens_out: i128) -> i128 {
         storage::extend_instance(&e);
         let admin = storage::get_admin(&e);
         admin.require_auth();
 
-        pool::execute_queue_set_reserve(&e, &asset, &metadata);
+        pool::execute_settle_backstop_credit(&e, &asset, min_tokens_out)
+    }
+
+    fn queue_set_emode_category(e: Env, category: u32, config: EModeCategory) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
 
-        PoolEvents::queue_set_reserve(&e, admin, asset, metadata);
+        pool::execute_admin_op(&e, &admin, AdminOp::QueueSetEModeCategory(category, config));
     }
 
-    fn cancel_set_reserve(e: Env, asset: Address) {
+    fn cancel_set_emode_category(e: Env, category: u32) {
         storage::extend_instance(&e);
         let admin = storage::get_admin(&e);
         admin.require_auth();
 
-        pool::execute_cancel_queued_set_reserve(&e, &asset);
+        pool::execute_admin_op(&e, &admin, AdminOp::CancelSetEModeCategory(category));
+    }
+
+    fn set_emode_category(e: Env, category: u32) -> EModeCategory {
+        storage::extend_instance(&e);
+
+        let config = pool::execute_set_emode_category(&e, category);
 
-        PoolEvents::cancel_set_reserve(&e, admin, asset);
+        PoolEvents::set_emode_category(&e, category, config.clone());
+        config
     }
 
-    fn set_reserve(e: Env, asset: Address) -> u32 {
-        let index = pool::execute_set_reserve(&e, &asset);
+    fn get_queued_emode_category(e: Env, category: u32) -> Option<QueuedEModeCategory> {
+        storage::get_queued_emode_category(&e, category)
+    }
 
-        PoolEvents::set_reserve(&e, asset, index);
-        index
+    fn get_emode_category(e: Env, category: u32) -> Option<EModeCategory> {
+        storage::get_emode_category(&e, category)
     }
 
-    fn get_config(e: Env) -> PoolConfig {
-        storage::get_pool_config(&e)
+    fn set_user_emode(e: Env, user: Address, category: u32) {
+        storage::extend_instance(&e);
+        user.require_auth();
+
+        pool::execute_set_user_emode(&e, &user, category);
+
+        PoolEvents::set_user_emode(&e, user, category);
     }
 
-    fn get_admin(e: Env) -> Address {
-        storage::get_admin(&e)
+    fn get_user_emode(e: Env, user: Address) -> u32 {
+        storage::get_user_emode(&e, &user)
     }
 
-    fn get_reserve(e: Env, asset: Address) -> Reserve {
-        let pool_config = storage::get_pool_config(&e);
-        Reserve::load(&e, &pool_config, &asset)
+    fn set_siloed_deposits(e: Env, user: Address, siloed: bool) {
+        storage::extend_instance(&e);
+        user.require_auth();
+
+        storage::set_user_siloed(&e, &user, siloed);
+
+        PoolEvents::set_siloed_deposits(&e, user, siloed);
     }
 
-    fn get_positions(e: Env, address: Address) -> Positions {
-        storage::get_user_positions(&e, &address)
+    fn get_siloed_deposits(e: Env, user: Address) -> bool {
+        storage::get_user_siloed(&e, &user)
     }
 
-    fn submit(
-        e: Env,
-        from: Address,
-        spender: Address,
-        to: Address,
-        requests: Vec<Request>,
-    ) -> Positions {
+    fn allow_deposits_from(e: Env, user: Address, depositor: Address, allowed: bool) {
         storage::extend_instance(&e);
-        spender.require_auth();
-        if from != spender {
-            from.require_auth();
-        }
+        user.require_auth();
+
+        storage::set_deposits_allowed(&e, &user, &depositor, allowed);
 
-        pool::execute_submit(&e, &from, &spender, &to, requests, false)
+        PoolEvents::allow_deposits_from(&e, user, depositor, allowed);
     }
 
-    fn flash_loan(
-        e: Env,
-        from: Address,
-        flash_loan: FlashLoan,
-        requests: Vec<Request>,
-    ) -> Positions {
+    fn get_deposits_allowed(e: Env, user: Address, depositor: Address) -> bool {
+        storage::get_deposits_allowed(&e, &user, &depositor)
+    }
+
+    fn set_self_repay(e: Env, user: Address, asset: Option<Address>) {
         storage::extend_instance(&e);
-        from.require_auth();
+        user.require_auth();
 
-        pool::execute_submit_with_flash_loan(&e, &from, flash_loan, requests)
+        pool::execute_set_self_repay(&e, &user, &asset);
+
+        PoolEvents::set_self_repay(&e, user, asset);
     }
 
-    fn submit_with_allowance(
-        e: Env,
-        from: Address,
-        spender: Address,
-        to: Address,
-        requests: Vec<Request>,
-    ) -> Positions {
+    fn self_repay(e: Env, user: Address) -> i128 {
         storage::extend_instance(&e);
-        spender.require_auth();
-        if from != spender {
-            from.require_auth();
-        }
+        pool::execute_self_repay(&e, &user)
+    }
 
-        pool::execute_submit(&e, &from, &spender, &to, requests, true)
+    fn get_self_repay(e: Env, user: Address) -> Option<SelfRepayConfig> {
+        storage::get_self_repay(&e, &user)
     }
 
-    fn bad_debt(e: Env, user: Address) {
-        pool::transfer_bad_debt_to_backstop(&e, &user);
+    fn deploy_b_token(e: Env, asset: Address, name: String, symbol: String) -> Address {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let b_token = pool::execute_deploy_b_token(&e, &asset, &name, &symbol);
+
+        PoolEvents::deploy_b_token(&e, admin, asset, b_token.clone());
+        b_token
+    }
+
+    fn get_b_token(e: Env, asset: Address) -> Option<Address> {
+        storage::get_res_b_token(&e, &asset)
+    }
+
+    fn b_token_balance(e: Env, asset: Address, id: Address) -> i128 {
+        pool::load_b_token_balance(&e, &asset, &id)
+    }
+
+    fn b_token_supply(e: Env, asset: Address) -> i128 {
+        storage::get_res_data(&e, &asset).b_supply
+    }
+
+    fn b_token_transfer(e: Env, asset: Address, from: Address, to: Address, amount: i128) {
+        storage::extend_instance(&e);
+
+        pool::execute_b_token_transfer(&e, &asset, &from, &to, amount);
     }
 
     fn update_status(e: Env) -> u32 {
         storage::extend_instance(&e);
+        let prev_status = storage::get_pool_config(&e).status;
         let new_status = pool::execute_update_pool_status(&e);
+        if new_status != prev_status {
+            pool::record_pool_config(&e, &e.current_contract_address());
+        }
 
         PoolEvents::set_status(&e, new_status);
         new_status
@@ -443,9 +2944,8 @@ impl Pool for PoolContract {
         storage::extend_instance(&e);
         let admin = storage::get_admin(&e);
         admin.require_auth();
-        pool::execute_set_pool_status(&e, pool_status);
 
-        PoolEvents::set_status_admin(&e, admin, pool_status);
+        pool::execute_admin_op(&e, &admin, AdminOp::SetStatus(pool_status));
     }
 
     fn gulp(e: Env, asset: Address) -> i128 {
@@ -456,6 +2956,11 @@ impl Pool for PoolContract {
         token_delta
     }
 
+    fn accrue(e: Env, asset: Address) -> Reserve {
+        storage::extend_instance(&e);
+        pool::execute_accrue(&e, &asset)
+    }
+
     /********* Emission Functions **********/
 
     fn gulp_emissions(e: Env) -> i128 {
@@ -470,7 +2975,23 @@ impl Pool for PoolContract {
         let admin = storage::get_admin(&e);
         admin.require_auth();
 
-        emissions::set_pool_emissions(&e, res_emission_metadata);
+        pool::execute_admin_op(
+            &e,
+            &admin,
+            AdminOp::SetEmissionsConfig(res_emission_metadata),
+        );
+    }
+
+    fn set_util_weighted_emissions(e: Env, enabled: bool) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        pool::execute_admin_op(&e, &admin, AdminOp::SetUtilWeightedEmissions(enabled));
+    }
+
+    fn get_util_weighted_emissions(e: Env) -> bool {
+        storage::get_util_weighted_emissions(&e)
     }
 
     fn claim(e: Env, from: Address, reserve_token_ids: Vec<u32>, to: Address) -> i128 {
@@ -500,8 +3021,17 @@ impl Pool for PoolContract {
         })
     }
 
+    fn get_emission_epoch(e: Env, epoch: u32) -> Option<EmissionEpoch> {
+        storage::get_emission_epoch(&e, epoch)
+    }
+
+    fn get_emission_epoch_count(e: Env) -> u32 {
+        storage::get_emission_epoch_count(&e)
+    }
+
     /***** Auction / Liquidation Functions *****/
 
+    #[cfg(feature = "auctions")]
     fn new_auction(
         e: Env,
         auction_type: u32,
@@ -511,14 +3041,278 @@ impl Pool for PoolContract {
         percent: u32,
     ) -> AuctionData {
         storage::extend_instance(&e);
+        if auction_type == auctions::AuctionType::UserLiquidation as u32 {
+            auctions::require_allowed_liquidator(&e, None);
+        }
+
+        let auction_data = auctions::create_auction(&e, auction_type, &user, &bid, &lot, percent);
+
+        if !auction_data.bid.is_empty() {
+            PoolEvents::new_auction(&e, auction_type, user, percent, auction_data.clone());
+        }
+        auction_data
+    }
+
+    #[cfg(feature = "auctions")]
+    fn new_auction_with_keeper(
+        e: Env,
+        keeper: Address,
+        auction_type: u32,
+        user: Address,
+        bid: Vec<Address>,
+        lot: Vec<Address>,
+        percent: u32,
+    ) -> AuctionData {
+        storage::extend_instance(&e);
+        keeper.require_auth();
+        if auction_type == auctions::AuctionType::UserLiquidation as u32 {
+            auctions::require_allowed_liquidator(&e, Some(&keeper));
+        }
 
         let auction_data = auctions::create_auction(&e, auction_type, &user, &bid, &lot, percent);
+        if !auction_data.bid.is_empty() {
+            storage::set_auction_keeper(&e, &auction_type, &user, &Some(keeper));
+            PoolEvents::new_auction(&e, auction_type, user, percent, auction_data.clone());
+        }
+        auction_data
+    }
+
+    #[cfg(feature = "auctions")]
+    fn set_keeper_bounty(e: Env, bounty: u32) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        auctions::execute_set_keeper_bounty(&e, bounty);
+
+        PoolEvents::set_keeper_bounty(&e, admin, bounty);
+    }
+
+    #[cfg(feature = "auctions")]
+    fn set_liquidation_protection(e: Env, user: Address, protection: Option<Address>) {
+        storage::extend_instance(&e);
+        user.require_auth();
+
+        storage::set_liquidation_protection(&e, &user, &protection);
+
+        PoolEvents::set_liquidation_protection(&e, user, protection);
+    }
+
+    #[cfg(feature = "auctions")]
+    fn get_liquidation_protection(e: Env, user: Address) -> Option<Address> {
+        storage::get_liquidation_protection(&e, &user)
+    }
+
+    #[cfg(feature = "auctions")]
+    fn get_keeper_bounty(e: Env) -> u32 {
+        storage::get_keeper_bounty(&e)
+    }
+
+    #[cfg(feature = "auctions")]
+    fn set_bad_debt_threshold(e: Env, threshold: i128) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        auctions::execute_set_bad_debt_threshold(&e, threshold);
+
+        PoolEvents::set_bad_debt_threshold(&e, admin, threshold);
+    }
+
+    #[cfg(feature = "auctions")]
+    fn get_bad_debt_threshold(e: Env) -> i128 {
+        storage::get_bad_debt_threshold(&e)
+    }
+
+    #[cfg(feature = "auctions")]
+    fn set_liquidator_allowlist(e: Env, enabled: bool) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        storage::set_liquidator_allowlist(&e, enabled);
+
+        PoolEvents::set_liquidator_allowlist(&e, admin, enabled);
+    }
+
+    #[cfg(feature = "auctions")]
+    fn get_liquidator_allowlist(e: Env) -> bool {
+        storage::get_liquidator_allowlist(&e)
+    }
+
+    #[cfg(feature = "auctions")]
+    fn set_allowed_liquidator(e: Env, liquidator: Address, allowed: bool) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        storage::set_allowed_liquidator(&e, &liquidator, allowed);
+
+        PoolEvents::set_allowed_liquidator(&e, admin, liquidator, allowed);
+    }
+
+    #[cfg(feature = "auctions")]
+    fn get_allowed_liquidator(e: Env, liquidator: Address) -> bool {
+        storage::get_allowed_liquidator(&e, &liquidator)
+    }
+
+    #[cfg(feature = "auctions")]
+    fn draw_on_bad_debt(e: Env) -> Option<AuctionData> {
+        storage::extend_instance(&e);
+
+        // the backstop data is loaded fail soft, like the rest of the bad debt check
+        let backstop_client = BackstopClient::new(&e, &storage::get_backstop(&e));
+        let pool_address = e.current_contract_address();
+        let backstop_token = backstop_client
+            .try_pool_backstop_token(&pool_address)
+            .ok()?
+            .ok()?;
+        let pool_backstop_data = backstop_client.try_pool_data(&pool_address).ok()?.ok()?;
+
+        let auction_data =
+            auctions::try_create_bad_debt_auction(&e, &backstop_token, &pool_backstop_data);
+        if let Some(auction_data) = auction_data.clone() {
+            let auction_type = auctions::AuctionType::BadDebtAuction as u32;
+            let backstop = storage::get_backstop(&e);
+            PoolEvents::new_auction(&e, auction_type, backstop, 100, auction_data);
+        }
+        auction_data
+    }
+
+    #[cfg(feature = "auctions")]
+    fn check_bad_debt(e: Env, backstop_token: Address, pool_backstop_data: PoolBackstopData) {
+        storage::extend_instance(&e);
+        let backstop = storage::get_backstop(&e);
+        backstop.require_auth();
+
+        if let Some(auction_data) =
+            auctions::try_create_bad_debt_auction(&e, &backstop_token, &pool_backstop_data)
+        {
+            let auction_type = auctions::AuctionType::BadDebtAuction as u32;
+            PoolEvents::new_auction(&e, auction_type, backstop, 100, auction_data);
+        }
+    }
+
+    #[cfg(feature = "auctions")]
+    fn new_account_liquidation(
+        e: Env,
+        liquidator: Address,
+        user: Address,
+        account_id: u32,
+        bid: Vec<Address>,
+        lot: Vec<Address>,
+        percent: u32,
+    ) -> AuctionData {
+        storage::extend_instance(&e);
+        if storage::get_liquidator_allowlist(&e) {
+            liquidator.require_auth();
+            auctions::require_allowed_liquidator(&e, Some(&liquidator));
+        }
 
-        PoolEvents::new_auction(&e, auction_type, user, percent, auction_data.clone());
+        let auction_data =
+            auctions::create_account_liquidation(&e, &user, account_id, &bid, &lot, percent);
+
+        if !auction_data.bid.is_empty() {
+            PoolEvents::new_auction(&e, 0, user, percent, auction_data.clone());
+        }
         auction_data
     }
 
+    #[cfg(feature = "auctions")]
+    fn stake_risk_bot(e: Env, bot: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        bot.require_auth();
+
+        let stake = auctions::execute_stake_risk_bot(&e, &bot, amount);
+
+        PoolEvents::stake_risk_bot(&e, bot, amount, stake);
+        stake
+    }
+
+    #[cfg(feature = "auctions")]
+    fn queue_unstake_risk_bot(e: Env, bot: Address, amount: i128) -> RiskBotUnstake {
+        storage::extend_instance(&e);
+        bot.require_auth();
+
+        let unstake = auctions::execute_queue_unstake_risk_bot(&e, &bot, amount);
+
+        PoolEvents::queue_unstake_risk_bot(&e, bot, amount, unstake.clone());
+        unstake
+    }
+
+    #[cfg(feature = "auctions")]
+    fn unstake_risk_bot(e: Env, bot: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        bot.require_auth();
+
+        let stake = auctions::execute_unstake_risk_bot(&e, &bot, amount);
+
+        PoolEvents::unstake_risk_bot(&e, bot, amount, stake);
+        stake
+    }
+
+    #[cfg(feature = "auctions")]
+    fn new_liquidation_auctions(
+        e: Env,
+        bot: Address,
+        requests: Vec<LiquidationRequest>,
+    ) -> Vec<Address> {
+        storage::extend_instance(&e);
+        bot.require_auth();
+
+        auctions::execute_batch_liquidations(&e, &bot, &requests)
+    }
+
+    #[cfg(feature = "auctions")]
     fn get_auction(e: Env, auction_type: u32, user: Address) -> AuctionData {
         storage::get_auction(&e, &auction_type, &user)
     }
+
+    #[cfg(feature = "auctions")]
+    fn get_auction_queue(e: Env, start: u32, limit: u32) -> Vec<QueuedAuction> {
+        auctions::load_auction_queue(&e, start, limit)
+    }
+
+    #[cfg(feature = "auctions")]
+    fn preview_auction_fill(
+        e: Env,
+        auction_type: u32,
+        user: Address,
+        percent_filled: u64,
+        at_block: u32,
+    ) -> AuctionData {
+        auctions::preview_fill(&e, auction_type, &user, percent_filled, at_block)
+    }
+
+    #[cfg(feature = "auctions")]
+    fn set_auction_config(e: Env, config: AuctionConfig) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        auctions::execute_set_auction_config(&e, &config);
+
+        PoolEvents::set_auction_config(&e, admin, config);
+    }
+
+    #[cfg(feature = "auctions")]
+    fn get_auction_config(e: Env) -> AuctionConfig {
+        storage::get_auction_config(&e)
+    }
+
+    #[cfg(feature = "auctions")]
+    fn set_liquidation_config(e: Env, config: LiquidationConfig) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        auctions::execute_set_liquidation_config(&e, &config);
+
+        PoolEvents::set_liquidation_config(&e, admin, config);
+    }
+
+    #[cfg(feature = "auctions")]
+    fn get_liquidation_config(e: Env) -> LiquidationConfig {
+        storage::get_liquidation_config(&e)
+    }
 }
@@ -1,2 +1,16 @@
 mod backstop;
 pub use backstop::{Client as BackstopClient, PoolBackstopData};
+
+mod pool_factory;
+pub use pool_factory::PoolFactoryClient;
+
+mod protection;
+pub use protection::{LiquidationProtection, LiquidationProtectionClient};
+
+mod util_hook;
+pub use util_hook::{UtilizationHook, UtilizationHookClient};
+
+#[cfg(feature = "flash-loans")]
+mod swap_adapter;
+#[cfg(feature = "flash-loans")]
+pub use swap_adapter::{SwapAdapter, SwapAdapterClient};
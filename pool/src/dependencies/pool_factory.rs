@@ -0,0 +1,8 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// The pool registry exposed by the pool factory the backstop verifies pools with
+#[contractclient(name = "PoolFactoryClient")]
+pub trait PoolFactory {
+    /// Check if "pool_address" was deployed by the pool factory
+    fn is_pool(e: Env, pool_address: Address) -> bool;
+}
@@ -0,0 +1,22 @@
+use soroban_sdk::{contractclient, Address, Env, Vec};
+
+/// The interface of a liquidation protection contract a user can register with the pool
+#[contractclient(name = "LiquidationProtectionClient")]
+pub trait LiquidationProtection {
+    /// Called before a liquidation auction is created for `user`. The contract can approve the
+    /// pool to pull collateral from it and return the collateral to supply for the user.
+    ///
+    /// Returns a vec of (asset, amount) collateral the pool can pull from the contract
+    ///
+    /// ### Arguments
+    /// * `pool` - The address of the pool
+    /// * `user` - The address of the user being liquidated
+    /// * `shortfall` - The amount the user's liabilities exceed their collateral by, in the
+    ///   oracle's base asset
+    fn on_liquidation_warning(
+        e: Env,
+        pool: Address,
+        user: Address,
+        shortfall: i128,
+    ) -> Vec<(Address, i128)>;
+}
@@ -0,0 +1,24 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// The interface of a swap adapter used to swap collateral in the pool
+#[contractclient(name = "SwapAdapterClient")]
+pub trait SwapAdapter {
+    /// Swap the input tokens sent to the adapter and transfer the output tokens to `to`
+    ///
+    /// ### Arguments
+    /// * `caller` - The address of the user swapping collateral
+    /// * `to` - The address to send the output tokens to
+    /// * `token_in` - The token sent to the adapter
+    /// * `amount_in` - The amount of `token_in` sent to the adapter
+    /// * `token_out` - The token to send to `to`
+    /// * `amount_out` - The amount of `token_out` to send to `to`
+    fn exec_swap(
+        e: Env,
+        caller: Address,
+        to: Address,
+        token_in: Address,
+        amount_in: i128,
+        token_out: Address,
+        amount_out: i128,
+    );
+}
@@ -0,0 +1,18 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// The interface of a utilization hook contract the pool admin can register with the pool
+#[contractclient(name = "UtilizationHookClient")]
+pub trait UtilizationHook {
+    /// Called when the pool stores a reserve that accrued interest since it was last stored.
+    /// Failures are ignored by the pool.
+    ///
+    /// The pool cannot be called back during the hook. A controller adjusts a reserve's
+    /// interest rate config afterwards through the queued reserve update path.
+    ///
+    /// ### Arguments
+    /// * `pool` - The address of the pool
+    /// * `asset` - The underlying asset of the reserve
+    /// * `util` - The utilization of the reserve as stored (7 decimals)
+    /// * `ir_mod` - The interest rate modifier of the reserve as stored (9 decimals)
+    fn on_accrual(e: Env, pool: Address, asset: Address, util: i128, ir_mod: i128);
+}
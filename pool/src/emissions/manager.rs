@@ -3,7 +3,8 @@ use crate::{
     dependencies::BackstopClient,
     errors::PoolError,
     events::PoolEvents,
-    storage::{self, ReserveConfig, ReserveEmissionData},
+    pool::Pool,
+    storage::{self, EmissionEpoch, ReserveConfig, ReserveEmissionData},
 };
 use cast::{i128, u64};
 use soroban_fixed_point_math::FixedPoint;
@@ -25,7 +26,8 @@ pub struct ReserveEmissionMetadata {
 
 /// Set the pool emissions
 ///
-/// These will not be applied until the next `update_emissions` is run
+/// These will not be applied until the next emission epoch starts with `gulp_emissions`. Past
+/// epochs keep the config they were started with.
 ///
 /// ### Arguments
 /// * `res_emission_metadata` - A vector of `ReserveEmissionMetadata` that details each reserve token's share
@@ -51,6 +53,20 @@ pub fn set_pool_emissions(e: &Env, res_emission_metadata: Vec<ReserveEmissionMet
     storage::set_pool_emissions(e, &pool_emissions);
 }
 
+/// Set if reserve emissions are weighted by the utilization each reserve realized since the
+/// last emission gulp, instead of only by the configured shares
+///
+/// ### Arguments
+/// * `enabled` - True if reserve emissions are weighted by utilization
+pub fn set_util_weighted_emissions(e: &Env, enabled: bool) {
+    if enabled && !storage::get_util_weighted_emissions(e) {
+        // start a fresh utilization epoch so the first gulp only considers utilization
+        // realized while enabled
+        take_util_accums(e, &storage::get_res_list(e));
+    }
+    storage::set_util_weighted_emissions(e, &enabled);
+}
+
 /// Consume emitted tokens from the backstop and distribute them to reserves
 ///
 /// Returns the number of new tokens distributed for emissions
@@ -72,30 +88,54 @@ fn do_gulp_emissions(e: &Env, new_emissions: i128) {
     }
     let pool_emissions = storage::get_pool_emissions(e);
     let reserve_list = storage::get_res_list(e);
-    let mut pool_emis_enabled: Vec<(ReserveConfig, Address, u32, u64)> = Vec::new(e);
+    let util_accums = if storage::get_util_weighted_emissions(e) {
+        Some(take_util_accums(e, &reserve_list))
+    } else {
+        None
+    };
+    let mut pool_emis_enabled: Vec<(ReserveConfig, Address, u32, i128, i128)> = Vec::new(e);
 
+    let mut reserve_emissions: Map<u32, i128> = map![e];
     let mut total_share: i128 = 0;
+    let mut total_util_share: i128 = 0;
     for (res_token_id, res_eps_share) in pool_emissions.iter() {
         let reserve_index = res_token_id / 2;
         let res_asset_address = reserve_list.get_unchecked(reserve_index);
         let res_config = storage::get_res_config(e, &res_asset_address);
 
         if res_config.enabled {
+            let util_share = match &util_accums {
+                Some(util_accums) => {
+                    i128(res_eps_share) * util_accums.get_unchecked(reserve_index)
+                }
+                None => 0,
+            };
             pool_emis_enabled.push_back((
                 res_config,
                 res_asset_address,
                 res_token_id,
-                res_eps_share,
+                i128(res_eps_share),
+                util_share,
             ));
             total_share += i128(res_eps_share);
+            total_util_share += util_share;
         }
     }
-    for (res_config, res_asset_address, res_token_id, res_eps_share) in pool_emis_enabled {
-        let new_reserve_emissions = i128(res_eps_share)
-            .fixed_div_floor(total_share, SCALAR_7)
+    for (res_config, res_asset_address, res_token_id, res_eps_share, util_share) in
+        pool_emis_enabled
+    {
+        // fallback to the configured shares if no utilization was realized
+        let (share, total) = if total_util_share > 0 {
+            (util_share, total_util_share)
+        } else {
+            (res_eps_share, total_share)
+        };
+        let new_reserve_emissions = share
+            .fixed_div_floor(total, SCALAR_7)
             .unwrap_optimized()
             .fixed_mul_floor(new_emissions, SCALAR_7)
             .unwrap_optimized();
+        reserve_emissions.set(res_token_id, new_reserve_emissions);
 
         update_reserve_emission_eps(
             e,
@@ -105,6 +145,51 @@ fn do_gulp_emissions(e: &Env, new_emissions: i128) {
             new_reserve_emissions,
         );
     }
+    record_emission_epoch(e, new_emissions, pool_emissions, reserve_emissions);
+}
+
+/// Record the start of a new emission epoch
+///
+/// ### Arguments
+/// * `emissions` - The tokens gulped from the backstop for the epoch
+/// * `shares` - The emission config applied for the epoch
+/// * `reserve_emissions` - The new tokens distributed to each reserve token id
+fn record_emission_epoch(
+    e: &Env,
+    emissions: i128,
+    shares: Map<u32, u64>,
+    reserve_emissions: Map<u32, i128>,
+) {
+    let epoch = storage::get_emission_epoch_count(e);
+    let start = e.ledger().timestamp();
+    storage::set_emission_epoch(
+        e,
+        &EmissionEpoch {
+            epoch,
+            start,
+            expiration: start + 7 * 24 * 60 * 60,
+            emissions,
+            shares,
+            reserve_emissions,
+        },
+    );
+    storage::set_emission_epoch_count(e, epoch + 1);
+}
+
+/// Accrue each reserve to the current timestamp and reset their utilization accumulators
+///
+/// Returns the utilization accumulated by each reserve, ordered by reserve index
+fn take_util_accums(e: &Env, reserve_list: &Vec<Address>) -> Vec<i128> {
+    let mut pool = Pool::load(e);
+    let mut util_accums: Vec<i128> = Vec::new(e);
+    for asset in reserve_list.iter() {
+        let mut reserve = pool.load_reserve(e, &asset, true);
+        util_accums.push_back(reserve.util_accum);
+        reserve.util_accum = 0;
+        pool.cache_reserve(reserve);
+    }
+    pool.store_cached_reserves(e);
+    util_accums
 }
 
 fn update_reserve_emission_eps(
@@ -171,7 +256,7 @@ fn update_reserve_emission_eps(
 
 #[cfg(test)]
 mod tests {
-    use crate::testutils;
+    use crate::{storage::PoolConfig, testutils};
 
     use super::*;
     use soroban_sdk::{
@@ -307,6 +392,216 @@ mod tests {
             assert_eq!(r_1_s_config.eps, 0_12500000000000);
             assert_eq!(r_1_s_data.index, 111110000000);
             assert_eq!(r_1_s_data.last_time, 1500000000);
+
+            // verify the epoch was recorded
+            assert_eq!(storage::get_emission_epoch_count(&e), 1);
+            let epoch = storage::get_emission_epoch(&e, 0).unwrap_optimized();
+            assert_eq!(epoch.epoch, 0);
+            assert_eq!(epoch.start, 1500000000);
+            assert_eq!(epoch.expiration, 1500000000 + 7 * 24 * 60 * 60);
+            assert_eq!(epoch.emissions, new_emissions);
+            assert_eq!(epoch.shares, pool_emissions);
+            assert_eq!(epoch.reserve_emissions.len(), 3);
+            assert_eq!(epoch.reserve_emissions.get_unchecked(0), 60_480_0000000);
+            assert_eq!(epoch.reserve_emissions.get_unchecked(2), 166_320_0000000);
+            assert_eq!(epoch.reserve_emissions.get_unchecked(3), 75_600_0000000);
+        });
+    }
+
+    #[test]
+    fn test_gulp_emissions_epochs_are_immutable() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1500000000,
+            protocol_version: 22,
+            sequence_number: 20100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 1499900000;
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            set_pool_emissions(
+                &e,
+                vec![
+                    &e,
+                    ReserveEmissionMetadata {
+                        res_index: 0,
+                        res_type: 0,
+                        share: 1_0000000,
+                    },
+                ],
+            );
+            do_gulp_emissions(&e, 100_0000000);
+
+            // changing the config mid epoch does not change the current epoch
+            set_pool_emissions(
+                &e,
+                vec![
+                    &e,
+                    ReserveEmissionMetadata {
+                        res_index: 0,
+                        res_type: 1,
+                        share: 1_0000000,
+                    },
+                ],
+            );
+            let epoch_0 = storage::get_emission_epoch(&e, 0).unwrap_optimized();
+            assert_eq!(epoch_0.shares, map![&e, (0, 1_0000000)]);
+            assert_eq!(epoch_0.reserve_emissions, map![&e, (0, 100_0000000)]);
+
+            e.ledger().set(LedgerInfo {
+                timestamp: 1500000000 + 7 * 24 * 60 * 60,
+                protocol_version: 22,
+                sequence_number: 20100,
+                network_id: Default::default(),
+                base_reserve: 10,
+                min_temp_entry_ttl: 10,
+                min_persistent_entry_ttl: 10,
+                max_entry_ttl: 3110400,
+            });
+            do_gulp_emissions(&e, 50_0000000);
+
+            assert_eq!(storage::get_emission_epoch_count(&e), 2);
+            let epoch_0 = storage::get_emission_epoch(&e, 0).unwrap_optimized();
+            assert_eq!(epoch_0.shares, map![&e, (0, 1_0000000)]);
+            assert_eq!(epoch_0.emissions, 100_0000000);
+
+            let epoch_1 = storage::get_emission_epoch(&e, 1).unwrap_optimized();
+            assert_eq!(epoch_1.epoch, 1);
+            assert_eq!(epoch_1.start, 1500000000 + 7 * 24 * 60 * 60);
+            assert_eq!(epoch_1.shares, map![&e, (1, 1_0000000)]);
+            assert_eq!(epoch_1.reserve_emissions, map![&e, (1, 50_0000000)]);
+            assert!(storage::get_emission_epoch(&e, 2).is_none());
+        });
+    }
+
+    #[test]
+    fn test_gulp_emissions_util_weighted() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1500000000,
+            protocol_version: 22,
+            sequence_number: 20100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let new_emissions: i128 = 302_400_0000000;
+        let pool_emissions: Map<u32, u64> = map![
+            &e,
+            (0, 0_5000000), // reserve_0 liability
+            (2, 0_5000000)  // reserve_1 liability
+        ];
+
+        // reserve_0 at 75% utilization, reserve_1 at 25% utilization
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 1499900000;
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+        reserve_data.d_supply = 25_0000000;
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_pool_emissions(&e, &pool_emissions);
+            storage::set_util_weighted_emissions(&e, &true);
+
+            do_gulp_emissions(&e, new_emissions);
+
+            // emissions are split 3:1 by the utilization realized over the epoch
+            let r_0_l_data = storage::get_res_emis_data(&e, &0).unwrap_optimized();
+            assert_eq!(r_0_l_data.expiration, 1500000000 + 7 * 24 * 60 * 60);
+            assert_eq!(r_0_l_data.eps, 0_37500000000000);
+            let r_1_l_data = storage::get_res_emis_data(&e, &2).unwrap_optimized();
+            assert_eq!(r_1_l_data.expiration, 1500000000 + 7 * 24 * 60 * 60);
+            assert_eq!(r_1_l_data.eps, 0_12500000000000);
+
+            // utilization accumulators are reset for the next epoch
+            let reserve_data_0 = storage::get_res_data(&e, &underlying_0);
+            assert_eq!(reserve_data_0.util_accum, 0);
+            assert_eq!(reserve_data_0.last_time, 1500000000);
+            let reserve_data_1 = storage::get_res_data(&e, &underlying_1);
+            assert_eq!(reserve_data_1.util_accum, 0);
+            assert_eq!(reserve_data_1.last_time, 1500000000);
+        });
+    }
+
+    #[test]
+    fn test_set_util_weighted_emissions_resets_accumulators() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1500000000,
+            protocol_version: 22,
+            sequence_number: 20100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 1499900000;
+        reserve_data.util_accum = 0_5000000 * 1000;
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            assert!(!storage::get_util_weighted_emissions(&e));
+
+            set_util_weighted_emissions(&e, true);
+
+            assert!(storage::get_util_weighted_emissions(&e));
+            let reserve_data_0 = storage::get_res_data(&e, &underlying_0);
+            assert_eq!(reserve_data_0.util_accum, 0);
+            assert_eq!(reserve_data_0.last_time, 1500000000);
         });
     }
 
@@ -1,5 +1,7 @@
 mod manager;
-pub use manager::{gulp_emissions, set_pool_emissions, ReserveEmissionMetadata};
+pub use manager::{
+    gulp_emissions, set_pool_emissions, set_util_weighted_emissions, ReserveEmissionMetadata,
+};
 
 mod distributor;
-pub use distributor::{execute_claim, update_emissions};
+pub use distributor::{execute_claim, update_emission_data, update_emissions};
@@ -51,4 +51,29 @@ pub enum PoolError {
     InvalidBid = 1221,
     InvalidLot = 1222,
     ReserveDisabled = 1223,
+    SupplyLocked = 1224,
+    InvalidPriceAttestation = 1225,
+    InsufficientRiskBotStake = 1226,
+    ReserveRestricted = 1227,
+    InvalidSubmitSignature = 1228,
+    InsufficientCreditDelegation = 1229,
+    IsolatedCollateral = 1230,
+    ExceededDebtCeiling = 1231,
+    ExceededDebtCap = 1232,
+    InsufficientSwapOutput = 1233,
+    ActionPaused = 1234,
+    ExcessivePriceDeviation = 1235,
+    InvalidMigration = 1236,
+    AdminTransferLocked = 1237,
+    SiloedDeposits = 1238,
+    ExcessiveLeverage = 1239,
+    AuctionBelowFloor = 1240,
+    BorrowerNotAllowed = 1241,
+    TransferShortfall = 1242,
+    AuctionFillTooSmall = 1243,
+    DepositorNotAllowed = 1244,
+    LiquidatorNotAllowed = 1245,
+    PositionUnderMinValue = 1246,
+    InvalidPrice = 1247,
+    RiskBotStakeLocked = 1248,
 }
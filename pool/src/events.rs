@@ -1,23 +1,74 @@
-use soroban_sdk::{Address, Env, Symbol, Vec};
+use soroban_sdk::{Address, BytesN, Env, Symbol, Vec};
 
-use crate::{AuctionData, ReserveConfig};
+use crate::{
+    CreditSettlementConfig, EModeCategory, OracleConfig, QueuedEModeCategory, QueuedReserveInit,
+    RateBounds, ReserveEmissionMetadata,
+};
+
+#[cfg(feature = "auctions")]
+use crate::{AuctionConfig, AuctionData, LiquidationConfig, RiskBotUnstake};
+#[cfg(feature = "auctions")]
+use soroban_sdk::Map;
+#[cfg(feature = "flash-loans")]
+use crate::FeeTier;
 
 pub struct PoolEvents {}
 
 impl PoolEvents {
-    /// Emitted when a new admin is set for a pool
+    /// Emitted when a new admin is proposed for a pool
     ///
-    /// - topics - `["set_admin", admin: Address]`
+    /// - topics - `["propose_admin", admin: Address]`
+    /// - data - `[new_admin: Address, unlock_time: u64]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * new_admin - The proposed admin of the pool
+    /// * unlock_time - The timestamp after which the proposed admin can accept
+    pub fn propose_admin(e: &Env, admin: Address, new_admin: Address, unlock_time: u64) {
+        let topics = (Symbol::new(&e, "propose_admin"), admin);
+        e.events().publish(topics, (new_admin, unlock_time));
+    }
+
+    /// Emitted when a pending admin proposal is cancelled
+    ///
+    /// - topics - `["cancel_admin_proposal", admin: Address]`
     /// - data - `new_admin: Address`
     ///
     /// ### Arguments
     /// * admin - The current admin of the pool
+    /// * new_admin - The proposed admin that was cancelled
+    pub fn cancel_admin_proposal(e: &Env, admin: Address, new_admin: Address) {
+        let topics = (Symbol::new(&e, "cancel_admin_proposal"), admin);
+        e.events().publish(topics, new_admin);
+    }
+
+    /// Emitted when a proposed admin accepts the admin role of a pool
+    ///
+    /// - topics - `["set_admin", admin: Address]`
+    /// - data - `new_admin: Address`
+    ///
+    /// ### Arguments
+    /// * admin - The previous admin of the pool
     /// * new_admin - The new admin of the pool
     pub fn set_admin(e: &Env, admin: Address, new_admin: Address) {
         let topics = (Symbol::new(&e, "set_admin"), admin);
         e.events().publish(topics, new_admin);
     }
 
+    /// Emitted when an admin delay change is queued for a pool
+    ///
+    /// - topics - `["set_admin_delay", admin: Address]`
+    /// - data - `[delay: u64, unlock_time: u64]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * delay - The delay in seconds before a proposed admin can accept
+    /// * unlock_time - The timestamp after which the new delay applies
+    pub fn set_admin_delay(e: &Env, admin: Address, delay: u64, unlock_time: u64) {
+        let topics = (Symbol::new(&e, "set_admin_delay"), admin);
+        e.events().publish(topics, (delay, unlock_time));
+    }
+
     /// Emitted when pool parameters are updated
     ///
     /// - topics - `["update_pool", admin: Address]`
@@ -33,33 +84,429 @@ impl PoolEvents {
             .publish(topics, (backstop_take_rate, max_positions));
     }
 
+    /// Emitted when a share token is deployed for the pool's backstop
+    ///
+    /// - topics - `["deploy_backstop_share_token", admin: Address]`
+    /// - data - `share_token: Address`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * share_token - The address of the share token contract
+    pub fn deploy_backstop_share_token(e: &Env, admin: Address, share_token: Address) {
+        let topics = (Symbol::new(&e, "deploy_backstop_share_token"), admin);
+        e.events().publish(topics, share_token);
+    }
+
+    /// Emitted when the admin funds a reward token for the pool's backstop depositors
+    ///
+    /// - topics - `["fund_backstop_reward", admin: Address, token: Address]`
+    /// - data - `[amount: i128, duration: u64]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * token - The address of the reward token
+    /// * amount - The amount of the reward token funded
+    /// * duration - The duration in seconds the rewards are emitted over
+    pub fn fund_backstop_reward(
+        e: &Env,
+        admin: Address,
+        token: Address,
+        amount: i128,
+        duration: u64,
+    ) {
+        let topics = (Symbol::new(&e, "fund_backstop_reward"), admin, token);
+        e.events().publish(topics, (amount, duration));
+    }
+
+    /// Emitted when the admin removes a reward token from the pool's backstop
+    ///
+    /// - topics - `["remove_backstop_reward", admin: Address, token: Address]`
+    /// - data - `()`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * token - The address of the reward token
+    pub fn remove_backstop_reward(e: &Env, admin: Address, token: Address) {
+        let topics = (Symbol::new(&e, "remove_backstop_reward"), admin, token);
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when the flash loan fee is updated
+    ///
+    /// - topics - `["set_flash_loan_fee", admin: Address]`
+    /// - data - `flash_loan_fee: u32`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * flash_loan_fee - The new flash loan fee
+    #[cfg(feature = "flash-loans")]
+    pub fn set_flash_loan_fee(e: &Env, admin: Address, flash_loan_fee: u32) {
+        let topics = (Symbol::new(&e, "set_flash_loan_fee"), admin);
+        e.events().publish(topics, flash_loan_fee);
+    }
+
+    /// Emitted when the fallback oracle is updated
+    ///
+    /// - topics - `["set_fallback_oracle", admin: Address]`
+    /// - data - `fallback_oracle: Option<Address>`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * fallback_oracle - The new fallback oracle, or None if the fallback oracle was removed
+    pub fn set_fallback_oracle(e: &Env, admin: Address, fallback_oracle: Option<Address>) {
+        let topics = (Symbol::new(&e, "set_fallback_oracle"), admin);
+        e.events().publish(topics, fallback_oracle);
+    }
+
+    /// Emitted when the borrow rate bounds of a reserve are set
+    ///
+    /// - topics - `["set_rate_bounds", admin: Address, asset: Address]`
+    /// - data - `rate_bounds: Option<RateBounds>`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the reserve
+    /// * rate_bounds - The new borrow rate bounds, or None if the bounds were removed
+    pub fn set_rate_bounds(
+        e: &Env,
+        admin: Address,
+        asset: Address,
+        rate_bounds: Option<RateBounds>,
+    ) {
+        let topics = (Symbol::new(&e, "set_rate_bounds"), admin, asset);
+        e.events().publish(topics, rate_bounds);
+    }
+
+    /// Emitted when the half-life of the decay of a reserve's rate modifier is set
+    ///
+    /// - topics - `["set_ir_mod_half_life", admin: Address, asset: Address]`
+    /// - data - `half_life: Option<u64>`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the reserve
+    /// * half_life - The new half-life in seconds, or None if the decay was removed
+    pub fn set_ir_mod_half_life(e: &Env, admin: Address, asset: Address, half_life: Option<u64>) {
+        let topics = (Symbol::new(&e, "set_ir_mod_half_life"), admin, asset);
+        e.events().publish(topics, half_life);
+    }
+
+    /// Emitted when interest accrues on a reserve at a borrow rate clamped to its rate bounds
+    ///
+    /// - topics - `["rate_clamped", asset: Address]`
+    /// - data - `rate: i128`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset of the reserve
+    /// * rate - The clamped borrow rate interest accrued at
+    pub fn rate_clamped(e: &Env, asset: Address, rate: i128) {
+        let topics = (Symbol::new(&e, "rate_clamped"), asset);
+        e.events().publish(topics, rate);
+    }
+
+    /// Emitted when a reserve that accrued interest is stored. The share of the interest
+    /// credited to the backstop is emitted as a `credit_backstop` event.
+    ///
+    /// - topics - `["accrue_interest", asset: Address]`
+    /// - data - `[b_rate: i128, d_rate: i128, ir_mod: i128, accrued: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset of the reserve
+    /// * b_rate - The new b_rate of the reserve
+    /// * d_rate - The new d_rate of the reserve
+    /// * ir_mod - The new interest rate modifier of the reserve
+    /// * accrued - The amount of interest accrued by borrowers
+    pub fn accrue_interest(
+        e: &Env,
+        asset: Address,
+        b_rate: i128,
+        d_rate: i128,
+        ir_mod: i128,
+        accrued: i128,
+    ) {
+        let topics = (Symbol::new(&e, "accrue_interest"), asset);
+        e.events().publish(topics, (b_rate, d_rate, ir_mod, accrued));
+    }
+
+    /// Emitted when a reserve's total supply or total liabilities are pushed above its cap by
+    /// interest accrual, since accrual cannot be blocked by the cap
+    ///
+    /// - topics - `["cap_exceeded", asset: Address, cap_type: u32]`
+    /// - data - `[total: i128, cap: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset of the reserve
+    /// * cap_type - The cap that was exceeded, 0 for the collateral cap and 1 for the debt cap
+    /// * total - The total supply or total liabilities of the reserve
+    /// * cap - The cap of the reserve
+    pub fn cap_exceeded(e: &Env, asset: Address, cap_type: u32, total: i128, cap: i128) {
+        let topics = (Symbol::new(&e, "cap_exceeded"), asset, cap_type);
+        e.events().publish(topics, (total, cap));
+    }
+
+    /// Emitted when the minimum health factor of the pool is set
+    ///
+    /// - topics - `["set_min_health_factor", admin: Address]`
+    /// - data - `min_health_factor: u32`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * min_health_factor - The minimum health factor of a user's positions after an action
+    pub fn set_min_health_factor(e: &Env, admin: Address, min_health_factor: u32) {
+        let topics = (Symbol::new(&e, "set_min_health_factor"), admin);
+        e.events().publish(topics, min_health_factor);
+    }
+
+    /// Emitted when the maximum account leverage of the pool is set
+    ///
+    /// - topics - `["set_max_account_leverage", admin: Address]`
+    /// - data - `max_account_leverage: u32`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * max_account_leverage - The maximum leverage of a user's positions after an action
+    pub fn set_max_account_leverage(e: &Env, admin: Address, max_account_leverage: u32) {
+        let topics = (Symbol::new(&e, "set_max_account_leverage"), admin);
+        e.events().publish(topics, max_account_leverage);
+    }
+
+    /// Emitted when a price is loaded from the fallback oracle because the oracle's price is
+    /// missing or stale
+    ///
+    /// - topics - `["oracle_fallback_used", asset: Address]`
+    /// - data - `fallback_oracle: Address`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset of the price
+    /// * fallback_oracle - The fallback oracle the price was loaded from
+    pub fn oracle_fallback_used(e: &Env, asset: Address, fallback_oracle: Address) {
+        let topics = (Symbol::new(&e, "oracle_fallback_used"), asset);
+        e.events().publish(topics, fallback_oracle);
+    }
+
+    /// Emitted when a liquidation loads a price that deviates from the asset's last price by more
+    /// than the max deviation. Liquidations are exempt from the deviation check, so a large price
+    /// move can't block them, but any other action would have failed.
+    ///
+    /// - topics - `["price_deviation_exempted", asset: Address]`
+    /// - data - `[price: i128, last_price: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset of the price
+    /// * price - The price loaded from the oracle
+    /// * last_price - The last price loaded for the asset
+    pub fn price_deviation_exempted(e: &Env, asset: Address, price: i128, last_price: i128) {
+        let topics = (Symbol::new(&e, "price_deviation_exempted"), asset);
+        e.events().publish(topics, (price, last_price));
+    }
+
+    /// Emitted when the backstop credit settlement schedule is updated
+    ///
+    /// - topics - `["set_credit_settlement", admin: Address]`
+    /// - data - `config: CreditSettlementConfig`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * config - The new backstop credit settlement config
+    pub fn set_credit_settlement(e: &Env, admin: Address, config: CreditSettlementConfig) {
+        let topics = (Symbol::new(&e, "set_credit_settlement"), admin);
+        e.events().publish(topics, config);
+    }
+
+    /// Emitted when the auction curve config is updated
+    ///
+    /// - topics - `["set_auction_config", admin: Address]`
+    /// - data - `config: AuctionConfig`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * config - The new auction curve config
+    #[cfg(feature = "auctions")]
+    pub fn set_auction_config(e: &Env, admin: Address, config: AuctionConfig) {
+        let topics = (Symbol::new(&e, "set_auction_config"), admin);
+        e.events().publish(topics, config);
+    }
+
+    /// Emitted when the oracle config is updated
+    ///
+    /// - topics - `["set_oracle_config", admin: Address]`
+    /// - data - `config: OracleConfig`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * config - The new oracle config
+    pub fn set_oracle_config(e: &Env, admin: Address, config: OracleConfig) {
+        let topics = (Symbol::new(&e, "set_oracle_config"), admin);
+        e.events().publish(topics, config);
+    }
+
+    /// Emitted when the liquidation config is updated
+    ///
+    /// - topics - `["set_liquidation_config", admin: Address]`
+    /// - data - `config: LiquidationConfig`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * config - The new liquidation config
+    #[cfg(feature = "auctions")]
+    pub fn set_liquidation_config(e: &Env, admin: Address, config: LiquidationConfig) {
+        let topics = (Symbol::new(&e, "set_liquidation_config"), admin);
+        e.events().publish(topics, config);
+    }
+
+    /// Emitted when a change to the config of an e-mode category is queued
+    ///
+    /// - topics - `["queue_set_emode_category", admin: Address, category: u32]`
+    /// - data - `[config: EModeCategory, unlock_time: u64]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * category - The e-mode category
+    /// * queued - The queued change
+    pub fn queue_set_emode_category(
+        e: &Env,
+        admin: Address,
+        category: u32,
+        queued: QueuedEModeCategory,
+    ) {
+        let topics = (Symbol::new(&e, "queue_set_emode_category"), admin, category);
+        e.events().publish(topics, (queued.config, queued.unlock_time));
+    }
+
+    /// Emitted when a queued change to the config of an e-mode category is cancelled
+    ///
+    /// - topics - `["cancel_set_emode_category", admin: Address, category: u32]`
+    /// - data - `config: EModeCategory`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * category - The e-mode category
+    /// * config - The config of the cancelled change
+    pub fn cancel_set_emode_category(
+        e: &Env,
+        admin: Address,
+        category: u32,
+        config: EModeCategory,
+    ) {
+        let topics = (Symbol::new(&e, "cancel_set_emode_category"), admin, category);
+        e.events().publish(topics, config);
+    }
+
+    /// Emitted when the config of an e-mode category is updated
+    ///
+    /// - topics - `["set_emode_category", category: u32]`
+    /// - data - `config: EModeCategory`
+    ///
+    /// ### Arguments
+    /// * category - The e-mode category
+    /// * config - The new config of the e-mode category
+    pub fn set_emode_category(e: &Env, category: u32, config: EModeCategory) {
+        let topics = (Symbol::new(&e, "set_emode_category"), category);
+        e.events().publish(topics, config);
+    }
+
+    /// Emitted when a bToken is deployed for a reserve
+    ///
+    /// - topics - `["deploy_b_token", admin: Address, asset: Address]`
+    /// - data - `b_token: Address`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the reserve
+    /// * b_token - The address of the bToken contract
+    pub fn deploy_b_token(e: &Env, admin: Address, asset: Address, b_token: Address) {
+        let topics = (Symbol::new(&e, "deploy_b_token"), admin, asset);
+        e.events().publish(topics, b_token);
+    }
+
+    /// Emitted when the flash loan fee tiers are updated
+    ///
+    /// - topics - `["set_fee_tiers", admin: Address]`
+    /// - data - `fee_tiers: Vec<FeeTier>`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * fee_tiers - The new fee tiers
+    #[cfg(feature = "flash-loans")]
+    pub fn set_fee_tiers(e: &Env, admin: Address, fee_tiers: Vec<FeeTier>) {
+        let topics = (Symbol::new(&e, "set_fee_tiers"), admin);
+        e.events().publish(topics, fee_tiers);
+    }
+
     /// Emitted when a new reserve configuration change is queued
     ///
     /// - topics - `["queue_set_reserve", admin: Address]`
-    /// - data - `[asset: Address, metadata: ReserveMetadata]`
+    /// - data - `[asset: Address, metadata: ReserveMetadata, ir_config: Option<InterestRateConfig>,
+    ///            unlock_time: u64, payload_hash: BytesN<32>]`
     ///
     /// ### Arguments
     /// * admin - The current admin of the pool
     /// * asset - The asset to change the reserve configuration of
-    /// * metadata - The new reserve configuration
-    pub fn queue_set_reserve(e: &Env, admin: Address, asset: Address, metadata: ReserveConfig) {
+    /// * proposal - The queued reserve configuration change
+    pub fn queue_set_reserve(e: &Env, admin: Address, asset: Address, proposal: QueuedReserveInit) {
         let topics = (Symbol::new(&e, "queue_set_reserve"), admin);
-        e.events().publish(topics, (asset, metadata));
+        e.events().publish(
+            topics,
+            (
+                asset,
+                proposal.new_config,
+                proposal.ir_config,
+                proposal.unlock_time,
+                proposal.payload_hash,
+            ),
+        );
     }
 
     /// Emitted when a queued reserve configuration change is cancelled
     ///
     /// - topics - `["cancel_set_reserve", admin: Address]`
-    /// - data - `asset: Address`
+    /// - data - `[asset: Address, payload_hash: BytesN<32>]`
     ///
     /// ### Arguments
     /// * admin - The current admin of the pool
     /// * asset - The asset to cancel the reserve configuration change of
-    pub fn cancel_set_reserve(e: &Env, admin: Address, asset: Address) {
+    /// * payload_hash - The payload hash of the cancelled proposal
+    pub fn cancel_set_reserve(e: &Env, admin: Address, asset: Address, payload_hash: BytesN<32>) {
         let topics = (Symbol::new(&e, "cancel_set_reserve"), admin);
+        e.events().publish(topics, (asset, payload_hash));
+    }
+
+    /// Emitted when a reserve is removed
+    ///
+    /// - topics - `["remove_reserve", admin: Address]`
+    /// - data - `asset: Address`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the removed reserve
+    pub fn remove_reserve(e: &Env, admin: Address, asset: Address) {
+        let topics = (Symbol::new(&e, "remove_reserve"), admin);
         e.events().publish(topics, asset);
     }
 
+    /// Emitted when a user's dust positions are withdrawn from a disabled reserve
+    ///
+    /// - topics - `["clear_reserve_dust", admin: Address, asset: Address]`
+    /// - data - `[user: Address, account_id: u32, amount: i128]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the reserve
+    /// * user - The address of the user
+    /// * account_id - The sub-account of the user
+    /// * amount - The amount of underlying tokens sent to the user
+    pub fn clear_reserve_dust(
+        e: &Env,
+        admin: Address,
+        asset: Address,
+        user: Address,
+        account_id: u32,
+        amount: i128,
+    ) {
+        let topics = (Symbol::new(&e, "clear_reserve_dust"), admin, asset);
+        e.events().publish(topics, (user, account_id, amount));
+    }
+
     /// Emitted when a reserve configuration change is set
     ///
     /// - topics - `["set_reserve"]`
@@ -73,6 +520,151 @@ impl PoolEvents {
         e.events().publish(topics, (asset, index));
     }
 
+    /// Emitted when the anomaly detection thresholds for a reserve are set
+    ///
+    /// - topics - `["set_anomaly_thresholds", admin: Address]`
+    /// - data - `[asset: Address, max_b_supply_delta: i128, max_d_supply_delta: i128]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the reserve
+    /// * max_b_supply_delta - The maximum change in b_supply within a ledger
+    /// * max_d_supply_delta - The maximum change in d_supply within a ledger
+    pub fn set_anomaly_thresholds(
+        e: &Env,
+        admin: Address,
+        asset: Address,
+        max_b_supply_delta: i128,
+        max_d_supply_delta: i128,
+    ) {
+        let topics = (Symbol::new(&e, "set_anomaly_thresholds"), admin);
+        e.events()
+            .publish(topics, (asset, max_b_supply_delta, max_d_supply_delta));
+    }
+
+    /// Emitted when the net outflow from a reserve exceeds its anomaly thresholds within a ledger,
+    /// restricting new borrows from the reserve
+    ///
+    /// - topics - `["reserve_anomaly", asset: Address]`
+    /// - data - `[b_supply_delta: i128, d_supply_delta: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset of the reserve
+    /// * b_supply_delta - The net decrease in b_supply within the ledger
+    /// * d_supply_delta - The net increase in d_supply within the ledger
+    pub fn reserve_anomaly(e: &Env, asset: Address, b_supply_delta: i128, d_supply_delta: i128) {
+        let topics = (Symbol::new(&e, "reserve_anomaly"), asset);
+        e.events().publish(topics, (b_supply_delta, d_supply_delta));
+    }
+
+    /// Emitted when the restriction on a reserve is lifted
+    ///
+    /// - topics - `["lift_restriction", admin: Address]`
+    /// - data - `asset: Address`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the reserve
+    pub fn lift_restriction(e: &Env, admin: Address, asset: Address) {
+        let topics = (Symbol::new(&e, "lift_restriction"), admin);
+        e.events().publish(topics, asset);
+    }
+
+    /// Emitted when the share of the backstop take rate paid to referrers is set
+    ///
+    /// - topics - `["set_referral_rate", admin: Address]`
+    /// - data - `rate: u32`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * rate - The share of the backstop take rate paid to referrers
+    pub fn set_referral_rate(e: &Env, admin: Address, rate: u32) {
+        let topics = (Symbol::new(&e, "set_referral_rate"), admin);
+        e.events().publish(topics, rate);
+    }
+
+    /// Emitted when borrowing from the pool is restricted to, or opened from, the borrower
+    /// allowlist
+    ///
+    /// - topics - `["set_borrow_allowlist", admin: Address]`
+    /// - data - `enabled: bool`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * enabled - If borrowing is restricted to the borrower allowlist
+    pub fn set_borrow_allowlist(e: &Env, admin: Address, enabled: bool) {
+        let topics = (Symbol::new(e, "set_borrow_allowlist"), admin);
+        e.events().publish(topics, enabled);
+    }
+
+    /// Emitted when a user is added to or removed from the borrower allowlist
+    ///
+    /// - topics - `["set_allowed_borrower", admin: Address, user: Address]`
+    /// - data - `allowed: bool`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * user - The user
+    /// * allowed - If the user is on the borrower allowlist
+    pub fn set_allowed_borrower(e: &Env, admin: Address, user: Address, allowed: bool) {
+        let topics = (Symbol::new(e, "set_allowed_borrower"), admin, user);
+        e.events().publish(topics, allowed);
+    }
+
+    /// Emitted when the pause guardian of the pool is set
+    ///
+    /// - topics - `["set_pause_guardian", admin: Address]`
+    /// - data - `guardian: Option<Address>`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * guardian - The new pause guardian, or None if the pause guardian was removed
+    pub fn set_pause_guardian(e: &Env, admin: Address, guardian: Option<Address>) {
+        let topics = (Symbol::new(&e, "set_pause_guardian"), admin);
+        e.events().publish(topics, guardian);
+    }
+
+    /// Emitted when the utilization hook of the pool is set
+    ///
+    /// - topics - `["set_util_hook", admin: Address]`
+    /// - data - `hook: Option<Address>`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * hook - The new utilization hook, or None if the hook was removed
+    pub fn set_util_hook(e: &Env, admin: Address, hook: Option<Address>) {
+        let topics = (Symbol::new(&e, "set_util_hook"), admin);
+        e.events().publish(topics, hook);
+    }
+
+    /// Emitted when pool actions are paused
+    ///
+    /// - topics - `["pause_actions", caller: Address]`
+    /// - data - `[actions: u32, paused_actions: u32]`
+    ///
+    /// ### Arguments
+    /// * caller - The admin or pause guardian pausing the actions
+    /// * actions - The bitmask of actions paused
+    /// * paused_actions - The bitmask of all paused actions
+    pub fn pause_actions(e: &Env, caller: Address, actions: u32, paused_actions: u32) {
+        let topics = (Symbol::new(&e, "pause_actions"), caller);
+        e.events().publish(topics, (actions, paused_actions));
+    }
+
+    /// Emitted when pool actions are unpaused
+    ///
+    /// - topics - `["unpause_actions", admin: Address]`
+    /// - data - `[actions: u32, paused_actions: u32]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * actions - The bitmask of actions unpaused
+    /// * paused_actions - The bitmask of all paused actions
+    pub fn unpause_actions(e: &Env, admin: Address, actions: u32, paused_actions: u32) {
+        let topics = (Symbol::new(&e, "unpause_actions"), admin);
+        e.events().publish(topics, (actions, paused_actions));
+    }
+
     /// Emitted when pool status is updated (non-admin)
     ///
     /// - topics - `["set_status"]`
@@ -98,6 +690,20 @@ impl PoolEvents {
         e.events().publish(topics, pool_status);
     }
 
+    /// Emitted when the price signers are updated
+    ///
+    /// - topics - `["set_price_signers", admin: Address]`
+    /// - data - `[signers: Vec<BytesN<32>>, max_age: u64]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * signers - The keys allowed to sign price attestations
+    /// * max_age - The maximum age of an attestation in seconds
+    pub fn set_price_signers(e: &Env, admin: Address, signers: Vec<BytesN<32>>, max_age: u64) {
+        let topics = (Symbol::new(&e, "set_price_signers"), admin);
+        e.events().publish(topics, (signers, max_age));
+    }
+
     /// Emitted when reserve emissions are updated
     ///
     /// - topics - `["reserve_emission_update"]`
@@ -112,9 +718,39 @@ impl PoolEvents {
         e.events().publish(topics, (res_token_id, eps, expiration));
     }
 
+    /// Emitted when the emissions config of the pool is set by the admin
+    ///
+    /// - topics - `["set_emissions_config", admin: Address]`
+    /// - data - `res_emission_metadata: Vec<ReserveEmissionMetadata>`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * res_emission_metadata - The new emissions share of each reserve token
+    pub fn set_emissions_config(
+        e: &Env,
+        admin: Address,
+        res_emission_metadata: Vec<ReserveEmissionMetadata>,
+    ) {
+        let topics = (Symbol::new(e, "set_emissions_config"), admin);
+        e.events().publish(topics, res_emission_metadata);
+    }
+
+    /// Emitted when utilization weighted emissions are enabled or disabled by the admin
+    ///
+    /// - topics - `["set_util_weighted_emissions", admin: Address]`
+    /// - data - `enabled: bool`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * enabled - If emissions are weighted by the utilization of each reserve
+    pub fn set_util_weighted_emissions(e: &Env, admin: Address, enabled: bool) {
+        let topics = (Symbol::new(e, "set_util_weighted_emissions"), admin);
+        e.events().publish(topics, enabled);
+    }
+
     /// Emitted when emissions are gulped
     ///
-    /// - topics - `["update_emissions"]`
+    /// - topics - `["gulp_emissions"]`
     /// - data - `emissions: i128`
     ///
     /// ### Arguments
@@ -139,31 +775,131 @@ impl PoolEvents {
             .publish(topics, (reserve_token_ids, amount_claimed));
     }
 
-    /// Emitted when bad debt is recorded
+    /// Emitted when bad debt is recorded
+    ///
+    /// - topics - `["bad_debt", user: Address, asset: Address]`
+    /// - data - `[d_tokens: i128]`
+    ///
+    /// ### Arguments
+    /// * user - The user with bad debt
+    /// * asset - The asset with bad debt
+    /// * d_tokens - The amount of bad debt
+    pub fn bad_debt(e: &Env, user: Address, asset: Address, d_tokens: i128) {
+        let topics = (Symbol::new(e, "bad_debt"), user, asset);
+        e.events().publish(topics, d_tokens);
+    }
+
+    /// Emitted when the backstop's bad debt is repaid directly with USDC from the backstop
+    ///
+    /// - topics - `["recapitalize", asset: Address]`
+    /// - data - `[amount: i128, d_tokens_burnt: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The asset repaid
+    /// * amount - The amount of underlying tokens repaid
+    /// * d_tokens_burnt - The amount of d_tokens burnt
+    pub fn recapitalize(e: &Env, asset: Address, amount: i128, d_tokens_burnt: i128) {
+        let topics = (Symbol::new(e, "recapitalize"), asset);
+        e.events().publish(topics, (amount, d_tokens_burnt));
+    }
+
+    /// Emitted when backstop credit is settled directly into the backstop
+    ///
+    /// - topics - `["settle_backstop_credit", asset: Address]`
+    /// - data - `[amount: i128, tokens_out: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The asset settled
+    /// * amount - The amount of backstop credit settled
+    /// * tokens_out - The amount of backstop tokens donated to the backstop
+    pub fn settle_backstop_credit(e: &Env, asset: Address, amount: i128, tokens_out: i128) {
+        let topics = (Symbol::new(e, "settle_backstop_credit"), asset);
+        e.events().publish(topics, (amount, tokens_out));
+    }
+
+    /// Emitted when bad debt is defaulted
+    ///
+    /// - topics - `["defaulted_debt", asset: Address]`
+    /// - data - `[d_tokens_burnt: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The asset with defaulted debt
+    /// * d_tokens_burnt - The amount of defaulted d_tokens
+    #[cfg(feature = "auctions")]
+    pub fn defaulted_debt(e: &Env, asset: Address, d_tokens_burnt: i128) {
+        let topics = (Symbol::new(e, "defaulted_debt"), asset);
+        e.events().publish(topics, d_tokens_burnt);
+    }
+
+    /// Emitted when a debt rebate is queued
+    ///
+    /// - topics - `["queue_debt_rebate", admin: Address]`
+    /// - data - `[asset: Address, accounts: Vec<Address>, rebate: i128, funder: Option<Address>]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The asset the rebate is applied to
+    /// * accounts - The accounts receiving the rebate
+    /// * rebate - The portion of each account's liabilities to forgive
+    /// * funder - The address funding the rebate, or None if funded by the backstop credit
+    pub fn queue_debt_rebate(
+        e: &Env,
+        admin: Address,
+        asset: Address,
+        accounts: Vec<Address>,
+        rebate: i128,
+        funder: Option<Address>,
+    ) {
+        let topics = (Symbol::new(&e, "queue_debt_rebate"), admin);
+        e.events().publish(topics, (asset, accounts, rebate, funder));
+    }
+
+    /// Emitted when a queued debt rebate is cancelled
+    ///
+    /// - topics - `["cancel_debt_rebate", admin: Address]`
+    /// - data - `asset: Address`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The asset of the cancelled rebate
+    pub fn cancel_debt_rebate(e: &Env, admin: Address, asset: Address) {
+        let topics = (Symbol::new(&e, "cancel_debt_rebate"), admin);
+        e.events().publish(topics, asset);
+    }
+
+    /// Emitted when a user's debt is forgiven by a debt rebate
     ///
-    /// - topics - `["bad_debt", user: Address, asset: Address]`
-    /// - data - `[d_tokens: i128]`
+    /// - topics - `["debt_rebate", asset: Address, user: Address]`
+    /// - data - `[tokens_forgiven: i128, d_tokens_burnt: i128]`
     ///
     /// ### Arguments
-    /// * user - The user with bad debt
-    /// * asset - The asset with bad debt
-    /// * d_tokens - The amount of bad debt
-    pub fn bad_debt(e: &Env, user: Address, asset: Address, d_tokens: i128) {
-        let topics = (Symbol::new(e, "bad_debt"), user, asset);
-        e.events().publish(topics, d_tokens);
+    /// * asset - The asset of the forgiven debt
+    /// * user - The user receiving the rebate
+    /// * tokens_forgiven - The amount of underlying tokens forgiven
+    /// * d_tokens_burnt - The amount of d_tokens burnt
+    pub fn debt_rebate(
+        e: &Env,
+        asset: Address,
+        user: Address,
+        tokens_forgiven: i128,
+        d_tokens_burnt: i128,
+    ) {
+        let topics = (Symbol::new(e, "debt_rebate"), asset, user);
+        e.events().publish(topics, (tokens_forgiven, d_tokens_burnt));
     }
 
-    /// Emitted when bad debt is defaulted
+    /// Emitted when a debt rebate is funded
     ///
-    /// - topics - `["defaulted_debt", asset: Address]`
-    /// - data - `[d_tokens_burnt: i128]`
+    /// - topics - `["fund_debt_rebate", asset: Address]`
+    /// - data - `[funder: Option<Address>, amount: i128]`
     ///
     /// ### Arguments
-    /// * asset - The asset with defaulted debt
-    /// * d_tokens_burnt - The amount of defaulted d_tokens
-    pub fn defaulted_debt(e: &Env, asset: Address, d_tokens_burnt: i128) {
-        let topics = (Symbol::new(e, "defaulted_debt"), asset);
-        e.events().publish(topics, d_tokens_burnt);
+    /// * asset - The asset of the forgiven debt
+    /// * funder - The address that funded the rebate, or None if funded by the backstop credit
+    /// * amount - The total amount of underlying tokens forgiven
+    pub fn fund_debt_rebate(e: &Env, asset: Address, funder: Option<Address>, amount: i128) {
+        let topics = (Symbol::new(e, "fund_debt_rebate"), asset);
+        e.events().publish(topics, (funder, amount));
     }
 
     /// Emitted when tokens are supplied
@@ -202,6 +938,42 @@ impl PoolEvents {
         e.events().publish(topics, (tokens_out, b_tokens_burnt));
     }
 
+    /// Emitted when a throttled withdrawal is partially filled due to a lack of available liquidity
+    ///
+    /// - topics - `["withdraw_throttled", asset: Address, from: Address]`
+    /// - data - `[tokens_out: i128, tokens_remaining: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The asset
+    /// * from - The address whose position is being modified
+    /// * tokens_out - The amount of tokens withdrawn from the pool
+    /// * tokens_remaining - The amount of requested tokens that could not be withdrawn
+    pub fn withdraw_throttled(
+        e: &Env,
+        asset: Address,
+        from: Address,
+        tokens_out: i128,
+        tokens_remaining: i128,
+    ) {
+        let topics = (Symbol::new(e, "withdraw_throttled"), asset, from);
+        e.events().publish(topics, (tokens_out, tokens_remaining));
+    }
+
+    /// Emitted when a request that allows partial execution is skipped
+    ///
+    /// - topics - `["skip_request", address: Address, from: Address]`
+    /// - data - `[request_type: u32, error: u32]`
+    ///
+    /// ### Arguments
+    /// * address - The asset address or liquidatee of the request
+    /// * from - The address whose positions the request acts on
+    /// * request_type - The type of the request
+    /// * error - The error code of the check the request failed
+    pub fn skip_request(e: &Env, address: Address, from: Address, request_type: u32, error: u32) {
+        let topics = (Symbol::new(e, "skip_request"), address, from);
+        e.events().publish(topics, (request_type, error));
+    }
+
     /// Emitted when collateral is supplied
     ///
     /// - topics - `["supply_collateral", asset: Address, from: Address]`
@@ -259,6 +1031,109 @@ impl PoolEvents {
         e.events().publish(topics, (tokens_out, d_tokens_minted));
     }
 
+    /// Emitted when a user delegates credit to another address
+    ///
+    /// - topics - `["delegate_credit", asset: Address, from: Address, delegatee: Address]`
+    /// - data - `amount: i128`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset that can be borrowed
+    /// * from - The delegator whose positions take on the liabilities
+    /// * delegatee - The address allowed to borrow on behalf of the delegator
+    /// * amount - The amount of the asset the delegatee can borrow
+    pub fn delegate_credit(
+        e: &Env,
+        asset: Address,
+        from: Address,
+        delegatee: Address,
+        amount: i128,
+    ) {
+        let topics = (Symbol::new(e, "delegate_credit"), asset, from, delegatee);
+        e.events().publish(topics, amount);
+    }
+
+    /// Emitted when a user enters or leaves an e-mode category
+    ///
+    /// - topics - `["set_user_emode", user: Address]`
+    /// - data - `category: u32`
+    ///
+    /// ### Arguments
+    /// * user - The user
+    /// * category - The e-mode category entered, or 0 if the user left e-mode
+    pub fn set_user_emode(e: &Env, user: Address, category: u32) {
+        let topics = (Symbol::new(e, "set_user_emode"), user);
+        e.events().publish(topics, category);
+    }
+
+    /// Emitted when a user silos or unsilos their supplied assets
+    ///
+    /// - topics - `["set_siloed_deposits", user: Address]`
+    /// - data - `siloed: bool`
+    ///
+    /// ### Arguments
+    /// * user - The user
+    /// * siloed - If the user's supplied assets are siloed
+    pub fn set_siloed_deposits(e: &Env, user: Address, siloed: bool) {
+        let topics = (Symbol::new(e, "set_siloed_deposits"), user);
+        e.events().publish(topics, siloed);
+    }
+
+    /// Emitted when a user allows or disallows a depositor to supply collateral on their behalf
+    ///
+    /// - topics - `["allow_deposits_from", user: Address, depositor: Address]`
+    /// - data - `allowed: bool`
+    ///
+    /// ### Arguments
+    /// * user - The user
+    /// * depositor - The depositor
+    /// * allowed - If the depositor can supply collateral on behalf of the user
+    pub fn allow_deposits_from(e: &Env, user: Address, depositor: Address, allowed: bool) {
+        let topics = (Symbol::new(e, "allow_deposits_from"), user, depositor);
+        e.events().publish(topics, allowed);
+    }
+
+    /// Emitted when a user opts in or out of a self-repaying loan
+    ///
+    /// - topics - `["set_self_repay", user: Address]`
+    /// - data - `asset: Option<Address>`
+    ///
+    /// ### Arguments
+    /// * user - The user
+    /// * asset - The reserve whose yield repays the user's liability, or None if the user opted out
+    pub fn set_self_repay(e: &Env, user: Address, asset: Option<Address>) {
+        let topics = (Symbol::new(e, "set_self_repay"), user);
+        e.events().publish(topics, asset);
+    }
+
+    /// Emitted when the pool receives less than the amount of tokens sent for a user, and the
+    /// shortfall is deducted from the user's positions
+    ///
+    /// - topics - `["transfer_shortfall", asset: Address, user: Address]`
+    /// - data - `shortfall: i128`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset of the reserve
+    /// * user - The user
+    /// * shortfall - The amount of underlying tokens the pool did not receive
+    pub fn transfer_shortfall(e: &Env, asset: Address, user: Address, shortfall: i128) {
+        let topics = (Symbol::new(e, "transfer_shortfall"), asset, user);
+        e.events().publish(topics, shortfall);
+    }
+
+    /// Emitted when collateral yield is used to repay a user's liability
+    ///
+    /// - topics - `["self_repay", asset: Address, user: Address]`
+    /// - data - `amount: i128`
+    ///
+    /// ### Arguments
+    /// * asset - The underlying asset of the reserve
+    /// * user - The user
+    /// * amount - The amount of underlying tokens repaid
+    pub fn self_repay(e: &Env, asset: Address, user: Address, amount: i128) {
+        let topics = (Symbol::new(e, "self_repay"), asset, user);
+        e.events().publish(topics, amount);
+    }
+
     /// Emitted when a loan is repaid
     ///
     /// - topics - `["repay", asset: Address, from: Address]`
@@ -274,6 +1149,94 @@ impl PoolEvents {
         e.events().publish(topics, (tokens_in, d_tokens_burnt));
     }
 
+    /// Emitted when a repayment within the repayment grace window receives an interest rebate
+    ///
+    /// - topics - `["repay_rebate", asset: Address, from: Address]`
+    /// - data - `amount: i128`
+    ///
+    /// ### Arguments
+    /// * asset - The asset
+    /// * from - The address whose position was repaid
+    /// * amount - The amount of tokens refunded
+    pub fn repay_rebate(e: &Env, asset: Address, from: Address, amount: i128) {
+        let topics = (Symbol::new(e, "repay_rebate"), asset, from);
+        e.events().publish(topics, amount);
+    }
+
+    /// Emitted when a user borrows from a reserve at a fixed rate
+    ///
+    /// - topics - `["borrow_fixed", asset: Address, from: Address]`
+    /// - data - `[amount: i128, rate: i128, maturity: u64]`
+    ///
+    /// ### Arguments
+    /// * asset - The asset
+    /// * from - The address borrowing
+    /// * amount - The amount of tokens borrowed
+    /// * rate - The fixed rate of the borrow
+    /// * maturity - The maturity of the user's fixed-rate loan
+    pub fn borrow_fixed(
+        e: &Env,
+        asset: Address,
+        from: Address,
+        amount: i128,
+        rate: i128,
+        maturity: u64,
+    ) {
+        let topics = (Symbol::new(e, "borrow_fixed"), asset, from);
+        e.events().publish(topics, (amount, rate, maturity));
+    }
+
+    /// Emitted when a user's fixed-rate loan is settled
+    ///
+    /// - topics - `["settle_fixed_loan", asset: Address, user: Address]`
+    /// - data - `[value: i128, d_tokens: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The asset
+    /// * user - The user with the loan
+    /// * value - The fixed value of the loan
+    /// * d_tokens - The dTokens minted (positive) or burnt (negative) for the user
+    pub fn settle_fixed_loan(e: &Env, asset: Address, user: Address, value: i128, d_tokens: i128) {
+        let topics = (Symbol::new(e, "settle_fixed_loan"), asset, user);
+        e.events().publish(topics, (value, d_tokens));
+    }
+
+    /// Emitted when the fixed-rate borrowing config of a reserve is set
+    ///
+    /// - topics - `["set_fixed_rate_config", admin: Address, asset: Address]`
+    /// - data - `[spread: u32, max_term: u64]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the reserve
+    /// * spread - The spread added to the current borrow rate for fixed rates
+    /// * max_term - The maximum number of seconds until a fixed-rate loan's maturity
+    pub fn set_fixed_rate_config(
+        e: &Env,
+        admin: Address,
+        asset: Address,
+        spread: u32,
+        max_term: u64,
+    ) {
+        let topics = (Symbol::new(e, "set_fixed_rate_config"), admin, asset);
+        e.events().publish(topics, (spread, max_term));
+    }
+
+    /// Emitted when the repayment grace window of a reserve is set
+    ///
+    /// - topics - `["set_repay_grace", admin: Address, asset: Address]`
+    /// - data - `[window: u64, rebate: u32]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * asset - The underlying asset of the reserve
+    /// * window - The number of seconds after borrowing a repayment receives the rebate
+    /// * rebate - The share of the accrued interest refunded
+    pub fn set_repay_grace(e: &Env, admin: Address, asset: Address, window: u64, rebate: u32) {
+        let topics = (Symbol::new(e, "set_repay_grace"), admin, asset);
+        e.events().publish(topics, (window, rebate));
+    }
+
     /// Emitted during a flash loan
     ///
     /// - topics - `["flash_loan", asset: Address, from: Address]`
@@ -285,6 +1248,7 @@ impl PoolEvents {
     /// * contract - The address of the flash loan contract
     /// * tokens_out - The amount of tokens sent from the pool
     /// * d_tokens_burnt - The amount of d_tokens burnt
+    #[cfg(feature = "flash-loans")]
     pub fn flash_loan(
         e: &Env,
         asset: Address,
@@ -297,6 +1261,102 @@ impl PoolEvents {
         e.events().publish(topics, (tokens_out, d_tokens_minted));
     }
 
+    /// Emitted when collateral is swapped through a swap adapter
+    ///
+    /// - topics - `["swap_collateral", from: Address, adapter: Address]`
+    /// - data - `[asset_in: Address, amount_in: i128, asset_out: Address, amount_out: i128]`
+    ///
+    /// ### Arguments
+    /// * from - The address whose position is being modified
+    /// * adapter - The address of the swap adapter contract
+    /// * asset_in - The collateral asset sent to the swap adapter
+    /// * amount_in - The amount of collateral sent to the swap adapter
+    /// * asset_out - The collateral asset returned by the swap adapter
+    /// * amount_out - The amount of collateral returned by the swap adapter
+    #[cfg(feature = "flash-loans")]
+    pub fn swap_collateral(
+        e: &Env,
+        from: Address,
+        adapter: Address,
+        asset_in: Address,
+        amount_in: i128,
+        asset_out: Address,
+        amount_out: i128,
+    ) {
+        let topics = (Symbol::new(e, "swap_collateral"), from, adapter);
+        e.events()
+            .publish(topics, (asset_in, amount_in, asset_out, amount_out));
+    }
+
+    /// Emitted when a position is deleveraged by repaying debt with collateral swapped through a
+    /// swap adapter
+    ///
+    /// - topics - `["deleverage", from: Address, adapter: Address]`
+    /// - data - `[asset_in: Address, amount_in: i128, asset_out: Address, amount_out: i128]`
+    ///
+    /// ### Arguments
+    /// * from - The address whose position is being modified
+    /// * adapter - The address of the swap adapter contract
+    /// * asset_in - The collateral asset sent to the swap adapter
+    /// * amount_in - The amount of collateral sent to the swap adapter
+    /// * asset_out - The debt asset returned by the swap adapter
+    /// * amount_out - The amount of the debt asset returned by the swap adapter
+    #[cfg(feature = "flash-loans")]
+    pub fn deleverage(
+        e: &Env,
+        from: Address,
+        adapter: Address,
+        asset_in: Address,
+        amount_in: i128,
+        asset_out: Address,
+        amount_out: i128,
+    ) {
+        let topics = (Symbol::new(e, "deleverage"), from, adapter);
+        e.events()
+            .publish(topics, (asset_in, amount_in, asset_out, amount_out));
+    }
+
+    /// Emitted when positions are migrated from another pool
+    ///
+    /// - topics - `["migrate_position", from: Address, source_pool: Address]`
+    /// - data - `assets: Vec<Address>`
+    ///
+    /// ### Arguments
+    /// * from - The address whose positions were migrated
+    /// * source_pool - The address of the pool the positions were migrated from
+    /// * assets - The underlying assets of the migrated positions
+    pub fn migrate_position(e: &Env, from: Address, source_pool: Address, assets: Vec<Address>) {
+        let topics = (Symbol::new(e, "migrate_position"), from, source_pool);
+        e.events().publish(topics, assets);
+    }
+
+    /// Emitted when a referrer is attributed to a user
+    ///
+    /// - topics - `["set_referrer", user: Address]`
+    /// - data - `referrer: Address`
+    ///
+    /// ### Arguments
+    /// * user - The address of the user
+    /// * referrer - The address of the referrer
+    pub fn set_referrer(e: &Env, user: Address, referrer: Address) {
+        let topics = (Symbol::new(e, "set_referrer"), user);
+        e.events().publish(topics, referrer);
+    }
+
+    /// Emitted when referral fees are claimed
+    ///
+    /// - topics - `["claim_referral_fees", referrer: Address, asset: Address]`
+    /// - data - `amount: i128`
+    ///
+    /// ### Arguments
+    /// * referrer - The address of the referrer
+    /// * asset - The underlying asset of the fees
+    /// * amount - The amount of fees claimed
+    pub fn claim_referral_fees(e: &Env, referrer: Address, asset: Address, amount: i128) {
+        let topics = (Symbol::new(e, "claim_referral_fees"), referrer, asset);
+        e.events().publish(topics, amount);
+    }
+
     /// Emitted when a reserve updates its bToken rate
     ///
     /// - topics - `["gulp", asset: Address]`
@@ -311,6 +1371,22 @@ impl PoolEvents {
         e.events().publish(topics, (token_delta, new_b_rate));
     }
 
+    /// Emitted when a reserve that credited the backstop with a share of tokens accrued to its
+    /// supply is stored
+    ///
+    /// - topics - `["credit_backstop", asset: Address]`
+    /// - data - `[amount: i128, accrued_fees: i128]`
+    ///
+    /// ### Arguments
+    /// * asset - The asset
+    /// * amount - The amount of underlying tokens credited to the backstop since the reserve was
+    ///   last stored
+    /// * accrued_fees - The total underlying tokens ever credited to the backstop by the reserve
+    pub fn credit_backstop(e: &Env, asset: Address, amount: i128, accrued_fees: i128) {
+        let topics = (Symbol::new(e, "credit_backstop"), asset);
+        e.events().publish(topics, (amount, accrued_fees));
+    }
+
     /// Emitted when a new auction is created
     ///
     /// - topics - `["new_auction", user: Address, auction_type: u32]`
@@ -321,6 +1397,7 @@ impl PoolEvents {
     /// * user - The auction user
     /// * percent - The percent of assets auctioned off
     /// * auction_data - The auction data
+    #[cfg(feature = "auctions")]
     pub fn new_auction(
         e: &Env,
         auction_type: u32,
@@ -343,6 +1420,7 @@ impl PoolEvents {
     /// * filler - The address of the filler
     /// * fill_percent - The percentage of the auction filled
     /// * filled_auction_data - The filled auction data
+    #[cfg(feature = "auctions")]
     pub fn fill_auction(
         e: &Env,
         auction_type: u32,
@@ -356,6 +1434,244 @@ impl PoolEvents {
             .publish(topics, (filler, fill_percent, filled_auction_data));
     }
 
+    /// Emitted when the keeper that created an auction is paid a bounty for a fill
+    ///
+    /// - topics - `["keeper_bounty", auction_type: u32, user: Address]`
+    /// - data - `[keeper: Address, bounty: Map<Address, i128>]`
+    ///
+    /// ### Arguments
+    /// * auction_type - The type of auction
+    /// * user - The auction user
+    /// * keeper - The keeper that created the auction
+    /// * bounty - The underlying tokens paid to the keeper from the backstop credit
+    #[cfg(feature = "auctions")]
+    pub fn keeper_bounty(
+        e: &Env,
+        auction_type: u32,
+        user: Address,
+        keeper: Address,
+        bounty: Map<Address, i128>,
+    ) {
+        let topics = (Symbol::new(e, "keeper_bounty"), auction_type, user);
+        e.events().publish(topics, (keeper, bounty));
+    }
+
+    /// Emitted when the share of a filled auction lot paid to keepers is set
+    ///
+    /// - topics - `["set_keeper_bounty", admin: Address]`
+    /// - data - `bounty: u32`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * bounty - The share of a filled auction's value paid to keepers
+    #[cfg(feature = "auctions")]
+    pub fn set_keeper_bounty(e: &Env, admin: Address, bounty: u32) {
+        let topics = (Symbol::new(e, "set_keeper_bounty"), admin);
+        e.events().publish(topics, bounty);
+    }
+
+    /// Emitted when a user sets their liquidation protection contract
+    ///
+    /// - topics - `["set_liquidation_protection", user: Address]`
+    /// - data - `protection: Option<Address>`
+    ///
+    /// ### Arguments
+    /// * user - The user
+    /// * protection - The liquidation protection contract, or None if it was removed
+    #[cfg(feature = "auctions")]
+    pub fn set_liquidation_protection(e: &Env, user: Address, protection: Option<Address>) {
+        let topics = (Symbol::new(e, "set_liquidation_protection"), user);
+        e.events().publish(topics, protection);
+    }
+
+    /// Emitted when the health factor under which a submit emits a health warning is set
+    ///
+    /// - topics - `["set_health_warning_threshold", admin: Address]`
+    /// - data - `threshold: u32`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * threshold - The health factor, or 0 if health warnings are disabled
+    pub fn set_health_warning_threshold(e: &Env, admin: Address, threshold: u32) {
+        let topics = (Symbol::new(e, "set_health_warning_threshold"), admin);
+        e.events().publish(topics, threshold);
+    }
+
+    /// Emitted when the minimum value of a collateral or liability position is set
+    ///
+    /// - topics - `["set_min_position_value", admin: Address]`
+    /// - data - `value: i128`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * value - The value in the oracle's base asset, or 0 if there is no minimum
+    pub fn set_min_position_value(e: &Env, admin: Address, value: i128) {
+        let topics = (Symbol::new(e, "set_min_position_value"), admin);
+        e.events().publish(topics, value);
+    }
+
+    /// Emitted when a ledger is declared to record the positions of every user at
+    ///
+    /// - topics - `["declare_snapshot", admin: Address]`
+    /// - data - `[id: u32, ledger: u32]`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * id - The ID of the snapshot
+    /// * ledger - The ledger sequence positions are recorded at
+    pub fn declare_snapshot(e: &Env, admin: Address, id: u32, ledger: u32) {
+        let topics = (Symbol::new(e, "declare_snapshot"), admin);
+        e.events().publish(topics, (id, ledger));
+    }
+
+    /// Emitted when a submit leaves a user's health factor under the health warning threshold
+    ///
+    /// - topics - `["health_warning", user: Address]`
+    /// - data - `[account_id: u32, health_factor: i128, shortfall: i128]`
+    ///
+    /// ### Arguments
+    /// * user - The user
+    /// * account_id - The ID of the sub-account
+    /// * health_factor - The health factor of the positions, expressed in 7 decimals
+    /// * shortfall - The effective collateral, in the oracle's base asset, the positions need to
+    ///               reach the health warning threshold
+    pub fn health_warning(
+        e: &Env,
+        user: Address,
+        account_id: u32,
+        health_factor: i128,
+        shortfall: i128,
+    ) {
+        let topics = (Symbol::new(e, "health_warning"), user);
+        e.events()
+            .publish(topics, (account_id, health_factor, shortfall));
+    }
+
+    /// Emitted when a user's liquidation protection contract is warned before a liquidation
+    ///
+    /// - topics - `["liquidation_warning", user: Address]`
+    /// - data - `[account_id: u32, shortfall: i128, protected: bool]`
+    ///
+    /// ### Arguments
+    /// * user - The user being liquidated
+    /// * account_id - The ID of the sub-account being liquidated
+    /// * shortfall - The amount the user's liabilities exceeded their collateral by
+    /// * protected - If the user's health was restored and no auction was created
+    pub fn liquidation_warning(
+        e: &Env,
+        user: Address,
+        account_id: u32,
+        shortfall: i128,
+        protected: bool,
+    ) {
+        let topics = (Symbol::new(e, "liquidation_warning"), user);
+        e.events().publish(topics, (account_id, shortfall, protected));
+    }
+
+    /// Emitted when the value of backstop bad debt that starts a bad debt auction is set
+    ///
+    /// - topics - `["set_bad_debt_threshold", admin: Address]`
+    /// - data - `threshold: i128`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * threshold - The value of bad debt in the oracle's base asset, or 0 if disabled
+    #[cfg(feature = "auctions")]
+    pub fn set_bad_debt_threshold(e: &Env, admin: Address, threshold: i128) {
+        let topics = (Symbol::new(e, "set_bad_debt_threshold"), admin);
+        e.events().publish(topics, threshold);
+    }
+
+    /// Emitted when user liquidations in the pool are restricted to, or opened from, the
+    /// liquidator allowlist
+    ///
+    /// - topics - `["set_liquidator_allowlist", admin: Address]`
+    /// - data - `enabled: bool`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * enabled - If user liquidations are restricted to the liquidator allowlist
+    #[cfg(feature = "auctions")]
+    pub fn set_liquidator_allowlist(e: &Env, admin: Address, enabled: bool) {
+        let topics = (Symbol::new(e, "set_liquidator_allowlist"), admin);
+        e.events().publish(topics, enabled);
+    }
+
+    /// Emitted when an address is added to or removed from the liquidator allowlist
+    ///
+    /// - topics - `["set_allowed_liquidator", admin: Address, liquidator: Address]`
+    /// - data - `allowed: bool`
+    ///
+    /// ### Arguments
+    /// * admin - The current admin of the pool
+    /// * liquidator - The liquidator
+    /// * allowed - If the liquidator is on the liquidator allowlist
+    #[cfg(feature = "auctions")]
+    pub fn set_allowed_liquidator(e: &Env, admin: Address, liquidator: Address, allowed: bool) {
+        let topics = (Symbol::new(e, "set_allowed_liquidator"), admin, liquidator);
+        e.events().publish(topics, allowed);
+    }
+
+    /// Emitted when a risk bot stakes BLND
+    ///
+    /// - topics - `["stake_risk_bot", bot: Address]`
+    /// - data - `[amount: i128, stake: i128]`
+    ///
+    /// ### Arguments
+    /// * bot - The address of the risk bot
+    /// * amount - The amount of BLND staked
+    /// * stake - The bot's new stake
+    #[cfg(feature = "auctions")]
+    pub fn stake_risk_bot(e: &Env, bot: Address, amount: i128, stake: i128) {
+        let topics = (Symbol::new(e, "stake_risk_bot"), bot);
+        e.events().publish(topics, (amount, stake));
+    }
+
+    /// Emitted when a risk bot queues BLND to unstake
+    ///
+    /// - topics - `["queue_unstake_risk_bot", bot: Address]`
+    /// - data - `[amount: i128, unstake: RiskBotUnstake]`
+    ///
+    /// ### Arguments
+    /// * bot - The address of the risk bot
+    /// * amount - The amount of BLND queued
+    /// * unstake - The bot's queued unstake
+    #[cfg(feature = "auctions")]
+    pub fn queue_unstake_risk_bot(e: &Env, bot: Address, amount: i128, unstake: RiskBotUnstake) {
+        let topics = (Symbol::new(e, "queue_unstake_risk_bot"), bot);
+        e.events().publish(topics, (amount, unstake));
+    }
+
+    /// Emitted when a risk bot unstakes BLND
+    ///
+    /// - topics - `["unstake_risk_bot", bot: Address]`
+    /// - data - `[amount: i128, stake: i128]`
+    ///
+    /// ### Arguments
+    /// * bot - The address of the risk bot
+    /// * amount - The amount of BLND unstaked
+    /// * stake - The bot's new stake
+    #[cfg(feature = "auctions")]
+    pub fn unstake_risk_bot(e: &Env, bot: Address, amount: i128, stake: i128) {
+        let topics = (Symbol::new(e, "unstake_risk_bot"), bot);
+        e.events().publish(topics, (amount, stake));
+    }
+
+    /// Emitted when a risk bot is slashed for requesting a liquidation of a healthy user
+    ///
+    /// - topics - `["slash_risk_bot", bot: Address]`
+    /// - data - `[user: Address, amount: i128]`
+    ///
+    /// ### Arguments
+    /// * bot - The address of the risk bot
+    /// * user - The healthy user
+    /// * amount - The amount of BLND slashed
+    #[cfg(feature = "auctions")]
+    pub fn slash_risk_bot(e: &Env, bot: Address, user: Address, amount: i128) {
+        let topics = (Symbol::new(e, "slash_risk_bot"), bot);
+        e.events().publish(topics, (user, amount));
+    }
+
     /// Emitted when a liquidation auction is deleted
     ///
     /// - topics - `["delete_liquidation_auction", from: Address]`
@@ -363,6 +1679,7 @@ impl PoolEvents {
     ///
     /// ### Arguments
     /// * from - The address of the liquidated user
+    #[cfg(feature = "auctions")]
     pub fn delete_liquidation_auction(e: &Env, from: Address) {
         let topics = (Symbol::new(&e, "delete_liquidation_auction"), from);
         e.events().publish(topics, ());
@@ -18,12 +18,28 @@ mod storage;
 mod testutils;
 mod validator;
 
-pub use auctions::{AuctionData, AuctionType};
+pub use auctions::{AuctionData, AuctionType, QueuedAuction};
+#[cfg(feature = "auctions")]
+pub use auctions::LiquidationRequest;
 pub use contract::*;
+pub use dependencies::{LiquidationProtection, LiquidationProtectionClient};
+pub use dependencies::{UtilizationHook, UtilizationHookClient};
+#[cfg(feature = "flash-loans")]
+pub use dependencies::{SwapAdapter, SwapAdapterClient};
 pub use emissions::ReserveEmissionMetadata;
 pub use errors::PoolError;
-pub use pool::{FlashLoan, Positions, Request, RequestType};
+pub use pool::{
+    AdminOp, CollateralSwap, FlashLoan, HealthFactorData, PositionValue, Positions,
+    PriceAttestation, Request, RequestOptions, RequestResult, RequestType, ReservePreview,
+    ReserveRates, SimulateResult, SubmitResult, SubmitSignature,
+};
 pub use storage::{
-    AuctionKey, PoolConfig, PoolDataKey, PoolEmissionConfig, ReserveConfig, ReserveData,
-    ReserveEmissionData, UserEmissionData, UserReserveKey,
+    AccountKey, AccrualSnapshot, AuctionConfig, AuctionKey, BorrowSnapshot, ConfigChange,
+    ConfigHistoryEntry, CreditDelegationKey, CreditSettlementConfig, EModeCategory, EmissionEpoch,
+    FeeTier, FixedLoan, FixedRateConfig, FixedTranche, InterestRateConfig, LiquidationConfig,
+    OracleConfig, PendingAdmin, PendingAdminDelay, PoolConfig, PoolDataKey, PoolEmissionConfig,
+    PositionSnapshot, PriceSigners, QueuedEModeCategory, QueuedReserveInit, RateBounds,
+    ReferralFeeKey, RepayGraceConfig, ReserveAnomalyConfig, ReserveConfig, ReserveData,
+    ReserveEmissionData, RiskBotUnstake, SelfRepayConfig, UserEmissionData, UserFeeVolume,
+    UserReserveKey, UserSnapshotKey,
 };
@@ -1,11 +1,19 @@
+use cast::i128;
+use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::Map;
-use soroban_sdk::{contracttype, panic_with_error, Address, Env, Vec};
+use soroban_sdk::{contracttype, panic_with_error, unwrap::UnwrapOptimized, Address, Env, Vec};
 
 use crate::events::PoolEvents;
-use crate::{auctions, errors::PoolError, validator::require_nonnegative};
+#[cfg(feature = "auctions")]
+use crate::auctions;
+use crate::{errors::PoolError, storage, validator::require_nonnegative};
 
+use super::credit_delegation::spend_credit_delegation;
+use super::fixed_rate::{reduce_fixed_loan, settle_fixed_loan};
+use super::isolation::{add_isolated_debt, remove_isolated_debt, require_isolation_allowed};
 use super::pool::Pool;
-use super::User;
+use super::repay_grace::{apply_repay_rebate, record_borrow};
+use super::{Reserve, User};
 
 /// A request a user makes against the pool
 #[derive(Clone)]
@@ -13,7 +21,18 @@ use super::User;
 pub struct Request {
     pub request_type: u32,
     pub address: Address, // asset address or liquidatee
-    pub amount: i128,
+    pub amount: i128,     // i128::MAX withdraws or repays the entire position
+}
+
+/// The optional settings of a request, submitted alongside the requests with `submit_with_options`
+#[derive(Clone, Default)]
+#[contracttype]
+pub struct RequestOptions {
+    pub target: Option<Address>, // the user whose positions are acted on, for requests made on behalf of another user
+    pub account_id: u32, // the sub-account of the user the request acts on, 0 for the main account
+    pub spender: Option<Address>, // the address that sends tokens to the pool for the request, in place of the submit's spender
+    pub to: Option<Address>, // the address that receives tokens from the pool for the request, in place of the submit's "to"
+    pub allow_partial: bool, // skip the request instead of failing the submit if it fails a recoverable check
 }
 
 /// The type of request to be made against the pool
@@ -30,6 +49,11 @@ pub enum RequestType {
     FillBadDebtAuction = 7,
     FillInterestAuction = 8,
     DeleteLiquidationAuction = 9,
+    RepayOnBehalf = 10,
+    WithdrawThrottled = 11,
+    WithdrawCollateralThrottled = 12,
+    FillUserLiquidationAuctionToTarget = 13,
+    SupplyCollateralFor = 14,
 }
 
 impl RequestType {
@@ -49,11 +73,32 @@ impl RequestType {
             7 => RequestType::FillBadDebtAuction,
             8 => RequestType::FillInterestAuction,
             9 => RequestType::DeleteLiquidationAuction,
+            10 => RequestType::RepayOnBehalf,
+            11 => RequestType::WithdrawThrottled,
+            12 => RequestType::WithdrawCollateralThrottled,
+            13 => RequestType::FillUserLiquidationAuctionToTarget,
+            14 => RequestType::SupplyCollateralFor,
             _ => panic_with_error!(e, PoolError::BadRequest),
         }
     }
 }
 
+/// The result of a single request processed by the pool
+#[derive(Clone)]
+#[contracttype]
+pub struct RequestResult {
+    pub request_type: u32,
+    pub address: Address, // asset address or liquidatee
+    pub tokens_in: i128,  // the amount of underlying tokens sent to the pool
+    pub tokens_out: i128, // the amount of underlying tokens sent from the pool
+    pub b_tokens: i128,   // the b_tokens minted (positive) or burnt (negative)
+    pub d_tokens: i128,   // the d_tokens minted (positive) or burnt (negative)
+    pub rate: i128,       // the b_rate or d_rate the request was executed at, 0 for auction requests
+    pub error: u32,       // the error code the request was skipped with, 0 if it was processed
+    pub bid: Map<Address, i128>, // the auction bid paid by the filler, empty for other requests
+    pub lot: Map<Address, i128>, // the auction lot received by the filler, empty for other requests
+}
+
 #[contracttype]
 pub struct FlashLoan {
     pub contract: Address,
@@ -61,11 +106,25 @@ pub struct FlashLoan {
     pub amount: i128,
 }
 
+/// A swap of a collateral asset through a swap adapter contract, either for another collateral
+/// asset or for a debt asset being repaid
+#[contracttype]
+pub struct CollateralSwap {
+    pub adapter: Address,   // the swap adapter contract
+    pub asset_in: Address,  // the collateral asset withdrawn and sent to the adapter
+    pub amount_in: i128,    // the amount of collateral withdrawn and sent to the adapter
+    pub asset_out: Address, // the asset returned by the adapter and supplied or repaid
+    pub amount_out: i128,   // the amount of the asset returned by the adapter
+}
+
 /// Transfer actions to be taken by the sender and pool
 pub struct Actions {
     pub spender_transfer: Map<Address, i128>,
     pub pool_transfer: Map<Address, i128>,
+    pub request_spender_transfer: Map<(Address, Address), i128>, // keyed by (spender, asset)
+    pub request_pool_transfer: Map<(Address, Address), i128>, // keyed by (to, asset)
     pub check_health: bool,
+    pub results: Vec<RequestResult>,
 }
 
 impl Actions {
@@ -74,7 +133,10 @@ impl Actions {
         Actions {
             spender_transfer: Map::new(e),
             pool_transfer: Map::new(e),
+            request_spender_transfer: Map::new(e),
+            request_pool_transfer: Map::new(e),
             check_health: false,
+            results: Vec::new(e),
         }
     }
 
@@ -94,20 +156,145 @@ impl Actions {
         );
     }
 
+    /// Add tokens the request's spender needs to transfer to the pool, or the sender if the
+    /// request does not specify a spender
+    pub fn add_for_request_spender_transfer(
+        &mut self,
+        options: &RequestOptions,
+        asset: &Address,
+        amount: i128,
+    ) {
+        match &options.spender {
+            Some(spender) => {
+                let key = (spender.clone(), asset.clone());
+                self.request_spender_transfer.set(
+                    key.clone(),
+                    amount + self.request_spender_transfer.get(key).unwrap_or(0),
+                );
+            }
+            None => self.add_for_spender_transfer(asset, amount),
+        }
+    }
+
+    /// Add tokens the pool needs to transfer to the request's "to", or to "to" if the request
+    /// does not specify one
+    pub fn add_for_request_pool_transfer(
+        &mut self,
+        options: &RequestOptions,
+        asset: &Address,
+        amount: i128,
+    ) {
+        match &options.to {
+            Some(to) => {
+                let key = (to.clone(), asset.clone());
+                self.request_pool_transfer.set(
+                    key.clone(),
+                    amount + self.request_pool_transfer.get(key).unwrap_or(0),
+                );
+            }
+            None => self.add_for_pool_transfer(asset, amount),
+        }
+    }
+
+    /// Check if any request specified its own spender or "to"
+    pub fn has_request_transfers(&self) -> bool {
+        !self.request_spender_transfer.is_empty() || !self.request_pool_transfer.is_empty()
+    }
+
     // just a simple flag since we won't need
     // to switch it back to false once set to true.
     pub fn do_check_health(&mut self) {
         self.check_health = true
     }
+
+    /// Record the result of a processed request
+    pub fn add_result(
+        &mut self,
+        request: &Request,
+        tokens_in: i128,
+        tokens_out: i128,
+        b_tokens: i128,
+        d_tokens: i128,
+        rate: i128,
+    ) {
+        self.results.push_back(RequestResult {
+            request_type: request.request_type,
+            address: request.address.clone(),
+            tokens_in,
+            tokens_out,
+            b_tokens,
+            d_tokens,
+            rate,
+            error: 0,
+            bid: Map::new(self.results.env()),
+            lot: Map::new(self.results.env()),
+        });
+    }
+
+    /// Record the result of a filled auction, reporting the bid and lot exchanged by the filler
+    #[cfg(feature = "auctions")]
+    pub fn add_auction_result(
+        &mut self,
+        request: &Request,
+        filled_auction: &auctions::AuctionData,
+    ) {
+        self.results.push_back(RequestResult {
+            request_type: request.request_type,
+            address: request.address.clone(),
+            tokens_in: 0,
+            tokens_out: 0,
+            b_tokens: 0,
+            d_tokens: 0,
+            rate: 0,
+            error: 0,
+            bid: filled_auction.bid.clone(),
+            lot: filled_auction.lot.clone(),
+        });
+    }
+
+    /// Record a request that was skipped because it failed a recoverable check
+    pub fn add_skipped(&mut self, request: &Request, error: u32) {
+        self.results.push_back(RequestResult {
+            request_type: request.request_type,
+            address: request.address.clone(),
+            tokens_in: 0,
+            tokens_out: 0,
+            b_tokens: 0,
+            d_tokens: 0,
+            rate: 0,
+            error,
+            bid: Map::new(self.results.env()),
+            lot: Map::new(self.results.env()),
+        });
+    }
+}
+
+/// Build a set of pool actions and the new positions from the supplied requests, with the default
+/// options for every request. See `build_actions_with_options`.
+pub fn build_actions_from_request(
+    e: &Env,
+    pool: &mut Pool,
+    from_state: &mut User,
+    requests: Vec<Request>,
+    delegatee: Option<&Address>,
+) -> Actions {
+    build_actions_with_options(e, pool, from_state, requests, &Vec::new(e), delegatee)
 }
 
 /// Build a set of pool actions and the new positions from the supplied requests. Validates that the requests
-/// are valid based on the status and supported reserves in the pool.
+/// are valid based on the status and supported reserves in the pool. Requests that set
+/// `allow_partial` and fail a recoverable check are skipped, and recorded in the results with the
+/// error they failed with.
 ///
 /// ### Arguments
 /// * pool - The pool
 /// * from - The sender of the requests
 /// * requests - The requests to be processed
+/// * options - The options of each request, in the order of the requests, or empty to use the
+///             default options for every request
+/// * delegatee - The address submitting the requests on behalf of "from" using credit delegation,
+///               if any. Delegatees are only able to borrow and repay, and can't set a request's
+///               spender or "to".
 ///
 /// ### Returns
 /// A tuple of (actions, positions, check_health) where:
@@ -116,18 +303,58 @@ impl Actions {
 /// * check_health - A bool indicating if a health factor check should be performed
 ///
 /// ### Panics
-/// If the request is invalid, or if the pool is in an invalid state.
-pub fn build_actions_from_request(
+/// If the request is invalid, the options don't match the requests, or if the pool is in an
+/// invalid state.
+pub fn build_actions_with_options(
     e: &Env,
     pool: &mut Pool,
     from_state: &mut User,
     requests: Vec<Request>,
+    options: &Vec<RequestOptions>,
+    delegatee: Option<&Address>,
 ) -> Actions {
+    if !options.is_empty() && options.len() != requests.len() {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
     let mut actions = Actions::new(e);
     let prev_positions_count = from_state.positions.effective_count();
-    for request in requests.iter() {
+    for (index, request) in requests.iter().enumerate() {
+        let options = options.get(index as u32).unwrap_or_default();
         // verify the request is allowed
         require_nonnegative(e, &request.amount);
+        if options.account_id != from_state.account_id {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+        // the pool can't send tokens to itself or take on a position
+        let pool_address = e.current_contract_address();
+        if options.spender.as_ref() == Some(&pool_address)
+            || options.to.as_ref() == Some(&pool_address)
+            || options.target.as_ref() == Some(&pool_address)
+        {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+        if delegatee.is_some()
+            && ((request.request_type != RequestType::Borrow as u32
+                && request.request_type != RequestType::Repay as u32)
+                || options.spender.is_some()
+                || options.to.is_some())
+        {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+        if options.allow_partial {
+            if let Err(error) = check_request(e, pool, &request) {
+                let error = error as u32;
+                actions.add_skipped(&request, error);
+                PoolEvents::skip_request(
+                    e,
+                    request.address.clone(),
+                    from_state.address.clone(),
+                    request.request_type,
+                    error,
+                );
+                continue;
+            }
+        }
         pool.require_action_allowed(e, request.request_type);
         match RequestType::from_u32(e, request.request_type) {
             RequestType::Supply => {
@@ -135,7 +362,16 @@ pub fn build_actions_from_request(
                 reserve.require_action_allowed(e, request.request_type);
                 let b_tokens_minted = reserve.to_b_token_down(request.amount);
                 from_state.add_supply(e, &mut reserve, b_tokens_minted);
-                actions.add_for_spender_transfer(&reserve.asset, request.amount);
+                reserve.record_supply(e, from_state);
+                actions.add_for_request_spender_transfer(&options, &reserve.asset, request.amount);
+                actions.add_result(
+                    &request,
+                    request.amount,
+                    0,
+                    b_tokens_minted,
+                    0,
+                    reserve.b_rate,
+                );
                 pool.cache_reserve(reserve);
                 PoolEvents::supply(
                     e,
@@ -145,35 +381,59 @@ pub fn build_actions_from_request(
                     b_tokens_minted,
                 );
             }
-            RequestType::Withdraw => {
+            RequestType::Withdraw | RequestType::WithdrawThrottled => {
                 let mut reserve = pool.load_reserve(e, &request.address, true);
+                reserve.require_action_allowed(e, request.request_type);
+                reserve.require_supply_unlocked(e, &from_state.address);
                 let cur_b_tokens = from_state.get_supply(reserve.index);
-                let mut to_burn = reserve.to_b_token_up(request.amount);
-                let mut tokens_out = request.amount;
-                if to_burn > cur_b_tokens {
-                    to_burn = cur_b_tokens;
-                    tokens_out = reserve.to_asset_from_b_token(cur_b_tokens);
-                }
+                let (to_burn, tokens_out, remaining) =
+                    if request.request_type == RequestType::WithdrawThrottled as u32 {
+                        calc_throttled_withdraw(&reserve, cur_b_tokens, request.amount)
+                    } else {
+                        let (to_burn, tokens_out) =
+                            calc_withdraw(&reserve, cur_b_tokens, request.amount);
+                        (to_burn, tokens_out, 0)
+                    };
                 from_state.remove_supply(e, &mut reserve, to_burn);
-                actions.add_for_pool_transfer(&reserve.asset, tokens_out);
+                actions.add_for_request_pool_transfer(&options, &reserve.asset, tokens_out);
+                actions.add_result(&request, 0, tokens_out, -to_burn, 0, reserve.b_rate);
                 pool.cache_reserve(reserve);
                 PoolEvents::withdraw(
                     e,
                     request.address.clone(),
                     from_state.address.clone(),
-                    request.amount,
+                    tokens_out,
                     to_burn,
                 );
+                if remaining > 0 {
+                    PoolEvents::withdraw_throttled(
+                        e,
+                        request.address.clone(),
+                        from_state.address.clone(),
+                        tokens_out,
+                        remaining,
+                    );
+                }
             }
             RequestType::SupplyCollateral => {
                 let mut reserve = pool.load_reserve(e, &request.address, true);
                 reserve.require_action_allowed(e, request.request_type);
                 let b_tokens_minted = reserve.to_b_token_down(request.amount);
+                let prev_b_tokens = from_state.get_collateral(reserve.index);
                 from_state.add_collateral(e, &mut reserve, b_tokens_minted);
-                actions.add_for_spender_transfer(&reserve.asset, request.amount);
-                if reserve.to_asset_from_b_token(reserve.b_supply) > reserve.collateral_cap {
-                    panic_with_error!(e, PoolError::ExceededCollateralCap);
-                }
+                require_min_collateral(e, pool, &reserve, from_state, prev_b_tokens);
+                require_isolation_allowed(e, pool, from_state, &reserve);
+                reserve.record_supply(e, from_state);
+                actions.add_for_request_spender_transfer(&options, &reserve.asset, request.amount);
+                actions.add_result(
+                    &request,
+                    request.amount,
+                    0,
+                    b_tokens_minted,
+                    0,
+                    reserve.b_rate,
+                );
+                reserve.require_collateral_below_cap(e);
                 pool.cache_reserve(reserve);
                 PoolEvents::supply_collateral(
                     e,
@@ -183,17 +443,29 @@ pub fn build_actions_from_request(
                     b_tokens_minted,
                 );
             }
-            RequestType::WithdrawCollateral => {
+            RequestType::WithdrawCollateral | RequestType::WithdrawCollateralThrottled => {
                 let mut reserve = pool.load_reserve(e, &request.address, true);
+                reserve.require_action_allowed(e, request.request_type);
+                reserve.require_supply_unlocked(e, &from_state.address);
                 let cur_b_tokens = from_state.get_collateral(reserve.index);
-                let mut to_burn = reserve.to_b_token_up(request.amount);
-                let mut tokens_out = request.amount;
-                if to_burn > cur_b_tokens {
-                    to_burn = cur_b_tokens;
-                    tokens_out = reserve.to_asset_from_b_token(cur_b_tokens);
-                }
+                let (to_burn, tokens_out, remaining) = if request.request_type
+                    == RequestType::WithdrawCollateralThrottled as u32
+                {
+                    calc_throttled_withdraw(&reserve, cur_b_tokens, request.amount)
+                } else {
+                    let (to_burn, tokens_out) =
+                        calc_withdraw(&reserve, cur_b_tokens, request.amount);
+                    (to_burn, tokens_out, 0)
+                };
+                // a throttled withdrawal leaves the rest of the position to be withdrawn later
+                let (to_burn, tokens_out) = if remaining == 0 {
+                    close_dust_withdraw(e, pool, &reserve, cur_b_tokens, to_burn, tokens_out)
+                } else {
+                    (to_burn, tokens_out)
+                };
                 from_state.remove_collateral(e, &mut reserve, to_burn);
-                actions.add_for_pool_transfer(&reserve.asset, tokens_out);
+                actions.add_for_request_pool_transfer(&options, &reserve.asset, tokens_out);
+                actions.add_result(&request, 0, tokens_out, -to_burn, 0, reserve.b_rate);
                 actions.do_check_health();
                 pool.cache_reserve(reserve);
                 PoolEvents::withdraw_collateral(
@@ -203,16 +475,47 @@ pub fn build_actions_from_request(
                     tokens_out,
                     to_burn,
                 );
+                if remaining > 0 {
+                    PoolEvents::withdraw_throttled(
+                        e,
+                        request.address.clone(),
+                        from_state.address.clone(),
+                        tokens_out,
+                        remaining,
+                    );
+                }
             }
             RequestType::Borrow => {
+                require_allowed_borrower(e, &from_state.address);
                 let mut reserve = pool.load_reserve(e, &request.address, true);
                 reserve.require_action_allowed(e, request.request_type);
+                if let Some(delegatee) = delegatee {
+                    spend_credit_delegation(
+                        e,
+                        &from_state.address,
+                        delegatee,
+                        &reserve.asset,
+                        request.amount,
+                    );
+                }
                 let d_tokens_minted = reserve.to_d_token_up(request.amount);
                 from_state.add_liabilities(e, &mut reserve, d_tokens_minted);
+                require_min_liabilities(e, pool, &reserve, from_state);
                 reserve.require_utilization_below_max(e);
-                actions.add_for_pool_transfer(&reserve.asset, request.amount);
+                reserve.require_debt_below_cap(e);
+                record_borrow(e, &reserve, from_state);
+                actions.add_for_request_pool_transfer(&options, &reserve.asset, request.amount);
+                actions.add_result(
+                    &request,
+                    0,
+                    request.amount,
+                    0,
+                    d_tokens_minted,
+                    reserve.d_rate,
+                );
                 actions.do_check_health();
                 pool.cache_reserve(reserve);
+                add_isolated_debt(e, pool, from_state, &request.address, request.amount);
                 PoolEvents::borrow(
                     e,
                     request.address.clone(),
@@ -222,37 +525,62 @@ pub fn build_actions_from_request(
                 );
             }
             RequestType::Repay => {
-                let mut reserve = pool.load_reserve(e, &request.address, true);
-                let cur_d_tokens = from_state.get_liabilities(reserve.index);
-                let d_tokens_burnt = reserve.to_d_token_down(request.amount);
-                if d_tokens_burnt > cur_d_tokens {
-                    let cur_underlying_borrowed = reserve.to_asset_from_d_token(cur_d_tokens);
-                    let amount_to_refund = request.amount - cur_underlying_borrowed;
-                    require_nonnegative(e, &amount_to_refund);
-                    actions.add_for_spender_transfer(&reserve.asset, request.amount);
-                    actions.add_for_pool_transfer(&reserve.asset, amount_to_refund);
-                    from_state.remove_liabilities(e, &mut reserve, cur_d_tokens);
-                    PoolEvents::repay(
-                        e,
-                        request.address.clone(),
-                        from_state.address.clone(),
-                        cur_underlying_borrowed,
-                        cur_d_tokens,
-                    );
-                } else {
-                    actions.add_for_spender_transfer(&reserve.asset, request.amount);
-                    from_state.remove_liabilities(e, &mut reserve, d_tokens_burnt);
-                    PoolEvents::repay(
-                        e,
-                        request.address.clone(),
-                        from_state.address.clone(),
-                        request.amount,
-                        d_tokens_burnt,
-                    );
+                build_repay(e, pool, &mut actions, from_state, &request, &options);
+            }
+            RequestType::RepayOnBehalf => {
+                let target = match options.target.clone() {
+                    Some(target) if target != from_state.address => target,
+                    _ => panic_with_error!(e, PoolError::BadRequest),
+                };
+                let mut target_state = User::load(e, &target);
+                target_state.read_only = from_state.read_only;
+                build_repay(e, pool, &mut actions, &mut target_state, &request, &options);
+                target_state.store(e);
+            }
+            RequestType::SupplyCollateralFor => {
+                let target = match options.target.clone() {
+                    Some(target) if target != from_state.address => target,
+                    _ => panic_with_error!(e, PoolError::BadRequest),
+                };
+                if !storage::get_deposits_allowed(e, &target, &from_state.address) {
+                    panic_with_error!(e, PoolError::DepositorNotAllowed);
                 }
+                let mut target_state = User::load(e, &target);
+                target_state.read_only = from_state.read_only;
+                let prev_target_count = target_state.positions.effective_count();
+                let mut reserve = pool.load_reserve(e, &request.address, true);
+                reserve.require_action_allowed(e, request.request_type);
+                let b_tokens_minted = reserve.to_b_token_down(request.amount);
+                let prev_b_tokens = target_state.get_collateral(reserve.index);
+                target_state.add_collateral(e, &mut reserve, b_tokens_minted);
+                require_min_collateral(e, pool, &reserve, &target_state, prev_b_tokens);
+                require_isolation_allowed(e, pool, &target_state, &reserve);
+                reserve.record_supply(e, &target_state);
+                actions.add_for_request_spender_transfer(&options, &reserve.asset, request.amount);
+                actions.add_result(
+                    &request,
+                    request.amount,
+                    0,
+                    b_tokens_minted,
+                    0,
+                    reserve.b_rate,
+                );
+                reserve.require_collateral_below_cap(e);
                 pool.cache_reserve(reserve);
+                pool.require_under_max(e, &target_state.positions, prev_target_count);
+                target_state.store(e);
+                PoolEvents::supply_collateral(
+                    e,
+                    request.address.clone(),
+                    target,
+                    request.amount,
+                    b_tokens_minted,
+                );
             }
+            #[cfg(feature = "auctions")]
             RequestType::FillUserLiquidationAuction => {
+                auctions::require_allowed_liquidator(e, Some(&from_state.address));
+                pool.skip_price_deviation_check();
                 let filled_auction = auctions::fill(
                     e,
                     pool,
@@ -262,6 +590,7 @@ pub fn build_actions_from_request(
                     request.amount as u64,
                 );
                 actions.do_check_health();
+                actions.add_auction_result(&request, &filled_auction);
 
                 PoolEvents::fill_auction(
                     e,
@@ -272,6 +601,39 @@ pub fn build_actions_from_request(
                     filled_auction,
                 );
             }
+            #[cfg(feature = "auctions")]
+            RequestType::FillUserLiquidationAuctionToTarget => {
+                auctions::require_allowed_liquidator(e, Some(&from_state.address));
+                pool.skip_price_deviation_check();
+                // fill only as much of the auction as needed to restore the user to the target
+                // health factor, up to the requested percent
+                let percent_filled = auctions::calc_target_fill_percent(
+                    e,
+                    pool,
+                    &request.address,
+                    request.amount as u64,
+                );
+                let filled_auction = auctions::fill(
+                    e,
+                    pool,
+                    0,
+                    &request.address,
+                    from_state,
+                    percent_filled,
+                );
+                actions.do_check_health();
+                actions.add_auction_result(&request, &filled_auction);
+
+                PoolEvents::fill_auction(
+                    e,
+                    0u32,
+                    request.address.clone(),
+                    from_state.address.clone(),
+                    percent_filled as i128,
+                    filled_auction,
+                );
+            }
+            #[cfg(feature = "auctions")]
             RequestType::FillBadDebtAuction => {
                 // Note: will fail if input address is not the backstop since there cannot be a bad debt auction for a different address in storage
                 let filled_auction = auctions::fill(
@@ -283,6 +645,7 @@ pub fn build_actions_from_request(
                     request.amount as u64,
                 );
                 actions.do_check_health();
+                actions.add_auction_result(&request, &filled_auction);
 
                 PoolEvents::fill_auction(
                     e,
@@ -293,6 +656,7 @@ pub fn build_actions_from_request(
                     filled_auction,
                 );
             }
+            #[cfg(feature = "auctions")]
             RequestType::FillInterestAuction => {
                 // Note: will fail if input address is not the backstop since there cannot be an interest auction for a different address in storage
                 let filled_auction = auctions::fill(
@@ -303,6 +667,7 @@ pub fn build_actions_from_request(
                     from_state,
                     request.amount as u64,
                 );
+                actions.add_auction_result(&request, &filled_auction);
                 PoolEvents::fill_auction(
                     e,
                     2u32,
@@ -312,12 +677,16 @@ pub fn build_actions_from_request(
                     filled_auction,
                 );
             }
+            #[cfg(feature = "auctions")]
             RequestType::DeleteLiquidationAuction => {
                 // Note: request object is ignored besides type
-                auctions::delete_liquidation(e, &from_state.address);
+                auctions::delete_liquidation(e, &from_state.address, from_state.account_id);
                 actions.do_check_health();
+                actions.add_result(&request, 0, 0, 0, 0, 0);
                 PoolEvents::delete_liquidation_auction(e, from_state.address.clone());
             }
+            #[cfg(not(feature = "auctions"))]
+            _ => panic_with_error!(e, PoolError::BadRequest),
         }
     }
 
@@ -327,25 +696,289 @@ pub fn build_actions_from_request(
     actions
 }
 
+/// Check if a request passes the pool's recoverable checks, without modifying any positions.
+/// Recoverable checks are the pool and reserve status, the reserve's collateral and debt caps,
+/// and the reserve's max utilization.
+///
+/// Returns the error the request would fail with, if any
+///
+/// ### Arguments
+/// * pool - The pool
+/// * request - The request to check
+fn check_request(e: &Env, pool: &mut Pool, request: &Request) -> Result<(), PoolError> {
+    pool.check_action_allowed(e, request.request_type)?;
+    let request_type = RequestType::from_u32(e, request.request_type);
+    if request_type != RequestType::Supply
+        && request_type != RequestType::SupplyCollateral
+        && request_type != RequestType::SupplyCollateralFor
+        && request_type != RequestType::Borrow
+    {
+        return Ok(());
+    }
+
+    let mut reserve = pool.load_reserve(e, &request.address, false);
+    // cache the accrued reserve so it isn't loaded again when the request is processed
+    pool.cache_reserve(reserve.clone());
+    reserve.check_action_allowed(e, request.request_type)?;
+    if request_type == RequestType::SupplyCollateral
+        || request_type == RequestType::SupplyCollateralFor
+    {
+        reserve.b_supply += reserve.to_b_token_down(request.amount);
+        if reserve.total_supply() > reserve.collateral_cap {
+            return Err(PoolError::ExceededCollateralCap);
+        }
+    } else if request_type == RequestType::Borrow {
+        reserve.d_supply += reserve.to_d_token_up(request.amount);
+        if reserve.utilization() > i128(reserve.max_util) {
+            return Err(PoolError::InvalidUtilRate);
+        }
+        if reserve.total_liabilities() > reserve.debt_cap {
+            return Err(PoolError::ExceededDebtCap);
+        }
+    }
+    Ok(())
+}
+
+/// Require that the user can take on liabilities in the pool. If borrowing is restricted to the
+/// pool's borrower allowlist, the user must be on it.
+///
+/// ### Arguments
+/// * user - The address of the user taking on the liabilities
+///
+/// ### Panics
+/// If borrowing is restricted and the user is not on the borrower allowlist
+pub fn require_allowed_borrower(e: &Env, user: &Address) {
+    if storage::get_borrow_allowlist(e) && !storage::get_allowed_borrower(e, user) {
+        panic_with_error!(e, PoolError::BorrowerNotAllowed);
+    }
+}
+
+/// Repay the liabilities of a user, refunding any amount over the user's liabilities to the
+/// receiver of the pool transfers
+///
+/// ### Arguments
+/// * pool - The pool
+/// * actions - The actions being built
+/// * user - The user whose liabilities are being repaid
+/// * request - The repay request
+/// * options - The options of the repay request
+fn build_repay(
+    e: &Env,
+    pool: &mut Pool,
+    actions: &mut Actions,
+    user: &mut User,
+    request: &Request,
+    options: &RequestOptions,
+) {
+    let mut reserve = pool.load_reserve(e, &request.address, true);
+    // fixed-rate loans are settled to their fixed value before being repaid
+    settle_fixed_loan(e, pool.config.bstop_rate, &mut reserve, user);
+    let cur_d_tokens = user.get_liabilities(reserve.index);
+    // an amount of i128::MAX repays the exact outstanding liability
+    let amount = if request.amount == i128::MAX {
+        reserve.to_asset_from_d_token(cur_d_tokens)
+    } else {
+        request.amount
+    };
+    let d_tokens_burnt = reserve.to_d_token_down(amount);
+    if d_tokens_burnt > cur_d_tokens {
+        let cur_underlying_borrowed = reserve.to_asset_from_d_token(cur_d_tokens);
+        let amount_to_refund = amount - cur_underlying_borrowed;
+        require_nonnegative(e, &amount_to_refund);
+        actions.add_for_request_spender_transfer(options, &reserve.asset, amount);
+        if amount_to_refund > 0 {
+            actions.add_for_request_pool_transfer(options, &reserve.asset, amount_to_refund);
+        }
+        user.remove_liabilities(e, &mut reserve, cur_d_tokens);
+        let rebate = refund_repay_rebate(e, &mut reserve, actions, user, options, cur_d_tokens);
+        actions.add_result(
+            request,
+            amount,
+            amount_to_refund + rebate,
+            0,
+            -cur_d_tokens,
+            reserve.d_rate,
+        );
+        reduce_fixed_loan(e, &reserve, user);
+        pool.cache_reserve(reserve);
+        remove_isolated_debt(e, pool, user, &request.address, cur_underlying_borrowed);
+        PoolEvents::repay(
+            e,
+            request.address.clone(),
+            user.address.clone(),
+            cur_underlying_borrowed,
+            cur_d_tokens,
+        );
+    } else {
+        actions.add_for_request_spender_transfer(options, &reserve.asset, amount);
+        user.remove_liabilities(e, &mut reserve, d_tokens_burnt);
+        require_min_liabilities(e, pool, &reserve, user);
+        let rebate = refund_repay_rebate(e, &mut reserve, actions, user, options, d_tokens_burnt);
+        actions.add_result(request, amount, rebate, 0, -d_tokens_burnt, reserve.d_rate);
+        reduce_fixed_loan(e, &reserve, user);
+        pool.cache_reserve(reserve);
+        remove_isolated_debt(e, pool, user, &request.address, amount);
+        PoolEvents::repay(
+            e,
+            request.address.clone(),
+            user.address.clone(),
+            amount,
+            d_tokens_burnt,
+        );
+    }
+}
+
+/// Refund the interest rebate for a repayment within the reserve's repayment grace window, if any
+///
+/// Returns the amount of underlying tokens refunded
+fn refund_repay_rebate(
+    e: &Env,
+    reserve: &mut Reserve,
+    actions: &mut Actions,
+    user: &User,
+    options: &RequestOptions,
+    d_tokens_burnt: i128,
+) -> i128 {
+    let rebate = apply_repay_rebate(e, reserve, user, d_tokens_burnt);
+    if rebate > 0 {
+        actions.add_for_request_pool_transfer(options, &reserve.asset, rebate);
+        PoolEvents::repay_rebate(e, reserve.asset.clone(), user.address.clone(), rebate);
+    }
+    rebate
+}
+
+/// Calculate the b_tokens to burn and the underlying tokens to send out to withdraw "amount" from
+/// a position of "cur_b_tokens". An amount of i128::MAX, or any amount exceeding the position,
+/// withdraws the entire position.
+///
+/// Returns (b_tokens burnt, tokens out)
+fn calc_withdraw(reserve: &Reserve, cur_b_tokens: i128, amount: i128) -> (i128, i128) {
+    if amount != i128::MAX {
+        let to_burn = reserve.to_b_token_up(amount);
+        if to_burn <= cur_b_tokens {
+            return (to_burn, amount);
+        }
+    }
+    (cur_b_tokens, reserve.to_asset_from_b_token(cur_b_tokens))
+}
+
+/// Calculate the b_tokens to burn and the underlying tokens to send out to close the entire
+/// position instead, if withdrawing "to_burn" of "cur_b_tokens" would leave a position under the
+/// pool's minimum position value
+///
+/// Returns (b_tokens burnt, tokens out)
+fn close_dust_withdraw(
+    e: &Env,
+    pool: &mut Pool,
+    reserve: &Reserve,
+    cur_b_tokens: i128,
+    to_burn: i128,
+    tokens_out: i128,
+) -> (i128, i128) {
+    if to_burn < cur_b_tokens {
+        let remaining = reserve.to_asset_from_b_token(cur_b_tokens - to_burn);
+        if is_under_min_position_value(e, pool, reserve, remaining) {
+            return (cur_b_tokens, reserve.to_asset_from_b_token(cur_b_tokens));
+        }
+    }
+    (to_burn, tokens_out)
+}
+
+/// Require that a collateral position opened by a supply is at least the pool's minimum position
+/// value. Supplies to an existing position are always allowed, so a user whose position has
+/// fallen under the minimum can still top it up.
+///
+/// ### Arguments
+/// * `prev_b_tokens` - The user's collateral in the reserve before the supply
+///
+/// ### Panics
+/// If a new collateral position is under the minimum position value
+fn require_min_collateral(
+    e: &Env,
+    pool: &mut Pool,
+    reserve: &Reserve,
+    user: &User,
+    prev_b_tokens: i128,
+) {
+    if prev_b_tokens > 0 {
+        return;
+    }
+    let b_tokens = user.get_collateral(reserve.index);
+    if b_tokens > 0
+        && is_under_min_position_value(e, pool, reserve, reserve.to_asset_from_b_token(b_tokens))
+    {
+        panic_with_error!(e, PoolError::PositionUnderMinValue);
+    }
+}
+
+/// Require that the user's liabilities in the reserve are empty or at least the pool's minimum
+/// position value
+///
+/// ### Panics
+/// If the liability position is under the minimum position value
+fn require_min_liabilities(e: &Env, pool: &mut Pool, reserve: &Reserve, user: &User) {
+    let d_tokens = user.get_liabilities(reserve.index);
+    if d_tokens > 0
+        && is_under_min_position_value(e, pool, reserve, reserve.to_asset_from_d_token(d_tokens))
+    {
+        panic_with_error!(e, PoolError::PositionUnderMinValue);
+    }
+}
+
+/// Check if "amount" of the reserve's underlying tokens is valued under the pool's minimum
+/// position value. The price is only loaded if the pool has a minimum.
+fn is_under_min_position_value(e: &Env, pool: &mut Pool, reserve: &Reserve, amount: i128) -> bool {
+    let min_value = storage::get_min_position_value(e);
+    if min_value == 0 {
+        return false;
+    }
+    let value = pool
+        .load_price(e, &reserve.asset)
+        .fixed_mul_floor(amount, reserve.scalar)
+        .unwrap_optimized();
+    value < min_value
+}
+
+/// Calculate the amount of b_tokens to burn and tokens to withdraw, capped at the reserve's
+/// available liquidity.
+///
+/// Returns (to_burn, tokens_out, remaining), where remaining is the amount of underlying tokens
+/// that could not be withdrawn due to a lack of available liquidity
+fn calc_throttled_withdraw(
+    reserve: &Reserve,
+    cur_b_tokens: i128,
+    amount: i128,
+) -> (i128, i128, i128) {
+    let (to_burn, tokens_out) = calc_withdraw(reserve, cur_b_tokens, amount);
+    let max_burn = reserve.max_withdrawable_b_tokens();
+    if to_burn <= max_burn {
+        return (to_burn, tokens_out, 0);
+    }
+    let throttled_out = reserve.to_asset_from_b_token(max_burn);
+    (max_burn, throttled_out, tokens_out - throttled_out)
+}
+
 #[cfg(test)]
 mod tests {
 
     use crate::{
         constants::SCALAR_7,
-        storage::{self, PoolConfig},
+        pool::repay_grace::execute_set_repay_grace,
+        storage::{self, AuctionConfig, BorrowSnapshot, PoolConfig},
         testutils::{self, create_comet_lp_pool, create_pool},
         AuctionData, AuctionType, Positions,
     };
 
     use super::*;
+    use sep_40_oracle::testutils::Asset;
     use soroban_sdk::{
         map,
         testutils::{Address as _, Ledger, LedgerInfo},
-        vec,
+        vec, Symbol,
     };
 
-    // d_rate -> 1_000_001_142
-    // b_rate -> 1_000_000_686
+    // d_rate -> 1_000_001_141_553
+    // b_rate -> 1_000_000_686_000
 
     /***** supply *****/
 
@@ -377,6 +1010,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -393,7 +1031,7 @@ mod tests {
             ];
 
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, false);
 
@@ -447,6 +1085,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
 
         let user_positions = Positions {
@@ -469,7 +1112,7 @@ mod tests {
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, false);
 
@@ -521,6 +1164,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions {
             liabilities: map![&e],
@@ -542,7 +1190,7 @@ mod tests {
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, false);
 
@@ -562,10 +1210,8 @@ mod tests {
         });
     }
 
-    /***** supply collateral *****/
-
     #[test]
-    fn test_build_actions_from_request_supply_collateral() {
+    fn test_build_actions_from_request_withdraw_throttled() {
         let e = Env::default();
         e.mock_all_auths();
 
@@ -574,7 +1220,8 @@ mod tests {
         let pool = testutils::create_pool(&e);
 
         let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
-        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
         testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
 
         e.ledger().set(LedgerInfo {
@@ -589,55 +1236,53 @@ mod tests {
         });
         let pool_config = PoolConfig {
             oracle: Address::generate(&e),
-            bstop_rate: 0_1000000,
+            bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e],
+            supply: map![&e, (0, 30_0000000)],
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
 
             let mut pool = Pool::load(&e);
 
             let requests = vec![
                 &e,
                 Request {
-                    request_type: RequestType::SupplyCollateral as u32,
+                    request_type: RequestType::WithdrawThrottled as u32,
                     address: underlying.clone(),
-                    amount: 10_1234567,
+                    amount: 28_0000000,
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, false);
 
-            let spender_transfer = actions.spender_transfer;
             let pool_transfer = actions.pool_transfer;
-            assert_eq!(spender_transfer.len(), 1);
-            assert_eq!(
-                spender_transfer.get_unchecked(underlying.clone()),
-                10_1234567
-            );
-            assert_eq!(pool_transfer.len(), 0);
-
-            let positions = user.positions.clone();
-            assert_eq!(positions.liabilities.len(), 0);
-            assert_eq!(positions.collateral.len(), 1);
-            assert_eq!(positions.supply.len(), 0);
-            assert_eq!(user.get_collateral(0), 10_1234488);
+            assert_eq!(actions.spender_transfer.len(), 0);
+            assert_eq!(pool_transfer.len(), 1);
+            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 25_0000000);
+            assert_eq!(user.get_supply(0), 5_0000000);
 
-            let reserve = pool.load_reserve(&e, &underlying.clone(), false);
-            assert_eq!(
-                reserve.b_supply,
-                reserve_data.b_supply + user.get_collateral(0)
-            );
+            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(reserve.b_supply, reserve_data.b_supply - 25_0000000);
+            assert_eq!(reserve.total_supply(), reserve.total_liabilities());
         });
     }
 
-    /***** withdraw collateral *****/
-
     #[test]
-    fn test_build_actions_from_request_withdraw_collateral() {
+    fn test_build_actions_from_request_withdraw_collateral_throttled_under_liquidity() {
         let e = Env::default();
         e.mock_all_auths();
 
@@ -646,7 +1291,8 @@ mod tests {
         let pool = testutils::create_pool(&e);
 
         let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
-        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
         testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
 
         e.ledger().set(LedgerInfo {
@@ -664,10 +1310,15 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions {
             liabilities: map![&e],
-            collateral: map![&e, (0, 20_0000000)],
+            collateral: map![&e, (0, 30_0000000)],
             supply: map![&e],
         };
         e.as_contract(&pool, || {
@@ -679,43 +1330,1599 @@ mod tests {
             let requests = vec![
                 &e,
                 Request {
-                    request_type: RequestType::WithdrawCollateral as u32,
+                    request_type: RequestType::WithdrawCollateralThrottled as u32,
                     address: underlying.clone(),
-                    amount: 10_1234567,
+                    amount: 10_0000000,
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, true);
 
+            let pool_transfer = actions.pool_transfer;
+            assert_eq!(pool_transfer.len(), 1);
+            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 10_0000000);
+            assert_eq!(user.get_collateral(0), 20_0000000);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_withdraw_max() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e],
+            supply: map![&e, (0, 20_0000000)],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Withdraw as u32,
+                    address: underlying.clone(),
+                    amount: i128::MAX,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            assert_eq!(actions.check_health, false);
+
             let spender_transfer = actions.spender_transfer;
             let pool_transfer = actions.pool_transfer;
             assert_eq!(spender_transfer.len(), 0);
             assert_eq!(pool_transfer.len(), 1);
-            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 10_1234567);
+            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 20_0000137);
+
+            let positions = user.positions.clone();
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.supply.len(), 0);
+
+            let reserve = pool.load_reserve(&e, &underlying.clone(), false);
+            assert_eq!(reserve.b_supply, reserve_data.b_supply - 20_0000000);
+        });
+    }
+
+    /***** supply collateral *****/
+
+    #[test]
+    fn test_build_actions_from_request_supply_collateral() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            assert_eq!(actions.check_health, false);
+
+            let spender_transfer = actions.spender_transfer;
+            let pool_transfer = actions.pool_transfer;
+            assert_eq!(spender_transfer.len(), 1);
+            assert_eq!(
+                spender_transfer.get_unchecked(underlying.clone()),
+                10_1234567
+            );
+            assert_eq!(pool_transfer.len(), 0);
 
             let positions = user.positions.clone();
             assert_eq!(positions.liabilities.len(), 0);
             assert_eq!(positions.collateral.len(), 1);
             assert_eq!(positions.supply.len(), 0);
-            assert_eq!(user.get_collateral(0), 9_8765502);
+            assert_eq!(user.get_collateral(0), 10_1234488);
+
+            let reserve = pool.load_reserve(&e, &underlying.clone(), false);
+            assert_eq!(
+                reserve.b_supply,
+                reserve_data.b_supply + user.get_collateral(0)
+            );
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_allow_partial() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_0, reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.enabled = false;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config_0, &reserve_data_0);
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config_1, reserve_data_1) = testutils::default_reserve_meta();
+        reserve_config_1.collateral_cap = 105_0000000;
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config_1, &reserve_data_1);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Supply as u32,
+                    address: underlying_0.clone(),
+                    amount: 10_0000000,
+                },
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_1.clone(),
+                    amount: 10_0000000,
+                },
+                Request {
+                    request_type: RequestType::Supply as u32,
+                    address: underlying_1.clone(),
+                    amount: 1_0000000,
+                },
+            ];
+            let options = RequestOptions {
+                allow_partial: true,
+                ..Default::default()
+            };
+            let options = vec![&e, options.clone(), options.clone(), options];
+            let mut user = User::load(&e, &samwise);
+            let actions =
+                build_actions_with_options(&e, &mut pool, &mut user, requests, &options, None);
+
+            // the disabled reserve and the collateral cap are skipped
+            let results = actions.results;
+            assert_eq!(results.len(), 3);
+            assert_eq!(results.get_unchecked(0).error, 1223);
+            assert_eq!(results.get_unchecked(0).tokens_in, 0);
+            assert_eq!(results.get_unchecked(1).error, 1220);
+            assert_eq!(results.get_unchecked(1).tokens_in, 0);
+            assert_eq!(results.get_unchecked(2).error, 0);
+            assert_eq!(results.get_unchecked(2).tokens_in, 1_0000000);
+
+            let spender_transfer = actions.spender_transfer;
+            assert_eq!(spender_transfer.len(), 1);
+            assert_eq!(
+                spender_transfer.get_unchecked(underlying_1.clone()),
+                1_0000000
+            );
+
+            let positions = user.positions.clone();
+            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.supply.len(), 1);
+            assert!(user.get_supply(1) > 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_build_actions_with_options_to_pool() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e],
+            supply: map![&e, (0, 20_0000000)],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+
+            let mut pool_state = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Withdraw as u32,
+                    address: underlying.clone(),
+                    amount: 10_0000000,
+                },
+            ];
+            let options = vec![
+                &e,
+                RequestOptions {
+                    to: Some(pool.clone()),
+                    ..Default::default()
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_with_options(&e, &mut pool_state, &mut user, requests, &options, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1230)")]
+    fn test_build_actions_from_request_supply_collateral_isolated_with_other_collateral() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.isolated = true;
+        reserve_config.debt_ceiling = 1_000_0000000_0000000;
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e, (0, 20_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_1.clone(),
+                    amount: 10_0000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_borrow_against_isolated_collateral() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.isolated = true;
+        reserve_config.debt_ceiling = 100_0000000_0000000;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 2_0000000]);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0.clone(),
+                    amount: 500_0000000,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_1.clone(),
+                    amount: 15_0000000,
+                },
+                Request {
+                    request_type: RequestType::Repay as u32,
+                    address: underlying_1.clone(),
+                    amount: 5_0000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            let reserve = pool.load_reserve(&e, &underlying_0, false);
+            assert_eq!(reserve.isolated_debt, 20_0000000_0000000);
+        });
+    }
+
+    /***** withdraw collateral *****/
+
+    #[test]
+    fn test_build_actions_from_request_withdraw_collateral() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e, (0, 20_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::WithdrawCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            assert_eq!(actions.check_health, true);
+
+            let spender_transfer = actions.spender_transfer;
+            let pool_transfer = actions.pool_transfer;
+            assert_eq!(spender_transfer.len(), 0);
+            assert_eq!(pool_transfer.len(), 1);
+            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 10_1234567);
+
+            let positions = user.positions.clone();
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(positions.collateral.len(), 1);
+            assert_eq!(positions.supply.len(), 0);
+            assert_eq!(user.get_collateral(0), 9_8765502);
+
+            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(
+                reserve.b_supply,
+                reserve_data.b_supply - (20_0000000 - 9_8765502)
+            );
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_withdraw_collateral_closes_dust() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(underlying.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e, (0, 20_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+            storage::set_min_position_value(&e, &1_0000000_0000000);
+
+            let mut pool = Pool::load(&e);
+
+            // leaves 0.1 tokens of collateral, under the minimum position value
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::WithdrawCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 19_9000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            let pool_transfer = actions.pool_transfer;
+            assert_eq!(pool_transfer.len(), 1);
+            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 20_0000137);
+            assert_eq!(actions.results.get_unchecked(0).b_tokens, -20_0000000);
+
+            let positions = user.positions.clone();
+            assert_eq!(positions.collateral.len(), 0);
+
+            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(reserve.b_supply, reserve_data.b_supply - 20_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1246)")]
+    fn test_build_actions_from_request_supply_collateral_under_min_position_value() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(underlying.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+            storage::set_min_position_value(&e, &1_0000000_0000000);
+
+            let mut pool = Pool::load(&e);
+
+            // opens a collateral position under the minimum position value
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 5000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_supply_collateral_tops_up_dust() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(underlying.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e, (0, 5000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+            storage::set_min_position_value(&e, &1_0000000_0000000);
+
+            let mut pool = Pool::load(&e);
+
+            // the position is under the minimum position value, but can still be topped up
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 1000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            assert!(user.positions.collateral.get_unchecked(0) > 5000000);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_withdraw_collateral_over_balance() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e, (0, 20_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::WithdrawCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 21_0000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            assert_eq!(actions.check_health, true);
+
+            let spender_transfer = actions.spender_transfer;
+            let pool_transfer = actions.pool_transfer;
+            assert_eq!(spender_transfer.len(), 0);
+            assert_eq!(pool_transfer.len(), 1);
+            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 20_0000137);
+
+            let positions = user.positions.clone();
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.supply.len(), 0);
+
+            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(reserve.b_supply, reserve_data.b_supply - 20_0000000);
+        });
+    }
+
+    /***** borrow *****/
+
+    #[test]
+    fn test_build_actions_from_request_borrow() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            assert_eq!(actions.check_health, true);
+
+            let spender_transfer = actions.spender_transfer;
+            let pool_transfer = actions.pool_transfer;
+            assert_eq!(spender_transfer.len(), 0);
+            assert_eq!(pool_transfer.len(), 1);
+            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 10_1234567);
+
+            let positions = user.positions.clone();
+            assert_eq!(positions.liabilities.len(), 1);
+            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.supply.len(), 0);
+            assert_eq!(user.get_liabilities(0), 10_1234452);
+
+            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(reserve.d_supply, reserve_data.d_supply + 10_1234452);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1246)")]
+    fn test_build_actions_from_request_borrow_under_min_position_value() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(underlying.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_min_position_value(&e, &1_0000000_0000000);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying.clone(),
+                    amount: 0_9000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_borrow_allowed_borrower() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_borrow_allowlist(&e, true);
+            storage::set_allowed_borrower(&e, &samwise, true);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            assert_eq!(actions.pool_transfer.get_unchecked(underlying.clone()), 10_1234567);
+            assert_eq!(user.get_liabilities(0), 10_1234452);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1241)")]
+    fn test_build_actions_from_request_borrow_not_allowed_borrower() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_borrow_allowlist(&e, true);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_borrow_with_delegation() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_credit_delegation(&e, &samwise, &frodo, &underlying, 15_0000000);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions =
+                build_actions_from_request(&e, &mut pool, &mut user, requests, Some(&frodo));
+
+            assert_eq!(actions.check_health, true);
+            assert_eq!(actions.pool_transfer.get_unchecked(underlying.clone()), 10_1234567);
+            assert_eq!(user.get_liabilities(0), 10_1234452);
+            assert_eq!(
+                storage::get_credit_delegation(&e, &samwise, &frodo, &underlying),
+                4_8765433
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1229)")]
+    fn test_build_actions_from_request_borrow_over_delegation() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_credit_delegation(&e, &samwise, &frodo, &underlying, 10_0000000);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying.clone(),
+                    amount: 10_0000001,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, Some(&frodo));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_build_actions_from_request_withdraw_with_delegation() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_credit_delegation(&e, &samwise, &frodo, &underlying, 10_0000000);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::WithdrawCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 1_0000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, Some(&frodo));
+        });
+    }
+
+    /***** repay *****/
+
+    #[test]
+    fn test_build_actions_from_request_repay() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e, (0, 20_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Repay as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            assert_eq!(actions.check_health, false);
+
+            let spender_transfer = actions.spender_transfer;
+            let pool_transfer = actions.pool_transfer;
+            assert_eq!(spender_transfer.len(), 1);
+            assert_eq!(
+                spender_transfer.get_unchecked(underlying.clone()),
+                10_1234567
+            );
+            assert_eq!(pool_transfer.len(), 0);
+
+            let positions = user.positions.clone();
+            assert_eq!(positions.liabilities.len(), 1);
+            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.supply.len(), 0);
+            let d_tokens_repaid = 10_1234451;
+            assert_eq!(user.get_liabilities(0), 20_0000000 - d_tokens_repaid);
+
+            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(reserve.d_supply, reserve_data.d_supply - d_tokens_repaid);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_repay_with_rebate() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.d_rate = 1_010_000_000_000;
+        reserve_data.backstop_credit = 1_0000000;
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e, (0, 20_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+            execute_set_repay_grace(&e, &underlying, 600, 0_5000000);
+            storage::set_user_last_borrow(
+                &e,
+                &samwise,
+                0,
+                &BorrowSnapshot {
+                    timestamp: 500,
+                    d_rate: 1_000_000_000_000,
+                },
+                600,
+            );
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Repay as u32,
+                    address: underlying.clone(),
+                    amount: 10_1000000,
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            // 0.1 tokens of interest accrued on the repaid dTokens since the borrow, half of it
+            // is refunded
+            assert_eq!(
+                actions.spender_transfer.get_unchecked(underlying.clone()),
+                10_1000000
+            );
+            assert_eq!(actions.pool_transfer.get_unchecked(underlying.clone()), 0_0500000);
+
+            let result = actions.results.get_unchecked(0);
+            assert_eq!(result.tokens_in, 10_1000000);
+            assert_eq!(result.tokens_out, 0_0500000);
+            assert_eq!(result.d_tokens, -10_0000000);
+            assert_eq!(user.get_liabilities(0), 10_0000000);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_repay_on_behalf() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let frodo_positions = Positions {
+            liabilities: map![&e, (0, 20_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &frodo, &frodo_positions);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::RepayOnBehalf as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let options = vec![
+                &e,
+                RequestOptions {
+                    target: Some(frodo.clone()),
+                    ..Default::default()
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions =
+                build_actions_with_options(&e, &mut pool, &mut user, requests, &options, None);
+
+            assert_eq!(actions.check_health, false);
+
+            let spender_transfer = actions.spender_transfer;
+            let pool_transfer = actions.pool_transfer;
+            assert_eq!(spender_transfer.len(), 1);
+            assert_eq!(
+                spender_transfer.get_unchecked(underlying.clone()),
+                10_1234567
+            );
+            assert_eq!(pool_transfer.len(), 0);
+
+            // the sender's positions are untouched
+            assert_eq!(user.positions.liabilities.len(), 0);
+
+            let d_tokens_repaid = 10_1234451;
+            let frodo_positions = storage::get_user_positions(&e, &frodo);
+            assert_eq!(
+                frodo_positions.liabilities.get_unchecked(0),
+                20_0000000 - d_tokens_repaid
+            );
+
+            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(reserve.d_supply, reserve_data.d_supply - d_tokens_repaid);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_from_request_supply_collateral_for() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_deposits_allowed(&e, &frodo, &samwise, true);
+
+            let mut pool = Pool::load(&e);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateralFor as u32,
+                    address: underlying.clone(),
+                    amount: 10_1234567,
+                },
+            ];
+            let options = vec![
+                &e,
+                RequestOptions {
+                    target: Some(frodo.clone()),
+                    ..Default::default()
+                },
+            ];
+            let mut user = User::load(&e, &samwise);
+            let actions =
+                build_actions_with_options(&e, &mut pool, &mut user, requests, &options, None);
 
-            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(actions.check_health, false);
             assert_eq!(
-                reserve.b_supply,
-                reserve_data.b_supply - (20_0000000 - 9_8765502)
+                actions.spender_transfer.get_unchecked(underlying.clone()),
+                10_1234567
             );
+            assert_eq!(actions.pool_transfer.len(), 0);
+
+            // the sender's positions are untouched
+            assert_eq!(user.positions.collateral.len(), 0);
+
+            let frodo_positions = storage::get_user_positions(&e, &frodo);
+            assert_eq!(frodo_positions.collateral.get_unchecked(0), 10_1234488);
+
+            let reserve = pool.load_reserve(&e, &underlying, false);
+            assert_eq!(reserve.b_supply, reserve_data.b_supply + 10_1234488);
         });
     }
 
     #[test]
-    fn test_build_actions_from_request_withdraw_collateral_over_balance() {
+    #[should_panic(expected = "Error(Contract, #1244)")]
+    fn test_build_actions_from_request_supply_collateral_for_not_allowed() {
         let e = Env::default();
         e.mock_all_auths();
 
         let bombadil = Address::generate(&e);
         let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
         let pool = testutils::create_pool(&e);
 
         let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
@@ -737,51 +2944,41 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
-        };
-        let user_positions = Positions {
-            liabilities: map![&e],
-            collateral: map![&e, (0, 20_0000000)],
-            supply: map![&e],
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
-            storage::set_user_positions(&e, &samwise, &user_positions);
+            storage::set_deposits_allowed(&e, &samwise, &frodo, true);
 
             let mut pool = Pool::load(&e);
 
             let requests = vec![
                 &e,
                 Request {
-                    request_type: RequestType::WithdrawCollateral as u32,
+                    request_type: RequestType::SupplyCollateralFor as u32,
                     address: underlying.clone(),
-                    amount: 21_0000000,
+                    amount: 10_1234567,
+                },
+            ];
+            let options = vec![
+                &e,
+                RequestOptions {
+                    target: Some(frodo.clone()),
+                    ..Default::default()
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
-
-            assert_eq!(actions.check_health, true);
-
-            let spender_transfer = actions.spender_transfer;
-            let pool_transfer = actions.pool_transfer;
-            assert_eq!(spender_transfer.len(), 0);
-            assert_eq!(pool_transfer.len(), 1);
-            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 20_0000137);
-
-            let positions = user.positions.clone();
-            assert_eq!(positions.liabilities.len(), 0);
-            assert_eq!(positions.collateral.len(), 0);
-            assert_eq!(positions.supply.len(), 0);
-
-            let reserve = pool.load_reserve(&e, &underlying, false);
-            assert_eq!(reserve.b_supply, reserve_data.b_supply - 20_0000000);
+            build_actions_with_options(&e, &mut pool, &mut user, requests, &options, None);
         });
     }
 
-    /***** borrow *****/
-
     #[test]
-    fn test_build_actions_from_request_borrow() {
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_build_actions_from_request_repay_on_behalf_of_self() {
         let e = Env::default();
         e.mock_all_auths();
 
@@ -792,61 +2989,51 @@ mod tests {
         let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
         let (reserve_config, reserve_data) = testutils::default_reserve_meta();
         testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
-        e.ledger().set(LedgerInfo {
-            timestamp: 600,
-            protocol_version: 22,
-            sequence_number: 1234,
-            network_id: Default::default(),
-            base_reserve: 10,
-            min_temp_entry_ttl: 10,
-            min_persistent_entry_ttl: 10,
-            max_entry_ttl: 3110400,
-        });
+
         let pool_config = PoolConfig {
             oracle: Address::generate(&e),
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e, (0, 20_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
 
             let mut pool = Pool::load(&e);
 
             let requests = vec![
                 &e,
                 Request {
-                    request_type: RequestType::Borrow as u32,
+                    request_type: RequestType::RepayOnBehalf as u32,
                     address: underlying.clone(),
                     amount: 10_1234567,
                 },
             ];
+            let options = vec![
+                &e,
+                RequestOptions {
+                    target: Some(samwise.clone()),
+                    ..Default::default()
+                },
+            ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
-
-            assert_eq!(actions.check_health, true);
-
-            let spender_transfer = actions.spender_transfer;
-            let pool_transfer = actions.pool_transfer;
-            assert_eq!(spender_transfer.len(), 0);
-            assert_eq!(pool_transfer.len(), 1);
-            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 10_1234567);
-
-            let positions = user.positions.clone();
-            assert_eq!(positions.liabilities.len(), 1);
-            assert_eq!(positions.collateral.len(), 0);
-            assert_eq!(positions.supply.len(), 0);
-            assert_eq!(user.get_liabilities(0), 10_1234452);
-
-            let reserve = pool.load_reserve(&e, &underlying, false);
-            assert_eq!(reserve.d_supply, reserve_data.d_supply + 10_1234452);
+            build_actions_with_options(&e, &mut pool, &mut user, requests, &options, None);
         });
     }
 
-    /***** repay *****/
-
     #[test]
-    fn test_build_actions_from_request_repay() {
+    fn test_build_actions_from_request_repay_over_balance() {
         let e = Env::default();
         e.mock_all_auths();
 
@@ -873,6 +3060,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions {
             liabilities: map![&e, (0, 20_0000000)],
@@ -890,11 +3082,11 @@ mod tests {
                 Request {
                     request_type: RequestType::Repay as u32,
                     address: underlying.clone(),
-                    amount: 10_1234567,
+                    amount: 21_0000000,
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, false);
 
@@ -903,24 +3095,23 @@ mod tests {
             assert_eq!(spender_transfer.len(), 1);
             assert_eq!(
                 spender_transfer.get_unchecked(underlying.clone()),
-                10_1234567
+                21_0000000
             );
-            assert_eq!(pool_transfer.len(), 0);
+            assert_eq!(pool_transfer.len(), 1);
+            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 0_9999771);
 
             let positions = user.positions.clone();
-            assert_eq!(positions.liabilities.len(), 1);
+            assert_eq!(positions.liabilities.len(), 0);
             assert_eq!(positions.collateral.len(), 0);
             assert_eq!(positions.supply.len(), 0);
-            let d_tokens_repaid = 10_1234451;
-            assert_eq!(user.get_liabilities(0), 20_0000000 - d_tokens_repaid);
 
             let reserve = pool.load_reserve(&e, &underlying, false);
-            assert_eq!(reserve.d_supply, reserve_data.d_supply - d_tokens_repaid);
+            assert_eq!(reserve.d_supply, reserve_data.d_supply - 20_0000000);
         });
     }
 
     #[test]
-    fn test_build_actions_from_request_repay_over_balance() {
+    fn test_build_actions_from_request_repay_max() {
         let e = Env::default();
         e.mock_all_auths();
 
@@ -947,6 +3138,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions {
             liabilities: map![&e, (0, 20_0000000)],
@@ -964,11 +3160,11 @@ mod tests {
                 Request {
                     request_type: RequestType::Repay as u32,
                     address: underlying.clone(),
-                    amount: 21_0000000,
+                    amount: i128::MAX,
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, false);
 
@@ -977,10 +3173,9 @@ mod tests {
             assert_eq!(spender_transfer.len(), 1);
             assert_eq!(
                 spender_transfer.get_unchecked(underlying.clone()),
-                21_0000000
+                20_0000229
             );
-            assert_eq!(pool_transfer.len(), 1);
-            assert_eq!(pool_transfer.get_unchecked(underlying.clone()), 0_9999771);
+            assert_eq!(pool_transfer.len(), 0);
 
             let positions = user.positions.clone();
             assert_eq!(positions.liabilities.len(), 0);
@@ -1027,6 +3222,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions::env_default(&e);
         e.as_contract(&pool, || {
@@ -1069,7 +3269,7 @@ mod tests {
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, true);
 
@@ -1124,7 +3324,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1138,7 +3338,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -1172,12 +3372,19 @@ mod tests {
                 (underlying_1.clone(), 1_5395739)
             ],
             block: 176,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let pool_config = PoolConfig {
             oracle: oracle_address,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let positions: Positions = Positions {
             collateral: map![
@@ -1209,7 +3416,7 @@ mod tests {
                 },
             ];
             let mut user = User::load(&e, &frodo);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, true);
             let exp_new_auction = AuctionData {
@@ -1220,6 +3427,8 @@ mod tests {
                     (underlying_1.clone(), 7697870)
                 ],
                 block: 176,
+                account_id: 0,
+                config: AuctionConfig::default(),
             };
             let new_auction =
                 storage::get_auction(&e, &(AuctionType::UserLiquidation as u32), &samwise);
@@ -1228,6 +3437,19 @@ mod tests {
             assert_eq!(exp_new_auction.block, new_auction.block);
             assert_eq!(actions.pool_transfer.len(), 0);
             assert_eq!(actions.spender_transfer.len(), 0);
+
+            // the result reports the filled half of the auction
+            let result = actions.results.get_unchecked(0);
+            assert_eq!(result.address, samwise);
+            assert_eq!(result.bid, map![&e, (underlying_2.clone(), 6187500)]);
+            assert_eq!(
+                result.lot,
+                map![
+                    &e,
+                    (underlying_0.clone(), 15_2797664),
+                    (underlying_1.clone(), 7697869)
+                ]
+            );
         });
     }
 
@@ -1269,7 +3491,7 @@ mod tests {
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
         reserve_data_0.last_time = 12345;
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_config_0.c_factor = 0_8500000;
         reserve_config_0.l_factor = 0_9000000;
         reserve_config_0.index = 0;
@@ -1283,7 +3505,7 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_200_000_000;
+        reserve_data_1.b_rate = 1_200_000_000_000;
         reserve_config_1.c_factor = 0_7500000;
         reserve_config_1.l_factor = 0_7500000;
         reserve_data_1.last_time = 12345;
@@ -1300,11 +3522,18 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let auction_data = AuctionData {
             bid: map![&e, (underlying_0, 10_0000000), (underlying_1, 2_5000000)],
             lot: map![&e, (backstop_token_id, 95_2000000)],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
         let positions: Positions = Positions {
             collateral: map![&e],
@@ -1339,7 +3568,7 @@ mod tests {
                 },
             ];
             let mut user = User::load(&e, &frodo);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, true);
             assert_eq!(
@@ -1394,7 +3623,7 @@ mod tests {
 
         let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
-        reserve_data_0.b_rate = 1_100_000_000;
+        reserve_data_0.b_rate = 1_100_000_000_000;
         reserve_data_0.last_time = 12345;
         reserve_config_0.index = 0;
         testutils::create_reserve(
@@ -1408,7 +3637,7 @@ mod tests {
 
         let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
-        reserve_data_1.b_rate = 1_100_000_000;
+        reserve_data_1.b_rate = 1_100_000_000_000;
         reserve_data_1.last_time = 12345;
         reserve_config_1.index = 1;
         testutils::create_reserve(
@@ -1422,7 +3651,7 @@ mod tests {
 
         let (underlying_2, underlying_2_client) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config_2, mut reserve_data_2) = testutils::default_reserve_meta();
-        reserve_data_2.b_rate = 1_100_000_000;
+        reserve_data_2.b_rate = 1_100_000_000_000;
         reserve_data_2.last_time = 12345;
         reserve_config_2.index = 1;
         testutils::create_reserve(
@@ -1439,6 +3668,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let auction_data = AuctionData {
             bid: map![&e, (backstop_token_id.clone(), 100_0000000)],
@@ -1448,6 +3682,8 @@ mod tests {
                 (underlying_1.clone(), 25_0000000)
             ],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
 
         backstop_token_client.approve(
@@ -1479,7 +3715,7 @@ mod tests {
             ];
             let pre_fill_backstop_token_balance = backstop_token_client.balance(&backstop_address);
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(backstop_token_client.balance(&samwise), 25_0000000);
             assert_eq!(
@@ -1489,6 +3725,16 @@ mod tests {
             assert_eq!(underlying_0_client.balance(&samwise), 100_0000000);
             assert_eq!(underlying_1_client.balance(&samwise), 25_0000000);
             assert_eq!(actions.check_health, false);
+            let result = actions.results.get_unchecked(0);
+            assert_eq!(result.bid, map![&e, (backstop_token_id.clone(), 75_0000000)]);
+            assert_eq!(
+                result.lot,
+                map![
+                    &e,
+                    (underlying_0.clone(), 100_0000000),
+                    (underlying_1.clone(), 25_0000000)
+                ]
+            );
             assert_eq!(
                 storage::has_auction(
                     &e,
@@ -1532,6 +3778,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let auction_data = AuctionData {
             bid: map![&e, (underlying_0.clone(), 952_0000000)],
@@ -1541,6 +3792,8 @@ mod tests {
                 (underlying_1.clone(), 25_0000000)
             ],
             block: 51,
+            account_id: 0,
+            config: AuctionConfig::default(),
         };
 
         e.as_contract(&pool_address, || {
@@ -1564,7 +3817,7 @@ mod tests {
                 },
             ];
             let mut user = User::load(&e, &samwise);
-            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
 
             assert_eq!(actions.check_health, true);
             assert_eq!(
@@ -1611,6 +3864,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
 
         let user_positions = Positions {
@@ -1634,7 +3892,7 @@ mod tests {
             ];
 
             let mut user = User::load(&e, &samwise);
-            let _ = build_actions_from_request(&e, &mut pool, &mut user, requests);
+            let _ = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
             assert_eq!(user.positions.effective_count(), 3)
         });
     }
@@ -1669,6 +3927,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 1,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
 
         let user_positions = Positions {
@@ -1692,7 +3955,7 @@ mod tests {
             ];
 
             let mut user = User::load(&e, &samwise);
-            build_actions_from_request(&e, &mut pool, &mut user, requests);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
         });
     }
 
@@ -1716,6 +3979,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 1,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
 
         let requests = vec![
@@ -1732,7 +4000,52 @@ mod tests {
             let mut pool = Pool::load(&e);
 
             let mut user = User::load(&e, &samwise);
-            build_actions_from_request(&e, &mut pool, &mut user, requests);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1232)")]
+    fn test_exceed_debt_cap() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.debt_cap = 80_0000000; // Set debt cap just above current liabilities
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+
+        let requests = vec![
+            &e,
+            Request {
+                request_type: RequestType::Borrow as u32,
+                address: underlying.clone(),
+                amount: 10_0000000, // Try to borrow past the cap
+            },
+        ];
+
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            let mut pool = Pool::load(&e);
+
+            let mut user = User::load(&e, &samwise);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
         });
     }
 
@@ -1756,6 +4069,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 1,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
 
         let requests = vec![
@@ -1772,7 +4090,7 @@ mod tests {
             let mut pool = Pool::load(&e);
             let mut user = User::load(&e, &samwise);
 
-            build_actions_from_request(&e, &mut pool, &mut user, requests);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
         });
     }
 
@@ -1796,6 +4114,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 1,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
 
         let requests = vec![
@@ -1812,7 +4135,141 @@ mod tests {
             let mut pool = Pool::load(&e);
             let mut user = User::load(&e, &samwise);
 
-            build_actions_from_request(&e, &mut pool, &mut user, requests);
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1224)")]
+    fn test_build_actions_withdraw_before_min_supply_duration() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.min_supply_ledgers = 100;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let mut pool = Pool::load(&e);
+            let mut user = User::load(&e, &samwise);
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 20_0000000,
+                },
+            ];
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+            assert_eq!(storage::get_user_last_supply(&e, &samwise, 0), Some(1234));
+
+            e.ledger().set_sequence_number(1234 + 99);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::WithdrawCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 10_0000000,
+                },
+            ];
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+        });
+    }
+
+    #[test]
+    fn test_build_actions_withdraw_after_min_supply_duration() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.min_supply_ledgers = 100;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let mut pool = Pool::load(&e);
+            let mut user = User::load(&e, &samwise);
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Supply as u32,
+                    address: underlying.clone(),
+                    amount: 20_0000000,
+                },
+            ];
+            build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+
+            e.ledger().set_sequence_number(1234 + 100);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Withdraw as u32,
+                    address: underlying.clone(),
+                    amount: 10_0000000,
+                },
+            ];
+            let actions = build_actions_from_request(&e, &mut pool, &mut user, requests, None);
+            assert_eq!(
+                actions.pool_transfer.get_unchecked(underlying.clone()),
+                10_0000000
+            );
         });
     }
 }
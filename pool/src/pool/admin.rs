@@ -0,0 +1,207 @@
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::{
+    constants::MAX_ADMIN_DELAY,
+    errors::PoolError,
+    storage::{self, PendingAdmin, PendingAdminDelay},
+};
+
+/// Propose a new admin. The proposed admin can accept the admin role once the admin delay
+/// has passed. Replaces any existing proposal.
+///
+/// ### Arguments
+/// * `new_admin` - The proposed admin
+pub fn execute_propose_admin(e: &Env, new_admin: &Address) -> PendingAdmin {
+    let pending_admin = PendingAdmin {
+        new_admin: new_admin.clone(),
+        unlock_time: e.ledger().timestamp() + load_admin_delay(e),
+    };
+    storage::set_pending_admin(e, &Some(pending_admin.clone()));
+    pending_admin
+}
+
+/// Cancel the pending admin transfer
+///
+/// ### Panics
+/// If there is no pending admin transfer
+pub fn execute_cancel_admin_proposal(e: &Env) -> Address {
+    match storage::get_pending_admin(e) {
+        Some(pending_admin) => {
+            storage::set_pending_admin(e, &None);
+            pending_admin.new_admin
+        }
+        None => panic_with_error!(e, PoolError::BadRequest),
+    }
+}
+
+/// Accept the pending admin transfer, making the proposed admin the pool admin
+///
+/// Returns the proposed admin
+///
+/// ### Panics
+/// If there is no pending admin transfer or the admin delay has not passed
+pub fn execute_accept_admin(e: &Env) -> Address {
+    let pending_admin = match storage::get_pending_admin(e) {
+        Some(pending_admin) => pending_admin,
+        None => panic_with_error!(e, PoolError::BadRequest),
+    };
+    if e.ledger().timestamp() < pending_admin.unlock_time {
+        panic_with_error!(e, PoolError::AdminTransferLocked);
+    }
+    storage::set_admin(e, &pending_admin.new_admin);
+    storage::set_pending_admin(e, &None);
+    pending_admin.new_admin
+}
+
+/// Queue a change to the delay before a proposed admin can accept the admin role. The new delay
+/// applies once the current delay has passed, so the delay can't be cut ahead of an admin
+/// transfer. Replaces any pending delay change, and does not affect a pending admin transfer.
+///
+/// Returns the pending delay change
+///
+/// ### Arguments
+/// * `delay` - The delay in seconds
+///
+/// ### Panics
+/// If the delay exceeds the maximum admin delay
+pub fn execute_set_admin_delay(e: &Env, delay: u64) -> PendingAdminDelay {
+    if delay > MAX_ADMIN_DELAY {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    // apply any pending change that has unlocked before it is replaced
+    let current_delay = load_admin_delay(e);
+    storage::set_admin_delay(e, &current_delay);
+    let pending_delay = PendingAdminDelay {
+        delay,
+        unlock_time: e.ledger().timestamp() + current_delay,
+    };
+    storage::set_pending_admin_delay(e, &Some(pending_delay.clone()));
+    pending_delay
+}
+
+/// Load the delay before a proposed admin can accept the admin role, including any pending
+/// delay change that has unlocked
+pub fn load_admin_delay(e: &Env) -> u64 {
+    match storage::get_pending_admin_delay(e) {
+        Some(pending_delay) if e.ledger().timestamp() >= pending_delay.unlock_time => {
+            pending_delay.delay
+        }
+        _ => storage::get_admin_delay(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::DEFAULT_ADMIN_DELAY, testutils};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_propose_and_accept_admin() {
+        let e = Env::default();
+        e.ledger().set_timestamp(1000);
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_admin_delay(&e, &3600);
+
+            let pending_admin = execute_propose_admin(&e, &samwise);
+            assert_eq!(pending_admin.new_admin, samwise);
+            assert_eq!(pending_admin.unlock_time, 4600);
+            assert_eq!(storage::get_admin(&e), bombadil);
+
+            e.ledger().set_timestamp(4600);
+            let new_admin = execute_accept_admin(&e);
+            assert_eq!(new_admin, samwise);
+            assert_eq!(storage::get_admin(&e), samwise);
+            assert!(storage::get_pending_admin(&e).is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1237)")]
+    fn test_accept_admin_before_delay() {
+        let e = Env::default();
+        e.ledger().set_timestamp(1000);
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_admin_delay(&e, &3600);
+            execute_propose_admin(&e, &samwise);
+
+            e.ledger().set_timestamp(4599);
+            execute_accept_admin(&e);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_accept_admin_after_cancel() {
+        let e = Env::default();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            execute_propose_admin(&e, &samwise);
+            assert_eq!(execute_cancel_admin_proposal(&e), samwise);
+
+            execute_accept_admin(&e);
+        });
+    }
+
+    #[test]
+    fn test_set_admin_delay_waits_for_current_delay() {
+        let e = Env::default();
+        e.ledger().set_timestamp(1000);
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            assert_eq!(load_admin_delay(&e), DEFAULT_ADMIN_DELAY);
+
+            let pending_delay = execute_set_admin_delay(&e, 0);
+            assert_eq!(pending_delay.delay, 0);
+            assert_eq!(pending_delay.unlock_time, 1000 + DEFAULT_ADMIN_DELAY);
+
+            // proposals made before the change unlocks use the current delay
+            let pending_admin = execute_propose_admin(&e, &samwise);
+            assert_eq!(pending_admin.unlock_time, 1000 + DEFAULT_ADMIN_DELAY);
+
+            e.ledger().set_timestamp(1000 + DEFAULT_ADMIN_DELAY);
+            assert_eq!(load_admin_delay(&e), 0);
+            let pending_admin = execute_propose_admin(&e, &samwise);
+            assert_eq!(pending_admin.unlock_time, 1000 + DEFAULT_ADMIN_DELAY);
+
+            // a new change is applied over the unlocked change
+            let pending_delay = execute_set_admin_delay(&e, 3600);
+            assert_eq!(storage::get_admin_delay(&e), 0);
+            assert_eq!(pending_delay.unlock_time, 1000 + DEFAULT_ADMIN_DELAY);
+            assert_eq!(load_admin_delay(&e), 3600);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_admin_delay_too_long() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            execute_set_admin_delay(&e, MAX_ADMIN_DELAY + 1);
+        });
+    }
+}
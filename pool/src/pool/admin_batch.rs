@@ -0,0 +1,269 @@
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+use crate::{
+    emissions::{self, ReserveEmissionMetadata},
+    events::PoolEvents,
+    storage::{
+        self, CreditSettlementConfig, EModeCategory, InterestRateConfig, OracleConfig,
+        ReserveConfig,
+    },
+};
+
+use super::{
+    anomaly::{execute_lift_reserve_restriction, execute_set_anomaly_thresholds},
+    config::{
+        execute_cancel_queued_set_reserve, execute_queue_set_reserve, execute_set_fallback_oracle,
+        execute_set_max_account_leverage, execute_set_min_health_factor,
+        execute_set_oracle_config, execute_update_pool,
+    },
+    config_history::record_pool_config,
+    credit_settlement::execute_set_credit_settlement,
+    emode::{execute_cancel_queued_set_emode_category, execute_queue_set_emode_category},
+    pause::execute_unpause_actions,
+    status::execute_set_pool_status,
+};
+
+/// An admin operation that can be executed as part of an admin batch
+#[derive(Clone)]
+#[contracttype]
+pub enum AdminOp {
+    /// (backstop_take_rate, max_positions)
+    UpdatePool(u32, u32),
+    /// (asset, metadata, ir_config)
+    QueueSetReserve(Address, ReserveConfig, Option<InterestRateConfig>),
+    /// (asset)
+    CancelSetReserve(Address),
+    /// (pool_status)
+    SetStatus(u32),
+    /// (res_emission_metadata)
+    SetEmissionsConfig(Vec<ReserveEmissionMetadata>),
+    /// (enabled)
+    SetUtilWeightedEmissions(bool),
+    /// (config)
+    SetOracleConfig(OracleConfig),
+    /// (fallback_oracle)
+    SetFallbackOracle(Option<Address>),
+    /// (min_health_factor)
+    SetMinHealthFactor(u32),
+    /// (guardian)
+    SetPauseGuardian(Option<Address>),
+    /// (actions)
+    UnpauseActions(u32),
+    /// (category, config)
+    QueueSetEModeCategory(u32, EModeCategory),
+    /// (category)
+    CancelSetEModeCategory(u32),
+    /// (asset, max_b_supply_delta, max_d_supply_delta)
+    SetAnomalyThresholds(Address, i128, i128),
+    /// (asset)
+    LiftRestriction(Address),
+    /// (config)
+    SetCreditSettlement(CreditSettlementConfig),
+    /// (max_account_leverage)
+    SetMaxAccountLeverage(u32),
+}
+
+/// Execute a batch of admin operations in order. Each operation behaves the same as the
+/// standalone admin function, including the events it emits.
+///
+/// ### Arguments
+/// * `admin` - The admin of the pool
+/// * `ops` - The admin operations to execute
+///
+/// ### Panics
+/// If any operation fails
+pub fn execute_admin_batch(e: &Env, admin: &Address, ops: Vec<AdminOp>) {
+    for op in ops.iter() {
+        execute_admin_op(e, admin, op);
+    }
+}
+
+/// Execute an admin operation, recording any config history and emitting its event. The
+/// standalone admin functions execute their operation through this as well, so both paths
+/// have the same side effects.
+///
+/// ### Arguments
+/// * `admin` - The admin of the pool
+/// * `op` - The admin operation to execute
+///
+/// ### Panics
+/// If the operation fails
+pub fn execute_admin_op(e: &Env, admin: &Address, op: AdminOp) {
+    match op {
+        AdminOp::UpdatePool(backstop_take_rate, max_positions) => {
+            execute_update_pool(e, backstop_take_rate, max_positions);
+            record_pool_config(e, admin);
+            PoolEvents::update_pool(e, admin.clone(), backstop_take_rate, max_positions);
+        }
+        AdminOp::QueueSetReserve(asset, metadata, ir_config) => {
+            let proposal = execute_queue_set_reserve(e, admin, &asset, &metadata, &ir_config);
+            PoolEvents::queue_set_reserve(e, admin.clone(), asset, proposal);
+        }
+        AdminOp::CancelSetReserve(asset) => {
+            let proposal = execute_cancel_queued_set_reserve(e, &asset);
+            PoolEvents::cancel_set_reserve(e, admin.clone(), asset, proposal.payload_hash);
+        }
+        AdminOp::SetStatus(pool_status) => {
+            execute_set_pool_status(e, pool_status);
+            record_pool_config(e, admin);
+            PoolEvents::set_status_admin(e, admin.clone(), pool_status);
+        }
+        AdminOp::SetEmissionsConfig(res_emission_metadata) => {
+            emissions::set_pool_emissions(e, res_emission_metadata.clone());
+            PoolEvents::set_emissions_config(e, admin.clone(), res_emission_metadata);
+        }
+        AdminOp::SetUtilWeightedEmissions(enabled) => {
+            emissions::set_util_weighted_emissions(e, enabled);
+            PoolEvents::set_util_weighted_emissions(e, admin.clone(), enabled);
+        }
+        AdminOp::SetOracleConfig(config) => {
+            execute_set_oracle_config(e, &config);
+            PoolEvents::set_oracle_config(e, admin.clone(), config);
+        }
+        AdminOp::SetFallbackOracle(fallback_oracle) => {
+            execute_set_fallback_oracle(e, &fallback_oracle);
+            record_pool_config(e, admin);
+            PoolEvents::set_fallback_oracle(e, admin.clone(), fallback_oracle);
+        }
+        AdminOp::SetMinHealthFactor(min_health_factor) => {
+            execute_set_min_health_factor(e, min_health_factor);
+            record_pool_config(e, admin);
+            PoolEvents::set_min_health_factor(e, admin.clone(), min_health_factor);
+        }
+        AdminOp::SetPauseGuardian(guardian) => {
+            storage::set_pause_guardian(e, &guardian);
+            PoolEvents::set_pause_guardian(e, admin.clone(), guardian);
+        }
+        AdminOp::UnpauseActions(actions) => {
+            let paused_actions = execute_unpause_actions(e, actions);
+            PoolEvents::unpause_actions(e, admin.clone(), actions, paused_actions);
+        }
+        AdminOp::QueueSetEModeCategory(category, config) => {
+            let queued = execute_queue_set_emode_category(e, category, &config);
+            PoolEvents::queue_set_emode_category(e, admin.clone(), category, queued);
+        }
+        AdminOp::CancelSetEModeCategory(category) => {
+            let queued = execute_cancel_queued_set_emode_category(e, category);
+            PoolEvents::cancel_set_emode_category(e, admin.clone(), category, queued.config);
+        }
+        AdminOp::SetAnomalyThresholds(asset, max_b_supply_delta, max_d_supply_delta) => {
+            execute_set_anomaly_thresholds(e, &asset, max_b_supply_delta, max_d_supply_delta);
+            PoolEvents::set_anomaly_thresholds(
+                e,
+                admin.clone(),
+                asset,
+                max_b_supply_delta,
+                max_d_supply_delta,
+            );
+        }
+        AdminOp::LiftRestriction(asset) => {
+            execute_lift_reserve_restriction(e, &asset);
+            PoolEvents::lift_restriction(e, admin.clone(), asset);
+        }
+        AdminOp::SetCreditSettlement(config) => {
+            execute_set_credit_settlement(e, &config);
+            PoolEvents::set_credit_settlement(e, admin.clone(), config);
+        }
+        AdminOp::SetMaxAccountLeverage(max_account_leverage) => {
+            execute_set_max_account_leverage(e, max_account_leverage);
+            record_pool_config(e, admin);
+            PoolEvents::set_max_account_leverage(e, admin.clone(), max_account_leverage);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::PAUSE_BORROW, storage::PoolConfig, testutils};
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    #[test]
+    fn test_execute_admin_batch() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let guardian = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_paused_actions(&e, &PAUSE_BORROW);
+
+            let ops = vec![
+                &e,
+                AdminOp::UpdatePool(0_2000000, 6),
+                AdminOp::SetPauseGuardian(Some(guardian.clone())),
+                AdminOp::UnpauseActions(PAUSE_BORROW),
+                AdminOp::SetAnomalyThresholds(underlying.clone(), 0_5000000, 0_2500000),
+                AdminOp::SetOracleConfig(OracleConfig {
+                    max_price_age: 3600,
+                    max_deviation: 0_1000000,
+                }),
+            ];
+            execute_admin_batch(&e, &bombadil, ops);
+
+            let new_pool_config = storage::get_pool_config(&e);
+            assert_eq!(new_pool_config.bstop_rate, 0_2000000);
+            assert_eq!(new_pool_config.max_positions, 6);
+            assert_eq!(storage::get_pause_guardian(&e), Some(guardian.clone()));
+            assert_eq!(storage::get_paused_actions(&e), 0);
+            let anomaly_config = storage::get_res_anomaly_config(&e, &underlying).unwrap();
+            assert_eq!(anomaly_config.max_b_supply_delta, 0_5000000);
+            assert_eq!(anomaly_config.max_d_supply_delta, 0_2500000);
+            let oracle_config = storage::get_oracle_config(&e);
+            assert_eq!(oracle_config.max_price_age, 3600);
+            assert_eq!(oracle_config.max_deviation, 0_1000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_admin_batch_reverts_on_failed_op() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pool_config(&e, &pool_config);
+
+            let ops = vec![
+                &e,
+                AdminOp::UpdatePool(0_2000000, 6),
+                AdminOp::UnpauseActions(0),
+            ];
+            execute_admin_batch(&e, &bombadil, ops);
+        });
+    }
+}
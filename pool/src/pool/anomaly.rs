@@ -0,0 +1,339 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env};
+
+use crate::{
+    constants::SCALAR_7,
+    errors::PoolError,
+    events::PoolEvents,
+    storage::{self, ReserveAnomalyConfig, SupplySnapshot},
+};
+
+use super::Reserve;
+
+/// Set the anomaly detection thresholds for a reserve. Any existing restriction on the
+/// reserve is kept.
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `max_b_supply_delta` - The maximum net decrease in b_supply within a ledger (7 decimals)
+/// * `max_d_supply_delta` - The maximum net increase in d_supply within a ledger (7 decimals)
+///
+/// ### Panics
+/// If the reserve does not exist or a threshold is not positive
+pub fn execute_set_anomaly_thresholds(
+    e: &Env,
+    asset: &Address,
+    max_b_supply_delta: i128,
+    max_d_supply_delta: i128,
+) {
+    if !storage::has_res(e, asset) || max_b_supply_delta <= 0 || max_d_supply_delta <= 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let restricted = storage::get_res_anomaly_config(e, asset)
+        .map(|config| config.restricted)
+        .unwrap_or(false);
+    storage::set_res_anomaly_config(
+        e,
+        asset,
+        &ReserveAnomalyConfig {
+            max_b_supply_delta,
+            max_d_supply_delta,
+            restricted,
+        },
+    );
+}
+
+/// Lift the restriction placed on a reserve after an anomaly was detected
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+///
+/// ### Panics
+/// If the reserve is not restricted
+pub fn execute_lift_reserve_restriction(e: &Env, asset: &Address) {
+    match storage::get_res_anomaly_config(e, asset) {
+        Some(mut config) if config.restricted => {
+            config.restricted = false;
+            storage::set_res_anomaly_config(e, asset, &config);
+        }
+        _ => panic_with_error!(e, PoolError::BadRequest),
+    }
+}
+
+/// Check if the reserve is restricted from new borrows
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+pub fn is_reserve_restricted(e: &Env, asset: &Address) -> bool {
+    storage::get_res_anomaly_config(e, asset).is_some_and(|config| config.restricted)
+}
+
+/// Check the net outflow from the reserve within the current ledger against the reserve's anomaly
+/// thresholds. If either threshold is exceeded, the reserve is restricted from new borrows pending
+/// admin review.
+///
+/// Only net outflows are measured: a net decrease in b_supply (withdrawals) or a net increase in
+/// d_supply (borrows) since the start of the ledger. Supplies and repayments can't trip the
+/// restriction, and offset withdrawals and borrows made in the same ledger.
+///
+/// Must be called before the updated reserve is written to the ledger.
+///
+/// ### Arguments
+/// * `reserve` - The updated reserve
+pub fn check_supply_anomaly(e: &Env, reserve: &Reserve) {
+    let mut config = match storage::get_res_anomaly_config(e, &reserve.asset) {
+        Some(config) => config,
+        None => return,
+    };
+    let snapshot = load_supply_snapshot(e, reserve);
+    if config.restricted {
+        return;
+    }
+
+    let (b_supply_outflow, d_supply_outflow) = calc_net_outflow(reserve, &snapshot);
+    if is_anomalous(&config, &snapshot, b_supply_outflow, d_supply_outflow) {
+        config.restricted = true;
+        storage::set_res_anomaly_config(e, &reserve.asset, &config);
+        PoolEvents::reserve_anomaly(e, reserve.asset.clone(), b_supply_outflow, d_supply_outflow);
+    }
+}
+
+/// Require a flash loan to not push the net outflow from the reserve within the current ledger
+/// past the reserve's anomaly thresholds. Flash loans are checked before the borrowed tokens leave
+/// the pool, as the loan may be repaid before the reserve is stored.
+///
+/// ### Arguments
+/// * `reserve` - The reserve with the flash loan liabilities added
+///
+/// ### Panics
+/// If the reserve is restricted or the flash loan exceeds an anomaly threshold
+#[cfg(feature = "flash-loans")]
+pub fn require_flash_loan_below_anomaly(e: &Env, reserve: &Reserve) {
+    let config = match storage::get_res_anomaly_config(e, &reserve.asset) {
+        Some(config) => config,
+        None => return,
+    };
+    let snapshot = load_supply_snapshot(e, reserve);
+    let (b_supply_outflow, d_supply_outflow) = calc_net_outflow(reserve, &snapshot);
+    if config.restricted || is_anomalous(&config, &snapshot, b_supply_outflow, d_supply_outflow) {
+        panic_with_error!(e, PoolError::ReserveRestricted);
+    }
+}
+
+/// Load the supply of the reserve at the start of the current ledger, recording it on the first
+/// update of the ledger
+fn load_supply_snapshot(e: &Env, reserve: &Reserve) -> SupplySnapshot {
+    let sequence = e.ledger().sequence();
+    match storage::get_supply_snapshot(e, &reserve.asset) {
+        Some(snapshot) if snapshot.ledger == sequence => snapshot,
+        _ => {
+            // the first update this ledger, so the stored supply is the supply at the start of the ledger
+            let reserve_data = storage::get_res_data(e, &reserve.asset);
+            let snapshot = SupplySnapshot {
+                ledger: sequence,
+                b_supply: reserve_data.b_supply,
+                d_supply: reserve_data.d_supply,
+            };
+            storage::set_supply_snapshot(e, &reserve.asset, &snapshot);
+            snapshot
+        }
+    }
+}
+
+/// Calculate the net decrease in b_supply and net increase in d_supply since the snapshot
+fn calc_net_outflow(reserve: &Reserve, snapshot: &SupplySnapshot) -> (i128, i128) {
+    (
+        (snapshot.b_supply - reserve.b_supply).max(0),
+        (reserve.d_supply - snapshot.d_supply).max(0),
+    )
+}
+
+fn is_anomalous(
+    config: &ReserveAnomalyConfig,
+    snapshot: &SupplySnapshot,
+    b_supply_outflow: i128,
+    d_supply_outflow: i128,
+) -> bool {
+    is_over_threshold(b_supply_outflow, snapshot.b_supply, config.max_b_supply_delta)
+        || is_over_threshold(d_supply_outflow, snapshot.d_supply, config.max_d_supply_delta)
+}
+
+fn is_over_threshold(outflow: i128, supply: i128, max_delta: i128) -> bool {
+    supply > 0
+        && outflow
+            > supply
+                .fixed_mul_floor(max_delta, SCALAR_7)
+                .unwrap_optimized()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool::actions::RequestType, storage::PoolConfig, testutils};
+    use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+
+    #[test]
+    fn test_check_supply_anomaly() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 0,
+            protocol_version: 22,
+            sequence_number: 100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            execute_set_anomaly_thresholds(&e, &underlying, 0_5000000, 0_1000000);
+
+            // supplies and repayments never trip the restriction
+            let mut reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.b_supply += 100_0000000;
+            reserve.d_supply -= 10_0000000;
+            reserve.store(&e);
+            assert!(!is_reserve_restricted(&e, &underlying));
+
+            // withdrawals are netted against supplies made in the same ledger
+            let mut reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.b_supply -= 140_0000000;
+            reserve.d_supply += 10_0000000;
+            reserve.store(&e);
+            assert!(!is_reserve_restricted(&e, &underlying));
+
+            // d_supply of 75 can increase by 7.5 within the ledger
+            let mut reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.d_supply += 5_0000000;
+            reserve.store(&e);
+            assert!(!is_reserve_restricted(&e, &underlying));
+
+            let mut reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.d_supply += 2_5000001;
+            reserve.store(&e);
+            assert!(is_reserve_restricted(&e, &underlying));
+
+            // the delta is measured from the start of each ledger
+            execute_lift_reserve_restriction(&e, &underlying);
+            e.ledger().set_sequence_number(101);
+            let mut reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.d_supply += 7_0000000;
+            reserve.store(&e);
+            assert!(!is_reserve_restricted(&e, &underlying));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1227)")]
+    fn test_restricted_reserve_blocks_borrow() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            execute_set_anomaly_thresholds(&e, &underlying, 0_5000000, 0_1000000);
+
+            let mut reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.b_supply -= 50_0000001;
+            reserve.store(&e);
+
+            // repays and withdrawals are still allowed
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.require_action_allowed(&e, RequestType::Repay as u32);
+            reserve.require_action_allowed(&e, RequestType::Withdraw as u32);
+            reserve.require_action_allowed(&e, RequestType::Borrow as u32);
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "flash-loans")]
+    #[should_panic(expected = "Error(Contract, #1227)")]
+    fn test_flash_loan_over_anomaly_threshold() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            execute_set_anomaly_thresholds(&e, &underlying, 0_5000000, 0_1000000);
+
+            let mut reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.d_supply += 7_5000000;
+            require_flash_loan_below_anomaly(&e, &reserve);
+
+            // rejected even if the loan would be repaid before the reserve is stored
+            reserve.d_supply += 1;
+            require_flash_loan_below_anomaly(&e, &reserve);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_lift_reserve_restriction_not_restricted() {
+        let e = Env::default();
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            execute_set_anomaly_thresholds(&e, &underlying, 0_5000000, 0_1000000);
+            execute_lift_reserve_restriction(&e, &underlying);
+        });
+    }
+}
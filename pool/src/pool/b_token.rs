@@ -0,0 +1,246 @@
+use soroban_sdk::{panic_with_error, Address, Bytes, Env, String};
+
+use crate::{
+    dependencies::BackstopClient, errors::PoolError, storage, validator::require_nonnegative,
+};
+
+use super::{Pool, User};
+
+/// Deploy a bToken contract for a reserve, which exposes the non-collateral supply of the
+/// reserve as a transferable SEP-41 token. The bToken is deployed from the wasm hash set by
+/// governance on the backstop.
+///
+/// Returns the address of the bToken contract
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `name` - The name of the bToken
+/// * `symbol` - The symbol of the bToken
+///
+/// ### Panics
+/// If the reserve does not exist, already has a bToken, or governance has not set the bToken
+/// wasm hash
+pub fn execute_deploy_b_token(
+    e: &Env,
+    asset: &Address,
+    name: &String,
+    symbol: &String,
+) -> Address {
+    if !storage::has_res(e, asset) || storage::get_res_b_token(e, asset).is_some() {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let wasm_hash = match BackstopClient::new(e, &storage::get_backstop(e)).b_token_wasm() {
+        Some(wasm_hash) => wasm_hash,
+        None => panic_with_error!(e, PoolError::BadRequest),
+    };
+
+    let mut as_u8s: [u8; 56] = [0; 56];
+    asset.to_string().copy_into_slice(&mut as_u8s);
+    let salt = e.crypto().keccak256(&Bytes::from_array(e, &as_u8s));
+    let b_token = e.deployer().with_current_contract(salt).deploy_v2(
+        wasm_hash,
+        (
+            e.current_contract_address(),
+            asset.clone(),
+            name.clone(),
+            symbol.clone(),
+        ),
+    );
+    storage::set_res_b_token(e, asset, &b_token);
+    b_token
+}
+
+/// Transfer non-collateral supply of a reserve between users on behalf of the reserve's bToken
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `from` - The address sending the bTokens
+/// * `to` - The address receiving the bTokens
+/// * `amount` - The amount of bTokens to transfer
+///
+/// ### Panics
+/// If the reserve has no bToken, the bToken did not authorize the transfer, "from" does not
+/// hold enough supply or is still supply locked, or "to" would exceed the max positions
+pub fn execute_b_token_transfer(
+    e: &Env,
+    asset: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+) {
+    let b_token = match storage::get_res_b_token(e, asset) {
+        Some(b_token) => b_token,
+        None => panic_with_error!(e, PoolError::BadRequest),
+    };
+    b_token.require_auth();
+    require_nonnegative(e, &amount);
+    if from == to || to == &e.current_contract_address() {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    if amount == 0 {
+        return;
+    }
+
+    let mut pool = Pool::load(e);
+    let mut reserve = pool.load_reserve(e, asset, true);
+    reserve.require_supply_unlocked(e, from);
+    let mut from_state = User::load(e, from);
+    let mut to_state = User::load(e, to);
+    let prev_positions_count = to_state.positions.effective_count();
+    from_state.remove_supply(e, &mut reserve, amount);
+    to_state.add_supply(e, &mut reserve, amount);
+    pool.require_under_max(e, &to_state.positions, prev_positions_count);
+    pool.cache_reserve(reserve);
+
+    pool.store_cached_reserves(e);
+    from_state.store(e);
+    to_state.store(e);
+}
+
+/// Fetch the amount of bTokens a user holds as non-collateral supply of a reserve
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `id` - The address of the user
+pub fn load_b_token_balance(e: &Env, asset: &Address, id: &Address) -> i128 {
+    let reserve_config = storage::get_res_config(e, asset);
+    storage::get_user_positions(e, id)
+        .supply
+        .get(reserve_config.index)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool::Positions, testutils};
+    use soroban_sdk::{map, testutils::Address as _};
+
+    #[test]
+    fn test_b_token_transfer() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let b_token = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e, (0, 10_0000000)],
+            supply: map![&e, (0, 20_0000000)],
+        };
+        e.as_contract(&pool, || {
+            storage::set_res_b_token(&e, &underlying, &b_token);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+
+            execute_b_token_transfer(&e, &underlying, &samwise, &frodo, 5_0000000);
+
+            assert_eq!(load_b_token_balance(&e, &underlying, &samwise), 15_0000000);
+            assert_eq!(load_b_token_balance(&e, &underlying, &frodo), 5_0000000);
+            let samwise_positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(samwise_positions.collateral.get_unchecked(0), 10_0000000);
+            let frodo_positions = storage::get_user_positions(&e, &frodo);
+            assert_eq!(frodo_positions.collateral.len(), 0);
+            assert_eq!(storage::get_res_data(&e, &underlying).b_supply, reserve_data.b_supply);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #8)")]
+    fn test_b_token_transfer_over_balance() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let b_token = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        // collateral can't be transferred as bTokens
+        let user_positions = Positions {
+            liabilities: map![&e],
+            collateral: map![&e, (0, 10_0000000)],
+            supply: map![&e, (0, 20_0000000)],
+        };
+        e.as_contract(&pool, || {
+            storage::set_res_b_token(&e, &underlying, &b_token);
+            storage::set_user_positions(&e, &samwise, &user_positions);
+
+            execute_b_token_transfer(&e, &underlying, &samwise, &frodo, 25_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_b_token_transfer_no_b_token() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            execute_b_token_transfer(&e, &underlying, &samwise, &frodo, 5_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_deploy_b_token_no_reserve() {
+        let e = Env::default();
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+
+        e.as_contract(&pool, || {
+            execute_deploy_b_token(
+                &e,
+                &underlying,
+                &String::from_str(&e, "Blend bToken"),
+                &String::from_str(&e, "bTKN"),
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_deploy_b_token_no_wasm() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+        let (blnd, _) = testutils::create_blnd_token(&e, &pool, &bombadil);
+        let (usdc, _) = testutils::create_token_contract(&e, &bombadil);
+        let (lp_token, _) = testutils::create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        testutils::create_backstop(&e, &pool, &lp_token, &usdc, &blnd);
+
+        e.as_contract(&pool, || {
+            execute_deploy_b_token(
+                &e,
+                &underlying,
+                &String::from_str(&e, "Blend bToken"),
+                &String::from_str(&e, "bTKN"),
+            );
+        });
+    }
+}
@@ -1,6 +1,8 @@
 use soroban_sdk::{panic_with_error, Address, Env};
 
 use crate::{
+    auctions::AuctionType,
+    dependencies::BackstopClient,
     errors::PoolError,
     events::PoolEvents,
     storage::{self},
@@ -13,22 +15,24 @@ use super::{user::User, Pool};
 ///
 /// ### Arguments
 /// * `user` - The user who has bad debt
+/// * `account_id` - The ID of the sub-account of the user that has bad debt
 ///
 /// ### Panics
 /// If the user does not have bad debt
-pub fn transfer_bad_debt_to_backstop(e: &Env, user: &Address) {
+pub fn transfer_bad_debt_to_backstop(e: &Env, user: &Address, account_id: u32) {
     let backstop_address = storage::get_backstop(e);
     if user.clone() == backstop_address {
         panic_with_error!(e, PoolError::BadRequest);
     }
 
-    let user_state = User::load(e, user);
+    let user_state = User::load_account(e, user, account_id);
     if !user_state.positions.collateral.is_empty() || user_state.positions.liabilities.is_empty() {
         panic_with_error!(e, PoolError::BadRequest);
     }
 
     // the user does not have collateral and currently holds a liability meaning they hold bad debt
-    // transfer all of the user's debt to the backstop
+    // transfer all of the user's debt to the backstop. Any isolated debt the liabilities counted
+    // towards was already removed when the user's last collateral was liquidated.
     let mut pool = Pool::load(e);
     let reserve_list = storage::get_res_list(e);
     let backstop_state = User::load(e, &backstop_address);
@@ -49,6 +53,51 @@ pub fn transfer_bad_debt_to_backstop(e: &Env, user: &Address) {
     new_user_state.store(e);
 }
 
+/// Repay the backstop's bad debt in the reserve for the backstop's USDC directly with USDC
+/// exited from the backstop's LP tokens, bypassing the bad debt auction. Returns the amount
+/// of USDC repaid.
+///
+/// ### Arguments
+/// * `max_tokens_in` - The maximum amount of backstop tokens the backstop can burn to exit the USDC
+///
+/// ### Panics
+/// If the pool does not have a USDC reserve, the backstop does not hold bad debt in the USDC
+/// reserve, a bad debt auction is in progress, or the backstop cannot cover the debt
+pub fn execute_recapitalize_bad_debt(e: &Env, max_tokens_in: i128) -> i128 {
+    let backstop_address = storage::get_backstop(e);
+    if storage::has_auction(e, &(AuctionType::BadDebtAuction as u32), &backstop_address) {
+        panic_with_error!(e, PoolError::AuctionInProgress);
+    }
+
+    let backstop_client = BackstopClient::new(e, &backstop_address);
+    let usdc = backstop_client.usdc_token();
+    let mut pool = Pool::load(e);
+    let mut reserve = pool.load_reserve(e, &usdc, true);
+    let mut backstop_state = User::load(e, &backstop_address);
+    let d_tokens = backstop_state.get_liabilities(reserve.index);
+    if d_tokens == 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+
+    // the debt is denominated in USDC, so it is repaid 1:1 without an oracle conversion
+    let amount = reserve.to_asset_from_d_token(d_tokens);
+    backstop_state.remove_liabilities(e, &mut reserve, d_tokens);
+    pool.cache_reserve(reserve);
+
+    backstop_client.draw_usdc(
+        &e.current_contract_address(),
+        &amount,
+        &e.current_contract_address(),
+        &max_tokens_in,
+    );
+
+    pool.store_cached_reserves(e);
+    backstop_state.store(e);
+
+    PoolEvents::recapitalize(e, usdc, amount, d_tokens);
+    amount
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{pool::Positions, storage::PoolConfig, testutils};
@@ -57,6 +106,7 @@ mod tests {
     use soroban_sdk::{
         map,
         testutils::{Address as _, Ledger, LedgerInfo},
+        vec,
     };
 
     /***** transfer_bad_debt_to_backstop ******/
@@ -97,6 +147,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions {
             liabilities: map![&e, (0, 24_0000000), (1, 25_0000000)],
@@ -109,7 +164,7 @@ mod tests {
             storage::set_user_positions(&e, &samwise, &user_positions);
 
             e.cost_estimate().budget().reset_unlimited();
-            transfer_bad_debt_to_backstop(&e, &samwise);
+            transfer_bad_debt_to_backstop(&e, &samwise, 0);
 
             let new_user_positions = storage::get_user_positions(&e, &samwise);
             let new_backstop_positions = storage::get_user_positions(&e, &backstop);
@@ -164,6 +219,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions {
             liabilities: map![&e, (0, 24_0000000), (1, 25_0000000)],
@@ -175,7 +235,7 @@ mod tests {
             storage::set_backstop(&e, &backstop);
             storage::set_user_positions(&e, &samwise, &user_positions);
 
-            transfer_bad_debt_to_backstop(&e, &samwise);
+            transfer_bad_debt_to_backstop(&e, &samwise, 0);
         });
     }
 
@@ -217,6 +277,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions::env_default(&e);
         e.as_contract(&pool, || {
@@ -225,7 +290,7 @@ mod tests {
             storage::set_user_positions(&e, &samwise, &user_positions);
 
             e.cost_estimate().budget().reset_unlimited();
-            transfer_bad_debt_to_backstop(&e, &samwise);
+            transfer_bad_debt_to_backstop(&e, &samwise, 0);
         });
     }
 
@@ -266,6 +331,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         let user_positions = Positions {
             liabilities: map![&e, (0, 24_0000000), (1, 25_0000000)],
@@ -278,7 +348,128 @@ mod tests {
             storage::set_user_positions(&e, &samwise, &user_positions);
 
             e.cost_estimate().budget().reset_unlimited();
-            transfer_bad_debt_to_backstop(&e, &backstop);
+            transfer_bad_debt_to_backstop(&e, &backstop, 0);
+        });
+    }
+
+    /***** execute_recapitalize_bad_debt ******/
+
+    #[test]
+    fn test_recapitalize_bad_debt() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited(); // setup exhausts budget
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 12345,
+            protocol_version: 22,
+            sequence_number: 51,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let pool_address = testutils::create_pool(&e);
+
+        let (blnd, blnd_client) = testutils::create_blnd_token(&e, &pool_address, &bombadil);
+        let (usdc, usdc_client) = testutils::create_token_contract(&e, &bombadil);
+        let (lp_token, lp_token_client) =
+            testutils::create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (backstop_address, backstop_client) =
+            testutils::create_backstop(&e, &pool_address, &lp_token, &usdc, &blnd);
+        // mint lp tokens
+        blnd_client.mint(&samwise, &500_001_0000000);
+        blnd_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        usdc_client.mint(&samwise, &12_501_0000000);
+        usdc_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        lp_token_client.join_pool(
+            &50_000_0000000,
+            &vec![&e, 500_001_0000000, 12_501_0000000],
+            &samwise,
+        );
+        backstop_client.deposit(&samwise, &pool_address, &50_000_0000000);
+        backstop_client.update_tkn_val();
+
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.d_rate = 1_100_000_000_000;
+        reserve_data.last_time = 12345;
+        testutils::create_reserve(&e, &pool_address, &usdc, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let backstop_positions = Positions {
+            liabilities: map![&e, (0, 10_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &backstop_address, &backstop_positions);
+
+            let pre_pool_usdc = usdc_client.balance(&pool_address);
+            let pre_backstop_tokens = backstop_client.pool_data(&pool_address).tokens;
+
+            // 11 USDC is worth 8.8 backstop tokens before slippage and fees
+            let repaid = execute_recapitalize_bad_debt(&e, 12_0000000);
+            assert_eq!(repaid, 11_0000000);
+            assert_eq!(usdc_client.balance(&pool_address), pre_pool_usdc + 11_0000000);
+
+            let new_backstop_positions = storage::get_user_positions(&e, &backstop_address);
+            assert_eq!(new_backstop_positions.liabilities.len(), 0);
+            let new_reserve_data = storage::get_res_data(&e, &usdc);
+            assert_eq!(new_reserve_data.d_supply, reserve_data.d_supply - 10_0000000);
+            assert!(backstop_client.pool_data(&pool_address).tokens < pre_backstop_tokens);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_recapitalize_bad_debt_without_liabilities_panics() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let bombadil = Address::generate(&e);
+
+        let pool_address = testutils::create_pool(&e);
+
+        let (blnd, _) = testutils::create_blnd_token(&e, &pool_address, &bombadil);
+        let (usdc, _) = testutils::create_token_contract(&e, &bombadil);
+        let (lp_token, _) = testutils::create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        testutils::create_backstop(&e, &pool_address, &lp_token, &usdc, &blnd);
+
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool_address, &usdc, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_address, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_recapitalize_bad_debt(&e, 1_0000000);
         });
     }
 }
@@ -1,13 +1,18 @@
 use crate::{
-    constants::{SCALAR_7, SCALAR_9, SECONDS_PER_WEEK},
+    constants::{
+        MIN_HEALTH_FACTOR_CEILING, MIN_HEALTH_FACTOR_FLOOR, SCALAR_12, SCALAR_7, SCALAR_9,
+        SECONDS_PER_WEEK, SECONDS_PER_YEAR,
+    },
+    dependencies::BackstopClient,
     errors::PoolError,
     storage::{
-        self, has_queued_reserve_set, PoolConfig, QueuedReserveInit, ReserveConfig, ReserveData,
+        self, has_queued_reserve_set, InterestRateConfig, OracleConfig, PoolConfig,
+        QueuedReserveInit, RateBounds, ReserveConfig, ReserveData,
     },
 };
-use soroban_sdk::{panic_with_error, Address, Env, String};
+use soroban_sdk::{panic_with_error, xdr::ToXdr, Address, BytesN, Env, String};
 
-use super::pool::Pool;
+use super::{health_factor::HealthStrategy, pool::Pool};
 
 /// Initialize the pool
 ///
@@ -20,6 +25,7 @@ pub fn execute_initialize(
     oracle: &Address,
     bstop_rate: &u32,
     max_positions: &u32,
+    hf_strategy: &u32,
     backstop_address: &Address,
     blnd_id: &Address,
 ) {
@@ -33,6 +39,11 @@ pub fn execute_initialize(
         panic_with_error!(&e, PoolError::InvalidPoolInitArgs);
     }
 
+    // verify the health strategy exists
+    if *hf_strategy > HealthStrategy::PortfolioMargin as u32 {
+        panic_with_error!(&e, PoolError::InvalidPoolInitArgs);
+    }
+
     storage::set_admin(e, admin);
     storage::set_name(e, name);
     storage::set_backstop(e, backstop_address);
@@ -43,6 +54,11 @@ pub fn execute_initialize(
             bstop_rate: *bstop_rate,
             status: 6,
             max_positions: *max_positions,
+            flash_loan_fee: 0,
+            hf_strategy: *hf_strategy,
+            fallback_oracle: None,
+            min_health_factor: MIN_HEALTH_FACTOR_FLOOR,
+            max_account_leverage: 0,
         },
     );
     storage::set_blnd_token(e, blnd_id);
@@ -60,30 +76,256 @@ pub fn execute_update_pool(e: &Env, backstop_take_rate: u32, max_positions: u32)
     storage::set_pool_config(e, &pool_config);
 }
 
+/// Deploy a share token for the pool's backstop, which exposes the backstop shares of the pool
+/// as a transferable SEP-41 token
+///
+/// Returns the address of the share token contract
+///
+/// ### Panics
+/// If the backstop rejects the deployment
+pub fn execute_deploy_backstop_share_token(e: &Env, name: &String, symbol: &String) -> Address {
+    let backstop_client = BackstopClient::new(e, &storage::get_backstop(e));
+    backstop_client.deploy_share_token(&e.current_contract_address(), name, symbol)
+}
+
+/// Fund a reward token from `from` to be emitted to the pool's backstop depositors over
+/// `duration` seconds
+///
+/// ### Panics
+/// If the backstop rejects the reward token or duration
+pub fn execute_fund_backstop_reward(
+    e: &Env,
+    from: &Address,
+    token: &Address,
+    amount: i128,
+    duration: u64,
+) {
+    let backstop_client = BackstopClient::new(e, &storage::get_backstop(e));
+    backstop_client.fund_reward_token(
+        from,
+        &e.current_contract_address(),
+        token,
+        &amount,
+        &duration,
+    );
+}
+
+/// Remove a reward token from the pool's backstop once it can no longer be claimed
+///
+/// ### Panics
+/// If the backstop rejects the removal
+pub fn execute_remove_backstop_reward(e: &Env, token: &Address) {
+    let backstop_client = BackstopClient::new(e, &storage::get_backstop(e));
+    backstop_client.remove_reward_token(&e.current_contract_address(), token);
+}
+
+/// Set the fee charged on flash loans
+///
+/// ### Panics
+/// If the fee is not within [0, 1)
+#[cfg(feature = "flash-loans")]
+pub fn execute_set_flash_loan_fee(e: &Env, flash_loan_fee: u32) {
+    if flash_loan_fee >= SCALAR_7 as u32 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let mut pool_config = storage::get_pool_config(e);
+    pool_config.flash_loan_fee = flash_loan_fee;
+    storage::set_pool_config(e, &pool_config);
+}
+
+/// Set the bounds on the borrow rate of a reserve
+///
+/// ### Panics
+/// If the reserve does not exist, or the min rate is over the max rate
+pub fn execute_set_rate_bounds(e: &Env, asset: &Address, rate_bounds: &Option<RateBounds>) {
+    if !storage::has_res(e, asset) {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    if let Some(bounds) = rate_bounds {
+        if bounds.min_rate > bounds.max_rate {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+    }
+    // accrue and store reserve data to the ledger at the current bounds
+    let mut pool = Pool::load(e);
+    let reserve = pool.load_reserve(e, asset, false);
+    reserve.store(e);
+    storage::set_res_rate_bounds(e, asset, rate_bounds);
+}
+
+/// Set the half-life of the decay of a reserve's rate modifier towards 1 while the reserve is
+/// under its target utilization
+///
+/// ### Panics
+/// If the reserve does not exist, or the half-life is zero or over a year
+pub fn execute_set_ir_mod_half_life(e: &Env, asset: &Address, half_life: &Option<u64>) {
+    if !storage::has_res(e, asset) {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    if let Some(half_life) = half_life {
+        if *half_life == 0 || *half_life > SECONDS_PER_YEAR as u64 {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+    }
+    // accrue and store reserve data to the ledger at the current half-life
+    let mut pool = Pool::load(e);
+    let reserve = pool.load_reserve(e, asset, false);
+    reserve.store(e);
+    storage::set_res_ir_mod_half_life(e, asset, half_life);
+}
+
+/// Set the minimum health factor a user's positions must have after an action
+///
+/// ### Panics
+/// If the minimum health factor is not within [1.0000100, 1.5]
+pub fn execute_set_min_health_factor(e: &Env, min_health_factor: u32) {
+    if !(MIN_HEALTH_FACTOR_FLOOR..=MIN_HEALTH_FACTOR_CEILING).contains(&min_health_factor) {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let mut pool_config = storage::get_pool_config(e);
+    pool_config.min_health_factor = min_health_factor;
+    storage::set_pool_config(e, &pool_config);
+}
+
+/// Set the maximum leverage, as gross exposure over net equity, a user's positions can have
+/// after an action. Applies regardless of the health factor of the positions.
+///
+/// ### Panics
+/// If the maximum leverage is not 0 and is under 1x
+pub fn execute_set_max_account_leverage(e: &Env, max_account_leverage: u32) {
+    if max_account_leverage != 0 && max_account_leverage < SCALAR_7 as u32 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let mut pool_config = storage::get_pool_config(e);
+    pool_config.max_account_leverage = max_account_leverage;
+    storage::set_pool_config(e, &pool_config);
+}
+
+/// Set the health factor under which a submit emits a health warning for the user, so
+/// monitoring services can alert users before their positions can be liquidated
+///
+/// ### Panics
+/// If the threshold is not 0 and is under 1.0
+pub fn execute_set_health_warning_threshold(e: &Env, threshold: u32) {
+    if threshold != 0 && threshold < SCALAR_7 as u32 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_health_warning_threshold(e, &threshold);
+}
+
+/// Set the minimum value, in the oracle's base asset, of a collateral or liability position left
+/// by a request, so users can't leave dust positions that are uneconomical to liquidate
+///
+/// ### Panics
+/// If the value is negative
+pub fn execute_set_min_position_value(e: &Env, value: i128) {
+    if value < 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_min_position_value(e, &value);
+}
+
+/// Set the fallback oracle used when the pool's oracle price is missing or stale. The fallback
+/// oracle can use different decimals than the pool's oracle, as prices from both are normalized
+/// to the same precision.
+///
+/// ### Panics
+/// If the fallback oracle is the pool's oracle
+pub fn execute_set_fallback_oracle(e: &Env, fallback_oracle: &Option<Address>) {
+    let mut pool_config = storage::get_pool_config(e);
+    if fallback_oracle.as_ref() == Some(&pool_config.oracle) {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    pool_config.fallback_oracle = fallback_oracle.clone();
+    storage::set_pool_config(e, &pool_config);
+}
+
+/// Set the bounds on prices loaded from the pool's oracle
+///
+/// ### Panics
+/// If the max price age is not within (0, 24 hours], or the max deviation is over 100%
+pub fn execute_set_oracle_config(e: &Env, config: &OracleConfig) {
+    if config.max_price_age == 0
+        || config.max_price_age > 24 * 60 * 60
+        || config.max_deviation > SCALAR_7 as u32
+    {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_oracle_config(e, config);
+}
+
 /// Execute a queueing a reserve initialization for the pool
-pub fn execute_queue_set_reserve(e: &Env, asset: &Address, metadata: &ReserveConfig) {
+///
+/// Returns the queued proposal
+///
+/// ### Arguments
+/// * `proposer` - The admin queueing the proposal
+/// * `asset` - The underlying asset of the reserve
+/// * `metadata` - The reserve configuration to set
+/// * `ir_config` - The custom interest rate curve, if any
+///
+/// ### Panics
+/// If the reserve is already queued, or the config or interest rate curve is invalid
+pub fn execute_queue_set_reserve(
+    e: &Env,
+    proposer: &Address,
+    asset: &Address,
+    metadata: &ReserveConfig,
+    ir_config: &Option<InterestRateConfig>,
+) -> QueuedReserveInit {
     if has_queued_reserve_set(e, asset) {
         panic_with_error!(&e, PoolError::BadRequest)
     }
     require_valid_reserve_metadata(e, metadata);
+    if let Some(ir_config) = ir_config {
+        require_valid_ir_config(e, ir_config);
+    }
     let mut unlock_time = e.ledger().timestamp();
     // require a timelock if pool status is not setup
     if storage::get_pool_config(e).status != 6 {
         unlock_time += SECONDS_PER_WEEK;
     }
-    storage::set_queued_reserve_set(
-        &e,
-        &QueuedReserveInit {
-            new_config: metadata.clone(),
-            unlock_time,
-        },
-        &asset,
-    );
+    let queued_init = QueuedReserveInit {
+        new_config: metadata.clone(),
+        ir_config: ir_config.clone(),
+        unlock_time,
+        proposer: proposer.clone(),
+        payload_hash: reserve_payload_hash(e, asset, metadata, ir_config),
+    };
+    storage::set_queued_reserve_set(&e, &queued_init, &asset);
+    queued_init
+}
+
+/// Hash the payload of a reserve initialization proposal, so off-chain governance processes can
+/// verify the queued proposal matches the one that was voted on
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `metadata` - The reserve configuration to set
+/// * `ir_config` - The custom interest rate curve, if any
+pub fn reserve_payload_hash(
+    e: &Env,
+    asset: &Address,
+    metadata: &ReserveConfig,
+    ir_config: &Option<InterestRateConfig>,
+) -> BytesN<32> {
+    let payload = (asset.clone(), metadata.clone(), ir_config.clone()).to_xdr(e);
+    e.crypto().sha256(&payload).to_bytes()
 }
 
 /// Execute cancelling a queueing a reserve initialization for the pool
-pub fn execute_cancel_queued_set_reserve(e: &Env, asset: &Address) {
+///
+/// Returns the cancelled proposal
+///
+/// ### Panics
+/// If the reserve is not queued for initialization
+pub fn execute_cancel_queued_set_reserve(e: &Env, asset: &Address) -> QueuedReserveInit {
+    if !has_queued_reserve_set(e, asset) {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let queued_init = storage::get_queued_reserve_set(e, asset);
     storage::del_queued_reserve_set(&e, &asset);
+    queued_init
 }
 
 /// Execute a queued reserve initialization for the pool
@@ -98,11 +340,16 @@ pub fn execute_set_reserve(e: &Env, asset: &Address) -> u32 {
     storage::del_queued_reserve_set(e, asset);
 
     // initialize reserve
-    initialize_reserve(e, asset, &queued_init.new_config)
+    initialize_reserve(e, asset, &queued_init.new_config, &queued_init.ir_config)
 }
 
 /// sets reserve data for the pool
-fn initialize_reserve(e: &Env, asset: &Address, config: &ReserveConfig) -> u32 {
+fn initialize_reserve(
+    e: &Env,
+    asset: &Address,
+    config: &ReserveConfig,
+    ir_config: &Option<InterestRateConfig>,
+) -> u32 {
     let index: u32;
     // if reserve already exists, ensure index and scalar do not change
     if storage::has_res(e, asset) {
@@ -122,20 +369,30 @@ fn initialize_reserve(e: &Env, asset: &Address, config: &ReserveConfig) -> u32 {
             || reserve_config.r_two != config.r_two
             || reserve_config.r_three != config.r_three
             || reserve_config.util != config.util
+            || !is_same_ir_config(&storage::get_res_ir_config(e, asset), ir_config)
         {
             reserve.ir_mod = SCALAR_9;
         }
         reserve.store(e);
+        // setting a removed reserve restores it
+        let mut removed = storage::get_removed_res(e);
+        if let Some(removed_index) = removed.first_index_of(asset) {
+            removed.remove(removed_index);
+            storage::set_removed_res(e, &removed);
+        }
     } else {
         index = storage::push_res_list(e, asset);
         let init_data = ReserveData {
-            b_rate: SCALAR_9,
-            d_rate: SCALAR_9,
+            b_rate: SCALAR_12,
+            d_rate: SCALAR_12,
             ir_mod: SCALAR_9,
             d_supply: 0,
             b_supply: 0,
             last_time: e.ledger().timestamp(),
             backstop_credit: 0,
+            isolated_debt: 0,
+            util_accum: 0,
+            accrued_fees: 0,
         };
         storage::set_res_data(e, asset, &init_data);
     }
@@ -153,13 +410,31 @@ fn initialize_reserve(e: &Env, asset: &Address, config: &ReserveConfig) -> u32 {
         r_three: config.r_three,
         reactivity: config.reactivity,
         collateral_cap: config.collateral_cap,
+        debt_cap: config.debt_cap,
         enabled: config.enabled,
+        min_supply_ledgers: config.min_supply_ledgers,
+        isolated: config.isolated,
+        debt_ceiling: config.debt_ceiling,
+        category: config.category,
     };
     storage::set_res_config(e, asset, &reserve_config);
+    match ir_config {
+        Some(ir_config) => storage::set_res_ir_config(e, asset, ir_config),
+        None => storage::del_res_ir_config(e, asset),
+    }
 
     index
 }
 
+/// Check if two optional interest rate curves are the same
+fn is_same_ir_config(a: &Option<InterestRateConfig>, b: &Option<InterestRateConfig>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.breakpoints == b.breakpoints && a.slopes == b.slopes,
+        (None, None) => true,
+        _ => false,
+    }
+}
+
 #[allow(clippy::zero_prefixed_literal)]
 fn require_valid_reserve_metadata(e: &Env, metadata: &ReserveConfig) {
     const SCALAR_7_U32: u32 = SCALAR_7 as u32;
@@ -172,155 +447,685 @@ fn require_valid_reserve_metadata(e: &Env, metadata: &ReserveConfig) {
         || metadata.r_base < 0_0001000
         || (metadata.r_one > metadata.r_two || metadata.r_two > metadata.r_three)
         || (metadata.reactivity > 0_0001000)
+        || metadata.min_supply_ledgers > 17280 * 7
+        || metadata.debt_ceiling < 0
     {
         panic_with_error!(e, PoolError::InvalidReserveMetadata);
     }
-}
+}
+
+/// Require that a custom interest rate curve has between one and three strictly increasing
+/// breakpoints below 100% utilization, and a non-decreasing slope for each segment
+fn require_valid_ir_config(e: &Env, ir_config: &InterestRateConfig) {
+    let len = ir_config.breakpoints.len();
+    if len == 0 || len > 3 || ir_config.slopes.len() != len + 1 {
+        panic_with_error!(e, PoolError::InvalidReserveMetadata);
+    }
+    let mut prev_breakpoint = 0;
+    for breakpoint in ir_config.breakpoints.iter() {
+        if breakpoint <= prev_breakpoint || breakpoint >= SCALAR_7 as u32 {
+            panic_with_error!(e, PoolError::InvalidReserveMetadata);
+        }
+        prev_breakpoint = breakpoint;
+    }
+    let mut prev_slope = 0;
+    for slope in ir_config.slopes.iter() {
+        if slope < prev_slope {
+            panic_with_error!(e, PoolError::InvalidReserveMetadata);
+        }
+        prev_slope = slope;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::{PoolDataKey, QueuedReserveInit, QueuedReserveInitV1, ReserveConfigV1};
+    use crate::testutils;
+
+    use super::*;
+    use sep_40_oracle::testutils::Asset;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger, LedgerInfo},
+        vec, Symbol,
+    };
+
+    #[test]
+    fn test_execute_initialize() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+
+        let admin = Address::generate(&e);
+        let name = String::from_str(&e, "pool_name");
+        let oracle = Address::generate(&e);
+        let bstop_rate: u32 = 0_1000000;
+        let max_positions = 2;
+        let hf_strategy = HealthStrategy::EMode as u32;
+        let backstop_address = Address::generate(&e);
+        let blnd_id = Address::generate(&e);
+
+        e.as_contract(&pool, || {
+            execute_initialize(
+                &e,
+                &admin,
+                &name,
+                &oracle,
+                &bstop_rate,
+                &max_positions,
+                &hf_strategy,
+                &backstop_address,
+                &blnd_id,
+            );
+
+            assert_eq!(storage::get_admin(&e), admin);
+            let pool_config = storage::get_pool_config(&e);
+            assert_eq!(pool_config.oracle, oracle);
+            assert_eq!(pool_config.bstop_rate, bstop_rate);
+            assert_eq!(pool_config.status, 6);
+            assert_eq!(pool_config.hf_strategy, hf_strategy);
+            assert_eq!(pool_config.min_health_factor, 1_0000100);
+            assert_eq!(storage::get_backstop(&e), backstop_address);
+            assert_eq!(storage::get_blnd_token(&e), blnd_id);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1201)")]
+    fn test_execute_initialize_bad_take_rate() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+
+        let admin = Address::generate(&e);
+        let name = String::from_str(&e, "pool_name");
+        let oracle = Address::generate(&e);
+        let bstop_rate = 1_0000000;
+        let max_positions = 3;
+        let hf_strategy = 0;
+        let backstop_address = Address::generate(&e);
+        let blnd_id = Address::generate(&e);
+
+        e.as_contract(&pool, || {
+            execute_initialize(
+                &e,
+                &admin,
+                &name,
+                &oracle,
+                &bstop_rate,
+                &max_positions,
+                &hf_strategy,
+                &backstop_address,
+                &blnd_id,
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1201)")]
+    fn test_execute_initialize_bad_max_positions() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+
+        let admin = Address::generate(&e);
+        let name = String::from_str(&e, "pool_name");
+        let oracle = Address::generate(&e);
+        let bstop_rate = 0_1000000;
+        let max_positions = 1;
+        let hf_strategy = 0;
+        let backstop_address = Address::generate(&e);
+        let blnd_id = Address::generate(&e);
+
+        e.as_contract(&pool, || {
+            execute_initialize(
+                &e,
+                &admin,
+                &name,
+                &oracle,
+                &bstop_rate,
+                &max_positions,
+                &hf_strategy,
+                &backstop_address,
+                &blnd_id,
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1201)")]
+    fn test_execute_initialize_bad_hf_strategy() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+
+        let admin = Address::generate(&e);
+        let name = String::from_str(&e, "pool_name");
+        let oracle = Address::generate(&e);
+        let bstop_rate = 0_1000000;
+        let max_positions = 4;
+        let hf_strategy = 3;
+        let backstop_address = Address::generate(&e);
+        let blnd_id = Address::generate(&e);
+
+        e.as_contract(&pool, || {
+            execute_initialize(
+                &e,
+                &admin,
+                &name,
+                &oracle,
+                &bstop_rate,
+                &max_positions,
+                &hf_strategy,
+                &backstop_address,
+                &blnd_id,
+            );
+        });
+    }
+
+    #[test]
+    fn test_execute_update_pool() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            // happy path
+            execute_update_pool(&e, 0_2000000, 4u32);
+            let new_pool_config = storage::get_pool_config(&e);
+            assert_eq!(new_pool_config.bstop_rate, 0_2000000);
+            assert_eq!(new_pool_config.oracle, pool_config.oracle);
+            assert_eq!(new_pool_config.status, pool_config.status);
+            assert_eq!(new_pool_config.max_positions, 4u32)
+        });
+    }
+
+    #[test]
+    fn test_execute_fund_backstop_reward() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.cost_estimate().budget().reset_unlimited();
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (blnd, _) = testutils::create_blnd_token(&e, &pool, &bombadil);
+        let (usdc, _) = testutils::create_token_contract(&e, &bombadil);
+        let (lp_token, _) = testutils::create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (backstop, backstop_client) =
+            testutils::create_backstop(&e, &pool, &lp_token, &usdc, &blnd);
+        let (reward, reward_client) = testutils::create_token_contract(&e, &bombadil);
+        reward_client.mint(&bombadil, &1000_0000000);
+
+        e.as_contract(&pool, || {
+            execute_fund_backstop_reward(&e, &bombadil, &reward, 1000_0000000, 30 * 24 * 60 * 60);
+        });
+        assert_eq!(backstop_client.reward_tokens(&pool), vec![&e, reward]);
+        assert_eq!(reward_client.balance(&backstop), 1000_0000000);
+        assert_eq!(reward_client.balance(&bombadil), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_update_pool_validates() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_update_pool(&e, 1_0000000, 4u32);
+        });
+    }
+
+    #[test]
+    fn test_execute_set_flash_loan_fee() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_flash_loan_fee(&e, 0_0009000);
+            let new_pool_config = storage::get_pool_config(&e);
+            assert_eq!(new_pool_config.flash_loan_fee, 0_0009000);
+            assert_eq!(new_pool_config.bstop_rate, pool_config.bstop_rate);
+            assert_eq!(new_pool_config.max_positions, pool_config.max_positions);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_set_flash_loan_fee_validates() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_flash_loan_fee(&e, 1_0000000);
+        });
+    }
+
+    #[test]
+    fn test_execute_set_rate_bounds() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let rate_bounds = RateBounds {
+                min_rate: 0_0200000,
+                max_rate: 1_0000000,
+            };
+            execute_set_rate_bounds(&e, &underlying, &Some(rate_bounds));
+            let stored = storage::get_res_rate_bounds(&e, &underlying).unwrap();
+            assert_eq!(stored.min_rate, 0_0200000);
+            assert_eq!(stored.max_rate, 1_0000000);
+
+            execute_set_rate_bounds(&e, &underlying, &None);
+            assert!(storage::get_res_rate_bounds(&e, &underlying).is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_set_rate_bounds_min_over_max() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            let rate_bounds = RateBounds {
+                min_rate: 0_5000000,
+                max_rate: 0_2000000,
+            };
+            execute_set_rate_bounds(&e, &underlying, &Some(rate_bounds));
+        });
+    }
+
+    #[test]
+    fn test_execute_set_ir_mod_half_life() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_ir_mod_half_life(&e, &underlying, &Some(7 * 24 * 60 * 60));
+            assert_eq!(
+                storage::get_res_ir_mod_half_life(&e, &underlying),
+                Some(7 * 24 * 60 * 60)
+            );
+
+            execute_set_ir_mod_half_life(&e, &underlying, &None);
+            assert!(storage::get_res_ir_mod_half_life(&e, &underlying).is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_set_ir_mod_half_life_zero() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            execute_set_ir_mod_half_life(&e, &underlying, &Some(0));
+        });
+    }
+
+    #[test]
+    fn test_execute_set_min_health_factor() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_min_health_factor(&e, 1_0200000);
+            let new_pool_config = storage::get_pool_config(&e);
+            assert_eq!(new_pool_config.min_health_factor, 1_0200000);
+            assert_eq!(new_pool_config.bstop_rate, pool_config.bstop_rate);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_set_min_health_factor_under_floor() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_min_health_factor(&e, 1_0000099);
+        });
+    }
+
+    #[test]
+    fn test_execute_set_max_account_leverage() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_max_account_leverage(&e, 1_0200000);
+            let new_pool_config = storage::get_pool_config(&e);
+            assert_eq!(new_pool_config.min_health_factor, 1_0200000);
+            assert_eq!(new_pool_config.bstop_rate, pool_config.bstop_rate);
+        });
+    }
 
-#[cfg(test)]
-mod tests {
-    use crate::storage::QueuedReserveInit;
-    use crate::testutils;
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_set_max_account_leverage_under_one() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
 
-    use super::*;
-    use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_max_account_leverage(&e, 0_9999999);
+        });
+    }
 
     #[test]
-    fn test_execute_initialize() {
+    fn test_execute_set_health_warning_threshold() {
         let e = Env::default();
-        e.mock_all_auths();
         let pool = testutils::create_pool(&e);
 
-        let admin = Address::generate(&e);
-        let name = String::from_str(&e, "pool_name");
-        let oracle = Address::generate(&e);
-        let bstop_rate: u32 = 0_1000000;
-        let max_positions = 2;
-        let backstop_address = Address::generate(&e);
-        let blnd_id = Address::generate(&e);
-
         e.as_contract(&pool, || {
-            execute_initialize(
-                &e,
-                &admin,
-                &name,
-                &oracle,
-                &bstop_rate,
-                &max_positions,
-                &backstop_address,
-                &blnd_id,
-            );
+            assert_eq!(storage::get_health_warning_threshold(&e), 0);
 
-            assert_eq!(storage::get_admin(&e), admin);
-            let pool_config = storage::get_pool_config(&e);
-            assert_eq!(pool_config.oracle, oracle);
-            assert_eq!(pool_config.bstop_rate, bstop_rate);
-            assert_eq!(pool_config.status, 6);
-            assert_eq!(storage::get_backstop(&e), backstop_address);
-            assert_eq!(storage::get_blnd_token(&e), blnd_id);
+            execute_set_health_warning_threshold(&e, 1_1000000);
+            assert_eq!(storage::get_health_warning_threshold(&e), 1_1000000);
+
+            execute_set_health_warning_threshold(&e, 0);
+            assert_eq!(storage::get_health_warning_threshold(&e), 0);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1201)")]
-    fn test_execute_initialize_bad_take_rate() {
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_set_health_warning_threshold_under_one() {
         let e = Env::default();
-        e.mock_all_auths();
         let pool = testutils::create_pool(&e);
 
-        let admin = Address::generate(&e);
-        let name = String::from_str(&e, "pool_name");
-        let oracle = Address::generate(&e);
-        let bstop_rate = 1_0000000;
-        let max_positions = 3;
-        let backstop_address = Address::generate(&e);
-        let blnd_id = Address::generate(&e);
-
         e.as_contract(&pool, || {
-            execute_initialize(
-                &e,
-                &admin,
-                &name,
-                &oracle,
-                &bstop_rate,
-                &max_positions,
-                &backstop_address,
-                &blnd_id,
-            );
+            execute_set_health_warning_threshold(&e, 0_9999999);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1201)")]
-    fn test_execute_initialize_bad_max_positions() {
+    fn test_execute_set_min_position_value() {
         let e = Env::default();
-        e.mock_all_auths();
         let pool = testutils::create_pool(&e);
 
-        let admin = Address::generate(&e);
-        let name = String::from_str(&e, "pool_name");
-        let oracle = Address::generate(&e);
-        let bstop_rate = 0_1000000;
-        let max_positions = 1;
-        let backstop_address = Address::generate(&e);
-        let blnd_id = Address::generate(&e);
+        e.as_contract(&pool, || {
+            assert_eq!(storage::get_min_position_value(&e), 0);
+
+            execute_set_min_position_value(&e, 10_0000000);
+            assert_eq!(storage::get_min_position_value(&e), 10_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_set_min_position_value_negative() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
 
         e.as_contract(&pool, || {
-            execute_initialize(
-                &e,
-                &admin,
-                &name,
-                &oracle,
-                &bstop_rate,
-                &max_positions,
-                &backstop_address,
-                &blnd_id,
-            );
+            execute_set_min_position_value(&e, -1);
         });
     }
 
     #[test]
-    fn test_execute_update_pool() {
+    fn test_execute_set_fallback_oracle() {
         let e = Env::default();
         e.mock_all_auths();
         let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        let (fallback_oracle, fallback_oracle_client) = testutils::create_mock_oracle(&e);
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(Address::generate(&e))],
+            &7,
+            &300,
+        );
+        // the fallback oracle can use different decimals than the pool's oracle
+        fallback_oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(Address::generate(&e))],
+            &14,
+            &300,
+        );
 
         let pool_config = PoolConfig {
-            oracle: Address::generate(&e),
+            oracle,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
 
-            // happy path
-            execute_update_pool(&e, 0_2000000, 4u32);
+            execute_set_fallback_oracle(&e, &Some(fallback_oracle.clone()));
             let new_pool_config = storage::get_pool_config(&e);
-            assert_eq!(new_pool_config.bstop_rate, 0_2000000);
+            assert_eq!(new_pool_config.fallback_oracle, Some(fallback_oracle));
             assert_eq!(new_pool_config.oracle, pool_config.oracle);
-            assert_eq!(new_pool_config.status, pool_config.status);
-            assert_eq!(new_pool_config.max_positions, 4u32)
+
+            execute_set_fallback_oracle(&e, &None);
+            assert_eq!(storage::get_pool_config(&e).fallback_oracle, None);
         });
     }
 
     #[test]
     #[should_panic(expected = "Error(Contract, #1200)")]
-    fn test_execute_update_pool_validates() {
+    fn test_execute_set_fallback_oracle_is_pool_oracle() {
         let e = Env::default();
         e.mock_all_auths();
         let pool = testutils::create_pool(&e);
 
+        let (oracle, _) = testutils::create_mock_oracle(&e);
+
         let pool_config = PoolConfig {
-            oracle: Address::generate(&e),
+            oracle: oracle.clone(),
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
 
-            execute_update_pool(&e, 1_0000000, 4u32);
+            execute_set_fallback_oracle(&e, &Some(oracle));
+        });
+    }
+
+    #[test]
+    fn test_execute_set_oracle_config() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            execute_set_oracle_config(
+                &e,
+                &OracleConfig {
+                    max_price_age: 60 * 60,
+                    max_deviation: 0_2000000,
+                },
+            );
+            let config = storage::get_oracle_config(&e);
+            assert_eq!(config.max_price_age, 60 * 60);
+            assert_eq!(config.max_deviation, 0_2000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_set_oracle_config_validates() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            execute_set_oracle_config(
+                &e,
+                &OracleConfig {
+                    max_price_age: 24 * 60 * 60 + 1,
+                    max_deviation: 0_2000000,
+                },
+            );
         });
     }
 
@@ -346,17 +1151,27 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let pool_config = PoolConfig {
             oracle: Address::generate(&e),
             bstop_rate: 0_1000000,
             status: 6,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
-            execute_queue_set_reserve(&e, &asset_id_0, &metadata);
+            execute_queue_set_reserve(&e, &bombadil, &asset_id_0, &metadata, &None);
             let queued_res = storage::get_queued_reserve_set(&e, &asset_id_0);
             let res_config_0 = queued_res.new_config;
             assert_eq!(res_config_0.decimals, metadata.decimals);
@@ -396,17 +1211,27 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let pool_config = PoolConfig {
             oracle: Address::generate(&e),
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
-            execute_queue_set_reserve(&e, &asset_id_0, &metadata);
+            execute_queue_set_reserve(&e, &bombadil, &asset_id_0, &metadata, &None);
             let queued_init = storage::get_queued_reserve_set(&e, &asset_id_0);
             assert_eq!(queued_init.new_config.decimals, metadata.decimals);
             assert_eq!(queued_init.new_config.c_factor, metadata.c_factor);
@@ -423,6 +1248,15 @@ mod tests {
                 queued_init.unlock_time,
                 e.ledger().timestamp() + SECONDS_PER_WEEK
             );
+            assert_eq!(queued_init.proposer, bombadil);
+            assert_eq!(
+                queued_init.payload_hash,
+                reserve_payload_hash(&e, &asset_id_0, &metadata, &None)
+            );
+            assert_ne!(
+                queued_init.payload_hash,
+                reserve_payload_hash(&e, &bombadil, &metadata, &None)
+            );
         });
     }
 
@@ -449,23 +1283,33 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let pool_config = PoolConfig {
             oracle: Address::generate(&e),
             bstop_rate: 0_1000000,
             status: 6,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
-            execute_queue_set_reserve(&e, &asset_id_0, &metadata);
+            execute_queue_set_reserve(&e, &bombadil, &asset_id_0, &metadata, &None);
             let queued_res = storage::get_queued_reserve_set(&e, &asset_id_0);
             let res_config_0 = queued_res.new_config;
             assert_eq!(res_config_0.index, 0);
 
             // try and queue the same reserve
-            execute_queue_set_reserve(&e, &asset_id_0, &metadata);
+            execute_queue_set_reserve(&e, &bombadil, &asset_id_0, &metadata, &None);
         });
     }
 
@@ -491,17 +1335,58 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
+        };
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            execute_queue_set_reserve(&e, &bombadil, &asset_id, &metadata, &None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1202)")]
+    fn test_queue_set_reserve_validates_ir_config() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+        let (asset_id, _) = testutils::create_token_contract(&e, &bombadil);
+
+        let (metadata, _) = testutils::default_reserve_meta();
+        let ir_config = InterestRateConfig {
+            breakpoints: vec![&e, 0_8000000, 0_5000000],
+            slopes: vec![&e, 0_0400000, 0_1000000, 1_0000000],
         };
         let pool_config = PoolConfig {
             oracle: Address::generate(&e),
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
-            execute_queue_set_reserve(&e, &asset_id, &metadata);
+            execute_queue_set_reserve(&e, &bombadil, &asset_id, &metadata, &Some(ir_config));
         });
     }
 
@@ -527,24 +1412,97 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         e.as_contract(&pool, || {
             storage::set_queued_reserve_set(
                 &e,
                 &QueuedReserveInit {
                     new_config: metadata.clone(),
+                    ir_config: None,
                     unlock_time: e.ledger().timestamp(),
+                    proposer: bombadil.clone(),
+                    payload_hash: BytesN::from_array(&e, &[0; 32]),
                 },
                 &asset_id_0,
             );
-            execute_cancel_queued_set_reserve(&e, &asset_id_0);
+            let cancelled = execute_cancel_queued_set_reserve(&e, &asset_id_0);
+            assert_eq!(cancelled.proposer, bombadil);
             let result = storage::has_queued_reserve_set(&e, &asset_id_0);
 
             assert!(!result);
         });
     }
 
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_cancel_queued_reserve_initialization_not_queued() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        let asset_id_0 = Address::generate(&e);
+
+        e.as_contract(&pool, || {
+            execute_cancel_queued_set_reserve(&e, &asset_id_0);
+        });
+    }
+
+    #[test]
+    fn test_execute_set_reserve_migrates_legacy_proposal() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (asset_id_0, _) = testutils::create_token_contract(&e, &bombadil);
+
+        let legacy_metadata = ReserveConfigV1 {
+            index: 0,
+            decimals: 7,
+            c_factor: 0_7500000,
+            l_factor: 0_7500000,
+            util: 0_5000000,
+            max_util: 0_9500000,
+            r_base: 0_0100000,
+            r_one: 0_0500000,
+            r_two: 0_5000000,
+            r_three: 1_5000000,
+            reactivity: 100,
+            collateral_cap: 1000000000000000000,
+            enabled: true,
+        };
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            // a proposal queued before proposals recorded their proposer and payload
+            e.storage().temporary().set(
+                &PoolDataKey::ResInit(asset_id_0.clone()),
+                &QueuedReserveInitV1 {
+                    new_config: legacy_metadata,
+                    unlock_time: e.ledger().timestamp(),
+                },
+            );
+
+            let queued_init = storage::get_queued_reserve_set(&e, &asset_id_0);
+            assert_eq!(queued_init.proposer, bombadil);
+            assert_eq!(queued_init.new_config.debt_cap, i128::MAX);
+            assert_eq!(
+                queued_init.payload_hash,
+                reserve_payload_hash(&e, &asset_id_0, &queued_init.new_config, &None)
+            );
+
+            execute_set_reserve(&e, &asset_id_0);
+            let res_config_0 = storage::get_res_config(&e, &asset_id_0);
+            assert_eq!(res_config_0.c_factor, 0_7500000);
+            assert_eq!(res_config_0.collateral_cap, 1000000000000000000);
+            assert_eq!(res_config_0.debt_cap, i128::MAX);
+            assert_eq!(res_config_0.index, 0);
+        });
+    }
+
     #[test]
     fn test_execute_set_reserve_first_reserve() {
         let e = Env::default();
@@ -567,14 +1525,22 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         e.as_contract(&pool, || {
             storage::set_queued_reserve_set(
                 &e,
                 &QueuedReserveInit {
                     new_config: metadata.clone(),
+                    ir_config: None,
                     unlock_time: e.ledger().timestamp(),
+                    proposer: bombadil.clone(),
+                    payload_hash: BytesN::from_array(&e, &[0; 32]),
                 },
                 &asset_id_0,
             );
@@ -616,13 +1582,19 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         e.as_contract(&pool, || {
             storage::set_queued_reserve_set(
                 &e,
                 &QueuedReserveInit {
                     new_config: metadata.clone(),
+                    ir_config: None,
                     unlock_time: e.ledger().timestamp() + 1,
                 },
                 &asset_id_0,
@@ -677,6 +1649,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -685,7 +1662,10 @@ mod tests {
                 &e,
                 &QueuedReserveInit {
                     new_config: new_metadata.clone(),
+                    ir_config: None,
                     unlock_time: e.ledger().timestamp(),
+                    proposer: bombadil.clone(),
+                    payload_hash: BytesN::from_array(&e, &[0; 32]),
                 },
                 &underlying,
             );
@@ -705,13 +1685,79 @@ mod tests {
 
             // validate interest was accrued
             let res_data = storage::get_res_data(&e, &underlying);
-            assert!(res_data.d_rate > 1_000_000_000);
+            assert!(res_data.d_rate > 1_000_000_000_000);
             assert!(res_data.backstop_credit > 0);
             assert_eq!(res_data.last_time, 10000);
             assert!(res_data.ir_mod != 1_000_000_000);
         });
     }
 
+    #[test]
+    fn test_execute_set_reserve_ir_config() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 500,
+            protocol_version: 22,
+            sequence_number: 100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.ir_mod = 1_100_000_000;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let ir_config = InterestRateConfig {
+            breakpoints: vec![&e, 0_5000000, 0_8000000],
+            slopes: vec![&e, 0_0400000, 0_1000000, 1_0000000],
+        };
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 6,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            // setting a custom curve resets the rate modifier
+            execute_queue_set_reserve(
+                &e,
+                &bombadil,
+                &underlying,
+                &reserve_config,
+                &Some(ir_config.clone()),
+            );
+            execute_set_reserve(&e, &underlying);
+            let res_ir_config = storage::get_res_ir_config(&e, &underlying).unwrap();
+            assert_eq!(res_ir_config.breakpoints, ir_config.breakpoints);
+            assert_eq!(res_ir_config.slopes, ir_config.slopes);
+            assert_eq!(storage::get_res_data(&e, &underlying).ir_mod, 1_000_000_000);
+
+            // removing the custom curve falls back to the default curve
+            let mut res_data = storage::get_res_data(&e, &underlying);
+            res_data.ir_mod = 1_100_000_000;
+            storage::set_res_data(&e, &underlying, &res_data);
+            execute_queue_set_reserve(&e, &bombadil, &underlying, &reserve_config, &None);
+            execute_set_reserve(&e, &underlying);
+            assert!(storage::get_res_ir_config(&e, &underlying).is_none());
+            assert_eq!(storage::get_res_data(&e, &underlying).ir_mod, 1_000_000_000);
+        });
+    }
+
     #[test]
     fn test_execute_set_reserve_update_resets_ir_mod() {
         let e = Env::default();
@@ -754,6 +1800,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -762,7 +1813,10 @@ mod tests {
                 &e,
                 &QueuedReserveInit {
                     new_config: new_metadata.clone(),
+                    ir_config: None,
                     unlock_time: e.ledger().timestamp(),
+                    proposer: bombadil.clone(),
+                    payload_hash: BytesN::from_array(&e, &[0; 32]),
                 },
                 &underlying,
             );
@@ -781,7 +1835,7 @@ mod tests {
             assert_eq!(res_config_updated.index, reserve_config.index);
 
             let res_data = storage::get_res_data(&e, &underlying);
-            assert!(res_data.d_rate > 1_000_000_000);
+            assert!(res_data.d_rate > 1_000_000_000_000);
             assert!(res_data.backstop_credit > 0);
             assert_eq!(res_data.last_time, 10000);
             assert_eq!(res_data.ir_mod, 1_000_000_000);
@@ -824,7 +1878,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 105,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
 
         let pool_config = PoolConfig {
@@ -832,6 +1891,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -840,7 +1904,10 @@ mod tests {
                 &e,
                 &QueuedReserveInit {
                     new_config: new_metadata.clone(),
+                    ir_config: None,
                     unlock_time: e.ledger().timestamp(),
+                    proposer: bombadil.clone(),
+                    payload_hash: BytesN::from_array(&e, &[0; 32]),
                 },
                 &underlying,
             );
@@ -871,12 +1938,17 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         e.as_contract(&pool, || {
-            initialize_reserve(&e, &asset_id_0, &metadata);
+            initialize_reserve(&e, &asset_id_0, &metadata, &None);
 
-            initialize_reserve(&e, &asset_id_1, &metadata);
+            initialize_reserve(&e, &asset_id_1, &metadata, &None);
             let res_config_0 = storage::get_res_config(&e, &asset_id_0);
             let res_config_1 = storage::get_res_config(&e, &asset_id_1);
             assert_eq!(res_config_0.decimals, metadata.decimals);
@@ -911,7 +1983,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
         // no panic
@@ -936,7 +2013,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
@@ -959,7 +2041,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
@@ -982,7 +2069,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
@@ -1005,7 +2097,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
@@ -1028,7 +2125,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
@@ -1051,7 +2153,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
@@ -1074,7 +2181,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
@@ -1097,7 +2209,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 100,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
@@ -1120,7 +2237,12 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 0_0001001,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         require_valid_reserve_metadata(&e, &metadata);
     }
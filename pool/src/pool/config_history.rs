@@ -0,0 +1,176 @@
+use soroban_sdk::{vec, Address, Env, Vec};
+
+use crate::storage::{self, ConfigChange, ConfigHistoryEntry};
+
+/// The maximum number of configuration history entries retained by the pool
+const MAX_CONFIG_HISTORY: u32 = 256;
+
+/// The maximum number of configuration history entries returned by a single page
+const MAX_CONFIG_HISTORY_PAGE: u32 = 50;
+
+/// Record the current pool config in the configuration history
+///
+/// ### Arguments
+/// * `actor` - The address that changed the pool config
+pub fn record_pool_config(e: &Env, actor: &Address) {
+    let pool_config = storage::get_pool_config(e);
+    append_config_history(e, actor, ConfigChange::Pool(pool_config));
+}
+
+/// Record the current config of a reserve in the configuration history
+///
+/// ### Arguments
+/// * `actor` - The address that changed the reserve config
+/// * `asset` - The underlying asset of the reserve
+pub fn record_reserve_config(e: &Env, actor: &Address, asset: &Address) {
+    let reserve_config = storage::get_res_config(e, asset);
+    append_config_history(e, actor, ConfigChange::Reserve(asset.clone(), reserve_config));
+}
+
+/// Load a page of the configuration history, ordered from oldest to newest. Entries older than
+/// the retained history are skipped.
+///
+/// ### Arguments
+/// * `start` - The version of the first entry to load
+/// * `limit` - The maximum number of entries to load
+pub fn load_config_history(e: &Env, start: u32, limit: u32) -> Vec<ConfigHistoryEntry> {
+    let next_version = storage::get_config_version(e);
+    let start = start.max(next_version.saturating_sub(MAX_CONFIG_HISTORY));
+    let end = start
+        .saturating_add(limit.min(MAX_CONFIG_HISTORY_PAGE))
+        .min(next_version);
+    let mut entries = vec![e];
+    for version in start..end {
+        if let Some(entry) = storage::get_config_history_entry(e, version) {
+            entries.push_back(entry);
+        }
+    }
+    entries
+}
+
+/// Append an entry to the configuration history, removing the oldest entry if the history is full
+fn append_config_history(e: &Env, actor: &Address, change: ConfigChange) {
+    let version = storage::get_config_version(e);
+    storage::set_config_history_entry(
+        e,
+        &ConfigHistoryEntry {
+            version,
+            ledger: e.ledger().sequence(),
+            actor: actor.clone(),
+            change,
+        },
+    );
+    if version >= MAX_CONFIG_HISTORY {
+        storage::del_config_history_entry(e, version - MAX_CONFIG_HISTORY);
+    }
+    storage::set_config_version(e, version + 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::PoolConfig, testutils};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    #[test]
+    fn test_record_config_history() {
+        let e = Env::default();
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.ledger().set_sequence_number(100);
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            record_pool_config(&e, &bombadil);
+
+            e.ledger().set_sequence_number(150);
+            record_reserve_config(&e, &bombadil, &underlying);
+
+            assert_eq!(storage::get_config_version(&e), 2);
+            let history = load_config_history(&e, 0, 10);
+            assert_eq!(history.len(), 2);
+
+            let entry = history.get_unchecked(0);
+            assert_eq!(entry.version, 0);
+            assert_eq!(entry.ledger, 100);
+            assert_eq!(entry.actor, bombadil);
+            match entry.change {
+                ConfigChange::Pool(config) => {
+                    assert_eq!(config.bstop_rate, 0_2000000);
+                    assert_eq!(config.max_positions, 4);
+                }
+                _ => panic!("expected pool config"),
+            }
+
+            let entry = history.get_unchecked(1);
+            assert_eq!(entry.version, 1);
+            assert_eq!(entry.ledger, 150);
+            match entry.change {
+                ConfigChange::Reserve(asset, config) => {
+                    assert_eq!(asset, underlying);
+                    assert_eq!(config.c_factor, reserve_config.c_factor);
+                    assert_eq!(config.index, reserve_config.index);
+                }
+                _ => panic!("expected reserve config"),
+            }
+
+            // pages start at the requested version
+            let history = load_config_history(&e, 1, 10);
+            assert_eq!(history.len(), 1);
+            assert_eq!(history.get_unchecked(0).version, 1);
+        });
+    }
+
+    #[test]
+    fn test_config_history_is_bounded() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            for _ in 0..(MAX_CONFIG_HISTORY + 5) {
+                record_pool_config(&e, &bombadil);
+            }
+
+            assert_eq!(storage::get_config_version(&e), MAX_CONFIG_HISTORY + 5);
+            assert!(storage::get_config_history_entry(&e, 4).is_none());
+            assert!(storage::get_config_history_entry(&e, 5).is_some());
+
+            // pruned entries are skipped and pages are limited in size
+            let history = load_config_history(&e, 0, 100);
+            assert_eq!(history.len(), MAX_CONFIG_HISTORY_PAGE);
+            assert_eq!(history.get_unchecked(0).version, 5);
+
+            let history = load_config_history(&e, MAX_CONFIG_HISTORY + 3, 10);
+            assert_eq!(history.len(), 2);
+            assert_eq!(history.get_unchecked(1).version, MAX_CONFIG_HISTORY + 4);
+        });
+    }
+}
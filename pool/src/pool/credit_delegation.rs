@@ -0,0 +1,120 @@
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::{errors::PoolError, storage, validator::require_nonnegative};
+
+/// Set the amount of an asset a delegatee can borrow on behalf of a delegator. The borrowed
+/// liabilities are added to the delegator's positions, and must be supported by the delegator's
+/// collateral.
+///
+/// ### Arguments
+/// * `delegator` - The address whose positions take on the liabilities
+/// * `delegatee` - The address allowed to borrow
+/// * `asset` - The underlying asset that can be borrowed
+/// * `amount` - The amount of the asset that can be borrowed
+///
+/// ### Panics
+/// If the amount is negative, the delegator is the delegatee, or the reserve does not exist
+pub fn execute_delegate_credit(
+    e: &Env,
+    delegator: &Address,
+    delegatee: &Address,
+    asset: &Address,
+    amount: i128,
+) {
+    require_nonnegative(e, &amount);
+    if delegator == delegatee || !storage::has_res(e, asset) {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_credit_delegation(e, delegator, delegatee, asset, amount);
+}
+
+/// Spend a delegatee's credit delegation for a borrow made on behalf of a delegator
+///
+/// ### Arguments
+/// * `delegator` - The address whose positions take on the liabilities
+/// * `delegatee` - The address borrowing
+/// * `asset` - The underlying asset being borrowed
+/// * `amount` - The amount of the asset being borrowed
+///
+/// ### Panics
+/// If the amount exceeds the remaining credit delegation
+pub fn spend_credit_delegation(
+    e: &Env,
+    delegator: &Address,
+    delegatee: &Address,
+    asset: &Address,
+    amount: i128,
+) {
+    let credit = storage::get_credit_delegation(e, delegator, delegatee, asset);
+    if amount > credit {
+        panic_with_error!(e, PoolError::InsufficientCreditDelegation);
+    }
+    storage::set_credit_delegation(e, delegator, delegatee, asset, credit - amount);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_delegate_credit() {
+        let e = Env::default();
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            execute_delegate_credit(&e, &samwise, &frodo, &underlying, 100_0000000);
+            assert_eq!(
+                storage::get_credit_delegation(&e, &samwise, &frodo, &underlying),
+                100_0000000
+            );
+            assert_eq!(storage::get_credit_delegation(&e, &frodo, &samwise, &underlying), 0);
+
+            spend_credit_delegation(&e, &samwise, &frodo, &underlying, 40_0000000);
+            assert_eq!(
+                storage::get_credit_delegation(&e, &samwise, &frodo, &underlying),
+                60_0000000
+            );
+
+            // a new delegation replaces the remaining delegation
+            execute_delegate_credit(&e, &samwise, &frodo, &underlying, 0);
+            assert_eq!(storage::get_credit_delegation(&e, &samwise, &frodo, &underlying), 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_delegate_credit_to_self() {
+        let e = Env::default();
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            execute_delegate_credit(&e, &samwise, &samwise, &underlying, 100_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_delegate_credit_no_reserve() {
+        let e = Env::default();
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            execute_delegate_credit(&e, &samwise, &frodo, &Address::generate(&e), 100_0000000);
+        });
+    }
+}
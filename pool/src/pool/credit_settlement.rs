@@ -0,0 +1,274 @@
+use sep_41_token::TokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env};
+
+use crate::{
+    auctions::AuctionType,
+    dependencies::BackstopClient,
+    errors::PoolError,
+    events::PoolEvents,
+    storage::{self, CreditSettlementConfig},
+};
+
+use super::Pool;
+
+/// Set the schedule for settling small amounts of backstop credit directly into backstop deposits
+///
+/// ### Arguments
+/// * `config` - The backstop credit settlement config
+///
+/// ### Panics
+/// If the interval is zero or the threshold is not positive
+pub fn execute_set_credit_settlement(e: &Env, config: &CreditSettlementConfig) {
+    if config.interval == 0 || config.threshold <= 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_credit_settlement_config(e, config);
+}
+
+/// Settle the backstop credit of a reserve by donating it to the backstop, which joins the
+/// backstop token LP with the credit. Only the backstop token's underlying assets, BLND and
+/// USDC, can be settled, and only when the value of the credit is under the settlement threshold.
+/// Larger amounts of credit are settled through interest auctions.
+///
+/// Returns the amount of backstop credit settled
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `min_tokens_out` - The minimum amount of backstop tokens the backstop must mint with the
+///                      credit
+///
+/// ### Panics
+/// If settlements are not configured, the settlement interval has not passed since the last
+/// settlement, an interest auction is in progress, the reserve has no backstop credit, the value
+/// of the credit exceeds the threshold, or the asset is not BLND or USDC
+pub fn execute_settle_backstop_credit(e: &Env, asset: &Address, min_tokens_out: i128) -> i128 {
+    let config = match storage::get_credit_settlement_config(e) {
+        Some(config) => config,
+        None => panic_with_error!(e, PoolError::BadRequest),
+    };
+    let timestamp = e.ledger().timestamp();
+    if timestamp < storage::get_last_credit_settlement(e) + config.interval {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    // an interest auction's lot is drawn from the backstop credit when it is filled
+    let backstop_address = storage::get_backstop(e);
+    if storage::has_auction(e, &(AuctionType::InterestAuction as u32), &backstop_address) {
+        panic_with_error!(e, PoolError::AuctionInProgress);
+    }
+
+    let mut pool = Pool::load(e);
+    let mut reserve = pool.load_reserve(e, asset, true);
+    let amount = reserve.backstop_credit;
+    if amount <= 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let value = pool
+        .load_price(e, asset)
+        .fixed_mul_floor(amount, reserve.scalar)
+        .unwrap_optimized();
+    if value > config.threshold {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    reserve.backstop_credit = 0;
+    pool.cache_reserve(reserve);
+    pool.store_cached_reserves(e);
+    storage::set_last_credit_settlement(e, timestamp);
+
+    TokenClient::new(e, asset).approve(
+        &e.current_contract_address(),
+        &backstop_address,
+        &amount,
+        &e.ledger().sequence(),
+    );
+    let tokens_out = BackstopClient::new(e, &backstop_address).donate_underlying(
+        &e.current_contract_address(),
+        asset,
+        &amount,
+        &min_tokens_out,
+    );
+
+    PoolEvents::settle_backstop_credit(e, asset.clone(), amount, tokens_out);
+    amount
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils;
+    use sep_41_token::testutils::MockTokenClient;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        vec,
+    };
+
+    #[test]
+    fn test_settle_backstop_credit() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited(); // setup exhausts budget
+
+        e.ledger().set_timestamp(12345);
+        e.ledger().set_sequence_number(51);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 12345;
+        reserve_data.backstop_credit = 50_0000000;
+        let (pool_address, assets) =
+            testutils::create_pool_with_reserves(&e, &[(reserve_config, reserve_data, 1_0000000)]);
+        let usdc = assets.get_unchecked(0);
+        let usdc_client = MockTokenClient::new(&e, &usdc);
+
+        let (blnd, blnd_client) = testutils::create_blnd_token(&e, &pool_address, &bombadil);
+        let (lp_token, lp_token_client) =
+            testutils::create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (_, backstop_client) =
+            testutils::create_backstop(&e, &pool_address, &lp_token, &usdc, &blnd);
+        // mint lp tokens
+        blnd_client.mint(&samwise, &500_001_0000000);
+        blnd_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        usdc_client.mint(&samwise, &12_501_0000000);
+        usdc_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        lp_token_client.join_pool(
+            &50_000_0000000,
+            &vec![&e, 500_001_0000000, 12_501_0000000],
+            &samwise,
+        );
+        backstop_client.deposit(&samwise, &pool_address, &50_000_0000000);
+        backstop_client.update_tkn_val();
+
+        e.as_contract(&pool_address, || {
+            execute_set_credit_settlement(
+                &e,
+                &CreditSettlementConfig {
+                    interval: 3600,
+                    threshold: 200_0000000_0000000,
+                },
+            );
+        });
+        let pre_backstop_tokens = backstop_client.pool_data(&pool_address).tokens;
+        let pre_pool_usdc = usdc_client.balance(&pool_address);
+
+        e.as_contract(&pool_address, || {
+            let settled = execute_settle_backstop_credit(&e, &usdc, 30_0000000);
+            assert_eq!(settled, 50_0000000);
+            assert_eq!(storage::get_res_data(&e, &usdc).backstop_credit, 0);
+            assert_eq!(storage::get_last_credit_settlement(&e), 12345);
+        });
+        assert_eq!(usdc_client.balance(&pool_address), pre_pool_usdc - 50_0000000);
+
+        // 50 USDC is worth 40 backstop tokens before slippage and fees
+        let backstop_tokens = backstop_client.pool_data(&pool_address).tokens;
+        assert!(backstop_tokens > pre_backstop_tokens + 30_0000000);
+        assert!(backstop_tokens < pre_backstop_tokens + 40_0000000);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_settle_backstop_credit_before_interval() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited(); // setup exhausts budget
+
+        e.ledger().set_timestamp(12345);
+        e.ledger().set_sequence_number(51);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 12345;
+        reserve_data.backstop_credit = 50_0000000;
+        let (pool_address, assets) =
+            testutils::create_pool_with_reserves(&e, &[(reserve_config, reserve_data, 1_0000000)]);
+        let usdc = assets.get_unchecked(0);
+        let usdc_client = MockTokenClient::new(&e, &usdc);
+
+        let (blnd, blnd_client) = testutils::create_blnd_token(&e, &pool_address, &bombadil);
+        let (lp_token, lp_token_client) =
+            testutils::create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (_, backstop_client) =
+            testutils::create_backstop(&e, &pool_address, &lp_token, &usdc, &blnd);
+        // mint lp tokens
+        blnd_client.mint(&samwise, &500_001_0000000);
+        blnd_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        usdc_client.mint(&samwise, &12_501_0000000);
+        usdc_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        lp_token_client.join_pool(
+            &50_000_0000000,
+            &vec![&e, 500_001_0000000, 12_501_0000000],
+            &samwise,
+        );
+        backstop_client.deposit(&samwise, &pool_address, &50_000_0000000);
+        backstop_client.update_tkn_val();
+
+        e.as_contract(&pool_address, || {
+            execute_set_credit_settlement(
+                &e,
+                &CreditSettlementConfig {
+                    interval: 3600,
+                    threshold: 200_0000000_0000000,
+                },
+            );
+        });
+
+        e.as_contract(&pool_address, || {
+            storage::set_last_credit_settlement(&e, 12345 - 3599);
+            execute_settle_backstop_credit(&e, &usdc, 30_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_settle_backstop_credit_over_threshold() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.cost_estimate().budget().reset_unlimited(); // setup exhausts budget
+
+        e.ledger().set_timestamp(12345);
+        e.ledger().set_sequence_number(51);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 12345;
+        reserve_data.backstop_credit = 200_0000001;
+        let (pool_address, assets) =
+            testutils::create_pool_with_reserves(&e, &[(reserve_config, reserve_data, 1_0000000)]);
+        let usdc = assets.get_unchecked(0);
+        let usdc_client = MockTokenClient::new(&e, &usdc);
+
+        let (blnd, blnd_client) = testutils::create_blnd_token(&e, &pool_address, &bombadil);
+        let (lp_token, lp_token_client) =
+            testutils::create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (_, backstop_client) =
+            testutils::create_backstop(&e, &pool_address, &lp_token, &usdc, &blnd);
+        // mint lp tokens
+        blnd_client.mint(&samwise, &500_001_0000000);
+        blnd_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        usdc_client.mint(&samwise, &12_501_0000000);
+        usdc_client.approve(&samwise, &lp_token, &i128::MAX, &99999);
+        lp_token_client.join_pool(
+            &50_000_0000000,
+            &vec![&e, 500_001_0000000, 12_501_0000000],
+            &samwise,
+        );
+        backstop_client.deposit(&samwise, &pool_address, &50_000_0000000);
+        backstop_client.update_tkn_val();
+
+        e.as_contract(&pool_address, || {
+            execute_set_credit_settlement(
+                &e,
+                &CreditSettlementConfig {
+                    interval: 3600,
+                    threshold: 200_0000000_0000000,
+                },
+            );
+        });
+
+        e.as_contract(&pool_address, || {
+            execute_settle_backstop_credit(&e, &usdc, 30_0000000);
+        });
+    }
+}
@@ -0,0 +1,415 @@
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::{
+    constants::{MAX_EMODE_BOOST, SCALAR_7, SECONDS_PER_WEEK},
+    errors::PoolError,
+    storage::{self, EModeCategory, QueuedEModeCategory},
+};
+
+use super::{
+    fixed_rate::settle_fixed_loans,
+    health_factor::{HealthStrategy, PositionData},
+    Pool, User,
+};
+
+/// Queue a change to the collateral and liability factors of an e-mode category. Changes are
+/// timelocked like reserve changes, unless the pool is being set up.
+///
+/// Returns the queued change
+///
+/// ### Arguments
+/// * `category` - The e-mode category
+/// * `config` - The config of the e-mode category
+///
+/// ### Panics
+/// If a change is already queued for the category, or the config is invalid
+pub fn execute_queue_set_emode_category(
+    e: &Env,
+    category: u32,
+    config: &EModeCategory,
+) -> QueuedEModeCategory {
+    if storage::get_queued_emode_category(e, category).is_some() {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    require_valid_emode_category(e, category, config);
+    let mut unlock_time = e.ledger().timestamp();
+    // require a timelock if pool status is not setup
+    if storage::get_pool_config(e).status != 6 {
+        unlock_time += SECONDS_PER_WEEK;
+    }
+    let queued = QueuedEModeCategory {
+        config: config.clone(),
+        unlock_time,
+    };
+    storage::set_queued_emode_category(e, category, &queued);
+    queued
+}
+
+/// Cancel a queued change to an e-mode category
+///
+/// Returns the cancelled change
+///
+/// ### Arguments
+/// * `category` - The e-mode category
+///
+/// ### Panics
+/// If no change is queued for the category
+pub fn execute_cancel_queued_set_emode_category(e: &Env, category: u32) -> QueuedEModeCategory {
+    let queued = match storage::get_queued_emode_category(e, category) {
+        Some(queued) => queued,
+        None => panic_with_error!(e, PoolError::BadRequest),
+    };
+    storage::del_queued_emode_category(e, category);
+    queued
+}
+
+/// Execute a queued change to an e-mode category
+///
+/// Returns the new config of the category
+///
+/// ### Arguments
+/// * `category` - The e-mode category
+///
+/// ### Panics
+/// If no change is queued for the category, the change is still locked, or the config is no
+/// longer valid for the reserves in the category
+pub fn execute_set_emode_category(e: &Env, category: u32) -> EModeCategory {
+    let queued = match storage::get_queued_emode_category(e, category) {
+        Some(queued) => queued,
+        None => panic_with_error!(e, PoolError::BadRequest),
+    };
+    if queued.unlock_time > e.ledger().timestamp() {
+        panic_with_error!(e, PoolError::InitNotUnlocked);
+    }
+    // the reserves in the category may have changed since the config was queued
+    require_valid_emode_category(e, category, &queued.config);
+    storage::del_queued_emode_category(e, category);
+    storage::set_emode_category(e, category, &queued.config);
+    queued.config
+}
+
+/// Require that an e-mode category config is valid. The category's factors must be at least the
+/// factors of every reserve in the category, so entering e-mode never lowers a user's health
+/// factor, and at most `MAX_EMODE_BOOST` above them.
+///
+/// ### Panics
+/// If the category is 0 or the factors are invalid
+fn require_valid_emode_category(e: &Env, category: u32, config: &EModeCategory) {
+    if category == 0
+        || config.c_factor > SCALAR_7 as u32
+        || config.l_factor > SCALAR_7 as u32
+        || config.l_factor == 0
+    {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    for asset in storage::get_res_list(e).iter() {
+        let reserve_config = storage::get_res_config(e, &asset);
+        if reserve_config.category != category {
+            continue;
+        }
+        if config.c_factor < reserve_config.c_factor
+            || config.c_factor > reserve_config.c_factor + MAX_EMODE_BOOST
+            || config.l_factor < reserve_config.l_factor
+            || config.l_factor > reserve_config.l_factor + MAX_EMODE_BOOST
+        {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+    }
+}
+
+/// Set the e-mode category of a user. The category's factors only apply while all of the user's
+/// collateral and liabilities are in reserves of the category.
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `category` - The e-mode category to enter, or 0 to leave e-mode
+///
+/// ### Panics
+/// If the pool does not use the e-mode health strategy, the category does not exist, or the
+/// user's health factor is too low after the change
+pub fn execute_set_user_emode(e: &Env, user: &Address, category: u32) {
+    let mut pool = Pool::load(e);
+    if category != 0
+        && (HealthStrategy::from_u32(e, pool.config.hf_strategy) != HealthStrategy::EMode
+            || storage::get_emode_category(e, category).is_none())
+    {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_user_emode(e, user, category);
+
+    // panics if the new category does not meet the health factor requirement
+    let mut user_state = User::load(e, user);
+    if settle_fixed_loans(e, &mut pool, &mut user_state) {
+        pool.store_cached_reserves(e);
+        user_state.store(e);
+    }
+    if user_state.has_liabilities()
+        && PositionData::calculate_from_positions(e, &mut pool, &user_state.positions, category)
+            .is_hf_under(pool.min_health_factor())
+    {
+        panic_with_error!(e, PoolError::InvalidHf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool::Positions, testutils};
+    use soroban_sdk::{
+        map,
+        testutils::{Address as _, Ledger},
+    };
+
+    #[test]
+    fn test_set_emode_category_timelocked() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, _) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            let mut pool_config = storage::get_pool_config(&e);
+            pool_config.hf_strategy = HealthStrategy::EMode as u32;
+            storage::set_pool_config(&e, &pool_config);
+            let category = EModeCategory {
+                c_factor: 0_9500000,
+                l_factor: 0_9500000,
+            };
+            storage::set_emode_category(&e, 1, &category);
+        });
+
+        let config = EModeCategory {
+            c_factor: 0_9000000,
+            l_factor: 0_9000000,
+        };
+        e.as_contract(&pool, || {
+            let queued = execute_queue_set_emode_category(&e, 1, &config);
+            assert_eq!(queued.unlock_time, SECONDS_PER_WEEK);
+            // the current config is unchanged while the change is queued
+            assert_eq!(storage::get_emode_category(&e, 1).unwrap().c_factor, 0_9500000);
+
+            e.ledger().set_timestamp(SECONDS_PER_WEEK);
+            let new_config = execute_set_emode_category(&e, 1);
+            assert_eq!(new_config.c_factor, 0_9000000);
+            assert_eq!(storage::get_emode_category(&e, 1).unwrap().l_factor, 0_9000000);
+            assert!(storage::get_queued_emode_category(&e, 1).is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1203)")]
+    fn test_set_emode_category_locked() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, _) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            let mut pool_config = storage::get_pool_config(&e);
+            pool_config.hf_strategy = HealthStrategy::EMode as u32;
+            storage::set_pool_config(&e, &pool_config);
+            let category = EModeCategory {
+                c_factor: 0_9500000,
+                l_factor: 0_9500000,
+            };
+            storage::set_emode_category(&e, 1, &category);
+        });
+
+        let config = EModeCategory {
+            c_factor: 0_9000000,
+            l_factor: 0_9000000,
+        };
+        e.as_contract(&pool, || {
+            execute_queue_set_emode_category(&e, 1, &config);
+
+            e.ledger().set_timestamp(SECONDS_PER_WEEK - 1);
+            execute_set_emode_category(&e, 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_queue_set_emode_category_under_base_factors() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, _) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            let mut pool_config = storage::get_pool_config(&e);
+            pool_config.hf_strategy = HealthStrategy::EMode as u32;
+            storage::set_pool_config(&e, &pool_config);
+            let category = EModeCategory {
+                c_factor: 0_9500000,
+                l_factor: 0_9500000,
+            };
+            storage::set_emode_category(&e, 1, &category);
+        });
+
+        // the reserves in the category have factors of 0.75
+        let config = EModeCategory {
+            c_factor: 0_7000000,
+            l_factor: 0_9000000,
+        };
+        e.as_contract(&pool, || {
+            execute_queue_set_emode_category(&e, 1, &config);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_queue_set_emode_category_over_max_boost() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, _) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            let mut pool_config = storage::get_pool_config(&e);
+            pool_config.hf_strategy = HealthStrategy::EMode as u32;
+            storage::set_pool_config(&e, &pool_config);
+            let category = EModeCategory {
+                c_factor: 0_9500000,
+                l_factor: 0_9500000,
+            };
+            storage::set_emode_category(&e, 1, &category);
+        });
+
+        let config = EModeCategory {
+            c_factor: 0_9000000,
+            l_factor: 0_9500001,
+        };
+        e.as_contract(&pool, || {
+            execute_queue_set_emode_category(&e, 1, &config);
+        });
+    }
+
+    #[test]
+    fn test_cancel_queued_set_emode_category() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, _) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            let mut pool_config = storage::get_pool_config(&e);
+            pool_config.hf_strategy = HealthStrategy::EMode as u32;
+            storage::set_pool_config(&e, &pool_config);
+            let category = EModeCategory {
+                c_factor: 0_9500000,
+                l_factor: 0_9500000,
+            };
+            storage::set_emode_category(&e, 1, &category);
+        });
+
+        let config = EModeCategory {
+            c_factor: 0_9000000,
+            l_factor: 0_9000000,
+        };
+        e.as_contract(&pool, || {
+            execute_queue_set_emode_category(&e, 1, &config);
+            let cancelled = execute_cancel_queued_set_emode_category(&e, 1);
+            assert_eq!(cancelled.config.c_factor, 0_9000000);
+            assert!(storage::get_queued_emode_category(&e, 1).is_none());
+        });
+    }
+
+    #[test]
+    fn test_set_user_emode() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let samwise = Address::generate(&e);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, _) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            let mut pool_config = storage::get_pool_config(&e);
+            pool_config.hf_strategy = HealthStrategy::EMode as u32;
+            storage::set_pool_config(&e, &pool_config);
+            let category = EModeCategory {
+                c_factor: 0_9500000,
+                l_factor: 0_9500000,
+            };
+            storage::set_emode_category(&e, 1, &category);
+        });
+
+        // 100 collateral supports 56.25 liabilities without e-mode, and 90.25 with it
+        let positions = Positions {
+            liabilities: map![&e, (1, 80_0000000)],
+            collateral: map![&e, (0, 100_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+
+            execute_set_user_emode(&e, &samwise, 1);
+            assert_eq!(storage::get_user_emode(&e, &samwise), 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1205)")]
+    fn test_set_user_emode_leave_unhealthy() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let samwise = Address::generate(&e);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, _) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            let mut pool_config = storage::get_pool_config(&e);
+            pool_config.hf_strategy = HealthStrategy::EMode as u32;
+            storage::set_pool_config(&e, &pool_config);
+            let category = EModeCategory {
+                c_factor: 0_9500000,
+                l_factor: 0_9500000,
+            };
+            storage::set_emode_category(&e, 1, &category);
+        });
+
+        let positions = Positions {
+            liabilities: map![&e, (1, 80_0000000)],
+            collateral: map![&e, (0, 100_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            execute_set_user_emode(&e, &samwise, 1);
+
+            execute_set_user_emode(&e, &samwise, 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_user_emode_missing_category() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let samwise = Address::generate(&e);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, _) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            let mut pool_config = storage::get_pool_config(&e);
+            pool_config.hf_strategy = HealthStrategy::EMode as u32;
+            storage::set_pool_config(&e, &pool_config);
+            let category = EModeCategory {
+                c_factor: 0_9500000,
+                l_factor: 0_9500000,
+            };
+            storage::set_emode_category(&e, 1, &category);
+        });
+
+        e.as_contract(&pool, || {
+            execute_set_user_emode(&e, &samwise, 2);
+        });
+    }
+}
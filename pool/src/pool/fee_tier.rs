@@ -0,0 +1,202 @@
+use cast::i128;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env, Vec};
+
+use crate::{
+    constants::{SCALAR_7, SECONDS_PER_WEEK},
+    errors::PoolError,
+    storage::{self, FeeTier, UserFeeVolume},
+};
+
+/// The maximum number of fee tiers a pool can have
+const MAX_FEE_TIERS: u32 = 10;
+
+/// Set the volume tiers for the flash loan fee. An empty set of tiers disables fee tiering.
+///
+/// ### Arguments
+/// * `fee_tiers` - The fee tiers, ordered from lowest to highest
+///
+/// ### Panics
+/// If there are too many tiers, a tier is invalid, or the tiers are not ordered by increasing
+/// thresholds and discounts
+pub fn execute_set_fee_tiers(e: &Env, fee_tiers: &Vec<FeeTier>) {
+    if fee_tiers.len() > MAX_FEE_TIERS {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let mut prev_tier: Option<FeeTier> = None;
+    for tier in fee_tiers.iter() {
+        if tier.min_volume < 0 || tier.discount == 0 || tier.discount > SCALAR_7 as u32 {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+        if let Some(prev_tier) = prev_tier {
+            if tier.min_count < prev_tier.min_count
+                || tier.min_volume < prev_tier.min_volume
+                || tier.discount <= prev_tier.discount
+            {
+                panic_with_error!(e, PoolError::BadRequest);
+            }
+        }
+        prev_tier = Some(tier);
+    }
+    storage::set_fee_tiers(e, fee_tiers);
+}
+
+/// Fetch the fee paying activity of a user in the current fee epoch. Fee epochs last one week.
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn load_user_fee_volume(e: &Env, user: &Address) -> UserFeeVolume {
+    let epoch = e.ledger().timestamp() / SECONDS_PER_WEEK;
+    let fee_volume = storage::get_user_fee_volume(e, user);
+    if fee_volume.epoch == epoch {
+        fee_volume
+    } else {
+        UserFeeVolume {
+            epoch,
+            count: 0,
+            volume: 0,
+        }
+    }
+}
+
+/// Apply the discount of the highest tier the user's fee paying activity qualifies for to a fee
+///
+/// ### Arguments
+/// * `fee` - The fee before any discount (7 decimals)
+/// * `fee_tiers` - The fee tiers of the pool
+/// * `fee_volume` - The fee paying activity of the user in the current fee epoch
+pub fn apply_fee_tier(fee: u32, fee_tiers: &Vec<FeeTier>, fee_volume: &UserFeeVolume) -> u32 {
+    let mut discount = 0;
+    for tier in fee_tiers.iter() {
+        if fee_volume.count >= tier.min_count && fee_volume.volume >= tier.min_volume {
+            discount = discount.max(tier.discount);
+        }
+    }
+    let reduction = i128(fee)
+        .fixed_mul_floor(i128(discount), SCALAR_7)
+        .unwrap_optimized();
+    fee - reduction as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        vec,
+    };
+
+    #[test]
+    fn test_set_fee_tiers() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        let fee_tiers = vec![
+            &e,
+            FeeTier {
+                min_count: 10,
+                min_volume: 0,
+                discount: 0_1000000,
+            },
+            FeeTier {
+                min_count: 10,
+                min_volume: 100_000_0000000,
+                discount: 0_5000000,
+            },
+        ];
+
+        e.as_contract(&pool, || {
+            execute_set_fee_tiers(&e, &fee_tiers);
+            let stored_tiers = storage::get_fee_tiers(&e);
+            assert_eq!(stored_tiers.len(), 2);
+            let tier = stored_tiers.get_unchecked(1);
+            assert_eq!(tier.min_count, 10);
+            assert_eq!(tier.min_volume, 100_000_0000000);
+            assert_eq!(tier.discount, 0_5000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_fee_tiers_validates_order() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        let fee_tiers = vec![
+            &e,
+            FeeTier {
+                min_count: 10,
+                min_volume: 100_000_0000000,
+                discount: 0_5000000,
+            },
+            FeeTier {
+                min_count: 10,
+                min_volume: 0,
+                discount: 0_6000000,
+            },
+        ];
+
+        e.as_contract(&pool, || {
+            execute_set_fee_tiers(&e, &fee_tiers);
+        });
+    }
+
+    #[test]
+    fn test_load_user_fee_volume_resets_each_epoch() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        let samwise = Address::generate(&e);
+
+        e.ledger().set_timestamp(SECONDS_PER_WEEK * 3 + 100);
+        e.as_contract(&pool, || {
+            storage::set_user_fee_volume(
+                &e,
+                &samwise,
+                &UserFeeVolume {
+                    epoch: 3,
+                    count: 4,
+                    volume: 1_000_0000000,
+                },
+            );
+            let fee_volume = load_user_fee_volume(&e, &samwise);
+            assert_eq!(fee_volume.epoch, 3);
+            assert_eq!(fee_volume.count, 4);
+            assert_eq!(fee_volume.volume, 1_000_0000000);
+
+            e.ledger().set_timestamp(SECONDS_PER_WEEK * 4);
+            let fee_volume = load_user_fee_volume(&e, &samwise);
+            assert_eq!(fee_volume.epoch, 4);
+            assert_eq!(fee_volume.count, 0);
+            assert_eq!(fee_volume.volume, 0);
+        });
+    }
+
+    #[test]
+    fn test_apply_fee_tier() {
+        let e = Env::default();
+        let fee_tiers = vec![
+            &e,
+            FeeTier {
+                min_count: 10,
+                min_volume: 0,
+                discount: 0_1000000,
+            },
+            FeeTier {
+                min_count: 10,
+                min_volume: 100_000_0000000,
+                discount: 0_5000000,
+            },
+        ];
+        let mut fee_volume = UserFeeVolume {
+            epoch: 0,
+            count: 9,
+            volume: 500_000_0000000,
+        };
+        assert_eq!(apply_fee_tier(0_0010000, &fee_tiers, &fee_volume), 0_0010000);
+
+        fee_volume.count = 10;
+        assert_eq!(apply_fee_tier(0_0010000, &fee_tiers, &fee_volume), 0_0005000);
+
+        fee_volume.volume = 50_000_0000000;
+        assert_eq!(apply_fee_tier(0_0010000, &fee_tiers, &fee_volume), 0_0009000);
+    }
+}
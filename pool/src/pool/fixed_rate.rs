@@ -0,0 +1,733 @@
+use cast::i128;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env};
+
+use crate::{
+    constants::{MAX_FIXED_RATE_TERM, SCALAR_12, SCALAR_7, SECONDS_PER_WEEK, SECONDS_PER_YEAR},
+    errors::PoolError,
+    events::PoolEvents,
+    storage::{self, FixedLoan, FixedRateConfig, FixedTranche},
+};
+
+use super::{
+    execute_submit, preview::calc_reserve_rates, Pool, Positions, Request, RequestType, Reserve,
+    User,
+};
+
+/// Set the fixed-rate borrowing config for a reserve. A max term of zero stops new fixed-rate
+/// loans, and existing loans keep their fixed rate until they are settled.
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `spread` - The spread added to the current borrow rate for fixed rates (7 decimals)
+/// * `max_term` - The maximum number of seconds until a fixed-rate loan's maturity
+///
+/// ### Panics
+/// If the reserve does not exist, the spread is over 100%, or the max term is invalid
+pub fn execute_set_fixed_rate_config(e: &Env, asset: &Address, spread: u32, max_term: u64) {
+    if !storage::has_res(e, asset) || i128(spread) > SCALAR_7 || max_term > MAX_FIXED_RATE_TERM {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    if max_term == 0 {
+        storage::set_fixed_rate_config(e, asset, &None);
+    } else {
+        if max_term < SECONDS_PER_WEEK {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+        storage::set_fixed_rate_config(e, asset, &Some(FixedRateConfig { spread, max_term }));
+    }
+}
+
+/// Borrow from a reserve at a fixed rate until a maturity. The rate is the higher of the reserve's
+/// borrow rate after the borrow and its average borrow rate over the recent accrual history, plus
+/// the reserve's fixed-rate spread. The average keeps the rate from being locked in while the
+/// reserve's utilization is briefly pushed down.
+///
+/// The borrowed dTokens are held like any other liability, and are settled to the fixed value of
+/// the loan whenever the loan is settled. Maturities are bucketed by week, and a user can only
+/// hold one maturity per reserve. Additional fixed-rate borrows are merged into the loan at a
+/// rate weighted by value.
+///
+/// Returns the new positions for "from"
+///
+/// ### Arguments
+/// * `from` - The address borrowing
+/// * `asset` - The underlying asset of the reserve
+/// * `amount` - The amount of underlying tokens to borrow
+/// * `maturity` - The maturity of the loan, a multiple of a week
+///
+/// ### Panics
+/// If the reserve does not support fixed-rate loans, the maturity is invalid or differs from
+/// the user's existing loan, or the borrow can't be completed
+pub fn execute_borrow_fixed(
+    e: &Env,
+    from: &Address,
+    asset: &Address,
+    amount: i128,
+    maturity: u64,
+) -> Positions {
+    let config = match storage::get_fixed_rate_config(e, asset) {
+        Some(config) => config,
+        None => panic_with_error!(e, PoolError::BadRequest),
+    };
+    let now = e.ledger().timestamp();
+    if amount <= 0
+        || maturity <= now
+        || maturity % SECONDS_PER_WEEK != 0
+        || maturity - now > config.max_term
+    {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+
+    // settle any existing loan so the borrow is merged at the loan's current fixed value
+    let pool_config = storage::get_pool_config(e);
+    let mut reserve = Reserve::load(e, &pool_config, asset);
+    let mut user = User::load(e, from);
+    if let Some(loan) = storage::get_user_fixed_loan(e, from, reserve.index) {
+        if loan.maturity != maturity {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+    }
+    settle_fixed_loan(e, pool_config.bstop_rate, &mut reserve, &mut user);
+    reserve.store(e);
+    user.store(e);
+    let d_tokens_before = user.get_liabilities(reserve.index);
+
+    let request = Request {
+        request_type: RequestType::Borrow as u32,
+        address: asset.clone(),
+        amount,
+    };
+    let positions = execute_submit(e, from, from, from, vec![e, request], false, None).positions;
+    let d_tokens_minted = positions.liabilities.get(reserve.index).unwrap_or(0) - d_tokens_before;
+
+    // the reserve was stored by the borrow this ledger, so it does not accrue again
+    let reserve = Reserve::load(e, &pool_config, asset);
+    let spot_rate = calc_reserve_rates(e, pool_config.bstop_rate, &reserve).borrow_apr;
+    let rate = match calc_average_borrow_apr(e, &reserve) {
+        Some(average_rate) => spot_rate.max(average_rate),
+        None => spot_rate,
+    } + i128(config.spread);
+
+    let mut tranche = storage::get_fixed_tranche(e, asset);
+    let mut loan = match storage::get_user_fixed_loan(e, from, reserve.index) {
+        Some(loan) => {
+            remove_from_tranche(&mut tranche, &loan);
+            loan
+        }
+        None => FixedLoan {
+            d_tokens: 0,
+            value: 0,
+            rate: 0,
+            last_time: now,
+            maturity,
+        },
+    };
+    let value = loan.value + amount;
+    loan.rate = (loan.value * loan.rate + amount * rate) / value;
+    loan.value = value;
+    loan.d_tokens += d_tokens_minted;
+    add_to_tranche(&mut tranche, &loan);
+    storage::set_fixed_tranche(e, asset, &tranche);
+    storage::set_user_fixed_loan(e, from, reserve.index, &Some(loan));
+
+    PoolEvents::borrow_fixed(e, asset.clone(), from.clone(), amount, rate, maturity);
+    positions
+}
+
+/// Calculate the average borrow rate of a reserve (7 decimals) from the growth of its dToken rate
+/// since the oldest accrual snapshot
+///
+/// Returns None if the accrual history does not cover any time before the reserve's last accrual
+///
+/// ### Arguments
+/// * `reserve` - The reserve, accrued to the current ledger
+fn calc_average_borrow_apr(e: &Env, reserve: &Reserve) -> Option<i128> {
+    let oldest = storage::get_accrual_history(e, &reserve.asset).first()?;
+    let elapsed = reserve.last_time.checked_sub(oldest.timestamp)?;
+    if elapsed == 0 || oldest.d_rate <= 0 {
+        return None;
+    }
+    let growth = reserve.d_rate.fixed_div_floor(oldest.d_rate, SCALAR_12)? - SCALAR_12;
+    growth.fixed_mul_floor(SECONDS_PER_YEAR * SCALAR_7, i128(elapsed) * SCALAR_12)
+}
+
+/// Settle a user's fixed-rate loan. The user can settle their loan at any time, and anyone can
+/// settle it after its maturity, which converts the loan to a variable rate loan.
+///
+/// Returns the dTokens minted (positive) or burnt (negative) from the user to match the fixed
+/// value of the loan
+///
+/// ### Arguments
+/// * `caller` - The address settling the loan
+/// * `user` - The address of the user with the loan
+/// * `asset` - The underlying asset of the reserve
+///
+/// ### Panics
+/// If the user has no fixed-rate loan, or the caller is not the user and the loan is not mature
+pub fn execute_settle_fixed_loan(
+    e: &Env,
+    caller: &Address,
+    user: &Address,
+    asset: &Address,
+) -> i128 {
+    let pool_config = storage::get_pool_config(e);
+    let mut reserve = Reserve::load(e, &pool_config, asset);
+    let loan = match storage::get_user_fixed_loan(e, user, reserve.index) {
+        Some(loan) => loan,
+        None => panic_with_error!(e, PoolError::BadRequest),
+    };
+    if caller != user && e.ledger().timestamp() < loan.maturity {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+
+    let mut user_state = User::load(e, user);
+    let d_tokens_before = user_state.get_liabilities(reserve.index);
+    settle_fixed_loan(e, pool_config.bstop_rate, &mut reserve, &mut user_state);
+    reserve.store(e);
+    user_state.store(e);
+    user_state.get_liabilities(reserve.index) - d_tokens_before
+}
+
+/// Settle the user's fixed-rate loan from the reserve, if any, by minting or burning dTokens so
+/// the loan's dTokens are worth the fixed value of the loan. Interest charged above the variable
+/// rate is credited to suppliers and the backstop, and interest refunded below the variable rate
+/// is paid for by them.
+///
+/// Loans past their maturity are removed once settled, and accrue at the variable rate after.
+///
+/// ### Arguments
+/// * `bstop_rate` - The backstop take rate of the pool (7 decimals)
+/// * `reserve` - The reserve, accrued to the current ledger
+/// * `user` - The user with the loan
+pub fn settle_fixed_loan(e: &Env, bstop_rate: u32, reserve: &mut Reserve, user: &mut User) {
+    if user.account_id != 0 {
+        return;
+    }
+    let mut loan = match storage::get_user_fixed_loan(e, &user.address, reserve.index) {
+        Some(loan) => loan,
+        None => return,
+    };
+    let mut tranche = storage::get_fixed_tranche(e, &reserve.asset);
+    remove_from_tranche(&mut tranche, &loan);
+    cap_fixed_loan(&mut loan, user.get_liabilities(reserve.index));
+
+    let now = e.ledger().timestamp();
+    let fixed_value = calc_fixed_value(&loan, now);
+    let variable_value = reserve.to_asset_from_d_token(loan.d_tokens);
+    let mut d_tokens_delta = 0;
+    if fixed_value > variable_value {
+        let interest = fixed_value - variable_value;
+        let d_tokens_minted = reserve.to_d_token_up(interest);
+        if d_tokens_minted > 0 {
+            user.add_liabilities(e, reserve, d_tokens_minted);
+            reserve.gulp(bstop_rate, interest);
+            loan.d_tokens += d_tokens_minted;
+            d_tokens_delta = d_tokens_minted;
+        }
+    } else if variable_value > fixed_value {
+        let d_tokens_burnt = reserve.to_d_token_down(variable_value - fixed_value);
+        if d_tokens_burnt > 0 {
+            let refund = reserve.to_asset_from_d_token(d_tokens_burnt);
+            user.remove_liabilities(e, reserve, d_tokens_burnt);
+            refund_interest(reserve, bstop_rate, refund);
+            loan.d_tokens -= d_tokens_burnt;
+            d_tokens_delta = -d_tokens_burnt;
+        }
+    }
+    loan.value = fixed_value;
+    loan.last_time = now;
+
+    if !user.read_only {
+        if loan.d_tokens <= 0 || now >= loan.maturity {
+            storage::set_user_fixed_loan(e, &user.address, reserve.index, &None);
+        } else {
+            add_to_tranche(&mut tranche, &loan);
+            storage::set_user_fixed_loan(e, &user.address, reserve.index, &Some(loan));
+        }
+        storage::set_fixed_tranche(e, &reserve.asset, &tranche);
+    }
+
+    PoolEvents::settle_fixed_loan(
+        e,
+        reserve.asset.clone(),
+        user.address.clone(),
+        fixed_value,
+        d_tokens_delta,
+    );
+}
+
+/// Settle all of the user's fixed-rate loans, so the user's liabilities are valued at the fixed
+/// value of their loans before the user's health is checked or a liquidation is created
+///
+/// Returns true if any loan was settled
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `user` - The user with the loans
+pub fn settle_fixed_loans(e: &Env, pool: &mut Pool, user: &mut User) -> bool {
+    if user.account_id != 0 {
+        return false;
+    }
+    let reserve_list = storage::get_res_list(e);
+    let mut settled = false;
+    for reserve_index in user.positions.liabilities.keys() {
+        if storage::get_user_fixed_loan(e, &user.address, reserve_index).is_none() {
+            continue;
+        }
+        let asset = reserve_list.get_unchecked(reserve_index);
+        let mut reserve = pool.load_reserve(e, &asset, true);
+        settle_fixed_loan(e, pool.config.bstop_rate, &mut reserve, user);
+        pool.cache_reserve(reserve);
+        settled = true;
+    }
+    settled
+}
+
+/// Reduce the user's fixed-rate loan from the reserve to the user's remaining liability, after
+/// the liability is repaid
+///
+/// ### Arguments
+/// * `reserve` - The reserve
+/// * `user` - The user with the loan
+pub fn reduce_fixed_loan(e: &Env, reserve: &Reserve, user: &User) {
+    if user.account_id != 0 || user.read_only {
+        return;
+    }
+    let mut loan = match storage::get_user_fixed_loan(e, &user.address, reserve.index) {
+        Some(loan) => loan,
+        None => return,
+    };
+    let d_tokens = user.get_liabilities(reserve.index);
+    if loan.d_tokens <= d_tokens {
+        return;
+    }
+    let mut tranche = storage::get_fixed_tranche(e, &reserve.asset);
+    remove_from_tranche(&mut tranche, &loan);
+    cap_fixed_loan(&mut loan, d_tokens);
+    if loan.d_tokens == 0 {
+        storage::set_user_fixed_loan(e, &user.address, reserve.index, &None);
+    } else {
+        add_to_tranche(&mut tranche, &loan);
+        storage::set_user_fixed_loan(e, &user.address, reserve.index, &Some(loan));
+    }
+    storage::set_fixed_tranche(e, &reserve.asset, &tranche);
+}
+
+/// Calculate the value of a fixed-rate loan at a timestamp
+fn calc_fixed_value(loan: &FixedLoan, timestamp: u64) -> i128 {
+    let elapsed = i128(timestamp - loan.last_time);
+    loan.value
+        + loan
+            .value
+            .fixed_mul_ceil(loan.rate * elapsed, SCALAR_7 * SECONDS_PER_YEAR)
+            .unwrap_optimized()
+}
+
+/// Cap the dTokens of a loan to the user's liability, reducing the loan's value proportionally.
+/// The user's liability can be lower than the loan's dTokens after a repayment or liquidation.
+fn cap_fixed_loan(loan: &mut FixedLoan, d_tokens: i128) {
+    if loan.d_tokens > d_tokens {
+        loan.value = loan
+            .value
+            .fixed_mul_floor(d_tokens, loan.d_tokens)
+            .unwrap_optimized();
+        loan.d_tokens = d_tokens;
+    }
+}
+
+/// Refund interest to a borrower from the reserve. The backstop pays its share of the refund
+/// from the backstop credit, and suppliers pay the rest through the bToken rate.
+fn refund_interest(reserve: &mut Reserve, bstop_rate: u32, refund: i128) {
+    let backstop_share = refund
+        .fixed_mul_floor(i128(bstop_rate), SCALAR_7)
+        .unwrap_optimized()
+        .min(reserve.backstop_credit);
+    reserve.backstop_credit -= backstop_share;
+    if reserve.b_supply > 0 {
+        let b_rate_loss = (refund - backstop_share)
+            .fixed_div_ceil(reserve.b_supply, SCALAR_12)
+            .unwrap_optimized();
+        reserve.b_rate = (reserve.b_rate - b_rate_loss).max(0);
+    }
+}
+
+/// Add a loan to the fixed-rate tranche
+fn add_to_tranche(tranche: &mut FixedTranche, loan: &FixedLoan) {
+    let f_supply = tranche.f_supply + loan.d_tokens;
+    if f_supply > 0 {
+        tranche.f_rate = (tranche.f_supply * tranche.f_rate + loan.d_tokens * loan.rate) / f_supply;
+    }
+    tranche.f_supply = f_supply;
+}
+
+/// Remove a loan from the fixed-rate tranche
+fn remove_from_tranche(tranche: &mut FixedTranche, loan: &FixedLoan) {
+    let f_supply = tranche.f_supply - loan.d_tokens;
+    if f_supply > 0 {
+        tranche.f_rate =
+            ((tranche.f_supply * tranche.f_rate - loan.d_tokens * loan.rate) / f_supply).max(0);
+        tranche.f_supply = f_supply;
+    } else {
+        tranche.f_supply = 0;
+        tranche.f_rate = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::AccrualSnapshot, testutils};
+    use sep_41_token::testutils::MockTokenClient;
+    use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+
+    fn set_timestamp(e: &Env, timestamp: u64) {
+        e.ledger().set(LedgerInfo {
+            timestamp,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+    }
+
+    #[test]
+    fn test_borrow_fixed() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        set_timestamp(&e, SECONDS_PER_WEEK * 10 + 600);
+
+        let samwise = Address::generate(&e);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        let collateral = assets.get_unchecked(0);
+        let underlying = assets.get_unchecked(1);
+        MockTokenClient::new(&e, &collateral).mint(&samwise, &100_0000000);
+        e.as_contract(&pool, || {
+            let request = Request {
+                request_type: RequestType::SupplyCollateral as u32,
+                address: collateral,
+                amount: 100_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+            execute_set_fixed_rate_config(&e, &underlying, 0_0200000, SECONDS_PER_WEEK * 8);
+        });
+
+        e.as_contract(&pool, || {
+            let maturity = SECONDS_PER_WEEK * 14;
+            let positions = execute_borrow_fixed(&e, &samwise, &underlying, 10_0000000, maturity);
+
+            let pool_config = storage::get_pool_config(&e);
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            let d_tokens = positions.liabilities.get_unchecked(reserve.index);
+            let rate = calc_reserve_rates(&e, pool_config.bstop_rate, &reserve).borrow_apr;
+
+            let loan = storage::get_user_fixed_loan(&e, &samwise, reserve.index).unwrap();
+            assert_eq!(loan.d_tokens, d_tokens);
+            assert_eq!(loan.value, 10_0000000);
+            assert_eq!(loan.rate, rate + 0_0200000);
+            assert_eq!(loan.maturity, maturity);
+
+            let tranche = storage::get_fixed_tranche(&e, &underlying);
+            assert_eq!(tranche.f_supply, d_tokens);
+            assert_eq!(tranche.f_rate, loan.rate);
+        });
+    }
+
+    #[test]
+    fn test_borrow_fixed_uses_average_rate() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        let now = SECONDS_PER_WEEK * 10 + 600;
+        set_timestamp(&e, now);
+
+        let samwise = Address::generate(&e);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        let collateral = assets.get_unchecked(0);
+        let underlying = assets.get_unchecked(1);
+        MockTokenClient::new(&e, &collateral).mint(&samwise, &100_0000000);
+        e.as_contract(&pool, || {
+            let request = Request {
+                request_type: RequestType::SupplyCollateral as u32,
+                address: collateral,
+                amount: 100_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+            execute_set_fixed_rate_config(&e, &underlying, 0_0200000, SECONDS_PER_WEEK * 8);
+        });
+
+        e.as_contract(&pool, || {
+            // the dToken rate grew 1% over the last day, well above the spot borrow rate
+            let mut reserve_data = storage::get_res_data(&e, &underlying);
+            reserve_data.d_rate = 1_010_000_000_000;
+            reserve_data.last_time = now;
+            storage::set_res_data(&e, &underlying, &reserve_data);
+            let snapshot = AccrualSnapshot {
+                timestamp: now - 24 * 60 * 60,
+                b_rate: SCALAR_12,
+                d_rate: SCALAR_12,
+                util: 0,
+            };
+            storage::set_accrual_history(&e, &underlying, &vec![&e, snapshot]);
+
+            let maturity = SECONDS_PER_WEEK * 14;
+            execute_borrow_fixed(&e, &samwise, &underlying, 10_0000000, maturity);
+
+            let pool_config = storage::get_pool_config(&e);
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            let spot_rate = calc_reserve_rates(&e, pool_config.bstop_rate, &reserve).borrow_apr;
+            let average_rate = calc_average_borrow_apr(&e, &reserve).unwrap();
+            assert_eq!(average_rate, 3_6500000);
+            assert!(average_rate > spot_rate);
+
+            let loan = storage::get_user_fixed_loan(&e, &samwise, reserve.index).unwrap();
+            assert_eq!(loan.rate, average_rate + 0_0200000);
+        });
+    }
+
+    #[test]
+    fn test_settle_fixed_loan_after_maturity() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        set_timestamp(&e, SECONDS_PER_WEEK * 10 + 600);
+
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        let collateral = assets.get_unchecked(0);
+        let underlying = assets.get_unchecked(1);
+        MockTokenClient::new(&e, &collateral).mint(&samwise, &100_0000000);
+        e.as_contract(&pool, || {
+            let request = Request {
+                request_type: RequestType::SupplyCollateral as u32,
+                address: collateral,
+                amount: 100_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+            execute_set_fixed_rate_config(&e, &underlying, 0_0200000, SECONDS_PER_WEEK * 8);
+        });
+
+        e.as_contract(&pool, || {
+            let maturity = SECONDS_PER_WEEK * 11;
+            execute_borrow_fixed(&e, &samwise, &underlying, 10_0000000, maturity);
+        });
+        set_timestamp(&e, SECONDS_PER_WEEK * 11 + 600);
+        e.as_contract(&pool, || {
+            let pool_config = storage::get_pool_config(&e);
+            let loan = storage::get_user_fixed_loan(&e, &samwise, 1).unwrap();
+            let fixed_value = calc_fixed_value(&loan, e.ledger().timestamp());
+
+            execute_settle_fixed_loan(&e, &frodo, &samwise, &underlying);
+
+            // the loan's dTokens are worth the fixed value and the loan is now variable
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            let d_tokens = storage::get_user_positions(&e, &samwise)
+                .liabilities
+                .get_unchecked(1);
+            let value = reserve.to_asset_from_d_token(d_tokens);
+            assert!(value >= fixed_value && value - fixed_value <= 2);
+            assert!(storage::get_user_fixed_loan(&e, &samwise, 1).is_none());
+            let tranche = storage::get_fixed_tranche(&e, &underlying);
+            assert_eq!(tranche.f_supply, 0);
+            assert_eq!(tranche.f_rate, 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_settle_fixed_loan_before_maturity_not_user() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        set_timestamp(&e, SECONDS_PER_WEEK * 10 + 600);
+
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        let collateral = assets.get_unchecked(0);
+        let underlying = assets.get_unchecked(1);
+        MockTokenClient::new(&e, &collateral).mint(&samwise, &100_0000000);
+        e.as_contract(&pool, || {
+            let request = Request {
+                request_type: RequestType::SupplyCollateral as u32,
+                address: collateral,
+                amount: 100_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+            execute_set_fixed_rate_config(&e, &underlying, 0_0200000, SECONDS_PER_WEEK * 8);
+        });
+
+        e.as_contract(&pool, || {
+            execute_borrow_fixed(&e, &samwise, &underlying, 10_0000000, SECONDS_PER_WEEK * 12);
+            execute_settle_fixed_loan(&e, &frodo, &samwise, &underlying);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_borrow_fixed_maturity_not_bucketed() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        set_timestamp(&e, SECONDS_PER_WEEK * 10 + 600);
+
+        let samwise = Address::generate(&e);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        let collateral = assets.get_unchecked(0);
+        let underlying = assets.get_unchecked(1);
+        MockTokenClient::new(&e, &collateral).mint(&samwise, &100_0000000);
+        e.as_contract(&pool, || {
+            let request = Request {
+                request_type: RequestType::SupplyCollateral as u32,
+                address: collateral,
+                amount: 100_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+            execute_set_fixed_rate_config(&e, &underlying, 0_0200000, SECONDS_PER_WEEK * 8);
+        });
+
+        e.as_contract(&pool, || {
+            execute_borrow_fixed(&e, &samwise, &underlying, 10_0000000, SECONDS_PER_WEEK * 12 + 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_borrow_fixed_past_max_term() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        set_timestamp(&e, SECONDS_PER_WEEK * 10 + 600);
+
+        let samwise = Address::generate(&e);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        let collateral = assets.get_unchecked(0);
+        let underlying = assets.get_unchecked(1);
+        MockTokenClient::new(&e, &collateral).mint(&samwise, &100_0000000);
+        e.as_contract(&pool, || {
+            let request = Request {
+                request_type: RequestType::SupplyCollateral as u32,
+                address: collateral,
+                amount: 100_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+            execute_set_fixed_rate_config(&e, &underlying, 0_0200000, SECONDS_PER_WEEK * 8);
+        });
+
+        e.as_contract(&pool, || {
+            execute_borrow_fixed(&e, &samwise, &underlying, 10_0000000, SECONDS_PER_WEEK * 19);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1205)")]
+    fn test_health_check_settles_fixed_rate_above_variable_rate() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        set_timestamp(&e, SECONDS_PER_WEEK * 10 + 600);
+
+        let samwise = Address::generate(&e);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        let collateral = assets.get_unchecked(0);
+        let underlying = assets.get_unchecked(1);
+        MockTokenClient::new(&e, &collateral).mint(&samwise, &100_0000000);
+        e.as_contract(&pool, || {
+            let request = Request {
+                request_type: RequestType::SupplyCollateral as u32,
+                address: collateral,
+                amount: 100_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+            execute_set_fixed_rate_config(&e, &underlying, 0_0200000, SECONDS_PER_WEEK * 8);
+        });
+
+        e.as_contract(&pool, || {
+            execute_borrow_fixed(&e, &samwise, &underlying, 40_0000000, SECONDS_PER_WEEK * 18);
+            let mut loan = storage::get_user_fixed_loan(&e, &samwise, 1).unwrap();
+            loan.rate = 10_0000000;
+            storage::set_user_fixed_loan(&e, &samwise, 1, &Some(loan));
+        });
+        set_timestamp(&e, SECONDS_PER_WEEK * 14 + 600);
+        e.as_contract(&pool, || {
+            // healthy at the variable rate, but the fixed value of the loan is over 70
+            let request = Request {
+                request_type: RequestType::WithdrawCollateral as u32,
+                address: storage::get_res_list(&e).get_unchecked(0),
+                amount: 1_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+        });
+    }
+
+    #[test]
+    fn test_health_check_settles_fixed_rate_below_variable_rate() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        let now = SECONDS_PER_WEEK * 10 + 600;
+        set_timestamp(&e, now);
+
+        let samwise = Address::generate(&e);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        let collateral = assets.get_unchecked(0);
+        let underlying = assets.get_unchecked(1);
+        MockTokenClient::new(&e, &collateral).mint(&samwise, &100_0000000);
+        e.as_contract(&pool, || {
+            let request = Request {
+                request_type: RequestType::SupplyCollateral as u32,
+                address: collateral,
+                amount: 100_0000000,
+            };
+            execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None);
+            execute_set_fixed_rate_config(&e, &underlying, 0_0200000, SECONDS_PER_WEEK * 8);
+        });
+
+        e.as_contract(&pool, || {
+            execute_borrow_fixed(&e, &samwise, &underlying, 40_0000000, SECONDS_PER_WEEK * 18);
+            let mut loan = storage::get_user_fixed_loan(&e, &samwise, 1).unwrap();
+            loan.rate = 0;
+            storage::set_user_fixed_loan(&e, &samwise, 1, &Some(loan));
+
+            // the variable value of the loan doubles, which is unhealthy
+            let mut reserve_data = storage::get_res_data(&e, &underlying);
+            reserve_data.d_rate *= 2;
+            storage::set_res_data(&e, &underlying, &reserve_data);
+
+            let request = Request {
+                request_type: RequestType::WithdrawCollateral as u32,
+                address: storage::get_res_list(&e).get_unchecked(0),
+                amount: 1_0000000,
+            };
+            let positions =
+                execute_submit(&e, &samwise, &samwise, &samwise, vec![&e, request], false, None)
+                    .positions;
+
+            let pool_config = storage::get_pool_config(&e);
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            let value = reserve.to_asset_from_d_token(positions.liabilities.get_unchecked(1));
+            assert!(value >= 40_0000000 && value - 40_0000000 <= 2);
+        });
+    }
+}
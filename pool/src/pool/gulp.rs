@@ -1,7 +1,7 @@
 use sep_41_token::TokenClient;
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{panic_with_error, Address, Env};
 
-use crate::storage;
+use crate::{errors::PoolError, storage};
 
 use super::Reserve;
 
@@ -32,10 +32,31 @@ pub fn execute_gulp(e: &Env, asset: &Address) -> (i128, i128) {
     return (token_balance_delta, reserve.b_rate);
 }
 
+/// Accrue interest on a reserve up to the current ledger timestamp and store it, booking the
+/// backstop credit earned since the last accrual. Idle reserves otherwise only accrue when a
+/// user next interacts with them.
+///
+/// Returns the accrued reserve
+///
+/// ### Arguments
+/// * `asset` - The address of the asset to accrue
+///
+/// ### Panics
+/// If the reserve does not exist
+pub fn execute_accrue(e: &Env, asset: &Address) -> Reserve {
+    if !storage::has_res(e, asset) {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let pool_config = storage::get_pool_config(e);
+    let reserve = Reserve::load(e, &pool_config, asset);
+    reserve.store(e);
+    reserve
+}
+
 #[cfg(test)]
 mod tests {
     use crate::constants::SCALAR_7;
-    use crate::pool::execute_gulp;
+    use crate::pool::{execute_accrue, execute_gulp};
     use crate::storage::{self, PoolConfig};
     use crate::testutils;
     use soroban_sdk::{
@@ -71,11 +92,16 @@ mod tests {
                 bstop_rate: 0_1000000,
                 status: 0,
                 max_positions: 4,
+                flash_loan_fee: 0,
+                hf_strategy: 0,
+                fallback_oracle: None,
+                min_health_factor: 1_0000100,
+                max_account_leverage: 0,
             };
             storage::set_pool_config(&e, &pool_config);
             let (token_delta_result, new_b_rate) = execute_gulp(&e, &underlying);
             assert_eq!(token_delta_result, 10000000000);
-            assert_eq!(new_b_rate, 10000000130);
+            assert_eq!(new_b_rate, 10_000_000_129_000);
             let reserve_data = storage::get_res_data(&e, &underlying);
             assert_eq!(reserve_data.b_rate, new_b_rate);
             assert_eq!(reserve_data.last_time, 100);
@@ -111,12 +137,17 @@ mod tests {
                 bstop_rate: 0_1000000,
                 status: 0,
                 max_positions: 4,
+                flash_loan_fee: 0,
+                hf_strategy: 0,
+                fallback_oracle: None,
+                min_health_factor: 1_0000100,
+                max_account_leverage: 0,
             };
             storage::set_pool_config(&e, &pool_config);
             let (token_delta_result, new_b_rate) = execute_gulp(&e, &underlying);
             let reserve = storage::get_res_data(&e, &underlying);
             assert_eq!(token_delta_result, 0);
-            assert_eq!(new_b_rate, 1000000130); // Increase of 130 from interest
+            assert_eq!(new_b_rate, 1_000_000_129_000); // Increase of 129_000 from interest
             assert_eq!(reserve.b_rate, reserve_data.b_rate); // B rate should not change
             assert_eq!(reserve.last_time, 0); // Last time should not change
         });
@@ -143,29 +174,93 @@ mod tests {
 
         let (underlying, underlying_client) = testutils::create_token_contract(&e, &bombadil);
         let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
-        reserve_data.b_rate = 1_623_456_890;
-        reserve_data.d_rate = 1_323_456_890;
-        reserve_data.d_supply = 99_711 * SCALAR_7;
-        reserve_data.b_supply = 23_493_4 * SCALAR_7;
+        reserve_data.b_rate = 1_623_456_890_000;
+        reserve_data.d_rate = 1_323_456_890_000;
+        reserve_data.d_supply = 9_971_100 * SCALAR_7;
+        reserve_data.b_supply = 23_493_400 * SCALAR_7;
         // reserve_data.last_time = 100;
         testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
-        underlying_client.mint(&pool, &(1000));
+        underlying_client.mint(&pool, &(10));
         e.as_contract(&pool, || {
             let pool_config = PoolConfig {
                 oracle,
                 bstop_rate: 0_1000000,
                 status: 0,
                 max_positions: 4,
+                flash_loan_fee: 0,
+                hf_strategy: 0,
+                fallback_oracle: None,
+                min_health_factor: 1_0000100,
+                max_account_leverage: 0,
             };
             storage::set_pool_config(&e, &pool_config);
             let pre_gulp_reserve = storage::get_res_data(&e, &underlying);
             let (token_delta_result, new_b_rate) = execute_gulp(&e, &underlying);
             let reserve = storage::get_res_data(&e, &underlying);
             assert_eq!(token_delta_result, 0);
-            assert_eq!(new_b_rate, 1623456943); // Increase of 145 from interest
+            assert_eq!(new_b_rate, 1_623_456_943_006); // Increase of 53_006 from interest
             assert_eq!(reserve.backstop_credit, 0);
             assert_eq!(reserve.b_rate, pre_gulp_reserve.b_rate);
             assert_eq!(reserve.last_time, pre_gulp_reserve.last_time);
         });
     }
+
+    #[test]
+    fn test_execute_accrue() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, _) = testutils::create_mock_oracle(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            let pool_config = PoolConfig {
+                oracle,
+                bstop_rate: 0_2000000,
+                status: 0,
+                max_positions: 4,
+                flash_loan_fee: 0,
+                hf_strategy: 0,
+                fallback_oracle: None,
+                min_health_factor: 1_0000100,
+                max_account_leverage: 0,
+            };
+            storage::set_pool_config(&e, &pool_config);
+            let reserve = execute_accrue(&e, &underlying);
+            assert_eq!(reserve.d_rate, 1_000_001_141_553);
+            assert_eq!(reserve.b_rate, 1_000_000_686_000);
+            assert_eq!(reserve.backstop_credit, 171);
+
+            let reserve_data = storage::get_res_data(&e, &underlying);
+            assert_eq!(reserve_data.d_rate, reserve.d_rate);
+            assert_eq!(reserve_data.b_rate, reserve.b_rate);
+            assert_eq!(reserve_data.backstop_credit, 171);
+            assert_eq!(reserve_data.last_time, 600);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_accrue_no_reserve() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            execute_accrue(&e, &Address::generate(&e));
+        });
+    }
 }
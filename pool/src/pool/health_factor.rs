@@ -1,10 +1,85 @@
+use cast::i128;
 use soroban_fixed_point_math::FixedPoint;
-use soroban_sdk::{unwrap::UnwrapOptimized, Env};
+use soroban_sdk::{contracttype, panic_with_error, unwrap::UnwrapOptimized, Address, Env, Map, Vec};
 
-use crate::{constants::SCALAR_7, storage};
+use crate::{constants::SCALAR_7, errors::PoolError, storage};
 
 use super::{pool::Pool, Positions};
 
+/// The health of a user's positions
+#[derive(Clone)]
+#[contracttype]
+pub struct HealthFactorData {
+    pub collateral: i128,    // the effective collateral balance denominated in the base asset
+    pub liabilities: i128,   // the effective liability balance denominated in the base asset
+    pub health_factor: i128, // the health factor, expressed in 7 decimals. i128::MAX if there are no liabilities
+}
+
+/// Load the health of a user's positions
+///
+/// ### Arguments
+/// * user - The address of the user
+pub fn load_user_health_factor(e: &Env, user: &Address) -> HealthFactorData {
+    let mut pool = Pool::load(e);
+    let positions = storage::get_user_positions(e, user);
+    let emode = storage::get_user_emode(e, user);
+    PositionData::calculate_from_positions(e, &mut pool, &positions, emode).as_health_factor_data()
+}
+
+/// The value of a user's positions in a reserve
+#[derive(Clone)]
+#[contracttype]
+pub struct PositionValue {
+    pub collateral: i128,            // the collateral balance denominated in the base asset
+    pub liabilities: i128,           // the liability balance denominated in the base asset
+    pub effective_collateral: i128,  // the collateral balance weighted by its collateral factor
+    pub effective_liabilities: i128, // the liability balance weighted by its liability factor
+}
+
+/// Load the value of a user's positions in each reserve, using the same prices, rounding and
+/// health strategy as the user's health factor
+///
+/// Returns a map of reserve index to the value of the user's positions in the reserve
+///
+/// ### Arguments
+/// * user - The address of the user
+pub fn load_user_positions_value(e: &Env, user: &Address) -> Map<u32, PositionValue> {
+    let mut pool = Pool::load(e);
+    let positions = storage::get_user_positions(e, user);
+    let emode = storage::get_user_emode(e, user);
+    let mut values = Map::new(e);
+    PositionData::calculate(e, &mut pool, &positions, emode, Some(&mut values));
+    values
+}
+
+/// The strategy a pool uses to calculate the health factor of a user's positions
+#[derive(Clone, PartialEq)]
+#[repr(u32)]
+pub enum HealthStrategy {
+    /// Each position is weighted by the collateral or liability factor of its reserve
+    Standard = 0,
+    /// Positions entirely within the user's e-mode category are weighted by the category's factors
+    EMode = 1,
+    /// Collateral and liabilities in the same reserve offset each other before being weighted, up
+    /// to the net equity of the positions
+    PortfolioMargin = 2,
+}
+
+impl HealthStrategy {
+    /// Convert a u32 to a HealthStrategy
+    ///
+    /// ### Panics
+    /// If the value is not a valid HealthStrategy
+    pub fn from_u32(e: &Env, value: u32) -> Self {
+        match value {
+            0 => HealthStrategy::Standard,
+            1 => HealthStrategy::EMode,
+            2 => HealthStrategy::PortfolioMargin,
+            _ => panic_with_error!(e, PoolError::BadRequest),
+        }
+    }
+}
+
 pub struct PositionData {
     /// The effective collateral balance denominated in the base asset
     pub collateral_base: i128,
@@ -14,20 +89,58 @@ pub struct PositionData {
     pub liability_base: i128,
     // The raw liability balance demoninated in the base asset
     pub liability_raw: i128,
-    /// The scalar for the base asset
+    /// The scalar of ratios of the positions, like the health factor. Base asset values use
+    /// `PRICE_DECIMALS`, but ratios only need 7 decimals.
     pub scalar: i128,
 }
 
 impl PositionData {
-    /// Calculate the position data for a given set of of positions
+    /// Calculate the position data for a given set of of positions, using the pool's health
+    /// strategy to determine the effective collateral and liabilities.
+    ///
+    /// For e-mode pools, if the positions are in an e-mode category and all collateral and
+    /// liabilities are in reserves of the category, the category's collateral and liability
+    /// factors are used instead of the reserves'. For portfolio margin pools, collateral and
+    /// liabilities in the same reserve offset each other, and only the remainder is weighted. The
+    /// total offset is limited to the net equity of the positions, so borrowing against
+    /// collateral in the same reserve can't lever the positions beyond what the equity supports.
     ///
     /// ### Arguments
     /// * pool - The pool
     /// * positions - The positions to calculate the health factor for
-    pub fn calculate_from_positions(e: &Env, pool: &mut Pool, positions: &Positions) -> Self {
-        let oracle_scalar = 10i128.pow(pool.load_price_decimals(e));
+    /// * emode - The e-mode category of the positions, or 0 if the positions are not in one
+    pub fn calculate_from_positions(
+        e: &Env,
+        pool: &mut Pool,
+        positions: &Positions,
+        emode: u32,
+    ) -> Self {
+        PositionData::calculate(e, pool, positions, emode, None)
+    }
+
+    /// Calculate the position data for a given set of positions, and record the value of the
+    /// positions in each reserve to `values`, if provided
+    fn calculate(
+        e: &Env,
+        pool: &mut Pool,
+        positions: &Positions,
+        emode: u32,
+        mut values: Option<&mut Map<u32, PositionValue>>,
+    ) -> Self {
+        let strategy = HealthStrategy::from_u32(e, pool.config.hf_strategy);
+        let emode_category = if strategy == HealthStrategy::EMode && emode > 0 {
+            storage::get_emode_category(e, emode)
+        } else {
+            None
+        };
+        let mut in_category = true;
 
         let reserve_list = storage::get_res_list(e);
+        let mut netting_budget = if strategy == HealthStrategy::PortfolioMargin {
+            calc_net_equity(e, pool, positions, &reserve_list)
+        } else {
+            0
+        };
         let mut collateral_base = 0;
         let mut liability_base = 0;
         let mut collateral_raw = 0;
@@ -40,44 +153,105 @@ impl PositionData {
             }
             let reserve = pool.load_reserve(e, &reserve_list.get_unchecked(i), false);
             let asset_to_base = pool.load_price(e, &reserve.asset);
+            in_category = in_category && reserve.category == emode;
+
+            let mut asset_collateral = reserve.to_asset_from_b_token(b_token_balance);
+            let mut asset_liability = reserve.to_asset_from_d_token(d_token_balance);
+            let reserve_collateral_raw = asset_to_base
+                .fixed_mul_floor(asset_collateral, reserve.scalar)
+                .unwrap_optimized();
+            let reserve_liability_raw = asset_to_base
+                .fixed_mul_ceil(asset_liability, reserve.scalar)
+                .unwrap_optimized();
+            collateral_raw += reserve_collateral_raw;
+            liability_raw += reserve_liability_raw;
+
+            if netting_budget > 0 {
+                let max_offset = netting_budget
+                    .fixed_div_floor(asset_to_base, reserve.scalar)
+                    .unwrap_optimized();
+                let offset = asset_collateral.min(asset_liability).min(max_offset);
+                if offset > 0 {
+                    asset_collateral -= offset;
+                    asset_liability -= offset;
+                    netting_budget -= asset_to_base
+                        .fixed_mul_ceil(offset, reserve.scalar)
+                        .unwrap_optimized()
+                        .min(netting_budget);
+                }
+            }
 
-            if b_token_balance > 0 {
+            let mut reserve_collateral_base = 0;
+            if asset_collateral > 0 {
                 // append users effective collateral to collateral_base
-                let asset_collateral = reserve.to_effective_asset_from_b_token(b_token_balance);
-                collateral_base += asset_to_base
-                    .fixed_mul_floor(asset_collateral, reserve.scalar)
+                let effective_collateral = asset_collateral
+                    .fixed_mul_floor(i128(reserve.c_factor), SCALAR_7)
                     .unwrap_optimized();
-                collateral_raw += asset_to_base
-                    .fixed_mul_floor(
-                        reserve.to_asset_from_b_token(b_token_balance),
-                        reserve.scalar,
-                    )
+                reserve_collateral_base = asset_to_base
+                    .fixed_mul_floor(effective_collateral, reserve.scalar)
                     .unwrap_optimized();
+                collateral_base += reserve_collateral_base;
             }
 
-            if d_token_balance > 0 {
+            let mut reserve_liability_base = 0;
+            if asset_liability > 0 {
                 // append users effective liability to liability_base
-                let asset_liability = reserve.to_effective_asset_from_d_token(d_token_balance);
-                liability_base += asset_to_base
-                    .fixed_mul_ceil(asset_liability, reserve.scalar)
+                let effective_liability = asset_liability
+                    .fixed_div_ceil(i128(reserve.l_factor), SCALAR_7)
                     .unwrap_optimized();
-                liability_raw += asset_to_base
-                    .fixed_mul_ceil(
-                        reserve.to_asset_from_d_token(d_token_balance),
-                        reserve.scalar,
-                    )
+                reserve_liability_base = asset_to_base
+                    .fixed_mul_ceil(effective_liability, reserve.scalar)
                     .unwrap_optimized();
+                liability_base += reserve_liability_base;
+            }
+
+            if let Some(values) = values.as_deref_mut() {
+                values.set(
+                    i,
+                    PositionValue {
+                        collateral: reserve_collateral_raw,
+                        liabilities: reserve_liability_raw,
+                        effective_collateral: reserve_collateral_base,
+                        effective_liabilities: reserve_liability_base,
+                    },
+                );
             }
 
             pool.cache_reserve(reserve);
         }
 
+        if let Some(category) = emode_category {
+            if in_category {
+                collateral_base = collateral_raw
+                    .fixed_mul_floor(i128(category.c_factor), SCALAR_7)
+                    .unwrap_optimized();
+                liability_base = liability_raw
+                    .fixed_div_ceil(i128(category.l_factor), SCALAR_7)
+                    .unwrap_optimized();
+                // the category factors apply to the total value of the positions, so the
+                // effective values of each reserve can round differently than the totals
+                if let Some(values) = values {
+                    for (index, mut value) in values.iter() {
+                        value.effective_collateral = value
+                            .collateral
+                            .fixed_mul_floor(i128(category.c_factor), SCALAR_7)
+                            .unwrap_optimized();
+                        value.effective_liabilities = value
+                            .liabilities
+                            .fixed_div_ceil(i128(category.l_factor), SCALAR_7)
+                            .unwrap_optimized();
+                        values.set(index, value);
+                    }
+                }
+            }
+        }
+
         PositionData {
             collateral_base,
             collateral_raw,
             liability_base,
             liability_raw,
-            scalar: oracle_scalar,
+            scalar: SCALAR_7,
         }
     }
 
@@ -88,8 +262,25 @@ impl PositionData {
             .unwrap_optimized()
     }
 
+    /// Return the health factor data, with the health factor expressed in 7 decimals
+    pub fn as_health_factor_data(&self) -> HealthFactorData {
+        let health_factor = if self.liability_base == 0 {
+            i128::MAX
+        } else {
+            self.as_health_factor()
+                .fixed_mul_floor(SCALAR_7, self.scalar)
+                .unwrap_optimized()
+        };
+        HealthFactorData {
+            collateral: self.collateral_base,
+            liabilities: self.liability_base,
+            health_factor,
+        }
+    }
+
     // Check if the position data is over a maximum health factor
     // Note: max must be 7 decimals
+    #[cfg(feature = "auctions")]
     pub fn is_hf_over(&self, max: i128) -> bool {
         if self.liability_base == 0 {
             return true;
@@ -116,12 +307,70 @@ impl PositionData {
         }
         false
     }
+
+    /// Return the leverage of the positions, as the gross exposure (collateral plus liabilities)
+    /// over the net equity (collateral minus liabilities), expressed in 7 decimals. i128::MAX if
+    /// the positions have no net equity
+    pub fn as_leverage(&self) -> i128 {
+        let equity = self.collateral_raw - self.liability_raw;
+        if equity <= 0 {
+            return i128::MAX;
+        }
+        (self.collateral_raw + self.liability_raw)
+            .fixed_div_floor(equity, SCALAR_7)
+            .unwrap_optimized()
+    }
+
+    /// Check if the position data is over a maximum leverage. A max of 0 disables the check
+    /// Note: max must be 7 decimals
+    pub fn is_leverage_over(&self, max: i128) -> bool {
+        if max == 0 || self.liability_raw == 0 {
+            return false;
+        }
+        self.as_leverage() > max
+    }
+}
+
+/// Calculate the net equity of a set of positions denominated in the base asset, or 0 if the
+/// liabilities exceed the collateral
+///
+/// ### Arguments
+/// * pool - The pool
+/// * positions - The positions to calculate the net equity of
+/// * reserve_list - The reserve list of the pool
+fn calc_net_equity(
+    e: &Env,
+    pool: &mut Pool,
+    positions: &Positions,
+    reserve_list: &Vec<Address>,
+) -> i128 {
+    let mut equity = 0;
+    for i in 0..reserve_list.len() {
+        let b_token_balance = positions.collateral.get(i).unwrap_or(0);
+        let d_token_balance = positions.liabilities.get(i).unwrap_or(0);
+        if b_token_balance == 0 && d_token_balance == 0 {
+            continue;
+        }
+        let reserve = pool.load_reserve(e, &reserve_list.get_unchecked(i), false);
+        let asset_to_base = pool.load_price(e, &reserve.asset);
+        equity += asset_to_base
+            .fixed_mul_floor(reserve.to_asset_from_b_token(b_token_balance), reserve.scalar)
+            .unwrap_optimized();
+        equity -= asset_to_base
+            .fixed_mul_ceil(reserve.to_asset_from_d_token(d_token_balance), reserve.scalar)
+            .unwrap_optimized();
+        pool.cache_reserve(reserve);
+    }
+    equity.max(0)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{storage::PoolConfig, testutils};
+    use crate::{
+        storage::{EModeCategory, PoolConfig},
+        testutils,
+    };
     use sep_40_oracle::testutils::Asset;
     use soroban_sdk::{
         map,
@@ -129,13 +378,193 @@ mod tests {
         vec, Address, Symbol,
     };
 
+    /// Create a pool with three reserves in e-mode category 1 and a set of positions against it
+    fn setup_positions(e: &Env, hf_strategy: u32) -> (Address, Positions) {
+        let bombadil = Address::generate(e);
+        let pool = testutils::create_pool(e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(e);
+
+        let (underlying_0, _) = testutils::create_token_contract(e, &bombadil);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.category = 1;
+        testutils::create_reserve(e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, _) = testutils::create_token_contract(e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.decimals = 9;
+        reserve_config.c_factor = 0_8500000;
+        reserve_config.l_factor = 0_8000000;
+        reserve_config.category = 1;
+        reserve_data.b_supply = 100_000_000_000;
+        reserve_data.d_supply = 70_000_000_000;
+        reserve_data.b_rate = 1_100_000_000_000;
+        reserve_data.d_rate = 1_150_000_000_000;
+        reserve_config.index = 1;
+        testutils::create_reserve(e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        let (underlying_2, _) = testutils::create_token_contract(e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.decimals = 6;
+        reserve_config.index = 2;
+        reserve_config.category = 1;
+        reserve_data.b_supply = 10_000_000;
+        reserve_data.d_supply = 5_000_000;
+        reserve_data.b_rate = 1_001_100_000_000;
+        reserve_data.d_rate = 1_001_200_000_000;
+        testutils::create_reserve(e, &pool, &underlying_2, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(e, "USD")),
+            &vec![
+                e,
+                Asset::Stellar(underlying_0),
+                Asset::Stellar(underlying_1),
+                Asset::Stellar(underlying_2),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![e, 1_0000000, 2_5000000, 1000_0000000]);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 0,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 5,
+            flash_loan_fee: 0,
+            hf_strategy,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(e, &pool_config);
+            storage::set_emode_category(
+                e,
+                1,
+                &EModeCategory {
+                    c_factor: 0_9000000,
+                    l_factor: 0_9000000,
+                },
+            );
+        });
+
+        let positions = Positions {
+            liabilities: map![e, (0, 1_5000000), (1, 50_987_654_321)],
+            collateral: map![e, (0, 100_1234567), (2, 0_250_000)],
+            supply: map![e, (1, 120_987_654_321)],
+        };
+        (pool, positions)
+    }
+
     #[test]
     fn test_calculate_from_positions() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths();
 
+        let (pool, positions) = setup_positions(&e, HealthStrategy::Standard as u32);
+        e.as_contract(&pool, || {
+            let mut pool = Pool::load(&e);
+            // e-mode categories are ignored by the standard strategy
+            let position_data =
+                PositionData::calculate_from_positions(&e, &mut pool, &positions, 1);
+            assert_eq!(position_data.collateral_base, 262_7985925_0000000);
+            assert_eq!(position_data.liability_base, 185_2368827_2000000);
+            assert_eq!(position_data.collateral_raw, 350_3984567_0000000);
+            assert_eq!(position_data.liability_raw, 148_0895061_7500000);
+            assert_eq!(position_data.scalar, SCALAR_7);
+        });
+    }
+
+    #[test]
+    fn test_calculate_from_positions_emode() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+
+        let (pool, positions) = setup_positions(&e, HealthStrategy::EMode as u32);
+        e.as_contract(&pool, || {
+            let mut pool = Pool::load(&e);
+            let position_data =
+                PositionData::calculate_from_positions(&e, &mut pool, &positions, 1);
+            assert_eq!(position_data.collateral_base, 315_3586110_3000000);
+            assert_eq!(position_data.liability_base, 164_5438957_5000000);
+            assert_eq!(position_data.collateral_raw, 350_3984567_0000000);
+            assert_eq!(position_data.liability_raw, 148_0895061_7500000);
+
+            // positions outside of e-mode use the reserve factors
+            let position_data =
+                PositionData::calculate_from_positions(&e, &mut pool, &positions, 0);
+            assert_eq!(position_data.collateral_base, 262_7985925_0000000);
+            assert_eq!(position_data.liability_base, 185_2368827_2000000);
+        });
+    }
+
+    #[test]
+    fn test_calculate_from_positions_portfolio_margin() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+
+        let (pool, positions) = setup_positions(&e, HealthStrategy::PortfolioMargin as u32);
+        e.as_contract(&pool, || {
+            let mut pool = Pool::load(&e);
+            // the 1.5 liability of reserve 0 is offset by its collateral
+            let position_data =
+                PositionData::calculate_from_positions(&e, &mut pool, &positions, 0);
+            assert_eq!(position_data.collateral_base, 261_6735925_0000000);
+            assert_eq!(position_data.liability_base, 183_2368827_2000000);
+            assert_eq!(position_data.collateral_raw, 350_3984567_0000000);
+            assert_eq!(position_data.liability_raw, 148_0895061_7500000);
+        });
+    }
+
+    #[test]
+    fn test_calculate_from_positions_portfolio_margin_limited_to_equity() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+
+        let (pool, _) = setup_positions(&e, HealthStrategy::PortfolioMargin as u32);
+        // 10 of equity levered into 100 of collateral and 90 of liabilities in reserve 0
+        let positions = Positions {
+            liabilities: map![&e, (0, 90_0000000)],
+            collateral: map![&e, (0, 100_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            let mut pool = Pool::load(&e);
+            // only 10 of the positions offset each other, the rest is weighted
+            let position_data =
+                PositionData::calculate_from_positions(&e, &mut pool, &positions, 0);
+            assert_eq!(position_data.collateral_base, 67_5000000_0000000);
+            assert_eq!(position_data.liability_base, 106_6666667_0000000);
+            assert_eq!(position_data.collateral_raw, 100_0000000_0000000);
+            assert_eq!(position_data.liability_raw, 90_0000000_0000000);
+        });
+    }
+
+    #[test]
+    fn test_load_user_health_factor() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+
         let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
@@ -150,8 +579,8 @@ mod tests {
         reserve_config.l_factor = 0_8000000;
         reserve_data.b_supply = 100_000_000_000;
         reserve_data.d_supply = 70_000_000_000;
-        reserve_data.b_rate = 1_100_000_000;
-        reserve_data.d_rate = 1_150_000_000;
+        reserve_data.b_rate = 1_100_000_000_000;
+        reserve_data.d_rate = 1_150_000_000_000;
         reserve_config.index = 1;
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
@@ -161,8 +590,8 @@ mod tests {
         reserve_config.index = 2;
         reserve_data.b_supply = 10_000_000;
         reserve_data.d_supply = 5_000_000;
-        reserve_data.b_rate = 1_001_100_000;
-        reserve_data.d_rate = 1_001_200_000;
+        reserve_data.b_rate = 1_001_100_000_000;
+        reserve_data.d_rate = 1_001_200_000_000;
         testutils::create_reserve(&e, &pool, &underlying_2, &reserve_config, &reserve_data);
 
         oracle_client.set_data(
@@ -194,6 +623,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 5,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
 
         let positions = Positions {
@@ -203,13 +637,85 @@ mod tests {
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
-            let mut pool = Pool::load(&e);
-            let position_data = PositionData::calculate_from_positions(&e, &mut pool, &positions);
-            assert_eq!(position_data.collateral_base, 262_7985925);
-            assert_eq!(position_data.liability_base, 185_2368828);
-            assert_eq!(position_data.collateral_raw, 350_3984567);
-            assert_eq!(position_data.liability_raw, 148_0895062);
-            assert_eq!(position_data.scalar, SCALAR_7);
+            storage::set_user_positions(&e, &samwise, &positions);
+
+            let health_factor = load_user_health_factor(&e, &samwise);
+            assert_eq!(health_factor.collateral, 262_7985925_0000000);
+            assert_eq!(health_factor.liabilities, 185_2368827_2000000);
+            assert_eq!(health_factor.health_factor, 1_4187163);
+
+            // no liabilities
+            let health_factor = load_user_health_factor(&e, &frodo);
+            assert_eq!(health_factor.collateral, 0);
+            assert_eq!(health_factor.liabilities, 0);
+            assert_eq!(health_factor.health_factor, i128::MAX);
+        });
+    }
+
+    #[test]
+    fn test_load_user_positions_value() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+
+        let samwise = Address::generate(&e);
+        let (pool, positions) = setup_positions(&e, HealthStrategy::Standard as u32);
+        e.as_contract(&pool, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+
+            let values = load_user_positions_value(&e, &samwise);
+            assert_eq!(values.len(), 3);
+            let value_0 = values.get_unchecked(0);
+            assert_eq!(value_0.collateral, 100_1234567_0000000);
+            assert_eq!(value_0.liabilities, 1_5000000_0000000);
+            assert_eq!(value_0.effective_collateral, 75_0925925_0000000);
+            assert_eq!(value_0.effective_liabilities, 2_0000000_0000000);
+            let value_1 = values.get_unchecked(1);
+            assert_eq!(value_1.collateral, 0);
+            assert_eq!(value_1.liabilities, 146_5895061_7500000);
+            assert_eq!(value_1.effective_collateral, 0);
+            assert_eq!(value_1.effective_liabilities, 183_2368827_2000000);
+            let value_2 = values.get_unchecked(2);
+            assert_eq!(value_2.collateral, 250_2750000_0000000);
+            assert_eq!(value_2.liabilities, 0);
+            assert_eq!(value_2.effective_collateral, 187_7060000_0000000);
+            assert_eq!(value_2.effective_liabilities, 0);
+
+            // the values sum to the health factor totals
+            let health_factor = load_user_health_factor(&e, &samwise);
+            assert_eq!(
+                value_0.effective_collateral + value_2.effective_collateral,
+                health_factor.collateral
+            );
+            assert_eq!(
+                value_0.effective_liabilities + value_1.effective_liabilities,
+                health_factor.liabilities
+            );
+        });
+    }
+
+    #[test]
+    fn test_load_user_positions_value_emode() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+
+        let samwise = Address::generate(&e);
+        let (pool, positions) = setup_positions(&e, HealthStrategy::EMode as u32);
+        e.as_contract(&pool, || {
+            storage::set_user_positions(&e, &samwise, &positions);
+            storage::set_user_emode(&e, &samwise, 1);
+
+            let values = load_user_positions_value(&e, &samwise);
+            let value_0 = values.get_unchecked(0);
+            assert_eq!(value_0.collateral, 100_1234567_0000000);
+            assert_eq!(value_0.effective_collateral, 90_1111110_3000000);
+            assert_eq!(value_0.effective_liabilities, 1_6666666_6666667);
+            let value_1 = values.get_unchecked(1);
+            assert_eq!(value_1.liabilities, 146_5895061_7500000);
+            assert_eq!(value_1.effective_liabilities, 162_8772290_8333334);
+            let value_2 = values.get_unchecked(2);
+            assert_eq!(value_2.effective_collateral, 225_2475000_0000000);
         });
     }
 
@@ -288,6 +794,36 @@ mod tests {
         assert!(result);
     }
 
+    #[test]
+    fn test_as_leverage() {
+        let position_data = PositionData {
+            collateral_base: 9_0000000,
+            collateral_raw: 12_0000000,
+            liability_base: 8_0000000,
+            liability_raw: 6_0000000,
+            scalar: 1_0000000,
+        };
+
+        assert_eq!(position_data.as_leverage(), 3_0000000);
+        assert!(position_data.is_leverage_over(2_9999999));
+        assert!(!position_data.is_leverage_over(3_0000000));
+        assert!(!position_data.is_leverage_over(0));
+    }
+
+    #[test]
+    fn test_as_leverage_no_equity() {
+        let position_data = PositionData {
+            collateral_base: 7_5000000,
+            collateral_raw: 10_0000000,
+            liability_base: 13_3333334,
+            liability_raw: 10_0000000,
+            scalar: 1_0000000,
+        };
+
+        assert_eq!(position_data.as_leverage(), i128::MAX);
+        assert!(position_data.is_leverage_over(100_0000000));
+    }
+
     #[test]
     fn test_is_hf_over() {
         let position_data = PositionData {
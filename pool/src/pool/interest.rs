@@ -3,8 +3,8 @@ use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{unwrap::UnwrapOptimized, Env};
 
 use crate::{
-    constants::{SCALAR_7, SCALAR_9, SECONDS_PER_YEAR},
-    storage::ReserveConfig,
+    constants::{SCALAR_12, SCALAR_7, SCALAR_9, SECONDS_PER_YEAR},
+    storage::{InterestRateConfig, RateBounds, ReserveConfig},
 };
 
 /// Calculates the loan accrual ratio for the Reserve based on the current utilization and
@@ -12,60 +12,31 @@ use crate::{
 ///
 /// ### Arguments
 /// * `config` - The Reserve config to calculate an accrual for
+/// * `ir_config` - The custom interest rate curve of the Reserve, if any. The target utilization
+///                 of the Reserve config is still used to update the rate modifier.
+/// * `rate_bounds` - The bounds on the borrow rate of the Reserve, if any
+/// * `ir_mod_half_life` - The half-life in seconds of the rate modifier's decay towards 1 while
+///                        the Reserve is under its target utilization, if any
 /// * `cur_util` - The current utilization rate of the reserve (7 decimals)
 /// * `ir_mod` - The current interest rate modifier of the reserve (9 decimals)
 /// * `last_block` - The last block an accrual was performed
 ///
 /// ### Returns
-/// * (i128, i128) - (accrual amount scaled to 9 decimal places, new interest rate modifier scaled to 9 decimal places)
-#[allow(clippy::zero_prefixed_literal)]
+/// * (i128, i128, Option<i128>) - (accrual amount scaled to 12 decimal places, new interest rate modifier scaled to 9 decimal places,
+///   the interest rate if it was clamped to the rate bounds)
 pub fn calc_accrual(
     e: &Env,
     config: &ReserveConfig,
+    ir_config: &Option<InterestRateConfig>,
+    rate_bounds: &Option<RateBounds>,
+    ir_mod_half_life: &Option<u64>,
     cur_util: i128,
     ir_mod: i128,
     last_time: u64,
-) -> (i128, i128) {
-    let cur_ir: i128;
+) -> (i128, i128, Option<i128>) {
+    let curve_ir = calc_interest_rate(config, ir_config, cur_util, ir_mod);
+    let cur_ir = clamp_interest_rate(curve_ir, rate_bounds);
     let target_util: i128 = i128(config.util);
-    if cur_util <= target_util {
-        let util_scalar = cur_util
-            .fixed_div_ceil(target_util, SCALAR_7)
-            .unwrap_optimized();
-        let base_rate = util_scalar
-            .fixed_mul_ceil(i128(config.r_one), SCALAR_7)
-            .unwrap_optimized()
-            + i128(config.r_base);
-
-        cur_ir = base_rate
-            .fixed_mul_ceil(ir_mod, SCALAR_9)
-            .unwrap_optimized();
-    } else if cur_util <= 0_9500000 {
-        let util_scalar = (cur_util - target_util)
-            .fixed_div_ceil(0_9500000 - target_util, SCALAR_7)
-            .unwrap_optimized();
-        let base_rate = util_scalar
-            .fixed_mul_ceil(i128(config.r_two), SCALAR_7)
-            .unwrap_optimized()
-            + i128(config.r_one)
-            + i128(config.r_base);
-
-        cur_ir = base_rate
-            .fixed_mul_ceil(ir_mod, SCALAR_9)
-            .unwrap_optimized();
-    } else {
-        let util_scalar = (cur_util - 0_9500000)
-            .fixed_div_ceil(0_0500000, SCALAR_7)
-            .unwrap_optimized();
-        let extra_rate = util_scalar
-            .fixed_mul_ceil(i128(config.r_three), SCALAR_7)
-            .unwrap_optimized();
-
-        let intersection = ir_mod
-            .fixed_mul_ceil(i128(config.r_two + config.r_one + config.r_base), SCALAR_9)
-            .unwrap_optimized();
-        cur_ir = extra_rate + intersection;
-    }
 
     // update rate_modifier
     // scale delta blocks and util dif to 9 decimals
@@ -95,7 +66,12 @@ pub fn calc_accrual(
         let rate_dif = util_error
             .fixed_mul_ceil(i128(config.reactivity), SCALAR_7)
             .unwrap_optimized();
-        let next_ir_mod = ir_mod + rate_dif;
+        let mut next_ir_mod = ir_mod + rate_dif;
+        // decay the rate modifier towards 1 while under-utilized
+        if let Some(half_life) = ir_mod_half_life {
+            next_ir_mod =
+                calc_ir_mod_decay(next_ir_mod, *half_life, e.ledger().timestamp() - last_time);
+        }
         let ir_mod_min = SCALAR_9 / 10;
         if next_ir_mod < ir_mod_min {
             new_ir_mod = ir_mod_min;
@@ -105,20 +81,162 @@ pub fn calc_accrual(
     }
 
     // calc accrual amount over blocks
-    let time_weight = delta_time_scaled / SECONDS_PER_YEAR;
+    // scale delta blocks and the interest rate to 12 decimals to limit rounding on the d_rate
+    let time_weight = i128(e.ledger().timestamp() - last_time) * SCALAR_12 / SECONDS_PER_YEAR;
     (
-        SCALAR_9
+        SCALAR_12
             + time_weight
-                .fixed_mul_ceil(cur_ir * 100, SCALAR_9)
+                .fixed_mul_ceil(cur_ir * 100_000, SCALAR_12)
                 .unwrap_optimized(),
         new_ir_mod,
+        if cur_ir != curve_ir { Some(cur_ir) } else { None },
     )
 }
 
+/// Decay an interest rate modifier towards 1, halving its distance from 1 every half-life.
+///
+/// Partial half-lives are interpolated linearly, which slightly understates the decay.
+///
+/// ### Arguments
+/// * `ir_mod` - The interest rate modifier (9 decimals)
+/// * `half_life` - The half-life of the decay in seconds
+/// * `delta_time` - The time in seconds to decay the modifier over
+pub fn calc_ir_mod_decay(ir_mod: i128, half_life: u64, delta_time: u64) -> i128 {
+    let halvings = delta_time / half_life;
+    if halvings >= 64 {
+        return SCALAR_9;
+    }
+    let mut ir_mod_dif = (ir_mod - SCALAR_9) / (1i128 << halvings);
+    ir_mod_dif -= ir_mod_dif * i128(delta_time % half_life) / i128(2 * half_life);
+    SCALAR_9 + ir_mod_dif
+}
+
+/// Clamp an interest rate to the bounds on the borrow rate of a reserve
+///
+/// ### Arguments
+/// * `rate` - The interest rate (7 decimals)
+/// * `rate_bounds` - The bounds on the borrow rate of the Reserve, if any
+pub fn clamp_interest_rate(rate: i128, rate_bounds: &Option<RateBounds>) -> i128 {
+    match rate_bounds {
+        Some(bounds) => rate.clamp(i128(bounds.min_rate), i128(bounds.max_rate)),
+        None => rate,
+    }
+}
+
+/// Calculates the current interest rate for the Reserve based on the current utilization and
+/// rate modifier for the reserve.
+///
+/// ### Arguments
+/// * `config` - The Reserve config to calculate the interest rate for
+/// * `ir_config` - The custom interest rate curve of the Reserve, if any
+/// * `cur_util` - The current utilization rate of the reserve (7 decimals)
+/// * `ir_mod` - The current interest rate modifier of the reserve (9 decimals)
+///
+/// ### Returns
+/// * i128 - The annual interest rate scaled to 7 decimal places
+#[allow(clippy::zero_prefixed_literal)]
+pub fn calc_interest_rate(
+    config: &ReserveConfig,
+    ir_config: &Option<InterestRateConfig>,
+    cur_util: i128,
+    ir_mod: i128,
+) -> i128 {
+    let target_util: i128 = i128(config.util);
+    if let Some(ir_config) = ir_config {
+        calc_curve_rate(ir_config, i128(config.r_base), cur_util, ir_mod)
+    } else if cur_util <= target_util {
+        let util_scalar = cur_util
+            .fixed_div_ceil(target_util, SCALAR_7)
+            .unwrap_optimized();
+        let base_rate = util_scalar
+            .fixed_mul_ceil(i128(config.r_one), SCALAR_7)
+            .unwrap_optimized()
+            + i128(config.r_base);
+
+        base_rate
+            .fixed_mul_ceil(ir_mod, SCALAR_9)
+            .unwrap_optimized()
+    } else if cur_util <= 0_9500000 {
+        let util_scalar = (cur_util - target_util)
+            .fixed_div_ceil(0_9500000 - target_util, SCALAR_7)
+            .unwrap_optimized();
+        let base_rate = util_scalar
+            .fixed_mul_ceil(i128(config.r_two), SCALAR_7)
+            .unwrap_optimized()
+            + i128(config.r_one)
+            + i128(config.r_base);
+
+        base_rate
+            .fixed_mul_ceil(ir_mod, SCALAR_9)
+            .unwrap_optimized()
+    } else {
+        let util_scalar = (cur_util - 0_9500000)
+            .fixed_div_ceil(0_0500000, SCALAR_7)
+            .unwrap_optimized();
+        let extra_rate = util_scalar
+            .fixed_mul_ceil(i128(config.r_three), SCALAR_7)
+            .unwrap_optimized();
+
+        let intersection = ir_mod
+            .fixed_mul_ceil(i128(config.r_two + config.r_one + config.r_base), SCALAR_9)
+            .unwrap_optimized();
+        extra_rate + intersection
+    }
+}
+
+/// Calculates the interest rate of a custom interest rate curve at the current utilization.
+///
+/// The rate modifier is applied to the rate up to the last breakpoint, and the rate increase
+/// across the last segment is not modified.
+///
+/// ### Arguments
+/// * `ir_config` - The custom interest rate curve
+/// * `r_base` - The base rate of the reserve (7 decimals)
+/// * `cur_util` - The current utilization rate of the reserve (7 decimals)
+/// * `ir_mod` - The current interest rate modifier of the reserve (9 decimals)
+fn calc_curve_rate(
+    ir_config: &InterestRateConfig,
+    r_base: i128,
+    cur_util: i128,
+    ir_mod: i128,
+) -> i128 {
+    let mut base_rate = r_base;
+    let mut segment_start: i128 = 0;
+    for (index, breakpoint) in ir_config.breakpoints.iter().enumerate() {
+        let breakpoint = i128(breakpoint);
+        let slope = i128(ir_config.slopes.get_unchecked(index as u32));
+        if cur_util <= breakpoint {
+            let util_scalar = (cur_util - segment_start)
+                .fixed_div_ceil(breakpoint - segment_start, SCALAR_7)
+                .unwrap_optimized();
+            base_rate += util_scalar
+                .fixed_mul_ceil(slope, SCALAR_7)
+                .unwrap_optimized();
+            return base_rate.fixed_mul_ceil(ir_mod, SCALAR_9).unwrap_optimized();
+        }
+        base_rate += slope;
+        segment_start = breakpoint;
+    }
+
+    let util_scalar = (cur_util - segment_start)
+        .fixed_div_ceil(SCALAR_7 - segment_start, SCALAR_7)
+        .unwrap_optimized();
+    let extra_rate = util_scalar
+        .fixed_mul_ceil(
+            i128(ir_config.slopes.get_unchecked(ir_config.breakpoints.len())),
+            SCALAR_7,
+        )
+        .unwrap_optimized();
+    extra_rate + base_rate.fixed_mul_ceil(ir_mod, SCALAR_9).unwrap_optimized()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use soroban_sdk::testutils::{Ledger, LedgerInfo};
+    use soroban_sdk::{
+        testutils::{Ledger, LedgerInfo},
+        vec,
+    };
 
     #[test]
     fn test_calc_accrual_util_under_target() {
@@ -136,8 +254,13 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 0_0000020,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             index: 0,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let ir_mod: i128 = 1_000_000_000;
 
@@ -152,9 +275,10 @@ mod tests {
             max_entry_ttl: 3110400,
         });
 
-        let (accrual, ir_mod) = calc_accrual(&e, &reserve_config, 0_6565656, ir_mod, 0);
+        let (accrual, ir_mod, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_6565656, ir_mod, 0);
 
-        assert_eq!(accrual, 1_000_000_853);
+        assert_eq!(accrual, 1_000_000_852_536);
         assert_eq!(ir_mod, 0_999_906_566);
     }
 
@@ -174,8 +298,13 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 0_0000020,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             index: 0,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let ir_mod: i128 = 1_000_000_000;
 
@@ -190,9 +319,10 @@ mod tests {
             max_entry_ttl: 3110400,
         });
 
-        let (accrual, ir_mod) = calc_accrual(&e, &reserve_config, 0_7979797, ir_mod, 0);
+        let (accrual, ir_mod, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_7979797, ir_mod, 0);
 
-        assert_eq!(accrual, 1_000_002_853);
+        assert_eq!(accrual, 1_000_002_853_078);
         assert_eq!(ir_mod, 1_000_047_979);
     }
 
@@ -212,8 +342,13 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 0_0000020,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             index: 0,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let ir_mod: i128 = 1_000_000_000;
 
@@ -228,9 +363,10 @@ mod tests {
             max_entry_ttl: 3110400,
         });
 
-        let (accrual, ir_mod) = calc_accrual(&e, &reserve_config, 0_9696969, ir_mod, 0);
+        let (accrual, ir_mod, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_9696969, ir_mod, 0);
 
-        assert_eq!(accrual, 1_000_018_247);
+        assert_eq!(accrual, 1_000_018_247_510);
         assert_eq!(ir_mod, 1_000_219_696);
     }
 
@@ -250,8 +386,13 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 0_0000020,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             index: 0,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let ir_mod: i128 = 9_997_000_000;
 
@@ -266,7 +407,8 @@ mod tests {
             max_entry_ttl: 3110400,
         });
 
-        let (_accrual, ir_mod) = calc_accrual(&e, &reserve_config, 0_9696969, ir_mod, 0);
+        let (_accrual, ir_mod, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_9696969, ir_mod, 0);
 
         assert_eq!(ir_mod, 10_000_000_000);
     }
@@ -287,8 +429,13 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 0_0000020,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             index: 0,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let ir_mod: i128 = 0_150_000_000;
 
@@ -303,7 +450,8 @@ mod tests {
             max_entry_ttl: 3110400,
         });
 
-        let (_accrual, ir_mod) = calc_accrual(&e, &reserve_config, 0_2020202, ir_mod, 0);
+        let (_accrual, ir_mod, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_2020202, ir_mod, 0);
 
         assert_eq!(ir_mod, 0_100_000_000);
     }
@@ -324,8 +472,13 @@ mod tests {
             r_three: 1_5000000,
             reactivity: 0_0000020,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             index: 0,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let ir_mod: i128 = 0_100_000_000;
 
@@ -340,9 +493,10 @@ mod tests {
             max_entry_ttl: 3110400,
         });
 
-        let (accrual, ir_mod) = calc_accrual(&e, &reserve_config, 0_0500000, ir_mod, 500);
+        let (accrual, ir_mod, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_0500000, ir_mod, 500);
 
-        assert_eq!(accrual, 1_000_000_001);
+        assert_eq!(accrual, 1_000_000_000_043);
         assert_eq!(ir_mod, 0_100_000_000);
     }
 
@@ -362,8 +516,13 @@ mod tests {
             r_three: 0,
             reactivity: 0_0000020,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             index: 0,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         };
         let ir_mod: i128 = 1_000_000_000;
 
@@ -378,18 +537,278 @@ mod tests {
             max_entry_ttl: 3110400,
         });
 
-        let (accrual_0, ir_mod_0) = calc_accrual(&e, &reserve_config, 0, ir_mod, 0);
-        let (accrual_1, ir_mod_1) = calc_accrual(&e, &reserve_config, 0_6565656, ir_mod, 0);
-        let (accrual_2, ir_mod_2) = calc_accrual(&e, &reserve_config, 0_7565656, ir_mod, 0);
-        let (accrual_3, ir_mod_3) = calc_accrual(&e, &reserve_config, 0_9565656, ir_mod, 0);
+        let (accrual_0, ir_mod_0, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0, ir_mod, 0);
+        let (accrual_1, ir_mod_1, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_6565656, ir_mod, 0);
+        let (accrual_2, ir_mod_2, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_7565656, ir_mod, 0);
+        let (accrual_3, ir_mod_3, _) =
+            calc_accrual(&e, &reserve_config, &None, &None, &None, 0_9565656, ir_mod, 0);
 
-        assert_eq!(accrual_0, 1_000_003_964);
+        assert_eq!(accrual_0, 1_000_003_963_724);
         assert_eq!(ir_mod_0, 0_999_250_000);
-        assert_eq!(accrual_1, 1_000_003_964);
+        assert_eq!(accrual_1, 1_000_003_963_724);
         assert_eq!(ir_mod_1, 0_999_906_566);
-        assert_eq!(accrual_2, 1_000_003_964);
+        assert_eq!(accrual_2, 1_000_003_963_724);
         assert_eq!(ir_mod_2, 1_000_006_565);
-        assert_eq!(accrual_3, 1_000_003_964);
+        assert_eq!(accrual_3, 1_000_003_963_724);
         assert_eq!(ir_mod_3, 1_000_206_565);
     }
+
+    #[test]
+    fn test_calc_accrual_custom_curve() {
+        let e = Env::default();
+
+        let reserve_config = ReserveConfig {
+            decimals: 7,
+            c_factor: 0_7500000,
+            l_factor: 0_7500000,
+            util: 0_7500000,
+            max_util: 0_9500000,
+            r_base: 0_0100000,
+            r_one: 0_0500000,
+            r_two: 0_5000000,
+            r_three: 1_5000000,
+            reactivity: 0_0000020,
+            collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
+            index: 0,
+            enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
+        };
+        let ir_config = InterestRateConfig {
+            breakpoints: vec![&e, 0_5000000, 0_8000000],
+            slopes: vec![&e, 0_0400000, 0_1000000, 1_0000000],
+        };
+        let ir_mod: i128 = 1_000_000_000;
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 500,
+            protocol_version: 22,
+            sequence_number: 100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        // halfway through the second segment, the rate is 0.01 + 0.04 + 0.05
+        let (accrual, ir_mod, _) =
+            calc_accrual(&e, &reserve_config, &Some(ir_config), &None, &None, 0_6500000, ir_mod, 0);
+
+        assert_eq!(accrual, 1_000_001_585_490);
+        assert_eq!(ir_mod, 0_999_900_000);
+    }
+
+    #[test]
+    fn test_calc_accrual_custom_curve_last_segment() {
+        let e = Env::default();
+
+        let reserve_config = ReserveConfig {
+            decimals: 7,
+            c_factor: 0_7500000,
+            l_factor: 0_7500000,
+            util: 0_7500000,
+            max_util: 0_9500000,
+            r_base: 0_0100000,
+            r_one: 0_0500000,
+            r_two: 0_5000000,
+            r_three: 1_5000000,
+            reactivity: 0_0000020,
+            collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
+            index: 0,
+            enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
+        };
+        let ir_config = InterestRateConfig {
+            breakpoints: vec![&e, 0_5000000, 0_8000000],
+            slopes: vec![&e, 0_0400000, 0_1000000, 1_0000000],
+        };
+        let ir_mod: i128 = 2_000_000_000;
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 500,
+            protocol_version: 22,
+            sequence_number: 100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        // the rate modifier only applies up to the last breakpoint, 0.15 * 2 + 0.5
+        let (accrual, ir_mod, _) =
+            calc_accrual(&e, &reserve_config, &Some(ir_config), &None, &None, 0_9000000, ir_mod, 0);
+
+        assert_eq!(accrual, 1_000_012_683_916);
+        assert_eq!(ir_mod, 2_000_150_000);
+    }
+
+    #[test]
+    fn test_calc_accrual_rate_bounds() {
+        let e = Env::default();
+
+        let reserve_config = ReserveConfig {
+            decimals: 7,
+            c_factor: 0_7500000,
+            l_factor: 0_7500000,
+            util: 0_7500000,
+            max_util: 0_9500000,
+            r_base: 0_0100000,
+            r_one: 0_0500000,
+            r_two: 0_5000000,
+            r_three: 1_5000000,
+            reactivity: 0_0000020,
+            collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
+            index: 0,
+            enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
+        };
+        let ir_config = InterestRateConfig {
+            breakpoints: vec![&e, 0_5000000, 0_8000000],
+            slopes: vec![&e, 0_0400000, 0_1000000, 1_0000000],
+        };
+        let ir_mod: i128 = 2_000_000_000;
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 500,
+            protocol_version: 22,
+            sequence_number: 100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        // the curve's rate of 0.8 is clamped to the max rate
+        let rate_bounds = Some(RateBounds {
+            min_rate: 0_2000000,
+            max_rate: 0_5000000,
+        });
+        let ir_config = Some(ir_config);
+        let (accrual, ir_mod, clamped) = calc_accrual(
+            &e,
+            &reserve_config,
+            &ir_config,
+            &rate_bounds,
+            &None,
+            0_9000000,
+            ir_mod,
+            0,
+        );
+
+        assert_eq!(accrual, 1_000_007_927_448);
+        assert_eq!(ir_mod, 2_000_150_000);
+        assert_eq!(clamped, Some(0_5000000));
+
+        // the curve's rate of 0.1 is clamped to the min rate
+        let (accrual, _, clamped) = calc_accrual(
+            &e,
+            &reserve_config,
+            &ir_config,
+            &rate_bounds,
+            &None,
+            0_6500000,
+            SCALAR_9,
+            0,
+        );
+
+        assert_eq!(accrual, 1_000_003_170_979);
+        assert_eq!(clamped, Some(0_2000000));
+    }
+
+    #[test]
+    fn test_calc_ir_mod_decay() {
+        // whole half-lives halve the distance from 1
+        assert_eq!(calc_ir_mod_decay(3_000_000_000, 1000, 0), 3_000_000_000);
+        assert_eq!(calc_ir_mod_decay(3_000_000_000, 1000, 1000), 2_000_000_000);
+        assert_eq!(calc_ir_mod_decay(3_000_000_000, 1000, 2000), 1_500_000_000);
+        assert_eq!(calc_ir_mod_decay(0_200_000_000, 1000, 1000), 0_600_000_000);
+
+        // partial half-lives are interpolated
+        assert_eq!(calc_ir_mod_decay(3_000_000_000, 1000, 500), 2_500_000_000);
+        assert_eq!(calc_ir_mod_decay(0_200_000_000, 1000, 2500), 0_850_000_000);
+
+        // the modifier settles at 1
+        assert_eq!(calc_ir_mod_decay(10_000_000_000, 1, 64), 1_000_000_000);
+        assert_eq!(calc_ir_mod_decay(10_000_000_000, 1, u64::MAX), 1_000_000_000);
+    }
+
+    #[test]
+    fn test_calc_accrual_util_under_target_ir_mod_decay() {
+        let e = Env::default();
+
+        let reserve_config = ReserveConfig {
+            decimals: 7,
+            c_factor: 0_7500000,
+            l_factor: 0_7500000,
+            util: 0_7500000,
+            max_util: 0_9500000,
+            r_base: 0_0100000,
+            r_one: 0_0500000,
+            r_two: 0_5000000,
+            r_three: 1_5000000,
+            reactivity: 0_0000020, // 2e-6
+            collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
+            index: 0,
+            enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
+        };
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 500,
+            protocol_version: 22,
+            sequence_number: 100,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        // the utilization error lowers the modifier to 2.999906566 before it decays
+        let (_, ir_mod, _) = calc_accrual(
+            &e,
+            &reserve_config,
+            &None,
+            &None,
+            &Some(500),
+            0_6565656,
+            3_000_000_000,
+            0,
+        );
+        assert_eq!(ir_mod, 1_999_953_283);
+
+        // the modifier is not decayed when over the target utilization
+        let (_, ir_mod, _) = calc_accrual(
+            &e,
+            &reserve_config,
+            &None,
+            &None,
+            &Some(500),
+            0_7979797,
+            3_000_000_000,
+            0,
+        );
+        assert_eq!(ir_mod, 3_000_047_979);
+    }
 }
@@ -0,0 +1,310 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env};
+
+#[cfg(feature = "auctions")]
+use soroban_sdk::Map;
+
+use crate::{errors::PoolError, storage};
+
+use super::{Pool, Reserve, User};
+
+/// Require that a user's collateral respects isolation mode after supplying collateral to a
+/// reserve. Isolated reserves can only be used as collateral on their own.
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `user` - The user supplying collateral
+/// * `reserve` - The reserve collateral is being supplied to
+///
+/// ### Panics
+/// If the reserve is isolated and the user has other collateral, or if the user has isolated
+/// collateral in another reserve
+pub fn require_isolation_allowed(e: &Env, pool: &mut Pool, user: &User, reserve: &Reserve) {
+    let reserve_list = storage::get_res_list(e);
+    for index in user.positions.collateral.keys().iter() {
+        if index == reserve.index {
+            continue;
+        }
+        if reserve.isolated
+            || pool
+                .load_reserve(e, &reserve_list.get_unchecked(index), false)
+                .isolated
+        {
+            panic_with_error!(e, PoolError::IsolatedCollateral);
+        }
+    }
+}
+
+/// Add debt borrowed by a user to the isolated reserve the user is borrowing against, if any
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `user` - The user borrowing
+/// * `asset` - The underlying asset being borrowed
+/// * `amount` - The amount of the asset being borrowed
+///
+/// ### Panics
+/// If the debt borrowed against the isolated reserve exceeds its debt ceiling
+pub fn add_isolated_debt(e: &Env, pool: &mut Pool, user: &User, asset: &Address, amount: i128) {
+    if let Some(mut isolated_reserve) = load_isolated_collateral(e, pool, user) {
+        let scalar = pool.load_reserve(e, asset, false).scalar;
+        let value = pool
+            .load_price(e, asset)
+            .fixed_mul_ceil(amount, scalar)
+            .unwrap_optimized();
+        isolated_reserve.isolated_debt += value;
+        if isolated_reserve.isolated_debt > isolated_reserve.debt_ceiling {
+            panic_with_error!(e, PoolError::ExceededDebtCeiling);
+        }
+        pool.cache_reserve(isolated_reserve);
+    }
+}
+
+/// Remove debt repaid by a user from the isolated reserve the user is borrowing against, if any
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `user` - The user repaying
+/// * `asset` - The underlying asset being repaid
+/// * `amount` - The amount of the asset being repaid
+pub fn remove_isolated_debt(e: &Env, pool: &mut Pool, user: &User, asset: &Address, amount: i128) {
+    if let Some(mut isolated_reserve) = load_isolated_collateral(e, pool, user) {
+        let scalar = pool.load_reserve(e, asset, false).scalar;
+        let value = pool
+            .load_price(e, asset)
+            .fixed_mul_floor(amount, scalar)
+            .unwrap_optimized();
+        // debt is tracked at the price it was borrowed at, so repayments can exceed it
+        isolated_reserve.isolated_debt = (isolated_reserve.isolated_debt - value).max(0);
+        pool.cache_reserve(isolated_reserve);
+    }
+}
+
+/// Remove the debt liquidated from a user from the isolated reserve the user is borrowing
+/// against, if any. Once all of the user's collateral is liquidated, the user's remaining
+/// liabilities can only be moved to the backstop as bad debt, so they are removed as well.
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `user` - The user being liquidated, before the liquidated positions are removed
+/// * `liquidated_user` - The user being liquidated, after the liquidated positions are removed
+/// * `bid` - The d_tokens liquidated from the user for each underlying asset
+#[cfg(feature = "auctions")]
+pub fn remove_liquidated_isolated_debt(
+    e: &Env,
+    pool: &mut Pool,
+    user: &User,
+    liquidated_user: &User,
+    bid: &Map<Address, i128>,
+) {
+    if load_isolated_collateral(e, pool, user).is_none() {
+        return;
+    }
+    let mut d_tokens = bid.clone();
+    if liquidated_user.positions.collateral.is_empty() {
+        let reserve_list = storage::get_res_list(e);
+        for (index, balance) in liquidated_user.positions.liabilities.iter() {
+            let asset = reserve_list.get_unchecked(index);
+            d_tokens.set(asset.clone(), d_tokens.get(asset).unwrap_or(0) + balance);
+        }
+    }
+    for (asset, amount) in d_tokens.iter() {
+        let amount = pool
+            .load_reserve(e, &asset, false)
+            .to_asset_from_d_token(amount);
+        remove_isolated_debt(e, pool, user, &asset, amount);
+    }
+}
+
+/// Load the isolated reserve a user is borrowing against, if any
+fn load_isolated_collateral(e: &Env, pool: &mut Pool, user: &User) -> Option<Reserve> {
+    let reserve_list = storage::get_res_list(e);
+    for index in user.positions.collateral.keys().iter() {
+        let asset = reserve_list.get_unchecked(index);
+        if pool.load_reserve(e, &asset, false).isolated {
+            return Some(pool.load_reserve(e, &asset, true));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils;
+    use soroban_sdk::{
+        map,
+        testutils::{Address as _, Ledger},
+    };
+
+    #[test]
+    fn test_isolated_debt() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let samwise = Address::generate(&e);
+
+        e.ledger().set_timestamp(600);
+        e.ledger().set_sequence_number(1234);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.isolated = true;
+        reserve_config_0.debt_ceiling = 100_0000000_0000000;
+        reserve_data_0.last_time = 600;
+        let (reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 600;
+        let (pool_address, assets) = testutils::create_pool_with_reserves(
+            &e,
+            &[
+                (reserve_config_0, reserve_data_0, 1_0000000),
+                (reserve_config_1, reserve_data_1, 2_0000000),
+            ],
+        );
+        let underlying_0 = assets.get_unchecked(0);
+        let underlying_1 = assets.get_unchecked(1);
+
+        e.as_contract(&pool_address, || {
+            let mut pool = Pool::load(&e);
+            let mut user = User::load(&e, &samwise);
+            let mut reserve_0 = pool.load_reserve(&e, &underlying_0, true);
+            user.add_collateral(&e, &mut reserve_0, 500_0000000);
+            pool.cache_reserve(reserve_0);
+
+            add_isolated_debt(&e, &mut pool, &user, &underlying_1, 40_0000000);
+            let reserve_0 = pool.load_reserve(&e, &underlying_0, false);
+            assert_eq!(reserve_0.isolated_debt, 80_0000000_0000000);
+
+            remove_isolated_debt(&e, &mut pool, &user, &underlying_1, 10_0000000);
+            let reserve_0 = pool.load_reserve(&e, &underlying_0, false);
+            assert_eq!(reserve_0.isolated_debt, 60_0000000_0000000);
+
+            remove_isolated_debt(&e, &mut pool, &user, &underlying_1, 50_0000000);
+            let reserve_0 = pool.load_reserve(&e, &underlying_0, false);
+            assert_eq!(reserve_0.isolated_debt, 0);
+
+            pool.store_cached_reserves(&e);
+            assert_eq!(storage::get_res_data(&e, &underlying_0).isolated_debt, 0);
+        });
+    }
+
+    #[test]
+    fn test_remove_liquidated_isolated_debt() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let samwise = Address::generate(&e);
+
+        e.ledger().set_timestamp(600);
+        e.ledger().set_sequence_number(1234);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.isolated = true;
+        reserve_config_0.debt_ceiling = 100_0000000_0000000;
+        reserve_data_0.last_time = 600;
+        let (reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 600;
+        let (pool_address, assets) = testutils::create_pool_with_reserves(
+            &e,
+            &[
+                (reserve_config_0, reserve_data_0, 1_0000000),
+                (reserve_config_1, reserve_data_1, 2_0000000),
+            ],
+        );
+        let underlying_0 = assets.get_unchecked(0);
+        let underlying_1 = assets.get_unchecked(1);
+
+        e.as_contract(&pool_address, || {
+            let mut pool = Pool::load(&e);
+            let mut user = User::load(&e, &samwise);
+            user.positions.collateral = map![&e, (0, 500_0000000)];
+            user.positions.liabilities = map![&e, (1, 40_0000000)];
+            add_isolated_debt(&e, &mut pool, &user, &underlying_1, 40_0000000);
+
+            // part of the user's debt is liquidated
+            let mut liquidated_user = user.clone();
+            liquidated_user.positions.collateral = map![&e, (0, 250_0000000)];
+            liquidated_user.positions.liabilities = map![&e, (1, 30_0000000)];
+            let bid = map![&e, (underlying_1.clone(), 10_0000000)];
+            remove_liquidated_isolated_debt(&e, &mut pool, &user, &liquidated_user, &bid);
+            let reserve_0 = pool.load_reserve(&e, &underlying_0, false);
+            assert_eq!(reserve_0.isolated_debt, 60_0000000_0000000);
+
+            // the debt left once all collateral is liquidated is bad debt, and is removed too
+            let user = liquidated_user.clone();
+            liquidated_user.positions.collateral = map![&e];
+            liquidated_user.positions.liabilities = map![&e, (1, 20_0000000)];
+            remove_liquidated_isolated_debt(&e, &mut pool, &user, &liquidated_user, &bid);
+            let reserve_0 = pool.load_reserve(&e, &underlying_0, false);
+            assert_eq!(reserve_0.isolated_debt, 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1231)")]
+    fn test_isolated_debt_over_ceiling() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let samwise = Address::generate(&e);
+
+        e.ledger().set_timestamp(600);
+        e.ledger().set_sequence_number(1234);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.isolated = true;
+        reserve_config_0.debt_ceiling = 100_0000000_0000000;
+        reserve_data_0.last_time = 600;
+        let (reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 600;
+        let (pool_address, assets) = testutils::create_pool_with_reserves(
+            &e,
+            &[
+                (reserve_config_0, reserve_data_0, 1_0000000),
+                (reserve_config_1, reserve_data_1, 2_0000000),
+            ],
+        );
+        let underlying_0 = assets.get_unchecked(0);
+        let underlying_1 = assets.get_unchecked(1);
+
+        e.as_contract(&pool_address, || {
+            let mut pool = Pool::load(&e);
+            let mut user = User::load(&e, &samwise);
+            let mut reserve_0 = pool.load_reserve(&e, &underlying_0, true);
+            user.add_collateral(&e, &mut reserve_0, 500_0000000);
+            pool.cache_reserve(reserve_0);
+
+            add_isolated_debt(&e, &mut pool, &user, &underlying_1, 50_0000001);
+        });
+    }
+
+    #[test]
+    fn test_non_isolated_debt_is_not_tracked() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        let samwise = Address::generate(&e);
+
+        e.ledger().set_timestamp(600);
+        e.ledger().set_sequence_number(1234);
+        let (mut reserve_config_0, mut reserve_data_0) = testutils::default_reserve_meta();
+        reserve_config_0.isolated = true;
+        reserve_config_0.debt_ceiling = 100_0000000_0000000;
+        reserve_data_0.last_time = 600;
+        let (reserve_config_1, mut reserve_data_1) = testutils::default_reserve_meta();
+        reserve_data_1.last_time = 600;
+        let (pool_address, assets) = testutils::create_pool_with_reserves(
+            &e,
+            &[
+                (reserve_config_0, reserve_data_0, 1_0000000),
+                (reserve_config_1, reserve_data_1, 2_0000000),
+            ],
+        );
+        let underlying_0 = assets.get_unchecked(0);
+        let underlying_1 = assets.get_unchecked(1);
+
+        e.as_contract(&pool_address, || {
+            let mut pool = Pool::load(&e);
+            let mut user = User::load(&e, &samwise);
+            let mut reserve_1 = pool.load_reserve(&e, &underlying_1, true);
+            user.add_collateral(&e, &mut reserve_1, 500_0000000);
+            pool.cache_reserve(reserve_1);
+
+            add_isolated_debt(&e, &mut pool, &user, &underlying_0, 500_0000000);
+            let reserve_0 = pool.load_reserve(&e, &underlying_0, false);
+            assert_eq!(reserve_0.isolated_debt, 0);
+        });
+    }
+}
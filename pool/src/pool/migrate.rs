@@ -0,0 +1,446 @@
+use sep_41_token::TokenClient;
+use soroban_sdk::{panic_with_error, vec, Address, Env, Map, Vec};
+
+use crate::{
+    dependencies::{BackstopClient, PoolFactoryClient},
+    events::PoolEvents,
+    storage, PoolClient, PoolError,
+};
+
+use super::{
+    actions::{build_actions_from_request, Request, RequestType},
+    fixed_rate::settle_fixed_loans,
+    pool::Pool,
+    submit::require_healthy_positions,
+    Positions, User,
+};
+
+/// Migrate the positions of "from" in "assets" from the source pool to this pool.
+///
+/// The debt of "from" is borrowed in this pool and used to repay the source pool, and the
+/// collateral and supply of "from" are withdrawn from the source pool and supplied to this pool,
+/// within a single transaction. Each asset must be a reserve of both pools.
+///
+/// The source pool must be deployed by the pool factory the pool's backstop verifies pools with,
+/// as this pool approves it to pull the borrowed tokens. Its behavior is still not trusted. This
+/// pool verifies it received the withdrawn tokens before crediting them to "from", and clears
+/// its allowance to the source pool afterwards.
+///
+/// ### Arguments
+/// * `from` - The address whose positions are being migrated
+/// * `source_pool` - The address of the pool to migrate the positions from
+/// * `assets` - The underlying assets of the positions to migrate
+///
+/// ### Panics
+/// If the source pool was not deployed by the pool factory, no positions are migrated, the
+/// source pool does not send the withdrawn tokens, or the resulting position is unhealthy or
+/// over the pool's maximum account leverage
+pub fn execute_migrate_position(
+    e: &Env,
+    from: &Address,
+    source_pool: &Address,
+    assets: Vec<Address>,
+) -> Positions {
+    if from == &e.current_contract_address()
+        || source_pool == &e.current_contract_address()
+        || assets.is_empty()
+    {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+    let pool_factory = BackstopClient::new(e, &storage::get_backstop(e)).pool_factory();
+    if !PoolFactoryClient::new(e, &pool_factory).is_pool(source_pool) {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+    let source_client = PoolClient::new(e, source_pool);
+    let source_positions = source_client.get_positions(from);
+    let (source_requests, requests) =
+        build_migration_requests(e, &source_client, &source_positions, &assets);
+    if requests.is_empty() {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+
+    let mut pool = Pool::load(e);
+    let mut from_state = User::load(e, from);
+    settle_fixed_loans(e, &mut pool, &mut from_state);
+    let actions = build_actions_from_request(e, &mut pool, &mut from_state, requests, None);
+    // tokens are only exchanged with the source pool, so requests can't set a spender or "to"
+    if actions.has_request_transfers() {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+
+    // panics if the new positions set does not meet the health factor or leverage requirements
+    require_healthy_positions(e, &mut pool, &from_state, from_state.emode(e));
+
+    // the borrowed tokens are pulled by the source pool to repay "from"'s debt, and the withdrawn
+    // tokens are sent by the source pool to this pool
+    let mut pre_balances: Map<Address, i128> = Map::new(e);
+    for asset in assets.iter() {
+        let balance = TokenClient::new(e, &asset).balance(&e.current_contract_address());
+        pre_balances.set(asset, balance);
+    }
+    for (asset, amount) in actions.pool_transfer.iter() {
+        TokenClient::new(e, &asset).approve(
+            &e.current_contract_address(),
+            source_pool,
+            &amount,
+            &e.ledger().sequence(),
+        );
+    }
+    source_client.submit_with_allowance(
+        from,
+        &e.current_contract_address(),
+        &e.current_contract_address(),
+        &source_requests,
+    );
+    for (asset, _) in actions.pool_transfer.iter() {
+        TokenClient::new(e, &asset).approve(
+            &e.current_contract_address(),
+            source_pool,
+            &0,
+            &e.ledger().sequence(),
+        );
+    }
+
+    // verify this pool received at least the tokens supplied for "from", less the tokens borrowed
+    for (asset, pre_balance) in pre_balances.iter() {
+        let expected_delta = actions.spender_transfer.get(asset.clone()).unwrap_or(0)
+            - actions.pool_transfer.get(asset.clone()).unwrap_or(0);
+        let balance = TokenClient::new(e, &asset).balance(&e.current_contract_address());
+        if balance - pre_balance < expected_delta {
+            panic_with_error!(e, PoolError::InvalidMigration);
+        }
+    }
+
+    // store updated info to ledger
+    pool.store_cached_reserves(e);
+    from_state.store(e);
+
+    PoolEvents::migrate_position(e, from.clone(), source_pool.clone(), assets);
+    from_state.positions
+}
+
+/// Build the requests to close the positions of "from" in "assets" in the source pool, and the
+/// requests to open the same positions in this pool.
+///
+/// Returns (source pool requests, requests)
+fn build_migration_requests(
+    e: &Env,
+    source_client: &PoolClient,
+    source_positions: &Positions,
+    assets: &Vec<Address>,
+) -> (Vec<Request>, Vec<Request>) {
+    let mut source_requests: Vec<Request> = vec![e];
+    let mut requests: Vec<Request> = vec![e];
+    for asset in assets.iter() {
+        let source_reserve = source_client.get_reserve(&asset);
+        let index = source_reserve.index;
+
+        let d_tokens = source_positions.liabilities.get(index).unwrap_or(0);
+        if d_tokens > 0 {
+            let amount = source_reserve.to_asset_from_d_token(d_tokens);
+            source_requests.push_back(new_request(RequestType::Repay, &asset, amount));
+            requests.push_back(new_request(RequestType::Borrow, &asset, amount));
+        }
+
+        // one more token than the position is worth is withdrawn from the source pool to
+        // ensure the position is closed in full
+        let b_tokens = source_positions.collateral.get(index).unwrap_or(0);
+        if b_tokens > 0 {
+            let amount = source_reserve.to_asset_from_b_token(b_tokens);
+            let withdraw = new_request(RequestType::WithdrawCollateral, &asset, amount + 1);
+            source_requests.push_back(withdraw);
+            requests.push_back(new_request(RequestType::SupplyCollateral, &asset, amount));
+        }
+        let b_tokens = source_positions.supply.get(index).unwrap_or(0);
+        if b_tokens > 0 {
+            let amount = source_reserve.to_asset_from_b_token(b_tokens);
+            source_requests.push_back(new_request(RequestType::Withdraw, &asset, amount + 1));
+            requests.push_back(new_request(RequestType::Supply, &asset, amount));
+        }
+    }
+    (source_requests, requests)
+}
+
+fn new_request(request_type: RequestType, asset: &Address, amount: i128) -> Request {
+    Request {
+        request_type: request_type as u32,
+        address: asset.clone(),
+        amount,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        storage::{self, PoolConfig},
+        testutils,
+    };
+
+    use super::*;
+    use mock_pool_factory::MockPoolFactoryClient;
+    use sep_40_oracle::testutils::Asset;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger, LedgerInfo},
+        map, vec, Symbol,
+    };
+
+    /// Create the pool's backstop and register the source pool with its pool factory
+    fn register_source_pool(e: &Env, bombadil: &Address, pool: &Address, source_pool: &Address) {
+        let (usdc, _) = testutils::create_token_contract(e, bombadil);
+        let (_, backstop_client) = testutils::create_backstop(
+            e,
+            pool,
+            &Address::generate(e),
+            &usdc,
+            &Address::generate(e),
+        );
+        MockPoolFactoryClient::new(e, &backstop_client.pool_factory()).set_pool(source_pool);
+    }
+
+    #[test]
+    fn test_migrate_position() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let source_pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        register_source_pool(&e, &bombadil, &pool, &source_pool);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+        testutils::create_reserve(&e, &source_pool, &underlying_1, &reserve_config, &reserve_data);
+        reserve_data.d_supply = 0;
+        testutils::create_reserve(&e, &source_pool, &underlying_0, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 1_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        // the source pool lists the reserves in the opposite order
+        let source_positions = Positions {
+            liabilities: map![&e, (0, 10_0000000)],
+            collateral: map![&e, (1, 50_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&source_pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &source_positions);
+        });
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+            let pre_source_balance_0 = underlying_0_client.balance(&source_pool);
+            let pre_source_balance_1 = underlying_1_client.balance(&source_pool);
+
+            let assets = vec![&e, underlying_0.clone(), underlying_1.clone()];
+            let positions = execute_migrate_position(&e, &samwise, &source_pool, assets);
+
+            assert_eq!(positions.liabilities, map![&e, (1, 10_0000000)]);
+            assert_eq!(positions.collateral, map![&e, (0, 50_0000000)]);
+            assert_eq!(positions.supply.len(), 0);
+
+            assert_eq!(underlying_0_client.balance(&pool), pre_pool_balance_0 + 50_0000000);
+            assert_eq!(underlying_1_client.balance(&pool), pre_pool_balance_1 - 10_0000000);
+            assert_eq!(
+                underlying_0_client.balance(&source_pool),
+                pre_source_balance_0 - 50_0000000
+            );
+            assert_eq!(
+                underlying_1_client.balance(&source_pool),
+                pre_source_balance_1 + 10_0000000
+            );
+            assert_eq!(underlying_1_client.allowance(&pool, &source_pool), 0);
+            assert_eq!(underlying_0_client.balance(&samwise), 0);
+            assert_eq!(underlying_1_client.balance(&samwise), 0);
+        });
+        e.as_contract(&source_pool, || {
+            let positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(positions.collateral.len(), 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1239)")]
+    fn test_migrate_position_over_max_leverage() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let source_pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        register_source_pool(&e, &bombadil, &pool, &source_pool);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+        testutils::create_reserve(&e, &source_pool, &underlying_0, &reserve_config, &reserve_data);
+        testutils::create_reserve(&e, &source_pool, &underlying_1, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 1_0000000]);
+
+        let mut pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        // healthy, but levered 1.5x
+        let source_positions = Positions {
+            liabilities: map![&e, (1, 10_0000000)],
+            collateral: map![&e, (0, 50_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&source_pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &source_positions);
+        });
+        pool_config.max_account_leverage = 1_2000000;
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let assets = vec![&e, underlying_0.clone(), underlying_1.clone()];
+            execute_migrate_position(&e, &samwise, &source_pool, assets);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_migrate_position_no_positions() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let source_pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+        register_source_pool(&e, &bombadil, &pool, &source_pool);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+        testutils::create_reserve(&e, &source_pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&source_pool, || {
+            storage::set_pool_config(&e, &pool_config);
+        });
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_migrate_position(&e, &samwise, &source_pool, vec![&e, underlying_0]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_migrate_position_source_not_pool() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let source_pool = testutils::create_pool(&e);
+        let (usdc, _) = testutils::create_token_contract(&e, &bombadil);
+        testutils::create_backstop(
+            &e,
+            &pool,
+            &Address::generate(&e),
+            &usdc,
+            &Address::generate(&e),
+        );
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        e.as_contract(&pool, || {
+            execute_migrate_position(&e, &samwise, &source_pool, vec![&e, underlying_0]);
+        });
+    }
+}
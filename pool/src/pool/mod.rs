@@ -1,31 +1,139 @@
 mod actions;
-pub use actions::{FlashLoan, Request, RequestType};
+pub use actions::{
+    CollateralSwap, FlashLoan, Request, RequestOptions, RequestResult, RequestType,
+};
+
+mod admin;
+pub use admin::{
+    execute_accept_admin, execute_cancel_admin_proposal, execute_propose_admin,
+    execute_set_admin_delay, load_admin_delay,
+};
+
+mod admin_batch;
+pub use admin_batch::{execute_admin_batch, execute_admin_op, AdminOp};
+
+mod anomaly;
+pub use anomaly::{execute_lift_reserve_restriction, execute_set_anomaly_thresholds};
+
+mod b_token;
+pub use b_token::{execute_b_token_transfer, execute_deploy_b_token, load_b_token_balance};
 
 mod bad_debt;
-pub use bad_debt::transfer_bad_debt_to_backstop;
+pub use bad_debt::{execute_recapitalize_bad_debt, transfer_bad_debt_to_backstop};
 
 mod config;
 pub use config::{
-    execute_cancel_queued_set_reserve, execute_initialize, execute_queue_set_reserve,
-    execute_set_reserve, execute_update_pool,
+    execute_cancel_queued_set_reserve, execute_deploy_backstop_share_token,
+    execute_fund_backstop_reward, execute_initialize, execute_queue_set_reserve,
+    execute_remove_backstop_reward, execute_set_fallback_oracle,
+    execute_set_health_warning_threshold, execute_set_ir_mod_half_life,
+    execute_set_max_account_leverage, execute_set_min_health_factor, execute_set_min_position_value,
+    execute_set_oracle_config, execute_set_rate_bounds, execute_set_reserve, execute_update_pool,
+    reserve_payload_hash,
+};
+#[cfg(feature = "flash-loans")]
+pub use config::execute_set_flash_loan_fee;
+
+mod credit_settlement;
+pub use credit_settlement::{execute_set_credit_settlement, execute_settle_backstop_credit};
+
+mod credit_delegation;
+pub use credit_delegation::execute_delegate_credit;
+
+mod config_history;
+pub use config_history::{load_config_history, record_pool_config, record_reserve_config};
+
+mod rebate;
+pub use rebate::{
+    execute_cancel_queued_debt_rebate, execute_debt_rebate, execute_queue_debt_rebate,
+};
+
+mod emode;
+pub use emode::{
+    execute_cancel_queued_set_emode_category, execute_queue_set_emode_category,
+    execute_set_emode_category, execute_set_user_emode,
+};
+
+#[cfg(feature = "flash-loans")]
+mod fee_tier;
+#[cfg(feature = "flash-loans")]
+pub use fee_tier::{execute_set_fee_tiers, load_user_fee_volume};
+
+mod fixed_rate;
+pub use fixed_rate::{
+    execute_borrow_fixed, execute_set_fixed_rate_config, execute_settle_fixed_loan,
+    settle_fixed_loans,
 };
 
 mod health_factor;
-pub use health_factor::PositionData;
+pub use health_factor::{
+    load_user_health_factor, load_user_positions_value, HealthFactorData, HealthStrategy,
+    PositionData, PositionValue,
+};
 
 mod interest;
 
+mod isolation;
+#[cfg(feature = "auctions")]
+pub use isolation::remove_liquidated_isolated_debt;
+
+mod native;
+pub use native::{execute_repay_native, execute_supply_native};
+
 mod submit;
 
-pub use submit::{execute_submit, execute_submit_with_flash_loan};
+pub use submit::{
+    execute_simulate_submit, execute_submit, execute_submit_batch, execute_submit_with_delegation,
+    execute_submit_with_options, execute_submit_with_prices, SimulateResult, SubmitResult,
+};
+#[cfg(feature = "flash-loans")]
+pub use submit::{
+    execute_deleverage, execute_submit_with_collateral_swap, execute_submit_with_flash_loan,
+    execute_submit_with_flash_loans,
+};
 
 #[allow(clippy::module_inception)]
 mod pool;
 pub use pool::Pool;
 
+mod migrate;
+pub use migrate::execute_migrate_position;
+
+mod pause;
+pub use pause::{execute_pause_actions, execute_unpause_actions};
+
+mod position_uri;
+pub use position_uri::build_position_uri;
+
+mod price_attestation;
+pub use price_attestation::{execute_set_price_signers, PriceAttestation};
+
+mod preview;
+pub use preview::{
+    load_accrued_fees, load_reserve_preview, load_reserve_rates, ReservePreview, ReserveRates,
+};
+
+mod referral;
+pub use referral::{execute_claim_referral_fees, execute_set_referral_rate};
+
+mod repay_grace;
+pub use repay_grace::execute_set_repay_grace;
+
 mod reserve;
 pub use reserve::Reserve;
 
+mod reserve_removal;
+pub use reserve_removal::{execute_clear_reserve_dust, execute_remove_reserve};
+
+mod snapshot;
+pub use snapshot::{execute_declare_snapshot, load_snapshot_positions};
+
+mod self_repay;
+pub use self_repay::{execute_self_repay, execute_set_self_repay};
+
+mod signed_submit;
+pub use signed_submit::{execute_submit_with_signature, SubmitSignature};
+
 mod user;
 pub use user::{Positions, User};
 
@@ -35,4 +143,4 @@ pub use status::{
 };
 
 mod gulp;
-pub use gulp::execute_gulp;
+pub use gulp::{execute_accrue, execute_gulp};
@@ -0,0 +1,86 @@
+use soroban_sdk::{vec, Address, Bytes, Env};
+
+use super::{execute_submit, Positions, Request, RequestType};
+
+/// The XDR encoding of the native Stellar asset (`ASSET_TYPE_NATIVE`)
+const NATIVE_ASSET_XDR: [u8; 4] = [0u8; 4];
+
+/// Fetch the address of the Stellar Asset Contract for native XLM
+pub fn native_asset_address(e: &Env) -> Address {
+    e.deployer()
+        .with_stellar_asset(Bytes::from_array(e, &NATIVE_ASSET_XDR))
+        .deployed_address()
+}
+
+/// Check if an asset is the Stellar Asset Contract for native XLM
+pub fn is_native_asset(e: &Env, asset: &Address) -> bool {
+    native_asset_address(e) == *asset
+}
+
+/// Supply native XLM to the pool for "from", transferring the XLM directly from "from"
+///
+/// ### Arguments
+/// * `from` - The address supplying the XLM
+/// * `amount` - The amount of XLM to supply
+/// * `as_collateral` - If the XLM should be supplied as collateral
+///
+/// ### Panics
+/// If native XLM is not a reserve of the pool or the supply can't be completed
+pub fn execute_supply_native(
+    e: &Env,
+    from: &Address,
+    amount: i128,
+    as_collateral: bool,
+) -> Positions {
+    let request_type = if as_collateral {
+        RequestType::SupplyCollateral
+    } else {
+        RequestType::Supply
+    };
+    let request = Request {
+        request_type: request_type as u32,
+        address: native_asset_address(e),
+        amount,
+    };
+    execute_submit(e, from, from, from, vec![e, request], false, None).positions
+}
+
+/// Repay native XLM liabilities for "from", transferring the XLM directly from "from"
+///
+/// ### Arguments
+/// * `from` - The address repaying the XLM
+/// * `amount` - The amount of XLM to repay, i128::MAX repays the entire position
+///
+/// ### Panics
+/// If native XLM is not a reserve of the pool or the repayment can't be completed
+pub fn execute_repay_native(e: &Env, from: &Address, amount: i128) -> Positions {
+    let request = Request {
+        request_type: RequestType::Repay as u32,
+        address: native_asset_address(e),
+        amount,
+    };
+    execute_submit(e, from, from, from, vec![e, request], false, None).positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_native_asset_address() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        let native = e
+            .deployer()
+            .with_stellar_asset(Bytes::from_array(&e, &NATIVE_ASSET_XDR))
+            .deploy();
+        e.as_contract(&pool, || {
+            assert_eq!(native_asset_address(&e), native);
+            assert!(is_native_asset(&e, &native));
+            assert!(!is_native_asset(&e, &Address::generate(&e)));
+        });
+    }
+}
@@ -0,0 +1,175 @@
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::{
+    constants::{PAUSE_ALL, PAUSE_BORROW, PAUSE_LIQUIDATION, PAUSE_SUPPLY, PAUSE_WITHDRAW},
+    errors::PoolError,
+    storage,
+};
+
+use super::RequestType;
+
+/// Pause pool actions. Any actions already paused remain paused.
+///
+/// ### Arguments
+/// * `caller` - The address pausing the actions, either the admin or the pause guardian
+/// * `actions` - The bitmask of actions to pause
+///
+/// ### Panics
+/// If the caller is not the admin or pause guardian, or the bitmask includes an invalid flag
+pub fn execute_pause_actions(e: &Env, caller: &Address, actions: u32) -> u32 {
+    let is_guardian = storage::get_pause_guardian(e).is_some_and(|guardian| &guardian == caller);
+    if caller != &storage::get_admin(e) && !is_guardian {
+        panic_with_error!(e, PoolError::UnauthorizedError);
+    }
+    require_valid_actions(e, actions);
+    let paused_actions = storage::get_paused_actions(e) | actions;
+    storage::set_paused_actions(e, &paused_actions);
+    paused_actions
+}
+
+/// Unpause pool actions
+///
+/// ### Arguments
+/// * `actions` - The bitmask of actions to unpause
+///
+/// ### Panics
+/// If the bitmask includes an invalid flag
+pub fn execute_unpause_actions(e: &Env, actions: u32) -> u32 {
+    require_valid_actions(e, actions);
+    let paused_actions = storage::get_paused_actions(e) & !actions;
+    storage::set_paused_actions(e, &paused_actions);
+    paused_actions
+}
+
+/// Check if the action is paused
+///
+/// ### Arguments
+/// * `flag` - The pause flag of the action
+pub fn is_paused(e: &Env, flag: u32) -> bool {
+    storage::get_paused_actions(e) & flag != 0
+}
+
+/// Require that the action is not paused, or panic
+///
+/// ### Arguments
+/// * `flag` - The pause flag of the action
+#[cfg(feature = "flash-loans")]
+pub fn require_not_paused(e: &Env, flag: u32) {
+    if is_paused(e, flag) {
+        panic_with_error!(e, PoolError::ActionPaused);
+    }
+}
+
+/// Fetch the pause flag for a request type, or 0 if the request type cannot be paused
+///
+/// ### Arguments
+/// * `action_type` - The type of action being performed
+pub fn pause_flag(action_type: u32) -> u32 {
+    if action_type == RequestType::Borrow as u32 {
+        PAUSE_BORROW
+    } else if action_type == RequestType::Supply as u32
+        || action_type == RequestType::SupplyCollateral as u32
+        || action_type == RequestType::SupplyCollateralFor as u32
+    {
+        PAUSE_SUPPLY
+    } else if action_type == RequestType::Withdraw as u32
+        || action_type == RequestType::WithdrawCollateral as u32
+        || action_type == RequestType::WithdrawThrottled as u32
+        || action_type == RequestType::WithdrawCollateralThrottled as u32
+    {
+        PAUSE_WITHDRAW
+    } else if action_type == RequestType::FillUserLiquidationAuction as u32
+        || action_type == RequestType::FillUserLiquidationAuctionToTarget as u32
+    {
+        PAUSE_LIQUIDATION
+    } else {
+        0
+    }
+}
+
+fn require_valid_actions(e: &Env, actions: u32) {
+    if actions == 0 || actions & !PAUSE_ALL != 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::PAUSE_FLASH_LOAN, testutils};
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_pause_and_unpause_actions() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let guardian = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pause_guardian(&e, &Some(guardian.clone()));
+
+            let paused = execute_pause_actions(&e, &guardian, PAUSE_FLASH_LOAN);
+            assert_eq!(paused, PAUSE_FLASH_LOAN);
+            let paused = execute_pause_actions(&e, &bombadil, PAUSE_BORROW | PAUSE_SUPPLY);
+            assert_eq!(paused, PAUSE_FLASH_LOAN | PAUSE_BORROW | PAUSE_SUPPLY);
+            assert_eq!(storage::get_paused_actions(&e), paused);
+
+            let paused = execute_unpause_actions(&e, PAUSE_FLASH_LOAN | PAUSE_SUPPLY);
+            assert_eq!(paused, PAUSE_BORROW);
+            assert_eq!(storage::get_paused_actions(&e), PAUSE_BORROW);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #4)")]
+    fn test_pause_actions_not_guardian() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pause_guardian(&e, &Some(Address::generate(&e)));
+
+            execute_pause_actions(&e, &samwise, PAUSE_FLASH_LOAN);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_pause_actions_invalid_flag() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_admin(&e, &bombadil);
+
+            execute_pause_actions(&e, &bombadil, PAUSE_ALL + 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1234)")]
+    fn test_require_not_paused() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_paused_actions(&e, &PAUSE_LIQUIDATION);
+
+            require_not_paused(&e, PAUSE_BORROW);
+            require_not_paused(&e, pause_flag(RequestType::Repay as u32));
+            require_not_paused(&e, pause_flag(RequestType::FillUserLiquidationAuction as u32));
+        });
+    }
+}
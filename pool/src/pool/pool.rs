@@ -1,21 +1,32 @@
-use soroban_sdk::{map, panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Map, Vec};
+use cast::i128;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{map, panic_with_error, vec, Address, Env, Map, Vec};
 
-use sep_40_oracle::{Asset, PriceFeedClient};
+use sep_40_oracle::{Asset, PriceData, PriceFeedClient};
 
 use crate::{
+    constants::{MAX_LAST_PRICE_AGE, PAUSE_LIQUIDATION, PRICE_DECIMALS, SCALAR_7},
     errors::PoolError,
+    events::PoolEvents,
     storage::{self, PoolConfig},
     Positions,
 };
 
-use super::reserve::Reserve;
+use super::{
+    actions::RequestType,
+    pause::is_paused,
+    price_attestation::{verify_price_attestation, PriceAttestation},
+    reserve::Reserve,
+};
 
 pub struct Pool {
     pub config: PoolConfig,
     pub reserves: Map<Address, Reserve>,
     reserves_to_store: Vec<Address>,
-    price_decimals: Option<u32>,
     prices: Map<Address, i128>,
+    twap_records: u32,
+    check_price_deviation: bool,
+    read_only: bool,
 }
 
 impl Pool {
@@ -26,11 +37,49 @@ impl Pool {
             config: pool_config,
             reserves: map![e],
             reserves_to_store: vec![e],
-            price_decimals: None,
             prices: map![e],
+            twap_records: 0,
+            check_price_deviation: true,
+            read_only: false,
         }
     }
 
+    /// Set the number of oracle records used to load a time weighted average price (TWAP)
+    /// instead of the spot price. Any prices already loaded are cleared.
+    ///
+    /// ### Arguments
+    /// * twap_records - The number of oracle records in the TWAP, or 0 to use the spot price
+    #[cfg(feature = "auctions")]
+    pub fn set_twap_records(&mut self, e: &Env, twap_records: u32) {
+        self.twap_records = twap_records;
+        self.prices = map![e];
+    }
+
+    /// Skip the check that a price loaded from the oracle does not deviate too far from the last
+    /// price loaded for the asset. Used by liquidations, so a large price move can't block them.
+    /// The prices loaded still become the last prices of their assets.
+    #[cfg(feature = "auctions")]
+    pub fn skip_price_deviation_check(&mut self) {
+        self.check_price_deviation = false;
+    }
+
+    /// Stop the pool from writing to the ledger, for simulating requests. Reserves are never
+    /// stored, and the prices loaded are not recorded as the last prices of their assets.
+    pub fn set_read_only(&mut self) {
+        self.read_only = true;
+    }
+
+    /// The minimum health factor a user's positions must have after an action (7 decimals)
+    pub fn min_health_factor(&self) -> i128 {
+        i128(self.config.min_health_factor)
+    }
+
+    /// The maximum leverage a user's positions can have after an action (7 decimals), or 0 if
+    /// there is no limit
+    pub fn max_account_leverage(&self) -> i128 {
+        i128(self.config.max_account_leverage)
+    }
+
     /// Load a Reserve from the ledger and update to the current ledger timestamp. Returns
     /// a cached version if it exists.
     ///
@@ -57,8 +106,12 @@ impl Pool {
         self.reserves.set(reserve.asset.clone(), reserve);
     }
 
-    /// Store the cached reserves to the ledger that need to be written.
+    /// Store the cached reserves to the ledger that need to be written. Read only pools don't
+    /// store any reserves.
     pub fn store_cached_reserves(&self, e: &Env) {
+        if self.read_only {
+            return;
+        }
         for address in self.reserves_to_store.iter() {
             let reserve = self
                 .reserves
@@ -68,16 +121,35 @@ impl Pool {
         }
     }
 
-    /// Require that the action does not violate the pool status, or panic.
+    /// Check that the action does not violate the pool status. Returns the error the action
+    /// would fail with, if any.
     ///
     /// ### Arguments
     /// * `action_type` - The type of action being performed
-    pub fn require_action_allowed(&self, e: &Env, action_type: u32) {
+    pub fn check_action_allowed(&self, e: &Env, action_type: u32) -> Result<(), PoolError> {
         // disable borrowing or auction cancellation for any non-active pool and disable supplying for any frozen pool
         if (self.config.status > 1 && (action_type == 4 || action_type == 9))
-            || (self.config.status > 3 && (action_type == 2 || action_type == 0))
+            || (self.config.status > 3
+                && (action_type == 2 || action_type == 0 || action_type == 14))
+        {
+            return Err(PoolError::InvalidPoolStatus);
+        }
+        if (action_type == RequestType::FillUserLiquidationAuction as u32
+            || action_type == RequestType::FillUserLiquidationAuctionToTarget as u32)
+            && is_paused(e, PAUSE_LIQUIDATION)
         {
-            panic_with_error!(e, PoolError::InvalidPoolStatus);
+            return Err(PoolError::ActionPaused);
+        }
+        Ok(())
+    }
+
+    /// Require that the action does not violate the pool status, or panic.
+    ///
+    /// ### Arguments
+    /// * `action_type` - The type of action being performed
+    pub fn require_action_allowed(&self, e: &Env, action_type: u32) {
+        if let Err(error) = self.check_action_allowed(e, action_type) {
+            panic_with_error!(e, error);
         }
     }
 
@@ -97,52 +169,328 @@ impl Pool {
         }
     }
 
-    /// Load the decimals of the prices for the Pool's oracle. Returns a cached version if one
-    /// already exists.
-    pub fn load_price_decimals(&mut self, e: &Env) -> u32 {
-        if let Some(decimals) = self.price_decimals {
-            return decimals;
-        }
-        let oracle_client = PriceFeedClient::new(e, &self.config.oracle);
-        let decimals = oracle_client.decimals();
-        self.price_decimals = Some(decimals);
-        decimals
-    }
-
-    /// Load a price from the Pool's oracle. Returns a cached version if one already exists.
+    /// Load a price from the Pool's oracle, normalized to `PRICE_DECIMALS`. Returns a
+    /// cached version if one already exists.
+    ///
+    /// If the oracle's price is missing or stale, the price is loaded from the Pool's
+    /// fallback oracle instead. If the Pool uses TWAP prices, the TWAP is loaded from the
+    /// oracle's price history.
     ///
     /// ### Arguments
     /// * asset - The address of the underlying asset
     ///
     /// ### Panics
-    /// If the price is stale
+    /// If no fresh price is available, the price is not positive, or the price deviates too far
+    /// from the last price loaded for the asset in the last hour
     pub fn load_price(&mut self, e: &Env, asset: &Address) -> i128 {
+        self.try_load_price(e, asset).unwrap_or_else(|error| panic_with_error!(e, error))
+    }
+
+    /// Load a price from the Pool's oracle, normalized to `PRICE_DECIMALS`. Returns the
+    /// error loading the price would fail with instead of panicking, including if an oracle
+    /// can't be invoked.
+    ///
+    /// ### Arguments
+    /// * asset - The address of the underlying asset
+    pub fn try_load_price(&mut self, e: &Env, asset: &Address) -> Result<i128, PoolError> {
         if let Some(price) = self.prices.get(asset.clone()) {
-            return price;
+            return Ok(price);
+        }
+        let oracle_config = storage::get_oracle_config(e);
+        let max_age = oracle_config.max_price_age;
+        if self.twap_records > 0 {
+            let twap = load_twap_price(
+                e,
+                &self.config.oracle,
+                asset,
+                self.twap_records,
+                max_age,
+            )?;
+            let decimals = fetch_oracle_decimals(e, &self.config.oracle, !self.read_only)?;
+            let price = try_normalize_price(twap, decimals)?;
+            self.prices.set(asset.clone(), price);
+            return Ok(price);
         }
-        let oracle_client = PriceFeedClient::new(e, &self.config.oracle);
-        let oracle_asset = Asset::Stellar(asset.clone());
-        let price_data = oracle_client.lastprice(&oracle_asset).unwrap_optimized();
-        if price_data.timestamp + 24 * 60 * 60 < e.ledger().timestamp() {
-            panic_with_error!(e, PoolError::StalePrice);
+        let (price_data, decimals) = match load_fresh_price(e, &self.config.oracle, asset, max_age)
+        {
+            Some(price_data) => {
+                let decimals = fetch_oracle_decimals(e, &self.config.oracle, !self.read_only)?;
+                (price_data, decimals)
+            }
+            None => {
+                let fallback_oracle = self
+                    .config
+                    .fallback_oracle
+                    .clone()
+                    .ok_or(PoolError::StalePrice)?;
+                let price_data = load_fresh_price(e, &fallback_oracle, asset, max_age)
+                    .ok_or(PoolError::StalePrice)?;
+                let decimals = fetch_oracle_decimals(e, &fallback_oracle, !self.read_only)?;
+                PoolEvents::oracle_fallback_used(e, asset.clone(), fallback_oracle);
+                (price_data, decimals)
+            }
+        };
+        let price = try_normalize_price(price_data.price, decimals)?;
+        if oracle_config.max_deviation > 0 {
+            let now = e.ledger().timestamp();
+            // only recent prices are checked against, so a large price move can't block the
+            // asset's prices for longer than the max last price age
+            let last_price = storage::get_last_price(e, asset)
+                .filter(|last_price| last_price.timestamp + MAX_LAST_PRICE_AGE >= now);
+            if let Some(last_price) = last_price {
+                let excessive = (price - last_price.price)
+                    .abs()
+                    .fixed_div_floor(last_price.price, SCALAR_7)
+                    .map_or(true, |deviation| deviation > i128(oracle_config.max_deviation));
+                if excessive {
+                    if self.check_price_deviation {
+                        return Err(PoolError::ExcessivePriceDeviation);
+                    } else if !self.read_only {
+                        PoolEvents::price_deviation_exempted(
+                            e,
+                            asset.clone(),
+                            price,
+                            last_price.price,
+                        );
+                    }
+                }
+            }
+            if !self.read_only {
+                storage::set_last_price(
+                    e,
+                    asset,
+                    &PriceData {
+                        price,
+                        timestamp: now,
+                    },
+                );
+            }
         }
-        self.prices.set(asset.clone(), price_data.price);
-        price_data.price
+        self.prices.set(asset.clone(), price);
+        Ok(price)
     }
+
+    /// Verify a set of signed price attestations and cache the attested prices, so they
+    /// are used instead of the Pool's oracle.
+    ///
+    /// ### Arguments
+    /// * attestations - The signed price attestations
+    ///
+    /// ### Panics
+    /// If the pool has no price signers, any attestation is invalid, or an asset is attested
+    /// more than once
+    pub fn load_price_attestations(&mut self, e: &Env, attestations: &Vec<PriceAttestation>) {
+        if attestations.is_empty() {
+            return;
+        }
+        let price_signers = storage::get_price_signers(e)
+            .unwrap_or_else(|| panic_with_error!(e, PoolError::InvalidPriceAttestation));
+        let decimals = load_oracle_decimals(e, &self.config.oracle);
+        for attestation in attestations.iter() {
+            if self.prices.contains_key(attestation.asset.clone()) {
+                panic_with_error!(e, PoolError::InvalidPriceAttestation);
+            }
+            verify_price_attestation(e, &price_signers, &attestation);
+            storage::set_last_attestation_time(e, &attestation.asset, attestation.timestamp);
+            let price = normalize_price(e, attestation.price, decimals);
+            self.prices.set(attestation.asset, price);
+        }
+    }
+}
+
+/// Load the decimals of an oracle's prices. The decimals are read from the oracle once and
+/// cached, as a feed can't change its decimals without mispricing its consumers.
+///
+/// ### Arguments
+/// * oracle - The address of the oracle
+pub fn load_oracle_decimals(e: &Env, oracle: &Address) -> u32 {
+    try_load_oracle_decimals(e, oracle).unwrap_or_else(|error| panic_with_error!(e, error))
+}
+
+/// Load the decimals of an oracle's prices. Returns `StalePrice` instead of panicking if the
+/// oracle can't be invoked.
+///
+/// ### Arguments
+/// * oracle - The address of the oracle
+pub fn try_load_oracle_decimals(e: &Env, oracle: &Address) -> Result<u32, PoolError> {
+    fetch_oracle_decimals(e, oracle, true)
+}
+
+/// Load the decimals of an oracle's prices, and cache them if they are read from the oracle and
+/// `cache` is set
+fn fetch_oracle_decimals(e: &Env, oracle: &Address, cache: bool) -> Result<u32, PoolError> {
+    if let Some(decimals) = storage::get_oracle_decimals(e, oracle) {
+        return Ok(decimals);
+    }
+    let decimals = PriceFeedClient::new(e, oracle)
+        .try_decimals()
+        .ok()
+        .and_then(|decimals| decimals.ok())
+        .ok_or(PoolError::StalePrice)?;
+    if cache {
+        storage::set_oracle_decimals(e, oracle, &decimals);
+    }
+    Ok(decimals)
+}
+
+/// Normalize a price published with `decimals` to `PRICE_DECIMALS`, so prices from feeds with
+/// different decimals can be valued against each other. Prices from feeds with more decimals
+/// than `PRICE_DECIMALS` are rounded down.
+///
+/// ### Arguments
+/// * price - The price, expressed in `decimals`
+/// * decimals - The decimals of the price
+///
+/// ### Panics
+/// If the price is not positive, or it can't be normalized to a positive price
+pub fn normalize_price(e: &Env, price: i128, decimals: u32) -> i128 {
+    try_normalize_price(price, decimals).unwrap_or_else(|error| panic_with_error!(e, error))
+}
+
+/// Normalize a price published with `decimals` to `PRICE_DECIMALS`. Returns `InvalidPrice`
+/// instead of panicking if the price can't be normalized.
+///
+/// ### Arguments
+/// * price - The price, expressed in `decimals`
+/// * decimals - The decimals of the price
+pub fn try_normalize_price(price: i128, decimals: u32) -> Result<i128, PoolError> {
+    let price = if decimals > PRICE_DECIMALS {
+        10i128
+            .checked_pow(decimals - PRICE_DECIMALS)
+            .map_or(0, |scalar| price / scalar)
+    } else {
+        price
+            .checked_mul(10i128.pow(PRICE_DECIMALS - decimals))
+            .ok_or(PoolError::InvalidPrice)?
+    };
+    if price <= 0 {
+        return Err(PoolError::InvalidPrice);
+    }
+    Ok(price)
+}
+
+/// Load the last price of an asset from an oracle, if it exists and is not older than `max_age`
+///
+/// ### Arguments
+/// * oracle - The address of the oracle
+/// * asset - The address of the underlying asset
+/// * max_age - The max age of the price in seconds
+fn load_fresh_price(e: &Env, oracle: &Address, asset: &Address, max_age: u64) -> Option<PriceData> {
+    let oracle_client = PriceFeedClient::new(e, oracle);
+    oracle_client
+        .try_lastprice(&Asset::Stellar(asset.clone()))
+        .ok()
+        .and_then(|price_data| price_data.ok())
+        .flatten()
+        .filter(|price_data| price_data.timestamp + max_age >= e.ledger().timestamp())
+}
+
+/// Load the time weighted average price of an asset over the last `records` records of an oracle
+///
+/// ### Arguments
+/// * oracle - The address of the oracle
+/// * asset - The address of the underlying asset
+/// * records - The number of records to average
+/// * max_age - The max age of the newest record in seconds
+///
+/// Returns `StalePrice` if the oracle has no price history for the asset, or the newest record is
+/// stale
+fn load_twap_price(
+    e: &Env,
+    oracle: &Address,
+    asset: &Address,
+    records: u32,
+    max_age: u64,
+) -> Result<i128, PoolError> {
+    let oracle_client = PriceFeedClient::new(e, oracle);
+    let prices = oracle_client
+        .try_prices(&Asset::Stellar(asset.clone()), &records)
+        .ok()
+        .and_then(|prices| prices.ok())
+        .flatten()
+        .ok_or(PoolError::StalePrice)?;
+    calc_twap(e, &prices, max_age)
+}
+
+/// Calculate the time weighted average of a set of price records ordered from newest to oldest.
+/// Each price is weighted by the time until the following record, or until now for the newest.
+///
+/// Returns `StalePrice` if there are no records, or the newest record is stale
+fn calc_twap(e: &Env, prices: &Vec<PriceData>, max_age: u64) -> Result<i128, PoolError> {
+    let now = e.ledger().timestamp();
+    let newest = prices.first().ok_or(PoolError::StalePrice)?;
+    if newest.timestamp + max_age < now {
+        return Err(PoolError::StalePrice);
+    }
+
+    let mut next_time = now;
+    let mut weighted_sum: i128 = 0;
+    let mut total_time: i128 = 0;
+    for price_data in prices.iter() {
+        let elapsed = i128(next_time.saturating_sub(price_data.timestamp));
+        weighted_sum += price_data.price * elapsed;
+        total_time += elapsed;
+        next_time = next_time.min(price_data.timestamp);
+    }
+    if total_time == 0 {
+        return Ok(newest.price);
+    }
+    Ok(weighted_sum / total_time)
 }
 
 #[cfg(test)]
 mod tests {
     use sep_40_oracle::testutils::Asset;
     use soroban_sdk::{
-        testutils::{Address as _, Ledger, LedgerInfo},
-        Symbol,
+        testutils::{Address as _, Events, Ledger, LedgerInfo},
+        IntoVal, Symbol,
     };
 
-    use crate::{pool::User, storage::ReserveData, testutils};
+    use crate::{
+        constants::MIN_HEALTH_FACTOR_FLOOR,
+        pool::User,
+        storage::{OracleConfig, PoolConfigV1, ReserveData},
+        testutils,
+    };
 
     use super::*;
 
+    #[test]
+    fn test_load_migrates_legacy_pool_config() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+
+        e.as_contract(&pool, || {
+            // pool config stored before the pool config was extended
+            let legacy_config = PoolConfigV1 {
+                oracle: oracle.clone(),
+                bstop_rate: 0_2000000,
+                status: 1,
+                max_positions: 4,
+            };
+            e.storage()
+                .instance()
+                .set(&Symbol::new(&e, "Config"), &legacy_config);
+
+            let pool = Pool::load(&e);
+            assert_eq!(pool.config.oracle, oracle);
+            assert_eq!(pool.config.bstop_rate, 0_2000000);
+            assert_eq!(pool.config.status, 1);
+            assert_eq!(pool.config.max_positions, 4);
+            assert_eq!(pool.config.flash_loan_fee, 0);
+            assert_eq!(pool.config.hf_strategy, 0);
+            assert_eq!(pool.config.fallback_oracle, None);
+            assert_eq!(pool.config.min_health_factor, MIN_HEALTH_FACTOR_FLOOR);
+            assert_eq!(pool.config.max_account_leverage, 0);
+
+            // the migrated config is stored in the current layout
+            storage::set_pool_config(&e, &pool.config);
+            let config = storage::get_pool_config(&e);
+            assert_eq!(config.min_health_factor, MIN_HEALTH_FACTOR_FLOOR);
+        });
+    }
+
     #[test]
     fn test_reserve_cache() {
         let e = Env::default();
@@ -172,6 +520,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -191,6 +544,9 @@ mod tests {
                     d_supply: 0,
                     last_time: 0,
                     backstop_credit: 0,
+                    isolated_debt: 0,
+                    util_accum: 0,
+                    accrued_fees: 0,
                 },
             );
 
@@ -230,12 +586,12 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         reserve_config.index = 1;
-        reserve_data.d_rate = 1_001_000_000;
+        reserve_data.d_rate = 1_001_000_000_000;
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         reserve_config.index = 2;
-        reserve_data.d_rate = 1_002_000_000;
+        reserve_data.d_rate = 1_002_000_000_000;
         testutils::create_reserve(&e, &pool, &underlying_2, &reserve_config, &reserve_data);
 
         let pool_config = PoolConfig {
@@ -243,6 +599,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -274,6 +635,9 @@ mod tests {
                     d_supply: 0,
                     last_time: 0,
                     backstop_credit: 0,
+                    isolated_debt: 0,
+                    util_accum: 0,
+                    accrued_fees: 0,
                 },
             );
 
@@ -318,12 +682,12 @@ mod tests {
 
         let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         reserve_config.index = 1;
-        reserve_data.d_rate = 1_001_000_000;
+        reserve_data.d_rate = 1_001_000_000_000;
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
         let (underlying_2, _) = testutils::create_token_contract(&e, &bombadil);
         reserve_config.index = 2;
-        reserve_data.d_rate = 1_002_000_000;
+        reserve_data.d_rate = 1_002_000_000_000;
         testutils::create_reserve(&e, &pool, &underlying_2, &reserve_config, &reserve_data);
 
         let pool_config = PoolConfig {
@@ -331,6 +695,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -360,6 +729,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 2,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -380,6 +754,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 1,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -401,6 +780,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 2,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -421,6 +805,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 1,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -442,6 +831,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 4,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -463,6 +857,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 4,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -483,6 +882,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 4,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -497,7 +901,7 @@ mod tests {
     }
 
     #[test]
-    fn test_load_price_decimals() {
+    fn test_load_oracle_decimals() {
         let e = Env::default();
         e.mock_all_auths();
 
@@ -515,13 +919,137 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
-            let mut pool = Pool::load(&e);
+            assert_eq!(storage::get_oracle_decimals(&e, &pool_config.oracle), None);
 
-            let decimals = pool.load_price_decimals(&e);
+            let decimals = load_oracle_decimals(&e, &pool_config.oracle);
             assert_eq!(decimals, 7);
+            assert_eq!(storage::get_oracle_decimals(&e, &pool_config.oracle), Some(7));
+        });
+    }
+
+    #[test]
+    fn test_normalize_price() {
+        let e = Env::default();
+        assert_eq!(normalize_price(&e, 1_2345678, 7), 1_2345678_0000000);
+        assert_eq!(normalize_price(&e, 1_234567, 6), 1_2345670_0000000);
+        assert_eq!(normalize_price(&e, 1_2345678_9012345, 14), 1_2345678_9012345);
+        assert_eq!(normalize_price(&e, 1, 14), 1);
+        assert_eq!(normalize_price(&e, 1_2345678_9012345_6789, 18), 1_2345678_9012345);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1247)")]
+    fn test_normalize_price_zero() {
+        let e = Env::default();
+        normalize_price(&e, 0, 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1247)")]
+    fn test_normalize_price_rounds_to_zero() {
+        let e = Env::default();
+        normalize_price(&e, 9999, 18);
+    }
+
+    #[test]
+    fn test_load_price_normalizes_decimals() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 1000 + 24 * 60 * 60 + 1,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let asset_0 = Address::generate(&e);
+        let asset_1 = Address::generate(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        let (fallback_oracle, fallback_oracle_client) = testutils::create_mock_oracle(&e);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(asset_0.clone()),
+                Asset::Stellar(asset_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_2345678, 0_0000001]);
+        fallback_oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(asset_0.clone()),
+                Asset::Stellar(asset_1.clone()),
+            ],
+            &14,
+            &300,
+        );
+        fallback_oracle_client.set_price_stable(&vec![&e, 1_2345678_9000000, 0_0000001_2345678]);
+
+        let mut pool_config = PoolConfig {
+            oracle: fallback_oracle.clone(),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            // 14 decimal prices are kept at full precision
+            storage::set_pool_config(&e, &pool_config);
+            let mut pool = Pool::load(&e);
+
+            let price = pool.load_price(&e, &asset_0);
+            assert_eq!(price, 1_2345678_9000000);
+
+            let price = pool.load_price(&e, &asset_1);
+            assert_eq!(price, 0_0000001_2345678);
+
+            // 7 decimal prices are scaled up to the same precision
+            pool_config.oracle = oracle.clone();
+            pool_config.fallback_oracle = Some(fallback_oracle.clone());
+            storage::set_pool_config(&e, &pool_config);
+            let mut pool = Pool::load(&e);
+
+            let price = pool.load_price(&e, &asset_0);
+            assert_eq!(price, 1_2345678_0000000);
+
+            let price = pool.load_price(&e, &asset_1);
+            assert_eq!(price, 0_0000001_0000000);
+
+            // prices from a fallback oracle with different decimals use the same precision
+            oracle_client.set_price(&vec![&e, 1_2345678, 0_0000001], &1000);
+            let mut pool = Pool::load(&e);
+
+            let price = pool.load_price(&e, &asset_0);
+            assert_eq!(price, 1_2345678_9000000);
+
+            let price = pool.load_price(&e, &asset_1);
+            assert_eq!(price, 0_0000001_2345678);
         });
     }
 
@@ -554,21 +1082,26 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
             let mut pool = Pool::load(&e);
 
             let price = pool.load_price(&e, &asset_0);
-            assert_eq!(price, 123);
+            assert_eq!(price, 123_0000000);
 
             let price = pool.load_price(&e, &asset_1);
-            assert_eq!(price, 456);
+            assert_eq!(price, 456_0000000);
 
             // verify the price is cached
             oracle_client.set_price_stable(&vec![&e, 789, 101112]);
             let price = pool.load_price(&e, &asset_0);
-            assert_eq!(price, 123);
+            assert_eq!(price, 123_0000000);
         });
     }
 
@@ -606,6 +1139,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -616,6 +1154,323 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_load_price_uses_fallback_oracle_if_stale() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 1000 + 24 * 60 * 60 + 1,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        let (fallback_oracle, fallback_oracle_client) = testutils::create_mock_oracle(&e);
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(asset.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price(&vec![&e, 123], &1000);
+        fallback_oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(asset.clone())],
+            &7,
+            &300,
+        );
+        fallback_oracle_client.set_price_stable(&vec![&e, 125]);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: Some(fallback_oracle),
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            let mut pool = Pool::load(&e);
+
+            let price = pool.load_price(&e, &asset);
+            assert_eq!(price, 125_0000000);
+        });
+    }
+
+    #[test]
+    fn test_calc_twap() {
+        let e = Env::default();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1300,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let prices = vec![
+            &e,
+            PriceData {
+                price: 1_2000000,
+                timestamp: 1200,
+            },
+            PriceData {
+                price: 1_0000000,
+                timestamp: 900,
+            },
+            PriceData {
+                price: 0_8000000,
+                timestamp: 600,
+            },
+        ];
+        // (1.2 * 100 + 1 * 300 + 0.8 * 300) / 700
+        assert_eq!(calc_twap(&e, &prices, 300), Ok(0_9428571));
+
+        // the newest price has no weight if it was just reported
+        let prices = vec![
+            &e,
+            PriceData {
+                price: 1_2000000,
+                timestamp: 1300,
+            },
+            PriceData {
+                price: 1_0000000,
+                timestamp: 900,
+            },
+        ];
+        assert_eq!(calc_twap(&e, &prices, 300), Ok(1_0000000));
+    }
+
+    #[test]
+    fn test_calc_twap_stale() {
+        let e = Env::default();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1300,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let prices = vec![
+            &e,
+            PriceData {
+                price: 1_2000000,
+                timestamp: 1200,
+            },
+        ];
+        assert_eq!(calc_twap(&e, &prices, 60), Err(PoolError::StalePrice));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1210)")]
+    fn test_load_price_panics_if_older_than_max_price_age() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 1000 + 60 * 60 + 1,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(asset.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price(&vec![&e, 123], &1000);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_oracle_config(
+                &e,
+                &OracleConfig {
+                    max_price_age: 60 * 60,
+                    max_deviation: 0,
+                },
+            );
+            let mut pool = Pool::load(&e);
+
+            pool.load_price(&e, &asset);
+            assert!(false);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1235)")]
+    fn test_load_price_panics_if_deviation_exceeded() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(asset.clone())],
+            &14,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 100_0000000]);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_oracle_config(
+                &e,
+                &OracleConfig {
+                    max_price_age: 24 * 60 * 60,
+                    max_deviation: 0_1000000,
+                },
+            );
+
+            let mut pool = Pool::load(&e);
+            assert_eq!(pool.load_price(&e, &asset), 100_0000000);
+            let last_price = storage::get_last_price(&e, &asset).unwrap();
+            assert_eq!(last_price.price, 100_0000000);
+
+            // a price within the max deviation of the last price is accepted
+            oracle_client.set_price_stable(&vec![&e, 109_0000000]);
+            let mut pool = Pool::load(&e);
+            assert_eq!(pool.load_price(&e, &asset), 109_0000000);
+            let last_price = storage::get_last_price(&e, &asset).unwrap();
+            assert_eq!(last_price.price, 109_0000000);
+
+            oracle_client.set_price_stable(&vec![&e, 125_0000000]);
+            let mut pool = Pool::load(&e);
+            pool.load_price(&e, &asset);
+            assert!(false);
+        });
+    }
+
+    #[test]
+    fn test_load_price_deviation_skipped_or_last_price_expired() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+        e.ledger().set_timestamp(10000);
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(asset.clone())],
+            &14,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 100_0000000]);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_oracle_config(
+                &e,
+                &OracleConfig {
+                    max_price_age: 24 * 60 * 60,
+                    max_deviation: 0_1000000,
+                },
+            );
+            let mut pool = Pool::load(&e);
+            assert_eq!(pool.load_price(&e, &asset), 100_0000000);
+
+            // liquidations skip the check and update the last price
+            oracle_client.set_price_stable(&vec![&e, 125_0000000]);
+            let mut pool = Pool::load(&e);
+            assert_eq!(pool.try_load_price(&e, &asset), Err(PoolError::ExcessivePriceDeviation));
+            let mut pool = Pool::load(&e);
+            pool.skip_price_deviation_check();
+            assert_eq!(pool.load_price(&e, &asset), 125_0000000);
+            let event = e.events().all().last_unchecked();
+            assert_eq!(
+                event,
+                (
+                    e.current_contract_address(),
+                    (Symbol::new(&e, "price_deviation_exempted"), asset.clone()).into_val(&e),
+                    (125_0000000i128, 100_0000000i128).into_val(&e)
+                )
+            );
+            let last_price = storage::get_last_price(&e, &asset).unwrap();
+            assert_eq!(last_price.price, 125_0000000);
+            assert_eq!(last_price.timestamp, 10000);
+
+            // the last price is not checked against once it is older than the max last price age
+            e.ledger().set_timestamp(10000 + MAX_LAST_PRICE_AGE);
+            oracle_client.set_price_stable(&vec![&e, 150_0000000]);
+            let mut pool = Pool::load(&e);
+            assert_eq!(pool.try_load_price(&e, &asset), Err(PoolError::ExcessivePriceDeviation));
+            e.ledger().set_timestamp(10000 + MAX_LAST_PRICE_AGE + 1);
+            oracle_client.set_price_stable(&vec![&e, 150_0000000]);
+            let mut pool = Pool::load(&e);
+            assert_eq!(pool.load_price(&e, &asset), 150_0000000);
+            let last_price = storage::get_last_price(&e, &asset).unwrap();
+            assert_eq!(last_price.price, 150_0000000);
+        });
+    }
+
     #[test]
     fn test_require_under_max_empty() {
         let e = Env::default();
@@ -628,12 +1483,19 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -661,12 +1523,19 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -697,12 +1566,19 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -734,6 +1610,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         let (oracle, _) = testutils::create_mock_oracle(&e);
         let pool_config = PoolConfig {
@@ -741,6 +1619,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
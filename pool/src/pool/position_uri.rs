@@ -0,0 +1,272 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    panic_with_error, unwrap::UnwrapOptimized, Address, Bytes, Env, Map, String, Vec,
+};
+
+use crate::{constants::SCALAR_7, errors::PoolError, storage};
+
+use super::{health_factor::PositionData, pool::Pool, User};
+
+/// The maximum length of a rendered position uri in bytes
+const MAX_URI_LEN: u32 = 4096;
+
+/// Render a compact JSON description of a user's positions, so wallets and explorers can display
+/// lending positions without a custom integration.
+///
+/// The document contains the underlying amount held for each collateral, liability, and supply
+/// position, the raw collateral and liability values denominated in the oracle's base asset, and
+/// the health factor band of the positions:
+///
+/// `{"owner":"G...","collateral":[{"asset":"C...","amount":1}],"liabilities":[..],"supply":[..],
+/// "collateral_value":1,"liability_value":0,"hf_band":"none"}`
+///
+/// The health factor band is one of "none" (no liabilities), "liquidatable" (under 1),
+/// "at_risk" (under 1.1), "moderate" (under 1.5), or "healthy".
+///
+/// ### Arguments
+/// * `user` - The owner of the positions
+///
+/// ### Panics
+/// If the rendered document exceeds the maximum uri length
+pub fn build_position_uri(e: &Env, user: &Address) -> String {
+    let mut pool = Pool::load(e);
+    let user_state = User::load(e, user);
+    let positions = user_state.positions;
+    let reserve_list = storage::get_res_list(e);
+
+    let mut uri = Bytes::new(e);
+    uri.extend_from_slice(b"{\"owner\":");
+    push_address(&mut uri, user);
+    for (key, balances, is_liability) in [
+        ("collateral", &positions.collateral, false),
+        ("liabilities", &positions.liabilities, true),
+        ("supply", &positions.supply, false),
+    ] {
+        uri.extend_from_slice(b",\"");
+        uri.extend_from_slice(key.as_bytes());
+        uri.extend_from_slice(b"\":[");
+        push_balances(e, &mut uri, &mut pool, &reserve_list, balances, is_liability);
+        uri.push_back(b']');
+    }
+
+    let emode = storage::get_user_emode(e, user);
+    let position_data = PositionData::calculate_from_positions(e, &mut pool, &positions, emode);
+    uri.extend_from_slice(b",\"collateral_value\":");
+    push_i128(&mut uri, position_data.collateral_raw);
+    uri.extend_from_slice(b",\"liability_value\":");
+    push_i128(&mut uri, position_data.liability_raw);
+    uri.extend_from_slice(b",\"hf_band\":\"");
+    uri.extend_from_slice(hf_band(&position_data).as_bytes());
+    uri.extend_from_slice(b"\"}");
+
+    if uri.len() > MAX_URI_LEN {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let mut buffer = [0u8; MAX_URI_LEN as usize];
+    let len = uri.len() as usize;
+    uri.copy_into_slice(&mut buffer[..len]);
+    String::from_bytes(e, &buffer[..len])
+}
+
+/// Fetch the health factor band for a set of position data
+fn hf_band(position_data: &PositionData) -> &'static str {
+    if position_data.liability_base == 0 {
+        return "none";
+    }
+    let hf = position_data
+        .as_health_factor()
+        .fixed_mul_floor(SCALAR_7, position_data.scalar)
+        .unwrap_optimized();
+    if hf < 1_0000000 {
+        "liquidatable"
+    } else if hf < 1_1000000 {
+        "at_risk"
+    } else if hf < 1_5000000 {
+        "moderate"
+    } else {
+        "healthy"
+    }
+}
+
+/// Append a JSON array body of `{"asset":..,"amount":..}` objects for a map of
+/// reserve indexes to b or d token balances
+fn push_balances(
+    e: &Env,
+    uri: &mut Bytes,
+    pool: &mut Pool,
+    reserve_list: &Vec<Address>,
+    balances: &Map<u32, i128>,
+    is_liability: bool,
+) {
+    let mut first = true;
+    for (index, balance) in balances.iter() {
+        let reserve = pool.load_reserve(e, &reserve_list.get_unchecked(index), false);
+        let amount = if is_liability {
+            reserve.to_asset_from_d_token(balance)
+        } else {
+            reserve.to_asset_from_b_token(balance)
+        };
+        if !first {
+            uri.push_back(b',');
+        }
+        first = false;
+        uri.extend_from_slice(b"{\"asset\":");
+        push_address(uri, &reserve.asset);
+        uri.extend_from_slice(b",\"amount\":");
+        push_i128(uri, amount);
+        uri.push_back(b'}');
+        pool.cache_reserve(reserve);
+    }
+}
+
+/// Append an address as a quoted strkey
+fn push_address(uri: &mut Bytes, address: &Address) {
+    let strkey = address.to_string();
+    let mut buffer = [0u8; 56];
+    let len = strkey.len() as usize;
+    strkey.copy_into_slice(&mut buffer[..len]);
+    uri.push_back(b'"');
+    uri.extend_from_slice(&buffer[..len]);
+    uri.push_back(b'"');
+}
+
+/// Append an integer in base 10
+fn push_i128(uri: &mut Bytes, value: i128) {
+    if value < 0 {
+        uri.push_back(b'-');
+    }
+    let mut digits = [0u8; 40];
+    let mut len = 0;
+    let mut remaining = value.unsigned_abs();
+    loop {
+        digits[len] = b'0' + (remaining % 10) as u8;
+        len += 1;
+        remaining /= 10;
+        if remaining == 0 {
+            break;
+        }
+    }
+    for digit in digits[..len].iter().rev() {
+        uri.push_back(*digit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::PoolConfig, testutils, Positions};
+    use sep_40_oracle::testutils::Asset;
+    use soroban_sdk::{map, testutils::Address as _, vec, Symbol};
+
+    fn to_std_string(string: &String) -> std::string::String {
+        let mut buffer = std::vec![0u8; string.len() as usize];
+        string.copy_into_slice(&mut buffer);
+        std::string::String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_build_position_uri() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.index = 1;
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 2_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions = Positions {
+            collateral: map![&e, (0, 100_0000000)],
+            liabilities: map![&e, (1, 20_0000000)],
+            supply: map![&e, (1, 5_0000000)],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &positions);
+
+            let uri = to_std_string(&build_position_uri(&e, &samwise));
+
+            // collateral: 100 * 0.75 = 75, liabilities: 40 / 0.75 = 53.33 -> hf 1.406
+            let expected = std::format!(
+                "{{\"owner\":\"{}\",\"collateral\":[{{\"asset\":\"{}\",\"amount\":1000000000}}],\"liabilities\":[{{\"asset\":\"{}\",\"amount\":200000000}}],\"supply\":[{{\"asset\":\"{}\",\"amount\":50000000}}],\"collateral_value\":10000000000000000,\"liability_value\":4000000000000000,\"hf_band\":\"moderate\"}}",
+                to_std_string(&samwise.to_string()),
+                to_std_string(&underlying_0.to_string()),
+                to_std_string(&underlying_1.to_string()),
+                to_std_string(&underlying_1.to_string()),
+            );
+            assert_eq!(uri, expected);
+        });
+    }
+
+    #[test]
+    fn test_build_position_uri_no_positions() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+        oracle_client.set_data(
+            &Address::generate(&e),
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e],
+            &7,
+            &300,
+        );
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let uri = to_std_string(&build_position_uri(&e, &samwise));
+            let expected = std::format!(
+                "{{\"owner\":\"{}\",\"collateral\":[],\"liabilities\":[],\"supply\":[],\"collateral_value\":0,\"liability_value\":0,\"hf_band\":\"none\"}}",
+                to_std_string(&samwise.to_string()),
+            );
+            assert_eq!(uri, expected);
+        });
+    }
+}
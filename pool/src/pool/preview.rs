@@ -0,0 +1,291 @@
+use cast::i128;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, Map};
+
+use crate::{
+    constants::{SCALAR_7, SCALAR_9},
+    storage,
+};
+
+use super::{
+    interest::{calc_interest_rate, clamp_interest_rate},
+    reserve::Reserve,
+};
+
+/// A preview of a reserve accrued to the current ledger timestamp
+#[derive(Clone)]
+#[contracttype]
+pub struct ReservePreview {
+    pub asset: Address,        // the underlying asset address
+    pub last_time: u64,        // the timestamp the preview was accrued to
+    pub d_rate: i128,          // the conversion rate from dToken to underlying (12 decimals)
+    pub b_rate: i128,          // the conversion rate from bToken to underlying (12 decimals)
+    pub ir_mod: i128,          // the interest rate curve modifier (9 decimals)
+    pub b_supply: i128,        // the total supply of b tokens
+    pub d_supply: i128,        // the total supply of d tokens
+    pub backstop_credit: i128, // the total amount of underlying tokens owed to the backstop
+    pub utilization: i128,     // the utilization rate of the reserve (7 decimals)
+    pub borrow_apr: i128,      // the current borrow rate (7 decimals)
+    pub supply_apr: i128,      // the current supply rate, net of the backstop rate (7 decimals)
+}
+
+/// The current interest rates of a reserve
+#[derive(Clone)]
+#[contracttype]
+pub struct ReserveRates {
+    pub utilization: i128, // the utilization rate of the reserve (7 decimals)
+    pub borrow_apr: i128,  // the current borrow rate (7 decimals)
+    pub supply_apr: i128,  // the current supply rate, net of the backstop rate (7 decimals)
+    pub borrow_apy: i128,  // the borrow rate compounded daily (7 decimals)
+    pub supply_apy: i128,  // the supply rate compounded daily (7 decimals)
+}
+
+/// Load a preview of a reserve accrued to the current ledger timestamp. Nothing is written
+/// to the ledger other than the reserve's accrual snapshot.
+///
+/// ### Arguments
+/// * `asset` - The address of the underlying asset
+///
+/// ### Panics
+/// If the asset is not supported
+pub fn load_reserve_preview(e: &Env, asset: &Address) -> ReservePreview {
+    let pool_config = storage::get_pool_config(e);
+    let reserve = Reserve::load(e, &pool_config, asset);
+    let rates = calc_reserve_rates(e, pool_config.bstop_rate, &reserve);
+
+    ReservePreview {
+        asset: asset.clone(),
+        last_time: reserve.last_time,
+        d_rate: reserve.d_rate,
+        b_rate: reserve.b_rate,
+        ir_mod: reserve.ir_mod,
+        b_supply: reserve.b_supply,
+        d_supply: reserve.d_supply,
+        backstop_credit: reserve.backstop_credit,
+        utilization: rates.utilization,
+        borrow_apr: rates.borrow_apr,
+        supply_apr: rates.supply_apr,
+    }
+}
+
+/// Load the current interest rates of a reserve. The borrow rate is calculated from the
+/// interest rate curve at the current utilization, and the supply rate is the share of the
+/// borrow rate paid to suppliers after the backstop rate.
+///
+/// ### Arguments
+/// * `asset` - The address of the underlying asset
+///
+/// ### Panics
+/// If the asset is not supported
+pub fn load_reserve_rates(e: &Env, asset: &Address) -> ReserveRates {
+    let pool_config = storage::get_pool_config(e);
+    let reserve = Reserve::load(e, &pool_config, asset);
+    calc_reserve_rates(e, pool_config.bstop_rate, &reserve)
+}
+
+/// Load the total underlying tokens ever credited to the backstop by each reserve, including
+/// interest accrued to the current ledger timestamp. Nothing is written to the ledger other than
+/// the reserves' accrual snapshots.
+///
+/// Returns a map of each reserve's underlying asset to its accrued fees
+pub fn load_accrued_fees(e: &Env) -> Map<Address, i128> {
+    let pool_config = storage::get_pool_config(e);
+    let mut accrued_fees = Map::new(e);
+    for asset in storage::get_res_list(e).iter() {
+        let reserve = Reserve::load(e, &pool_config, &asset);
+        accrued_fees.set(asset, reserve.accrued_fees);
+    }
+    accrued_fees
+}
+
+/// Calculate the interest rates of a reserve that has been accrued to the current timestamp
+pub(super) fn calc_reserve_rates(e: &Env, bstop_rate: u32, reserve: &Reserve) -> ReserveRates {
+    let reserve_config = storage::get_res_config(e, &reserve.asset);
+
+    let utilization = if reserve.b_supply == 0 {
+        0
+    } else {
+        reserve.utilization()
+    };
+    let curve_apr = calc_interest_rate(
+        &reserve_config,
+        &storage::get_res_ir_config(e, &reserve.asset),
+        utilization,
+        reserve.ir_mod,
+    );
+    let rate_bounds = storage::get_res_rate_bounds(e, &reserve.asset);
+    let borrow_apr = clamp_interest_rate(curve_apr, &rate_bounds);
+    let supply_apr = borrow_apr
+        .fixed_mul_floor(utilization, SCALAR_7)
+        .unwrap_optimized()
+        .fixed_mul_floor(SCALAR_7 - i128(bstop_rate), SCALAR_7)
+        .unwrap_optimized();
+
+    ReserveRates {
+        utilization,
+        borrow_apr,
+        supply_apr,
+        borrow_apy: calc_apy(borrow_apr),
+        supply_apy: calc_apy(supply_apr),
+    }
+}
+
+/// Calculate the annual yield of a rate compounded daily
+///
+/// ### Arguments
+/// * `apr` - The annual rate (7 decimals)
+fn calc_apy(apr: i128) -> i128 {
+    // scale the daily rate to 9 decimals to reduce rounding error
+    let mut daily_rate = SCALAR_9 + apr * 100 / 365;
+    let mut result = SCALAR_9;
+    let mut periods: u32 = 365;
+    while periods > 0 {
+        if periods & 1 == 1 {
+            result = result
+                .fixed_mul_floor(daily_rate, SCALAR_9)
+                .unwrap_optimized();
+        }
+        periods >>= 1;
+        if periods > 0 {
+            daily_rate = daily_rate
+                .fixed_mul_floor(daily_rate, SCALAR_9)
+                .unwrap_optimized();
+        }
+    }
+    (result - SCALAR_9) / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::PoolConfig, testutils};
+    use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+
+    #[test]
+    fn test_load_accrued_fees() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 123456 * 5,
+            protocol_version: 22,
+            sequence_number: 123456,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.d_rate = 1_345_678_123_000;
+        reserve_data.b_rate = 1_123_456_789_000;
+        reserve_data.d_supply = 65_0000000;
+        reserve_data.b_supply = 99_0000000;
+        reserve_data.accrued_fees = 1_0000000;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.index = 1;
+        reserve_data.last_time = 123456 * 5;
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 5,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            let accrued_fees = load_accrued_fees(&e);
+
+            assert_eq!(accrued_fees.len(), 2);
+            assert_eq!(accrued_fees.get_unchecked(underlying_0), 1_0517357);
+            assert_eq!(accrued_fees.get_unchecked(underlying_1), 0);
+        });
+    }
+
+    #[test]
+    fn test_load_reserve_preview() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 123456 * 5,
+            protocol_version: 22,
+            sequence_number: 123456,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.d_rate = 1_345_678_123_000;
+        reserve_data.b_rate = 1_123_456_789_000;
+        reserve_data.d_supply = 65_0000000;
+        reserve_data.b_supply = 99_0000000;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 5,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            let preview = load_reserve_preview(&e, &underlying);
+
+            assert_eq!(preview.last_time, 617280);
+            assert_eq!(preview.d_rate, 1_349_657_798_173);
+            assert_eq!(preview.b_rate, 1_125_547_124_242);
+            assert_eq!(preview.ir_mod, 1_044_981_563);
+            assert_eq!(preview.backstop_credit, 0_0517357);
+            assert_eq!(preview.utilization, 0_7872962);
+            assert_eq!(preview.borrow_apr, 0_1601335);
+            assert_eq!(preview.supply_apr, 0_1008579);
+
+            let rates = load_reserve_rates(&e, &underlying);
+            assert_eq!(rates.utilization, 0_7872962);
+            assert_eq!(rates.borrow_apr, 0_1601335);
+            assert_eq!(rates.supply_apr, 0_1008579);
+            assert_eq!(rates.borrow_apy, 0_1736257);
+            assert_eq!(rates.supply_apy, 0_1061038);
+
+            // the reserve data is not written to the ledger
+            let reserve_data = storage::get_res_data(&e, &underlying);
+            assert_eq!(reserve_data.d_rate, 1_345_678_123_000);
+            assert_eq!(reserve_data.last_time, 0);
+        });
+    }
+
+    #[test]
+    fn test_calc_apy() {
+        assert_eq!(calc_apy(0), 0);
+        assert_eq!(calc_apy(0_1000000), 0_1051554);
+    }
+}
@@ -0,0 +1,364 @@
+use sep_40_oracle::PriceFeedClient;
+use soroban_sdk::{contracttype, panic_with_error, xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+use crate::{
+    errors::PoolError,
+    storage::{self, PriceSigners},
+};
+
+/// A price for an asset signed by one of the pool's price signers
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceAttestation {
+    pub asset: Address,
+    pub price: i128,            // the price of the asset, expressed in the oracle's decimals
+    pub timestamp: u64,         // the timestamp the price was observed at
+    pub signer: BytesN<32>,     // the ed25519 public key of the signer
+    pub signature: BytesN<64>,  // the ed25519 signature of the attestation payload
+}
+
+/// Set the keys allowed to sign price attestations for the pool. An empty set of signers
+/// disables price attestations.
+///
+/// The max age is bounded by the resolution of the pool's oracle, so a submitter can't pick
+/// between more signed prices than the oracle would have published over the same period.
+///
+/// ### Arguments
+/// * `signers` - The ed25519 public keys allowed to sign price attestations
+/// * `max_age` - The maximum age of an attestation in seconds
+///
+/// ### Panics
+/// If the max age is zero, greater than one day, or greater than the oracle's resolution
+pub fn execute_set_price_signers(e: &Env, signers: &Vec<BytesN<32>>, max_age: u64) {
+    let oracle = storage::get_pool_config(e).oracle;
+    let resolution = u64::from(PriceFeedClient::new(e, &oracle).resolution());
+    if max_age == 0 || max_age > 24 * 60 * 60 || max_age > resolution {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_price_signers(
+        e,
+        &PriceSigners {
+            signers: signers.clone(),
+            max_age,
+        },
+    );
+}
+
+/// Build the payload signed by a price signer. The payload is the XDR encoded pool address,
+/// followed by the XDR encoded asset address, the big-endian price, and the big-endian timestamp.
+///
+/// ### Arguments
+/// * `asset` - The address of the asset
+/// * `price` - The price of the asset
+/// * `timestamp` - The timestamp the price was observed at
+pub fn attestation_payload(e: &Env, asset: &Address, price: i128, timestamp: u64) -> Bytes {
+    let mut payload = e.current_contract_address().to_xdr(e);
+    payload.append(&asset.clone().to_xdr(e));
+    payload.extend_from_array(&price.to_be_bytes());
+    payload.extend_from_array(&timestamp.to_be_bytes());
+    payload
+}
+
+/// Verify a price attestation against the pool's price signers. Attestations for an asset must
+/// be used in timestamp order, so an older signed price can't replace a newer one.
+///
+/// ### Arguments
+/// * `price_signers` - The pool's price signers
+/// * `attestation` - The price attestation to verify
+///
+/// ### Panics
+/// If the attestation is not signed by a price signer, has a non-positive price, is from the
+/// future, is older than the max age, or is older than the last attestation used for the asset
+pub fn verify_price_attestation(
+    e: &Env,
+    price_signers: &PriceSigners,
+    attestation: &PriceAttestation,
+) {
+    if !price_signers.signers.contains(&attestation.signer) || attestation.price <= 0 {
+        panic_with_error!(e, PoolError::InvalidPriceAttestation);
+    }
+    let now = e.ledger().timestamp();
+    if attestation.timestamp > now {
+        panic_with_error!(e, PoolError::InvalidPriceAttestation);
+    }
+    if attestation.timestamp + price_signers.max_age < now {
+        panic_with_error!(e, PoolError::StalePrice);
+    }
+    if storage::get_last_attestation_time(e, &attestation.asset)
+        .is_some_and(|last_timestamp| attestation.timestamp < last_timestamp)
+    {
+        panic_with_error!(e, PoolError::InvalidPriceAttestation);
+    }
+    let payload = attestation_payload(
+        e,
+        &attestation.asset,
+        attestation.price,
+        attestation.timestamp,
+    );
+    // panics if the signature is invalid
+    e.crypto()
+        .ed25519_verify(&attestation.signer, &payload, &attestation.signature);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool::Pool, storage::PoolConfig, testutils};
+    use ed25519_dalek::{Signer, SigningKey};
+    use sep_40_oracle::testutils::Asset;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger, LedgerInfo},
+        vec, Symbol,
+    };
+
+    fn sign_attestation(
+        e: &Env,
+        key: &SigningKey,
+        asset: &Address,
+        price: i128,
+        timestamp: u64,
+    ) -> PriceAttestation {
+        let payload = attestation_payload(e, asset, price, timestamp);
+        let mut message = std::vec::Vec::new();
+        for byte in payload.iter() {
+            message.push(byte);
+        }
+        PriceAttestation {
+            asset: asset.clone(),
+            price,
+            timestamp,
+            signer: BytesN::from_array(e, &key.verifying_key().to_bytes()),
+            signature: BytesN::from_array(e, &key.sign(&message).to_bytes()),
+        }
+    }
+
+    fn set_ledger(e: &Env) {
+        e.ledger().set(LedgerInfo {
+            timestamp: 123456 * 5,
+            protocol_version: 22,
+            sequence_number: 123456,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+    }
+
+    fn setup_oracle(e: &Env, pool: &Address, asset: &Address, resolution: u32) {
+        let bombadil = Address::generate(e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(e);
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(e, "USD")),
+            &vec![e, Asset::Stellar(asset.clone())],
+            &7,
+            &resolution,
+        );
+        oracle_client.set_price_stable(&vec![e, 1_0000000]);
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(pool, || storage::set_pool_config(e, &pool_config));
+    }
+
+    #[test]
+    fn test_set_price_signers() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        setup_oracle(&e, &pool, &Address::generate(&e), 300);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signers = vec![&e, BytesN::from_array(&e, &key.verifying_key().to_bytes())];
+
+        e.as_contract(&pool, || {
+            execute_set_price_signers(&e, &signers, 300);
+
+            let price_signers = storage::get_price_signers(&e).unwrap();
+            assert_eq!(price_signers.signers, signers);
+            assert_eq!(price_signers.max_age, 300);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_price_signers_validates_max_age() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        setup_oracle(&e, &pool, &Address::generate(&e), 2 * 24 * 60 * 60);
+
+        e.as_contract(&pool, || {
+            execute_set_price_signers(&e, &vec![&e], 24 * 60 * 60 + 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_price_signers_max_age_over_resolution() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        setup_oracle(&e, &pool, &Address::generate(&e), 300);
+
+        e.as_contract(&pool, || {
+            execute_set_price_signers(&e, &vec![&e], 301);
+        });
+    }
+
+    #[test]
+    fn test_verify_price_attestation() {
+        let e = Env::default();
+        set_ledger(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let price_signers = PriceSigners {
+            signers: vec![&e, BytesN::from_array(&e, &key.verifying_key().to_bytes())],
+            max_age: 300,
+        };
+
+        e.as_contract(&pool, || {
+            let attestation = sign_attestation(&e, &key, &asset, 1_2345678, 123456 * 5 - 300);
+            verify_price_attestation(&e, &price_signers, &attestation);
+
+            // an attestation as recent as the last one used is valid
+            storage::set_last_attestation_time(&e, &asset, 123456 * 5 - 300);
+            verify_price_attestation(&e, &price_signers, &attestation);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1225)")]
+    fn test_verify_price_attestation_older_than_last() {
+        let e = Env::default();
+        set_ledger(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let price_signers = PriceSigners {
+            signers: vec![&e, BytesN::from_array(&e, &key.verifying_key().to_bytes())],
+            max_age: 300,
+        };
+
+        e.as_contract(&pool, || {
+            storage::set_last_attestation_time(&e, &asset, 123456 * 5 - 100);
+            let attestation = sign_attestation(&e, &key, &asset, 1_2345678, 123456 * 5 - 101);
+            verify_price_attestation(&e, &price_signers, &attestation);
+        });
+    }
+
+    #[test]
+    fn test_load_price_attestations_records_timestamp() {
+        let e = Env::default();
+        set_ledger(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        setup_oracle(&e, &pool, &asset, 300);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signers = vec![&e, BytesN::from_array(&e, &key.verifying_key().to_bytes())];
+
+        e.as_contract(&pool, || {
+            execute_set_price_signers(&e, &signers, 300);
+            let attestation = sign_attestation(&e, &key, &asset, 1_2345678, 123456 * 5 - 10);
+            let mut pool_state = Pool::load(&e);
+            pool_state.load_price_attestations(&e, &vec![&e, attestation]);
+
+            assert_eq!(pool_state.load_price(&e, &asset), 1_2345678_0000000);
+            assert_eq!(
+                storage::get_last_attestation_time(&e, &asset),
+                Some(123456 * 5 - 10)
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1225)")]
+    fn test_load_price_attestations_duplicate_asset() {
+        let e = Env::default();
+        set_ledger(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        setup_oracle(&e, &pool, &asset, 300);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signers = vec![&e, BytesN::from_array(&e, &key.verifying_key().to_bytes())];
+
+        e.as_contract(&pool, || {
+            execute_set_price_signers(&e, &signers, 300);
+            let attestations = vec![
+                &e,
+                sign_attestation(&e, &key, &asset, 1_2345678, 123456 * 5 - 10),
+                sign_attestation(&e, &key, &asset, 1_0000000, 123456 * 5),
+            ];
+            let mut pool_state = Pool::load(&e);
+            pool_state.load_price_attestations(&e, &attestations);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1225)")]
+    fn test_verify_price_attestation_unknown_signer() {
+        let e = Env::default();
+        set_ledger(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let other_key = SigningKey::from_bytes(&[2u8; 32]);
+        let price_signers = PriceSigners {
+            signers: vec![
+                &e,
+                BytesN::from_array(&e, &other_key.verifying_key().to_bytes()),
+            ],
+            max_age: 300,
+        };
+
+        e.as_contract(&pool, || {
+            let attestation = sign_attestation(&e, &key, &asset, 1_2345678, 123456 * 5);
+            verify_price_attestation(&e, &price_signers, &attestation);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1210)")]
+    fn test_verify_price_attestation_stale() {
+        let e = Env::default();
+        set_ledger(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let price_signers = PriceSigners {
+            signers: vec![&e, BytesN::from_array(&e, &key.verifying_key().to_bytes())],
+            max_age: 300,
+        };
+
+        e.as_contract(&pool, || {
+            let attestation = sign_attestation(&e, &key, &asset, 1_2345678, 123456 * 5 - 301);
+            verify_price_attestation(&e, &price_signers, &attestation);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_verify_price_attestation_tampered_price() {
+        let e = Env::default();
+        set_ledger(&e);
+        let pool = testutils::create_pool(&e);
+        let asset = Address::generate(&e);
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let price_signers = PriceSigners {
+            signers: vec![&e, BytesN::from_array(&e, &key.verifying_key().to_bytes())],
+            max_age: 300,
+        };
+
+        e.as_contract(&pool, || {
+            let mut attestation = sign_attestation(&e, &key, &asset, 1_2345678, 123456 * 5);
+            attestation.price = 2_0000000;
+            verify_price_attestation(&e, &price_signers, &attestation);
+        });
+    }
+}
@@ -0,0 +1,498 @@
+use sep_41_token::TokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Vec};
+
+use crate::{
+    constants::{SCALAR_7, SECONDS_PER_WEEK},
+    errors::PoolError,
+    events::PoolEvents,
+    storage::{self, QueuedDebtRebate},
+};
+
+use super::{Reserve, User};
+
+/// Execute queueing a debt rebate for a set of accounts in a reserve
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `accounts` - The accounts receiving the rebate
+/// * `rebate` - The portion of each account's dTokens to forgive (7 decimals)
+/// * `funder` - The address funding the rebate, or None to fund it from the backstop credit
+///
+/// ### Panics
+/// If a rebate is already queued for the reserve, an account is included more than once, or the
+/// arguments are invalid
+pub fn execute_queue_debt_rebate(
+    e: &Env,
+    asset: &Address,
+    accounts: &Vec<Address>,
+    rebate: i128,
+    funder: &Option<Address>,
+) {
+    if storage::has_queued_debt_rebate(e, asset)
+        || !storage::has_res(e, asset)
+        || accounts.is_empty()
+        || rebate <= 0
+        || rebate > SCALAR_7
+    {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let mut seen: Vec<Address> = vec![e];
+    for account in accounts.iter() {
+        if seen.contains(&account) {
+            panic_with_error!(e, PoolError::BadRequest);
+        }
+        seen.push_back(account);
+    }
+    storage::set_queued_debt_rebate(
+        e,
+        asset,
+        &QueuedDebtRebate {
+            accounts: accounts.clone(),
+            rebate,
+            funder: funder.clone(),
+            unlock_time: e.ledger().timestamp() + SECONDS_PER_WEEK,
+        },
+    );
+}
+
+/// Execute cancelling a queued debt rebate
+pub fn execute_cancel_queued_debt_rebate(e: &Env, asset: &Address) {
+    storage::del_queued_debt_rebate(e, asset);
+}
+
+/// Execute a queued debt rebate. The forgiven debt is paid for by the funder or by the
+/// reserve's backstop credit, such that suppliers are not impacted.
+///
+/// Returns the total amount of underlying tokens forgiven
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+///
+/// ### Panics
+/// If the rebate is not queued, not unlocked, or the backstop credit cannot cover the rebate
+pub fn execute_debt_rebate(e: &Env, asset: &Address) -> i128 {
+    let queued_rebate = storage::get_queued_debt_rebate(e, asset);
+    if queued_rebate.unlock_time > e.ledger().timestamp() {
+        panic_with_error!(e, PoolError::InitNotUnlocked);
+    }
+    storage::del_queued_debt_rebate(e, asset);
+
+    let pool_config = storage::get_pool_config(e);
+    let mut reserve = Reserve::load(e, &pool_config, asset);
+    let mut total_forgiven: i128 = 0;
+    for account in queued_rebate.accounts.iter() {
+        let mut user = User::load(e, &account);
+        let d_tokens_burnt = user
+            .get_liabilities(reserve.index)
+            .fixed_mul_floor(queued_rebate.rebate, SCALAR_7)
+            .unwrap_optimized();
+        if d_tokens_burnt > 0 {
+            let tokens_forgiven = reserve.to_asset_from_d_token(d_tokens_burnt);
+            user.remove_liabilities(e, &mut reserve, d_tokens_burnt);
+            user.store(e);
+            total_forgiven += tokens_forgiven;
+
+            PoolEvents::debt_rebate(e, asset.clone(), account, tokens_forgiven, d_tokens_burnt);
+        }
+    }
+
+    if total_forgiven > 0 {
+        match &queued_rebate.funder {
+            Some(funder) => {
+                TokenClient::new(e, asset).transfer(
+                    funder,
+                    &e.current_contract_address(),
+                    &total_forgiven,
+                );
+            }
+            None => {
+                if total_forgiven > reserve.backstop_credit {
+                    panic_with_error!(e, PoolError::BadRequest);
+                }
+                reserve.backstop_credit -= total_forgiven;
+            }
+        }
+    }
+    reserve.store(e);
+
+    PoolEvents::fund_debt_rebate(e, asset.clone(), queued_rebate.funder, total_forgiven);
+    total_forgiven
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pool::Positions, storage::PoolConfig, testutils};
+
+    use super::*;
+    use soroban_sdk::{
+        map,
+        testutils::{Address as _, Ledger, LedgerInfo},
+        vec,
+    };
+
+    /***** execute_queue_debt_rebate ******/
+
+    #[test]
+    fn test_queue_debt_rebate() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 123,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let accounts = vec![&e, samwise.clone(), frodo.clone()];
+        e.as_contract(&pool, || {
+            execute_queue_debt_rebate(&e, &underlying_0, &accounts, 0_5000000, &None);
+
+            let queued_rebate = storage::get_queued_debt_rebate(&e, &underlying_0);
+            assert_eq!(queued_rebate.accounts, accounts);
+            assert_eq!(queued_rebate.rebate, 0_5000000);
+            assert_eq!(queued_rebate.funder, None);
+            assert_eq!(queued_rebate.unlock_time, 600 + SECONDS_PER_WEEK);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_queue_debt_rebate_validates_rebate() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            execute_queue_debt_rebate(
+                &e,
+                &underlying_0,
+                &vec![&e, samwise.clone()],
+                1_0000001,
+                &None,
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_queue_debt_rebate_requires_reserve() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let pool = testutils::create_pool(&e);
+        let samwise = Address::generate(&e);
+        let underlying_0 = Address::generate(&e);
+
+        e.as_contract(&pool, || {
+            execute_queue_debt_rebate(
+                &e,
+                &underlying_0,
+                &vec![&e, samwise.clone()],
+                0_5000000,
+                &None,
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_queue_debt_rebate_duplicate_account() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            execute_queue_debt_rebate(
+                &e,
+                &underlying_0,
+                &vec![&e, samwise.clone(), frodo.clone(), samwise.clone()],
+                0_5000000,
+                &None,
+            );
+        });
+    }
+
+    /***** execute_debt_rebate ******/
+
+    #[test]
+    fn test_execute_debt_rebate_from_backstop_credit() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 123,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.backstop_credit = 10_0000000;
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let samwise_positions = Positions {
+            liabilities: map![&e, (0, 10_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        let frodo_positions = Positions {
+            liabilities: map![&e, (0, 5_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &samwise_positions);
+            storage::set_user_positions(&e, &frodo, &frodo_positions);
+            storage::set_queued_debt_rebate(
+                &e,
+                &underlying_0,
+                &QueuedDebtRebate {
+                    accounts: vec![&e, samwise.clone(), frodo.clone()],
+                    rebate: 0_5000000,
+                    funder: None,
+                    unlock_time: 600,
+                },
+            );
+
+            let pre_balance = underlying_0_client.balance(&pool);
+            let forgiven = execute_debt_rebate(&e, &underlying_0);
+            assert_eq!(forgiven, 7_5000000);
+
+            let samwise_positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(samwise_positions.liabilities.get_unchecked(0), 5_0000000);
+            let frodo_positions = storage::get_user_positions(&e, &frodo);
+            assert_eq!(frodo_positions.liabilities.get_unchecked(0), 2_5000000);
+
+            let reserve_data = storage::get_res_data(&e, &underlying_0);
+            assert_eq!(reserve_data.d_supply, 67_5000000);
+            assert_eq!(reserve_data.backstop_credit, 2_5000000);
+            assert_eq!(reserve_data.b_rate, 1_000_000_000_000);
+            assert_eq!(underlying_0_client.balance(&pool), pre_balance);
+            assert!(!storage::has_queued_debt_rebate(&e, &underlying_0));
+        });
+    }
+
+    #[test]
+    fn test_execute_debt_rebate_from_funder() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 123,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let treasury = Address::generate(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+        underlying_0_client.mint(&treasury, &10_0000000);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let samwise_positions = Positions {
+            liabilities: map![&e, (0, 10_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &samwise_positions);
+            storage::set_queued_debt_rebate(
+                &e,
+                &underlying_0,
+                &QueuedDebtRebate {
+                    accounts: vec![&e, samwise.clone()],
+                    rebate: 1_0000000,
+                    funder: Some(treasury.clone()),
+                    unlock_time: 600,
+                },
+            );
+
+            let pre_balance = underlying_0_client.balance(&pool);
+            let forgiven = execute_debt_rebate(&e, &underlying_0);
+            assert_eq!(forgiven, 10_0000000);
+
+            let samwise_positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(samwise_positions.liabilities.len(), 0);
+
+            let reserve_data = storage::get_res_data(&e, &underlying_0);
+            assert_eq!(reserve_data.d_supply, 65_0000000);
+            assert_eq!(reserve_data.backstop_credit, 0);
+            assert_eq!(underlying_0_client.balance(&pool), pre_balance + 10_0000000);
+            assert_eq!(underlying_0_client.balance(&treasury), 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1203)")]
+    fn test_execute_debt_rebate_requires_unlock() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 123,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.backstop_credit = 10_0000000;
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            storage::set_queued_debt_rebate(
+                &e,
+                &underlying_0,
+                &QueuedDebtRebate {
+                    accounts: vec![&e, samwise.clone()],
+                    rebate: 0_5000000,
+                    funder: None,
+                    unlock_time: 601,
+                },
+            );
+
+            execute_debt_rebate(&e, &underlying_0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_debt_rebate_insufficient_backstop_credit() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 123,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let pool = testutils::create_pool(&e);
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.backstop_credit = 1_0000000;
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let samwise_positions = Positions {
+            liabilities: map![&e, (0, 10_0000000)],
+            collateral: map![&e],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &samwise_positions);
+            storage::set_queued_debt_rebate(
+                &e,
+                &underlying_0,
+                &QueuedDebtRebate {
+                    accounts: vec![&e, samwise.clone()],
+                    rebate: 0_5000000,
+                    funder: None,
+                    unlock_time: 600,
+                },
+            );
+
+            execute_debt_rebate(&e, &underlying_0);
+        });
+    }
+}
@@ -0,0 +1,229 @@
+use cast::i128;
+use sep_41_token::TokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Vec};
+
+use crate::{
+    constants::{SCALAR_12, SCALAR_7},
+    errors::PoolError,
+    events::PoolEvents,
+    storage,
+};
+
+use super::Reserve;
+
+/// Set the share of the backstop take rate paid to referrers
+///
+/// ### Arguments
+/// * `rate` - The share of the backstop take rate (7 decimals)
+///
+/// ### Panics
+/// If the rate is over 100%
+pub fn execute_set_referral_rate(e: &Env, rate: u32) {
+    if i128(rate) > SCALAR_7 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    storage::set_referral_rate(e, &rate);
+}
+
+/// Attribute a referrer to a user. A user keeps the first referrer they are attributed to.
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `referrer` - The address of the referrer
+///
+/// ### Panics
+/// If the user refers themselves
+pub fn record_referrer(e: &Env, user: &Address, referrer: &Address) {
+    if user == referrer {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    if storage::get_referrer(e, user).is_none() {
+        storage::set_referrer(e, user, referrer);
+        PoolEvents::set_referrer(e, user.clone(), referrer.clone());
+    }
+}
+
+/// Accrue the referral fees owed for a user's liability in a reserve since it was last updated.
+/// The fees are the referral rate's share of the backstop credit accrued on the liability, and are
+/// deducted from the reserve's backstop credit.
+///
+/// ### Arguments
+/// * `reserve` - The reserve the liability is in, accrued to the current ledger
+/// * `user` - The address of the user
+/// * `d_tokens` - The user's liability balance in dTokens before it is updated
+pub fn accrue_referral_fees(e: &Env, reserve: &mut Reserve, user: &Address, d_tokens: i128) {
+    let referrer = match storage::get_referrer(e, user) {
+        Some(referrer) => referrer,
+        None => return,
+    };
+    let referral_rate = storage::get_referral_rate(e);
+    if let Some(last_d_rate) = storage::get_referral_d_rate(e, user, reserve.index) {
+        if d_tokens > 0 && referral_rate > 0 && reserve.d_rate > last_d_rate {
+            let bstop_rate = storage::get_pool_config(e).bstop_rate;
+            let interest = d_tokens
+                .fixed_mul_floor(reserve.d_rate - last_d_rate, SCALAR_12)
+                .unwrap_optimized();
+            let fee = interest
+                .fixed_mul_floor(i128(bstop_rate), SCALAR_7)
+                .unwrap_optimized()
+                .fixed_mul_floor(i128(referral_rate), SCALAR_7)
+                .unwrap_optimized()
+                .min(reserve.backstop_credit);
+            if fee > 0 {
+                reserve.backstop_credit -= fee;
+                let fees = storage::get_referral_fees(e, &referrer, &reserve.asset);
+                storage::set_referral_fees(e, &referrer, &reserve.asset, fees + fee);
+            }
+        }
+    }
+    storage::set_referral_d_rate(e, user, reserve.index, reserve.d_rate);
+}
+
+/// Claim the referral fees owed to a referrer
+///
+/// Returns the amount claimed for each asset
+///
+/// ### Arguments
+/// * `referrer` - The address of the referrer
+/// * `assets` - The underlying assets to claim fees for
+/// * `to` - The address to send the fees to
+pub fn execute_claim_referral_fees(
+    e: &Env,
+    referrer: &Address,
+    assets: Vec<Address>,
+    to: &Address,
+) -> Vec<i128> {
+    let mut claimed: Vec<i128> = vec![e];
+    for asset in assets.iter() {
+        let amount = storage::get_referral_fees(e, referrer, &asset);
+        if amount > 0 {
+            storage::set_referral_fees(e, referrer, &asset, 0);
+            TokenClient::new(e, &asset).transfer(&e.current_contract_address(), to, &amount);
+            PoolEvents::claim_referral_fees(e, referrer.clone(), asset, amount);
+        }
+        claimed.push_back(amount);
+    }
+    claimed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{storage::PoolConfig, testutils};
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_accrue_referral_fees() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.d_rate = 1_100_000_000_000;
+        reserve.backstop_credit = 5_0000000;
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_referral_rate(&e, &0_5000000);
+            record_referrer(&e, &samwise, &frodo);
+
+            // the first update only records the dToken rate
+            accrue_referral_fees(&e, &mut reserve, &samwise, 100_0000000);
+            assert_eq!(reserve.backstop_credit, 5_0000000);
+            assert_eq!(storage::get_referral_d_rate(&e, &samwise, 0), Some(1_100_000_000_000));
+
+            reserve.d_rate = 1_210_000_000_000;
+            accrue_referral_fees(&e, &mut reserve, &samwise, 100_0000000);
+            // 11 tokens of interest, 2.2 to the backstop, 1.1 to the referrer
+            assert_eq!(reserve.backstop_credit, 3_9000000);
+            assert_eq!(storage::get_referral_fees(&e, &frodo, &reserve.asset), 1_1000000);
+            assert_eq!(storage::get_referral_d_rate(&e, &samwise, 0), Some(1_210_000_000_000));
+        });
+    }
+
+    #[test]
+    fn test_record_referrer_keeps_first_referrer() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            record_referrer(&e, &samwise, &frodo);
+            record_referrer(&e, &samwise, &merry);
+            assert_eq!(storage::get_referrer(&e, &samwise), Some(frodo.clone()));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_record_referrer_self() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            record_referrer(&e, &samwise, &samwise);
+        });
+    }
+
+    #[test]
+    fn test_claim_referral_fees() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, underlying_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+        underlying_client.mint(&pool, &10_0000000);
+
+        e.as_contract(&pool, || {
+            storage::set_referral_fees(&e, &frodo, &underlying, 1_1000000);
+
+            let claimed = execute_claim_referral_fees(
+                &e,
+                &frodo,
+                vec![&e, underlying.clone(), underlying.clone()],
+                &frodo,
+            );
+            assert_eq!(claimed, vec![&e, 1_1000000, 0]);
+            assert_eq!(storage::get_referral_fees(&e, &frodo, &underlying), 0);
+            assert_eq!(underlying_client.balance(&frodo), 1_1000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_referral_rate_too_high() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            execute_set_referral_rate(&e, 1_0000001);
+        });
+    }
+}
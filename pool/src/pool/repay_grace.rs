@@ -0,0 +1,209 @@
+use cast::i128;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env};
+
+use crate::{
+    constants::{MAX_REPAY_GRACE_WINDOW, SCALAR_12, SCALAR_7},
+    errors::PoolError,
+    storage::{self, BorrowSnapshot, RepayGraceConfig},
+};
+
+use super::{Reserve, User};
+
+/// Set the interest rebate for repaying a reserve shortly after borrowing. A window or rebate of
+/// zero removes the rebate.
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `window` - The number of seconds after borrowing a repayment receives the rebate
+/// * `rebate` - The share of the accrued interest refunded (7 decimals)
+///
+/// ### Panics
+/// If the reserve does not exist, the window is over the max, or the rebate is over 100%
+pub fn execute_set_repay_grace(e: &Env, asset: &Address, window: u64, rebate: u32) {
+    if !storage::has_res(e, asset) || window > MAX_REPAY_GRACE_WINDOW || i128(rebate) > SCALAR_7 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    if window == 0 || rebate == 0 {
+        storage::set_repay_grace_config(e, asset, &None);
+    } else {
+        storage::set_repay_grace_config(e, asset, &Some(RepayGraceConfig { window, rebate }));
+    }
+}
+
+/// Record a borrow from the reserve for the repayment grace window, if the reserve has one
+///
+/// ### Arguments
+/// * `reserve` - The reserve being borrowed from, accrued to the current ledger
+/// * `user` - The user borrowing
+pub fn record_borrow(e: &Env, reserve: &Reserve, user: &User) {
+    if user.account_id != 0 || user.read_only {
+        return;
+    }
+    if let Some(config) = storage::get_repay_grace_config(e, &reserve.asset) {
+        // ledgers close at most once a second, so this outlives the window
+        let duration = config.window as u32;
+        let snapshot = BorrowSnapshot {
+            timestamp: e.ledger().timestamp(),
+            d_rate: reserve.d_rate,
+        };
+        storage::set_user_last_borrow(e, &user.address, reserve.index, &snapshot, duration);
+    }
+}
+
+/// Calculate the interest rebate for a repayment within the repayment grace window of the user's
+/// most recent borrow. The rebate is the configured share of the interest accrued on the repaid
+/// dTokens since that borrow, and is deducted from the reserve's backstop credit.
+///
+/// Returns the amount of underlying tokens to refund
+///
+/// ### Arguments
+/// * `reserve` - The reserve being repaid, accrued to the current ledger
+/// * `user` - The user whose liability is repaid
+/// * `d_tokens_burnt` - The amount of dTokens repaid
+pub fn apply_repay_rebate(
+    e: &Env,
+    reserve: &mut Reserve,
+    user: &User,
+    d_tokens_burnt: i128,
+) -> i128 {
+    if user.account_id != 0 {
+        return 0;
+    }
+    let config = match storage::get_repay_grace_config(e, &reserve.asset) {
+        Some(config) => config,
+        None => return 0,
+    };
+    let snapshot = match storage::get_user_last_borrow(e, &user.address, reserve.index) {
+        Some(snapshot) => snapshot,
+        None => return 0,
+    };
+    if e.ledger().timestamp() > snapshot.timestamp + config.window
+        || reserve.d_rate <= snapshot.d_rate
+    {
+        return 0;
+    }
+    let interest = d_tokens_burnt
+        .fixed_mul_floor(reserve.d_rate - snapshot.d_rate, SCALAR_12)
+        .unwrap_optimized();
+    let rebate = interest
+        .fixed_mul_floor(i128(config.rebate), SCALAR_7)
+        .unwrap_optimized()
+        .min(reserve.backstop_credit);
+    reserve.backstop_credit -= rebate;
+    rebate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool::Positions, testutils};
+    use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+
+    fn set_timestamp(e: &Env, timestamp: u64) {
+        e.ledger().set(LedgerInfo {
+            timestamp,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+    }
+
+    #[test]
+    fn test_apply_repay_rebate() {
+        let e = Env::default();
+        e.mock_all_auths();
+        set_timestamp(&e, 1000);
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.asset = underlying.clone();
+        reserve.backstop_credit = 1_0000000;
+        let user = User {
+            address: samwise.clone(),
+            account_id: 0,
+            positions: Positions::env_default(&e),
+            read_only: false,
+        };
+        e.as_contract(&pool, || {
+            execute_set_repay_grace(&e, &underlying, 600, 0_5000000);
+            record_borrow(&e, &reserve, &user);
+
+            set_timestamp(&e, 1600);
+            reserve.d_rate = 1_010_000_000_000;
+            // 1 token of interest accrued on the repaid dTokens, half of it is refunded
+            let rebate = apply_repay_rebate(&e, &mut reserve, &user, 100_0000000);
+            assert_eq!(rebate, 0_5000000);
+            assert_eq!(reserve.backstop_credit, 0_5000000);
+
+            // the rebate is limited by the backstop credit
+            let rebate = apply_repay_rebate(&e, &mut reserve, &user, 300_0000000);
+            assert_eq!(rebate, 0_5000000);
+            assert_eq!(reserve.backstop_credit, 0);
+        });
+    }
+
+    #[test]
+    fn test_apply_repay_rebate_outside_window() {
+        let e = Env::default();
+        e.mock_all_auths();
+        set_timestamp(&e, 1000);
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.asset = underlying.clone();
+        reserve.backstop_credit = 1_0000000;
+        let user = User {
+            address: samwise.clone(),
+            account_id: 0,
+            positions: Positions::env_default(&e),
+            read_only: false,
+        };
+        e.as_contract(&pool, || {
+            execute_set_repay_grace(&e, &underlying, 600, 0_5000000);
+            record_borrow(&e, &reserve, &user);
+
+            set_timestamp(&e, 1601);
+            reserve.d_rate = 1_010_000_000_000;
+            let rebate = apply_repay_rebate(&e, &mut reserve, &user, 100_0000000);
+            assert_eq!(rebate, 0);
+            assert_eq!(reserve.backstop_credit, 1_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_set_repay_grace_window_too_long() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            execute_set_repay_grace(&e, &underlying, MAX_REPAY_GRACE_WINDOW + 1, 0_5000000);
+        });
+    }
+}
@@ -3,13 +3,20 @@ use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{contracttype, panic_with_error, unwrap::UnwrapOptimized, Address, Env};
 
 use crate::{
-    constants::{SCALAR_7, SCALAR_9},
+    constants::{MAX_ACCRUAL_SNAPSHOTS, SCALAR_12, SCALAR_7},
+    dependencies::UtilizationHookClient,
     errors::PoolError,
+    events::PoolEvents,
     pool::actions::RequestType,
-    storage::{self, PoolConfig, ReserveData},
+    storage::{self, AccrualSnapshot, PoolConfig, ReserveData},
 };
 
-use super::interest::calc_accrual;
+use super::{
+    anomaly::{check_supply_anomaly, is_reserve_restricted},
+    interest::{calc_accrual, calc_ir_mod_decay},
+    pause::{is_paused, pause_flag},
+    User,
+};
 
 #[derive(Clone)]
 #[contracttype]
@@ -21,18 +28,28 @@ pub struct Reserve {
     pub max_util: u32,         // the maximum utilization rate for the reserve
     pub last_time: u64,        // the last block the data was updated
     pub scalar: i128,          // scalar used for positions, b/d token supply, and credit
-    pub d_rate: i128,          // the conversion rate from dToken to underlying (9 decimals)
-    pub b_rate: i128,          // the conversion rate from bToken to underlying (9 decimals)
+    pub d_rate: i128,          // the conversion rate from dToken to underlying (12 decimals)
+    pub b_rate: i128,          // the conversion rate from bToken to underlying (12 decimals)
     pub ir_mod: i128,          // the interest rate curve modifier (9 decimals)
     pub b_supply: i128,        // the total supply of b tokens
     pub d_supply: i128,        // the total supply of d tokens
     pub backstop_credit: i128, // the total amount of underlying tokens owed to the backstop
     pub collateral_cap: i128, // the total amount of underlying tokens that can be used as collateral
+    pub debt_cap: i128,       // the total amount of underlying tokens that can be borrowed
     pub enabled: bool,        // is the reserve enabled
+    pub min_supply_ledgers: u32, // the minimum number of ledgers supplied tokens must be held before withdrawal
+    pub isolated: bool,          // is the reserve only usable as collateral on its own
+    pub debt_ceiling: i128,      // the maximum debt borrowed against the reserve in isolation mode
+    pub isolated_debt: i128,     // the debt borrowed against the reserve in isolation mode
+    pub category: u32,           // the e-mode category of the reserve
+    pub util_accum: i128,        // the time weighted utilization since the last emission gulp
+    pub accrued_fees: i128,      // the total underlying tokens ever credited to the backstop
+    pub clamped_rate: Option<i128>, // the borrow rate interest accrued at when loaded, if it was clamped to the rate bounds
 }
 
 impl Reserve {
-    /// Load a Reserve from the ledger and update to the current ledger timestamp.
+    /// Load a Reserve from the ledger and update to the current ledger timestamp. Nothing is
+    /// written to the ledger or emitted until the reserve is stored.
     ///
     /// **NOTE**: This function is not cached, and should be called from the Pool.
     ///
@@ -61,7 +78,16 @@ impl Reserve {
             d_supply: reserve_data.d_supply,
             backstop_credit: reserve_data.backstop_credit,
             collateral_cap: reserve_config.collateral_cap,
+            debt_cap: reserve_config.debt_cap,
             enabled: reserve_config.enabled,
+            min_supply_ledgers: reserve_config.min_supply_ledgers,
+            isolated: reserve_config.isolated,
+            debt_ceiling: reserve_config.debt_ceiling,
+            isolated_debt: reserve_data.isolated_debt,
+            category: reserve_config.category,
+            util_accum: reserve_data.util_accum,
+            accrued_fees: reserve_data.accrued_fees,
+            clamped_rate: None,
         };
 
         // short circuit if the reserve has already been updated this ledger
@@ -69,31 +95,37 @@ impl Reserve {
             return reserve;
         }
 
+        let ir_mod_half_life = storage::get_res_ir_mod_half_life(e, asset);
         if reserve.b_supply == 0 {
+            reserve.decay_ir_mod(e, &ir_mod_half_life);
             reserve.last_time = e.ledger().timestamp();
             return reserve;
         }
 
         let cur_util = reserve.utilization();
+        reserve.util_accum += cur_util * i128(e.ledger().timestamp() - reserve.last_time);
         if cur_util == 0 {
             // if there are no assets borrowed, we don't need to update the reserve
+            reserve.decay_ir_mod(e, &ir_mod_half_life);
             reserve.last_time = e.ledger().timestamp();
             return reserve;
         }
 
-        let (loan_accrual, new_ir_mod) = calc_accrual(
+        let (loan_accrual, new_ir_mod, clamped_rate) = calc_accrual(
             e,
             &reserve_config,
+            &storage::get_res_ir_config(e, asset),
+            &storage::get_res_rate_bounds(e, asset),
+            &ir_mod_half_life,
             cur_util,
             reserve.ir_mod,
             reserve.last_time,
         );
         reserve.ir_mod = new_ir_mod;
+        reserve.clamped_rate = clamped_rate;
 
         let pre_update_liabilities = reserve.total_liabilities();
-        reserve.d_rate = loan_accrual
-            .fixed_mul_ceil(reserve.d_rate, SCALAR_9)
-            .unwrap_optimized();
+        reserve.d_rate = mul_div_ceil(loan_accrual, reserve.d_rate, SCALAR_12).unwrap_optimized();
         let accrued_interest = reserve.total_liabilities() - pre_update_liabilities;
 
         reserve.gulp(pool_config.bstop_rate, accrued_interest);
@@ -102,8 +134,86 @@ impl Reserve {
         reserve
     }
 
-    /// Store the updated reserve to the ledger.
+    /// Decay the rate modifier towards 1 since the last accrual, if the reserve has a decay
+    /// half-life. Used when the reserve has no liabilities, so no interest accrues.
+    fn decay_ir_mod(&mut self, e: &Env, ir_mod_half_life: &Option<u64>) {
+        if let Some(half_life) = ir_mod_half_life {
+            self.ir_mod =
+                calc_ir_mod_decay(self.ir_mod, *half_life, e.ledger().timestamp() - self.last_time);
+        }
+    }
+
+    /// Record the reserve's rates after an interest accrual, keeping only the most recent
+    /// `MAX_ACCRUAL_SNAPSHOTS` snapshots
+    fn record_accrual(&self, e: &Env) {
+        let mut history = storage::get_accrual_history(e, &self.asset);
+        history.push_back(AccrualSnapshot {
+            timestamp: self.last_time,
+            b_rate: self.b_rate,
+            d_rate: self.d_rate,
+            util: self.utilization(),
+        });
+        while history.len() > MAX_ACCRUAL_SNAPSHOTS {
+            history.pop_front();
+        }
+        storage::set_accrual_history(e, &self.asset, &history);
+    }
+
+    /// Emit the events for an interest accrual made since the reserve was last stored
+    ///
+    /// ### Arguments
+    /// * `prev_data` - The reserve data as last stored
+    fn emit_accrual(&self, e: &Env, prev_data: &ReserveData) {
+        if let Some(rate) = self.clamped_rate {
+            PoolEvents::rate_clamped(e, self.asset.clone(), rate);
+        }
+        // accruing interest does not change the token supplies, so the accrual is measured over
+        // the supplies as last stored
+        let pre_update_liabilities =
+            mul_div_ceil(prev_data.d_supply, prev_data.d_rate, SCALAR_12).unwrap_optimized();
+        let total_liabilities =
+            mul_div_ceil(prev_data.d_supply, self.d_rate, SCALAR_12).unwrap_optimized();
+        let pre_update_supply =
+            mul_div_floor(prev_data.b_supply, prev_data.b_rate, SCALAR_12).unwrap_optimized();
+        let total_supply =
+            mul_div_floor(prev_data.b_supply, self.b_rate, SCALAR_12).unwrap_optimized();
+        PoolEvents::accrue_interest(
+            e,
+            self.asset.clone(),
+            self.b_rate,
+            self.d_rate,
+            self.ir_mod,
+            total_liabilities - pre_update_liabilities,
+        );
+        // interest accrual cannot be blocked by the reserve caps, so report when it breaches them
+        if pre_update_supply <= self.collateral_cap && total_supply > self.collateral_cap {
+            PoolEvents::cap_exceeded(e, self.asset.clone(), 0, total_supply, self.collateral_cap);
+        }
+        if pre_update_liabilities <= self.debt_cap && total_liabilities > self.debt_cap {
+            PoolEvents::cap_exceeded(e, self.asset.clone(), 1, total_liabilities, self.debt_cap);
+        }
+    }
+
+    /// Store the updated reserve to the ledger. The interest accrued and the backstop credit
+    /// booked since the reserve was last stored are recorded and emitted here, rather than when
+    /// the reserve is loaded, so loads made by views have no side effects.
     pub fn store(&self, e: &Env) {
+        let prev_data = storage::get_res_data(e, &self.asset);
+        if self.last_time != prev_data.last_time {
+            notify_util_hook(e, self);
+        }
+        if self.d_rate != prev_data.d_rate {
+            self.record_accrual(e);
+            self.emit_accrual(e, &prev_data);
+        }
+        if self.accrued_fees > prev_data.accrued_fees {
+            PoolEvents::credit_backstop(
+                e,
+                self.asset.clone(),
+                self.accrued_fees - prev_data.accrued_fees,
+                self.accrued_fees,
+            );
+        }
         let reserve_data = ReserveData {
             d_rate: self.d_rate,
             b_rate: self.b_rate,
@@ -112,12 +222,19 @@ impl Reserve {
             d_supply: self.d_supply,
             backstop_credit: self.backstop_credit,
             last_time: self.last_time,
+            isolated_debt: self.isolated_debt,
+            util_accum: self.util_accum,
+            accrued_fees: self.accrued_fees,
         };
+        check_supply_anomaly(e, self);
         storage::set_res_data(e, &self.asset, &reserve_data);
     }
 
     /// Accrue tokens to the reserve supply. This issues any `backstop_credit` required and updates the reserve's bRate to account for the additional tokens.
     ///
+    /// Any credit issued is added to the reserve's cumulative accrued fees, and emitted as an event
+    /// when the reserve is stored.
+    ///
     /// ### Arguments
     /// * bstop_rate - The backstop take rate for the pool
     /// * accrued - The amount of additional underlying tokens
@@ -134,13 +251,18 @@ impl Reserve {
                     .unwrap_optimized();
                 self.backstop_credit += new_backstop_credit;
             }
-            self.b_rate = (pre_update_supply + accrued - new_backstop_credit)
-                .fixed_div_floor(self.b_supply, SCALAR_9)
-                .unwrap_optimized();
+            self.accrued_fees += new_backstop_credit;
+            self.b_rate = mul_div_floor(
+                pre_update_supply + accrued - new_backstop_credit,
+                SCALAR_12,
+                self.b_supply,
+            )
+            .unwrap_optimized();
         }
     }
 
-    /// Fetch the current utilization rate for the reserve normalized to 7 decimals
+    /// Fetch the current utilization rate for the reserve normalized to 7 decimals. Fixed-rate
+    /// loans are held as dTokens, so the fixed-rate tranche is included in the utilization.
     pub fn utilization(&self) -> i128 {
         self.total_liabilities()
             .fixed_div_ceil(self.total_supply(), SCALAR_7)
@@ -154,18 +276,96 @@ impl Reserve {
         }
     }
 
-    /// Check the action is allowed according to the reserve status, or panic.
+    /// Fetch the amount of b_tokens that can be burnt before the total supply falls below the
+    /// total liabilities, i.e. the b_tokens backed by the reserve's available liquidity
+    pub fn max_withdrawable_b_tokens(&self) -> i128 {
+        let liabilities = self.total_liabilities();
+        if liabilities == 0 {
+            return self.b_supply;
+        }
+        (self.b_supply - self.to_b_token_up(liabilities)).max(0)
+    }
+
+    /// Fetch the amount of underlying tokens that can be supplied before the total supply,
+    /// including accrued interest, reaches the collateral cap.
+    pub fn remaining_collateral_capacity(&self) -> i128 {
+        (self.collateral_cap - self.total_supply()).max(0)
+    }
+
+    /// Require that the total supply is below the collateral cap, or panic.
+    pub fn require_collateral_below_cap(&self, e: &Env) {
+        if self.total_supply() > self.collateral_cap {
+            panic_with_error!(e, PoolError::ExceededCollateralCap)
+        }
+    }
+
+    /// Require that the total liabilities are below the debt cap, or panic.
+    pub fn require_debt_below_cap(&self, e: &Env) {
+        if self.total_liabilities() > self.debt_cap {
+            panic_with_error!(e, PoolError::ExceededDebtCap)
+        }
+    }
+
+    /// Check the action is allowed according to the reserve status. Returns the error the action
+    /// would fail with, if any.
     ///
     /// ### Arguments
     /// * `action_type` - The type of action being performed
-    pub fn require_action_allowed(&self, e: &Env, action_type: u32) {
+    pub fn check_action_allowed(&self, e: &Env, action_type: u32) -> Result<(), PoolError> {
         // disable borrowing or auction cancellation for any non-active pool and disable supplying for any frozen pool
         if !self.enabled {
             if action_type == RequestType::Supply as u32
                 || action_type == RequestType::SupplyCollateral as u32
+                || action_type == RequestType::SupplyCollateralFor as u32
                 || action_type == RequestType::Borrow as u32
             {
-                panic_with_error!(e, PoolError::ReserveDisabled);
+                return Err(PoolError::ReserveDisabled);
+            }
+        }
+        // disable borrowing for any reserve restricted after a supply anomaly
+        if action_type == RequestType::Borrow as u32 && is_reserve_restricted(e, &self.asset) {
+            return Err(PoolError::ReserveRestricted);
+        }
+        if is_paused(e, pause_flag(action_type)) {
+            return Err(PoolError::ActionPaused);
+        }
+        Ok(())
+    }
+
+    /// Check the action is allowed according to the reserve status, or panic.
+    ///
+    /// ### Arguments
+    /// * `action_type` - The type of action being performed
+    pub fn require_action_allowed(&self, e: &Env, action_type: u32) {
+        if let Err(error) = self.check_action_allowed(e, action_type) {
+            panic_with_error!(e, error);
+        }
+    }
+
+    /// Record that the user supplied to the reserve, if the reserve enforces a minimum supply duration
+    ///
+    /// ### Arguments
+    /// * `user` - The user supplying
+    pub fn record_supply(&self, e: &Env, user: &User) {
+        if self.min_supply_ledgers > 0 && !user.read_only {
+            storage::set_user_last_supply(e, &user.address, self.index, self.min_supply_ledgers);
+        }
+    }
+
+    /// Require that the user's most recent supply to the reserve has been held for the
+    /// reserve's minimum supply duration
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user withdrawing
+    ///
+    /// ### Panics
+    /// If the minimum supply duration has not elapsed since the user's last supply
+    pub fn require_supply_unlocked(&self, e: &Env, user: &Address) {
+        if self.min_supply_ledgers > 0 {
+            if let Some(last_supply) = storage::get_user_last_supply(e, user, self.index) {
+                if e.ledger().sequence() < last_supply + self.min_supply_ledgers {
+                    panic_with_error!(e, PoolError::SupplyLocked);
+                }
             }
         }
     }
@@ -187,9 +387,7 @@ impl Reserve {
     /// ### Arguments
     /// * `d_tokens` - The amount of tokens to convert
     pub fn to_asset_from_d_token(&self, d_tokens: i128) -> i128 {
-        d_tokens
-            .fixed_mul_ceil(self.d_rate, SCALAR_9)
-            .unwrap_optimized()
+        mul_div_ceil(d_tokens, self.d_rate, SCALAR_12).unwrap_optimized()
     }
 
     /// Convert b_tokens to the corresponding asset value
@@ -197,9 +395,7 @@ impl Reserve {
     /// ### Arguments
     /// * `b_tokens` - The amount of tokens to convert
     pub fn to_asset_from_b_token(&self, b_tokens: i128) -> i128 {
-        b_tokens
-            .fixed_mul_floor(self.b_rate, SCALAR_9)
-            .unwrap_optimized()
+        mul_div_floor(b_tokens, self.b_rate, SCALAR_12).unwrap_optimized()
     }
 
     /// Convert d_tokens to their corresponding effective asset value. This
@@ -231,9 +427,7 @@ impl Reserve {
     /// ### Arguments
     /// * `amount` - The amount of tokens to convert
     pub fn to_d_token_up(&self, amount: i128) -> i128 {
-        amount
-            .fixed_div_ceil(self.d_rate, SCALAR_9)
-            .unwrap_optimized()
+        mul_div_ceil(amount, SCALAR_12, self.d_rate).unwrap_optimized()
     }
 
     /// Convert asset tokens to the corresponding d token value - rounding down
@@ -241,9 +435,7 @@ impl Reserve {
     /// ### Arguments
     /// * `amount` - The amount of tokens to convert
     pub fn to_d_token_down(&self, amount: i128) -> i128 {
-        amount
-            .fixed_div_floor(self.d_rate, SCALAR_9)
-            .unwrap_optimized()
+        mul_div_floor(amount, SCALAR_12, self.d_rate).unwrap_optimized()
     }
 
     /// Convert asset tokens to the corresponding b token value - round up
@@ -251,9 +443,7 @@ impl Reserve {
     /// ### Arguments
     /// * `amount` - The amount of tokens to convert
     pub fn to_b_token_up(&self, amount: i128) -> i128 {
-        amount
-            .fixed_div_ceil(self.b_rate, SCALAR_9)
-            .unwrap_optimized()
+        mul_div_ceil(amount, SCALAR_12, self.b_rate).unwrap_optimized()
     }
 
     /// Convert asset tokens to the corresponding b token value - round down
@@ -261,17 +451,74 @@ impl Reserve {
     /// ### Arguments
     /// * `amount` - The amount of tokens to convert
     pub fn to_b_token_down(&self, amount: i128) -> i128 {
-        amount
-            .fixed_div_floor(self.b_rate, SCALAR_9)
-            .unwrap_optimized()
+        mul_div_floor(amount, SCALAR_12, self.b_rate).unwrap_optimized()
     }
 }
 
+/// Notify the pool's utilization hook of an interest accrual, if one is set. The hook is notified
+/// the first time a reserve is stored after it accrues, so views that only load the reserve never
+/// call it. A failing hook does not block the update.
+fn notify_util_hook(e: &Env, reserve: &Reserve) {
+    if let Some(hook) = storage::get_util_hook(e) {
+        let _ = UtilizationHookClient::new(e, &hook).try_on_accrual(
+            &e.current_contract_address(),
+            &reserve.asset,
+            &reserve.utilization(),
+            &reserve.ir_mod,
+        );
+    }
+}
+
+/// Calculate `x * y / z` rounded down, for a non-negative `y`. If `x * y` overflows, `x` is
+/// split into a multiple of `z` and a remainder, so large balances can still be converted at
+/// 12 decimal rates.
+///
+/// Returns None if the result overflows or `z` is zero
+fn mul_div_floor(x: i128, y: i128, z: i128) -> Option<i128> {
+    x.fixed_mul_floor(y, z).or_else(|| {
+        let whole = x.checked_div_euclid(z)?.checked_mul(y)?;
+        let part = x.checked_rem_euclid(z)?.fixed_mul_floor(y, z)?;
+        whole.checked_add(part)
+    })
+}
+
+/// Calculate `x * y / z` rounded up, for a non-negative `y`. If `x * y` overflows, `x` is
+/// split into a multiple of `z` and a remainder, so large balances can still be converted at
+/// 12 decimal rates.
+///
+/// Returns None if the result overflows or `z` is zero
+fn mul_div_ceil(x: i128, y: i128, z: i128) -> Option<i128> {
+    x.fixed_mul_ceil(y, z).or_else(|| {
+        let whole = x.checked_div_euclid(z)?.checked_mul(y)?;
+        let part = x.checked_rem_euclid(z)?.fixed_mul_ceil(y, z)?;
+        whole.checked_add(part)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::testutils;
-    use soroban_sdk::testutils::{Address as _, Ledger, LedgerInfo};
+    use crate::{
+        constants::{PAUSE_BORROW, PAUSE_FLASH_LOAN, PAUSE_WITHDRAW},
+        storage::{PoolDataKey, ReserveConfigV1, ReserveDataV1},
+        testutils,
+    };
+    use soroban_sdk::{
+        contract, contractimpl,
+        testutils::{Address as _, Events, Ledger, LedgerInfo},
+        IntoVal, Symbol,
+    };
+
+    #[contract]
+    struct MockUtilHook;
+
+    #[contractimpl]
+    impl MockUtilHook {
+        pub fn on_accrual(e: Env, _pool: Address, asset: Address, util: i128, ir_mod: i128) {
+            e.storage().instance().set(&asset, &(util, ir_mod));
+        }
+    }
+
     #[test]
     fn test_load_reserve() {
         let e = Env::default();
@@ -294,8 +541,8 @@ mod tests {
 
         let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
         let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
-        reserve_data.d_rate = 1_345_678_123;
-        reserve_data.b_rate = 1_123_456_789;
+        reserve_data.d_rate = 1_345_678_123_000;
+        reserve_data.b_rate = 1_123_456_789_000;
         reserve_data.d_supply = 65_0000000;
         reserve_data.b_supply = 99_0000000;
         testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
@@ -305,19 +552,235 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 5,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
+            let event_count = e.events().all().len();
             let reserve = Reserve::load(&e, &pool_config, &underlying);
 
-            // (accrual: 1_002_957_369, util: .7864353)
-            assert_eq!(reserve.d_rate, 1_349_657_800);
-            assert_eq!(reserve.b_rate, 1_125_547_124);
+            // (accrual: 1_002_957_375_248, util: .7864353)
+            assert_eq!(reserve.d_rate, 1_349_657_798_173);
+            assert_eq!(reserve.b_rate, 1_125_547_124_242);
             assert_eq!(reserve.ir_mod, 1_044_981_563);
             assert_eq!(reserve.d_supply, 65_0000000);
             assert_eq!(reserve.b_supply, 99_0000000);
-            assert_eq!(reserve.backstop_credit, 0_0517358);
+            assert_eq!(reserve.backstop_credit, 0_0517357);
             assert_eq!(reserve.last_time, 617280);
+            assert_eq!(reserve.util_accum, 0_7864353 * 617280);
+
+            // loading the reserve has no side effects
+            assert_eq!(e.events().all().len(), event_count);
+            assert_eq!(storage::get_accrual_history(&e, &underlying).len(), 0);
+
+            reserve.store(&e);
+            let events = e.events().all();
+            assert_eq!(
+                events.get_unchecked(events.len() - 2),
+                (
+                    pool.clone(),
+                    (Symbol::new(&e, "accrue_interest"), underlying.clone()).into_val(&e),
+                    (
+                        1_125_547_124_242i128,
+                        1_349_657_798_173i128,
+                        1_044_981_563i128,
+                        0_2586789i128
+                    )
+                        .into_val(&e)
+                )
+            );
+            assert_eq!(
+                events.get_unchecked(events.len() - 1),
+                (
+                    pool.clone(),
+                    (Symbol::new(&e, "credit_backstop"), underlying.clone()).into_val(&e),
+                    (0_0517357i128, 0_0517357i128).into_val(&e)
+                )
+            );
+
+            let history = storage::get_accrual_history(&e, &underlying);
+            assert_eq!(history.len(), 1);
+            let snapshot = history.get_unchecked(0);
+            assert_eq!(snapshot.timestamp, 617280);
+            assert_eq!(snapshot.b_rate, reserve.b_rate);
+            assert_eq!(snapshot.d_rate, reserve.d_rate);
+            assert_eq!(snapshot.util, reserve.utilization());
+        });
+    }
+
+    #[test]
+    fn test_store_reserve_notifies_util_hook() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 123456 * 5,
+            protocol_version: 22,
+            sequence_number: 123456,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let hook = e.register(MockUtilHook {}, ());
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.d_rate = 1_345_678_123_000;
+        reserve_data.b_rate = 1_123_456_789_000;
+        reserve_data.d_supply = 65_0000000;
+        reserve_data.b_supply = 99_0000000;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 5,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_util_hook(&e, &Some(hook.clone()));
+            Reserve::load(&e, &pool_config, &underlying);
+        });
+        // loading the reserve does not notify the hook
+        let notified: Option<(i128, i128)> =
+            e.as_contract(&hook, || e.storage().instance().get(&underlying));
+        assert_eq!(notified, None);
+
+        let reserve = e.as_contract(&pool, || {
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.store(&e);
+            reserve
+        });
+        assert_eq!(reserve.ir_mod, 1_044_981_563);
+        let notified: Option<(i128, i128)> =
+            e.as_contract(&hook, || e.storage().instance().get(&underlying));
+        assert_eq!(notified, Some((reserve.utilization(), 1_044_981_563)));
+    }
+
+    #[test]
+    fn test_store_reserve_ignores_failing_util_hook() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 123456 * 5,
+            protocol_version: 22,
+            sequence_number: 123456,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.d_rate = 1_345_678_123_000;
+        reserve_data.b_rate = 1_123_456_789_000;
+        reserve_data.d_supply = 65_0000000;
+        reserve_data.b_supply = 99_0000000;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 5,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            // the hook does not implement the hook interface
+            storage::set_util_hook(&e, &Some(underlying.clone()));
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.store(&e);
+            let reserve_data = storage::get_res_data(&e, &underlying);
+            assert_eq!(reserve_data.d_rate, 1_349_657_798_173);
+            assert_eq!(reserve_data.ir_mod, 1_044_981_563);
+        });
+    }
+
+    #[test]
+    fn test_load_reserve_accrual_history() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 123456 * 5,
+            protocol_version: 22,
+            sequence_number: 123456,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.d_supply = 65_0000000;
+        reserve_data.b_supply = 99_0000000;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 5,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            for _ in 0..MAX_ACCRUAL_SNAPSHOTS + 2 {
+                e.ledger().set_timestamp(e.ledger().timestamp() + 60);
+                let reserve = Reserve::load(&e, &pool_config, &underlying);
+                reserve.store(&e);
+            }
+            let last_reserve = Reserve::load(&e, &pool_config, &underlying);
+            let last_time = last_reserve.last_time;
+
+            // only the most recent snapshots are kept, oldest first
+            let history = storage::get_accrual_history(&e, &underlying);
+            assert_eq!(history.len(), MAX_ACCRUAL_SNAPSHOTS);
+            let first = history.get_unchecked(0);
+            assert_eq!(
+                first.timestamp,
+                last_time - 60 * (MAX_ACCRUAL_SNAPSHOTS as u64 - 1)
+            );
+            let last = history.last().unwrap();
+            assert_eq!(last.timestamp, last_time);
+            assert_eq!(last.d_rate, last_reserve.d_rate);
+            assert!(last.d_rate > first.d_rate);
         });
     }
 
@@ -354,6 +817,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -401,6 +869,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -416,6 +889,58 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_load_reserve_zero_util_ir_mod_decay() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 123456 * 5,
+            protocol_version: 22,
+            sequence_number: 123456,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.d_rate = 0;
+        reserve_data.d_supply = 0;
+        reserve_data.ir_mod = 0_200_000_000;
+        reserve_data.last_time = 123456 * 5 - 3 * 24 * 60 * 60;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_res_ir_mod_half_life(&e, &underlying, &Some(2 * 24 * 60 * 60));
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+
+            // 1.5 half-lives => 0.8 / 2 * 0.75
+            assert_eq!(reserve.ir_mod, 0_700_000_000);
+            assert_eq!(reserve.d_rate, 0);
+            assert_eq!(reserve.b_rate, reserve_data.b_rate);
+            assert_eq!(reserve.last_time, 617280);
+        });
+    }
+
     #[test]
     fn test_load_reserve_zero_bstop_rate() {
         let e = Env::default();
@@ -438,8 +963,8 @@ mod tests {
 
         let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
         let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
-        reserve_data.d_rate = 1_345_678_123;
-        reserve_data.b_rate = 1_123_456_789;
+        reserve_data.d_rate = 1_345_678_123_000;
+        reserve_data.b_rate = 1_123_456_789_000;
         reserve_data.d_supply = 65_0000000;
         reserve_data.b_supply = 99_0000000;
         testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
@@ -449,14 +974,19 @@ mod tests {
             bstop_rate: 0,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
             let reserve = Reserve::load(&e, &pool_config, &underlying);
 
-            // (accrual: 1_002_957_369, util: .7864353)
-            assert_eq!(reserve.d_rate, 1_349_657_800);
-            assert_eq!(reserve.b_rate, 1_126_069_708);
+            // (accrual: 1_002_957_375_248, util: .7864353)
+            assert_eq!(reserve.d_rate, 1_349_657_798_173);
+            assert_eq!(reserve.b_rate, 1_126_069_707_070);
             assert_eq!(reserve.ir_mod, 1_044_981_563);
             assert_eq!(reserve.d_supply, 65_0000000);
             assert_eq!(reserve.b_supply, 99_0000000);
@@ -487,8 +1017,8 @@ mod tests {
 
         let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
         let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
-        reserve_data.d_rate = 1_345_678_123;
-        reserve_data.b_rate = 1_123_456_789;
+        reserve_data.d_rate = 1_345_678_123_000;
+        reserve_data.b_rate = 1_123_456_789_000;
         reserve_data.d_supply = 65_0000000;
         reserve_data.b_supply = 99_0000000;
         testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
@@ -498,6 +1028,11 @@ mod tests {
             bstop_rate: 0_2000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
@@ -506,24 +1041,159 @@ mod tests {
 
             let reserve_data = storage::get_res_data(&e, &underlying);
 
-            // (accrual: 1_002_957_369, util: .7864353)
-            assert_eq!(reserve_data.d_rate, 1_349_657_800);
-            assert_eq!(reserve_data.b_rate, 1_125_547_124);
+            // (accrual: 1_002_957_375_248, util: .7864353)
+            assert_eq!(reserve_data.d_rate, 1_349_657_798_173);
+            assert_eq!(reserve_data.b_rate, 1_125_547_124_242);
             assert_eq!(reserve_data.ir_mod, 1_044_981_563);
             assert_eq!(reserve_data.d_supply, 65_0000000);
             assert_eq!(reserve_data.b_supply, 99_0000000);
-            assert_eq!(reserve_data.backstop_credit, 0_0517358);
+            assert_eq!(reserve_data.backstop_credit, 0_0517357);
             assert_eq!(reserve_data.last_time, 617280);
+            assert_eq!(reserve_data.util_accum, 0_7864353 * 617280);
+        });
+    }
+
+    #[test]
+    fn test_load_reserve_migrates_legacy_rates() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 123456 * 5,
+            protocol_version: 22,
+            sequence_number: 123456,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 123456 * 5;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_2000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            // reserve data stored before the rates moved to 12 decimals
+            let legacy_data = ReserveDataV1 {
+                d_rate: 1_345_678_123,
+                b_rate: 1_123_456_789,
+                ir_mod: reserve_data.ir_mod,
+                b_supply: reserve_data.b_supply,
+                d_supply: reserve_data.d_supply,
+                backstop_credit: reserve_data.backstop_credit,
+                last_time: reserve_data.last_time,
+            };
+            e.storage()
+                .persistent()
+                .set(&PoolDataKey::ResData(underlying.clone()), &legacy_data);
+
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            assert_eq!(reserve.d_rate, 1_345_678_123_000);
+            assert_eq!(reserve.b_rate, 1_123_456_789_000);
+            assert_eq!(reserve.b_supply, reserve_data.b_supply);
+            assert_eq!(reserve.isolated_debt, 0);
+            assert_eq!(reserve.to_asset_from_d_token(10_0000000), 13_4567813);
+
+            reserve.store(&e);
+            let reserve_data = storage::get_res_data(&e, &underlying);
+            assert_eq!(reserve_data.d_rate, 1_345_678_123_000);
+            assert_eq!(reserve_data.b_rate, 1_123_456_789_000);
+
+            // migrated rates are not scaled again
+            let reserve = Reserve::load(&e, &pool_config, &underlying);
+            assert_eq!(reserve.d_rate, 1_345_678_123_000);
         });
     }
 
+    #[test]
+    fn test_load_reserve_migrates_legacy_config() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            // reserve config stored before the reserve config was extended
+            let legacy_config = ReserveConfigV1 {
+                index: 0,
+                decimals: reserve_config.decimals,
+                c_factor: reserve_config.c_factor,
+                l_factor: reserve_config.l_factor,
+                util: reserve_config.util,
+                max_util: reserve_config.max_util,
+                r_base: reserve_config.r_base,
+                r_one: reserve_config.r_one,
+                r_two: reserve_config.r_two,
+                r_three: reserve_config.r_three,
+                reactivity: reserve_config.reactivity,
+                collateral_cap: 1_000_0000000,
+                enabled: true,
+            };
+            e.storage()
+                .persistent()
+                .set(&PoolDataKey::ResConfig(underlying.clone()), &legacy_config);
+
+            let config = storage::get_res_config(&e, &underlying);
+            assert_eq!(config.c_factor, reserve_config.c_factor);
+            assert_eq!(config.collateral_cap, 1_000_0000000);
+            assert_eq!(config.debt_cap, i128::MAX);
+            assert_eq!(config.min_supply_ledgers, 0);
+            assert!(!config.isolated);
+            assert_eq!(config.debt_ceiling, 0);
+            assert_eq!(config.category, 0);
+            assert!(config.enabled);
+
+            // the migrated config is stored in the current layout
+            storage::set_res_config(&e, &underlying, &config);
+            let config = storage::get_res_config(&e, &underlying);
+            assert_eq!(config.debt_cap, i128::MAX);
+        });
+    }
+
+    #[test]
+    fn test_conversions_do_not_overflow() {
+        let e = Env::default();
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.d_rate = 1_500_000_000_000;
+        reserve.b_rate = 1_500_000_000_000;
+
+        // the product of the balance and the 12 decimal rate overflows an i128
+        let balance = i128::MAX / 1_000_000_000_000 / 2 * 2;
+        assert_eq!(reserve.to_asset_from_b_token(balance), balance + balance / 2);
+        assert_eq!(reserve.to_d_token_down(balance + balance / 2), balance);
+    }
+
     #[test]
     fn test_utilization() {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.d_rate = 1_345_678_123;
-        reserve.b_rate = 1_123_456_789;
+        reserve.d_rate = 1_345_678_123_000;
+        reserve.b_rate = 1_123_456_789_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -557,6 +1227,84 @@ mod tests {
         reserve.require_utilization_below_max(&e);
     }
 
+    #[test]
+    fn test_max_withdrawable_b_tokens() {
+        let e = Env::default();
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.b_supply = 100_0000000;
+        reserve.d_supply = 75_0000000;
+
+        let result = reserve.max_withdrawable_b_tokens();
+        assert_eq!(result, 25_0000000);
+
+        // withdrawals are not limited by the max utilization
+        reserve.d_supply = 96_0000000;
+        let result = reserve.max_withdrawable_b_tokens();
+        assert_eq!(result, 4_0000000);
+
+        reserve.d_supply = 100_0000000;
+        let result = reserve.max_withdrawable_b_tokens();
+        assert_eq!(result, 0);
+
+        reserve.d_supply = 0;
+        let result = reserve.max_withdrawable_b_tokens();
+        assert_eq!(result, 100_0000000);
+    }
+
+    #[test]
+    fn test_require_debt_below_cap_pass() {
+        let e = Env::default();
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.d_supply = 65_0000000;
+        reserve.debt_cap = 65_0000000;
+
+        reserve.require_debt_below_cap(&e);
+        // no panic
+        assert!(true);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1232)")]
+    fn test_require_debt_below_cap_panic() {
+        let e = Env::default();
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.d_supply = 65_0000001;
+        reserve.debt_cap = 65_0000000;
+
+        reserve.require_debt_below_cap(&e);
+    }
+
+    #[test]
+    fn test_remaining_collateral_capacity() {
+        let e = Env::default();
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.b_rate = 1_100_000_000_000;
+        reserve.b_supply = 90_0000000;
+        reserve.collateral_cap = 100_0000000;
+        assert_eq!(reserve.remaining_collateral_capacity(), 1_0000000);
+
+        // accrued interest counts towards the cap
+        reserve.b_rate = 1_200_000_000_000;
+        assert_eq!(reserve.remaining_collateral_capacity(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1220)")]
+    fn test_require_collateral_below_cap_panic() {
+        let e = Env::default();
+
+        let mut reserve = testutils::default_reserve(&e);
+        reserve.b_rate = 1_100_000_000_000;
+        reserve.b_supply = 91_0000000;
+        reserve.collateral_cap = 100_0000000;
+
+        reserve.require_collateral_below_cap(&e);
+    }
+
     /***** Token Transfer Math *****/
 
     #[test]
@@ -564,7 +1312,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.d_rate = 1_321_834_961;
+        reserve.d_rate = 1_321_834_961_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -578,7 +1326,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.b_rate = 1_321_834_961;
+        reserve.b_rate = 1_321_834_961_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -592,7 +1340,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.d_rate = 1_321_834_961;
+        reserve.d_rate = 1_321_834_961_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
         reserve.l_factor = 1_1000000;
@@ -607,7 +1355,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.b_rate = 1_321_834_961;
+        reserve.b_rate = 1_321_834_961_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
         reserve.c_factor = 0_8500000;
@@ -622,7 +1370,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.d_rate = 1_823_912_692;
+        reserve.d_rate = 1_823_912_692_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -636,7 +1384,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.b_rate = 1_823_912_692;
+        reserve.b_rate = 1_823_912_692_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -650,7 +1398,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.d_rate = 1_321_834_961;
+        reserve.d_rate = 1_321_834_961_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -664,7 +1412,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.d_rate = 1_321_834_961;
+        reserve.d_rate = 1_321_834_961_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -678,7 +1426,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.b_rate = 1_321_834_961;
+        reserve.b_rate = 1_321_834_961_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -692,7 +1440,7 @@ mod tests {
         let e = Env::default();
 
         let mut reserve = testutils::default_reserve(&e);
-        reserve.b_rate = 1_321_834_961;
+        reserve.b_rate = 1_321_834_961_000;
         reserve.b_supply = 99_0000000;
         reserve.d_supply = 65_0000000;
 
@@ -726,13 +1474,45 @@ mod tests {
     #[test]
     fn test_require_action_allowed_passed_if_withdraw_or_repay() {
         let e = Env::default();
+        let pool = testutils::create_pool(&e);
 
         let mut reserve = testutils::default_reserve(&e);
         reserve.enabled = false;
 
-        reserve.require_action_allowed(&e, RequestType::Withdraw as u32);
-        reserve.require_action_allowed(&e, RequestType::WithdrawCollateral as u32);
-        reserve.require_action_allowed(&e, RequestType::Repay as u32);
+        e.as_contract(&pool, || {
+            reserve.require_action_allowed(&e, RequestType::Withdraw as u32);
+            reserve.require_action_allowed(&e, RequestType::WithdrawCollateral as u32);
+            reserve.require_action_allowed(&e, RequestType::Repay as u32);
+        });
+    }
+
+    #[test]
+    fn test_require_action_allowed_passed_if_other_action_paused() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        let reserve = testutils::default_reserve(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_paused_actions(&e, &(PAUSE_BORROW | PAUSE_FLASH_LOAN));
+            reserve.require_action_allowed(&e, RequestType::Supply as u32);
+            reserve.require_action_allowed(&e, RequestType::Withdraw as u32);
+            reserve.require_action_allowed(&e, RequestType::Repay as u32);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1234)")]
+    fn test_require_action_allowed_panics_if_withdraw_paused() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+
+        let reserve = testutils::default_reserve(&e);
+
+        e.as_contract(&pool, || {
+            storage::set_paused_actions(&e, &PAUSE_WITHDRAW);
+            reserve.require_action_allowed(&e, RequestType::WithdrawCollateral as u32);
+        });
     }
 
     #[test]
@@ -756,7 +1536,8 @@ mod tests {
 
         reserve.gulp(0_2000000, 100_0000000);
         assert_eq!(reserve.backstop_credit, 20_0000000 + 0_1234567);
-        assert_eq!(reserve.b_rate, 1_800000000);
+        assert_eq!(reserve.accrued_fees, 20_0000000);
+        assert_eq!(reserve.b_rate, 1_800_000_000_000);
         assert_eq!(reserve.last_time, 0);
     }
 
@@ -781,7 +1562,8 @@ mod tests {
 
         reserve.gulp(0_2000000, -10_0000000);
         assert_eq!(reserve.backstop_credit, 0_1234567);
-        assert_eq!(reserve.b_rate, 1000000000);
+        assert_eq!(reserve.accrued_fees, 0);
+        assert_eq!(reserve.b_rate, 1_000_000_000_000);
         assert_eq!(reserve.last_time, 0);
     }
 }
@@ -0,0 +1,392 @@
+use sep_41_token::TokenClient;
+use soroban_sdk::{panic_with_error, Address, Env, Vec};
+
+use crate::{auctions::AuctionType, errors::PoolError, storage};
+
+use super::{
+    fixed_rate::settle_fixed_loans, submit::require_healthy_positions, Pool, User,
+};
+
+/// Remove an empty reserve from the pool. The reserve is disabled and its emissions are removed.
+///
+/// Positions reference reserves by their index, so a removed reserve keeps its index until every
+/// reserve after it is also removed. Removed reserves at the back of the reserve list are then
+/// dropped from the list, unless they have ever had emissions, as the users' emission indexes
+/// would carry over to a new reserve at the same index.
+///
+/// A removed reserve that is still in the reserve list is restored by setting the reserve again.
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+///
+/// ### Panics
+/// If the reserve does not exist, was already removed, has any supply, liabilities, fixed-rate
+/// loans or backstop credit, or an interest auction is in progress
+pub fn execute_remove_reserve(e: &Env, asset: &Address) {
+    let mut removed = storage::get_removed_res(e);
+    if !storage::has_res(e, asset) || removed.contains(asset) {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    // an interest auction's lot is drawn from the backstop credit when it is filled
+    let backstop_address = storage::get_backstop(e);
+    if storage::has_auction(e, &(AuctionType::InterestAuction as u32), &backstop_address) {
+        panic_with_error!(e, PoolError::AuctionInProgress);
+    }
+
+    let mut pool = Pool::load(e);
+    let reserve = pool.load_reserve(e, asset, true);
+    if reserve.b_supply != 0
+        || reserve.d_supply != 0
+        || reserve.backstop_credit != 0
+        || storage::get_fixed_tranche(e, asset).f_supply != 0
+    {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let index = reserve.index;
+    pool.cache_reserve(reserve);
+    pool.store_cached_reserves(e);
+
+    let mut reserve_config = storage::get_res_config(e, asset);
+    reserve_config.enabled = false;
+    storage::set_res_config(e, asset, &reserve_config);
+
+    let mut pool_emissions = storage::get_pool_emissions(e);
+    pool_emissions.remove(index * 2);
+    pool_emissions.remove(index * 2 + 1);
+    storage::set_pool_emissions(e, &pool_emissions);
+
+    removed.push_back(asset.clone());
+    compact_res_list(e, &mut removed);
+    storage::set_removed_res(e, &removed);
+}
+
+/// Drop removed reserves from the back of the reserve list, along with their config
+///
+/// The user state still keyed by a dropped reserve's index is a user's last supply and borrow,
+/// which are temporary and predate any use of a new reserve at the same index, and the dToken rate
+/// they last paid referral fees at, which is reset by their first borrow from the new reserve.
+///
+/// ### Arguments
+/// * `removed` - The removed reserves that are still in the reserve list
+fn compact_res_list(e: &Env, removed: &mut Vec<Address>) {
+    let mut res_list = storage::get_res_list(e);
+    while let Some(asset) = res_list.last() {
+        let removed_index = match removed.first_index_of(&asset) {
+            Some(removed_index) => removed_index,
+            None => break,
+        };
+        let index = res_list.len() - 1;
+        if storage::get_res_emis_data(e, &(index * 2)).is_some()
+            || storage::get_res_emis_data(e, &(index * 2 + 1)).is_some()
+        {
+            break;
+        }
+        res_list.pop_back();
+        removed.remove(removed_index);
+        storage::del_res(e, &asset);
+        storage::del_res_ir_config(e, &asset);
+        storage::set_res_ir_mod_half_life(e, &asset, &None);
+        storage::set_res_rate_bounds(e, &asset, &None);
+        storage::del_queued_debt_rebate(e, &asset);
+        storage::set_repay_grace_config(e, &asset, &None);
+        storage::set_fixed_rate_config(e, &asset, &None);
+    }
+    storage::set_res_list(e, &res_list);
+}
+
+/// Withdraw the supply and collateral of a user's account from a disabled reserve to the user,
+/// so dust positions left in the reserve don't prevent it from being removed
+///
+/// Returns the amount of underlying tokens sent to the user
+///
+/// ### Arguments
+/// * `asset` - The underlying asset of the reserve
+/// * `user` - The address of the user
+/// * `account_id` - The sub-account of the user
+///
+/// ### Panics
+/// If the reserve is enabled, the account has liabilities in the reserve or no supply or
+/// collateral in the reserve, or the account is unhealthy after the withdrawal
+pub fn execute_clear_reserve_dust(
+    e: &Env,
+    asset: &Address,
+    user: &Address,
+    account_id: u32,
+) -> i128 {
+    let mut pool = Pool::load(e);
+    let mut reserve = pool.load_reserve(e, asset, true);
+    if reserve.enabled {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let mut user_state = User::load_account(e, user, account_id);
+    settle_fixed_loans(e, &mut pool, &mut user_state);
+    if user_state.get_liabilities(reserve.index) > 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let collateral = user_state.get_collateral(reserve.index);
+    let supply = user_state.get_supply(reserve.index);
+    if collateral == 0 && supply == 0 {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    if collateral > 0 {
+        user_state.remove_collateral(e, &mut reserve, collateral);
+    }
+    if supply > 0 {
+        user_state.remove_supply(e, &mut reserve, supply);
+    }
+    let amount = reserve.to_asset_from_b_token(collateral + supply);
+    pool.cache_reserve(reserve);
+
+    require_healthy_positions(e, &mut pool, &user_state, user_state.emode(e));
+    pool.store_cached_reserves(e);
+    user_state.store(e);
+
+    if amount > 0 {
+        TokenClient::new(e, asset).transfer(&e.current_contract_address(), user, &amount);
+    }
+    amount
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        pool::{execute_claim_referral_fees, Positions},
+        storage::{FixedTranche, PoolConfig, ReserveEmissionData},
+        testutils,
+    };
+    use sep_41_token::testutils::MockTokenClient;
+    use soroban_sdk::{map, testutils::Address as _, vec};
+
+    #[test]
+    fn test_execute_remove_reserve() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.b_supply = 0;
+        reserve_data.d_supply = 0;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            storage::set_pool_emissions(&e, &map![&e, (0, 0_5000000), (3, 0_5000000)]);
+        });
+        let underlying_0 = assets.get_unchecked(0);
+        let underlying_1 = assets.get_unchecked(1);
+
+        e.as_contract(&pool, || {
+            // the first reserve keeps its index while the second reserve is in the list
+            execute_remove_reserve(&e, &underlying_0);
+            assert_eq!(storage::get_res_list(&e).len(), 2);
+            assert_eq!(storage::get_removed_res(&e), vec![&e, underlying_0.clone()]);
+            assert!(!storage::get_res_config(&e, &underlying_0).enabled);
+            assert_eq!(storage::get_pool_emissions(&e), map![&e, (3, 0_5000000)]);
+
+            // removing the last reserve compacts the list
+            execute_remove_reserve(&e, &underlying_1);
+            assert_eq!(storage::get_res_list(&e).len(), 0);
+            assert_eq!(storage::get_removed_res(&e).len(), 0);
+            assert!(!storage::has_res(&e, &underlying_0));
+            assert!(!storage::has_res(&e, &underlying_1));
+            assert_eq!(storage::get_pool_emissions(&e).len(), 0);
+        });
+    }
+
+    #[test]
+    fn test_execute_remove_reserve_keeps_emitted_index() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.b_supply = 0;
+        reserve_data.d_supply = 0;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            storage::set_pool_emissions(&e, &map![&e, (0, 0_5000000), (3, 0_5000000)]);
+        });
+        let underlying_1 = assets.get_unchecked(1);
+
+        e.as_contract(&pool, || {
+            storage::set_res_emis_data(
+                &e,
+                &3,
+                &ReserveEmissionData {
+                    expiration: 1000,
+                    eps: 0_1000000,
+                    index: 0,
+                    last_time: 0,
+                },
+            );
+
+            execute_remove_reserve(&e, &underlying_1);
+            assert_eq!(storage::get_res_list(&e).len(), 2);
+            assert!(storage::has_res(&e, &underlying_1));
+            assert_eq!(storage::get_removed_res(&e), vec![&e, underlying_1.clone()]);
+        });
+    }
+
+    #[test]
+    fn test_execute_remove_reserve_then_add_reserve_claims_referral_fees() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.b_supply = 0;
+        reserve_data.d_supply = 0;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            storage::set_pool_emissions(&e, &map![&e, (0, 0_5000000), (3, 0_5000000)]);
+        });
+        let underlying_1 = assets.get_unchecked(1);
+        let bombadil = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        let underlying_1_client = MockTokenClient::new(&e, &underlying_1);
+        underlying_1_client.mint(&pool, &1_0000000);
+        e.as_contract(&pool, || {
+            storage::set_referral_fees(&e, &frodo, &underlying_1, 1_0000000);
+            execute_remove_reserve(&e, &underlying_1);
+            assert_eq!(storage::get_res_list(&e).len(), 1);
+        });
+
+        // the new reserve takes the index of the removed reserve
+        let (underlying_2, underlying_2_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_2, &reserve_config, &reserve_data);
+
+        e.as_contract(&pool, || {
+            assert_eq!(storage::get_res_config(&e, &underlying_2).index, 1);
+            assert_eq!(storage::get_referral_fees(&e, &frodo, &underlying_2), 0);
+
+            let claimed = execute_claim_referral_fees(
+                &e,
+                &frodo,
+                vec![&e, underlying_2.clone(), underlying_1.clone()],
+                &frodo,
+            );
+            assert_eq!(claimed, vec![&e, 0, 1_0000000]);
+            assert_eq!(underlying_1_client.balance(&frodo), 1_0000000);
+            assert_eq!(underlying_2_client.balance(&frodo), 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_remove_reserve_with_fixed_rate_loans() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.b_supply = 0;
+        reserve_data.d_supply = 0;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            storage::set_pool_emissions(&e, &map![&e, (0, 0_5000000), (3, 0_5000000)]);
+        });
+        let underlying_0 = assets.get_unchecked(0);
+
+        e.as_contract(&pool, || {
+            storage::set_fixed_tranche(
+                &e,
+                &underlying_0,
+                &FixedTranche {
+                    f_supply: 1,
+                    f_rate: 0_0500000,
+                },
+            );
+
+            execute_remove_reserve(&e, &underlying_0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_remove_reserve_with_supply() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.b_supply = 0;
+        reserve_data.d_supply = 0;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            storage::set_pool_emissions(&e, &map![&e, (0, 0_5000000), (3, 0_5000000)]);
+        });
+        let underlying_0 = assets.get_unchecked(0);
+
+        e.as_contract(&pool, || {
+            let mut reserve_data = storage::get_res_data(&e, &underlying_0);
+            reserve_data.b_supply = 1;
+            storage::set_res_data(&e, &underlying_0, &reserve_data);
+
+            execute_remove_reserve(&e, &underlying_0);
+        });
+    }
+
+    #[test]
+    fn test_execute_clear_reserve_dust() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let (underlying, underlying_client) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, reserve_data) = testutils::default_reserve_meta();
+        reserve_config.enabled = false;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(
+                &e,
+                &samwise,
+                &Positions {
+                    liabilities: map![&e],
+                    collateral: map![&e, (0, 0_0000010)],
+                    supply: map![&e, (0, 0_0000005)],
+                },
+            );
+            let b_supply = storage::get_res_data(&e, &underlying).b_supply;
+
+            let amount = execute_clear_reserve_dust(&e, &underlying, &samwise, 0);
+            assert_eq!(amount, 0_0000015);
+            assert_eq!(underlying_client.balance(&samwise), 0_0000015);
+            let positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.supply.len(), 0);
+            assert_eq!(storage::get_res_data(&e, &underlying).b_supply, b_supply - 0_0000015);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_execute_clear_reserve_dust_enabled() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.b_supply = 0;
+        reserve_data.d_supply = 0;
+        let reserve = (reserve_config, reserve_data, 1_0000000);
+        let (pool, assets) = testutils::create_pool_with_reserves(&e, &[reserve.clone(), reserve]);
+        e.as_contract(&pool, || {
+            storage::set_pool_emissions(&e, &map![&e, (0, 0_5000000), (3, 0_5000000)]);
+        });
+        let underlying_0 = assets.get_unchecked(0);
+        let samwise = Address::generate(&e);
+
+        e.as_contract(&pool, || {
+            execute_clear_reserve_dust(&e, &underlying_0, &samwise, 0);
+        });
+    }
+}
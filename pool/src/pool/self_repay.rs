@@ -0,0 +1,267 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env};
+
+use crate::{
+    constants::SCALAR_12,
+    errors::PoolError,
+    events::PoolEvents,
+    storage::{self, SelfRepayConfig},
+};
+
+use super::{
+    fixed_rate::{reduce_fixed_loan, settle_fixed_loan},
+    isolation::remove_isolated_debt,
+    Pool, Reserve, User,
+};
+
+/// Opt in or out of a self-repaying loan. While opted in, the yield earned on the user's
+/// collateral in the reserve is used to repay the user's liability in the same reserve. Yield
+/// can't repay a liability in another reserve, as that would require swapping the yield.
+///
+/// Any yield accrued under the previous config is used to repay before the config changes.
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `asset` - The underlying asset of the reserve, or None to opt out
+///
+/// ### Panics
+/// If the reserve does not exist
+pub fn execute_set_self_repay(e: &Env, user: &Address, asset: &Option<Address>) {
+    execute_self_repay(e, user);
+    match asset {
+        Some(asset) => {
+            if !storage::has_res(e, asset) {
+                panic_with_error!(e, PoolError::BadRequest);
+            }
+            let pool_config = storage::get_pool_config(e);
+            let reserve = Reserve::load(e, &pool_config, asset);
+            reserve.store(e);
+            storage::set_self_repay(
+                e,
+                user,
+                &Some(SelfRepayConfig {
+                    asset: asset.clone(),
+                    b_rate: reserve.b_rate,
+                    accrued: 0,
+                }),
+            );
+        }
+        None => storage::set_self_repay(e, user, &None),
+    }
+}
+
+/// Use the yield accrued on a user's collateral to repay their liability, if the user has opted
+/// in to a self-repaying loan. Anyone can trigger the repayment.
+///
+/// Returns the amount of underlying tokens repaid
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn execute_self_repay(e: &Env, user: &Address) -> i128 {
+    if storage::get_self_repay(e, user).is_none() {
+        return 0;
+    }
+    let mut pool = Pool::load(e);
+    let mut user_state = User::load(e, user);
+    let repaid = apply_self_repay(e, &mut pool, &mut user_state);
+    pool.store_cached_reserves(e);
+    user_state.store(e);
+    repaid
+}
+
+/// Repay the user's liability with the yield accrued on their collateral, if the user has opted
+/// in to a self-repaying loan. The repaid liability and an equal value of collateral are burnt,
+/// so no tokens are transferred, and the user's health factor can only improve.
+///
+/// Yield is not kept while the user has no liability in the reserve.
+///
+/// Returns the amount of underlying tokens repaid
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `user` - The user
+pub fn apply_self_repay(e: &Env, pool: &mut Pool, user: &mut User) -> i128 {
+    if user.account_id != 0 {
+        return 0;
+    }
+    let asset = match storage::get_self_repay(e, &user.address) {
+        Some(config) => config.asset,
+        None => return 0,
+    };
+    let mut reserve = pool.load_reserve(e, &asset, true);
+    accrue_self_repay_yield(e, &reserve, &user.address, user.get_collateral(reserve.index));
+    settle_fixed_loan(e, pool.config.bstop_rate, &mut reserve, user);
+
+    let d_tokens = user.get_liabilities(reserve.index);
+    let b_tokens = user.get_collateral(reserve.index);
+    let liability = reserve.to_asset_from_d_token(d_tokens);
+    let mut config = storage::get_self_repay(e, &user.address).unwrap_optimized();
+    let amount = config
+        .accrued
+        .min(liability)
+        .min(reserve.to_asset_from_b_token(b_tokens));
+    let mut repaid = 0;
+    if amount > 0 {
+        let d_tokens_burnt = if amount == liability {
+            d_tokens
+        } else {
+            reserve.to_d_token_down(amount)
+        };
+        let b_tokens_burnt = reserve.to_b_token_up(amount).min(b_tokens);
+        if d_tokens_burnt > 0 && b_tokens_burnt > 0 {
+            user.remove_collateral(e, &mut reserve, b_tokens_burnt);
+            user.remove_liabilities(e, &mut reserve, d_tokens_burnt);
+            reduce_fixed_loan(e, &reserve, user);
+            repaid = amount;
+        }
+    }
+
+    config.accrued -= repaid;
+    if user.get_liabilities(reserve.index) == 0 {
+        config.accrued = 0;
+    }
+    config.b_rate = reserve.b_rate;
+    storage::set_self_repay(e, &user.address, &Some(config));
+    pool.cache_reserve(reserve);
+
+    if repaid > 0 {
+        remove_isolated_debt(e, pool, user, &asset, repaid);
+        PoolEvents::self_repay(e, asset, user.address.clone(), repaid);
+    }
+    repaid
+}
+
+/// Checkpoint the yield earned on a user's collateral in the reserve since the last checkpoint.
+/// Must be called before the user's collateral in the reserve changes.
+///
+/// ### Arguments
+/// * `reserve` - The reserve, accrued to the current ledger
+/// * `user` - The address of the user
+/// * `b_tokens` - The user's collateral in the reserve before the change
+pub fn accrue_self_repay_yield(e: &Env, reserve: &Reserve, user: &Address, b_tokens: i128) {
+    let mut config = match storage::get_self_repay(e, user) {
+        Some(config) if config.asset == reserve.asset && config.b_rate != reserve.b_rate => config,
+        _ => return,
+    };
+    // a lower bToken rate after bad debt is not deducted from the accrued yield
+    if reserve.b_rate > config.b_rate && b_tokens > 0 {
+        config.accrued += b_tokens
+            .fixed_mul_floor(reserve.b_rate - config.b_rate, SCALAR_12)
+            .unwrap_optimized();
+    }
+    config.b_rate = reserve.b_rate;
+    storage::set_self_repay(e, user, &Some(config));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool::Positions, storage::PoolConfig, testutils};
+    use soroban_sdk::{
+        map,
+        testutils::{Address as _, Ledger, LedgerInfo},
+    };
+
+    #[test]
+    fn test_apply_self_repay() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.ledger().set(LedgerInfo {
+            timestamp: 1000,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 1000;
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let positions = Positions {
+            liabilities: map![&e, (0, 10_0000000)],
+            collateral: map![&e, (0, 100_0000000)],
+            supply: map![&e],
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &positions);
+            execute_set_self_repay(&e, &samwise, &Some(underlying.clone()));
+
+            // simulate 0.5% of yield on the reserve's bTokens
+            let mut reserve = Reserve::load(&e, &pool_config, &underlying);
+            reserve.b_rate = 1_005_000_000_000;
+            reserve.store(&e);
+
+            let repaid = execute_self_repay(&e, &samwise);
+            assert_eq!(repaid, 0_5000000);
+
+            let positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(positions.liabilities.get_unchecked(0), 9_5000000);
+            assert_eq!(positions.collateral.get_unchecked(0), 99_5024875);
+
+            let config = storage::get_self_repay(&e, &samwise).unwrap();
+            assert_eq!(config.accrued, 0);
+            assert_eq!(config.b_rate, 1_005_000_000_000);
+
+            // no yield accrued since the last repayment
+            assert_eq!(execute_self_repay(&e, &samwise), 0);
+        });
+    }
+
+    #[test]
+    fn test_accrue_self_repay_yield_on_collateral_change() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let mut reserve = testutils::default_reserve(&e);
+        let mut user = User {
+            address: samwise.clone(),
+            positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
+        };
+        e.as_contract(&pool, || {
+            user.add_collateral(&e, &mut reserve, 100_0000000);
+            storage::set_self_repay(
+                &e,
+                &samwise,
+                &Some(SelfRepayConfig {
+                    asset: reserve.asset.clone(),
+                    b_rate: reserve.b_rate,
+                    accrued: 0,
+                }),
+            );
+
+            // yield earned before the collateral is added is checkpointed on the prior balance
+            reserve.b_rate += 0_010_000_000_000;
+            user.add_collateral(&e, &mut reserve, 100_0000000);
+
+            let config = storage::get_self_repay(&e, &samwise).unwrap();
+            assert_eq!(config.accrued, 1_0000000);
+            assert_eq!(config.b_rate, reserve.b_rate);
+        });
+    }
+}
@@ -0,0 +1,317 @@
+use soroban_sdk::{
+    contracttype, panic_with_error, xdr::ToXdr, Address, Bytes, BytesN, Env, String, Vec,
+};
+
+use crate::{errors::PoolError, storage};
+
+use super::{actions::Request, submit::execute_submit, Positions};
+
+/// An off-chain signature authorizing a set of requests for the account of the signer
+#[derive(Clone)]
+#[contracttype]
+pub struct SubmitSignature {
+    pub signer: BytesN<32>,    // the ed25519 public key of the Stellar account signing the requests
+    pub nonce: u64,            // the nonce of the signer, which must match the signer's next nonce
+    pub expiration: u64,       // the timestamp the signature expires at
+    pub signature: BytesN<64>, // the ed25519 signature of the submit payload hash
+}
+
+/// The base32 alphabet used for Stellar strkeys
+const STRKEY_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// The strkey version byte of an ed25519 account public key ("G...")
+const STRKEY_ACCOUNT_VERSION: u8 = 6 << 3;
+
+/// Build the hash of the payload signed for a delegated submit. The payload is the XDR encoded
+/// pool address, followed by the SHA-256 hash of the XDR encoded requests, the big-endian nonce,
+/// and the big-endian expiration.
+///
+/// ### Arguments
+/// * `requests` - The requests being submitted
+/// * `nonce` - The nonce of the signer
+/// * `expiration` - The timestamp the signature expires at
+pub fn submit_payload_hash(
+    e: &Env,
+    requests: &Vec<Request>,
+    nonce: u64,
+    expiration: u64,
+) -> BytesN<32> {
+    let requests_hash = e.crypto().sha256(&requests.clone().to_xdr(e));
+    let mut payload = e.current_contract_address().to_xdr(e);
+    payload.extend_from_array(&requests_hash.to_array());
+    payload.extend_from_array(&nonce.to_be_bytes());
+    payload.extend_from_array(&expiration.to_be_bytes());
+    e.crypto().sha256(&payload).to_bytes()
+}
+
+/// Execute a set of requests signed off-chain by a Stellar account. The requests are submitted
+/// on behalf of the account owning the signing key, which takes on the positions, sends any
+/// required tokens to the pool with transfer_from, and receives any tokens sent from the pool.
+///
+/// Returns the new positions for the signer
+///
+/// ### Arguments
+/// * `requests` - The requests signed by the account
+/// * `signature` - The signature authorizing the requests
+///
+/// ### Panics
+/// If the signature is expired, has an unexpected nonce, or is invalid, or if the requests are
+/// unable to be fully executed
+pub fn execute_submit_with_signature(
+    e: &Env,
+    requests: Vec<Request>,
+    signature: &SubmitSignature,
+) -> Positions {
+    if signature.expiration < e.ledger().timestamp() {
+        panic_with_error!(e, PoolError::InvalidSubmitSignature);
+    }
+    let from = account_address(e, &signature.signer);
+    let nonce = storage::get_user_nonce(e, &from);
+    if signature.nonce != nonce {
+        panic_with_error!(e, PoolError::InvalidSubmitSignature);
+    }
+
+    let payload_hash = submit_payload_hash(e, &requests, signature.nonce, signature.expiration);
+    // panics if the signature is invalid
+    e.crypto().ed25519_verify(
+        &signature.signer,
+        &Bytes::from_array(e, &payload_hash.to_array()),
+        &signature.signature,
+    );
+    storage::set_user_nonce(e, &from, nonce + 1);
+
+    execute_submit(e, &from, &from, &from, requests, true, None).positions
+}
+
+/// Fetch the address of the Stellar account for an ed25519 public key
+///
+/// ### Arguments
+/// * `public_key` - The ed25519 public key of the account
+pub fn account_address(e: &Env, public_key: &BytesN<32>) -> Address {
+    let mut data = [0u8; 35];
+    data[0] = STRKEY_ACCOUNT_VERSION;
+    data[1..33].copy_from_slice(&public_key.to_array());
+    let checksum = crc16_xmodem(&data[..33]);
+    data[33..].copy_from_slice(&checksum.to_le_bytes());
+
+    // 35 bytes encode to exactly 56 base32 characters
+    let mut strkey = [0u8; 56];
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    let mut index = 0;
+    for byte in data {
+        bits = (bits << 8) | byte as u32;
+        bit_count += 8;
+        while bit_count >= 5 {
+            bit_count -= 5;
+            strkey[index] = STRKEY_ALPHABET[((bits >> bit_count) & 0x1f) as usize];
+            index += 1;
+        }
+        bits &= (1 << bit_count) - 1;
+    }
+    Address::from_string(&String::from_bytes(e, &strkey))
+}
+
+/// Calculate the CRC-16/XMODEM checksum used by Stellar strkeys
+fn crc16_xmodem(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            if crc & 0x8000 != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pool::RequestType, testutils};
+    use ed25519_dalek::{Signer, SigningKey};
+    use sep_41_token::testutils::MockTokenClient;
+    use soroban_sdk::{testutils::Ledger, vec};
+
+    fn sign_requests(
+        e: &Env,
+        key: &SigningKey,
+        requests: &Vec<Request>,
+        nonce: u64,
+        expiration: u64,
+    ) -> SubmitSignature {
+        let payload_hash = submit_payload_hash(e, requests, nonce, expiration);
+        SubmitSignature {
+            signer: BytesN::from_array(e, &key.verifying_key().to_bytes()),
+            nonce,
+            expiration,
+            signature: BytesN::from_array(e, &key.sign(&payload_hash.to_array()).to_bytes()),
+        }
+    }
+
+    #[test]
+    fn test_account_address() {
+        let e = Env::default();
+        let address = account_address(&e, &BytesN::from_array(&e, &[0u8; 32]));
+        assert_eq!(
+            address.to_string(),
+            String::from_str(
+                &e,
+                "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF"
+            )
+        );
+    }
+
+    #[test]
+    fn test_submit_with_signature() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set_timestamp(600);
+        e.ledger().set_sequence_number(1234);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        let (pool, assets) =
+            testutils::create_pool_with_reserves(&e, &[(reserve_config, reserve_data, 1_0000000)]);
+        let underlying = assets.get_unchecked(0);
+        let underlying_client = MockTokenClient::new(&e, &underlying);
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let samwise = account_address(&e, &BytesN::from_array(&e, &key.verifying_key().to_bytes()));
+        underlying_client.mint(&samwise, &20_0000000);
+        underlying_client.approve(&samwise, &pool, &20_0000000, &10000);
+
+        e.as_contract(&pool, || {
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 15_0000000,
+                },
+            ];
+            let signature = sign_requests(&e, &key, &requests, 0, 700);
+
+            let positions = execute_submit_with_signature(&e, requests, &signature);
+            assert_eq!(positions.collateral.get_unchecked(0), 15_0000000);
+            assert_eq!(underlying_client.balance(&samwise), 5_0000000);
+            assert_eq!(storage::get_user_nonce(&e, &samwise), 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1228)")]
+    fn test_submit_with_signature_replay() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set_timestamp(600);
+        e.ledger().set_sequence_number(1234);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        let (pool, assets) =
+            testutils::create_pool_with_reserves(&e, &[(reserve_config, reserve_data, 1_0000000)]);
+        let underlying = assets.get_unchecked(0);
+        let underlying_client = MockTokenClient::new(&e, &underlying);
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let samwise = account_address(&e, &BytesN::from_array(&e, &key.verifying_key().to_bytes()));
+        underlying_client.mint(&samwise, &20_0000000);
+        underlying_client.approve(&samwise, &pool, &20_0000000, &10000);
+
+        e.as_contract(&pool, || {
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 5_0000000,
+                },
+            ];
+            let signature = sign_requests(&e, &key, &requests, 0, 700);
+
+            execute_submit_with_signature(&e, requests.clone(), &signature);
+            execute_submit_with_signature(&e, requests, &signature);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1228)")]
+    fn test_submit_with_signature_expired() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set_timestamp(600);
+        e.ledger().set_sequence_number(1234);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        let (pool, assets) =
+            testutils::create_pool_with_reserves(&e, &[(reserve_config, reserve_data, 1_0000000)]);
+        let underlying = assets.get_unchecked(0);
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+
+        e.as_contract(&pool, || {
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 5_0000000,
+                },
+            ];
+            let signature = sign_requests(&e, &key, &requests, 0, 599);
+
+            execute_submit_with_signature(&e, requests, &signature);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_submit_with_signature_tampered_requests() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set_timestamp(600);
+        e.ledger().set_sequence_number(1234);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        let (pool, assets) =
+            testutils::create_pool_with_reserves(&e, &[(reserve_config, reserve_data, 1_0000000)]);
+        let underlying = assets.get_unchecked(0);
+        let underlying_client = MockTokenClient::new(&e, &underlying);
+
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let samwise = account_address(&e, &BytesN::from_array(&e, &key.verifying_key().to_bytes()));
+        underlying_client.mint(&samwise, &20_0000000);
+        underlying_client.approve(&samwise, &pool, &20_0000000, &10000);
+
+        e.as_contract(&pool, || {
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 5_0000000,
+                },
+            ];
+            let signature = sign_requests(&e, &key, &requests, 0, 700);
+
+            let tampered_requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying.clone(),
+                    amount: 15_0000000,
+                },
+            ];
+            execute_submit_with_signature(&e, tampered_requests, &signature);
+        });
+    }
+}
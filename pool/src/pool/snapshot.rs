@@ -0,0 +1,255 @@
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::{
+    errors::PoolError,
+    storage::{self, PositionSnapshot},
+};
+
+use super::{Positions, User};
+
+/// Declare a ledger at which the positions of every user's accounts are recorded. Positions are
+/// recorded lazily, the first time an account's positions change at or after the snapshot ledger,
+/// so the snapshot holds each account's positions at the start of the snapshot ledger. Earlier
+/// snapshots are kept, so a new snapshot can only be declared once the previous snapshot ledger
+/// has been reached.
+///
+/// Returns the ID of the snapshot
+///
+/// ### Arguments
+/// * `ledger` - The ledger sequence to record positions at
+///
+/// ### Panics
+/// If the ledger has already passed, or the previous snapshot ledger has not been reached
+pub fn execute_declare_snapshot(e: &Env, ledger: u32) -> u32 {
+    if ledger < e.ledger().sequence() {
+        panic_with_error!(e, PoolError::BadRequest);
+    }
+    let id = match storage::get_position_snapshot(e) {
+        Some(snapshot) => {
+            if e.ledger().sequence() < snapshot.ledger {
+                panic_with_error!(e, PoolError::BadRequest);
+            }
+            snapshot.id + 1
+        }
+        None => 0,
+    };
+    storage::set_position_snapshot(e, &PositionSnapshot { id, ledger });
+    id
+}
+
+/// Fetch the ID of the most recent snapshot whose ledger has been reached, if any
+fn load_reached_snapshot_id(e: &Env) -> Option<u32> {
+    let snapshot = storage::get_position_snapshot(e)?;
+    if e.ledger().sequence() >= snapshot.ledger {
+        Some(snapshot.id)
+    } else {
+        // the previous snapshot ledger was reached before this snapshot was declared
+        snapshot.id.checked_sub(1)
+    }
+}
+
+/// Record the stored positions of a user's account for the most recently reached snapshot
+/// before they change, if they are not yet recorded
+///
+/// ### Arguments
+/// * `user` - The user whose positions are about to be stored
+pub fn checkpoint_snapshot(e: &Env, user: &User) {
+    let id = match load_reached_snapshot_id(e) {
+        Some(id) => id,
+        None => return,
+    };
+    if storage::get_user_position_snapshot(e, id, &user.address, user.account_id).is_some() {
+        return;
+    }
+    storage::set_user_position_snapshot(
+        e,
+        id,
+        &user.address,
+        user.account_id,
+        &storage::get_account_positions(e, &user.address, user.account_id),
+    );
+}
+
+/// Fetch the positions of a user's account at a snapshot ledger. An account's positions are
+/// recorded for the snapshot reached when they first change after the snapshot ledger, which
+/// can be a later snapshot. Accounts whose positions have not changed since the snapshot ledger
+/// are not recorded, so their current positions are returned.
+///
+/// Returns None if the snapshot does not exist or its ledger has not been reached
+///
+/// ### Arguments
+/// * `id` - The ID of the snapshot
+/// * `user` - The address of the user
+/// * `account_id` - The ID of the sub-account
+pub fn load_snapshot_positions(
+    e: &Env,
+    id: u32,
+    user: &Address,
+    account_id: u32,
+) -> Option<Positions> {
+    let reached_id = load_reached_snapshot_id(e)?;
+    if id > reached_id {
+        return None;
+    }
+    for snapshot_id in id..=reached_id {
+        if let Some(positions) =
+            storage::get_user_position_snapshot(e, snapshot_id, user, account_id)
+        {
+            return Some(positions);
+        }
+    }
+    Some(storage::get_account_positions(e, user, account_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils;
+    use soroban_sdk::{
+        map,
+        testutils::{Address as _, Ledger},
+    };
+
+    #[test]
+    fn test_snapshot_records_positions_on_first_change() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        e.ledger().set_sequence_number(100);
+
+        e.as_contract(&pool, || {
+            let mut samwise_state = User::load(&e, &samwise);
+            samwise_state.positions.collateral = map![&e, (0, 10_0000000)];
+            samwise_state.store(&e);
+            let mut frodo_state = User::load(&e, &frodo);
+            frodo_state.positions.liabilities = map![&e, (1, 5_0000000)];
+            frodo_state.store(&e);
+
+            assert_eq!(execute_declare_snapshot(&e, 200), 0);
+            assert!(load_snapshot_positions(&e, 0, &samwise, 0).is_none());
+
+            // changes before the snapshot ledger are not recorded
+            samwise_state.positions.collateral = map![&e, (0, 12_0000000)];
+            samwise_state.store(&e);
+            assert!(storage::get_user_position_snapshot(&e, 0, &samwise, 0).is_none());
+
+            // the first change at the snapshot ledger records the prior positions
+            e.ledger().set_sequence_number(200);
+            samwise_state.positions.collateral = map![&e, (0, 20_0000000)];
+            samwise_state.store(&e);
+            samwise_state.positions.collateral = map![&e, (0, 30_0000000)];
+            samwise_state.store(&e);
+
+            let positions = load_snapshot_positions(&e, 0, &samwise, 0).unwrap();
+            assert_eq!(positions.collateral, map![&e, (0, 12_0000000)]);
+
+            // unchanged positions are read from the user's current positions
+            let positions = load_snapshot_positions(&e, 0, &frodo, 0).unwrap();
+            assert_eq!(positions.liabilities, map![&e, (1, 5_0000000)]);
+
+            // a new snapshot keeps the positions recorded for the previous snapshot
+            assert_eq!(execute_declare_snapshot(&e, 300), 1);
+            assert!(load_snapshot_positions(&e, 1, &samwise, 0).is_none());
+
+            // changes before the new snapshot ledger are recorded for the previous snapshot
+            e.ledger().set_sequence_number(250);
+            frodo_state.positions.liabilities = map![&e, (1, 6_0000000)];
+            frodo_state.store(&e);
+
+            e.ledger().set_sequence_number(300);
+            let positions = load_snapshot_positions(&e, 1, &samwise, 0).unwrap();
+            assert_eq!(positions.collateral, map![&e, (0, 30_0000000)]);
+            let positions = load_snapshot_positions(&e, 0, &samwise, 0).unwrap();
+            assert_eq!(positions.collateral, map![&e, (0, 12_0000000)]);
+            let positions = load_snapshot_positions(&e, 0, &frodo, 0).unwrap();
+            assert_eq!(positions.liabilities, map![&e, (1, 5_0000000)]);
+            let positions = load_snapshot_positions(&e, 1, &frodo, 0).unwrap();
+            assert_eq!(positions.liabilities, map![&e, (1, 6_0000000)]);
+            assert!(load_snapshot_positions(&e, 2, &frodo, 0).is_none());
+        });
+    }
+
+    #[test]
+    fn test_snapshot_records_later_snapshot_for_earlier_ledger() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        let samwise = Address::generate(&e);
+        e.ledger().set_sequence_number(100);
+
+        e.as_contract(&pool, || {
+            let mut samwise_state = User::load(&e, &samwise);
+            samwise_state.positions.collateral = map![&e, (0, 10_0000000)];
+            samwise_state.store(&e);
+
+            execute_declare_snapshot(&e, 200);
+            e.ledger().set_sequence_number(200);
+            execute_declare_snapshot(&e, 300);
+
+            // the first change after both snapshot ledgers records the positions of both
+            e.ledger().set_sequence_number(300);
+            samwise_state.positions.collateral = map![&e, (0, 20_0000000)];
+            samwise_state.store(&e);
+
+            assert!(storage::get_user_position_snapshot(&e, 0, &samwise, 0).is_none());
+            let positions = load_snapshot_positions(&e, 0, &samwise, 0).unwrap();
+            assert_eq!(positions.collateral, map![&e, (0, 10_0000000)]);
+            let positions = load_snapshot_positions(&e, 1, &samwise, 0).unwrap();
+            assert_eq!(positions.collateral, map![&e, (0, 10_0000000)]);
+        });
+    }
+
+    #[test]
+    fn test_snapshot_records_sub_accounts() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        let samwise = Address::generate(&e);
+        e.ledger().set_sequence_number(100);
+
+        e.as_contract(&pool, || {
+            let mut main_state = User::load(&e, &samwise);
+            main_state.positions.collateral = map![&e, (0, 10_0000000)];
+            main_state.store(&e);
+            let mut sub_state = User::load_account(&e, &samwise, 1);
+            sub_state.positions.collateral = map![&e, (1, 5_0000000)];
+            sub_state.store(&e);
+
+            execute_declare_snapshot(&e, 200);
+            e.ledger().set_sequence_number(200);
+            sub_state.positions.collateral = map![&e, (1, 7_0000000)];
+            sub_state.store(&e);
+
+            let positions = load_snapshot_positions(&e, 0, &samwise, 1).unwrap();
+            assert_eq!(positions.collateral, map![&e, (1, 5_0000000)]);
+            let positions = load_snapshot_positions(&e, 0, &samwise, 0).unwrap();
+            assert_eq!(positions.collateral, map![&e, (0, 10_0000000)]);
+            assert!(storage::get_user_position_snapshot(&e, 0, &samwise, 0).is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_declare_snapshot_previous_not_reached() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        e.ledger().set_sequence_number(100);
+
+        e.as_contract(&pool, || {
+            execute_declare_snapshot(&e, 200);
+            e.ledger().set_sequence_number(199);
+            execute_declare_snapshot(&e, 300);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_declare_snapshot_ledger_passed() {
+        let e = Env::default();
+        let pool = testutils::create_pool(&e);
+        e.ledger().set_sequence_number(100);
+
+        e.as_contract(&pool, || {
+            execute_declare_snapshot(&e, 99);
+        });
+    }
+}
@@ -23,6 +23,11 @@ pub fn execute_update_pool_status(e: &Env) -> u32 {
     }
 
     match pool_config.status {
+        // Decommissioned
+        7 => {
+            // Decommissioned is permanent
+            panic_with_error!(e, PoolError::StatusNotAllowed);
+        }
         // Setup
         6 => {
             // Setup supersedes all other statuses
@@ -77,6 +82,11 @@ pub fn execute_set_pool_status(e: &Env, pool_status: u32) {
 
     let pool_backstop_data = backstop_client.pool_data(&e.current_contract_address());
 
+    if pool_config.status == 7 {
+        // Decommissioned is permanent
+        panic_with_error!(e, PoolError::StatusNotAllowed);
+    }
+
     match pool_status {
         0 => {
             // Threshold must be met and q4w must be under 50% for the admin to set Active
@@ -109,6 +119,16 @@ pub fn execute_set_pool_status(e: &Env, pool_status: u32) {
             // Admin Frozen
             pool_config.status = 4;
         }
+        7 => {
+            // Pool must be frozen and all debt must be repaid to decommission
+            if pool_config.status < 4 || has_outstanding_debt(e) {
+                panic_with_error!(e, PoolError::StatusNotAllowed);
+            }
+            // lift the Q4W lock on the pool's backstop
+            backstop_client.decommission_pool(&e.current_contract_address());
+            // Decommissioned
+            pool_config.status = 7;
+        }
         _ => {
             panic_with_error!(e, PoolError::BadRequest);
         }
@@ -116,6 +136,17 @@ pub fn execute_set_pool_status(e: &Env, pool_status: u32) {
     storage::set_pool_config(e, &pool_config);
 }
 
+/// Check if any reserve in the pool has outstanding liabilities
+fn has_outstanding_debt(e: &Env) -> bool {
+    let reserve_list = storage::get_res_list(e);
+    for asset in reserve_list.iter() {
+        if storage::get_res_data(e, &asset).d_supply > 0 {
+            return true;
+        }
+    }
+    false
+}
+
 /// Calculate the threshold for the pool's backstop balance
 ///
 /// Returns the threshold as a percentage^5 in SCALAR_7 points such that SCALAR_7 = 100%
@@ -151,7 +182,10 @@ pub fn calc_pool_backstop_threshold(pool_backstop_data: &PoolBackstopData) -> i1
 mod tests {
     use crate::{
         storage::PoolConfig,
-        testutils::{create_backstop, create_comet_lp_pool, create_pool, create_token_contract},
+        testutils::{
+            create_backstop, create_comet_lp_pool, create_pool, create_reserve,
+            create_token_contract, default_reserve_meta,
+        },
     };
 
     use super::*;
@@ -191,6 +225,11 @@ mod tests {
             bstop_rate: 0,
             status: 1,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -238,6 +277,11 @@ mod tests {
             bstop_rate: 0,
             status: 1,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -283,6 +327,11 @@ mod tests {
             bstop_rate: 0,
             status: 2,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -325,6 +374,11 @@ mod tests {
             bstop_rate: 0,
             status: 1,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -373,6 +427,11 @@ mod tests {
             bstop_rate: 0,
             status: 5,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -417,6 +476,11 @@ mod tests {
             bstop_rate: 0,
             status: 6,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -459,6 +523,11 @@ mod tests {
             bstop_rate: 0,
             status: 1,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -470,6 +539,158 @@ mod tests {
             assert_eq!(new_pool_config.status, 4);
         });
     }
+    #[test]
+    fn test_set_pool_status_decommissioned() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        let pool_id = create_pool(&e);
+        let oracle_id = Address::generate(&e);
+
+        let bombadil = Address::generate(&e);
+
+        let (blnd, _) = create_token_contract(&e, &bombadil);
+        let (usdc, _) = create_token_contract(&e, &bombadil);
+        let (lp_token, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        let (_, backstop_client) = create_backstop(&e, &pool_id, &lp_token, &usdc, &blnd);
+
+        let (underlying, _) = create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = default_reserve_meta();
+        reserve_data.d_supply = 0;
+        create_reserve(&e, &pool_id, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: oracle_id,
+            bstop_rate: 0,
+            status: 4,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_id, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_pool_status(&e, 7);
+
+            let new_pool_config = storage::get_pool_config(&e);
+            assert_eq!(new_pool_config.status, 7);
+        });
+        assert!(backstop_client.decommissioned(&pool_id));
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1204)")]
+    fn test_set_pool_status_decommissioned_with_debt() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        let pool_id = create_pool(&e);
+        let oracle_id = Address::generate(&e);
+
+        let bombadil = Address::generate(&e);
+
+        let (blnd, _) = create_token_contract(&e, &bombadil);
+        let (usdc, _) = create_token_contract(&e, &bombadil);
+        let (lp_token, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        create_backstop(&e, &pool_id, &lp_token, &usdc, &blnd);
+
+        let (underlying, _) = create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = default_reserve_meta();
+        create_reserve(&e, &pool_id, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: oracle_id,
+            bstop_rate: 0,
+            status: 4,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_id, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_pool_status(&e, 7);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1204)")]
+    fn test_set_pool_status_decommissioned_not_frozen() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        let pool_id = create_pool(&e);
+        let oracle_id = Address::generate(&e);
+
+        let bombadil = Address::generate(&e);
+
+        let (blnd, _) = create_token_contract(&e, &bombadil);
+        let (usdc, _) = create_token_contract(&e, &bombadil);
+        let (lp_token, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        create_backstop(&e, &pool_id, &lp_token, &usdc, &blnd);
+
+        let pool_config = PoolConfig {
+            oracle: oracle_id,
+            bstop_rate: 0,
+            status: 1,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_id, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_pool_status(&e, 7);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1204)")]
+    fn test_set_pool_status_from_decommissioned() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        let pool_id = create_pool(&e);
+        let oracle_id = Address::generate(&e);
+
+        let bombadil = Address::generate(&e);
+
+        let (blnd, _) = create_token_contract(&e, &bombadil);
+        let (usdc, _) = create_token_contract(&e, &bombadil);
+        let (lp_token, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        create_backstop(&e, &pool_id, &lp_token, &usdc, &blnd);
+
+        let pool_config = PoolConfig {
+            oracle: oracle_id,
+            bstop_rate: 0,
+            status: 7,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_id, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_set_pool_status(&e, 4);
+        });
+    }
+
     #[test]
     #[should_panic(expected = "Error(Contract, #1200)")]
     fn test_set_non_admin_pool_status_panics() {
@@ -505,6 +726,11 @@ mod tests {
             bstop_rate: 0,
             status: 2,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -548,6 +774,11 @@ mod tests {
             bstop_rate: 0,
             status: 3,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -595,6 +826,11 @@ mod tests {
             bstop_rate: 0,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -642,6 +878,11 @@ mod tests {
             bstop_rate: 0,
             status: 1,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -690,6 +931,11 @@ mod tests {
             bstop_rate: 0,
             status: 1,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -738,6 +984,11 @@ mod tests {
             bstop_rate: 0,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -786,6 +1037,11 @@ mod tests {
             bstop_rate: 0,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -834,6 +1090,11 @@ mod tests {
             bstop_rate: 0,
             status: 1,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -881,6 +1142,11 @@ mod tests {
             bstop_rate: 0,
             status: 2,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -929,6 +1195,11 @@ mod tests {
             bstop_rate: 0,
             status: 2,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -977,6 +1248,11 @@ mod tests {
             bstop_rate: 0,
             status: 4,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -1021,6 +1297,46 @@ mod tests {
             bstop_rate: 0,
             status: 6,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool_id, || {
+            storage::set_admin(&e, &bombadil);
+            storage::set_pool_config(&e, &pool_config);
+
+            execute_update_pool_status(&e);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1204)")]
+    fn test_update_pool_status_decommissioned() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+        let pool_id = create_pool(&e);
+        let oracle_id = Address::generate(&e);
+
+        let bombadil = Address::generate(&e);
+
+        let (blnd, _) = create_token_contract(&e, &bombadil);
+        let (usdc, _) = create_token_contract(&e, &bombadil);
+        let (lp_token, _) = create_comet_lp_pool(&e, &bombadil, &blnd, &usdc);
+        create_backstop(&e, &pool_id, &lp_token, &usdc, &blnd);
+
+        let pool_config = PoolConfig {
+            oracle: oracle_id,
+            bstop_rate: 0,
+            status: 7,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -1066,6 +1382,11 @@ mod tests {
             bstop_rate: 0,
             status: 5,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool_id, || {
             storage::set_admin(&e, &bombadil);
@@ -1,15 +1,60 @@
+use cast::i128;
+#[cfg(feature = "flash-loans")]
 use moderc3156::FlashLoanClient;
 use sep_41_token::TokenClient;
-use soroban_sdk::{panic_with_error, Address, Env, Map, Vec};
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    contracttype, panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Map, Vec,
+};
 
-use crate::{events::PoolEvents, PoolError};
+use crate::{constants::SCALAR_7, events::PoolEvents, storage, PoolError};
 
 use super::{
-    actions::{build_actions_from_request, Actions, Request},
+    actions::{
+        build_actions_from_request, build_actions_with_options, Actions, Request, RequestOptions,
+        RequestResult, RequestType,
+    },
+    fixed_rate::settle_fixed_loans,
     health_factor::PositionData,
+    native::is_native_asset,
     pool::Pool,
-    FlashLoan, Positions, User,
+    price_attestation::PriceAttestation,
+    referral::record_referrer,
+    self_repay::apply_self_repay,
+    Positions, User,
+};
+#[cfg(feature = "flash-loans")]
+use super::{
+    actions::{require_allowed_borrower, CollateralSwap},
+    anomaly::require_flash_loan_below_anomaly,
+    fee_tier::{apply_fee_tier, load_user_fee_volume},
+    isolation::add_isolated_debt,
+    pause::require_not_paused,
+    FlashLoan,
 };
+#[cfg(feature = "flash-loans")]
+use crate::{constants::PAUSE_FLASH_LOAN, dependencies::SwapAdapterClient};
+
+/// The result of a set of requests submitted against the pool
+#[derive(Clone)]
+#[contracttype]
+pub struct SubmitResult {
+    pub positions: Positions,        // the positions of the user after the requests are processed
+    pub results: Vec<RequestResult>, // the result of each request, in the order submitted
+}
+
+/// The projected outcome of a set of requests, if they were submitted against the pool
+#[derive(Clone)]
+#[contracttype]
+pub struct SimulateResult {
+    pub positions: Positions,        // the positions of the user after the requests are processed
+    pub results: Vec<RequestResult>, // the result of each request, in the order submitted
+    pub spender_transfer: Map<Address, i128>, // the tokens the spender would send to the pool
+    pub pool_transfer: Map<Address, i128>, // the tokens the pool would send to "to"
+    pub request_spender_transfer: Map<(Address, Address), i128>, // the tokens each request's spender would send to the pool, keyed by (spender, asset)
+    pub request_pool_transfer: Map<(Address, Address), i128>, // the tokens the pool would send to each request's "to", keyed by (to, asset)
+    pub health_factor: i128, // the health factor after the requests, expressed in 7 decimals. i128::MAX if there are no liabilities
+}
 
 /// Execute a set of updates for a user against the pool.
 ///
@@ -19,9 +64,13 @@ use super::{
 /// * to - The address of the user who is receiving tokens from the pool
 /// * requests - A vec of requests to be processed
 /// * use_allowance - A bool indicating if transfer_from is to be used
+/// * referrer - The address that referred the user, if any
+///
+/// ### Returns
+/// The user's positions after the requests are processed and the result of each request
 ///
 /// ### Panics
-/// If the request is unable to be fully executed
+/// If the request is unable to be fully executed, or the user refers themselves
 pub fn execute_submit(
     e: &Env,
     from: &Address,
@@ -29,7 +78,173 @@ pub fn execute_submit(
     to: &Address,
     requests: Vec<Request>,
     use_allowance: bool,
+    referrer: Option<&Address>,
+) -> SubmitResult {
+    if let Some(referrer) = referrer {
+        record_referrer(e, from, referrer);
+    }
+    submit_requests(e, from, spender, to, requests, &vec![e], use_allowance, &vec![e], None)
+}
+
+/// Same as `execute_submit` but with options for each request, like the sub-account the
+/// requests act on, or a spender and "to" for the tokens of a request
+///
+/// ### Arguments
+/// * from - The address of the user whose positions are being modified
+/// * spender - The address of the user who is sending tokens to the pool
+/// * to - The address of the user who is receiving tokens from the pool
+/// * requests - A vec of requests to be processed
+/// * options - The options of each request, in the order of the requests
+///
+/// ### Panics
+/// If the request is unable to be fully executed, or the options don't match the requests
+pub fn execute_submit_with_options(
+    e: &Env,
+    from: &Address,
+    spender: &Address,
+    to: &Address,
+    requests: Vec<Request>,
+    options: Vec<RequestOptions>,
+) -> SubmitResult {
+    if options.len() != requests.len() {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+    submit_requests(e, from, spender, to, requests, &options, false, &vec![e], None)
+}
+
+/// Same as `execute_submit` but uses the signed price attestations in place of the Pool's
+/// oracle for the attested assets.
+///
+/// ### Arguments
+/// * from - The address of the user whose positions are being modified
+/// * spender - The address of the user who is sending tokens to the pool
+/// * to - The address of the user who is receiving tokens from the pool
+/// * requests - A vec of requests to be processed
+/// * use_allowance - A bool indicating if transfer_from is to be used
+/// * prices - A vec of signed price attestations
+///
+/// ### Panics
+/// If the request is unable to be fully executed or any price attestation is invalid
+pub fn execute_submit_with_prices(
+    e: &Env,
+    from: &Address,
+    spender: &Address,
+    to: &Address,
+    requests: Vec<Request>,
+    use_allowance: bool,
+    prices: &Vec<PriceAttestation>,
+) -> Positions {
+    submit_requests(e, from, spender, to, requests, &vec![e], use_allowance, prices, None)
+        .positions
+}
+
+/// Same as `execute_submit` but submitted by a delegatee on behalf of "from" using credit
+/// delegation. The delegatee can only borrow against and repay "from"'s positions, and each borrow
+/// spends the delegatee's credit delegation for the borrowed asset.
+///
+/// ### Arguments
+/// * from - The address of the delegator whose positions are being modified
+/// * delegatee - The address of the delegatee who is sending tokens to the pool
+/// * to - The address of the user who is receiving tokens from the pool
+/// * requests - A vec of requests to be processed
+///
+/// ### Panics
+/// If the request is unable to be fully executed, a request is not a borrow or repay, or a borrow
+/// exceeds the delegatee's credit delegation
+pub fn execute_submit_with_delegation(
+    e: &Env,
+    from: &Address,
+    delegatee: &Address,
+    to: &Address,
+    requests: Vec<Request>,
 ) -> Positions {
+    if from == delegatee {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+    submit_requests(e, from, delegatee, to, requests, &vec![e], false, &vec![e], Some(delegatee))
+        .positions
+}
+
+/// Simulate a set of requests for a user against the pool. The requests are processed and health
+/// checked the same as `execute_submit`, but against a read only pool and user, so nothing is
+/// written to the ledger and no tokens are transferred.
+///
+/// ### Arguments
+/// * from - The address of the user whose positions would be modified
+/// * requests - A vec of requests to be simulated
+///
+/// ### Returns
+/// The projected positions, request results, transfers and health factor of the user
+///
+/// ### Panics
+/// If the requests would fail to execute, or a request fills or deletes an auction, as auctions
+/// are stored as they are processed
+pub fn execute_simulate_submit(e: &Env, from: &Address, requests: Vec<Request>) -> SimulateResult {
+    if from == &e.current_contract_address() {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+    for request in requests.iter() {
+        match RequestType::from_u32(e, request.request_type) {
+            RequestType::Supply
+            | RequestType::Withdraw
+            | RequestType::SupplyCollateral
+            | RequestType::WithdrawCollateral
+            | RequestType::Borrow
+            | RequestType::Repay
+            | RequestType::RepayOnBehalf
+            | RequestType::WithdrawThrottled
+            | RequestType::WithdrawCollateralThrottled
+            | RequestType::SupplyCollateralFor => {}
+            RequestType::FillUserLiquidationAuction
+            | RequestType::FillBadDebtAuction
+            | RequestType::FillInterestAuction
+            | RequestType::DeleteLiquidationAuction
+            | RequestType::FillUserLiquidationAuctionToTarget => {
+                panic_with_error!(e, &PoolError::BadRequest)
+            }
+        }
+    }
+    let mut pool = Pool::load(e);
+    pool.set_read_only();
+    let mut from_state = User::load(e, from);
+    from_state.read_only = true;
+    settle_fixed_loans(e, &mut pool, &mut from_state);
+
+    let actions = build_actions_from_request(e, &mut pool, &mut from_state, requests, None);
+
+    let emode = from_state.emode(e);
+    if actions.check_health {
+        require_healthy_positions(e, &mut pool, &from_state, emode);
+    }
+    let health_factor =
+        PositionData::calculate_from_positions(e, &mut pool, &from_state.positions, emode)
+            .as_health_factor_data()
+            .health_factor;
+
+    // the pool and user state are dropped without being stored
+    SimulateResult {
+        positions: from_state.positions,
+        results: actions.results,
+        spender_transfer: actions.spender_transfer,
+        pool_transfer: actions.pool_transfer,
+        request_spender_transfer: actions.request_spender_transfer,
+        request_pool_transfer: actions.request_pool_transfer,
+        health_factor,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn submit_requests(
+    e: &Env,
+    from: &Address,
+    spender: &Address,
+    to: &Address,
+    requests: Vec<Request>,
+    options: &Vec<RequestOptions>,
+    use_allowance: bool,
+    prices: &Vec<PriceAttestation>,
+    delegatee: Option<&Address>,
+) -> SubmitResult {
     if from == &e.current_contract_address()
         || spender == &e.current_contract_address()
         || to == &e.current_contract_address()
@@ -37,54 +252,196 @@ pub fn execute_submit(
         panic_with_error!(e, &PoolError::BadRequest);
     }
     let mut pool = Pool::load(e);
-    let mut from_state = User::load(e, from);
+    pool.load_price_attestations(e, prices);
+    let mut from_state = load_from_state(e, from, options);
+    let results = process_requests(
+        e,
+        &mut pool,
+        &mut from_state,
+        spender,
+        to,
+        requests,
+        options,
+        use_allowance,
+        delegatee,
+    );
 
-    let actions = build_actions_from_request(e, &mut pool, &mut from_state, requests);
+    // store updated info to ledger
+    pool.store_cached_reserves(e);
+    from_state.store(e);
 
-    // panics if the new positions set does not meet the health factor requirement
-    // min is 1.0000100 to prevent rounding errors
-    if actions.check_health
-        && from_state.has_liabilities()
-        && PositionData::calculate_from_positions(e, &mut pool, &from_state.positions)
-            .is_hf_under(1_0000100)
-    {
-        panic_with_error!(e, PoolError::InvalidHf);
+    SubmitResult {
+        positions: from_state.positions,
+        results,
     }
+}
 
-    if use_allowance {
-        handle_transfer_with_allowance(e, &actions, spender, to);
+/// Process a set of requests for "from_state" against the loaded pool, and settle the tokens
+/// sent to and from the pool with "spender" and "to". Neither the pool nor "from_state" is
+/// stored.
+///
+/// Returns the result of each request
+#[allow(clippy::too_many_arguments)]
+fn process_requests(
+    e: &Env,
+    pool: &mut Pool,
+    from_state: &mut User,
+    spender: &Address,
+    to: &Address,
+    requests: Vec<Request>,
+    options: &Vec<RequestOptions>,
+    use_allowance: bool,
+    delegatee: Option<&Address>,
+) -> Vec<RequestResult> {
+    settle_fixed_loans(e, pool, from_state);
+    apply_self_repay(e, pool, from_state);
+
+    let actions = build_actions_with_options(e, pool, from_state, requests, options, delegatee);
+
+    // panics if the new positions set does not meet the health factor or leverage requirements
+    if actions.check_health {
+        require_healthy_positions(e, pool, from_state, from_state.emode(e));
+    }
+
+    handle_request_spender_transfers(e, &actions);
+    let shortfalls = if use_allowance {
+        handle_transfer_with_allowance(e, &actions, spender, to)
     } else {
-        handle_transfers(e, &actions, spender, to);
+        handle_transfers(e, &actions, spender, to)
+    };
+    apply_transfer_shortfalls(e, pool, from_state, &shortfalls);
+    handle_request_pool_transfers(e, &actions);
+    actions.results
+}
+
+/// Execute a set of updates for multiple users against the pool. All users share a single
+/// pool load, so reserve updates and oracle reads are only performed once. Each user's requests
+/// are otherwise processed the same as `execute_submit`.
+///
+/// Each user has their own spender and "to", so a market maker can fund and collect from all of
+/// its sub-accounts with a single address.
+///
+/// ### Arguments
+/// * batch - A vec of (user, spender, to, requests) tuples, where the requests are processed for
+/// the user, the spender sends any required tokens to the pool and "to" receives any tokens sent
+/// from the pool
+///
+/// ### Panics
+/// If any request is unable to be fully executed, a user is included more than once, or the
+/// pool receives less than the amount of tokens sent by a spender
+pub fn execute_submit_batch(
+    e: &Env,
+    batch: Vec<(Address, Address, Address, Vec<Request>)>,
+) -> Vec<Positions> {
+    let mut pool = Pool::load(e);
+    let mut users: Vec<Address> = vec![e];
+    let mut batch_positions: Vec<Positions> = vec![e];
+    for (from, spender, to, requests) in batch.iter() {
+        if from == e.current_contract_address()
+            || spender == e.current_contract_address()
+            || to == e.current_contract_address()
+            || users.contains(&from)
+        {
+            panic_with_error!(e, &PoolError::BadRequest);
+        }
+        let mut from_state = User::load(e, &from);
+        process_requests(
+            e,
+            &mut pool,
+            &mut from_state,
+            &spender,
+            &to,
+            requests,
+            &vec![e],
+            false,
+            None,
+        );
+
+        from_state.store(e);
+        users.push_back(from);
+        batch_positions.push_back(from_state.positions);
     }
 
     // store updated info to ledger
     pool.store_cached_reserves(e);
-    from_state.store(e);
 
-    from_state.positions
+    batch_positions
 }
 
 /// Same as `execute_submit` but specifically made for performing a flash loan borrow before
 /// the other submitted requests.
+#[cfg(feature = "flash-loans")]
 pub fn execute_submit_with_flash_loan(
     e: &Env,
     from: &Address,
     flash_loan: FlashLoan,
     requests: Vec<Request>,
 ) -> Positions {
-    if from == &e.current_contract_address() {
+    execute_submit_with_flash_loans(e, from, vec![e, flash_loan], requests)
+}
+
+/// Same as `execute_submit` but specifically made for performing multiple flash loan borrows
+/// before the other submitted requests. All flash loaned assets are transferred before any
+/// receiver contract is called, so each receiver holds every flash loaned asset sent to it
+/// when it executes.
+///
+/// ### Panics
+/// If no flash loans are included, the user is not on the borrower allowlist of a pool that
+/// restricts borrowing, or the requests are unable to be fully executed
+#[cfg(feature = "flash-loans")]
+pub fn execute_submit_with_flash_loans(
+    e: &Env,
+    from: &Address,
+    flash_loans: Vec<FlashLoan>,
+    requests: Vec<Request>,
+) -> Positions {
+    if from == &e.current_contract_address() || flash_loans.is_empty() {
         panic_with_error!(e, &PoolError::BadRequest);
     }
+    require_not_paused(e, PAUSE_FLASH_LOAN);
+    require_allowed_borrower(e, from);
     let mut pool = Pool::load(e);
+    // a flash loan is a borrow, so it is subject to the same pool and reserve checks
+    pool.require_action_allowed(e, RequestType::Borrow as u32);
     let mut from_state = User::load(e, from);
+    settle_fixed_loans(e, &mut pool, &mut from_state);
+    let mut fees: Vec<i128> = vec![e];
+
+    // volume is only tracked if the pool charges a tiered fee
+    let fee_tiers = storage::get_fee_tiers(e);
+    let track_volume = pool.config.flash_loan_fee > 0 && !fee_tiers.is_empty();
+    let mut fee_volume = load_user_fee_volume(e, from);
+    let flash_loan_fee = apply_fee_tier(pool.config.flash_loan_fee, &fee_tiers, &fee_volume);
 
     // note: we add the flash loan liabilities before processing the other
     // requests.
-    {
+    for flash_loan in flash_loans.iter() {
         let mut reserve = pool.load_reserve(e, &flash_loan.asset, true);
+        reserve.require_action_allowed(e, RequestType::Borrow as u32);
         let d_tokens_minted = reserve.to_d_token_up(flash_loan.amount);
         from_state.add_liabilities(e, &mut reserve, d_tokens_minted);
         reserve.require_utilization_below_max(e);
+        reserve.require_debt_below_cap(e);
+        require_flash_loan_below_anomaly(e, &reserve);
+
+        // the flash loan fee is paid on top of the borrowed amount and is credited
+        // in full to the backstop
+        let fee = flash_loan
+            .amount
+            .fixed_mul_ceil(i128(flash_loan_fee), SCALAR_7)
+            .unwrap_optimized();
+        if fee > 0 {
+            reserve.gulp(SCALAR_7 as u32, fee);
+        }
+        fees.push_back(fee);
+        if track_volume {
+            fee_volume.volume += pool
+                .load_price(e, &flash_loan.asset)
+                .fixed_mul_floor(flash_loan.amount, reserve.scalar)
+                .unwrap_optimized();
+        }
+        pool.cache_reserve(reserve);
+        add_isolated_debt(e, &mut pool, &from_state, &flash_loan.asset, flash_loan.amount);
 
         PoolEvents::flash_loan(
             e,
@@ -98,47 +455,330 @@ pub fn execute_submit_with_flash_loan(
 
     // note: check_health is omitted since we always will want to check the health
     // if a flash loan is involved.
-    let actions = build_actions_from_request(e, &mut pool, &mut from_state, requests);
-
-    // panics if the new positions set does not meet the health factor requirement
-    // min is 1.0000100 to prevent rounding errors
-    if from_state.has_liabilities()
-        && PositionData::calculate_from_positions(e, &mut pool, &from_state.positions)
-            .is_hf_under(1_0000100)
-    {
-        panic_with_error!(e, PoolError::InvalidHf);
+    let mut actions = build_actions_from_request(e, &mut pool, &mut from_state, requests, None);
+    // tokens are only exchanged with "from", so requests can't set their own spender or "to"
+    if actions.has_request_transfers() {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+    for (flash_loan, fee) in flash_loans.iter().zip(fees.iter()) {
+        if fee > 0 {
+            actions.add_for_spender_transfer(&flash_loan.asset, fee);
+        }
     }
 
-    // we deal with the flashloan transfer before the others to allow the flash
-    // loan to yield the repaid or supplied amount in the transfers.
-    TokenClient::new(e, &flash_loan.asset).transfer(
-        &e.current_contract_address(),
-        &flash_loan.contract,
-        &flash_loan.amount,
-    );
-    // calls the receiver contract with "from" as the caller
-    FlashLoanClient::new(&e, &flash_loan.contract).exec_op(
-        &from,
-        &flash_loan.asset,
-        &flash_loan.amount,
-        &0,
-    );
+    // panics if the new positions set does not meet the health factor or leverage requirements
+    require_healthy_positions(e, &mut pool, &from_state, from_state.emode(e));
+
+    // we deal with the flashloan transfers before the others to allow the flash
+    // loans to yield the repaid or supplied amount in the transfers.
+    for flash_loan in flash_loans.iter() {
+        TokenClient::new(e, &flash_loan.asset).transfer(
+            &e.current_contract_address(),
+            &flash_loan.contract,
+            &flash_loan.amount,
+        );
+    }
+    // calls the receiver contracts with "from" as the caller
+    for (flash_loan, fee) in flash_loans.iter().zip(fees.iter()) {
+        FlashLoanClient::new(&e, &flash_loan.contract).exec_op(
+            &from,
+            &flash_loan.asset,
+            &flash_loan.amount,
+            &fee,
+        );
+    }
 
     // note: at this point, the pool has sum_by_asset(actions.flash_borrow.1) for each involed asset, but the user also has
     // increased liabilities. These will have to be either fully repaid by now in the requests following the flash borrow
     // or the user needs to have some previously added collateral to cover the borrow, i.e user is already healthy at this point,
     // we just have to make sure that they have the balances they are claiming to have through the transfers.
 
-    handle_transfer_with_allowance(e, &actions, from, from);
+    let shortfalls = handle_transfer_with_allowance(e, &actions, from, from);
+    apply_transfer_shortfalls(e, &mut pool, &mut from_state, &shortfalls);
 
     // store updated info to ledger
     pool.store_cached_reserves(e);
     from_state.store(e);
+    if track_volume {
+        fee_volume.count += 1;
+        storage::set_user_fee_volume(e, from, &fee_volume);
+    }
+
+    from_state.positions
+}
+
+/// Same as `execute_submit` but swaps collateral through a swap adapter before the other submitted
+/// requests. Like a flash loan, the new collateral is supplied for "from" before the pool holds it,
+/// and the old collateral is withdrawn and sent to the swap adapter, which must return the new
+/// collateral to the pool.
+///
+/// ### Panics
+/// If the swap is invalid, the swap adapter does not return the new collateral, or the requests
+/// are unable to be fully executed
+#[cfg(feature = "flash-loans")]
+pub fn execute_submit_with_collateral_swap(
+    e: &Env,
+    from: &Address,
+    swap: CollateralSwap,
+    requests: Vec<Request>,
+) -> Positions {
+    let (from_state, amount_in) =
+        submit_with_swap(e, from, &swap, RequestType::SupplyCollateral, requests);
+
+    PoolEvents::swap_collateral(
+        e,
+        from.clone(),
+        swap.adapter,
+        swap.asset_in,
+        amount_in,
+        swap.asset_out,
+        swap.amount_out,
+    );
+    from_state.positions
+}
 
+/// Unwind part of the position of "from" by repaying the liability of "asset_out" with collateral
+/// of "asset_in" swapped through a swap adapter. Like a flash loan, the debt is repaid before the
+/// pool holds the tokens, and the collateral is withdrawn and sent to the swap adapter, which must
+/// return at least "amount_out" of the debt asset to the pool. Any amount beyond the outstanding
+/// liability is refunded to "from".
+///
+/// ### Panics
+/// If the swap is invalid, the swap adapter does not return the debt asset, or the resulting
+/// position is unhealthy
+#[cfg(feature = "flash-loans")]
+pub fn execute_deleverage(e: &Env, from: &Address, swap: CollateralSwap) -> Positions {
+    let (from_state, amount_in) = submit_with_swap(e, from, &swap, RequestType::Repay, vec![e]);
+
+    PoolEvents::deleverage(
+        e,
+        from.clone(),
+        swap.adapter,
+        swap.asset_in,
+        amount_in,
+        swap.asset_out,
+        swap.amount_out,
+    );
     from_state.positions
 }
 
-fn handle_transfer_with_allowance(e: &Env, actions: &Actions, spender: &Address, to: &Address) {
+/// Withdraw "asset_in" collateral from "from" and swap it through the swap adapter for
+/// "asset_out", which is applied to the positions of "from" with a request of "out_request_type",
+/// then execute the remaining requests.
+///
+/// Returns the updated user and the amount of "asset_in" sent to the swap adapter
+#[cfg(feature = "flash-loans")]
+fn submit_with_swap(
+    e: &Env,
+    from: &Address,
+    swap: &CollateralSwap,
+    out_request_type: RequestType,
+    requests: Vec<Request>,
+) -> (User, i128) {
+    if from == &e.current_contract_address()
+        || swap.asset_in == swap.asset_out
+        || swap.amount_in <= 0
+        || swap.amount_out <= 0
+    {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+    let mut pool = Pool::load(e);
+    let mut from_state = User::load(e, from);
+    settle_fixed_loans(e, &mut pool, &mut from_state);
+
+    // note: the swap is processed as a collateral withdrawal and a request for the output
+    // before the other requests, but the tokens are exchanged with the swap adapter instead
+    // of "from"
+    let swap_requests = vec![
+        e,
+        Request {
+            request_type: RequestType::WithdrawCollateral as u32,
+            address: swap.asset_in.clone(),
+            amount: swap.amount_in,
+        },
+        Request {
+            request_type: out_request_type as u32,
+            address: swap.asset_out.clone(),
+            amount: swap.amount_out,
+        },
+    ];
+    let swap_actions =
+        build_actions_from_request(e, &mut pool, &mut from_state, swap_requests, None);
+    let amount_in = swap_actions
+        .pool_transfer
+        .get_unchecked(swap.asset_in.clone());
+    // a repayment beyond the outstanding liability is refunded to "from"
+    let refund_out = swap_actions
+        .pool_transfer
+        .get(swap.asset_out.clone())
+        .unwrap_or(0);
+
+    // note: check_health is omitted since we always will want to check the health
+    // if collateral is withdrawn.
+    let actions = build_actions_from_request(e, &mut pool, &mut from_state, requests, None);
+    // tokens are only exchanged with "from", so requests can't set their own spender or "to"
+    if actions.has_request_transfers() {
+        panic_with_error!(e, &PoolError::BadRequest);
+    }
+
+    // panics if the new positions set does not meet the health factor or leverage requirements
+    require_healthy_positions(e, &mut pool, &from_state, storage::get_user_emode(e, from));
+
+    // we deal with the swap before the other transfers, and verify the swap adapter
+    // returned the output tokens applied for "from"
+    TokenClient::new(e, &swap.asset_in).transfer(
+        &e.current_contract_address(),
+        &swap.adapter,
+        &amount_in,
+    );
+    let asset_out_client = TokenClient::new(e, &swap.asset_out);
+    let pre_balance = asset_out_client.balance(&e.current_contract_address());
+    SwapAdapterClient::new(e, &swap.adapter).exec_swap(
+        from,
+        &e.current_contract_address(),
+        &swap.asset_in,
+        &amount_in,
+        &swap.asset_out,
+        &swap.amount_out,
+    );
+    if asset_out_client.balance(&e.current_contract_address()) - pre_balance < swap.amount_out {
+        panic_with_error!(e, PoolError::InsufficientSwapOutput);
+    }
+    if refund_out > 0 {
+        asset_out_client.transfer(&e.current_contract_address(), from, &refund_out);
+    }
+
+    let shortfalls = handle_transfer_with_allowance(e, &actions, from, from);
+    apply_transfer_shortfalls(e, &mut pool, &mut from_state, &shortfalls);
+
+    // store updated info to ledger
+    pool.store_cached_reserves(e);
+    from_state.store(e);
+
+    (from_state, amount_in)
+}
+
+/// Check the user's positions meet the pool's health factor and leverage requirements, and emit
+/// a health warning if the positions are under the pool's health warning threshold
+///
+/// ### Panics
+/// If the user has liabilities and the positions are under the pool's minimum health factor or
+/// over the pool's maximum account leverage
+pub(super) fn require_healthy_positions(e: &Env, pool: &mut Pool, user: &User, emode: u32) {
+    if !user.has_liabilities() {
+        return;
+    }
+    let position_data = PositionData::calculate_from_positions(e, pool, &user.positions, emode);
+    // min is set by the pool, and is at least 1.0000100 to prevent rounding errors
+    if position_data.is_hf_under(pool.min_health_factor()) {
+        panic_with_error!(e, PoolError::InvalidHf);
+    }
+    if position_data.is_leverage_over(pool.max_account_leverage()) {
+        panic_with_error!(e, PoolError::ExcessiveLeverage);
+    }
+
+    let warning_threshold = i128(storage::get_health_warning_threshold(e));
+    if warning_threshold > 0 && position_data.is_hf_under(warning_threshold) {
+        // the effective collateral needed to bring the positions back to the threshold
+        let shortfall = position_data
+            .liability_base
+            .fixed_mul_ceil(warning_threshold, SCALAR_7)
+            .unwrap_optimized()
+            - position_data.collateral_base;
+        PoolEvents::health_warning(
+            e,
+            user.address.clone(),
+            user.account_id,
+            position_data.as_health_factor_data().health_factor,
+            shortfall,
+        );
+    }
+}
+
+/// Load the sub-account of "from" that the requests act on. Building the actions verifies that
+/// every request acts on the same sub-account.
+fn load_from_state(e: &Env, from: &Address, options: &Vec<RequestOptions>) -> User {
+    let account_id = options.first().map_or(0, |options| options.account_id);
+    User::load_account(e, from, account_id)
+}
+
+/// Transfer `amount` of `token` from `spender` to the pool
+///
+/// Returns the amount of tokens the pool received, which is less than `amount` for tokens
+/// that take a fee on transfer
+fn transfer_in(
+    e: &Env,
+    token: &TokenClient,
+    spender: &Address,
+    amount: i128,
+    use_allowance: bool,
+) -> i128 {
+    let pool = e.current_contract_address();
+    let pre_balance = token.balance(&pool);
+    if use_allowance {
+        token.transfer_from(&pool, spender, &pool, &amount);
+    } else {
+        token.transfer(spender, &pool, &amount);
+    }
+    token.balance(&pool) - pre_balance
+}
+
+/// Account for the tokens the pool was sent but did not receive, so a token that takes a fee on
+/// transfer can't credit the user with more than the pool holds. The shortfall is burnt from the
+/// user's supply, then from their collateral. Any remainder came from a repayment, so it is added
+/// back to the user's liabilities.
+///
+/// ### Arguments
+/// * `pool` - The pool
+/// * `user` - The user the tokens were sent for
+/// * `shortfalls` - A map of asset to the amount of underlying tokens the pool did not receive
+///
+/// ### Panics
+/// If the user's positions are no longer healthy after the shortfall is accounted for
+fn apply_transfer_shortfalls(
+    e: &Env,
+    pool: &mut Pool,
+    user: &mut User,
+    shortfalls: &Map<Address, i128>,
+) {
+    if shortfalls.is_empty() {
+        return;
+    }
+    for (asset, shortfall) in shortfalls.iter() {
+        let mut reserve = pool.load_reserve(e, &asset, true);
+        let mut b_tokens = reserve.to_b_token_up(shortfall);
+        let supply_burnt = b_tokens.min(user.get_supply(reserve.index));
+        if supply_burnt > 0 {
+            user.remove_supply(e, &mut reserve, supply_burnt);
+            b_tokens -= supply_burnt;
+        }
+        let collateral_burnt = b_tokens.min(user.get_collateral(reserve.index));
+        if collateral_burnt > 0 {
+            user.remove_collateral(e, &mut reserve, collateral_burnt);
+            b_tokens -= collateral_burnt;
+        }
+        if b_tokens > 0 {
+            let d_tokens = reserve.to_d_token_up(reserve.to_asset_from_b_token(b_tokens));
+            if d_tokens > 0 {
+                user.add_liabilities(e, &mut reserve, d_tokens);
+            }
+        }
+        pool.cache_reserve(reserve);
+        PoolEvents::transfer_shortfall(e, asset, user.address.clone(), shortfall);
+    }
+    require_healthy_positions(e, pool, user, user.emode(e));
+}
+
+/// Net the transfers between the spender and "to", and transfer the tokens using the spender's
+/// allowance for the pool
+///
+/// Returns a map of asset to the amount of tokens the pool did not receive from the spender
+fn handle_transfer_with_allowance(
+    e: &Env,
+    actions: &Actions,
+    spender: &Address,
+    to: &Address,
+) -> Map<Address, i128> {
     // map of token -> amount
     // amount can be negative:
     // pool owes when amount > 0
@@ -158,49 +798,114 @@ fn handle_transfer_with_allowance(e: &Env, actions: &Actions, spender: &Address,
         );
     }
 
+    let mut shortfalls: Map<Address, i128> = Map::new(e);
     for (address, amount) in net_balances {
         let token = TokenClient::new(e, &address);
         if amount < 0 {
-            // transfer tokens from sender to pool
-            token.transfer_from(
-                &e.current_contract_address(),
-                spender,
-                &e.current_contract_address(),
-                &amount.abs(),
-            );
+            // transfer tokens from sender to pool. Native XLM is transferred directly from the
+            // authorized spender, so wallets don't need to approve the pool before supplying or
+            // repaying XLM
+            let use_allowance = !is_native_asset(e, &address);
+            let received = transfer_in(e, &token, spender, amount.abs(), use_allowance);
+            if received < amount.abs() {
+                shortfalls.set(address, amount.abs() - received);
+            }
         } else if amount > 0 {
             // transfer tokens from pool to "to"
             token.transfer(&e.current_contract_address(), to, &amount);
         }
     }
+    shortfalls
+}
+
+/// Transfer tokens to the pool from the spenders set by individual requests. The tokens are always
+/// transferred directly, so each request's spender must authorize the transfer.
+///
+/// ### Panics
+/// If the pool receives less than the amount of tokens sent by a spender, as the shortfall can't
+/// be attributed to the position the tokens were sent for
+fn handle_request_spender_transfers(e: &Env, actions: &Actions) {
+    for ((spender, asset), amount) in actions.request_spender_transfer.iter() {
+        let received = transfer_in(e, &TokenClient::new(e, &asset), &spender, amount, false);
+        if received < amount {
+            panic_with_error!(e, PoolError::TransferShortfall);
+        }
+    }
+}
+
+/// Transfer tokens from the pool to the "to" addresses set by individual requests
+fn handle_request_pool_transfers(e: &Env, actions: &Actions) {
+    for ((to, asset), amount) in actions.request_pool_transfer.iter() {
+        TokenClient::new(e, &asset).transfer(&e.current_contract_address(), &to, &amount);
+    }
 }
 
-fn handle_transfers(e: &Env, actions: &Actions, spender: &Address, to: &Address) {
+/// Transfer the tokens directly between the spender and "to"
+///
+/// Returns a map of asset to the amount of tokens the pool did not receive from the spender
+fn handle_transfers(
+    e: &Env,
+    actions: &Actions,
+    spender: &Address,
+    to: &Address,
+) -> Map<Address, i128> {
     // transfer tokens from sender to pool
+    let mut shortfalls: Map<Address, i128> = Map::new(e);
     for (address, amount) in actions.spender_transfer.iter() {
-        TokenClient::new(e, &address).transfer(spender, &e.current_contract_address(), &amount);
+        let received = transfer_in(e, &TokenClient::new(e, &address), spender, amount, false);
+        if received < amount {
+            shortfalls.set(address, amount - received);
+        }
     }
 
     // transfer tokens from pool to "to"
     for (address, amount) in actions.pool_transfer.iter() {
         TokenClient::new(e, &address).transfer(&e.current_contract_address(), to, &amount);
     }
+    shortfalls
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        storage::{self, PoolConfig},
+        pool::{execute_set_price_signers, price_attestation::attestation_payload},
+        storage::{self, FeeTier, PoolConfig, UserFeeVolume},
         testutils, RequestType,
     };
 
     use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
     use sep_40_oracle::testutils::Asset;
     use soroban_sdk::{
-        testutils::{Address as _, Ledger, LedgerInfo},
-        vec, Symbol,
+        contract, contractimpl,
+        testutils::{Address as _, Events, Ledger, LedgerInfo},
+        map, vec, BytesN, IntoVal, Symbol,
     };
 
+    /// A token that burns 1% of every transfer
+    #[contract]
+    struct MockFeeToken;
+
+    #[contractimpl]
+    impl MockFeeToken {
+        pub fn mint(e: Env, to: Address, amount: i128) {
+            let balance = Self::balance(e.clone(), to.clone());
+            e.storage().instance().set(&to, &(balance + amount));
+        }
+
+        pub fn balance(e: Env, id: Address) -> i128 {
+            e.storage().instance().get(&id).unwrap_or(0)
+        }
+
+        pub fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+            from.require_auth();
+            let from_balance = Self::balance(e.clone(), from.clone());
+            e.storage().instance().set(&from, &(from_balance - amount));
+            let to_balance = Self::balance(e.clone(), to.clone());
+            e.storage().instance().set(&to, &(to_balance + amount - amount / 100));
+        }
+    }
+
     #[test]
     fn test_submit() {
         let e = Env::default();
@@ -253,6 +958,11 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             e.mock_all_auths_allowing_non_root_auth();
@@ -265,16 +975,17 @@ mod tests {
                 &e,
                 Request {
                     request_type: RequestType::SupplyCollateral as u32,
-                    address: underlying_0,
+                    address: underlying_0.clone(),
                     amount: 15_0000000,
                 },
                 Request {
                     request_type: RequestType::Borrow as u32,
-                    address: underlying_1,
+                    address: underlying_1.clone(),
                     amount: 1_5000000,
                 },
             ];
-            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, false);
+            let result = execute_submit(&e, &samwise, &frodo, &merry, requests, false, None);
+            let positions = result.positions;
 
             assert_eq!(positions.liabilities.len(), 1);
             assert_eq!(positions.collateral.len(), 1);
@@ -282,6 +993,27 @@ mod tests {
             assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
             assert_eq!(positions.liabilities.get_unchecked(1), 1_4999983);
 
+            let mut pool_state = Pool::load(&e);
+            let reserve_0 = pool_state.load_reserve(&e, &underlying_0, false);
+            let reserve_1 = pool_state.load_reserve(&e, &underlying_1, false);
+            assert_eq!(result.results.len(), 2);
+            let supply_result = result.results.get_unchecked(0);
+            assert_eq!(supply_result.request_type, RequestType::SupplyCollateral as u32);
+            assert_eq!(supply_result.address, underlying_0);
+            assert_eq!(supply_result.tokens_in, 15_0000000);
+            assert_eq!(supply_result.tokens_out, 0);
+            assert_eq!(supply_result.b_tokens, 14_9999884);
+            assert_eq!(supply_result.d_tokens, 0);
+            assert_eq!(supply_result.rate, reserve_0.b_rate);
+            let borrow_result = result.results.get_unchecked(1);
+            assert_eq!(borrow_result.request_type, RequestType::Borrow as u32);
+            assert_eq!(borrow_result.address, underlying_1);
+            assert_eq!(borrow_result.tokens_in, 0);
+            assert_eq!(borrow_result.tokens_out, 1_5000000);
+            assert_eq!(borrow_result.b_tokens, 0);
+            assert_eq!(borrow_result.d_tokens, 1_4999983);
+            assert_eq!(borrow_result.rate, reserve_1.d_rate);
+
             assert_eq!(
                 underlying_0_client.balance(&pool),
                 pre_pool_balance_0 + 15_0000000
@@ -297,7 +1029,7 @@ mod tests {
     }
 
     #[test]
-    fn test_submit_use_allowance() {
+    fn test_submit_with_request_transfers() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -317,6 +1049,7 @@ mod tests {
         let samwise = Address::generate(&e);
         let frodo = Address::generate(&e);
         let merry = Address::generate(&e);
+        let pippin = Address::generate(&e);
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
@@ -328,7 +1061,8 @@ mod tests {
         let (reserve_config, reserve_data) = testutils::default_reserve_meta();
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
-        underlying_0_client.mint(&frodo, &15_0000000);
+        underlying_0_client.mint(&frodo, &16_0000000);
+        underlying_0_client.mint(&pippin, &15_0000000);
 
         oracle_client.set_data(
             &bombadil,
@@ -347,15 +1081,17 @@ mod tests {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
-            max_positions: 4,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             e.mock_all_auths_allowing_non_root_auth();
             storage::set_pool_config(&e, &pool_config);
 
-            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
-            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
-
             let requests = vec![
                 &e,
                 Request {
@@ -365,81 +1101,41 @@ mod tests {
                 },
                 Request {
                     request_type: RequestType::Borrow as u32,
-                    address: underlying_1,
-                    amount: 1_5000000,
+                    address: underlying_1.clone(),
+                    amount: 1_0000000,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_1.clone(),
+                    amount: 0_5000000,
                 },
             ];
-            underlying_0_client.approve(&frodo, &pool, &15_0000000, &e.ledger().sequence());
-            assert_eq!(underlying_0_client.allowance(&frodo, &pool), 15_0000000);
-
-            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, true);
-
-            assert_eq!(positions.liabilities.len(), 1);
-            assert_eq!(positions.collateral.len(), 1);
-            assert_eq!(positions.supply.len(), 0);
-            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
-            assert_eq!(positions.liabilities.get_unchecked(1), 1_4999983);
-
-            assert_eq!(
-                underlying_0_client.balance(&pool),
-                pre_pool_balance_0 + 15_0000000
-            );
-            assert_eq!(underlying_1_client.allowance(&frodo, &pool), 0);
-            assert_eq!(
-                underlying_1_client.balance(&pool),
-                pre_pool_balance_1 - 1_5000000
-            );
-
-            assert_eq!(underlying_0_client.balance(&frodo), 0);
-            assert_eq!(underlying_1_client.balance(&merry), 1_5000000);
-        });
-
-        underlying_0_client.mint(&frodo, &15_0000000);
-
-        e.as_contract(&pool, || {
-            e.mock_all_auths_allowing_non_root_auth();
-            storage::set_pool_config(&e, &pool_config);
-
-            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
-
-            let requests = vec![
+            let options = vec![
                 &e,
-                Request {
-                    request_type: RequestType::SupplyCollateral as u32,
-                    address: underlying_0.clone(),
-                    amount: 15_0000000,
+                RequestOptions {
+                    spender: Some(pippin.clone()),
+                    ..Default::default()
                 },
-                Request {
-                    request_type: RequestType::Borrow as u32,
-                    address: underlying_0,
-                    amount: 1_0000000,
+                RequestOptions {
+                    to: Some(pippin.clone()),
+                    ..Default::default()
                 },
+                RequestOptions::default(),
             ];
-            underlying_0_client.approve(&frodo, &pool, &14_0000000, &e.ledger().sequence());
-            assert_eq!(underlying_0_client.allowance(&frodo, &pool), 14_0000000);
-            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, true);
-
-            // new_allowance = old_allowance - (deposit - borrow)
-            assert_eq!(underlying_0_client.allowance(&frodo, &pool), 0);
-
-            assert_eq!(positions.liabilities.len(), 2);
-            assert_eq!(positions.collateral.len(), 1);
-            assert_eq!(positions.supply.len(), 0);
-
-            assert_eq!(positions.collateral.get_unchecked(0), 29_9999768);
-            assert_eq!(positions.liabilities.get_unchecked(1), 1_4999983);
-
-            assert_eq!(
-                underlying_0_client.balance(&pool),
-                pre_pool_balance_0 + 14_0000000
-            );
-
-            assert_eq!(underlying_0_client.balance(&frodo), 1_0000000);
+            let result =
+                execute_submit_with_options(&e, &samwise, &frodo, &merry, requests, options);
+            assert_eq!(result.positions.collateral.get_unchecked(0), 14_9999884);
+
+            // each request's tokens are exchanged with its own spender or "to"
+            assert_eq!(underlying_0_client.balance(&pippin), 0);
+            assert_eq!(underlying_0_client.balance(&frodo), 16_0000000);
+            assert_eq!(underlying_1_client.balance(&pippin), 1_0000000);
+            assert_eq!(underlying_1_client.balance(&merry), 0_5000000);
         });
     }
 
     #[test]
-    fn test_submit_use_allowance_over_repay() {
+    fn test_simulate_submit() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -470,7 +1166,7 @@ mod tests {
         let (reserve_config, reserve_data) = testutils::default_reserve_meta();
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
-        underlying_0_client.mint(&frodo, &15_0000000);
+        underlying_0_client.mint(&frodo, &16_0000000);
 
         oracle_client.set_data(
             &bombadil,
@@ -489,7 +1185,12 @@ mod tests {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
-            max_positions: 4,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             e.mock_all_auths_allowing_non_root_auth();
@@ -499,7 +1200,7 @@ mod tests {
                 &e,
                 Request {
                     request_type: RequestType::SupplyCollateral as u32,
-                    address: underlying_0,
+                    address: underlying_0.clone(),
                     amount: 15_0000000,
                 },
                 Request {
@@ -508,54 +1209,76 @@ mod tests {
                     amount: 1_5000000,
                 },
             ];
-            underlying_0_client.approve(&frodo, &pool, &15_0000000, &e.ledger().sequence());
-            assert_eq!(underlying_0_client.allowance(&frodo, &pool), 15_0000000);
+            let result = execute_simulate_submit(&e, &samwise, requests);
 
-            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, true);
+            assert_eq!(result.positions.collateral.get_unchecked(0), 14_9999884);
+            assert_eq!(result.positions.liabilities.get_unchecked(1), 1_4999983);
+            assert_eq!(result.results.len(), 2);
+            assert_eq!(result.spender_transfer.get_unchecked(underlying_0.clone()), 15_0000000);
+            assert_eq!(result.pool_transfer.get_unchecked(underlying_1.clone()), 1_5000000);
+            assert!(result.health_factor > 1_1200000 && result.health_factor < 1_1300000);
 
-            assert_eq!(positions.liabilities.len(), 1);
-            assert_eq!(positions.collateral.len(), 1);
-            assert_eq!(positions.supply.len(), 0);
-            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
-            assert_eq!(positions.liabilities.get_unchecked(1), 1_4999983);
+            // nothing is stored or transferred
+            let positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(storage::get_res_data(&e, &underlying_0).b_supply, reserve_data.b_supply);
+            assert_eq!(storage::get_res_data(&e, &underlying_1).d_supply, reserve_data.d_supply);
+            assert_eq!(storage::get_accrual_history(&e, &underlying_1).len(), 0);
+            assert!(storage::get_oracle_decimals(&e, &pool_config.oracle).is_none());
+            assert_eq!(underlying_0_client.balance(&frodo), 16_0000000);
+            assert_eq!(underlying_1_client.balance(&merry), 0);
+        });
+    }
 
-            underlying_1_client.mint(&frodo, &1_6000000);
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_simulate_submit_rejects_auctions() {
+        let e = Env::default();
+        e.mock_all_auths();
 
-            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
 
+        e.as_contract(&pool, || {
             let requests = vec![
                 &e,
                 Request {
-                    request_type: RequestType::Repay as u32,
-                    address: underlying_1,
-                    amount: 1_6000000,
+                    request_type: RequestType::FillUserLiquidationAuction as u32,
+                    address: frodo,
+                    amount: 50,
                 },
             ];
-            underlying_1_client.approve(&frodo, &pool, &1_5000001, &e.ledger().sequence());
-            assert_eq!(underlying_1_client.allowance(&frodo, &pool), 1_5000001);
-            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, true);
-
-            // new_allowance = old_allowance - repay
-            assert_eq!(underlying_1_client.allowance(&frodo, &pool), 0);
-
-            assert_eq!(positions.liabilities.len(), 0);
-            assert_eq!(positions.collateral.len(), 1);
-            assert_eq!(positions.supply.len(), 0);
+            execute_simulate_submit(&e, &samwise, requests);
+        });
+    }
 
-            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_simulate_submit_rejects_fill_to_target() {
+        let e = Env::default();
+        e.mock_all_auths();
 
-            assert_eq!(
-                underlying_1_client.balance(&pool),
-                pre_pool_balance_1 + 1_5000001
-            );
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
 
-            assert_eq!(underlying_1_client.balance(&frodo), 999999);
+        e.as_contract(&pool, || {
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::FillUserLiquidationAuctionToTarget as u32,
+                    address: frodo,
+                    amount: 1_1000000,
+                },
+            ];
+            execute_simulate_submit(&e, &samwise, requests);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #9)")]
-    fn test_submit_use_allowance_no_allowance() {
+    fn test_submit_sub_account() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -573,8 +1296,6 @@ mod tests {
 
         let bombadil = Address::generate(&e);
         let samwise = Address::generate(&e);
-        let frodo = Address::generate(&e);
-        let merry = Address::generate(&e);
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
@@ -586,7 +1307,7 @@ mod tests {
         let (reserve_config, reserve_data) = testutils::default_reserve_meta();
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
-        underlying_0_client.mint(&frodo, &16_0000000);
+        underlying_0_client.mint(&samwise, &16_0000000);
 
         oracle_client.set_data(
             &bombadil,
@@ -606,11 +1327,16 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
-
         e.as_contract(&pool, || {
             e.mock_all_auths_allowing_non_root_auth();
             storage::set_pool_config(&e, &pool_config);
+
             let requests = vec![
                 &e,
                 Request {
@@ -624,12 +1350,31 @@ mod tests {
                     amount: 1_5000000,
                 },
             ];
+            let account = RequestOptions {
+                account_id: 2,
+                ..Default::default()
+            };
+            let options = vec![&e, account.clone(), account];
+            let positions =
+                execute_submit_with_options(&e, &samwise, &samwise, &samwise, requests, options)
+                    .positions;
+
+            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
+            assert_eq!(positions.liabilities.get_unchecked(1), 1_4999983);
 
-            execute_submit(&e, &samwise, &frodo, &merry, requests, true);
+            let account_positions = storage::get_account_positions(&e, &samwise, 2);
+            assert_eq!(account_positions.collateral.get_unchecked(0), 14_9999884);
+            assert_eq!(account_positions.liabilities.get_unchecked(1), 1_4999983);
+
+            let main_positions = storage::get_user_positions(&e, &samwise);
+            assert_eq!(main_positions.collateral.len(), 0);
+            assert_eq!(main_positions.liabilities.len(), 0);
         });
     }
+
     #[test]
-    fn test_submit_no_liabilities_does_not_load_oracle() {
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_submit_mixed_sub_accounts() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -647,34 +1392,47 @@ mod tests {
 
         let bombadil = Address::generate(&e);
         let samwise = Address::generate(&e);
-        let frodo = Address::generate(&e);
         let pool = testutils::create_pool(&e);
-        let oracle = Address::generate(&e); // will fail if executed against
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
         let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
         let (reserve_config, reserve_data) = testutils::default_reserve_meta();
         testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
 
-        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
         let (reserve_config, reserve_data) = testutils::default_reserve_meta();
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
-        underlying_0_client.mint(&frodo, &16_0000000);
-        underlying_1_client.mint(&frodo, &10_0000000);
+        underlying_0_client.mint(&samwise, &16_0000000);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
 
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             e.mock_all_auths_allowing_non_root_auth();
             storage::set_pool_config(&e, &pool_config);
 
-            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
-            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
-
             let requests = vec![
                 &e,
                 Request {
@@ -682,49 +1440,45 @@ mod tests {
                     address: underlying_0,
                     amount: 15_0000000,
                 },
-                // force check_health to true
                 Request {
                     request_type: RequestType::Borrow as u32,
-                    address: underlying_1.clone(),
+                    address: underlying_1,
                     amount: 1_5000000,
                 },
-                Request {
-                    request_type: RequestType::Repay as u32,
-                    address: underlying_1,
-                    amount: 1_5000001,
+            ];
+            let options = vec![
+                &e,
+                RequestOptions {
+                    account_id: 2,
+                    ..Default::default()
                 },
+                RequestOptions::default(),
             ];
-            let positions = execute_submit(&e, &samwise, &frodo, &frodo, requests, false);
-
-            assert_eq!(positions.liabilities.len(), 0);
-            assert_eq!(positions.collateral.len(), 1);
-            assert_eq!(positions.supply.len(), 0);
-            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
-
-            assert_eq!(
-                underlying_0_client.balance(&pool),
-                pre_pool_balance_0 + 15_0000000
-            );
-            assert_eq!(
-                underlying_1_client.balance(&pool),
-                pre_pool_balance_1 + 1 // repayment rounded against user
-            );
-
-            assert_eq!(underlying_0_client.balance(&frodo), 1_0000000);
-            assert_eq!(underlying_1_client.balance(&frodo), 10_0000000 - 1);
+            execute_submit_with_options(&e, &samwise, &samwise, &samwise, requests, options);
         });
     }
 
     #[test]
     #[should_panic(expected = "Error(Contract, #1205)")]
-    fn test_submit_requires_healhty() {
+    fn test_submit_with_prices_uses_attested_price() {
         let e = Env::default();
-        e.mock_all_auths();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
 
         let bombadil = Address::generate(&e);
         let samwise = Address::generate(&e);
         let frodo = Address::generate(&e);
-        let merry = Address::generate(&e);
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
@@ -751,24 +1505,36 @@ mod tests {
         );
         oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
 
-        e.ledger().set(LedgerInfo {
-            timestamp: 600,
-            protocol_version: 22,
-            sequence_number: 1234,
-            network_id: Default::default(),
-            base_reserve: 10,
-            min_temp_entry_ttl: 10,
-            min_persistent_entry_ttl: 10,
-            max_entry_ttl: 3110400,
-        });
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let signer = BytesN::from_array(&e, &key.verifying_key().to_bytes());
+
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
+            e.mock_all_auths_allowing_non_root_auth();
             storage::set_pool_config(&e, &pool_config);
+            execute_set_price_signers(&e, &vec![&e, signer.clone()], 300);
+
+            // attested price is double the oracle price, so the borrow is no longer healthy
+            let price = 10_0000000;
+            let payload = attestation_payload(&e, &underlying_1, price, 500);
+            let message: std::vec::Vec<u8> = payload.iter().collect();
+            let attestation = PriceAttestation {
+                asset: underlying_1.clone(),
+                price,
+                timestamp: 500,
+                signer,
+                signature: BytesN::from_array(&e, &key.sign(&message).to_bytes()),
+            };
 
             let requests = vec![
                 &e,
@@ -780,16 +1546,23 @@ mod tests {
                 Request {
                     request_type: RequestType::Borrow as u32,
                     address: underlying_1,
-                    amount: 1_7500000,
+                    amount: 1_5000000,
                 },
             ];
-            execute_submit(&e, &samwise, &frodo, &merry, requests, false);
+            execute_submit_with_prices(
+                &e,
+                &samwise,
+                &frodo,
+                &frodo,
+                requests,
+                false,
+                &vec![&e, attestation],
+            );
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1200)")]
-    fn test_submit_from_is_not_self() {
+    fn test_submit_batch() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -807,6 +1580,9 @@ mod tests {
 
         let bombadil = Address::generate(&e);
         let samwise = Address::generate(&e);
+        let pippin = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
@@ -814,42 +1590,164 @@ mod tests {
         let (reserve_config, reserve_data) = testutils::default_reserve_meta();
         testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
 
-        underlying_0_client.mint(&samwise, &16_0000000);
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&frodo, &16_0000000);
+        underlying_0_client.mint(&pippin, &15_0000000);
 
         oracle_client.set_data(
             &bombadil,
             &Asset::Other(Symbol::new(&e, "USD")),
-            &vec![&e, Asset::Stellar(underlying_0.clone())],
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
             &7,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
 
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             e.mock_all_auths_allowing_non_root_auth();
             storage::set_pool_config(&e, &pool_config);
 
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+
+            let batch = vec![
+                &e,
+                (
+                    samwise.clone(),
+                    frodo.clone(),
+                    merry.clone(),
+                    vec![
+                        &e,
+                        Request {
+                            request_type: RequestType::SupplyCollateral as u32,
+                            address: underlying_0.clone(),
+                            amount: 15_0000000,
+                        },
+                        Request {
+                            request_type: RequestType::Borrow as u32,
+                            address: underlying_1.clone(),
+                            amount: 1_5000000,
+                        },
+                    ],
+                ),
+                (
+                    pippin.clone(),
+                    pippin.clone(),
+                    pippin.clone(),
+                    vec![
+                        &e,
+                        Request {
+                            request_type: RequestType::Supply as u32,
+                            address: underlying_0.clone(),
+                            amount: 15_0000000,
+                        },
+                    ],
+                ),
+            ];
+            let positions = execute_submit_batch(&e, batch);
+
+            assert_eq!(positions.len(), 2);
+            let samwise_positions = positions.get_unchecked(0);
+            assert_eq!(samwise_positions.collateral.get_unchecked(0), 14_9999884);
+            assert_eq!(samwise_positions.liabilities.get_unchecked(1), 1_4999983);
+            let pippin_positions = positions.get_unchecked(1);
+            assert_eq!(pippin_positions.supply.get_unchecked(0), 14_9999884);
+            assert_eq!(pippin_positions.liabilities.len(), 0);
+
+            assert_eq!(
+                storage::get_user_positions(&e, &samwise).liabilities.get_unchecked(1),
+                1_4999983
+            );
+            assert_eq!(
+                storage::get_user_positions(&e, &pippin).supply.get_unchecked(0),
+                14_9999884
+            );
+
+            assert_eq!(
+                underlying_0_client.balance(&pool),
+                pre_pool_balance_0 + 30_0000000
+            );
+            assert_eq!(
+                underlying_1_client.balance(&pool),
+                pre_pool_balance_1 - 1_5000000
+            );
+            // each user's tokens move through their own spender and "to"
+            assert_eq!(underlying_0_client.balance(&frodo), 1_0000000);
+            assert_eq!(underlying_0_client.balance(&pippin), 0);
+            assert_eq!(underlying_1_client.balance(&merry), 1_5000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_submit_batch_duplicate_user() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, _) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&frodo, &30_0000000);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
             let requests = vec![
                 &e,
                 Request {
-                    request_type: RequestType::SupplyCollateral as u32,
-                    address: underlying_0,
+                    request_type: RequestType::Supply as u32,
+                    address: underlying_0.clone(),
                     amount: 15_0000000,
                 },
             ];
-            execute_submit(&e, &pool, &samwise, &samwise, requests, false);
+            let batch = vec![
+                &e,
+                (samwise.clone(), frodo.clone(), frodo.clone(), requests.clone()),
+                (samwise.clone(), frodo.clone(), frodo.clone(), requests),
+            ];
+            execute_submit_batch(&e, batch);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1200)")]
-    fn test_submit_spender_is_not_self() {
+    fn test_submit_use_allowance() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -867,6 +1765,8 @@ mod tests {
 
         let bombadil = Address::generate(&e);
         let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
@@ -874,42 +1774,1446 @@ mod tests {
         let (reserve_config, reserve_data) = testutils::default_reserve_meta();
         testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
 
-        underlying_0_client.mint(&samwise, &16_0000000);
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&frodo, &15_0000000);
 
         oracle_client.set_data(
             &bombadil,
             &Asset::Other(Symbol::new(&e, "USD")),
-            &vec![&e, Asset::Stellar(underlying_0.clone())],
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
             &7,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
 
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
-            max_positions: 2,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             e.mock_all_auths_allowing_non_root_auth();
             storage::set_pool_config(&e, &pool_config);
 
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+
             let requests = vec![
                 &e,
                 Request {
                     request_type: RequestType::SupplyCollateral as u32,
-                    address: underlying_0,
+                    address: underlying_0.clone(),
+                    amount: 15_0000000,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_1,
+                    amount: 1_5000000,
+                },
+            ];
+            underlying_0_client.approve(&frodo, &pool, &15_0000000, &e.ledger().sequence());
+            assert_eq!(underlying_0_client.allowance(&frodo, &pool), 15_0000000);
+
+            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, true, None)
+                .positions;
+
+            assert_eq!(positions.liabilities.len(), 1);
+            assert_eq!(positions.collateral.len(), 1);
+            assert_eq!(positions.supply.len(), 0);
+            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
+            assert_eq!(positions.liabilities.get_unchecked(1), 1_4999983);
+
+            assert_eq!(
+                underlying_0_client.balance(&pool),
+                pre_pool_balance_0 + 15_0000000
+            );
+            assert_eq!(underlying_1_client.allowance(&frodo, &pool), 0);
+            assert_eq!(
+                underlying_1_client.balance(&pool),
+                pre_pool_balance_1 - 1_5000000
+            );
+
+            assert_eq!(underlying_0_client.balance(&frodo), 0);
+            assert_eq!(underlying_1_client.balance(&merry), 1_5000000);
+        });
+
+        underlying_0_client.mint(&frodo, &15_0000000);
+
+        e.as_contract(&pool, || {
+            e.mock_all_auths_allowing_non_root_auth();
+            storage::set_pool_config(&e, &pool_config);
+
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0.clone(),
                     amount: 15_0000000,
                 },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_0,
+                    amount: 1_0000000,
+                },
             ];
-            execute_submit(&e, &samwise, &pool, &samwise, requests, false);
+            underlying_0_client.approve(&frodo, &pool, &14_0000000, &e.ledger().sequence());
+            assert_eq!(underlying_0_client.allowance(&frodo, &pool), 14_0000000);
+            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, true, None)
+                .positions;
+
+            // new_allowance = old_allowance - (deposit - borrow)
+            assert_eq!(underlying_0_client.allowance(&frodo, &pool), 0);
+
+            assert_eq!(positions.liabilities.len(), 2);
+            assert_eq!(positions.collateral.len(), 1);
+            assert_eq!(positions.supply.len(), 0);
+
+            assert_eq!(positions.collateral.get_unchecked(0), 29_9999768);
+            assert_eq!(positions.liabilities.get_unchecked(1), 1_4999983);
+
+            assert_eq!(
+                underlying_0_client.balance(&pool),
+                pre_pool_balance_0 + 14_0000000
+            );
+
+            assert_eq!(underlying_0_client.balance(&frodo), 1_0000000);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1200)")]
-    fn test_submit_to_is_not_self() {
+    fn test_submit_use_allowance_over_repay() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&frodo, &15_0000000);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            e.mock_all_auths_allowing_non_root_auth();
+            storage::set_pool_config(&e, &pool_config);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0,
+                    amount: 15_0000000,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_1.clone(),
+                    amount: 1_5000000,
+                },
+            ];
+            underlying_0_client.approve(&frodo, &pool, &15_0000000, &e.ledger().sequence());
+            assert_eq!(underlying_0_client.allowance(&frodo, &pool), 15_0000000);
+
+            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, true, None)
+                .positions;
+
+            assert_eq!(positions.liabilities.len(), 1);
+            assert_eq!(positions.collateral.len(), 1);
+            assert_eq!(positions.supply.len(), 0);
+            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
+            assert_eq!(positions.liabilities.get_unchecked(1), 1_4999983);
+
+            underlying_1_client.mint(&frodo, &1_6000000);
+
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Repay as u32,
+                    address: underlying_1,
+                    amount: 1_6000000,
+                },
+            ];
+            underlying_1_client.approve(&frodo, &pool, &1_5000001, &e.ledger().sequence());
+            assert_eq!(underlying_1_client.allowance(&frodo, &pool), 1_5000001);
+            let positions = execute_submit(&e, &samwise, &frodo, &merry, requests, true, None)
+                .positions;
+
+            // new_allowance = old_allowance - repay
+            assert_eq!(underlying_1_client.allowance(&frodo, &pool), 0);
+
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(positions.collateral.len(), 1);
+            assert_eq!(positions.supply.len(), 0);
+
+            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
+
+            assert_eq!(
+                underlying_1_client.balance(&pool),
+                pre_pool_balance_1 + 1_5000001
+            );
+
+            assert_eq!(underlying_1_client.balance(&frodo), 999999);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #9)")]
+    fn test_submit_use_allowance_no_allowance() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&frodo, &16_0000000);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+
+        e.as_contract(&pool, || {
+            e.mock_all_auths_allowing_non_root_auth();
+            storage::set_pool_config(&e, &pool_config);
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0,
+                    amount: 15_0000000,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_1,
+                    amount: 1_5000000,
+                },
+            ];
+
+            execute_submit(&e, &samwise, &frodo, &merry, requests, true, None);
+        });
+    }
+    #[test]
+    fn test_submit_no_liabilities_does_not_load_oracle() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e); // will fail if executed against
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&frodo, &16_0000000);
+        underlying_1_client.mint(&frodo, &10_0000000);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            e.mock_all_auths_allowing_non_root_auth();
+            storage::set_pool_config(&e, &pool_config);
+
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0,
+                    amount: 15_0000000,
+                },
+                // force check_health to true
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_1.clone(),
+                    amount: 1_5000000,
+                },
+                Request {
+                    request_type: RequestType::Repay as u32,
+                    address: underlying_1,
+                    amount: 1_5000001,
+                },
+            ];
+            let positions = execute_submit(&e, &samwise, &frodo, &frodo, requests, false, None)
+                .positions;
+
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(positions.collateral.len(), 1);
+            assert_eq!(positions.supply.len(), 0);
+            assert_eq!(positions.collateral.get_unchecked(0), 14_9999884);
+
+            assert_eq!(
+                underlying_0_client.balance(&pool),
+                pre_pool_balance_0 + 15_0000000
+            );
+            assert_eq!(
+                underlying_1_client.balance(&pool),
+                pre_pool_balance_1 + 1 // repayment rounded against user
+            );
+
+            assert_eq!(underlying_0_client.balance(&frodo), 1_0000000);
+            assert_eq!(underlying_1_client.balance(&frodo), 10_0000000 - 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1205)")]
+    fn test_submit_requires_healhty() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&frodo, &16_0000000);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0,
+                    amount: 15_0000000,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_1,
+                    amount: 1_7500000,
+                },
+            ];
+            execute_submit(&e, &samwise, &frodo, &merry, requests, false, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1239)")]
+    fn test_submit_over_max_leverage() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&frodo, &16_0000000);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 2_0000000,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            // health factor is 1.125, but leverage is (15 + 7.5) / (15 - 7.5) = 3x
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0,
+                    amount: 15_0000000,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: underlying_1,
+                    amount: 1_5000000,
+                },
+            ];
+            execute_submit(&e, &samwise, &frodo, &merry, requests, false, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_submit_from_is_not_self() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&samwise, &16_0000000);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(underlying_0.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            e.mock_all_auths_allowing_non_root_auth();
+            storage::set_pool_config(&e, &pool_config);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0,
+                    amount: 15_0000000,
+                },
+            ];
+            execute_submit(&e, &pool, &samwise, &samwise, requests, false, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_submit_spender_is_not_self() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&samwise, &16_0000000);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(underlying_0.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            e.mock_all_auths_allowing_non_root_auth();
+            storage::set_pool_config(&e, &pool_config);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0,
+                    amount: 15_0000000,
+                },
+            ];
+            execute_submit(&e, &samwise, &pool, &samwise, requests, false, None);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_submit_to_is_not_self() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        underlying_0_client.mint(&samwise, &16_0000000);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(underlying_0.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            e.mock_all_auths_allowing_non_root_auth();
+            storage::set_pool_config(&e, &pool_config);
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_0,
+                    amount: 15_0000000,
+                },
+            ];
+            execute_submit(&e, &samwise, &samwise, &pool, requests, false, None);
+        });
+    }
+
+    /***** submit_with_flash_loan *****/
+
+    #[test]
+    fn test_submit_with_flash_loan() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.max_util = 9500000;
+        reserve_data.b_supply = 100_0000000;
+        reserve_data.d_supply = 50_0000000;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            underlying_1_client.mint(&samwise, &25_0000000);
+            underlying_1_client.approve(&samwise, &pool, &100_0000000, &10000);
+
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+
+            // pool has 100 supplied and 50 borrowed for asset_0
+            // -> max util is 95%
+            let flash_loan: FlashLoan = FlashLoan {
+                contract: flash_loan_receiver,
+                asset: underlying_0,
+                amount: 25_0000000,
+            };
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_1,
+                    amount: 25_0000000,
+                },
+            ];
+            let positions = execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
+
+            assert_eq!(positions.liabilities.len(), 1);
+            assert_eq!(positions.collateral.len(), 1);
+            assert_eq!(positions.supply.len(), 0);
+            assert_eq!(positions.collateral.get_unchecked(1), 249999807);
+            // actual is 24.999979375 - rounds up
+            assert_eq!(positions.liabilities.get_unchecked(0), 249999794);
+
+            assert_eq!(
+                underlying_0_client.balance(&pool),
+                pre_pool_balance_0 - 25_0000000
+            );
+            assert_eq!(
+                underlying_1_client.balance(&pool),
+                pre_pool_balance_1 + 25_0000000
+            );
+
+            assert_eq!(underlying_0_client.balance(&samwise), 25_0000000);
+            assert_eq!(underlying_1_client.balance(&samwise), 0);
+
+            // check allowance is used
+            assert_eq!(
+                underlying_1_client.allowance(&samwise, &pool),
+                100_0000000 - 25_0000000
+            );
+        });
+    }
+
+    #[test]
+    fn test_submit_with_flash_loan_charges_fee() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.max_util = 9500000;
+        reserve_data.b_supply = 100_0000000;
+        reserve_data.d_supply = 50_0000000;
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0_0010000,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            underlying_1_client.mint(&samwise, &25_0000000);
+            underlying_1_client.approve(&samwise, &pool, &100_0000000, &10000);
+            underlying_0_client.approve(&samwise, &pool, &100_0000000, &10000);
+
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_reserve_data_0 = storage::get_res_data(&e, &underlying_0);
+
+            let flash_loan: FlashLoan = FlashLoan {
+                contract: flash_loan_receiver,
+                asset: underlying_0.clone(),
+                amount: 25_0000000,
+            };
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_1,
+                    amount: 25_0000000,
+                },
+            ];
+            execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
+
+            // fee of 0.1% is paid by the user and credited to the backstop
+            assert_eq!(underlying_0_client.balance(&samwise), 25_0000000 - 250000);
+            assert_eq!(
+                underlying_0_client.balance(&pool),
+                pre_pool_balance_0 - 25_0000000 + 250000
+            );
+            let reserve_data_0 = storage::get_res_data(&e, &underlying_0);
+            assert_eq!(
+                reserve_data_0.backstop_credit,
+                pre_reserve_data_0.backstop_credit + 250000
+            );
+            assert_eq!(reserve_data_0.b_rate, pre_reserve_data_0.b_rate);
+        });
+    }
+
+    #[test]
+    fn test_submit_with_flash_loan_applies_fee_tier() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.max_util = 9500000;
+        reserve_data.b_supply = 100_0000000;
+        reserve_data.d_supply = 50_0000000;
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0_0010000,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_fee_tiers(
+                &e,
+                &vec![
+                    &e,
+                    FeeTier {
+                        min_count: 2,
+                        min_volume: 10_0000000_0000000,
+                        discount: 0_4000000,
+                    },
+                ],
+            );
+            storage::set_user_fee_volume(
+                &e,
+                &samwise,
+                &UserFeeVolume {
+                    epoch: 0,
+                    count: 2,
+                    volume: 10_0000000_0000000,
+                },
+            );
+
+            underlying_1_client.mint(&samwise, &25_0000000);
+            underlying_1_client.approve(&samwise, &pool, &100_0000000, &10000);
+            underlying_0_client.approve(&samwise, &pool, &100_0000000, &10000);
+
+            let pre_reserve_data_0 = storage::get_res_data(&e, &underlying_0);
+
+            let flash_loan: FlashLoan = FlashLoan {
+                contract: flash_loan_receiver,
+                asset: underlying_0.clone(),
+                amount: 25_0000000,
+            };
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_1,
+                    amount: 25_0000000,
+                },
+            ];
+            execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
+
+            // fee of 0.06% is paid after the 40% tier discount
+            assert_eq!(underlying_0_client.balance(&samwise), 25_0000000 - 150000);
+            let reserve_data_0 = storage::get_res_data(&e, &underlying_0);
+            assert_eq!(
+                reserve_data_0.backstop_credit,
+                pre_reserve_data_0.backstop_credit + 150000
+            );
+
+            // the flash loan is added to the user's volume for the epoch
+            let fee_volume = storage::get_user_fee_volume(&e, &samwise);
+            assert_eq!(fee_volume.epoch, 0);
+            assert_eq!(fee_volume.count, 3);
+            assert_eq!(fee_volume.volume, 35_0000000_0000000);
+        });
+    }
+
+    #[test]
+    fn test_submit_with_flash_loans() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.max_util = 9500000;
+        reserve_data.b_supply = 100_0000000;
+        reserve_data.d_supply = 50_0000000;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            underlying_1_client.mint(&samwise, &25_0000000);
+            underlying_1_client.approve(&samwise, &pool, &100_0000000, &10000);
+
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+            let pre_d_supply_0 = storage::get_res_data(&e, &underlying_0).d_supply;
+            let pre_d_supply_1 = storage::get_res_data(&e, &underlying_1).d_supply;
+
+            let flash_loans = vec![
+                &e,
+                FlashLoan {
+                    contract: flash_loan_receiver.clone(),
+                    asset: underlying_0.clone(),
+                    amount: 25_0000000,
+                },
+                FlashLoan {
+                    contract: flash_loan_receiver,
+                    asset: underlying_1.clone(),
+                    amount: 2_0000000,
+                },
+            ];
+
+            // supply the flash loaned asset_1 as collateral along with the user's balance
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: underlying_1.clone(),
+                    amount: 27_0000000,
+                },
+            ];
+            let positions = execute_submit_with_flash_loans(&e, &samwise, flash_loans, requests);
+
+            assert_eq!(positions.liabilities.len(), 2);
+            assert_eq!(positions.collateral.len(), 1);
+            assert_eq!(positions.supply.len(), 0);
+            assert_eq!(positions.liabilities.get_unchecked(0), 249999794);
+
+            // liabilities are tracked per reserve
+            assert_eq!(
+                storage::get_res_data(&e, &underlying_0).d_supply,
+                pre_d_supply_0 + positions.liabilities.get_unchecked(0)
+            );
+            assert_eq!(
+                storage::get_res_data(&e, &underlying_1).d_supply,
+                pre_d_supply_1 + positions.liabilities.get_unchecked(1)
+            );
+
+            assert_eq!(
+                underlying_0_client.balance(&pool),
+                pre_pool_balance_0 - 25_0000000
+            );
+            assert_eq!(
+                underlying_1_client.balance(&pool),
+                pre_pool_balance_1 + 25_0000000
+            );
+
+            assert_eq!(underlying_0_client.balance(&samwise), 25_0000000);
+            assert_eq!(underlying_1_client.balance(&samwise), 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1200)")]
+    fn test_submit_with_flash_loans_requires_flash_loan() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.as_contract(&pool, || {
+            execute_submit_with_flash_loans(&e, &samwise, vec![&e], vec![&e]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1234)")]
+    fn test_submit_with_flash_loan_paused() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let flash_loan = FlashLoan {
+            contract: Address::generate(&e),
+            asset: Address::generate(&e),
+            amount: 1_0000000,
+        };
+
+        e.as_contract(&pool, || {
+            storage::set_paused_actions(&e, &PAUSE_FLASH_LOAN);
+            execute_submit_with_flash_loan(&e, &samwise, flash_loan, vec![&e]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1206)")]
+    fn test_submit_with_flash_loan_pool_on_ice() {
+        let e = Env::default();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let flash_loan = FlashLoan {
+            contract: Address::generate(&e),
+            asset: Address::generate(&e),
+            amount: 1_0000000,
+        };
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 2,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            execute_submit_with_flash_loan(&e, &samwise, flash_loan, vec![&e]);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1234)")]
+    fn test_submit_with_flash_loan_borrow_paused() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle: Address::generate(&e),
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let flash_loan = FlashLoan {
+            contract: flash_loan_receiver,
+            asset: underlying_0,
+            amount: 1_0000000,
+        };
+
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_paused_actions(&e, &crate::constants::PAUSE_BORROW);
+            execute_submit_with_flash_loan(&e, &samwise, flash_loan, vec![&e]);
+        });
+    }
+
+    #[test]
+    fn test_submit_with_flash_loan_process_flash_loan_first() {
+        let e = Env::default();
+        e.cost_estimate().budget().reset_unlimited();
+        e.mock_all_auths_allowing_non_root_auth();
+
+        e.ledger().set(LedgerInfo {
+            timestamp: 600,
+            protocol_version: 22,
+            sequence_number: 1234,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.max_util = 9500000;
+        reserve_data.b_supply = 100_0000000;
+        reserve_data.d_supply = 50_0000000;
+        testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
+
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+
+            underlying_0_client.mint(&samwise, &1_0000000);
+            underlying_0_client.approve(&samwise, &pool, &100_0000000, &10000);
+
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+
+            // pool has 100 supplied and 50 borrowed for asset_0
+            // -> max util is 95%
+            let flash_loan: FlashLoan = FlashLoan {
+                contract: flash_loan_receiver,
+                asset: underlying_0.clone(),
+                amount: 25_0000000,
+            };
+
+            let requests = vec![
+                &e,
+                Request {
+                    request_type: RequestType::Repay as u32,
+                    address: underlying_0,
+                    amount: 25_0000010,
+                },
+            ];
+            let positions = execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
+
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.supply.len(), 0);
+
+            assert_eq!(underlying_0_client.balance(&pool), pre_pool_balance_0 + 1,);
+            assert_eq!(underlying_1_client.balance(&pool), pre_pool_balance_1,);
+
+            // rounding causes 1 stroops to be lost
+            assert_eq!(underlying_0_client.balance(&samwise), 0_9999999);
+            assert_eq!(underlying_1_client.balance(&samwise), 0);
+
+            // check allowance is used
+            assert_eq!(
+                underlying_0_client.allowance(&samwise, &pool),
+                100_0000000 - 25_0000001
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1205)")]
+    fn test_submit_with_flash_loan_checks_health() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -930,47 +3234,72 @@ mod tests {
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
-        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
-        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
+        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_config.max_util = 9500000;
+        reserve_data.b_supply = 100_0000000;
+        reserve_data.d_supply = 50_0000000;
         testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
 
-        underlying_0_client.mint(&samwise, &16_0000000);
+        let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
         oracle_client.set_data(
             &bombadil,
             &Asset::Other(Symbol::new(&e, "USD")),
-            &vec![&e, Asset::Stellar(underlying_0.clone())],
+            &vec![
+                &e,
+                Asset::Stellar(underlying_0.clone()),
+                Asset::Stellar(underlying_1.clone()),
+            ],
             &7,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
 
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
-            max_positions: 2,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
-            e.mock_all_auths_allowing_non_root_auth();
             storage::set_pool_config(&e, &pool_config);
 
+            underlying_1_client.mint(&samwise, &25_0000000);
+            underlying_1_client.approve(&samwise, &pool, &100_0000000, &10000);
+
+            // pool has 100 supplied and 50 borrowed for asset_0
+            // -> max util is 95%
+            let flash_loan: FlashLoan = FlashLoan {
+                contract: flash_loan_receiver,
+                asset: underlying_0,
+                amount: 25_0000000,
+            };
+
             let requests = vec![
                 &e,
                 Request {
                     request_type: RequestType::SupplyCollateral as u32,
-                    address: underlying_0,
-                    amount: 15_0000000,
+                    address: underlying_1,
+                    amount: 8_0000000,
                 },
             ];
-            execute_submit(&e, &samwise, &samwise, &pool, requests, false);
+            execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
         });
     }
 
-    /***** submit_with_flash_loan *****/
-
     #[test]
-    fn test_submit_with_flash_loan() {
+    #[should_panic(expected = "Error(Contract, #1207)")]
+    fn test_submit_with_flash_loan_checks_max_util() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -993,7 +3322,7 @@ mod tests {
 
         let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
 
-        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
         let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
         reserve_config.max_util = 9500000;
         reserve_data.b_supply = 100_0000000;
@@ -1022,22 +3351,24 @@ mod tests {
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
 
-            underlying_1_client.mint(&samwise, &25_0000000);
+            underlying_1_client.mint(&samwise, &50_0000000);
             underlying_1_client.approve(&samwise, &pool, &100_0000000, &10000);
 
-            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
-            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
-
             // pool has 100 supplied and 50 borrowed for asset_0
             // -> max util is 95%
             let flash_loan: FlashLoan = FlashLoan {
                 contract: flash_loan_receiver,
                 asset: underlying_0,
-                amount: 25_0000000,
+                amount: 46_0000000,
             };
 
             let requests = vec![
@@ -1045,40 +3376,15 @@ mod tests {
                 Request {
                     request_type: RequestType::SupplyCollateral as u32,
                     address: underlying_1,
-                    amount: 25_0000000,
+                    amount: 50_0000000,
                 },
             ];
-            let positions = execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
-
-            assert_eq!(positions.liabilities.len(), 1);
-            assert_eq!(positions.collateral.len(), 1);
-            assert_eq!(positions.supply.len(), 0);
-            assert_eq!(positions.collateral.get_unchecked(1), 249999807);
-            // actual is 24.999979375 - rounds up
-            assert_eq!(positions.liabilities.get_unchecked(0), 249999794);
-
-            assert_eq!(
-                underlying_0_client.balance(&pool),
-                pre_pool_balance_0 - 25_0000000
-            );
-            assert_eq!(
-                underlying_1_client.balance(&pool),
-                pre_pool_balance_1 + 25_0000000
-            );
-
-            assert_eq!(underlying_0_client.balance(&samwise), 25_0000000);
-            assert_eq!(underlying_1_client.balance(&samwise), 0);
-
-            // check allowance is used
-            assert_eq!(
-                underlying_1_client.allowance(&samwise, &pool),
-                100_0000000 - 25_0000000
-            );
+            execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
         });
     }
 
     #[test]
-    fn test_submit_with_flash_loan_process_flash_loan_first() {
+    fn test_submit_with_collateral_swap() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -1099,17 +3405,16 @@ mod tests {
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
-        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+        let (swap_adapter, _) = testutils::create_swap_adapter(&e);
 
         let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
-        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
-        reserve_config.max_util = 9500000;
-        reserve_data.b_supply = 100_0000000;
-        reserve_data.d_supply = 50_0000000;
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
         testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
 
         let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
-        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
         oracle_client.set_data(
@@ -1123,63 +3428,59 @@ mod tests {
             &7,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 1_0000000]);
 
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e, (1, 10_0000000)],
+            collateral: map![&e, (0, 50_0000000)],
+            supply: map![&e],
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
 
-            underlying_0_client.mint(&samwise, &1_0000000);
-            underlying_0_client.approve(&samwise, &pool, &100_0000000, &10000);
+            underlying_1_client.mint(&swap_adapter, &20_0000000);
 
             let pre_pool_balance_0 = underlying_0_client.balance(&pool);
             let pre_pool_balance_1 = underlying_1_client.balance(&pool);
 
-            // pool has 100 supplied and 50 borrowed for asset_0
-            // -> max util is 95%
-            let flash_loan: FlashLoan = FlashLoan {
-                contract: flash_loan_receiver,
-                asset: underlying_0.clone(),
-                amount: 25_0000000,
+            let swap = CollateralSwap {
+                adapter: swap_adapter.clone(),
+                asset_in: underlying_0.clone(),
+                amount_in: 20_0000000,
+                asset_out: underlying_1.clone(),
+                amount_out: 20_0000000,
             };
+            let positions = execute_submit_with_collateral_swap(&e, &samwise, swap, vec![&e]);
 
-            let requests = vec![
-                &e,
-                Request {
-                    request_type: RequestType::Repay as u32,
-                    address: underlying_0,
-                    amount: 25_0000010,
-                },
-            ];
-            let positions = execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
-
-            assert_eq!(positions.liabilities.len(), 0);
-            assert_eq!(positions.collateral.len(), 0);
+            assert_eq!(positions.liabilities.get_unchecked(1), 10_0000000);
+            assert_eq!(positions.collateral.get_unchecked(0), 30_0000000);
+            assert_eq!(positions.collateral.get_unchecked(1), 20_0000000);
             assert_eq!(positions.supply.len(), 0);
 
-            assert_eq!(underlying_0_client.balance(&pool), pre_pool_balance_0 + 1,);
-            assert_eq!(underlying_1_client.balance(&pool), pre_pool_balance_1,);
-
-            // rounding causes 1 stroops to be lost
-            assert_eq!(underlying_0_client.balance(&samwise), 0_9999999);
+            assert_eq!(underlying_0_client.balance(&pool), pre_pool_balance_0 - 20_0000000);
+            assert_eq!(underlying_1_client.balance(&pool), pre_pool_balance_1 + 20_0000000);
+            assert_eq!(underlying_0_client.balance(&swap_adapter), 20_0000000);
+            assert_eq!(underlying_1_client.balance(&swap_adapter), 0);
+            assert_eq!(underlying_0_client.balance(&samwise), 0);
             assert_eq!(underlying_1_client.balance(&samwise), 0);
-
-            // check allowance is used
-            assert_eq!(
-                underlying_0_client.allowance(&samwise, &pool),
-                100_0000000 - 25_0000001
-            );
         });
     }
 
     #[test]
     #[should_panic(expected = "Error(Contract, #1205)")]
-    fn test_submit_with_flash_loan_checks_health() {
+    fn test_submit_with_collateral_swap_checks_health() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -1200,17 +3501,16 @@ mod tests {
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
-        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+        let (swap_adapter, _) = testutils::create_swap_adapter(&e);
 
         let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
-        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
-        reserve_config.max_util = 9500000;
-        reserve_data.b_supply = 100_0000000;
-        reserve_data.d_supply = 50_0000000;
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
         testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
 
         let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
-        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
         oracle_client.set_data(
@@ -1224,43 +3524,44 @@ mod tests {
             &7,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 1_0000000]);
 
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e, (1, 10_0000000)],
+            collateral: map![&e, (0, 50_0000000)],
+            supply: map![&e],
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
 
-            underlying_1_client.mint(&samwise, &25_0000000);
-            underlying_1_client.approve(&samwise, &pool, &100_0000000, &10000);
+            underlying_1_client.mint(&swap_adapter, &5_0000000);
 
-            // pool has 100 supplied and 50 borrowed for asset_0
-            // -> max util is 95%
-            let flash_loan: FlashLoan = FlashLoan {
-                contract: flash_loan_receiver,
-                asset: underlying_0,
-                amount: 25_0000000,
+            // swapping for too little collateral leaves the user unhealthy
+            let swap = CollateralSwap {
+                adapter: swap_adapter.clone(),
+                asset_in: underlying_0.clone(),
+                amount_in: 50_0000000,
+                asset_out: underlying_1.clone(),
+                amount_out: 5_0000000,
             };
-
-            let requests = vec![
-                &e,
-                Request {
-                    request_type: RequestType::SupplyCollateral as u32,
-                    address: underlying_1,
-                    amount: 8_0000000,
-                },
-            ];
-            execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
+            execute_submit_with_collateral_swap(&e, &samwise, swap, vec![&e]);
         });
     }
 
     #[test]
-    #[should_panic(expected = "Error(Contract, #1207)")]
-    fn test_submit_with_flash_loan_checks_max_util() {
+    fn test_deleverage() {
         let e = Env::default();
         e.cost_estimate().budget().reset_unlimited();
         e.mock_all_auths_allowing_non_root_auth();
@@ -1281,17 +3582,16 @@ mod tests {
         let pool = testutils::create_pool(&e);
         let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
 
-        let (flash_loan_receiver, _) = testutils::create_flashloan_receiver(&e);
+        let (swap_adapter, _) = testutils::create_swap_adapter(&e);
 
-        let (underlying_0, _) = testutils::create_token_contract(&e, &bombadil);
-        let (mut reserve_config, mut reserve_data) = testutils::default_reserve_meta();
-        reserve_config.max_util = 9500000;
-        reserve_data.b_supply = 100_0000000;
-        reserve_data.d_supply = 50_0000000;
+        let (underlying_0, underlying_0_client) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
         testutils::create_reserve(&e, &pool, &underlying_0, &reserve_config, &reserve_data);
 
         let (underlying_1, underlying_1_client) = testutils::create_token_contract(&e, &bombadil);
-        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        let (reserve_config, mut reserve_data) = testutils::default_reserve_meta();
+        reserve_data.last_time = 600;
         testutils::create_reserve(&e, &pool, &underlying_1, &reserve_config, &reserve_data);
 
         oracle_client.set_data(
@@ -1305,37 +3605,207 @@ mod tests {
             &7,
             &300,
         );
-        oracle_client.set_price_stable(&vec![&e, 1_0000000, 5_0000000]);
+        oracle_client.set_price_stable(&vec![&e, 1_0000000, 1_0000000]);
 
         let pool_config = PoolConfig {
             oracle,
             bstop_rate: 0_1000000,
             status: 0,
             max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let user_positions = Positions {
+            liabilities: map![&e, (1, 10_0000000)],
+            collateral: map![&e, (0, 50_0000000)],
+            supply: map![&e],
         };
         e.as_contract(&pool, || {
             storage::set_pool_config(&e, &pool_config);
+            storage::set_user_positions(&e, &samwise, &user_positions);
 
-            underlying_1_client.mint(&samwise, &50_0000000);
-            underlying_1_client.approve(&samwise, &pool, &100_0000000, &10000);
+            underlying_1_client.mint(&swap_adapter, &12_0000000);
 
-            // pool has 100 supplied and 50 borrowed for asset_0
-            // -> max util is 95%
-            let flash_loan: FlashLoan = FlashLoan {
-                contract: flash_loan_receiver,
-                asset: underlying_0,
-                amount: 46_0000000,
+            let pre_pool_balance_0 = underlying_0_client.balance(&pool);
+            let pre_pool_balance_1 = underlying_1_client.balance(&pool);
+
+            // swap returns more than the outstanding liability
+            let swap = CollateralSwap {
+                adapter: swap_adapter.clone(),
+                asset_in: underlying_0.clone(),
+                amount_in: 12_0000000,
+                asset_out: underlying_1.clone(),
+                amount_out: 12_0000000,
             };
+            let positions = execute_deleverage(&e, &samwise, swap);
 
-            let requests = vec![
-                &e,
-                Request {
-                    request_type: RequestType::SupplyCollateral as u32,
-                    address: underlying_1,
-                    amount: 50_0000000,
-                },
-            ];
-            execute_submit_with_flash_loan(&e, &samwise, flash_loan, requests);
+            assert_eq!(positions.liabilities.len(), 0);
+            assert_eq!(positions.collateral.get_unchecked(0), 38_0000000);
+            assert_eq!(positions.supply.len(), 0);
+
+            assert_eq!(underlying_0_client.balance(&pool), pre_pool_balance_0 - 12_0000000);
+            assert_eq!(underlying_1_client.balance(&pool), pre_pool_balance_1 + 10_0000000);
+            assert_eq!(underlying_0_client.balance(&swap_adapter), 12_0000000);
+            assert_eq!(underlying_1_client.balance(&swap_adapter), 0);
+            assert_eq!(underlying_0_client.balance(&samwise), 0);
+            assert_eq!(underlying_1_client.balance(&samwise), 2_0000000);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1242)")]
+    fn test_request_spender_transfer_shortfall() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let token = e.register(MockFeeToken, ());
+        let token_client = MockFeeTokenClient::new(&e, &token);
+        token_client.mint(&samwise, &10_0000000);
+
+        e.as_contract(&pool, || {
+            let mut actions = Actions::new(&e);
+            actions
+                .request_spender_transfer
+                .set((samwise.clone(), token.clone()), 10_0000000);
+            handle_request_spender_transfers(&e, &actions);
+        });
+    }
+
+    #[test]
+    fn test_apply_transfer_shortfalls() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let oracle = Address::generate(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 4,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        let mut user = User {
+            address: samwise.clone(),
+            positions: Positions {
+                liabilities: map![&e],
+                collateral: map![&e, (0, 10_0000000)],
+                supply: map![&e, (0, 1_0000000)],
+            },
+            account_id: 0,
+            read_only: false,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            let mut pool = Pool::load(&e);
+
+            // no shortfall leaves the positions untouched
+            apply_transfer_shortfalls(&e, &mut pool, &mut user, &map![&e]);
+            assert_eq!(user.get_supply(0), 1_0000000);
+            assert_eq!(user.get_collateral(0), 10_0000000);
+
+            // the shortfall is burnt from supply before collateral
+            let shortfalls = map![&e, (underlying.clone(), 3_0000000)];
+            apply_transfer_shortfalls(&e, &mut pool, &mut user, &shortfalls);
+            assert_eq!(user.get_supply(0), 0);
+            assert_eq!(user.get_collateral(0), 8_0000000);
+            assert_eq!(user.get_liabilities(0), 0);
+
+            pool.store_cached_reserves(&e);
+            let reserve_data = storage::get_res_data(&e, &underlying);
+            assert_eq!(reserve_data.b_supply, 97_0000000);
+        });
+    }
+
+    #[test]
+    fn test_require_healthy_positions_health_warning() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let bombadil = Address::generate(&e);
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+        let (oracle, oracle_client) = testutils::create_mock_oracle(&e);
+
+        let (underlying, _) = testutils::create_token_contract(&e, &bombadil);
+        let (reserve_config, reserve_data) = testutils::default_reserve_meta();
+        testutils::create_reserve(&e, &pool, &underlying, &reserve_config, &reserve_data);
+
+        oracle_client.set_data(
+            &bombadil,
+            &Asset::Other(Symbol::new(&e, "USD")),
+            &vec![&e, Asset::Stellar(underlying.clone())],
+            &7,
+            &300,
+        );
+        oracle_client.set_price_stable(&vec![&e, 1_0000000]);
+
+        let pool_config = PoolConfig {
+            oracle,
+            bstop_rate: 0_1000000,
+            status: 0,
+            max_positions: 2,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: 1_0000100,
+            max_account_leverage: 0,
+        };
+        // health factor of 7.5 / 6.6666667 = 1.1249999
+        let user = User {
+            address: samwise.clone(),
+            positions: Positions {
+                liabilities: map![&e, (0, 5_0000000)],
+                collateral: map![&e, (0, 10_0000000)],
+                supply: map![&e],
+            },
+            account_id: 0,
+            read_only: false,
+        };
+        e.as_contract(&pool, || {
+            storage::set_pool_config(&e, &pool_config);
+            storage::set_health_warning_threshold(&e, &1_1000000);
+            let mut pool = Pool::load(&e);
+
+            // no warning while the positions are over the threshold
+            let event_count = e.events().all().len();
+            require_healthy_positions(&e, &mut pool, &user, 0);
+            assert_eq!(e.events().all().len(), event_count);
+        });
+
+        e.as_contract(&pool, || {
+            storage::set_health_warning_threshold(&e, &1_2000000);
+            let mut pool = Pool::load(&e);
+
+            require_healthy_positions(&e, &mut pool, &user, 0);
         });
+        let events = e.events().all();
+        assert_eq!(
+            vec![&e, events.last_unchecked()],
+            vec![
+                &e,
+                (
+                    pool.clone(),
+                    (Symbol::new(&e, "health_warning"), samwise.clone()).into_val(&e),
+                    (0u32, 1_1249999i128, 5000000_4000000i128).into_val(&e),
+                ),
+            ]
+        );
     }
 }
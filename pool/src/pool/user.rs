@@ -1,9 +1,16 @@
-use soroban_fixed_point_math::SorobanFixedPoint;
 use soroban_sdk::{contracttype, panic_with_error, Address, Env, Map};
 
-use crate::{constants::SCALAR_9, emissions, storage, validator::require_nonnegative, PoolError};
+use crate::{emissions, storage, validator::require_nonnegative, PoolError};
+
+use super::{referral, self_repay, snapshot, Reserve};
+
+#[cfg(feature = "auctions")]
+use crate::constants::SCALAR_12;
+#[cfg(feature = "auctions")]
+use soroban_fixed_point_math::SorobanFixedPoint;
 
-use super::{Pool, Reserve};
+#[cfg(feature = "auctions")]
+use super::Pool;
 
 /// A user / contracts position's with the pool, stored in the Reserve's decimals
 #[derive(Clone)]
@@ -34,24 +41,53 @@ impl Positions {
 }
 
 /// A user / contracts position's with the pool
+///
+/// A user can hold independent positions in sub-accounts. Account 0 is the user's main account.
+/// Sub-accounts do not accrue emissions or referral fees, and are not in an e-mode category.
+///
+/// A read only user is never written to the ledger. Its positions are updated as usual, but the
+/// bookkeeping of its positions, like emissions and referral fees, is skipped.
 #[derive(Clone)]
 pub struct User {
     pub address: Address,
+    pub account_id: u32,
     pub positions: Positions,
+    pub read_only: bool,
 }
 
 impl User {
     /// Create an empty User object in the environment
     pub fn load(e: &Env, address: &Address) -> Self {
+        User::load_account(e, address, 0)
+    }
+
+    /// Load the positions of one of the user's sub-accounts
+    pub fn load_account(e: &Env, address: &Address, account_id: u32) -> Self {
         User {
             address: address.clone(),
-            positions: storage::get_user_positions(e, address),
+            account_id,
+            positions: storage::get_account_positions(e, address, account_id),
+            read_only: false,
         }
     }
 
-    /// Store the user's positions to the ledger
+    /// Store the user's positions to the ledger. Read only users are not stored.
     pub fn store(&self, e: &Env) {
-        storage::set_user_positions(e, &self.address, &self.positions);
+        if self.read_only {
+            return;
+        }
+        snapshot::checkpoint_snapshot(e, self);
+        storage::set_account_positions(e, &self.address, self.account_id, &self.positions);
+    }
+
+    /// Get the e-mode category of the user's positions. Sub-accounts are never in an e-mode
+    /// category.
+    pub fn emode(&self, e: &Env) -> u32 {
+        if self.account_id == 0 {
+            storage::get_user_emode(e, &self.address)
+        } else {
+            0
+        }
     }
 
     /// Check if the user has liabilities
@@ -72,6 +108,7 @@ impl User {
         }
         let balance = self.get_liabilities(reserve.index);
         self.update_d_emissions(e, reserve, balance);
+        self.update_d_referral(e, reserve, balance);
         self.positions
             .liabilities
             .set(reserve.index, balance + amount);
@@ -86,6 +123,7 @@ impl User {
         }
         let balance = self.get_liabilities(reserve.index);
         self.update_d_emissions(e, reserve, balance);
+        self.update_d_referral(e, reserve, balance);
         let new_balance = balance - amount;
         require_nonnegative(e, &new_balance);
         if new_balance == 0 {
@@ -101,12 +139,13 @@ impl User {
     ///
     /// This should only be called if the liabilities are being defaulted on. The liability will
     /// be forgiven and suppliers will lose funds.
+    #[cfg(feature = "auctions")]
     pub fn default_liabilities(&mut self, e: &Env, reserve: &mut Reserve, amount: i128) {
         self.remove_liabilities(e, reserve, amount);
         // determine amount of funds in underlying that have defaulted
         // and deduct them from the b_rate
         let default_amount = reserve.to_asset_from_d_token(amount);
-        let b_rate_loss = default_amount.fixed_div_floor(&e, &reserve.b_supply, &SCALAR_9);
+        let b_rate_loss = default_amount.fixed_div_floor(&e, &reserve.b_supply, &SCALAR_12);
         reserve.b_rate -= b_rate_loss;
         if reserve.b_rate < 0 {
             reserve.b_rate = 0;
@@ -120,12 +159,19 @@ impl User {
 
     /// Add collateral to the position expressed in blendTokens. Accrues emissions
     /// against the balance if necessary and updates the reserve's b_supply.
+    ///
+    /// ### Panics
+    /// If the amount is zero or the user's supplied assets are siloed
     pub fn add_collateral(&mut self, e: &Env, reserve: &mut Reserve, amount: i128) {
         if amount == 0 {
             panic_with_error!(e, PoolError::InvalidBTokenMintAmount)
         }
+        if storage::get_user_siloed(e, &self.address) {
+            panic_with_error!(e, PoolError::SiloedDeposits)
+        }
         let balance = self.get_collateral(reserve.index);
         self.update_b_emissions(e, reserve, self.get_total_supply(reserve.index));
+        self.update_self_repay(e, reserve, balance);
         self.positions
             .collateral
             .set(reserve.index, balance + amount);
@@ -140,6 +186,7 @@ impl User {
         }
         let balance = self.get_collateral(reserve.index);
         self.update_b_emissions(e, reserve, self.get_total_supply(reserve.index));
+        self.update_self_repay(e, reserve, balance);
         let new_balance = balance - amount;
         require_nonnegative(e, &new_balance);
         if new_balance == 0 {
@@ -191,6 +238,7 @@ impl User {
     }
 
     // Removes positions from a user - does not consider supply
+    #[cfg(feature = "auctions")]
     pub fn rm_positions(
         &mut self,
         e: &Env,
@@ -211,6 +259,7 @@ impl User {
     }
 
     // Adds positions to a user - does not consider supply
+    #[cfg(feature = "auctions")]
     pub fn add_positions(
         &mut self,
         e: &Env,
@@ -231,6 +280,15 @@ impl User {
     }
 
     fn update_d_emissions(&self, e: &Env, reserve: &Reserve, amount: i128) {
+        if self.read_only {
+            return;
+        }
+        // sub-accounts don't earn emissions, but the reserve's emission index must still be
+        // accrued over the supply before it changes
+        if self.account_id != 0 {
+            emissions::update_emission_data(e, reserve.index * 2, reserve.d_supply, reserve.scalar);
+            return;
+        }
         emissions::update_emissions(
             e,
             reserve.index * 2,
@@ -241,7 +299,33 @@ impl User {
         );
     }
 
+    fn update_d_referral(&self, e: &Env, reserve: &mut Reserve, amount: i128) {
+        if self.account_id != 0 || self.read_only {
+            return;
+        }
+        referral::accrue_referral_fees(e, reserve, &self.address, amount);
+    }
+
+    fn update_self_repay(&self, e: &Env, reserve: &Reserve, b_tokens: i128) {
+        if self.account_id != 0 || self.read_only {
+            return;
+        }
+        self_repay::accrue_self_repay_yield(e, reserve, &self.address, b_tokens);
+    }
+
     fn update_b_emissions(&self, e: &Env, reserve: &Reserve, amount: i128) {
+        if self.read_only {
+            return;
+        }
+        if self.account_id != 0 {
+            emissions::update_emission_data(
+                e,
+                reserve.index * 2 + 1,
+                reserve.b_supply,
+                reserve.scalar,
+            );
+            return;
+        }
         emissions::update_emissions(
             e,
             reserve.index * 2 + 1,
@@ -277,6 +361,8 @@ mod tests {
                 liabilities: map![&e],
                 supply: map![&e],
             },
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             user.store(&e);
@@ -289,6 +375,37 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_load_and_store_account() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let user = User {
+            address: samwise.clone(),
+            account_id: 3,
+            positions: Positions {
+                collateral: map![&e, (0, 10000)],
+                liabilities: map![&e, (1, 5000)],
+                supply: map![&e],
+            },
+            read_only: false,
+        };
+        e.as_contract(&pool, || {
+            user.store(&e);
+            let loaded_user = User::load_account(&e, &samwise, 3);
+            assert_eq!(loaded_user.account_id, 3);
+            assert_eq!(loaded_user.positions.collateral.get_unchecked(0), 10000);
+            assert_eq!(loaded_user.positions.liabilities.get_unchecked(1), 5000);
+
+            let main_user = User::load(&e, &samwise);
+            assert_eq!(main_user.account_id, 0);
+            assert_eq!(main_user.positions.collateral.len(), 0);
+            assert_eq!(main_user.positions.liabilities.len(), 0);
+        });
+    }
+
     #[test]
     fn test_liabilities() {
         let e = Env::default();
@@ -306,6 +423,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_liabilities(0), 0);
@@ -351,6 +470,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_liabilities(0), 0);
@@ -398,6 +519,8 @@ mod tests {
                 collateral: map![&e],
                 supply: map![&e],
             },
+            account_id: 0,
+            read_only: false,
         };
 
         e.as_contract(&pool, || {
@@ -426,6 +549,67 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_add_liabilities_sub_account_accrues_reserve_emissions() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        e.ledger().set(LedgerInfo {
+            protocol_version: 22,
+            sequence_number: 1,
+            timestamp: 10001000,
+            network_id: Default::default(),
+            base_reserve: 10,
+            min_temp_entry_ttl: 10,
+            min_persistent_entry_ttl: 10,
+            max_entry_ttl: 3110400,
+        });
+
+        let mut reserve_0 = testutils::default_reserve(&e);
+        let starting_d_supply_0 = reserve_0.d_supply;
+
+        let emis_res_data = ReserveEmissionData {
+            expiration: 20000000,
+            eps: 0_10000000000000,
+            index: 10000000000,
+            last_time: 10000000, // 1000s elapsed
+        };
+
+        let mut user = User {
+            address: samwise.clone(),
+            positions: Positions {
+                liabilities: map![&e, (reserve_0.index, 1000)],
+                collateral: map![&e],
+                supply: map![&e],
+            },
+            account_id: 1,
+            read_only: false,
+        };
+
+        e.as_contract(&pool, || {
+            let res_0_d_token_index = reserve_0.index * 2 + 0;
+            storage::set_res_emis_data(&e, &res_0_d_token_index, &emis_res_data);
+
+            user.add_liabilities(&e, &mut reserve_0, 123);
+            assert_eq!(user.get_liabilities(0), 1123);
+            assert_eq!(reserve_0.d_supply, starting_d_supply_0 + 123);
+
+            // the reserve index is accrued over the supply before the change
+            let new_emis_res_data = storage::get_res_emis_data(&e, &res_0_d_token_index).unwrap();
+            let new_index = 10000000000
+                + (1000i128 * 0_10000000000000)
+                    .fixed_div_floor(starting_d_supply_0, SCALAR_7)
+                    .unwrap();
+            assert_eq!(new_emis_res_data.last_time, 10001000);
+            assert_eq!(new_emis_res_data.index, new_index);
+
+            // the sub-account does not accrue emissions
+            assert!(storage::get_user_emissions(&e, &samwise, &res_0_d_token_index).is_none());
+        });
+    }
+
     #[test]
     #[should_panic(expected = "Error(Contract, #1219)")]
     fn test_remove_liabilities_zero_burn() {
@@ -439,6 +623,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_liabilities(0), 0);
@@ -488,6 +674,8 @@ mod tests {
                 collateral: map![&e],
                 supply: map![&e],
             },
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             let res_0_d_token_index = reserve_0.index * 2 + 0;
@@ -527,6 +715,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             user.add_liabilities(&e, &mut reserve_0, 123);
@@ -544,14 +734,16 @@ mod tests {
         let pool = testutils::create_pool(&e);
 
         let mut reserve_0 = testutils::default_reserve(&e);
-        reserve_0.d_rate = 1_500_000_000;
+        reserve_0.d_rate = 1_500_000_000_000;
         reserve_0.d_supply = 500_0000000;
-        reserve_0.b_rate = 1_250_000_000;
+        reserve_0.b_rate = 1_250_000_000_000;
         reserve_0.b_supply = 750_0000000;
 
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_liabilities(0), 0);
@@ -570,7 +762,7 @@ mod tests {
                 reserve_0.total_supply(),
                 total_supply - underlying_default_amount
             );
-            assert_eq!(reserve_0.b_rate, 1_210_000_000);
+            assert_eq!(reserve_0.b_rate, 1_210_000_000_000);
             assert_eq!(reserve_0.b_supply, 750_0000000);
         });
     }
@@ -583,14 +775,16 @@ mod tests {
         let pool = testutils::create_pool(&e);
 
         let mut reserve_0 = testutils::default_reserve(&e);
-        reserve_0.d_rate = 1_500_000_000;
+        reserve_0.d_rate = 1_500_000_000_000;
         reserve_0.d_supply = 500_0000000;
-        reserve_0.b_rate = 0_100_000_000;
+        reserve_0.b_rate = 0_100_000_000_000;
         reserve_0.b_supply = 750_0000000;
 
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_liabilities(0), 0);
@@ -626,6 +820,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_collateral(0), 0);
@@ -663,6 +859,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_collateral(0), 0);
@@ -671,6 +869,31 @@ mod tests {
         });
     }
 
+    #[test]
+    #[should_panic(expected = "Error(Contract, #1238)")]
+    fn test_add_collateral_siloed() {
+        let e = Env::default();
+        e.mock_all_auths();
+        let samwise = Address::generate(&e);
+        let pool = testutils::create_pool(&e);
+
+        let mut reserve_0 = testutils::default_reserve(&e);
+
+        let mut user = User {
+            address: samwise.clone(),
+            positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
+        };
+        e.as_contract(&pool, || {
+            user.add_supply(&e, &mut reserve_0, 123);
+            assert_eq!(user.get_supply(0), 123);
+
+            storage::set_user_siloed(&e, &samwise, true);
+            user.add_collateral(&e, &mut reserve_0, 123);
+        });
+    }
+
     #[test]
     fn test_add_collateral_accrues_emissions() {
         let e = Env::default();
@@ -710,6 +933,8 @@ mod tests {
                 collateral: map![&e, (reserve_0.index, 700)],
                 supply: map![&e, (reserve_0.index, 300)],
             },
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             let res_0_d_token_index = reserve_0.index * 2 + 1;
@@ -750,6 +975,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_collateral(0), 0);
@@ -800,6 +1027,8 @@ mod tests {
                 collateral: map![&e, (reserve_0.index, 700)],
                 supply: map![&e, (reserve_0.index, 300)],
             },
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             let res_0_d_token_index = reserve_0.index * 2 + 1;
@@ -840,6 +1069,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             user.add_collateral(&e, &mut reserve_0, 123);
@@ -866,6 +1097,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_supply(0), 0);
@@ -903,6 +1136,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_supply(0), 0);
@@ -950,6 +1185,8 @@ mod tests {
                 collateral: map![&e, (reserve_0.index, 700)],
                 supply: map![&e, (reserve_0.index, 300)],
             },
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             let res_0_d_token_index = reserve_0.index * 2 + 1;
@@ -990,6 +1227,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             assert_eq!(user.get_supply(0), 0);
@@ -1040,6 +1279,8 @@ mod tests {
                 collateral: map![&e, (reserve_0.index, 700)],
                 supply: map![&e, (reserve_0.index, 300)],
             },
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             let res_0_d_token_index = reserve_0.index * 2 + 1;
@@ -1080,6 +1321,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             user.add_supply(&e, &mut reserve_0, 123);
@@ -1104,6 +1347,8 @@ mod tests {
         let mut user = User {
             address: samwise.clone(),
             positions: Positions::env_default(&e),
+            account_id: 0,
+            read_only: false,
         };
         e.as_contract(&pool, || {
             user.add_supply(&e, &mut reserve_0, 123);
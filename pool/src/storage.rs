@@ -1,9 +1,18 @@
 use soroban_sdk::{
-    contracttype, map, panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, IntoVal, Map,
-    String, Symbol, TryFromVal, Val, Vec,
+    contracttype, map, panic_with_error, unwrap::UnwrapOptimized, vec, Address, BytesN, Env,
+    IntoVal, Map, String, Symbol, TryFromVal, Val, Vec,
 };
 
-use crate::{auctions::AuctionData, pool::Positions, PoolError};
+use sep_40_oracle::PriceData;
+
+use crate::{
+    constants::{DEFAULT_ADMIN_DELAY, MIN_HEALTH_FACTOR_FLOOR},
+    pool::{reserve_payload_hash, Positions},
+    PoolError,
+};
+
+#[cfg(feature = "auctions")]
+use crate::auctions::{AuctionData, AuctionDataV1};
 
 /********** Ledger Thresholds **********/
 
@@ -18,8 +27,16 @@ const LEDGER_BUMP_SHARED: u32 = LEDGER_THRESHOLD_SHARED + ONE_DAY_LEDGERS; // ~
 const LEDGER_THRESHOLD_USER: u32 = ONE_DAY_LEDGERS * 100; // ~ 100 days
 const LEDGER_BUMP_USER: u32 = LEDGER_THRESHOLD_USER + 20 * ONE_DAY_LEDGERS; // ~ 120 days
 
+const LEDGER_BUMP_PRICE: u32 = ONE_DAY_LEDGERS / 24; // ~ 1 hour
+const LEDGER_BUMP_ATTESTATION: u32 = ONE_DAY_LEDGERS; // ~ 1 day, the longest attestation max age
+
+const LEDGER_BUMP_ACCRUAL: u32 = ONE_DAY_LEDGERS * 7; // ~ 7 days
+
 /********** Storage Types **********/
 
+// Stored types whose layout changed since the previous release of the pool keep that layout as a
+// `V1` type, and are migrated when read. Types added since are not versioned.
+
 /// The pool's config
 #[derive(Clone)]
 #[contracttype]
@@ -28,6 +45,267 @@ pub struct PoolConfig {
     pub bstop_rate: u32, // the rate the backstop takes on accrued debt interest, expressed in 7 decimals
     pub status: u32,     // the status of the pool
     pub max_positions: u32, // the maximum number of effective positions (collateral + liabilities) a single user can hold
+    pub flash_loan_fee: u32, // the fee charged on flash loans and sent to the backstop, expressed in 7 decimals
+    pub hf_strategy: u32, // the strategy used to calculate the health factor of user positions
+    pub fallback_oracle: Option<Address>, // the oracle used if the oracle's price is stale
+    pub min_health_factor: u32, // the minimum health factor of a user's positions after an action, expressed in 7 decimals
+    pub max_account_leverage: u32, // the maximum gross exposure to net equity of a user's positions after an action, expressed in 7 decimals. 0 if there is no limit
+}
+
+/// The pool's config, as stored before the pool config was extended
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolConfigV1 {
+    pub oracle: Address,    // the contract address of the oracle
+    pub bstop_rate: u32, // the rate the backstop takes on accrued debt interest, expressed in 7 decimals
+    pub status: u32,     // the status of the pool
+    pub max_positions: u32, // the maximum number of effective positions (collateral + liabilities) a single user can hold
+}
+
+impl PoolConfigV1 {
+    /// Migrate the config to the current layout, disabling every feature added since
+    fn migrate(self) -> PoolConfig {
+        PoolConfig {
+            oracle: self.oracle,
+            bstop_rate: self.bstop_rate,
+            status: self.status,
+            max_positions: self.max_positions,
+            flash_loan_fee: 0,
+            hf_strategy: 0,
+            fallback_oracle: None,
+            min_health_factor: MIN_HEALTH_FACTOR_FLOOR,
+            max_account_leverage: 0,
+        }
+    }
+}
+
+/// The anomaly detection config for a reserve
+#[derive(Clone)]
+#[contracttype]
+pub struct ReserveAnomalyConfig {
+    pub max_b_supply_delta: i128, // the maximum net decrease in b_supply within a ledger, as a portion of the b_supply at the start of the ledger (7 decimals)
+    pub max_d_supply_delta: i128, // the maximum net increase in d_supply within a ledger, as a portion of the d_supply at the start of the ledger (7 decimals)
+    pub restricted: bool,         // whether the reserve is restricted from new borrows pending admin review
+}
+
+/// The supply of a reserve at the start of a ledger
+#[derive(Clone)]
+#[contracttype]
+pub struct SupplySnapshot {
+    pub ledger: u32,
+    pub b_supply: i128,
+    pub d_supply: i128,
+}
+
+/// The keys allowed to sign price attestations for the pool
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceSigners {
+    pub signers: Vec<BytesN<32>>, // the ed25519 public keys allowed to sign price attestations
+    pub max_age: u64,             // the maximum age of an attestation in seconds
+}
+
+/// A volume tier for the flash loan fee. Users who reach both the transaction count and the
+/// volume of the tier within a fee epoch receive the tier's discount on the fee.
+#[derive(Clone)]
+#[contracttype]
+pub struct FeeTier {
+    pub min_count: u32,   // the minimum number of fee paying transactions in the epoch
+    pub min_volume: i128, // the minimum volume in the epoch, denominated in the oracle's base asset
+    pub discount: u32,    // the discount applied to the fee, expressed in 7 decimals
+}
+
+/// The fee paying activity of a user within a fee epoch
+#[derive(Clone)]
+#[contracttype]
+pub struct UserFeeVolume {
+    pub epoch: u64,   // the fee epoch the activity was recorded in
+    pub count: u32,   // the number of fee paying transactions
+    pub volume: i128, // the volume, denominated in the oracle's base asset
+}
+
+/// The schedule for settling small amounts of backstop credit directly into backstop deposits
+#[derive(Clone)]
+#[contracttype]
+pub struct CreditSettlementConfig {
+    pub interval: u64,   // the minimum number of seconds between settlements
+    pub threshold: i128, // the maximum value settled, denominated in the oracle's base asset
+}
+
+/// The interest rebate for repaying a reserve shortly after borrowing
+#[derive(Clone)]
+#[contracttype]
+pub struct RepayGraceConfig {
+    pub window: u64, // the number of seconds after borrowing a repayment receives the rebate
+    pub rebate: u32, // the share of the accrued interest refunded (7 decimals)
+}
+
+/// The most recent borrow of a user from a reserve
+#[derive(Clone)]
+#[contracttype]
+pub struct BorrowSnapshot {
+    pub timestamp: u64, // the timestamp of the borrow
+    pub d_rate: i128,   // the dToken rate of the reserve at the borrow (12 decimals)
+}
+
+/// A ledger declared by the admin at which the positions of every user are recorded
+#[derive(Clone)]
+#[contracttype]
+pub struct PositionSnapshot {
+    pub id: u32,     // the ID of the snapshot, incremented for each declared snapshot
+    pub ledger: u32, // the ledger sequence the positions are recorded at the start of
+}
+
+/// The fixed-rate borrowing config for a reserve
+#[derive(Clone)]
+#[contracttype]
+pub struct FixedRateConfig {
+    pub spread: u32,   // the spread added to the current borrow rate for fixed rates (7 decimals)
+    pub max_term: u64, // the maximum number of seconds until a fixed-rate loan's maturity
+}
+
+/// The fixed-rate tranche of a reserve
+#[derive(Clone)]
+#[contracttype]
+pub struct FixedTranche {
+    pub f_supply: i128, // the dTokens of the reserve under fixed-rate loans
+    pub f_rate: i128,   // the average fixed rate of the tranche weighted by dTokens (7 decimals)
+}
+
+/// A user's fixed-rate loan from a reserve. The loan's dTokens are settled to the fixed value
+/// of the loan whenever the loan is settled.
+#[derive(Clone)]
+#[contracttype]
+pub struct FixedLoan {
+    pub d_tokens: i128, // the dTokens of the user under the fixed rate
+    pub value: i128,    // the value of the loan in underlying tokens at the last settlement
+    pub rate: i128,     // the fixed rate of the loan (7 decimals)
+    pub last_time: u64, // the timestamp of the last settlement
+    pub maturity: u64,  // the timestamp after which anyone can settle the loan
+}
+
+/// A user's self-repaying loan config
+#[derive(Clone)]
+#[contracttype]
+pub struct SelfRepayConfig {
+    pub asset: Address, // the reserve whose collateral yield repays the reserve's liability
+    pub b_rate: i128,   // the bToken rate of the reserve at the last yield checkpoint (12 decimals)
+    pub accrued: i128,  // the collateral yield not yet used to repay, in underlying tokens
+}
+
+/// A reserve's rates after an interest accrual
+#[derive(Clone)]
+#[contracttype]
+pub struct AccrualSnapshot {
+    pub timestamp: u64, // the timestamp of the accrual
+    pub b_rate: i128,   // the bToken rate of the reserve after the accrual (12 decimals)
+    pub d_rate: i128,   // the dToken rate of the reserve after the accrual (12 decimals)
+    pub util: i128,     // the utilization of the reserve after the accrual (7 decimals)
+}
+
+/// The dutch auction curve used to price auctions
+#[derive(Clone)]
+#[contracttype]
+pub struct AuctionConfig {
+    pub lot_start: u32,        // the percent of the lot offered when an auction begins (7 decimals)
+    pub step_blocks: u32,      // the blocks for the lot to reach 100%, then for the bid to reach 0%
+    pub max_premium: u32,      // the max premium of a liquidation's lot over its bid (7 decimals)
+    pub floor: u32,            // the min value of a liquidation fill's bid over its lot (7 decimals)
+    pub min_fill_percent: u32, // the min percent of the remaining auction a partial fill takes (i.e. 15 => 15%)
+    pub min_fill_value: i128,  // the min value of a partial fill in the oracle's base asset, or 0 for none
+}
+
+impl Default for AuctionConfig {
+    /// The default auction curve, used until the pool sets one
+    fn default() -> Self {
+        AuctionConfig {
+            lot_start: 0,
+            step_blocks: 200,
+            max_premium: 0_5000000,
+            floor: 0,
+            min_fill_percent: 0,
+            min_fill_value: 0,
+        }
+    }
+}
+
+/// The bounds on the size of user liquidations
+#[derive(Clone)]
+#[contracttype]
+pub struct LiquidationConfig {
+    pub min_close_factor: u32, // the minimum percent of a position that can be liquidated
+    pub max_hf: u32,           // the max health factor after a liquidation (7 decimals)
+    pub twap_records: u32,     // the number of oracle records in a liquidation TWAP, or 0 for spot
+    pub target_hf: u32,        // the health factor fills to target restore the user to (7 decimals)
+}
+
+/// BLND queued to be unstaked by a risk bot
+#[derive(Clone)]
+#[contracttype]
+pub struct RiskBotUnstake {
+    pub amount: i128,     // the amount of BLND queued to be unstaked
+    pub unlock_time: u64, // the timestamp after which the queued BLND can be unstaked
+}
+
+/// An admin transfer proposed by the current admin
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub new_admin: Address, // the proposed admin
+    pub unlock_time: u64,   // the timestamp after which the proposed admin can accept
+}
+
+/// An admin delay change queued by the admin
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingAdminDelay {
+    pub delay: u64,       // the new admin delay in seconds
+    pub unlock_time: u64, // the timestamp after which the new admin delay applies
+}
+
+/// The bounds on prices loaded from the pool's oracle
+#[derive(Clone)]
+#[contracttype]
+pub struct OracleConfig {
+    pub max_price_age: u64, // the max age of a price in seconds
+    pub max_deviation: u32, // the max change from the last loaded price (7 decimals), 0 to disable
+}
+
+/// The boosted collateral and liability factors of an e-mode category. The factors apply to users
+/// in the category whose collateral and liabilities are all in reserves of the category.
+#[derive(Clone)]
+#[contracttype]
+pub struct EModeCategory {
+    pub c_factor: u32, // the collateral factor for the category expressed in 7 decimals
+    pub l_factor: u32, // the liability factor for the category expressed in 7 decimals
+}
+
+/// A queued change to the config of an e-mode category
+#[derive(Clone)]
+#[contracttype]
+pub struct QueuedEModeCategory {
+    pub config: EModeCategory, // the e-mode category config to set
+    pub unlock_time: u64,      // the timestamp the change can be executed at
+}
+
+/// A change to the configuration of the pool or one of its reserves
+#[derive(Clone)]
+#[contracttype]
+pub enum ConfigChange {
+    // The new pool config
+    Pool(PoolConfig),
+    // The reserve asset and its new reserve config
+    Reserve(Address, ReserveConfig),
+}
+
+/// An entry in the pool's configuration history
+#[derive(Clone)]
+#[contracttype]
+pub struct ConfigHistoryEntry {
+    pub version: u32,         // the sequential version of the entry
+    pub ledger: u32,          // the ledger the change was made in
+    pub actor: Address,       // the address that made the change
+    pub change: ConfigChange, // the new configuration
 }
 
 /// The pool's emission config
@@ -38,10 +316,48 @@ pub struct PoolEmissionConfig {
     pub last_time: u64,
 }
 
+/// The emissions distributed to the pool's reserves in an emission epoch. An epoch starts each
+/// time emissions are gulped from the backstop, and is never modified once recorded.
+#[derive(Clone)]
+#[contracttype]
+pub struct EmissionEpoch {
+    pub epoch: u32,            // the sequential index of the epoch
+    pub start: u64,            // the timestamp the epoch started
+    pub expiration: u64,       // the timestamp the epoch's new emissions are fully emitted by
+    pub emissions: i128,       // the tokens gulped from the backstop for the epoch
+    pub shares: Map<u32, u64>, // the emission config applied for the epoch, by reserve token id
+    pub reserve_emissions: Map<u32, i128>, // the new tokens distributed to each reserve token id
+}
+
 /// The configuration information about a reserve asset
 #[derive(Clone)]
 #[contracttype]
 pub struct ReserveConfig {
+    pub index: u32,           // the index of the reserve in the list
+    pub decimals: u32,        // the decimals used in both the bToken and underlying contract
+    pub c_factor: u32, // the collateral factor for the reserve scaled expressed in 7 decimals
+    pub l_factor: u32, // the liability factor for the reserve scaled expressed in 7 decimals
+    pub util: u32,     // the target utilization rate scaled expressed in 7 decimals
+    pub max_util: u32, // the maximum allowed utilization rate scaled expressed in 7 decimals
+    pub r_base: u32, // the R0 value (base rate) in the interest rate formula scaled expressed in 7 decimals
+    pub r_one: u32,  // the R1 value in the interest rate formula scaled expressed in 7 decimals
+    pub r_two: u32,  // the R2 value in the interest rate formula scaled expressed in 7 decimals
+    pub r_three: u32, // the R3 value in the interest rate formula scaled expressed in 7 decimals
+    pub reactivity: u32, // the reactivity constant for the reserve scaled expressed in 7 decimals
+    pub collateral_cap: i128, // the total amount of underlying tokens that can be used as collateral
+    pub debt_cap: i128,       // the total amount of underlying tokens that can be borrowed
+    pub enabled: bool,        // the flag of the reserve
+    pub min_supply_ledgers: u32, // the minimum number of ledgers supplied tokens must be held before they can be withdrawn
+    pub isolated: bool, // the flag for isolation mode, where the reserve can only be used as collateral on its own
+    pub debt_ceiling: i128, // the maximum debt that can be borrowed against the reserve in isolation mode, denominated in the oracle's base asset
+    pub category: u32, // the e-mode category of the reserve, or 0 if the reserve is not in a category
+}
+
+/// The configuration information about a reserve asset, as stored before the reserve config was
+/// extended
+#[derive(Clone)]
+#[contracttype]
+pub struct ReserveConfigV1 {
     pub index: u32,           // the index of the reserve in the list
     pub decimals: u32,        // the decimals used in both the bToken and underlying contract
     pub c_factor: u32, // the collateral factor for the reserve scaled expressed in 7 decimals
@@ -57,19 +373,117 @@ pub struct ReserveConfig {
     pub enabled: bool,        // the flag of the reserve
 }
 
+impl ReserveConfigV1 {
+    /// Migrate the config to the current layout, without a debt cap and outside of isolation
+    /// mode and e-mode
+    fn migrate(self) -> ReserveConfig {
+        ReserveConfig {
+            index: self.index,
+            decimals: self.decimals,
+            c_factor: self.c_factor,
+            l_factor: self.l_factor,
+            util: self.util,
+            max_util: self.max_util,
+            r_base: self.r_base,
+            r_one: self.r_one,
+            r_two: self.r_two,
+            r_three: self.r_three,
+            reactivity: self.reactivity,
+            collateral_cap: self.collateral_cap,
+            debt_cap: i128::MAX,
+            enabled: self.enabled,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
+        }
+    }
+}
+
+/// A custom interest rate curve for a reserve. Utilization is split into segments by the
+/// breakpoints, and the interest rate increases linearly by the segment's slope across each
+/// segment. The last segment spans from the last breakpoint to 100% utilization.
+#[derive(Clone)]
+#[contracttype]
+pub struct InterestRateConfig {
+    pub breakpoints: Vec<u32>, // the utilization rates that end each segment, in ascending order, expressed in 7 decimals
+    pub slopes: Vec<u32>, // the interest rate increase across each segment, including the last, expressed in 7 decimals
+}
+
+/// The bounds on the borrow rate of a reserve
+#[derive(Clone)]
+#[contracttype]
+pub struct RateBounds {
+    pub min_rate: u32, // the minimum borrow rate, expressed in 7 decimals
+    pub max_rate: u32, // the maximum borrow rate, expressed in 7 decimals
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct QueuedReserveInit {
-    pub new_config: ReserveConfig,
-    pub unlock_time: u64,
+    pub new_config: ReserveConfig, // the reserve configuration to set
+    pub ir_config: Option<InterestRateConfig>, // the custom interest rate curve, if any
+    pub unlock_time: u64, // the timestamp the proposal can be executed at
+    pub proposer: Address, // the admin that queued the proposal
+    pub payload_hash: BytesN<32>, // the sha256 hash of the XDR encoded (asset, config, ir_config)
+}
+
+/// A queued reserve initialization, as stored before proposals recorded their proposer and payload
+#[derive(Clone)]
+#[contracttype]
+pub struct QueuedReserveInitV1 {
+    pub new_config: ReserveConfigV1, // the reserve configuration to set
+    pub unlock_time: u64,            // the timestamp the proposal can be executed at
+}
+
+impl QueuedReserveInitV1 {
+    /// Migrate the proposal to the current layout. The proposal is attributed to the current
+    /// admin, and uses the default interest rate curve.
+    fn migrate(self, e: &Env, asset: &Address) -> QueuedReserveInit {
+        let new_config = self.new_config.migrate();
+        let payload_hash = reserve_payload_hash(e, asset, &new_config, &None);
+        QueuedReserveInit {
+            new_config,
+            ir_config: None,
+            unlock_time: self.unlock_time,
+            proposer: get_admin(e),
+            payload_hash,
+        }
+    }
+}
+
+/// A queued rebate of debt for a set of accounts in a reserve
+#[derive(Clone)]
+#[contracttype]
+pub struct QueuedDebtRebate {
+    pub accounts: Vec<Address>, // the accounts receiving the rebate
+    pub rebate: i128, // the portion of each account's dTokens to forgive expressed in 7 decimals
+    pub funder: Option<Address>, // the address funding the rebate, or None to fund it from the backstop credit
+    pub unlock_time: u64, // the timestamp the rebate can be executed after
 }
 
 /// The data for a reserve asset
 #[derive(Clone)]
 #[contracttype]
 pub struct ReserveData {
+    pub d_rate: i128, // the conversion rate from dToken to underlying expressed in 12 decimals
+    pub b_rate: i128, // the conversion rate from bToken to underlying expressed in 12 decimals
+    pub ir_mod: i128, // the interest rate curve modifier
+    pub b_supply: i128, // the total supply of b tokens
+    pub d_supply: i128, // the total supply of d tokens
+    pub backstop_credit: i128, // the amount of underlying tokens currently owed to the backstop
+    pub last_time: u64, // the last block the data was updated
+    pub isolated_debt: i128, // the debt borrowed against the reserve in isolation mode, denominated in the oracle's base asset
+    pub util_accum: i128, // the time weighted utilization since the last emission gulp (7 decimals)
+    pub accrued_fees: i128, // the total underlying tokens ever credited to the backstop
+}
+
+/// The data for a reserve asset, as stored before the rates moved to 12 decimals
+#[derive(Clone)]
+#[contracttype]
+pub struct ReserveDataV1 {
     pub d_rate: i128, // the conversion rate from dToken to underlying expressed in 9 decimals
-    pub b_rate: i128, // the conversion rate from bToken to underlying expressed with the underlying's decimals
+    pub b_rate: i128, // the conversion rate from bToken to underlying expressed in 9 decimals
     pub ir_mod: i128, // the interest rate curve modifier
     pub b_supply: i128, // the total supply of b tokens
     pub d_supply: i128, // the total supply of d tokens
@@ -77,6 +491,24 @@ pub struct ReserveData {
     pub last_time: u64, // the last block the data was updated
 }
 
+impl ReserveDataV1 {
+    /// Migrate the data to the current layout, scaling the rates to 12 decimals
+    fn migrate(self) -> ReserveData {
+        ReserveData {
+            d_rate: self.d_rate * 1000,
+            b_rate: self.b_rate * 1000,
+            ir_mod: self.ir_mod,
+            b_supply: self.b_supply,
+            d_supply: self.d_supply,
+            backstop_credit: self.backstop_credit,
+            last_time: self.last_time,
+            isolated_debt: 0,
+            util_accum: 0,
+            accrued_fees: 0,
+        }
+    }
+}
+
 /// The emission data for the reserve b or d token
 #[derive(Clone)]
 #[contracttype]
@@ -103,7 +535,40 @@ const BACKSTOP_KEY: &str = "Backstop";
 const BLND_TOKEN_KEY: &str = "BLNDTkn";
 const POOL_CONFIG_KEY: &str = "Config";
 const RES_LIST_KEY: &str = "ResList";
+const RES_REMOVED_KEY: &str = "ResRemoved";
 const POOL_EMIS_KEY: &str = "PoolEmis";
+const PRICE_SIGNERS_KEY: &str = "PriceSigs";
+#[cfg(feature = "flash-loans")]
+const FEE_TIERS_KEY: &str = "FeeTiers";
+const CONFIG_VERSION_KEY: &str = "CfgVer";
+const CREDIT_SETTLEMENT_KEY: &str = "CreditSet";
+const LAST_SETTLEMENT_KEY: &str = "LastSet";
+#[cfg(feature = "auctions")]
+const AUCTION_CONFIG_KEY: &str = "AuctCfg";
+#[cfg(feature = "auctions")]
+const LIQUIDATION_CONFIG_KEY: &str = "LiqCfg";
+const UTIL_EMIS_KEY: &str = "UtilEmis";
+const ORACLE_CONFIG_KEY: &str = "OracleCfg";
+const PAUSE_GUARDIAN_KEY: &str = "PauseGrd";
+const PAUSED_ACTIONS_KEY: &str = "Paused";
+const PENDING_ADMIN_KEY: &str = "PendAdmin";
+const ADMIN_DELAY_KEY: &str = "AdmDelay";
+const PENDING_ADMIN_DELAY_KEY: &str = "PendDelay";
+const REFERRAL_RATE_KEY: &str = "RefRate";
+#[cfg(feature = "auctions")]
+const KEEPER_BOUNTY_KEY: &str = "KprBounty";
+#[cfg(feature = "auctions")]
+const BAD_DEBT_THRESHOLD_KEY: &str = "BDThresh";
+const BORROW_ALLOWLIST_KEY: &str = "BorrowAL";
+const EMIS_EPOCH_KEY: &str = "EmisEpoch";
+const HEALTH_WARNING_KEY: &str = "HFWarn";
+#[cfg(feature = "auctions")]
+const AUCTION_QUEUE_KEY: &str = "AuctQueue";
+const UTIL_HOOK_KEY: &str = "UtilHook";
+#[cfg(feature = "auctions")]
+const LIQUIDATOR_ALLOWLIST_KEY: &str = "LiqAL";
+const MIN_POSITION_VALUE_KEY: &str = "MinPosVal";
+const POSITION_SNAPSHOT_KEY: &str = "PosSnap";
 
 #[derive(Clone)]
 #[contracttype]
@@ -112,6 +577,43 @@ pub struct UserReserveKey {
     reserve_id: u32,
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct DepositorKey {
+    user: Address,      // the Address whose positions receive the deposits
+    depositor: Address, // the Address allowed to deposit on behalf of the user
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct CreditDelegationKey {
+    delegator: Address, // the Address whose positions take on the liabilities
+    delegatee: Address, // the Address allowed to borrow against the delegator's positions
+    asset: Address,     // the underlying asset that can be borrowed
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ReferralFeeKey {
+    referrer: Address, // the Address the referral fees are owed to
+    asset: Address,    // the underlying asset the referral fees are paid in
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AccountKey {
+    user: Address,   // the Address that owns the sub-account
+    account_id: u32, // the ID of the sub-account
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct UserSnapshotKey {
+    id: u32,         // the ID of the snapshot
+    user: Address,   // the Address that owns the sub-account
+    account_id: u32, // the ID of the sub-account
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub struct AuctionKey {
@@ -134,12 +636,90 @@ pub enum PoolDataKey {
     EmisData(u32),
     // Map of positions in the pool for a user
     Positions(Address),
+    // Map of positions in the pool for a user's sub-account
+    AcctPositions(AccountKey),
     // The emission information for a reserve asset for a user
     UserEmis(UserReserveKey),
     // The auction's data
     Auction(AuctionKey),
     // A list of auctions and their associated data
     AuctData(Address),
+    // The keeper that created the auction
+    AuctKeeper(AuctionKey),
+    // A map of underlying asset's contract address to queued debt rebate
+    DebtRebate(Address),
+    // The ledger a user last supplied to a reserve
+    LastSupply(UserReserveKey),
+    // The BLND staked by a risk bot
+    RiskBot(Address),
+    // The BLND queued to be unstaked by a risk bot
+    RiskBotQ(Address),
+    // A map of underlying asset's contract address to anomaly detection config
+    Anomaly(Address),
+    // A map of underlying asset's contract address to the supply at the start of the ledger
+    SupplySnap(Address),
+    // The fee paying activity of a user in the current fee epoch
+    FeeVolume(Address),
+    // The next nonce of a user for signed submits
+    Nonce(Address),
+    // An entry in the configuration history by version
+    CfgLog(u32),
+    // The amount a delegatee can borrow on behalf of a delegator
+    Credit(CreditDelegationKey),
+    // The e-mode category config by category ID
+    EMode(u32),
+    // The queued e-mode category config by category ID
+    EModeInit(u32),
+    // The e-mode category a user has entered
+    UserEMode(Address),
+    // A map of underlying asset's contract address to custom interest rate curve
+    IrConfig(Address),
+    // A map of underlying asset's contract address to borrow rate bounds
+    RateBounds(Address),
+    // A map of underlying asset's contract address to the half-life of its rate modifier decay
+    IrModDecay(Address),
+    // A map of underlying asset's contract address to its bToken contract
+    BToken(Address),
+    // A map of underlying asset's contract address to the last price loaded from the oracle
+    LastPrice(Address),
+    // A map of underlying asset's contract address to the timestamp of its last price attestation
+    AttestTime(Address),
+    // The referrer attributed to a user
+    Referrer(Address),
+    // The dToken rate a user's liability in a reserve last paid referral fees at
+    RefDRate(UserReserveKey),
+    // The referral fees owed to a referrer for a reserve's underlying asset
+    RefFees(ReferralFeeKey),
+    // A map of underlying asset's contract address to the repayment interest rebate config
+    RepayGrace(Address),
+    // The most recent borrow of a user from a reserve
+    LastBorrow(UserReserveKey),
+    // The liquidation protection contract registered by a user
+    Protection(Address),
+    // A map of underlying asset's contract address to its most recent accrual snapshots
+    AccrualHist(Address),
+    // If a user's supplied assets are siloed and can't be used as collateral
+    Siloed(Address),
+    // If a depositor can supply collateral on behalf of a user
+    DepositFrom(DepositorKey),
+    // A map of underlying asset's contract address to fixed-rate borrowing config
+    FixedCfg(Address),
+    // A map of underlying asset's contract address to its fixed-rate tranche
+    FixedData(Address),
+    // A user's fixed-rate loan from a reserve
+    FixedLoan(UserReserveKey),
+    // The self-repaying loan config of a user
+    SelfRepay(Address),
+    // If a user is on the pool's borrower allowlist
+    Borrower(Address),
+    // If an address is on the pool's liquidator allowlist
+    Liquidator(Address),
+    // The positions of a user's sub-account recorded for a snapshot
+    UserSnap(UserSnapshotKey),
+    // An emission epoch by index
+    EmisEpoch(u32),
+    // A map of oracle contract address to the decimals of its prices
+    OracleDec(Address),
 }
 
 /********** Storage **********/
@@ -201,58 +781,350 @@ pub fn set_user_positions(e: &Env, user: &Address, positions: &Positions) {
         .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
 
-/********** Admin **********/
-
-// Fetch the current admin Address
+/// Fetch the positions of a user's sub-account or return an empty Positions struct. Account
+/// 0 is the user's main account.
 ///
-/// ### Panics
-/// If the admin does not exist
-pub fn get_admin(e: &Env) -> Address {
-    e.storage()
-        .instance()
-        .get(&Symbol::new(e, ADMIN_KEY))
-        .unwrap_optimized()
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `account_id` - The ID of the sub-account
+pub fn get_account_positions(e: &Env, user: &Address, account_id: u32) -> Positions {
+    if account_id == 0 {
+        return get_user_positions(e, user);
+    }
+    let key = PoolDataKey::AcctPositions(AccountKey {
+        user: user.clone(),
+        account_id,
+    });
+    get_persistent_default(
+        e,
+        &key,
+        || Positions::env_default(e),
+        LEDGER_THRESHOLD_USER,
+        LEDGER_BUMP_USER,
+    )
 }
 
-/// Set a new admin
+/// Set the positions of a user's sub-account. Account 0 is the user's main account.
 ///
 /// ### Arguments
-/// * `new_admin` - The Address for the admin
-pub fn set_admin(e: &Env, new_admin: &Address) {
+/// * `user` - The address of the user
+/// * `account_id` - The ID of the sub-account
+/// * `positions` - The new positions for the sub-account
+pub fn set_account_positions(e: &Env, user: &Address, account_id: u32, positions: &Positions) {
+    if account_id == 0 {
+        return set_user_positions(e, user, positions);
+    }
+    let key = PoolDataKey::AcctPositions(AccountKey {
+        user: user.clone(),
+        account_id,
+    });
     e.storage()
-        .instance()
-        .set::<Symbol, Address>(&Symbol::new(e, ADMIN_KEY), new_admin);
+        .persistent()
+        .set::<PoolDataKey, Positions>(&key, positions);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
 
-/********** Metadata **********/
-
-/// Set a pool name
+/// Fetch the ledger the user last supplied to the reserve, if it is still within the reserve's
+/// minimum supply duration
 ///
 /// ### Arguments
-/// * `name` - The Name of the pool
-pub fn set_name(e: &Env, name: &String) {
-    e.storage()
-        .instance()
-        .set::<Symbol, String>(&Symbol::new(e, NAME_KEY), name);
+/// * `user` - The address of the user
+/// * `reserve_index` - The index of the reserve
+pub fn get_user_last_supply(e: &Env, user: &Address, reserve_index: u32) -> Option<u32> {
+    let key = PoolDataKey::LastSupply(UserReserveKey {
+        user: user.clone(),
+        reserve_id: reserve_index,
+    });
+    e.storage().temporary().get::<PoolDataKey, u32>(&key)
 }
 
-/********** Backstop **********/
-
-/// Fetch the backstop ID for the pool
+/// Set the ledger the user last supplied to the reserve. The entry is only kept alive for
+/// `duration` ledgers, after which the supply is no longer restricted.
 ///
-/// ### Panics
-/// If no backstop is set
-pub fn get_backstop(e: &Env) -> Address {
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `reserve_index` - The index of the reserve
+/// * `duration` - The number of ledgers the entry needs to live for
+pub fn set_user_last_supply(e: &Env, user: &Address, reserve_index: u32, duration: u32) {
+    let key = PoolDataKey::LastSupply(UserReserveKey {
+        user: user.clone(),
+        reserve_id: reserve_index,
+    });
     e.storage()
-        .instance()
-        .get(&Symbol::new(e, BACKSTOP_KEY))
-        .unwrap_optimized()
+        .temporary()
+        .set::<PoolDataKey, u32>(&key, &e.ledger().sequence());
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, duration, duration);
 }
 
-/// Set a new backstop ID
+/// Fetch the most recent borrow of the user from the reserve, if it is still within the reserve's
+/// repayment grace window
 ///
 /// ### Arguments
-/// * `backstop` - The address of the backstop
+/// * `user` - The address of the user
+/// * `reserve_index` - The index of the reserve
+pub fn get_user_last_borrow(e: &Env, user: &Address, reserve_index: u32) -> Option<BorrowSnapshot> {
+    let key = PoolDataKey::LastBorrow(UserReserveKey {
+        user: user.clone(),
+        reserve_id: reserve_index,
+    });
+    e.storage()
+        .temporary()
+        .get::<PoolDataKey, BorrowSnapshot>(&key)
+}
+
+/// Set the most recent borrow of the user from the reserve. The entry is only kept alive for
+/// `duration` ledgers.
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `reserve_index` - The index of the reserve
+/// * `snapshot` - The borrow snapshot
+/// * `duration` - The number of ledgers the entry needs to live for
+pub fn set_user_last_borrow(
+    e: &Env,
+    user: &Address,
+    reserve_index: u32,
+    snapshot: &BorrowSnapshot,
+    duration: u32,
+) {
+    let key = PoolDataKey::LastBorrow(UserReserveKey {
+        user: user.clone(),
+        reserve_id: reserve_index,
+    });
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, BorrowSnapshot>(&key, snapshot);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, duration, duration);
+}
+
+/// Fetch the fee paying activity of a user, or an empty record if the user has no recorded activity
+///
+/// ### Arguments
+/// * `user` - The address of the user
+#[cfg(feature = "flash-loans")]
+pub fn get_user_fee_volume(e: &Env, user: &Address) -> UserFeeVolume {
+    let key = PoolDataKey::FeeVolume(user.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || UserFeeVolume {
+            epoch: 0,
+            count: 0,
+            volume: 0,
+        },
+        LEDGER_THRESHOLD_USER,
+        LEDGER_BUMP_USER,
+    )
+}
+
+/// Set the fee paying activity of a user
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `fee_volume` - The fee paying activity of the user
+#[cfg(feature = "flash-loans")]
+pub fn set_user_fee_volume(e: &Env, user: &Address, fee_volume: &UserFeeVolume) {
+    let key = PoolDataKey::FeeVolume(user.clone());
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, UserFeeVolume>(&key, fee_volume);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Fetch the next nonce of a user for signed submits
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn get_user_nonce(e: &Env, user: &Address) -> u64 {
+    let key = PoolDataKey::Nonce(user.clone());
+    get_persistent_default(e, &key, || 0u64, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set the next nonce of a user for signed submits
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `nonce` - The next nonce of the user
+pub fn set_user_nonce(e: &Env, user: &Address, nonce: u64) {
+    let key = PoolDataKey::Nonce(user.clone());
+    e.storage().persistent().set::<PoolDataKey, u64>(&key, &nonce);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Fetch the amount of an asset a delegatee can borrow on behalf of a delegator
+///
+/// ### Arguments
+/// * `delegator` - The address whose positions take on the liabilities
+/// * `delegatee` - The address allowed to borrow
+/// * `asset` - The underlying asset that can be borrowed
+pub fn get_credit_delegation(
+    e: &Env,
+    delegator: &Address,
+    delegatee: &Address,
+    asset: &Address,
+) -> i128 {
+    let key = PoolDataKey::Credit(CreditDelegationKey {
+        delegator: delegator.clone(),
+        delegatee: delegatee.clone(),
+        asset: asset.clone(),
+    });
+    get_persistent_default(e, &key, || 0i128, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set the amount of an asset a delegatee can borrow on behalf of a delegator
+///
+/// ### Arguments
+/// * `delegator` - The address whose positions take on the liabilities
+/// * `delegatee` - The address allowed to borrow
+/// * `asset` - The underlying asset that can be borrowed
+/// * `amount` - The amount of the asset that can be borrowed
+pub fn set_credit_delegation(
+    e: &Env,
+    delegator: &Address,
+    delegatee: &Address,
+    asset: &Address,
+    amount: i128,
+) {
+    let key = PoolDataKey::Credit(CreditDelegationKey {
+        delegator: delegator.clone(),
+        delegatee: delegatee.clone(),
+        asset: asset.clone(),
+    });
+    e.storage().persistent().set::<PoolDataKey, i128>(&key, &amount);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/********** Admin **********/
+
+// Fetch the current admin Address
+///
+/// ### Panics
+/// If the admin does not exist
+pub fn get_admin(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, ADMIN_KEY))
+        .unwrap_optimized()
+}
+
+/// Set a new admin
+///
+/// ### Arguments
+/// * `new_admin` - The Address for the admin
+pub fn set_admin(e: &Env, new_admin: &Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, ADMIN_KEY), new_admin);
+}
+
+/// Fetch the pending admin transfer, if any
+pub fn get_pending_admin(e: &Env) -> Option<PendingAdmin> {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, PENDING_ADMIN_KEY))
+}
+
+/// Set the pending admin transfer, or remove it if None
+///
+/// ### Arguments
+/// * `pending_admin` - The pending admin transfer
+pub fn set_pending_admin(e: &Env, pending_admin: &Option<PendingAdmin>) {
+    match pending_admin {
+        Some(pending_admin) => e
+            .storage()
+            .instance()
+            .set::<Symbol, PendingAdmin>(&Symbol::new(e, PENDING_ADMIN_KEY), pending_admin),
+        None => e
+            .storage()
+            .instance()
+            .remove(&Symbol::new(e, PENDING_ADMIN_KEY)),
+    }
+}
+
+/// Fetch the delay in seconds before a proposed admin can accept the admin role, ignoring any
+/// pending change
+pub fn get_admin_delay(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, ADMIN_DELAY_KEY))
+        .unwrap_or(DEFAULT_ADMIN_DELAY)
+}
+
+/// Set the delay in seconds before a proposed admin can accept the admin role
+///
+/// ### Arguments
+/// * `delay` - The delay in seconds
+pub fn set_admin_delay(e: &Env, delay: &u64) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u64>(&Symbol::new(e, ADMIN_DELAY_KEY), delay);
+}
+
+/// Fetch the pending admin delay change, if any
+pub fn get_pending_admin_delay(e: &Env) -> Option<PendingAdminDelay> {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, PENDING_ADMIN_DELAY_KEY))
+}
+
+/// Set the pending admin delay change, or remove it if None
+///
+/// ### Arguments
+/// * `pending_delay` - The pending admin delay change
+pub fn set_pending_admin_delay(e: &Env, pending_delay: &Option<PendingAdminDelay>) {
+    match pending_delay {
+        Some(pending_delay) => e.storage().instance().set::<Symbol, PendingAdminDelay>(
+            &Symbol::new(e, PENDING_ADMIN_DELAY_KEY),
+            pending_delay,
+        ),
+        None => e
+            .storage()
+            .instance()
+            .remove(&Symbol::new(e, PENDING_ADMIN_DELAY_KEY)),
+    }
+}
+
+/********** Metadata **********/
+
+/// Set a pool name
+///
+/// ### Arguments
+/// * `name` - The Name of the pool
+pub fn set_name(e: &Env, name: &String) {
+    e.storage()
+        .instance()
+        .set::<Symbol, String>(&Symbol::new(e, NAME_KEY), name);
+}
+
+/********** Backstop **********/
+
+/// Fetch the backstop ID for the pool
+///
+/// ### Panics
+/// If no backstop is set
+pub fn get_backstop(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, BACKSTOP_KEY))
+        .unwrap_optimized()
+}
+
+/// Set a new backstop ID
+///
+/// ### Arguments
+/// * `backstop` - The address of the backstop
 pub fn set_backstop(e: &Env, backstop: &Address) {
     e.storage()
         .instance()
@@ -286,10 +1158,19 @@ pub fn set_blnd_token(e: &Env, blnd_token_id: &Address) {
 /// ### Panics
 /// If the pool's config is not set
 pub fn get_pool_config(e: &Env) -> PoolConfig {
-    e.storage()
+    let config = e
+        .storage()
         .instance()
-        .get(&Symbol::new(e, POOL_CONFIG_KEY))
-        .unwrap_optimized()
+        .get::<Symbol, Val>(&Symbol::new(e, POOL_CONFIG_KEY))
+        .unwrap_optimized();
+    // configs stored before the pool config was extended are migrated when read, and the
+    // migration is persisted the next time the config is stored
+    match PoolConfig::try_from_val(e, &config) {
+        Ok(pool_config) => pool_config,
+        Err(_) => PoolConfigV1::try_from_val(e, &config)
+            .unwrap_optimized()
+            .migrate(),
+    }
 }
 
 /// Set the pool configuration
@@ -302,334 +1183,1999 @@ pub fn set_pool_config(e: &Env, config: &PoolConfig) {
         .set::<Symbol, PoolConfig>(&Symbol::new(e, POOL_CONFIG_KEY), config);
 }
 
-/********** Reserve Config (ResConfig) **********/
+/********** Price Signers **********/
 
-/// Fetch the reserve data for an asset
+/// Fetch the price signers for the pool, if any are set
+pub fn get_price_signers(e: &Env) -> Option<PriceSigners> {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, PRICE_SIGNERS_KEY))
+}
+
+/// Set the price signers for the pool
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the asset
+/// * `price_signers` - The keys allowed to sign price attestations
+pub fn set_price_signers(e: &Env, price_signers: &PriceSigners) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PriceSigners>(&Symbol::new(e, PRICE_SIGNERS_KEY), price_signers);
+}
+
+/// Fetch the flash loan fee tiers for the pool
+#[cfg(feature = "flash-loans")]
+pub fn get_fee_tiers(e: &Env) -> Vec<FeeTier> {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, FEE_TIERS_KEY))
+        .unwrap_or(vec![e])
+}
+
+/// Set the flash loan fee tiers for the pool
 ///
-/// ### Panics
-/// If the reserve does not exist
-pub fn get_res_config(e: &Env, asset: &Address) -> ReserveConfig {
-    let key = PoolDataKey::ResConfig(asset.clone());
+/// ### Arguments
+/// * `fee_tiers` - The fee tiers, ordered from lowest to highest
+#[cfg(feature = "flash-loans")]
+pub fn set_fee_tiers(e: &Env, fee_tiers: &Vec<FeeTier>) {
     e.storage()
-        .persistent()
-        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+        .instance()
+        .set::<Symbol, Vec<FeeTier>>(&Symbol::new(e, FEE_TIERS_KEY), fee_tiers);
+}
+
+/// Fetch the backstop credit settlement config for the pool, if it exists
+pub fn get_credit_settlement_config(e: &Env) -> Option<CreditSettlementConfig> {
     e.storage()
-        .persistent()
-        .get::<PoolDataKey, ReserveConfig>(&key)
-        .unwrap_optimized()
+        .instance()
+        .get(&Symbol::new(e, CREDIT_SETTLEMENT_KEY))
 }
 
-/// Set the reserve configuration for an asset
+/// Set the backstop credit settlement config for the pool
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the asset
-/// * `config` - The reserve configuration for the asset
-pub fn set_res_config(e: &Env, asset: &Address, config: &ReserveConfig) {
-    let key = PoolDataKey::ResConfig(asset.clone());
+/// * `config` - The backstop credit settlement config
+pub fn set_credit_settlement_config(e: &Env, config: &CreditSettlementConfig) {
+    e.storage()
+        .instance()
+        .set::<Symbol, CreditSettlementConfig>(&Symbol::new(e, CREDIT_SETTLEMENT_KEY), config);
+}
+
+/// Fetch the timestamp of the last backstop credit settlement
+pub fn get_last_credit_settlement(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, LAST_SETTLEMENT_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the timestamp of the last backstop credit settlement
+///
+/// ### Arguments
+/// * `timestamp` - The timestamp of the settlement
+pub fn set_last_credit_settlement(e: &Env, timestamp: u64) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u64>(&Symbol::new(e, LAST_SETTLEMENT_KEY), &timestamp);
+}
+
+/// Fetch the auction curve config for the pool, or the default curve if none has been set
+#[cfg(feature = "auctions")]
+pub fn get_auction_config(e: &Env) -> AuctionConfig {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, AUCTION_CONFIG_KEY))
+        .unwrap_or_default()
+}
+
+/// Set the auction curve config for the pool
+///
+/// ### Arguments
+/// * `config` - The auction curve config
+#[cfg(feature = "auctions")]
+pub fn set_auction_config(e: &Env, config: &AuctionConfig) {
+    e.storage()
+        .instance()
+        .set::<Symbol, AuctionConfig>(&Symbol::new(e, AUCTION_CONFIG_KEY), config);
+}
+
+/// Fetch the user liquidation config for the pool, or the default bounds if none has been set
+#[cfg(feature = "auctions")]
+pub fn get_liquidation_config(e: &Env) -> LiquidationConfig {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, LIQUIDATION_CONFIG_KEY))
+        .unwrap_or(LiquidationConfig {
+            min_close_factor: 0,
+            max_hf: 1_1500000,
+            twap_records: 0,
+            target_hf: 1_1000000,
+        })
+}
+
+/// Set the user liquidation config for the pool
+///
+/// ### Arguments
+/// * `config` - The user liquidation config
+#[cfg(feature = "auctions")]
+pub fn set_liquidation_config(e: &Env, config: &LiquidationConfig) {
+    e.storage()
+        .instance()
+        .set::<Symbol, LiquidationConfig>(&Symbol::new(e, LIQUIDATION_CONFIG_KEY), config);
+}
+
+/********** Oracle **********/
+
+/// Fetch the oracle price bounds for the pool, or the default bounds if none have been set
+pub fn get_oracle_config(e: &Env) -> OracleConfig {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, ORACLE_CONFIG_KEY))
+        .unwrap_or(OracleConfig {
+            max_price_age: 24 * 60 * 60,
+            max_deviation: 0,
+        })
+}
+
+/// Set the oracle price bounds for the pool
+///
+/// ### Arguments
+/// * `config` - The oracle price bounds
+pub fn set_oracle_config(e: &Env, config: &OracleConfig) {
+    e.storage()
+        .instance()
+        .set::<Symbol, OracleConfig>(&Symbol::new(e, ORACLE_CONFIG_KEY), config);
+}
+
+/// Fetch the cached decimals of an oracle's prices, if they have been loaded
+///
+/// ### Arguments
+/// * `oracle` - The contract address of the oracle
+pub fn get_oracle_decimals(e: &Env, oracle: &Address) -> Option<u32> {
+    let key = PoolDataKey::OracleDec(oracle.clone());
+    let result = e.storage().persistent().get::<PoolDataKey, u32>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Cache the decimals of an oracle's prices
+///
+/// ### Arguments
+/// * `oracle` - The contract address of the oracle
+/// * `decimals` - The decimals of the oracle's prices
+pub fn set_oracle_decimals(e: &Env, oracle: &Address, decimals: &u32) {
+    let key = PoolDataKey::OracleDec(oracle.clone());
     e.storage()
         .persistent()
-        .set::<PoolDataKey, ReserveConfig>(&key, config);
+        .set::<PoolDataKey, u32>(&key, decimals);
     e.storage()
         .persistent()
         .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
 }
 
-/// Checks if a reserve exists for an asset
+/// Fetch the last price loaded from the oracle for an asset and the time it was loaded at, if it
+/// was loaded recently
 ///
 /// ### Arguments
 /// * `asset` - The contract address of the asset
-pub fn has_res(e: &Env, asset: &Address) -> bool {
-    let key = PoolDataKey::ResConfig(asset.clone());
-    e.storage().persistent().has(&key)
+pub fn get_last_price(e: &Env, asset: &Address) -> Option<PriceData> {
+    let key = PoolDataKey::LastPrice(asset.clone());
+    e.storage().temporary().get::<PoolDataKey, PriceData>(&key)
+}
+
+/// Set the last price loaded from the oracle for an asset
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `price` - The price loaded from the oracle and the time it was loaded at
+pub fn set_last_price(e: &Env, asset: &Address, price: &PriceData) {
+    let key = PoolDataKey::LastPrice(asset.clone());
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, PriceData>(&key, price);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, LEDGER_BUMP_PRICE, LEDGER_BUMP_PRICE);
+}
+
+/// Fetch the timestamp of the last price attestation used for an asset, if one was used within
+/// the longest attestation max age
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_last_attestation_time(e: &Env, asset: &Address) -> Option<u64> {
+    let key = PoolDataKey::AttestTime(asset.clone());
+    e.storage().temporary().get::<PoolDataKey, u64>(&key)
+}
+
+/// Set the timestamp of the last price attestation used for an asset
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `timestamp` - The timestamp of the attestation
+pub fn set_last_attestation_time(e: &Env, asset: &Address, timestamp: u64) {
+    let key = PoolDataKey::AttestTime(asset.clone());
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, u64>(&key, &timestamp);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, LEDGER_BUMP_ATTESTATION, LEDGER_BUMP_ATTESTATION);
+}
+
+/// Fetch the most recent accrual snapshots of a reserve, oldest first
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_accrual_history(e: &Env, asset: &Address) -> Vec<AccrualSnapshot> {
+    let key = PoolDataKey::AccrualHist(asset.clone());
+    e.storage()
+        .temporary()
+        .get::<PoolDataKey, Vec<AccrualSnapshot>>(&key)
+        .unwrap_or(vec![e])
+}
+
+/// Set the most recent accrual snapshots of a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `history` - The accrual snapshots, oldest first
+pub fn set_accrual_history(e: &Env, asset: &Address, history: &Vec<AccrualSnapshot>) {
+    let key = PoolDataKey::AccrualHist(asset.clone());
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, Vec<AccrualSnapshot>>(&key, history);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, LEDGER_BUMP_ACCRUAL, LEDGER_BUMP_ACCRUAL);
+}
+
+/********** Config History **********/
+
+/// Fetch the number of configuration history entries recorded for the pool
+pub fn get_config_version(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, CONFIG_VERSION_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the number of configuration history entries recorded for the pool
+///
+/// ### Arguments
+/// * `version` - The number of entries recorded
+pub fn set_config_version(e: &Env, version: u32) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, CONFIG_VERSION_KEY), &version);
+}
+
+/// Fetch a configuration history entry, if it exists
+///
+/// ### Arguments
+/// * `version` - The version of the entry
+pub fn get_config_history_entry(e: &Env, version: u32) -> Option<ConfigHistoryEntry> {
+    let key = PoolDataKey::CfgLog(version);
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, ConfigHistoryEntry>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set a configuration history entry
+///
+/// ### Arguments
+/// * `entry` - The configuration history entry
+pub fn set_config_history_entry(e: &Env, entry: &ConfigHistoryEntry) {
+    let key = PoolDataKey::CfgLog(entry.version);
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, ConfigHistoryEntry>(&key, entry);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Delete a configuration history entry
+///
+/// ### Arguments
+/// * `version` - The version of the entry
+pub fn del_config_history_entry(e: &Env, version: u32) {
+    e.storage()
+        .persistent()
+        .remove(&PoolDataKey::CfgLog(version));
+}
+
+/********** Reserve Config (ResConfig) **********/
+
+/// Fetch the reserve data for an asset
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+///
+/// ### Panics
+/// If the reserve does not exist
+pub fn get_res_config(e: &Env, asset: &Address) -> ReserveConfig {
+    let key = PoolDataKey::ResConfig(asset.clone());
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    let config = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, Val>(&key)
+        .unwrap_optimized();
+    // configs stored before the reserve config was extended are migrated when read, and the
+    // migration is persisted the next time the config is stored
+    match ReserveConfig::try_from_val(e, &config) {
+        Ok(reserve_config) => reserve_config,
+        Err(_) => ReserveConfigV1::try_from_val(e, &config)
+            .unwrap_optimized()
+            .migrate(),
+    }
+}
+
+/// Set the reserve configuration for an asset
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `config` - The reserve configuration for the asset
+pub fn set_res_config(e: &Env, asset: &Address, config: &ReserveConfig) {
+    let key = PoolDataKey::ResConfig(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, ReserveConfig>(&key, config);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Checks if a reserve exists for an asset
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn has_res(e: &Env, asset: &Address) -> bool {
+    let key = PoolDataKey::ResConfig(asset.clone());
+    e.storage().persistent().has(&key)
+}
+
+/// Fetch a queued reserve set
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+///
+/// ### Panics
+/// If the reserve set has not been queued
+pub fn get_queued_reserve_set(e: &Env, asset: &Address) -> QueuedReserveInit {
+    let key = PoolDataKey::ResInit(asset.clone());
+    let queued_init = e
+        .storage()
+        .temporary()
+        .get::<PoolDataKey, Val>(&key)
+        .unwrap_optimized();
+    // proposals queued before proposals recorded their proposer and payload are migrated when
+    // read
+    match QueuedReserveInit::try_from_val(e, &queued_init) {
+        Ok(queued_init) => queued_init,
+        Err(_) => QueuedReserveInitV1::try_from_val(e, &queued_init)
+            .unwrap_optimized()
+            .migrate(e, asset),
+    }
+}
+
+/// Check if a reserve is actively queued
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn has_queued_reserve_set(e: &Env, asset: &Address) -> bool {
+    let key = PoolDataKey::ResInit(asset.clone());
+    e.storage().temporary().has(&key)
+}
+
+/// Set a new queued reserve set
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `config` - The reserve configuration for the asset
+pub fn set_queued_reserve_set(e: &Env, res_init: &QueuedReserveInit, asset: &Address) {
+    let key = PoolDataKey::ResInit(asset.clone());
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, QueuedReserveInit>(&key, res_init);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Delete a queued reserve set
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+///
+/// ### Panics
+/// If the reserve set has not been queued
+pub fn del_queued_reserve_set(e: &Env, asset: &Address) {
+    let key = PoolDataKey::ResInit(asset.clone());
+    e.storage().temporary().remove(&key);
+}
+
+/********** Debt Rebates **********/
+
+/// Fetch a queued debt rebate
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+///
+/// ### Panics
+/// If the debt rebate has not been queued
+pub fn get_queued_debt_rebate(e: &Env, asset: &Address) -> QueuedDebtRebate {
+    let key = PoolDataKey::DebtRebate(asset.clone());
+    e.storage()
+        .temporary()
+        .get::<PoolDataKey, QueuedDebtRebate>(&key)
+        .unwrap_optimized()
+}
+
+/// Check if a debt rebate is actively queued
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn has_queued_debt_rebate(e: &Env, asset: &Address) -> bool {
+    let key = PoolDataKey::DebtRebate(asset.clone());
+    e.storage().temporary().has(&key)
+}
+
+/// Set a new queued debt rebate
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `rebate` - The queued debt rebate for the asset
+pub fn set_queued_debt_rebate(e: &Env, asset: &Address, rebate: &QueuedDebtRebate) {
+    let key = PoolDataKey::DebtRebate(asset.clone());
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, QueuedDebtRebate>(&key, rebate);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Delete a queued debt rebate
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn del_queued_debt_rebate(e: &Env, asset: &Address) {
+    let key = PoolDataKey::DebtRebate(asset.clone());
+    e.storage().temporary().remove(&key);
+}
+
+/********** Reserve Data (ResData) **********/
+
+/// Fetch the reserve data for an asset
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+///
+/// ### Panics
+/// If the reserve does not exist
+pub fn get_res_data(e: &Env, asset: &Address) -> ReserveData {
+    let key = PoolDataKey::ResData(asset.clone());
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    let data = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, Val>(&key)
+        .unwrap_optimized();
+    // reserves stored before the rates moved to 12 decimals are migrated when read, and the
+    // migration is persisted the next time the reserve is stored
+    match ReserveData::try_from_val(e, &data) {
+        Ok(reserve_data) => reserve_data,
+        Err(_) => ReserveDataV1::try_from_val(e, &data)
+            .unwrap_optimized()
+            .migrate(),
+    }
+}
+
+/// Set the reserve data for an asset
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `data` - The reserve data for the asset
+pub fn set_res_data(e: &Env, asset: &Address, data: &ReserveData) {
+    let key = PoolDataKey::ResData(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, ReserveData>(&key, data);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/********** Reserve Interest Rate Curve **********/
+
+/// Fetch the custom interest rate curve for a reserve, if it exists
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_res_ir_config(e: &Env, asset: &Address) -> Option<InterestRateConfig> {
+    let key = PoolDataKey::IrConfig(asset.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, InterestRateConfig>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set the custom interest rate curve for a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `config` - The interest rate curve for the reserve
+pub fn set_res_ir_config(e: &Env, asset: &Address, config: &InterestRateConfig) {
+    let key = PoolDataKey::IrConfig(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, InterestRateConfig>(&key, config);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Delete the custom interest rate curve for a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn del_res_ir_config(e: &Env, asset: &Address) {
+    let key = PoolDataKey::IrConfig(asset.clone());
+    e.storage().persistent().remove(&key);
+}
+
+/// Fetch the borrow rate bounds for a reserve, if they exist
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_res_rate_bounds(e: &Env, asset: &Address) -> Option<RateBounds> {
+    let key = PoolDataKey::RateBounds(asset.clone());
+    let result = e.storage().persistent().get::<PoolDataKey, RateBounds>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set the borrow rate bounds for a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `bounds` - The borrow rate bounds for the reserve, or None to remove them
+pub fn set_res_rate_bounds(e: &Env, asset: &Address, bounds: &Option<RateBounds>) {
+    let key = PoolDataKey::RateBounds(asset.clone());
+    match bounds {
+        Some(bounds) => {
+            e.storage()
+                .persistent()
+                .set::<PoolDataKey, RateBounds>(&key, bounds);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/// Fetch the half-life in seconds of the rate modifier decay for a reserve, if it exists
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_res_ir_mod_half_life(e: &Env, asset: &Address) -> Option<u64> {
+    let key = PoolDataKey::IrModDecay(asset.clone());
+    let result = e.storage().persistent().get::<PoolDataKey, u64>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set the half-life in seconds of the rate modifier decay for a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `half_life` - The half-life of the decay, or None to remove it
+pub fn set_res_ir_mod_half_life(e: &Env, asset: &Address, half_life: &Option<u64>) {
+    let key = PoolDataKey::IrModDecay(asset.clone());
+    match half_life {
+        Some(half_life) => {
+            e.storage()
+                .persistent()
+                .set::<PoolDataKey, u64>(&key, half_life);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/********** Reserve bTokens **********/
+
+/// Fetch the bToken contract for a reserve, if it has been deployed
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_res_b_token(e: &Env, asset: &Address) -> Option<Address> {
+    let key = PoolDataKey::BToken(asset.clone());
+    let result = e.storage().persistent().get::<PoolDataKey, Address>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set the bToken contract for a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `b_token` - The contract address of the bToken
+pub fn set_res_b_token(e: &Env, asset: &Address, b_token: &Address) {
+    let key = PoolDataKey::BToken(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, Address>(&key, b_token);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/********** Reserve Anomalies **********/
+
+/// Fetch the anomaly detection config for a reserve, if it exists
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_res_anomaly_config(e: &Env, asset: &Address) -> Option<ReserveAnomalyConfig> {
+    let key = PoolDataKey::Anomaly(asset.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, ReserveAnomalyConfig>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set the anomaly detection config for a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `config` - The anomaly detection config for the reserve
+pub fn set_res_anomaly_config(e: &Env, asset: &Address, config: &ReserveAnomalyConfig) {
+    let key = PoolDataKey::Anomaly(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, ReserveAnomalyConfig>(&key, config);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Fetch the supply snapshot for a reserve, if it exists
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_supply_snapshot(e: &Env, asset: &Address) -> Option<SupplySnapshot> {
+    let key = PoolDataKey::SupplySnap(asset.clone());
+    e.storage()
+        .temporary()
+        .get::<PoolDataKey, SupplySnapshot>(&key)
+}
+
+/// Set the supply snapshot for a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `snapshot` - The supply of the reserve at the start of the ledger
+pub fn set_supply_snapshot(e: &Env, asset: &Address, snapshot: &SupplySnapshot) {
+    let key = PoolDataKey::SupplySnap(asset.clone());
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, SupplySnapshot>(&key, snapshot);
+}
+
+/********** Reserve List (ResList) **********/
+
+/// Fetch the list of reserves
+pub fn get_res_list(e: &Env) -> Vec<Address> {
+    get_persistent_default(
+        e,
+        &Symbol::new(e, RES_LIST_KEY),
+        || vec![e],
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Add a reserve to the back of the list and returns the index
+///
+/// ### Arguments
+/// * `asset` - The contract address of the underlying asset
+///
+/// ### Panics
+/// If the number of reserves in the list exceeds 32
+///
+// @dev: Only removed reserves at the back of the list can be removed from it
+pub fn push_res_list(e: &Env, asset: &Address) -> u32 {
+    let mut res_list = get_res_list(e);
+    if res_list.len() == 32 {
+        panic_with_error!(e, PoolError::BadRequest)
+    }
+    res_list.push_back(asset.clone());
+    let new_index = res_list.len() - 1;
+    set_res_list(e, &res_list);
+    new_index
+}
+
+/// Set the list of reserves
+///
+/// ### Arguments
+/// * `res_list` - The list of reserves
+pub fn set_res_list(e: &Env, res_list: &Vec<Address>) {
+    e.storage()
+        .persistent()
+        .set::<Symbol, Vec<Address>>(&Symbol::new(e, RES_LIST_KEY), res_list);
+    e.storage().persistent().extend_ttl(
+        &Symbol::new(e, RES_LIST_KEY),
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    );
+}
+
+/// Fetch the list of removed reserves that are still in the reserve list
+pub fn get_removed_res(e: &Env) -> Vec<Address> {
+    get_persistent_default(
+        e,
+        &Symbol::new(e, RES_REMOVED_KEY),
+        || vec![e],
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the list of removed reserves that are still in the reserve list
+///
+/// ### Arguments
+/// * `removed` - The list of removed reserves
+pub fn set_removed_res(e: &Env, removed: &Vec<Address>) {
+    e.storage()
+        .persistent()
+        .set::<Symbol, Vec<Address>>(&Symbol::new(e, RES_REMOVED_KEY), removed);
+    e.storage().persistent().extend_ttl(
+        &Symbol::new(e, RES_REMOVED_KEY),
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    );
+}
+
+/// Delete the config and data of a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn del_res(e: &Env, asset: &Address) {
+    e.storage()
+        .persistent()
+        .remove(&PoolDataKey::ResConfig(asset.clone()));
+    e.storage()
+        .persistent()
+        .remove(&PoolDataKey::ResData(asset.clone()));
+}
+
+/********** Reserve Emissions **********/
+
+/// Fetch the emission data for the reserve b or d token
+///
+/// ### Arguments
+/// * `res_token_index` - The d/bToken index for the reserve
+pub fn get_res_emis_data(e: &Env, res_token_index: &u32) -> Option<ReserveEmissionData> {
+    let key = PoolDataKey::EmisData(*res_token_index);
+    get_persistent_default(
+        e,
+        &key,
+        || None,
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the emission data for the reserve b or d token
+///
+/// ### Arguments
+/// * `res_token_index` - The d/bToken index for the reserve
+/// * `res_emis_data` - The new emission data for the reserve token
+pub fn set_res_emis_data(e: &Env, res_token_index: &u32, res_emis_data: &ReserveEmissionData) {
+    let key = PoolDataKey::EmisData(*res_token_index);
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, ReserveEmissionData>(&key, res_emis_data);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/********** User Emissions **********/
+
+/// Fetch the users emission data for a reserve's b or d token
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `res_token_index` - The d/bToken index for the reserve
+pub fn get_user_emissions(
+    e: &Env,
+    user: &Address,
+    res_token_index: &u32,
+) -> Option<UserEmissionData> {
+    let key = PoolDataKey::UserEmis(UserReserveKey {
+        user: user.clone(),
+        reserve_id: *res_token_index,
+    });
+    get_persistent_default(e, &key, || None, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set the users emission data for a reserve's d or d token
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `res_token_index` - The d/bToken index for the reserve
+/// * `data` - The new user emission d ata for the d/bToken
+pub fn set_user_emissions(e: &Env, user: &Address, res_token_index: &u32, data: &UserEmissionData) {
+    let key = PoolDataKey::UserEmis(UserReserveKey {
+        user: user.clone(),
+        reserve_id: *res_token_index,
+    });
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, UserEmissionData>(&key, data)
+}
+
+/********** Pool Emissions **********/
+
+/// Fetch the pool reserve emissions
+pub fn get_pool_emissions(e: &Env) -> Map<u32, u64> {
+    get_persistent_default(
+        e,
+        &Symbol::new(e, POOL_EMIS_KEY),
+        || map![e],
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the pool reserve emissions
+///
+/// ### Arguments
+/// * `emissions` - The map of emissions by reserve token id to share of emissions as
+///                 a percentage of 1e7 (e.g. 15% = 1500000)
+pub fn set_pool_emissions(e: &Env, emissions: &Map<u32, u64>) {
+    e.storage()
+        .persistent()
+        .set::<Symbol, Map<u32, u64>>(&Symbol::new(e, POOL_EMIS_KEY), emissions);
+    e.storage().persistent().extend_ttl(
+        &Symbol::new(e, POOL_EMIS_KEY),
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    );
+}
+
+/// Fetch if reserve emissions are weighted by the utilization of each reserve
+pub fn get_util_weighted_emissions(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, UTIL_EMIS_KEY))
+        .unwrap_or(false)
+}
+
+/// Set if reserve emissions are weighted by the utilization of each reserve
+///
+/// ### Arguments
+/// * `enabled` - True if reserve emissions are weighted by utilization
+pub fn set_util_weighted_emissions(e: &Env, enabled: &bool) {
+    e.storage()
+        .instance()
+        .set::<Symbol, bool>(&Symbol::new(e, UTIL_EMIS_KEY), enabled);
+}
+
+/// Fetch the number of emission epochs recorded for the pool
+pub fn get_emission_epoch_count(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, EMIS_EPOCH_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the number of emission epochs recorded for the pool
+///
+/// ### Arguments
+/// * `count` - The number of epochs recorded
+pub fn set_emission_epoch_count(e: &Env, count: u32) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, EMIS_EPOCH_KEY), &count);
+}
+
+/// Fetch an emission epoch, if it exists
+///
+/// ### Arguments
+/// * `epoch` - The index of the epoch
+pub fn get_emission_epoch(e: &Env, epoch: u32) -> Option<EmissionEpoch> {
+    let key = PoolDataKey::EmisEpoch(epoch);
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, EmissionEpoch>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set an emission epoch
+///
+/// ### Arguments
+/// * `epoch` - The emission epoch
+pub fn set_emission_epoch(e: &Env, epoch: &EmissionEpoch) {
+    let key = PoolDataKey::EmisEpoch(epoch.epoch);
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, EmissionEpoch>(&key, epoch);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/********** Pause **********/
+
+/// Fetch the pause guardian of the pool, if one is set
+pub fn get_pause_guardian(e: &Env) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, PAUSE_GUARDIAN_KEY))
+}
+
+/// Set the pause guardian of the pool, or remove it if None
+///
+/// ### Arguments
+/// * `guardian` - The address allowed to pause pool actions
+pub fn set_pause_guardian(e: &Env, guardian: &Option<Address>) {
+    match guardian {
+        Some(guardian) => e
+            .storage()
+            .instance()
+            .set::<Symbol, Address>(&Symbol::new(e, PAUSE_GUARDIAN_KEY), guardian),
+        None => e
+            .storage()
+            .instance()
+            .remove(&Symbol::new(e, PAUSE_GUARDIAN_KEY)),
+    }
+}
+
+/// Fetch the utilization hook of the pool, if one is set
+pub fn get_util_hook(e: &Env) -> Option<Address> {
+    e.storage().instance().get(&Symbol::new(e, UTIL_HOOK_KEY))
+}
+
+/// Set the utilization hook of the pool, or remove it if None
+///
+/// ### Arguments
+/// * `hook` - The address of the contract notified after each interest accrual
+pub fn set_util_hook(e: &Env, hook: &Option<Address>) {
+    match hook {
+        Some(hook) => e
+            .storage()
+            .instance()
+            .set::<Symbol, Address>(&Symbol::new(e, UTIL_HOOK_KEY), hook),
+        None => e.storage().instance().remove(&Symbol::new(e, UTIL_HOOK_KEY)),
+    }
+}
+
+/// Fetch the bitmask of paused pool actions
+pub fn get_paused_actions(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, PAUSED_ACTIONS_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the bitmask of paused pool actions
+///
+/// ### Arguments
+/// * `paused_actions` - The bitmask of paused pool actions
+pub fn set_paused_actions(e: &Env, paused_actions: &u32) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, PAUSED_ACTIONS_KEY), paused_actions);
+}
+
+/********** Auctions ***********/
+
+/// Fetch the auction data for an auction
+///
+/// ### Arguments
+/// * `auction_type` - The type of auction
+/// * `user` - The user who is auctioning off assets
+///
+/// ### Panics
+/// If the auction does not exist
+#[cfg(feature = "auctions")]
+pub fn get_auction(e: &Env, auction_type: &u32, user: &Address) -> AuctionData {
+    let key = PoolDataKey::Auction(AuctionKey {
+        user: user.clone(),
+        auct_type: *auction_type,
+    });
+    let auction_data = e
+        .storage()
+        .temporary()
+        .get::<PoolDataKey, Val>(&key)
+        .unwrap_optimized();
+    // auctions created before auctions recorded their sub-account and config are migrated when
+    // read
+    match AuctionData::try_from_val(e, &auction_data) {
+        Ok(auction_data) => auction_data,
+        Err(_) => AuctionDataV1::try_from_val(e, &auction_data)
+            .unwrap_optimized()
+            .migrate(),
+    }
+}
+
+/// Check if an auction exists for the given type and user
+///
+/// ### Arguments
+/// * `auction_type` - The type of auction
+/// * `user` - The user who is auctioning off assets
+pub fn has_auction(e: &Env, auction_type: &u32, user: &Address) -> bool {
+    let key = PoolDataKey::Auction(AuctionKey {
+        user: user.clone(),
+        auct_type: *auction_type,
+    });
+    e.storage().temporary().has(&key)
+}
+
+/// Set the the starting block for an auction
+///
+/// ### Arguments
+/// * `auction_type` - The type of auction
+/// * `user` - The user who is auctioning off assets
+/// * `auction_data` - The auction data
+#[cfg(feature = "auctions")]
+pub fn set_auction(e: &Env, auction_type: &u32, user: &Address, auction_data: &AuctionData) {
+    let key = PoolDataKey::Auction(AuctionKey {
+        user: user.clone(),
+        auct_type: *auction_type,
+    });
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, AuctionData>(&key, auction_data);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Fetch the keeper that created an auction, if one was recorded
+///
+/// ### Arguments
+/// * `auction_type` - The type of auction
+/// * `user` - The user who is auctioning off assets
+#[cfg(feature = "auctions")]
+pub fn get_auction_keeper(e: &Env, auction_type: &u32, user: &Address) -> Option<Address> {
+    let key = PoolDataKey::AuctKeeper(AuctionKey {
+        user: user.clone(),
+        auct_type: *auction_type,
+    });
+    e.storage().temporary().get::<PoolDataKey, Address>(&key)
+}
+
+/// Set the keeper that created an auction
+///
+/// ### Arguments
+/// * `auction_type` - The type of auction
+/// * `user` - The user who is auctioning off assets
+/// * `keeper` - The keeper that created the auction, or None to remove it
+#[cfg(feature = "auctions")]
+pub fn set_auction_keeper(e: &Env, auction_type: &u32, user: &Address, keeper: &Option<Address>) {
+    let key = PoolDataKey::AuctKeeper(AuctionKey {
+        user: user.clone(),
+        auct_type: *auction_type,
+    });
+    match keeper {
+        Some(keeper) => {
+            e.storage()
+                .temporary()
+                .set::<PoolDataKey, Address>(&key, keeper);
+            e.storage()
+                .temporary()
+                .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+        }
+        None => e.storage().temporary().remove(&key),
+    }
+}
+
+/// Fetch the share of a filled auction's value paid to the keeper that created the auction, or 0
+/// if none is set (7 decimals)
+#[cfg(feature = "auctions")]
+pub fn get_keeper_bounty(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, KEEPER_BOUNTY_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the share of a filled auction's value paid to the keeper that created the auction
+///
+/// ### Arguments
+/// * `bounty` - The share of the filled value (7 decimals)
+#[cfg(feature = "auctions")]
+pub fn set_keeper_bounty(e: &Env, bounty: &u32) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, KEEPER_BOUNTY_KEY), bounty);
+}
+
+/// Fetch the value of backstop bad debt, in the oracle's base asset, that triggers a bad debt
+/// auction automatically, or 0 if automatic bad debt auctions are disabled
+#[cfg(feature = "auctions")]
+pub fn get_bad_debt_threshold(e: &Env) -> i128 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, BAD_DEBT_THRESHOLD_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the value of backstop bad debt that triggers a bad debt auction automatically
+///
+/// ### Arguments
+/// * `threshold` - The value of bad debt in the oracle's base asset, or 0 to disable
+#[cfg(feature = "auctions")]
+pub fn set_bad_debt_threshold(e: &Env, threshold: &i128) {
+    e.storage()
+        .instance()
+        .set::<Symbol, i128>(&Symbol::new(e, BAD_DEBT_THRESHOLD_KEY), threshold);
+}
+
+/// Remove an auction
+///
+/// ### Arguments
+/// * `auction_type` - The type of auction
+/// * `user` - The user who is auctioning off assets
+#[cfg(feature = "auctions")]
+pub fn del_auction(e: &Env, auction_type: &u32, user: &Address) {
+    let key = PoolDataKey::Auction(AuctionKey {
+        user: user.clone(),
+        auct_type: *auction_type,
+    });
+    e.storage().temporary().remove(&key);
+}
+
+/// Fetch the open auctions, as (auction type, user) pairs ordered from oldest to newest
+#[cfg(feature = "auctions")]
+pub fn get_auction_queue(e: &Env) -> Vec<(u32, Address)> {
+    get_persistent_default(
+        e,
+        &Symbol::new(e, AUCTION_QUEUE_KEY),
+        || vec![e],
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the open auctions
+///
+/// ### Arguments
+/// * `queue` - The (auction type, user) pairs of the open auctions
+#[cfg(feature = "auctions")]
+pub fn set_auction_queue(e: &Env, queue: &Vec<(u32, Address)>) {
+    e.storage()
+        .persistent()
+        .set::<Symbol, Vec<(u32, Address)>>(&Symbol::new(e, AUCTION_QUEUE_KEY), queue);
+    e.storage().persistent().extend_ttl(
+        &Symbol::new(e, AUCTION_QUEUE_KEY),
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    );
+}
+
+/********** Risk Bots ***********/
+
+/// Fetch the BLND staked by a risk bot
+///
+/// ### Arguments
+/// * `bot` - The address of the risk bot
+#[cfg(feature = "auctions")]
+pub fn get_risk_bot_stake(e: &Env, bot: &Address) -> i128 {
+    let key = PoolDataKey::RiskBot(bot.clone());
+    get_persistent_default(e, &key, || 0i128, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set the BLND staked by a risk bot
+///
+/// ### Arguments
+/// * `bot` - The address of the risk bot
+/// * `stake` - The amount of BLND staked
+#[cfg(feature = "auctions")]
+pub fn set_risk_bot_stake(e: &Env, bot: &Address, stake: i128) {
+    let key = PoolDataKey::RiskBot(bot.clone());
+    e.storage().persistent().set::<PoolDataKey, i128>(&key, &stake);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Fetch the BLND queued to be unstaked by a risk bot, if any
+///
+/// ### Arguments
+/// * `bot` - The address of the risk bot
+#[cfg(feature = "auctions")]
+pub fn get_risk_bot_unstake(e: &Env, bot: &Address) -> Option<RiskBotUnstake> {
+    let key = PoolDataKey::RiskBotQ(bot.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, RiskBotUnstake>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
+}
+
+/// Set the BLND queued to be unstaked by a risk bot
+///
+/// ### Arguments
+/// * `bot` - The address of the risk bot
+/// * `unstake` - The queued BLND, or None to remove it
+#[cfg(feature = "auctions")]
+pub fn set_risk_bot_unstake(e: &Env, bot: &Address, unstake: &Option<RiskBotUnstake>) {
+    let key = PoolDataKey::RiskBotQ(bot.clone());
+    match unstake {
+        Some(unstake) => {
+            e.storage()
+                .persistent()
+                .set::<PoolDataKey, RiskBotUnstake>(&key, unstake);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/********** E-Mode ***********/
+
+/// Fetch the e-mode category a user has entered, or 0 if the user has not entered one
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn get_user_emode(e: &Env, user: &Address) -> u32 {
+    let key = PoolDataKey::UserEMode(user.clone());
+    get_persistent_default(e, &key, || 0u32, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set the e-mode category a user has entered
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `category` - The e-mode category, or 0 to leave e-mode
+pub fn set_user_emode(e: &Env, user: &Address, category: u32) {
+    let key = PoolDataKey::UserEMode(user.clone());
+    e.storage().persistent().set::<PoolDataKey, u32>(&key, &category);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Fetch the config of an e-mode category, if it exists
+///
+/// ### Arguments
+/// * `category` - The e-mode category
+pub fn get_emode_category(e: &Env, category: u32) -> Option<EModeCategory> {
+    let key = PoolDataKey::EMode(category);
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, EModeCategory>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set the config of an e-mode category
+///
+/// ### Arguments
+/// * `category` - The e-mode category
+/// * `config` - The config of the e-mode category
+pub fn set_emode_category(e: &Env, category: u32, config: &EModeCategory) {
+    let key = PoolDataKey::EMode(category);
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, EModeCategory>(&key, config);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Fetch the queued config change of an e-mode category, if any
+///
+/// ### Arguments
+/// * `category` - The e-mode category
+pub fn get_queued_emode_category(e: &Env, category: u32) -> Option<QueuedEModeCategory> {
+    let key = PoolDataKey::EModeInit(category);
+    e.storage()
+        .temporary()
+        .get::<PoolDataKey, QueuedEModeCategory>(&key)
+}
+
+/// Set the queued config change of an e-mode category
+///
+/// ### Arguments
+/// * `category` - The e-mode category
+/// * `queued` - The queued config change
+pub fn set_queued_emode_category(e: &Env, category: u32, queued: &QueuedEModeCategory) {
+    let key = PoolDataKey::EModeInit(category);
+    e.storage()
+        .temporary()
+        .set::<PoolDataKey, QueuedEModeCategory>(&key, queued);
+    e.storage()
+        .temporary()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Delete the queued config change of an e-mode category
+///
+/// ### Arguments
+/// * `category` - The e-mode category
+pub fn del_queued_emode_category(e: &Env, category: u32) {
+    let key = PoolDataKey::EModeInit(category);
+    e.storage().temporary().remove(&key);
+}
+
+/********** Fixed-Rate Tranche ***********/
+
+/// Fetch the fixed-rate borrowing config for a reserve, if it supports fixed-rate loans
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_fixed_rate_config(e: &Env, asset: &Address) -> Option<FixedRateConfig> {
+    let key = PoolDataKey::FixedCfg(asset.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, FixedRateConfig>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
+}
+
+/// Set the fixed-rate borrowing config for a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `config` - The fixed-rate borrowing config, or None to stop new fixed-rate loans
+pub fn set_fixed_rate_config(e: &Env, asset: &Address, config: &Option<FixedRateConfig>) {
+    let key = PoolDataKey::FixedCfg(asset.clone());
+    match config {
+        Some(config) => {
+            e.storage()
+                .persistent()
+                .set::<PoolDataKey, FixedRateConfig>(&key, config);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/// Fetch the fixed-rate tranche of a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+pub fn get_fixed_tranche(e: &Env, asset: &Address) -> FixedTranche {
+    let key = PoolDataKey::FixedData(asset.clone());
+    get_persistent_default(
+        e,
+        &key,
+        || FixedTranche {
+            f_supply: 0,
+            f_rate: 0,
+        },
+        LEDGER_THRESHOLD_SHARED,
+        LEDGER_BUMP_SHARED,
+    )
+}
+
+/// Set the fixed-rate tranche of a reserve
+///
+/// ### Arguments
+/// * `asset` - The contract address of the asset
+/// * `tranche` - The fixed-rate tranche
+pub fn set_fixed_tranche(e: &Env, asset: &Address, tranche: &FixedTranche) {
+    let key = PoolDataKey::FixedData(asset.clone());
+    e.storage()
+        .persistent()
+        .set::<PoolDataKey, FixedTranche>(&key, tranche);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/// Fetch a user's fixed-rate loan from a reserve, if any
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `reserve_index` - The index of the reserve
+pub fn get_user_fixed_loan(e: &Env, user: &Address, reserve_index: u32) -> Option<FixedLoan> {
+    let key = PoolDataKey::FixedLoan(UserReserveKey {
+        user: user.clone(),
+        reserve_id: reserve_index,
+    });
+    let result = e.storage().persistent().get::<PoolDataKey, FixedLoan>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
+}
+
+/// Set a user's fixed-rate loan from a reserve
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `reserve_index` - The index of the reserve
+/// * `loan` - The fixed-rate loan, or None to remove it
+pub fn set_user_fixed_loan(e: &Env, user: &Address, reserve_index: u32, loan: &Option<FixedLoan>) {
+    let key = PoolDataKey::FixedLoan(UserReserveKey {
+        user: user.clone(),
+        reserve_id: reserve_index,
+    });
+    match loan {
+        Some(loan) => {
+            e.storage()
+                .persistent()
+                .set::<PoolDataKey, FixedLoan>(&key, loan);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/********** Self-Repaying Loans ***********/
+
+/// Fetch the self-repaying loan config of a user, if the user has opted in
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn get_self_repay(e: &Env, user: &Address) -> Option<SelfRepayConfig> {
+    let key = PoolDataKey::SelfRepay(user.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, SelfRepayConfig>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
+}
+
+/// Set the self-repaying loan config of a user
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `config` - The self-repaying loan config, or None to opt out
+pub fn set_self_repay(e: &Env, user: &Address, config: &Option<SelfRepayConfig>) {
+    let key = PoolDataKey::SelfRepay(user.clone());
+    match config {
+        Some(config) => {
+            e.storage()
+                .persistent()
+                .set::<PoolDataKey, SelfRepayConfig>(&key, config);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/********** Siloed Deposits ***********/
+
+/// Check if a user's supplied assets are siloed and can't be used as collateral
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn get_user_siloed(e: &Env, user: &Address) -> bool {
+    let key = PoolDataKey::Siloed(user.clone());
+    get_persistent_default(e, &key, || false, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set if a user's supplied assets are siloed and can't be used as collateral
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `siloed` - If the user's supplied assets are siloed
+pub fn set_user_siloed(e: &Env, user: &Address, siloed: bool) {
+    let key = PoolDataKey::Siloed(user.clone());
+    if siloed {
+        e.storage().persistent().set::<PoolDataKey, bool>(&key, &true);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+/// Check if a depositor can supply collateral on behalf of a user
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `depositor` - The address of the depositor
+pub fn get_deposits_allowed(e: &Env, user: &Address, depositor: &Address) -> bool {
+    let key = PoolDataKey::DepositFrom(DepositorKey {
+        user: user.clone(),
+        depositor: depositor.clone(),
+    });
+    get_persistent_default(e, &key, || false, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set if a depositor can supply collateral on behalf of a user
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `depositor` - The address of the depositor
+/// * `allowed` - If the depositor can supply collateral on behalf of the user
+pub fn set_deposits_allowed(e: &Env, user: &Address, depositor: &Address, allowed: bool) {
+    let key = PoolDataKey::DepositFrom(DepositorKey {
+        user: user.clone(),
+        depositor: depositor.clone(),
+    });
+    if allowed {
+        e.storage().persistent().set::<PoolDataKey, bool>(&key, &true);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+/********** Borrower Allowlist ***********/
+
+/// Check if borrowing from the pool is restricted to the borrower allowlist
+pub fn get_borrow_allowlist(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, BORROW_ALLOWLIST_KEY))
+        .unwrap_or(false)
+}
+
+/// Set if borrowing from the pool is restricted to the borrower allowlist
+///
+/// ### Arguments
+/// * `enabled` - If borrowing is restricted to the borrower allowlist
+pub fn set_borrow_allowlist(e: &Env, enabled: bool) {
+    e.storage()
+        .instance()
+        .set::<Symbol, bool>(&Symbol::new(e, BORROW_ALLOWLIST_KEY), &enabled);
+}
+
+/// Check if a user is on the pool's borrower allowlist
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn get_allowed_borrower(e: &Env, user: &Address) -> bool {
+    let key = PoolDataKey::Borrower(user.clone());
+    get_persistent_default(e, &key, || false, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+}
+
+/// Set if a user is on the pool's borrower allowlist
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `allowed` - If the user is on the borrower allowlist
+pub fn set_allowed_borrower(e: &Env, user: &Address, allowed: bool) {
+    let key = PoolDataKey::Borrower(user.clone());
+    if allowed {
+        e.storage().persistent().set::<PoolDataKey, bool>(&key, &true);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+/********** Liquidator Allowlist ***********/
+
+/// Check if user liquidations in the pool are restricted to the liquidator allowlist
+#[cfg(feature = "auctions")]
+pub fn get_liquidator_allowlist(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, LIQUIDATOR_ALLOWLIST_KEY))
+        .unwrap_or(false)
+}
+
+/// Set if user liquidations in the pool are restricted to the liquidator allowlist
+///
+/// ### Arguments
+/// * `enabled` - If user liquidations are restricted to the liquidator allowlist
+#[cfg(feature = "auctions")]
+pub fn set_liquidator_allowlist(e: &Env, enabled: bool) {
+    e.storage()
+        .instance()
+        .set::<Symbol, bool>(&Symbol::new(e, LIQUIDATOR_ALLOWLIST_KEY), &enabled);
+}
+
+/// Check if an address is on the pool's liquidator allowlist
+///
+/// ### Arguments
+/// * `liquidator` - The address of the liquidator
+#[cfg(feature = "auctions")]
+pub fn get_allowed_liquidator(e: &Env, liquidator: &Address) -> bool {
+    let key = PoolDataKey::Liquidator(liquidator.clone());
+    get_persistent_default(e, &key, || false, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
 }
 
-/// Fetch a queued reserve set
+/// Set if an address is on the pool's liquidator allowlist
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the asset
-///
-/// ### Panics
-/// If the reserve set has not been queued
-pub fn get_queued_reserve_set(e: &Env, asset: &Address) -> QueuedReserveInit {
-    let key = PoolDataKey::ResInit(asset.clone());
+/// * `liquidator` - The address of the liquidator
+/// * `allowed` - If the liquidator is on the liquidator allowlist
+#[cfg(feature = "auctions")]
+pub fn set_allowed_liquidator(e: &Env, liquidator: &Address, allowed: bool) {
+    let key = PoolDataKey::Liquidator(liquidator.clone());
+    if allowed {
+        e.storage().persistent().set::<PoolDataKey, bool>(&key, &true);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+/********** Health Warnings ***********/
+
+/// Fetch the health factor under which a submit emits a health warning for the user, or 0 if
+/// health warnings are disabled (7 decimals)
+pub fn get_health_warning_threshold(e: &Env) -> u32 {
     e.storage()
-        .temporary()
-        .get::<PoolDataKey, QueuedReserveInit>(&key)
-        .unwrap_optimized()
+        .instance()
+        .get(&Symbol::new(e, HEALTH_WARNING_KEY))
+        .unwrap_or(0)
 }
 
-/// Check if a reserve is actively queued
+/// Set the health factor under which a submit emits a health warning for the user
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the asset
-pub fn has_queued_reserve_set(e: &Env, asset: &Address) -> bool {
-    let key = PoolDataKey::ResInit(asset.clone());
-    e.storage().temporary().has(&key)
+/// * `threshold` - The health factor (7 decimals), or 0 to disable
+pub fn set_health_warning_threshold(e: &Env, threshold: &u32) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, HEALTH_WARNING_KEY), threshold);
 }
 
-/// Set a new queued reserve set
+/********** Minimum Position Value ***********/
+
+/// Fetch the minimum value, in the oracle's base asset, of a collateral or liability position
+/// left by a request, or 0 if there is no minimum
+pub fn get_min_position_value(e: &Env) -> i128 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, MIN_POSITION_VALUE_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the minimum value of a collateral or liability position left by a request
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the asset
-/// * `config` - The reserve configuration for the asset
-pub fn set_queued_reserve_set(e: &Env, res_init: &QueuedReserveInit, asset: &Address) {
-    let key = PoolDataKey::ResInit(asset.clone());
+/// * `value` - The value in the oracle's base asset, or 0 for no minimum
+pub fn set_min_position_value(e: &Env, value: &i128) {
     e.storage()
-        .temporary()
-        .set::<PoolDataKey, QueuedReserveInit>(&key, res_init);
+        .instance()
+        .set::<Symbol, i128>(&Symbol::new(e, MIN_POSITION_VALUE_KEY), value);
+}
+
+/********** Position Snapshots ***********/
+
+/// Fetch the most recently declared position snapshot, if any
+pub fn get_position_snapshot(e: &Env) -> Option<PositionSnapshot> {
     e.storage()
-        .temporary()
-        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+        .instance()
+        .get(&Symbol::new(e, POSITION_SNAPSHOT_KEY))
 }
 
-/// Delete a queued reserve set
+/// Set the most recently declared position snapshot
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the asset
-///
-/// ### Panics
-/// If the reserve set has not been queued
-pub fn del_queued_reserve_set(e: &Env, asset: &Address) {
-    let key = PoolDataKey::ResInit(asset.clone());
-    e.storage().temporary().remove(&key);
+/// * `snapshot` - The position snapshot
+pub fn set_position_snapshot(e: &Env, snapshot: &PositionSnapshot) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PositionSnapshot>(&Symbol::new(e, POSITION_SNAPSHOT_KEY), snapshot);
 }
 
-/********** Reserve Data (ResData) **********/
-
-/// Fetch the reserve data for an asset
+/// Fetch the positions of a user's sub-account recorded for a snapshot, if any
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the asset
-///
-/// ### Panics
-/// If the reserve does not exist
-pub fn get_res_data(e: &Env, asset: &Address) -> ReserveData {
-    let key = PoolDataKey::ResData(asset.clone());
-    e.storage()
-        .persistent()
-        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
-    e.storage()
-        .persistent()
-        .get::<PoolDataKey, ReserveData>(&key)
-        .unwrap_optimized()
+/// * `id` - The ID of the snapshot
+/// * `user` - The address of the user
+/// * `account_id` - The ID of the sub-account
+pub fn get_user_position_snapshot(
+    e: &Env,
+    id: u32,
+    user: &Address,
+    account_id: u32,
+) -> Option<Positions> {
+    let key = PoolDataKey::UserSnap(UserSnapshotKey {
+        id,
+        user: user.clone(),
+        account_id,
+    });
+    get_persistent_default(e, &key, || None, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
 }
 
-/// Set the reserve data for an asset
+/// Set the positions of a user's sub-account recorded for a snapshot
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the asset
-/// * `data` - The reserve data for the asset
-pub fn set_res_data(e: &Env, asset: &Address, data: &ReserveData) {
-    let key = PoolDataKey::ResData(asset.clone());
+/// * `id` - The ID of the snapshot
+/// * `user` - The address of the user
+/// * `account_id` - The ID of the sub-account
+/// * `positions` - The positions of the sub-account at the snapshot ledger
+pub fn set_user_position_snapshot(
+    e: &Env,
+    id: u32,
+    user: &Address,
+    account_id: u32,
+    positions: &Positions,
+) {
+    let key = PoolDataKey::UserSnap(UserSnapshotKey {
+        id,
+        user: user.clone(),
+        account_id,
+    });
     e.storage()
         .persistent()
-        .set::<PoolDataKey, ReserveData>(&key, data);
+        .set::<PoolDataKey, Positions>(&key, positions);
     e.storage()
         .persistent()
-        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
 
-/********** Reserve List (ResList) **********/
+/********** Referrals ***********/
 
-/// Fetch the list of reserves
-pub fn get_res_list(e: &Env) -> Vec<Address> {
-    get_persistent_default(
-        e,
-        &Symbol::new(e, RES_LIST_KEY),
-        || vec![e],
-        LEDGER_THRESHOLD_SHARED,
-        LEDGER_BUMP_SHARED,
-    )
+/// Fetch the share of the backstop take rate paid to referrers, or 0 if none is set (7 decimals)
+pub fn get_referral_rate(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&Symbol::new(e, REFERRAL_RATE_KEY))
+        .unwrap_or(0)
 }
 
-/// Add a reserve to the back of the list and returns the index
+/// Set the share of the backstop take rate paid to referrers
 ///
 /// ### Arguments
-/// * `asset` - The contract address of the underlying asset
-///
-/// ### Panics
-/// If the number of reserves in the list exceeds 32
-///
-// @dev: Once added it can't be removed
-pub fn push_res_list(e: &Env, asset: &Address) -> u32 {
-    let mut res_list = get_res_list(e);
-    if res_list.len() == 32 {
-        panic_with_error!(e, PoolError::BadRequest)
-    }
-    res_list.push_back(asset.clone());
-    let new_index = res_list.len() - 1;
+/// * `rate` - The share of the backstop take rate (7 decimals)
+pub fn set_referral_rate(e: &Env, rate: &u32) {
     e.storage()
-        .persistent()
-        .set::<Symbol, Vec<Address>>(&Symbol::new(e, RES_LIST_KEY), &res_list);
-    e.storage().persistent().extend_ttl(
-        &Symbol::new(e, RES_LIST_KEY),
-        LEDGER_THRESHOLD_SHARED,
-        LEDGER_BUMP_SHARED,
-    );
-    new_index
+        .instance()
+        .set::<Symbol, u32>(&Symbol::new(e, REFERRAL_RATE_KEY), rate);
 }
 
-/********** Reserve Emissions **********/
-
-/// Fetch the emission data for the reserve b or d token
+/// Fetch the referrer attributed to a user, if one exists
 ///
 /// ### Arguments
-/// * `res_token_index` - The d/bToken index for the reserve
-pub fn get_res_emis_data(e: &Env, res_token_index: &u32) -> Option<ReserveEmissionData> {
-    let key = PoolDataKey::EmisData(*res_token_index);
-    get_persistent_default(
-        e,
-        &key,
-        || None,
-        LEDGER_THRESHOLD_SHARED,
-        LEDGER_BUMP_SHARED,
-    )
+/// * `user` - The address of the user
+pub fn get_referrer(e: &Env, user: &Address) -> Option<Address> {
+    let key = PoolDataKey::Referrer(user.clone());
+    let result = e.storage().persistent().get::<PoolDataKey, Address>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
 }
 
-/// Set the emission data for the reserve b or d token
+/// Set the referrer attributed to a user
 ///
 /// ### Arguments
-/// * `res_token_index` - The d/bToken index for the reserve
-/// * `res_emis_data` - The new emission data for the reserve token
-pub fn set_res_emis_data(e: &Env, res_token_index: &u32, res_emis_data: &ReserveEmissionData) {
-    let key = PoolDataKey::EmisData(*res_token_index);
+/// * `user` - The address of the user
+/// * `referrer` - The address of the referrer
+pub fn set_referrer(e: &Env, user: &Address, referrer: &Address) {
+    let key = PoolDataKey::Referrer(user.clone());
     e.storage()
         .persistent()
-        .set::<PoolDataKey, ReserveEmissionData>(&key, res_emis_data);
+        .set::<PoolDataKey, Address>(&key, referrer);
     e.storage()
         .persistent()
-        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
 
-/********** User Emissions **********/
-
-/// Fetch the users emission data for a reserve's b or d token
+/// Fetch the dToken rate a user's liability in a reserve last paid referral fees at, if any
 ///
 /// ### Arguments
 /// * `user` - The address of the user
-/// * `res_token_index` - The d/bToken index for the reserve
-pub fn get_user_emissions(
-    e: &Env,
-    user: &Address,
-    res_token_index: &u32,
-) -> Option<UserEmissionData> {
-    let key = PoolDataKey::UserEmis(UserReserveKey {
+/// * `reserve_index` - The index of the reserve
+pub fn get_referral_d_rate(e: &Env, user: &Address, reserve_index: u32) -> Option<i128> {
+    let key = PoolDataKey::RefDRate(UserReserveKey {
         user: user.clone(),
-        reserve_id: *res_token_index,
+        reserve_id: reserve_index,
     });
-    get_persistent_default(e, &key, || None, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
+    let result = e.storage().persistent().get::<PoolDataKey, i128>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
 }
 
-/// Set the users emission data for a reserve's d or d token
+/// Set the dToken rate a user's liability in a reserve last paid referral fees at
 ///
 /// ### Arguments
 /// * `user` - The address of the user
-/// * `res_token_index` - The d/bToken index for the reserve
-/// * `data` - The new user emission d ata for the d/bToken
-pub fn set_user_emissions(e: &Env, user: &Address, res_token_index: &u32, data: &UserEmissionData) {
-    let key = PoolDataKey::UserEmis(UserReserveKey {
+/// * `reserve_index` - The index of the reserve
+/// * `d_rate` - The dToken rate
+pub fn set_referral_d_rate(e: &Env, user: &Address, reserve_index: u32, d_rate: i128) {
+    let key = PoolDataKey::RefDRate(UserReserveKey {
         user: user.clone(),
-        reserve_id: *res_token_index,
+        reserve_id: reserve_index,
     });
+    e.storage().persistent().set::<PoolDataKey, i128>(&key, &d_rate);
     e.storage()
         .persistent()
-        .set::<PoolDataKey, UserEmissionData>(&key, data)
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
 
-/********** Pool Emissions **********/
-
-/// Fetch the pool reserve emissions
-pub fn get_pool_emissions(e: &Env) -> Map<u32, u64> {
-    get_persistent_default(
-        e,
-        &Symbol::new(e, POOL_EMIS_KEY),
-        || map![e],
-        LEDGER_THRESHOLD_SHARED,
-        LEDGER_BUMP_SHARED,
-    )
+/// Fetch the referral fees owed to a referrer for a reserve, in the reserve's underlying asset
+///
+/// ### Arguments
+/// * `referrer` - The address of the referrer
+/// * `asset` - The underlying asset of the reserve
+pub fn get_referral_fees(e: &Env, referrer: &Address, asset: &Address) -> i128 {
+    let key = PoolDataKey::RefFees(ReferralFeeKey {
+        referrer: referrer.clone(),
+        asset: asset.clone(),
+    });
+    get_persistent_default(e, &key, || 0i128, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER)
 }
 
-/// Set the pool reserve emissions
+/// Set the referral fees owed to a referrer for a reserve
 ///
 /// ### Arguments
-/// * `emissions` - The map of emissions by reserve token id to share of emissions as
-///                 a percentage of 1e7 (e.g. 15% = 1500000)
-pub fn set_pool_emissions(e: &Env, emissions: &Map<u32, u64>) {
+/// * `referrer` - The address of the referrer
+/// * `asset` - The underlying asset of the reserve
+/// * `amount` - The amount of the reserve's underlying asset owed
+pub fn set_referral_fees(e: &Env, referrer: &Address, asset: &Address, amount: i128) {
+    let key = PoolDataKey::RefFees(ReferralFeeKey {
+        referrer: referrer.clone(),
+        asset: asset.clone(),
+    });
+    e.storage().persistent().set::<PoolDataKey, i128>(&key, &amount);
     e.storage()
         .persistent()
-        .set::<Symbol, Map<u32, u64>>(&Symbol::new(e, POOL_EMIS_KEY), emissions);
-    e.storage().persistent().extend_ttl(
-        &Symbol::new(e, POOL_EMIS_KEY),
-        LEDGER_THRESHOLD_SHARED,
-        LEDGER_BUMP_SHARED,
-    );
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
 
-/********** Auctions ***********/
+/********** Repayment Grace ***********/
 
-/// Fetch the auction data for an auction
+/// Fetch the repayment interest rebate config for a reserve, if it exists
 ///
 /// ### Arguments
-/// * `auction_type` - The type of auction
-/// * `user` - The user who is auctioning off assets
-///
-/// ### Panics
-/// If the auction does not exist
-pub fn get_auction(e: &Env, auction_type: &u32, user: &Address) -> AuctionData {
-    let key = PoolDataKey::Auction(AuctionKey {
-        user: user.clone(),
-        auct_type: *auction_type,
-    });
-    e.storage()
-        .temporary()
-        .get::<PoolDataKey, AuctionData>(&key)
-        .unwrap_optimized()
+/// * `asset` - The underlying asset of the reserve
+pub fn get_repay_grace_config(e: &Env, asset: &Address) -> Option<RepayGraceConfig> {
+    let key = PoolDataKey::RepayGrace(asset.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<PoolDataKey, RepayGraceConfig>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+    }
+    result
 }
 
-/// Check if an auction exists for the given type and user
+/// Set the repayment interest rebate config for a reserve
 ///
 /// ### Arguments
-/// * `auction_type` - The type of auction
-/// * `user` - The user who is auctioning off assets
-pub fn has_auction(e: &Env, auction_type: &u32, user: &Address) -> bool {
-    let key = PoolDataKey::Auction(AuctionKey {
-        user: user.clone(),
-        auct_type: *auction_type,
-    });
-    e.storage().temporary().has(&key)
+/// * `asset` - The underlying asset of the reserve
+/// * `config` - The repayment interest rebate config, or None to remove it
+pub fn set_repay_grace_config(e: &Env, asset: &Address, config: &Option<RepayGraceConfig>) {
+    let key = PoolDataKey::RepayGrace(asset.clone());
+    match config {
+        Some(config) => {
+            e.storage()
+                .persistent()
+                .set::<PoolDataKey, RepayGraceConfig>(&key, config);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
 }
 
-/// Set the the starting block for an auction
+/********** Liquidation Protection ***********/
+
+/// Fetch the liquidation protection contract registered by a user, if any
 ///
 /// ### Arguments
-/// * `auction_type` - The type of auction
-/// * `user` - The user who is auctioning off assets
-/// * `auction_data` - The auction data
-pub fn set_auction(e: &Env, auction_type: &u32, user: &Address, auction_data: &AuctionData) {
-    let key = PoolDataKey::Auction(AuctionKey {
-        user: user.clone(),
-        auct_type: *auction_type,
-    });
-    e.storage()
-        .temporary()
-        .set::<PoolDataKey, AuctionData>(&key, auction_data);
-    e.storage()
-        .temporary()
-        .extend_ttl(&key, LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+/// * `user` - The address of the user
+pub fn get_liquidation_protection(e: &Env, user: &Address) -> Option<Address> {
+    let key = PoolDataKey::Protection(user.clone());
+    let result = e.storage().persistent().get::<PoolDataKey, Address>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
 }
 
-/// Remove an auction
+/// Set the liquidation protection contract registered by a user
 ///
 /// ### Arguments
-/// * `auction_type` - The type of auction
-/// * `user` - The user who is auctioning off assets
-pub fn del_auction(e: &Env, auction_type: &u32, user: &Address) {
-    let key = PoolDataKey::Auction(AuctionKey {
-        user: user.clone(),
-        auct_type: *auction_type,
-    });
-    e.storage().temporary().remove(&key);
+/// * `user` - The address of the user
+/// * `protection` - The address of the protection contract, or None to remove it
+#[cfg(feature = "auctions")]
+pub fn set_liquidation_protection(e: &Env, user: &Address, protection: &Option<Address>) {
+    let key = PoolDataKey::Protection(user.clone());
+    match protection {
+        Some(protection) => {
+            e.storage()
+                .persistent()
+                .set::<PoolDataKey, Address>(&key, protection);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
 }
@@ -1,21 +1,23 @@
 #![cfg(test)]
 
 use crate::{
-    constants::{SCALAR_7, SCALAR_9},
+    constants::{SCALAR_12, SCALAR_7},
     pool::Reserve,
-    storage::{self, ReserveConfig, ReserveData},
+    storage::{self, PoolConfig, ReserveConfig, ReserveData},
     PoolContract,
 };
 use blend_contract_sdk::emitter::{Client as EmitterClient, WASM as EmitterWASM};
-use sep_40_oracle::testutils::{MockPriceOracleClient, MockPriceOracleWASM};
+use sep_40_oracle::testutils::{Asset, MockPriceOracleClient, MockPriceOracleWASM};
 use sep_41_token::testutils::{MockTokenClient, MockTokenWASM};
 use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{
     testutils::Address as _, unwrap::UnwrapOptimized, vec, Address, BytesN, Env, IntoVal, String,
+    Symbol, Vec,
 };
 
 use backstop::{BackstopClient, BackstopContract};
 use mock_pool_factory::{MockPoolFactory, MockPoolFactoryClient, PoolInitMeta};
+use mock_swap_adapter::{MockSwapAdapter, MockSwapAdapterClient};
 use moderc3156_example::{
     FlashLoanReceiverModifiedERC3156, FlashLoanReceiverModifiedERC3156Client,
 };
@@ -33,6 +35,7 @@ pub(crate) fn create_pool(e: &Env) -> Address {
             Address::generate(e),
             0_1000000u32,
             4u32,
+            0u32,
             Address::generate(e),
             Address::generate(e),
         ),
@@ -135,6 +138,7 @@ pub(crate) fn create_backstop<'a>(
             usdc_token,
             pool_factory,
             vec![e, (pool_address.clone(), 40_000_000 * SCALAR_7)],
+            Address::generate(e),
         ),
     );
     e.as_contract(pool_address, || {
@@ -194,6 +198,17 @@ pub fn create_flashloan_receiver<'a>(
     )
 }
 
+/// Create a swap adapter contract.
+///
+/// This keeps the tokens sent to it and pays out the swapped tokens from
+/// its own balance for test purposes.
+pub fn create_swap_adapter<'a>(e: &Env) -> (Address, MockSwapAdapterClient<'a>) {
+    let contract_id = Address::generate(e);
+    e.register_at(&contract_id, MockSwapAdapter {}, ());
+
+    (contract_id.clone(), MockSwapAdapterClient::new(e, &contract_id))
+}
+
 //************************************************
 //            Object Creation Helpers
 //************************************************
@@ -209,14 +224,23 @@ pub(crate) fn default_reserve(e: &Env) -> Reserve {
         max_util: 0_9500000,
         last_time: 0,
         scalar: 1_0000000,
-        d_rate: 1_000_000_000,
-        b_rate: 1_000_000_000,
+        d_rate: 1_000_000_000_000,
+        b_rate: 1_000_000_000_000,
         ir_mod: 1_000_000_000,
         b_supply: 100_0000000,
         d_supply: 75_0000000,
         backstop_credit: 0,
         collateral_cap: 1000000000000000000,
+        debt_cap: 1000000000000000000,
         enabled: true,
+        min_supply_ledgers: 0,
+        isolated: false,
+        debt_ceiling: 0,
+        isolated_debt: 0,
+        category: 0,
+        util_accum: 0,
+        accrued_fees: 0,
+        clamped_rate: None,
     }
 }
 
@@ -235,16 +259,24 @@ pub(crate) fn default_reserve_meta() -> (ReserveConfig, ReserveData) {
             reactivity: 0_0000020, // 2e-6
             index: 0,
             collateral_cap: 1000000000000000000,
+            debt_cap: 1000000000000000000,
             enabled: true,
+            min_supply_ledgers: 0,
+            isolated: false,
+            debt_ceiling: 0,
+            category: 0,
         },
         ReserveData {
-            b_rate: 1_000_000_000,
-            d_rate: 1_000_000_000,
+            b_rate: 1_000_000_000_000,
+            d_rate: 1_000_000_000_000,
             ir_mod: 1_000_000_000,
             b_supply: 100_0000000,
             d_supply: 75_0000000,
             last_time: 0,
             backstop_credit: 0,
+            isolated_debt: 0,
+            util_accum: 0,
+            accrued_fees: 0,
         },
     )
 }
@@ -274,14 +306,62 @@ pub(crate) fn create_reserve(
     // mint pool assets to set expected b_rate
     let total_supply = reserve_data
         .b_supply
-        .fixed_mul_floor(reserve_data.b_rate, SCALAR_9)
+        .fixed_mul_floor(reserve_data.b_rate, SCALAR_12)
         .unwrap_optimized();
     let total_liabilities = reserve_data
         .d_supply
-        .fixed_mul_floor(reserve_data.d_rate, SCALAR_9)
+        .fixed_mul_floor(reserve_data.d_rate, SCALAR_12)
         .unwrap_optimized();
     let to_mint_pool = total_supply - total_liabilities + reserve_data.backstop_credit;
     underlying_client
         .mock_all_auths()
         .mint(&pool_address, &to_mint_pool);
 }
+
+/// Create a pool with a reserve for each (config, data, price) entry, and a default pool config.
+/// The reserves are priced in USD with 7 decimals by a mock oracle.
+///
+/// Returns the pool address and the underlying asset of each reserve
+pub(crate) fn create_pool_with_reserves(
+    e: &Env,
+    reserves: &[(ReserveConfig, ReserveData, i128)],
+) -> (Address, Vec<Address>) {
+    let bombadil = Address::generate(e);
+    let pool = create_pool(e);
+    let (oracle, oracle_client) = create_mock_oracle(e);
+
+    let mut assets = vec![e];
+    let mut oracle_assets = vec![e];
+    let mut prices = vec![e];
+    for (reserve_config, reserve_data, price) in reserves.iter() {
+        let (underlying, _) = create_token_contract(e, &bombadil);
+        create_reserve(e, &pool, &underlying, reserve_config, reserve_data);
+        oracle_assets.push_back(Asset::Stellar(underlying.clone()));
+        prices.push_back(*price);
+        assets.push_back(underlying);
+    }
+    oracle_client.set_data(
+        &bombadil,
+        &Asset::Other(Symbol::new(e, "USD")),
+        &oracle_assets,
+        &7,
+        &300,
+    );
+    oracle_client.set_price_stable(&prices);
+
+    let pool_config = PoolConfig {
+        oracle,
+        bstop_rate: 0_1000000,
+        status: 0,
+        max_positions: 4,
+        flash_loan_fee: 0,
+        hf_strategy: 0,
+        fallback_oracle: None,
+        min_health_factor: 1_0000100,
+        max_account_leverage: 0,
+    };
+    e.as_contract(&pool, || {
+        storage::set_pool_config(e, &pool_config);
+    });
+    (pool, assets)
+}
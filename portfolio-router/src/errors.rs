@@ -0,0 +1,18 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+/// Error codes for the portfolio router contract. Common errors are codes that match up with the
+/// built-in contracts error reporting. Portfolio router specific errors start at 1900.
+pub enum PortfolioRouterError {
+    // Common Errors
+    InternalError = 1,
+
+    // Portfolio Router
+    BadRequest = 1900,
+    PoolNotRegistered = 1901,
+    MaxPoolsExceeded = 1902,
+    InvalidHf = 1903,
+    OracleMismatch = 1904,
+}
@@ -0,0 +1,58 @@
+use soroban_sdk::{Address, Env, Symbol};
+
+pub struct PortfolioRouterEvents {}
+
+impl PortfolioRouterEvents {
+    /// Emitted when a pool is registered
+    ///
+    /// - topics - `["add_pool", admin: Address]`
+    /// - data - `pool: Address`
+    ///
+    /// ### Arguments
+    /// * `admin` - The admin of the contract
+    /// * `pool` - The registered pool
+    pub fn add_pool(e: &Env, admin: Address, pool: Address) {
+        let topics = (Symbol::new(e, "add_pool"), admin);
+        e.events().publish(topics, pool);
+    }
+
+    /// Emitted when a pool is unregistered
+    ///
+    /// - topics - `["remove_pool", admin: Address]`
+    /// - data - `pool: Address`
+    ///
+    /// ### Arguments
+    /// * `admin` - The admin of the contract
+    /// * `pool` - The unregistered pool
+    pub fn remove_pool(e: &Env, admin: Address, pool: Address) {
+        let topics = (Symbol::new(e, "remove_pool"), admin);
+        e.events().publish(topics, pool);
+    }
+
+    /// Emitted when the minimum combined health factor is set
+    ///
+    /// - topics - `["set_min_health_factor", admin: Address]`
+    /// - data - `min_health_factor: u32`
+    ///
+    /// ### Arguments
+    /// * `admin` - The admin of the contract
+    /// * `min_health_factor` - The new minimum combined health factor
+    pub fn set_min_health_factor(e: &Env, admin: Address, min_health_factor: u32) {
+        let topics = (Symbol::new(e, "set_min_health_factor"), admin);
+        e.events().publish(topics, min_health_factor);
+    }
+
+    /// Emitted when requests are routed to the registered pools
+    ///
+    /// - topics - `["submit", from: Address]`
+    /// - data - `[pools: u32, health_factor: i128]`
+    ///
+    /// ### Arguments
+    /// * `from` - The address the requests were submitted for
+    /// * `pools` - The number of pools the requests were routed to
+    /// * `health_factor` - The combined health factor after the requests
+    pub fn submit(e: &Env, from: Address, pools: u32, health_factor: i128) {
+        let topics = (Symbol::new(e, "submit"), from);
+        e.events().publish(topics, (pools, health_factor));
+    }
+}
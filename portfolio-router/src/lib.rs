@@ -0,0 +1,15 @@
+#![no_std]
+
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+
+mod dependencies;
+mod errors;
+mod events;
+mod portfolio_router;
+mod storage;
+
+pub use dependencies::Request;
+pub use errors::PortfolioRouterError;
+pub use portfolio_router::*;
+pub use storage::PortfolioRouterConfig;
@@ -0,0 +1,277 @@
+use crate::{
+    dependencies::{PoolClient, Request},
+    errors::PortfolioRouterError,
+    events::PortfolioRouterEvents,
+    storage::{self, PortfolioRouterConfig},
+};
+use sep_40_oracle::{Asset, PriceFeedClient};
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    contract, contractimpl, contracttype, panic_with_error, unwrap::UnwrapOptimized, Address, Env,
+    Vec,
+};
+
+const SCALAR_7: i128 = 1_0000000;
+
+// the maximum number of pools that can be registered, to bound the cost of loading a portfolio
+const MAX_POOLS: u32 = 8;
+
+/// The requests to submit to a single pool
+#[derive(Clone)]
+#[contracttype]
+pub struct PoolRequests {
+    pub pool: Address,
+    pub requests: Vec<Request>,
+}
+
+/// The combined value of a user's positions across the registered pools, denominated in the
+/// pools' oracle base asset
+#[derive(Clone)]
+#[contracttype]
+pub struct Portfolio {
+    pub collateral: i128,            // the collateral balance
+    pub liabilities: i128,           // the liability balance
+    pub effective_collateral: i128,  // the collateral balance weighted by its collateral factors
+    pub effective_liabilities: i128, // the liability balance weighted by its liability factors
+    // the combined health factor, expressed in 7 decimals. i128::MAX if there are no liabilities
+    pub health_factor: i128,
+}
+
+/// ### PortfolioRouter
+///
+/// A view of a user's positions across a set of registered pools as a combined health factor,
+/// and a router for requests to multiple pools in a single call.
+///
+/// This is not portfolio margin. Collateral in one pool is never recognized by another, as each
+/// pool can only liquidate the collateral it holds. Each pool still requires the user's positions
+/// within it to be healthy, and users can still submit to the pools directly. A routed submit only
+/// adds a check that the combined health factor stays above the contract's minimum.
+///
+/// The registered pools must price their positions in the same oracle base asset and decimals, as
+/// their position values are summed directly. Only the main account of a user is included in the
+/// portfolio.
+#[contract]
+pub struct PortfolioRouterContract;
+
+#[contractimpl]
+impl PortfolioRouterContract {
+    /// Construct the portfolio router contract
+    ///
+    /// ### Arguments
+    /// * `admin` - The admin that registers pools
+    /// * `min_health_factor` - The minimum combined health factor after a routed submit,
+    ///                         expressed in 7 decimals
+    pub fn __constructor(e: Env, admin: Address, min_health_factor: u32) {
+        require_valid_health_factor(&e, min_health_factor);
+        storage::set_config(
+            &e,
+            &PortfolioRouterConfig {
+                admin,
+                min_health_factor,
+            },
+        );
+    }
+
+    /// Fetch the contract config
+    pub fn get_config(e: Env) -> PortfolioRouterConfig {
+        storage::get_config(&e)
+    }
+
+    /// Fetch the registered pools
+    pub fn get_pools(e: Env) -> Vec<Address> {
+        storage::get_pools(&e)
+    }
+
+    /// (Admin only) Register a pool
+    ///
+    /// ### Arguments
+    /// * `pool` - The pool to register
+    ///
+    /// ### Panics
+    /// If the pool is already registered, the maximum number of pools is registered, or the pool's
+    /// oracles don't share the base asset and decimals of the registered pools
+    pub fn add_pool(e: Env, pool: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_config(&e).admin;
+        admin.require_auth();
+
+        let mut pools = storage::get_pools(&e);
+        if pools.contains(&pool) {
+            panic_with_error!(&e, PortfolioRouterError::BadRequest);
+        }
+        if pools.len() >= MAX_POOLS {
+            panic_with_error!(&e, PortfolioRouterError::MaxPoolsExceeded);
+        }
+        let quote = load_pool_quote(&e, &pool);
+        if let Some(registered_pool) = pools.first() {
+            if load_pool_quote(&e, &registered_pool) != quote {
+                panic_with_error!(&e, PortfolioRouterError::OracleMismatch);
+            }
+        }
+        pools.push_back(pool.clone());
+        storage::set_pools(&e, &pools);
+
+        PortfolioRouterEvents::add_pool(&e, admin, pool);
+    }
+
+    /// (Admin only) Unregister a pool
+    ///
+    /// ### Arguments
+    /// * `pool` - The pool to unregister
+    ///
+    /// ### Panics
+    /// If the pool is not registered
+    pub fn remove_pool(e: Env, pool: Address) {
+        storage::extend_instance(&e);
+        let admin = storage::get_config(&e).admin;
+        admin.require_auth();
+
+        let mut pools = storage::get_pools(&e);
+        match pools.first_index_of(&pool) {
+            Some(index) => pools.remove(index),
+            None => panic_with_error!(&e, PortfolioRouterError::PoolNotRegistered),
+        };
+        storage::set_pools(&e, &pools);
+
+        PortfolioRouterEvents::remove_pool(&e, admin, pool);
+    }
+
+    /// (Admin only) Set the minimum combined health factor after a routed submit
+    ///
+    /// ### Arguments
+    /// * `min_health_factor` - The new minimum combined health factor (7 decimals)
+    ///
+    /// ### Panics
+    /// If the health factor is under 1
+    pub fn set_min_health_factor(e: Env, min_health_factor: u32) {
+        storage::extend_instance(&e);
+        let mut config = storage::get_config(&e);
+        config.admin.require_auth();
+
+        require_valid_health_factor(&e, min_health_factor);
+        config.min_health_factor = min_health_factor;
+        storage::set_config(&e, &config);
+
+        PortfolioRouterEvents::set_min_health_factor(&e, config.admin, min_health_factor);
+    }
+
+    /// Fetch the combined value of a user's positions across the registered pools
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    ///
+    /// ### Panics
+    /// If the registered pools no longer share an oracle base asset and decimals
+    pub fn get_portfolio(e: Env, user: Address) -> Portfolio {
+        load_portfolio(&e, &user)
+    }
+
+    /// Submit requests for "from" to the registered pools, in order. "from" sends and receives
+    /// all tokens moved by the requests.
+    ///
+    /// Returns the combined value of the positions of "from" after the requests
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the user submitting the requests
+    /// * `requests` - The requests to submit to each pool
+    ///
+    /// ### Panics
+    /// If a pool is not registered, a pool rejects its requests, the registered pools no longer
+    /// share an oracle base asset and decimals, or the combined health factor of "from" is under
+    /// the minimum after the requests
+    pub fn submit(e: Env, from: Address, requests: Vec<PoolRequests>) -> Portfolio {
+        from.require_auth();
+        storage::extend_instance(&e);
+
+        if requests.is_empty() {
+            panic_with_error!(&e, PortfolioRouterError::BadRequest);
+        }
+        let pools = storage::get_pools(&e);
+        for pool_requests in requests.iter() {
+            if !pools.contains(&pool_requests.pool) {
+                panic_with_error!(&e, PortfolioRouterError::PoolNotRegistered);
+            }
+            PoolClient::new(&e, &pool_requests.pool).submit(
+                &from,
+                &from,
+                &from,
+                &pool_requests.requests,
+            );
+        }
+
+        let portfolio = load_portfolio(&e, &from);
+        let min_health_factor = storage::get_config(&e).min_health_factor as i128;
+        if portfolio.health_factor < min_health_factor {
+            panic_with_error!(&e, PortfolioRouterError::InvalidHf);
+        }
+
+        PortfolioRouterEvents::submit(&e, from, requests.len(), portfolio.health_factor);
+        portfolio
+    }
+}
+
+/// Sum the value of a user's positions in each registered pool
+///
+/// ### Panics
+/// If the registered pools don't share an oracle base asset and decimals, as a pool's admin can
+/// change its oracle after the pool is registered
+fn load_portfolio(e: &Env, user: &Address) -> Portfolio {
+    let mut portfolio = Portfolio {
+        collateral: 0,
+        liabilities: 0,
+        effective_collateral: 0,
+        effective_liabilities: 0,
+        health_factor: i128::MAX,
+    };
+    let mut portfolio_quote: Option<(Asset, u32)> = None;
+    for pool in storage::get_pools(e).iter() {
+        let quote = load_pool_quote(e, &pool);
+        if portfolio_quote
+            .as_ref()
+            .is_some_and(|portfolio_quote| portfolio_quote != &quote)
+        {
+            panic_with_error!(e, PortfolioRouterError::OracleMismatch);
+        }
+        portfolio_quote = Some(quote);
+        let values = PoolClient::new(e, &pool).get_positions_value(user);
+        for value in values.values().iter() {
+            portfolio.collateral += value.collateral;
+            portfolio.liabilities += value.liabilities;
+            portfolio.effective_collateral += value.effective_collateral;
+            portfolio.effective_liabilities += value.effective_liabilities;
+        }
+    }
+    if portfolio.effective_liabilities > 0 {
+        portfolio.health_factor = portfolio
+            .effective_collateral
+            .fixed_div_floor(portfolio.effective_liabilities, SCALAR_7)
+            .unwrap_optimized();
+    }
+    portfolio
+}
+
+/// Load the base asset and decimals a pool prices positions in. Pools normalize their prices to the
+/// highest decimals of their oracle and fallback oracle, and both oracles must quote the same base.
+///
+/// ### Panics
+/// If the pool's oracle and fallback oracle quote different base assets
+fn load_pool_quote(e: &Env, pool: &Address) -> (Asset, u32) {
+    let pool_config = PoolClient::new(e, pool).get_config();
+    let oracle = PriceFeedClient::new(e, &pool_config.oracle);
+    let base = oracle.base();
+    let mut decimals = oracle.decimals();
+    if let Some(fallback_oracle) = pool_config.fallback_oracle {
+        let fallback_oracle = PriceFeedClient::new(e, &fallback_oracle);
+        if fallback_oracle.base() != base {
+            panic_with_error!(e, PortfolioRouterError::OracleMismatch);
+        }
+        decimals = decimals.max(fallback_oracle.decimals());
+    }
+    (base, decimals)
+}
+
+fn require_valid_health_factor(e: &Env, health_factor: u32) {
+    if (health_factor as i128) < SCALAR_7 {
+        panic_with_error!(e, PortfolioRouterError::InvalidHf);
+    }
+}
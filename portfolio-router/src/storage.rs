@@ -0,0 +1,61 @@
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, vec, Address, Env, Symbol, Vec};
+
+/********** Ledger Thresholds **********/
+
+const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5s a ledger
+
+const LEDGER_THRESHOLD_INSTANCE: u32 = ONE_DAY_LEDGERS * 30; // ~ 30 days
+const LEDGER_BUMP_INSTANCE: u32 = LEDGER_THRESHOLD_INSTANCE + ONE_DAY_LEDGERS; // ~ 31 days
+
+const CONFIG_KEY: &str = "Config";
+const POOLS_KEY: &str = "Pools";
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PortfolioRouterConfig {
+    pub admin: Address,         // the admin that registers pools
+    pub min_health_factor: u32, // the minimum combined health factor after a routed submit, expressed in 7 decimals
+}
+
+/// Bump the instance rent for the contract
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LEDGER_THRESHOLD_INSTANCE, LEDGER_BUMP_INSTANCE);
+}
+
+/// Fetch the contract config
+pub fn get_config(e: &Env) -> PortfolioRouterConfig {
+    e.storage()
+        .instance()
+        .get::<Symbol, PortfolioRouterConfig>(&Symbol::new(e, CONFIG_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the contract config
+///
+/// ### Arguments
+/// * `config` - The config of the contract
+pub fn set_config(e: &Env, config: &PortfolioRouterConfig) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PortfolioRouterConfig>(&Symbol::new(e, CONFIG_KEY), config)
+}
+
+/// Fetch the registered pools
+pub fn get_pools(e: &Env) -> Vec<Address> {
+    e.storage()
+        .instance()
+        .get::<Symbol, Vec<Address>>(&Symbol::new(e, POOLS_KEY))
+        .unwrap_or(vec![e])
+}
+
+/// Set the registered pools
+///
+/// ### Arguments
+/// * `pools` - The registered pools
+pub fn set_pools(e: &Env, pools: &Vec<Address>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Vec<Address>>(&Symbol::new(e, POOLS_KEY), pools)
+}
@@ -0,0 +1,14 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// The share accounting exposed by the backstop that deployed the share token
+#[contractclient(name = "BackstopClient")]
+pub trait Backstop {
+    /// Fetch the amount of backstop shares "id" holds for "pool", excluding queued withdrawals
+    fn share_token_balance(e: Env, pool: Address, id: Address) -> i128;
+
+    /// Fetch the total amount of backstop shares issued for "pool", excluding queued withdrawals
+    fn share_token_supply(e: Env, pool: Address) -> i128;
+
+    /// Transfer backstop shares of "pool" from "from" to "to"
+    fn share_token_transfer(e: Env, pool: Address, from: Address, to: Address, amount: i128);
+}
@@ -0,0 +1,2 @@
+mod backstop;
+pub use backstop::BackstopClient;
@@ -0,0 +1,34 @@
+use soroban_sdk::{Address, Env, Symbol};
+
+pub struct ShareTokenEvents {}
+
+impl ShareTokenEvents {
+    /// Emitted when shares are transferred
+    ///
+    /// - topics - `["transfer", from: Address, to: Address]`
+    /// - data - `amount: i128`
+    ///
+    /// ### Arguments
+    /// * `from` - The address sending the shares
+    /// * `to` - The address receiving the shares
+    /// * `amount` - The amount of shares transferred
+    pub fn transfer(e: &Env, from: Address, to: Address, amount: i128) {
+        let topics = (Symbol::new(e, "transfer"), from, to);
+        e.events().publish(topics, amount);
+    }
+
+    /// Emitted when an allowance is set
+    ///
+    /// - topics - `["approve", from: Address, spender: Address]`
+    /// - data - `[amount: i128, expiration_ledger: u32]`
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the owner of the shares
+    /// * `spender` - The address allowed to spend the shares
+    /// * `amount` - The amount of shares allowed to be spent
+    /// * `expiration_ledger` - The ledger the allowance expires at
+    pub fn approve(e: &Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        let topics = (Symbol::new(e, "approve"), from, spender);
+        e.events().publish(topics, (amount, expiration_ledger));
+    }
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+
+mod dependencies;
+mod errors;
+mod events;
+mod share_token;
+mod storage;
+
+pub use errors::ShareTokenError;
+pub use share_token::*;
+pub use storage::{AllowanceDataKey, AllowanceValue, ShareTokenDataKey, ShareTokenMeta};
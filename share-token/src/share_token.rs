@@ -0,0 +1,193 @@
+use crate::{
+    dependencies::BackstopClient,
+    errors::ShareTokenError,
+    events::ShareTokenEvents,
+    storage::{self, AllowanceValue, ShareTokenMeta},
+};
+use soroban_sdk::{contract, contractimpl, panic_with_error, Address, Env, String};
+
+/// ### ShareToken
+///
+/// A SEP-41 token representing the backstop shares of a single pool, excluding shares queued
+/// for withdrawal. The backstop deploys a share token for a pool and remains the source of truth
+/// for all balances, so transfers move shares directly within the backstop.
+#[contract]
+pub struct ShareTokenContract;
+
+#[contractimpl]
+impl ShareTokenContract {
+    /// Construct the share token contract
+    ///
+    /// ### Arguments
+    /// * `backstop` - The backstop that issues the shares
+    /// * `pool` - The pool the shares insure
+    /// * `name` - The name of the share token
+    /// * `symbol` - The symbol of the share token
+    pub fn __constructor(e: Env, backstop: Address, pool: Address, name: String, symbol: String) {
+        storage::set_meta(
+            &e,
+            &ShareTokenMeta {
+                backstop,
+                pool,
+                name,
+                symbol,
+            },
+        );
+    }
+
+    /// Fetch the allowance "from" has given "spender"
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the owner of the shares
+    /// * `spender` - The address of the spender
+    pub fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        storage::extend_instance(&e);
+        storage::get_allowance(&e, &from, &spender).amount
+    }
+
+    /// Allow "spender" to spend up to "amount" of the shares of "from"
+    ///
+    /// ### Arguments
+    /// * `from` - The address of the owner of the shares
+    /// * `spender` - The address of the spender
+    /// * `amount` - The amount of shares "spender" can spend
+    /// * `expiration_ledger` - The ledger the allowance expires at
+    ///
+    /// ### Panics
+    /// If the amount is negative or the expiration ledger has passed for a non-zero amount
+    pub fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        from.require_auth();
+        storage::extend_instance(&e);
+
+        if amount < 0 {
+            panic_with_error!(&e, ShareTokenError::NegativeAmountError);
+        }
+        if amount > 0 && expiration_ledger < e.ledger().sequence() {
+            panic_with_error!(&e, ShareTokenError::InvalidExpirationLedger);
+        }
+        storage::set_allowance(
+            &e,
+            &from,
+            &spender,
+            &AllowanceValue {
+                amount,
+                expiration_ledger,
+            },
+        );
+
+        ShareTokenEvents::approve(&e, from, spender, amount, expiration_ledger);
+    }
+
+    /// Fetch the share balance of "id", excluding shares queued for withdrawal
+    ///
+    /// ### Arguments
+    /// * `id` - The address to fetch the balance of
+    pub fn balance(e: Env, id: Address) -> i128 {
+        storage::extend_instance(&e);
+        let meta = storage::get_meta(&e);
+        BackstopClient::new(&e, &meta.backstop).share_token_balance(&meta.pool, &id)
+    }
+
+    /// Fetch the total amount of shares issued by the backstop for the pool, excluding shares
+    /// queued for withdrawal
+    pub fn total_supply(e: Env) -> i128 {
+        storage::extend_instance(&e);
+        let meta = storage::get_meta(&e);
+        BackstopClient::new(&e, &meta.backstop).share_token_supply(&meta.pool)
+    }
+
+    /// Transfer "amount" shares from "from" to "to"
+    ///
+    /// ### Arguments
+    /// * `from` - The address sending the shares
+    /// * `to` - The address receiving the shares
+    /// * `amount` - The amount of shares to transfer
+    ///
+    /// ### Panics
+    /// If "from" does not hold enough shares or the backstop rejects the transfer
+    pub fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        storage::extend_instance(&e);
+
+        transfer_shares(&e, from, to, amount);
+    }
+
+    /// Transfer "amount" shares from "from" to "to", spending the allowance of "spender"
+    ///
+    /// ### Arguments
+    /// * `spender` - The address spending the shares
+    /// * `from` - The address sending the shares
+    /// * `to` - The address receiving the shares
+    /// * `amount` - The amount of shares to transfer
+    ///
+    /// ### Panics
+    /// If "spender" does not have enough allowance, or the transfer fails
+    pub fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        spender.require_auth();
+        storage::extend_instance(&e);
+
+        spend_allowance(&e, &from, &spender, amount);
+        transfer_shares(&e, from, to, amount);
+    }
+
+    /// Not supported, as shares can only be burnt by withdrawing from the backstop
+    pub fn burn(e: Env, _from: Address, _amount: i128) {
+        panic_with_error!(&e, ShareTokenError::NotSupported);
+    }
+
+    /// Not supported, as shares can only be burnt by withdrawing from the backstop
+    pub fn burn_from(e: Env, _spender: Address, _from: Address, _amount: i128) {
+        panic_with_error!(&e, ShareTokenError::NotSupported);
+    }
+
+    /// Fetch the decimals of the share token, which match the backstop's shares
+    pub fn decimals(_e: Env) -> u32 {
+        7
+    }
+
+    /// Fetch the name of the share token
+    pub fn name(e: Env) -> String {
+        storage::get_meta(&e).name
+    }
+
+    /// Fetch the symbol of the share token
+    pub fn symbol(e: Env) -> String {
+        storage::get_meta(&e).symbol
+    }
+
+    /// Fetch the backstop and pool of the share token
+    pub fn pool(e: Env) -> (Address, Address) {
+        let meta = storage::get_meta(&e);
+        (meta.backstop, meta.pool)
+    }
+}
+
+/// Move "amount" of shares from "from" to "to" in the backstop
+fn transfer_shares(e: &Env, from: Address, to: Address, amount: i128) {
+    if amount < 0 {
+        panic_with_error!(e, ShareTokenError::NegativeAmountError);
+    }
+    let meta = storage::get_meta(e);
+    BackstopClient::new(e, &meta.backstop).share_token_transfer(&meta.pool, &from, &to, &amount);
+
+    ShareTokenEvents::transfer(e, from, to, amount);
+}
+
+/// Spend "amount" of the allowance "from" has given "spender"
+fn spend_allowance(e: &Env, from: &Address, spender: &Address, amount: i128) {
+    let allowance = storage::get_allowance(e, from, spender);
+    if allowance.amount < amount {
+        panic_with_error!(e, ShareTokenError::AllowanceError);
+    }
+    if amount > 0 {
+        storage::set_allowance(
+            e,
+            from,
+            spender,
+            &AllowanceValue {
+                amount: allowance.amount - amount,
+                expiration_ledger: allowance.expiration_ledger,
+            },
+        );
+    }
+}
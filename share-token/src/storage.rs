@@ -0,0 +1,109 @@
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, String, Symbol};
+
+/********** Ledger Thresholds **********/
+
+const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5s a ledger
+
+const LEDGER_THRESHOLD_INSTANCE: u32 = ONE_DAY_LEDGERS * 30; // ~ 30 days
+const LEDGER_BUMP_INSTANCE: u32 = LEDGER_THRESHOLD_INSTANCE + ONE_DAY_LEDGERS; // ~ 31 days
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceDataKey {
+    pub from: Address,
+    pub spender: Address,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceValue {
+    pub amount: i128,
+    pub expiration_ledger: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ShareTokenDataKey {
+    Allowance(AllowanceDataKey),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ShareTokenMeta {
+    pub backstop: Address, // the backstop that issues the shares
+    pub pool: Address,     // the pool the shares insure
+    pub name: String,
+    pub symbol: String,
+}
+
+/// Bump the instance rent for the contract
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LEDGER_THRESHOLD_INSTANCE, LEDGER_BUMP_INSTANCE);
+}
+
+/// Fetch the share token metadata
+pub fn get_meta(e: &Env) -> ShareTokenMeta {
+    e.storage()
+        .instance()
+        .get::<Symbol, ShareTokenMeta>(&Symbol::new(e, "Meta"))
+        .unwrap_optimized()
+}
+
+/// Set the share token metadata
+///
+/// ### Arguments
+/// * `meta` - The metadata of the share token
+pub fn set_meta(e: &Env, meta: &ShareTokenMeta) {
+    e.storage()
+        .instance()
+        .set::<Symbol, ShareTokenMeta>(&Symbol::new(e, "Meta"), meta)
+}
+
+/// Fetch the allowance "from" has given "spender"
+///
+/// Returns an empty allowance if none exists or it has expired
+///
+/// ### Arguments
+/// * `from` - The address of the owner of the tokens
+/// * `spender` - The address of the spender
+pub fn get_allowance(e: &Env, from: &Address, spender: &Address) -> AllowanceValue {
+    let key = ShareTokenDataKey::Allowance(AllowanceDataKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    match e
+        .storage()
+        .temporary()
+        .get::<ShareTokenDataKey, AllowanceValue>(&key)
+    {
+        Some(allowance) if allowance.expiration_ledger >= e.ledger().sequence() => allowance,
+        _ => AllowanceValue {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+/// Set the allowance "from" has given "spender"
+///
+/// ### Arguments
+/// * `from` - The address of the owner of the tokens
+/// * `spender` - The address of the spender
+/// * `allowance` - The allowance
+pub fn set_allowance(e: &Env, from: &Address, spender: &Address, allowance: &AllowanceValue) {
+    let key = ShareTokenDataKey::Allowance(AllowanceDataKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    e.storage()
+        .temporary()
+        .set::<ShareTokenDataKey, AllowanceValue>(&key, allowance);
+    if allowance.amount > 0 {
+        let ledgers_to_live = allowance.expiration_ledger - e.ledger().sequence();
+        e.storage()
+            .temporary()
+            .extend_ttl(&key, ledgers_to_live, ledgers_to_live);
+    }
+}
@@ -15,6 +15,7 @@ pub fn create_backstop<'a>(
     usdc_token: &Address,
     pool_factory: &Address,
     drop_list: &Vec<(Address, i128)>,
+    governance: &Address,
 ) -> BackstopClient<'a> {
     if wasm {
         e.register_at(
@@ -27,6 +28,7 @@ pub fn create_backstop<'a>(
                 usdc_token,
                 pool_factory,
                 drop_list.clone(),
+                governance,
             ),
         );
     } else {
@@ -40,6 +42,7 @@ pub fn create_backstop<'a>(
                 usdc_token,
                 pool_factory,
                 drop_list.clone(),
+                governance,
             ),
         );
     }
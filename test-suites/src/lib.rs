@@ -1,12 +1,14 @@
 #![allow(clippy::all)]
+pub mod b_token;
 pub mod backstop;
 pub mod emitter;
 pub mod liquidity_pool;
 pub mod oracle;
 pub mod pool;
 pub mod pool_factory;
+pub mod share_token;
 mod setup;
-pub use setup::create_fixture_with_data;
+pub use setup::{create_fixture_with_data, create_fixture_with_economy, ECONOMY_ACTORS};
 pub mod assertions;
 pub mod moderc3156;
 pub mod snapshot;
@@ -1,6 +1,7 @@
 use moderc3156_example::{
     FlashLoanReceiverModifiedERC3156, FlashLoanReceiverModifiedERC3156Client,
 };
+use moderc3156_receiver::FlashLoanReceiverClient;
 use soroban_sdk::{testutils::Address as _, Address, Env};
 
 pub fn create_flashloan_receiver<'a>(
@@ -14,3 +15,10 @@ pub fn create_flashloan_receiver<'a>(
         FlashLoanReceiverModifiedERC3156Client::new(e, &contract_id),
     )
 }
+
+pub fn create_reference_flashloan_receiver<'a>(
+    e: &Env,
+    pool: &Address,
+) -> (Address, FlashLoanReceiverClient<'a>) {
+    moderc3156_receiver::testutils::create_flash_loan_receiver(e, pool)
+}
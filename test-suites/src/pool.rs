@@ -19,6 +19,11 @@ pub fn default_reserve_metadata() -> ReserveConfig {
         reactivity: 0_0000020, // 2e-6
         index: 0,
         collateral_cap: 1000000000000000000,
+        debt_cap: 1000000000000000000,
         enabled: true,
+        min_supply_ledgers: 0,
+        isolated: false,
+        debt_ceiling: 0,
+        category: 0,
     }
 }
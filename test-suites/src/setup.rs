@@ -1,5 +1,5 @@
 use pool::{Request, RequestType, ReserveEmissionMetadata};
-use soroban_sdk::{vec as svec, String, Vec as SVec};
+use soroban_sdk::{testutils::Address as _, vec as svec, Address, String, Vec as SVec};
 
 use crate::{
     pool::default_reserve_metadata,
@@ -139,10 +139,103 @@ pub fn create_fixture_with_data<'a>(wasm: bool) -> TestFixture<'a> {
     fixture
 }
 
+/// The number of simulated actors added by `create_fixture_with_economy`
+pub const ECONOMY_ACTORS: usize = 12;
+
+/// Create a test fixture with two pools in the reward zone and a dozen simulated actors
+///
+/// Builds on `create_fixture_with_data` and adds a second pool, "Kettle" (1), with STABLE (0) and XLM (1)
+/// reserves. The actors are appended to `fixture.users` after frodo, such that `users[1..7]` act in the
+/// Teapot pool (0) and `users[7..13]` act in the Kettle pool (1). Within each pool, even offsets are
+/// suppliers that post STABLE collateral, and odd offsets are borrowers that post XLM collateral and
+/// borrow STABLE.
+pub fn create_fixture_with_economy<'a>(wasm: bool) -> TestFixture<'a> {
+    let mut fixture = create_fixture_with_data(wasm);
+    let frodo = fixture.users[0].clone();
+
+    // create the second pool
+    fixture.create_pool(String::from_str(&fixture.env, "Kettle"), 0_2000000, 4);
+
+    let mut stable_config = default_reserve_metadata();
+    stable_config.decimals = 6;
+    stable_config.c_factor = 0_900_0000;
+    stable_config.l_factor = 0_950_0000;
+    stable_config.util = 0_850_0000;
+    fixture.create_pool_reserve(1, TokenIndex::STABLE, &stable_config);
+
+    let mut xlm_config = default_reserve_metadata();
+    xlm_config.c_factor = 0_750_0000;
+    xlm_config.l_factor = 0_750_0000;
+    xlm_config.util = 0_500_0000;
+    fixture.create_pool_reserve(1, TokenIndex::XLM, &xlm_config);
+
+    let pool_fixture = &fixture.pools[1];
+    let reserve_emissions: SVec<ReserveEmissionMetadata> = svec![
+        &fixture.env,
+        ReserveEmissionMetadata {
+            res_index: 0, // STABLE
+            res_type: 1,  // b_token
+            share: 1_000_0000
+        },
+    ];
+    pool_fixture.pool.set_emissions_config(&reserve_emissions);
+
+    // deposit into backstop, add to reward zone
+    fixture
+        .backstop
+        .deposit(&frodo, &pool_fixture.pool.address, &(50_000 * SCALAR_7));
+    fixture
+        .backstop
+        .add_reward(&pool_fixture.pool.address, &None);
+    pool_fixture.pool.set_status(&3);
+    pool_fixture.pool.update_status();
+
+    // create the actors and open their positions
+    for i in 0..ECONOMY_ACTORS {
+        let actor = Address::generate(&fixture.env);
+        fixture.tokens[TokenIndex::STABLE].mint(&actor, &(100_000 * 10i128.pow(6)));
+        fixture.tokens[TokenIndex::XLM].mint(&actor, &(1_000_000 * SCALAR_7));
+        fixture.tokens[TokenIndex::WETH].mint(&actor, &(10 * 10i128.pow(9)));
+
+        let pool_fixture = &fixture.pools[i / (ECONOMY_ACTORS / 2)];
+        let requests: SVec<Request> = if i % 2 == 0 {
+            svec![
+                &fixture.env,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: fixture.tokens[TokenIndex::STABLE].address.clone(),
+                    amount: 20_000 * 10i128.pow(6),
+                },
+            ]
+        } else {
+            svec![
+                &fixture.env,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: fixture.tokens[TokenIndex::XLM].address.clone(),
+                    amount: 100_000 * SCALAR_7,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: fixture.tokens[TokenIndex::STABLE].address.clone(),
+                    amount: 5_000 * 10i128.pow(6),
+                },
+            ]
+        };
+        pool_fixture.pool.submit(&actor, &actor, &actor, &requests);
+        fixture.users.push(actor);
+    }
+
+    fixture.jump(60 * 60); // 1 hr
+
+    fixture.env.cost_estimate().budget().reset_unlimited();
+    fixture
+}
+
 #[cfg(test)]
 mod tests {
 
-    use crate::test_fixture::PoolFixture;
+    use crate::{assertions::assert_approx_eq_rel, test_fixture::PoolFixture};
 
     use super::*;
 
@@ -259,4 +352,47 @@ mod tests {
             emis_data.expiration
         )
     }
+
+    #[test]
+    fn test_create_fixture_with_economy() {
+        let fixture = create_fixture_with_economy(false);
+
+        assert_eq!(fixture.pools.len(), 2);
+        assert_eq!(fixture.users.len(), 1 + ECONOMY_ACTORS);
+
+        // validate each pool is in the reward zone with an active status
+        for pool_fixture in fixture.pools.iter() {
+            let pool_config = pool_fixture.pool.get_config();
+            assert_eq!(pool_config.status, 1);
+            let pool_data = fixture.backstop.pool_data(&pool_fixture.pool.address);
+            assert!(pool_data.tokens >= 50_000 * SCALAR_7);
+        }
+
+        // validate actor positions
+        for (i, actor) in fixture.users[1..].iter().enumerate() {
+            let pool_fixture = &fixture.pools[i / (ECONOMY_ACTORS / 2)];
+            let stable_index = pool_fixture.reserves[&TokenIndex::STABLE];
+            let xlm_index = pool_fixture.reserves[&TokenIndex::XLM];
+            let positions = pool_fixture.pool.get_positions(actor);
+            if i % 2 == 0 {
+                assert_approx_eq_rel(
+                    positions.collateral.get_unchecked(stable_index),
+                    20_000 * 10i128.pow(6),
+                    0_0100000,
+                );
+                assert_eq!(positions.liabilities.len(), 0);
+            } else {
+                assert_approx_eq_rel(
+                    positions.collateral.get_unchecked(xlm_index),
+                    100_000 * SCALAR_7,
+                    0_0100000,
+                );
+                assert_approx_eq_rel(
+                    positions.liabilities.get_unchecked(stable_index),
+                    5_000 * 10i128.pow(6),
+                    0_0100000,
+                );
+            }
+        }
+    }
 }
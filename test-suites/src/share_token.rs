@@ -0,0 +1,6 @@
+mod share_token_contract {
+    soroban_sdk::contractimport!(
+        file = "../target/wasm32-unknown-unknown/optimized/share_token.wasm"
+    );
+}
+pub use share_token_contract::WASM as SHARE_TOKEN_WASM;
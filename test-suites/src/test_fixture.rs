@@ -19,6 +19,7 @@ use soroban_sdk::{vec as svec, Address, BytesN, Env, Map, String, Symbol};
 
 pub const SCALAR_7: i128 = 1_000_0000;
 pub const SCALAR_9: i128 = 1_000_000_000;
+pub const SCALAR_12: i128 = 1_000_000_000_000;
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum TokenIndex {
@@ -113,6 +114,7 @@ impl TestFixture<'_> {
                 (bombadil.clone(), 10_000_000 * SCALAR_7),
                 (frodo.clone(), 30_000_000 * SCALAR_7)
             ],
+            &bombadil,
         );
         let pool_hash = e.deployer().upload_contract_wasm(POOL_WASM);
         let pool_init_meta = PoolInitMeta {
@@ -181,6 +183,7 @@ impl TestFixture<'_> {
             &self.oracle.address,
             &backstop_take_rate,
             &max_positions,
+            &0,
         );
         self.pools.push(PoolFixture {
             pool: PoolClient::new(&self.env, &pool_id),
@@ -198,7 +201,7 @@ impl TestFixture<'_> {
         let token = &self.tokens[asset_index];
         pool_fixture
             .pool
-            .queue_set_reserve(&token.address, reserve_config);
+            .queue_set_reserve(&token.address, reserve_config, &None);
         let index = pool_fixture.pool.set_reserve(&token.address);
         pool_fixture.reserves.insert(asset_index, index);
         self.pools.insert(pool_index, pool_fixture);
@@ -0,0 +1,80 @@
+#![cfg(test)]
+use pool::{Request, RequestType};
+use sep_41_token::TokenClient;
+use soroban_sdk::{testutils::Address as _, vec, Address, String, Vec};
+use test_suites::{
+    b_token::B_TOKEN_WASM,
+    create_fixture_with_data,
+    test_fixture::{TokenIndex, SCALAR_7},
+};
+
+#[test]
+fn test_b_token() {
+    let fixture = create_fixture_with_data(false);
+    let pool_fixture = &fixture.pools[0];
+    let xlm = &fixture.tokens[TokenIndex::XLM];
+    let xlm_address = xlm.address.clone();
+    let xlm_pool_index = pool_fixture.reserves[&TokenIndex::XLM];
+
+    let samwise = Address::generate(&fixture.env);
+    let merry = Address::generate(&fixture.env);
+    xlm.mint(&samwise, &(100 * SCALAR_7));
+
+    // deploy the bToken for the XLM reserve
+    let b_token_hash = fixture.env.deployer().upload_contract_wasm(B_TOKEN_WASM);
+    fixture.backstop.set_b_token_wasm(&b_token_hash);
+    let b_token_address = pool_fixture.pool.deploy_b_token(
+        &xlm_address,
+        &String::from_str(&fixture.env, "Blend XLM"),
+        &String::from_str(&fixture.env, "bXLM"),
+    );
+    assert_eq!(pool_fixture.pool.get_b_token(&xlm_address), Some(b_token_address.clone()));
+    let b_token = TokenClient::new(&fixture.env, &b_token_address);
+    assert_eq!(b_token.decimals(), 7);
+    assert_eq!(b_token.symbol(), String::from_str(&fixture.env, "bXLM"));
+
+    // supply XLM and check the bToken tracks the supply position
+    let requests: Vec<Request> = vec![
+        &fixture.env,
+        Request {
+            request_type: RequestType::Supply as u32,
+            address: xlm_address.clone(),
+            amount: 100 * SCALAR_7,
+        },
+    ];
+    let positions = pool_fixture
+        .pool
+        .submit(&samwise, &samwise, &samwise, &requests);
+    let b_tokens = positions.supply.get_unchecked(xlm_pool_index);
+    assert_eq!(b_token.balance(&samwise), b_tokens);
+    assert_eq!(b_token.balance(&merry), 0);
+
+    // transfer half of the bTokens
+    let transfer_amount = b_tokens / 2;
+    b_token.transfer(&samwise, &merry, &transfer_amount);
+    assert_eq!(b_token.balance(&samwise), b_tokens - transfer_amount);
+    assert_eq!(b_token.balance(&merry), transfer_amount);
+    let merry_positions = pool_fixture.pool.get_positions(&merry);
+    assert_eq!(merry_positions.supply.get_unchecked(xlm_pool_index), transfer_amount);
+
+    // transferred bTokens can be withdrawn from the pool
+    let requests: Vec<Request> = vec![
+        &fixture.env,
+        Request {
+            request_type: RequestType::Withdraw as u32,
+            address: xlm_address.clone(),
+            amount: 100 * SCALAR_7,
+        },
+    ];
+    let positions = pool_fixture.pool.submit(&merry, &merry, &merry, &requests);
+    assert_eq!(positions.supply.len(), 0);
+    assert_eq!(b_token.balance(&merry), 0);
+    assert!(xlm.balance(&merry) >= 50 * SCALAR_7 - 1);
+
+    // bTokens can be spent with an allowance
+    let approval_ledger = fixture.env.ledger().sequence() + 17280;
+    b_token.approve(&samwise, &merry, &transfer_amount, &approval_ledger);
+    b_token.transfer_from(&merry, &samwise, &merry, &transfer_amount);
+    assert_eq!(b_token.allowance(&samwise, &merry), 0);
+    assert_eq!(b_token.balance(&merry), transfer_amount);
+}
@@ -520,6 +520,7 @@ fn test_backstop_constructor() {
             usdc_token.clone(),
             pool_factory.clone(),
             drop_list.clone(),
+            Address::generate(&e),
         ),
     );
 
@@ -590,6 +591,7 @@ fn test_backstop_constructor_over_max() {
             usdc_token.clone(),
             pool_factory.clone(),
             drop_list.clone(),
+            Address::generate(&e),
         ),
     );
 }
@@ -0,0 +1,60 @@
+#![cfg(test)]
+use claims::{ClaimsContract, ClaimsContractClient, PoolClaim};
+use soroban_sdk::vec;
+use test_suites::{create_fixture_with_data, test_fixture::TokenIndex};
+
+#[test]
+fn test_claim_all() {
+    let fixture = create_fixture_with_data(false);
+    let pool_fixture = &fixture.pools[0];
+    let frodo = fixture.users[0].clone();
+    let blnd = &fixture.tokens[TokenIndex::BLND];
+    let stable_pool_index = pool_fixture.reserves[&TokenIndex::STABLE];
+    let xlm_pool_index = pool_fixture.reserves[&TokenIndex::XLM];
+
+    let claims_address = fixture.env.register(
+        ClaimsContract {},
+        (fixture.backstop.address.clone(), blnd.address.clone()),
+    );
+    let claims = ClaimsContractClient::new(&fixture.env, &claims_address);
+    assert_eq!(claims.backstop(), fixture.backstop.address);
+
+    fixture.jump(7 * 24 * 60 * 60);
+
+    let blnd_balance = blnd.balance(&frodo);
+    let backstop_shares = fixture
+        .backstop
+        .user_balance(&pool_fixture.pool.address, &frodo)
+        .shares;
+    let pool_claims = vec![
+        &fixture.env,
+        PoolClaim {
+            pool: pool_fixture.pool.address.clone(),
+            reserve_token_ids: vec![
+                &fixture.env,
+                stable_pool_index * 2,
+                xlm_pool_index * 2 + 1,
+            ],
+            referral_assets: vec![&fixture.env],
+        },
+    ];
+    let transferred = claims.claim_all(&frodo, &pool_claims, &true);
+
+    // pool emissions are sent to frodo in a single transfer
+    let claimed = transferred.get_unchecked(blnd.address.clone());
+    assert!(claimed > 0);
+    assert_eq!(transferred.len(), 1);
+    assert_eq!(blnd.balance(&frodo), blnd_balance + claimed);
+    assert_eq!(blnd.balance(&claims_address), 0);
+
+    // backstop emissions are deposited into frodo's backstop position
+    let new_backstop_shares = fixture
+        .backstop
+        .user_balance(&pool_fixture.pool.address, &frodo)
+        .shares;
+    assert!(new_backstop_shares > backstop_shares);
+
+    // a second claim in the same ledger has nothing to transfer
+    let transferred = claims.claim_all(&frodo, &pool_claims, &false);
+    assert_eq!(transferred.len(), 0);
+}
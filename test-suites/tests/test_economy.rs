@@ -0,0 +1,337 @@
+#![cfg(test)]
+use pool::{FlashLoan, Request, RequestType};
+use soroban_sdk::{testutils::Address as _, vec, Address, Vec};
+use test_suites::{
+    assertions::assert_approx_eq_rel,
+    create_fixture_with_economy,
+    moderc3156::create_flashloan_receiver,
+    test_fixture::{TestFixture, TokenIndex, SCALAR_7},
+    ECONOMY_ACTORS,
+};
+
+/// Tank the XLM price such that every borrower in the economy is underwater
+fn tank_xlm_price(fixture: &TestFixture) {
+    fixture.oracle.set_price_stable(&vec![
+        &fixture.env,
+        2000_0000000, // eth
+        1_0000000,    // usdc
+        0_0100000,    // xlm
+        1_0000000,    // stable
+    ]);
+}
+
+/// Test pools correctly handle leaving and re-entering the reward zone
+#[test]
+fn test_economy_reward_zone_churn() {
+    let fixture = create_fixture_with_economy(false);
+    let frodo = &fixture.users[0];
+    let teapot = &fixture.pools[0].pool;
+    let kettle = &fixture.pools[1].pool;
+
+    // both pools receive emissions while in the reward zone
+    fixture.jump(60 * 60 * 24);
+    fixture.emitter.distribute();
+    fixture.backstop.distribute();
+    assert!(teapot.gulp_emissions() > 0);
+    assert!(kettle.gulp_emissions() > 0);
+
+    // frodo exits the kettle backstop, dropping it below the reward zone threshold
+    fixture
+        .backstop
+        .queue_withdrawal(frodo, &kettle.address, &(50_000 * SCALAR_7));
+    fixture.jump(60 * 60 * 24 * 21);
+    fixture.emitter.distribute();
+    fixture.backstop.distribute();
+    assert!(teapot.gulp_emissions() > 0);
+    fixture
+        .backstop
+        .withdraw(frodo, &kettle.address, &(50_000 * SCALAR_7));
+    fixture.backstop.remove_reward(&kettle.address);
+    assert_eq!(fixture.backstop.pool_data(&kettle.address).tokens, 0);
+
+    // the teapot continues to receive emissions alone
+    fixture.jump(60 * 60 * 24);
+    fixture.emitter.distribute();
+    fixture.backstop.distribute();
+    assert!(teapot.gulp_emissions() > 0);
+
+    // frodo re-enters the kettle backstop and the kettle rejoins the reward zone
+    fixture
+        .backstop
+        .deposit(frodo, &kettle.address, &(50_000 * SCALAR_7));
+    fixture.backstop.add_reward(&kettle.address, &None);
+
+    fixture.jump(60 * 60 * 24);
+    fixture.emitter.distribute();
+    fixture.backstop.distribute();
+    assert!(teapot.gulp_emissions() > 0);
+    assert!(kettle.gulp_emissions() > 0);
+}
+
+/// Test underwater borrowers in both pools are liquidated and their bad debt is
+/// auctioned off by each pool's backstop
+#[test]
+fn test_economy_bad_debt_cascade() {
+    let fixture = create_fixture_with_economy(false);
+    let stable = &fixture.tokens[TokenIndex::STABLE];
+    let xlm = &fixture.tokens[TokenIndex::XLM];
+
+    tank_xlm_price(&fixture);
+
+    // start a full liquidation for a borrower in each pool
+    for (pool_index, pool_fixture) in fixture.pools.iter().enumerate() {
+        let borrower = &fixture.users[2 + pool_index * ECONOMY_ACTORS / 2];
+        let auction_data = pool_fixture.pool.new_auction(
+            &0,
+            borrower,
+            &vec![&fixture.env, stable.address.clone()],
+            &vec![&fixture.env, xlm.address.clone()],
+            &100,
+        );
+        let positions = pool_fixture.pool.get_positions(borrower);
+        let xlm_index = pool_fixture.reserves[&TokenIndex::XLM];
+        assert_eq!(
+            auction_data.lot.get_unchecked(xlm.address.clone()),
+            positions.collateral.get_unchecked(xlm_index)
+        );
+    }
+
+    // let the bid decay such that the liquidations leave bad debt behind
+    fixture.jump_with_sequence(390 * 5);
+
+    for (pool_index, pool_fixture) in fixture.pools.iter().enumerate() {
+        let pool = &pool_fixture.pool;
+        let liquidator = &fixture.users[1 + pool_index * ECONOMY_ACTORS / 2];
+        let borrower = &fixture.users[2 + pool_index * ECONOMY_ACTORS / 2];
+        let stable_index = pool_fixture.reserves[&TokenIndex::STABLE];
+
+        let fill_requests: Vec<Request> = vec![
+            &fixture.env,
+            Request {
+                request_type: RequestType::FillUserLiquidationAuction as u32,
+                address: borrower.clone(),
+                amount: 100,
+            },
+        ];
+        pool.submit(liquidator, liquidator, liquidator, &fill_requests);
+
+        // the borrower is left with only liabilities, which are moved to the backstop
+        let borrower_positions = pool.get_positions(borrower);
+        assert_eq!(borrower_positions.collateral.len(), 0);
+        let bad_debt = borrower_positions.liabilities.get_unchecked(stable_index);
+        assert!(bad_debt > 0);
+
+        pool.bad_debt(borrower);
+        let backstop_positions = pool.get_positions(&fixture.backstop.address);
+        assert_eq!(
+            backstop_positions.liabilities.get_unchecked(stable_index),
+            bad_debt
+        );
+        assert_eq!(pool.get_positions(borrower).liabilities.len(), 0);
+
+        // the backstop auctions off its deposits to cover the bad debt
+        let auction_data = pool.new_auction(
+            &1,
+            &fixture.backstop.address,
+            &vec![&fixture.env, stable.address.clone()],
+            &vec![&fixture.env, fixture.lp.address.clone()],
+            &100,
+        );
+        assert_eq!(
+            auction_data.bid.get_unchecked(stable.address.clone()),
+            bad_debt
+        );
+    }
+
+    fixture.jump_with_sequence(101 * 5);
+
+    for (pool_index, pool_fixture) in fixture.pools.iter().enumerate() {
+        let pool = &pool_fixture.pool;
+        let liquidator = &fixture.users[1 + pool_index * ECONOMY_ACTORS / 2];
+        let backstop_tokens_pre_fill = fixture.backstop.pool_data(&pool.address).tokens;
+        let liquidator_lp_pre_fill = fixture.lp.balance(liquidator);
+
+        let fill_requests: Vec<Request> = vec![
+            &fixture.env,
+            Request {
+                request_type: RequestType::FillBadDebtAuction as u32,
+                address: fixture.backstop.address.clone(),
+                amount: 100,
+            },
+        ];
+        pool.submit(liquidator, liquidator, liquidator, &fill_requests);
+
+        let backstop_positions = pool.get_positions(&fixture.backstop.address);
+        assert_eq!(backstop_positions.liabilities.len(), 0);
+        let lp_received = fixture.lp.balance(liquidator) - liquidator_lp_pre_fill;
+        assert!(lp_received > 0);
+        assert_eq!(
+            fixture.backstop.pool_data(&pool.address).tokens,
+            backstop_tokens_pre_fill - lp_received
+        );
+    }
+}
+
+/// Test a liquidator with no positions can use a flash loan to fill a liquidation
+#[test]
+fn test_economy_flash_loan_liquidation() {
+    let fixture = create_fixture_with_economy(false);
+    let pool_fixture = &fixture.pools[0];
+    let stable = &fixture.tokens[TokenIndex::STABLE];
+    let xlm = &fixture.tokens[TokenIndex::XLM];
+    let stable_index = pool_fixture.reserves[&TokenIndex::STABLE];
+    let xlm_index = pool_fixture.reserves[&TokenIndex::XLM];
+    let borrower = &fixture.users[4];
+
+    let (receiver_address, _) = create_flashloan_receiver(&fixture.env);
+    let liquidator = Address::generate(&fixture.env);
+    let starting_stable_balance = 20_000 * 10i128.pow(6);
+    stable.mint(&liquidator, &starting_stable_balance);
+    stable.approve(
+        &liquidator,
+        &pool_fixture.pool.address,
+        &i128::MAX,
+        &(fixture.env.ledger().sequence() + 17280),
+    );
+
+    tank_xlm_price(&fixture);
+
+    let borrower_positions = pool_fixture.pool.get_positions(borrower);
+    let borrower_collateral = borrower_positions.collateral.get_unchecked(xlm_index);
+    pool_fixture.pool.new_auction(
+        &0,
+        borrower,
+        &vec![&fixture.env, stable.address.clone()],
+        &vec![&fixture.env, xlm.address.clone()],
+        &100,
+    );
+
+    // fill when the full lot is available and the full bid is still owed
+    fixture.jump_with_sequence(201 * 5);
+
+    let borrower_debt = pool_fixture
+        .pool
+        .get_reserve(&stable.address)
+        .to_asset_from_d_token(borrower_positions.liabilities.get_unchecked(stable_index));
+    let flash_loan = FlashLoan {
+        contract: receiver_address.clone(),
+        asset: stable.address.clone(),
+        amount: 1_000 * 10i128.pow(6),
+    };
+    let requests: Vec<Request> = vec![
+        &fixture.env,
+        Request {
+            request_type: RequestType::FillUserLiquidationAuction as u32,
+            address: borrower.clone(),
+            amount: 100,
+        },
+        Request {
+            request_type: RequestType::Repay as u32,
+            address: stable.address.clone(),
+            amount: 10_000 * 10i128.pow(6),
+        },
+    ];
+    let liquidator_positions =
+        pool_fixture
+            .pool
+            .flash_loan(&liquidator, &flash_loan, &requests);
+
+    // the liquidator repaid the flash loan and the liquidated debt, and holds the lot
+    assert_eq!(liquidator_positions.liabilities.len(), 0);
+    assert_eq!(
+        liquidator_positions.collateral.get_unchecked(xlm_index),
+        borrower_collateral
+    );
+    assert_approx_eq_rel(
+        stable.balance(&liquidator),
+        starting_stable_balance - borrower_debt,
+        0_0010000,
+    );
+
+    // the borrower's positions and auction are cleared
+    let borrower_positions = pool_fixture.pool.get_positions(borrower);
+    assert_eq!(borrower_positions.collateral.len(), 0);
+    assert_eq!(borrower_positions.liabilities.len(), 0);
+    assert!(pool_fixture
+        .pool
+        .try_get_auction(&0, borrower)
+        .is_err());
+}
+
+/// Test actors accrue and claim emissions as time progresses
+#[test]
+fn test_economy_emission_claims() {
+    let fixture = create_fixture_with_economy(false);
+    let frodo = &fixture.users[0];
+    let blnd = &fixture.tokens[TokenIndex::BLND];
+
+    // start a new emission cycle for both pools
+    fixture.jump(60 * 60 * 24);
+    fixture.emitter.distribute();
+    fixture.backstop.distribute();
+    fixture.pools[0].pool.gulp_emissions();
+    fixture.pools[1].pool.gulp_emissions();
+
+    let mut total_claimed = [0i128; ECONOMY_ACTORS];
+    for _ in 0..3 {
+        fixture.jump(60 * 60 * 24 * 2);
+
+        let mut claims = [0i128; ECONOMY_ACTORS];
+        for i in 0..ECONOMY_ACTORS {
+            let actor = &fixture.users[1 + i];
+            let pool_index = i / (ECONOMY_ACTORS / 2);
+            let pool_fixture = &fixture.pools[pool_index];
+            // the teapot emits to STABLE dTokens and XLM bTokens, the kettle to STABLE bTokens
+            let reserve_token_ids = if pool_index == 0 {
+                vec![
+                    &fixture.env,
+                    pool_fixture.reserves[&TokenIndex::STABLE] * 2,
+                    pool_fixture.reserves[&TokenIndex::XLM] * 2 + 1,
+                ]
+            } else {
+                vec![
+                    &fixture.env,
+                    pool_fixture.reserves[&TokenIndex::STABLE] * 2 + 1,
+                ]
+            };
+            claims[i] = pool_fixture.pool.claim(actor, &reserve_token_ids, actor);
+            total_claimed[i] += claims[i];
+
+            // a claim in the same ledger yields nothing
+            assert_eq!(
+                pool_fixture.pool.claim(actor, &reserve_token_ids, actor),
+                0
+            );
+
+            let is_borrower = i % 2 == 1;
+            if (pool_index == 0) == is_borrower {
+                assert!(claims[i] > 0);
+            } else {
+                assert_eq!(claims[i], 0);
+            }
+        }
+
+        // actors with matching positions earn matching emissions
+        for i in 2..ECONOMY_ACTORS {
+            if claims[i] > 0 && i % (ECONOMY_ACTORS / 2) > 1 {
+                assert_approx_eq_rel(claims[i], claims[i - 2], 0_0100000);
+            }
+        }
+    }
+
+    for i in 0..ECONOMY_ACTORS {
+        assert_eq!(blnd.balance(&fixture.users[1 + i]), total_claimed[i]);
+    }
+
+    // frodo earns backstop emissions from both pools
+    let backstop_claim = fixture.backstop.claim(
+        frodo,
+        &vec![
+            &fixture.env,
+            fixture.pools[0].pool.address.clone(),
+            fixture.pools[1].pool.address.clone(),
+        ],
+        frodo,
+    );
+    assert!(backstop_claim > 0);
+}
@@ -1,6 +1,6 @@
 #![cfg(test)]
 
-use pool::{Request, RequestType, ReserveEmissionMetadata};
+use pool::{InterestRateConfig, Request, RequestType, ReserveEmissionMetadata};
 use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{
     testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events},
@@ -10,7 +10,7 @@ use test_suites::{
     assertions::assert_approx_eq_abs,
     create_fixture_with_data,
     pool::default_reserve_metadata,
-    test_fixture::{TokenIndex, SCALAR_7, SCALAR_9},
+    test_fixture::{TokenIndex, SCALAR_12, SCALAR_7},
 };
 
 /// Test user exposed functions on the lending pool for basic user functionality, auth, and events.
@@ -113,7 +113,7 @@ fn test_pool_user() {
     assert_eq!(weth.balance(&pool_fixture.pool.address), pool_weth_balance);
     assert_eq!(weth.allowance(&sam, &pool_fixture.pool.address), 0);
     sam_weth_btoken_balance += amount
-        .fixed_div_floor(reserve_data.b_rate, SCALAR_9)
+        .fixed_div_floor(reserve_data.b_rate, SCALAR_12)
         .unwrap();
     assert_approx_eq_abs(
         result.supply.get_unchecked(weth_pool_index),
@@ -163,7 +163,7 @@ fn test_pool_user() {
     assert_eq!(weth.balance(&sam), sam_weth_balance);
     assert_eq!(weth.balance(&pool_fixture.pool.address), pool_weth_balance);
     let pool_tokens = amount
-        .fixed_div_ceil(reserve_data.b_rate, SCALAR_9)
+        .fixed_div_ceil(reserve_data.b_rate, SCALAR_12)
         .unwrap();
     sam_weth_btoken_balance -= pool_tokens;
     assert_approx_eq_abs(
@@ -245,7 +245,7 @@ fn test_pool_user() {
     assert_eq!(xlm.balance(&sam), sam_xlm_balance);
     assert_eq!(xlm.balance(&pool_fixture.pool.address), pool_xlm_balance);
     sam_xlm_btoken_balance += amount
-        .fixed_div_floor(reserve_data.b_rate, SCALAR_9)
+        .fixed_div_floor(reserve_data.b_rate, SCALAR_12)
         .unwrap();
     assert_approx_eq_abs(
         result.collateral.get_unchecked(xlm_pool_index),
@@ -316,7 +316,7 @@ fn test_pool_user() {
     assert_eq!(weth.balance(&sam), sam_weth_balance);
     assert_eq!(weth.balance(&pool_fixture.pool.address), pool_weth_balance);
     sam_weth_dtoken_balance += amount
-        .fixed_div_ceil(reserve_data.d_rate, SCALAR_9)
+        .fixed_div_ceil(reserve_data.d_rate, SCALAR_12)
         .unwrap();
     assert_eq!(
         result.liabilities.get_unchecked(weth_pool_index),
@@ -413,7 +413,7 @@ fn test_pool_user() {
     let event = vec![&fixture.env, events.get_unchecked(events.len() - 5)];
     let xlm_reserve_data = fixture.read_reserve_data(0, TokenIndex::XLM);
     let est_xlm = sam_xlm_btoken_balance
-        .fixed_mul_floor(xlm_reserve_data.b_rate, SCALAR_9)
+        .fixed_mul_floor(xlm_reserve_data.b_rate, SCALAR_12)
         .unwrap();
     pool_xlm_balance -= est_xlm;
     sam_xlm_balance += est_xlm;
@@ -447,7 +447,7 @@ fn test_pool_user() {
     );
     let weth_reserve_data = fixture.read_reserve_data(0, TokenIndex::WETH);
     let est_weth = sam_weth_dtoken_balance
-        .fixed_mul_ceil(weth_reserve_data.d_rate, SCALAR_9)
+        .fixed_mul_ceil(weth_reserve_data.d_rate, SCALAR_12)
         .unwrap();
     pool_weth_balance += est_weth;
     sam_weth_balance -= est_weth;
@@ -585,7 +585,7 @@ fn test_pool_config() {
     reserve_config.c_factor = 0_200_0000;
     pool_fixture
         .pool
-        .queue_set_reserve(&blnd.address, &reserve_config);
+        .queue_set_reserve(&blnd.address, &reserve_config, &None);
     assert_eq!(
         fixture.env.auths()[0],
         (
@@ -597,7 +597,8 @@ fn test_pool_config() {
                     vec![
                         &fixture.env,
                         blnd.address.to_val(),
-                        reserve_config.into_val(&fixture.env)
+                        reserve_config.into_val(&fixture.env),
+                        None::<InterestRateConfig>.into_val(&fixture.env)
                     ]
                 )),
                 sub_invocations: std::vec![]
@@ -634,7 +635,7 @@ fn test_pool_config() {
     reserve_config.c_factor = 0;
     pool_fixture
         .pool
-        .queue_set_reserve(&blnd.address, &reserve_config);
+        .queue_set_reserve(&blnd.address, &reserve_config, &None);
     assert_eq!(
         fixture.env.auths()[0],
         (
@@ -646,7 +647,8 @@ fn test_pool_config() {
                     vec![
                         &fixture.env,
                         blnd.address.to_val(),
-                        reserve_config.into_val(&fixture.env)
+                        reserve_config.into_val(&fixture.env),
+                        None::<InterestRateConfig>.into_val(&fixture.env)
                     ]
                 )),
                 sub_invocations: std::vec![]
@@ -683,9 +685,9 @@ fn test_pool_config() {
     assert_eq!(new_reserve_config.c_factor, 0);
     assert_eq!(new_reserve_config.index, 3);
 
-    // Set admin (admin only)
+    // Propose admin (admin only)
     let new_admin = Address::generate(&fixture.env);
-    pool_fixture.pool.set_admin(&new_admin);
+    pool_fixture.pool.propose_admin(&new_admin);
     assert_eq!(
         fixture.env.auths()[0],
         (
@@ -693,27 +695,36 @@ fn test_pool_config() {
             AuthorizedInvocation {
                 function: AuthorizedFunction::Contract((
                     pool_fixture.pool.address.clone(),
-                    Symbol::new(&fixture.env, "set_admin"),
+                    Symbol::new(&fixture.env, "propose_admin"),
                     vec![&fixture.env, new_admin.to_val(),]
                 )),
                 sub_invocations: std::vec![]
             }
         )
     );
+    let pending_admin = pool_fixture.pool.get_pending_admin().unwrap();
+    assert_eq!(pending_admin.new_admin, new_admin);
+    assert_eq!(pool_fixture.pool.get_admin(), fixture.bombadil);
+
+    // Accept admin (proposed admin only, once the admin delay has passed)
+    fixture.jump(pool_fixture.pool.get_admin_delay());
+    pool_fixture.pool.accept_admin();
     assert_eq!(
-        fixture.env.auths()[1],
+        fixture.env.auths()[0],
         (
             new_admin.clone(),
             AuthorizedInvocation {
                 function: AuthorizedFunction::Contract((
                     pool_fixture.pool.address.clone(),
-                    Symbol::new(&fixture.env, "set_admin"),
-                    vec![&fixture.env, new_admin.to_val(),]
+                    Symbol::new(&fixture.env, "accept_admin"),
+                    vec![&fixture.env]
                 )),
                 sub_invocations: std::vec![]
             }
         )
     );
+    assert_eq!(pool_fixture.pool.get_admin(), new_admin);
+    assert!(pool_fixture.pool.get_pending_admin().is_none());
     let event = vec![&fixture.env, fixture.env.events().all().last_unchecked()];
     assert_eq!(
         event,
@@ -0,0 +1,110 @@
+#![cfg(test)]
+use pool::RequestType;
+use portfolio_router::{
+    PoolRequests, PortfolioRouterContract, PortfolioRouterContractClient, Request,
+};
+use soroban_sdk::{testutils::Address as _, vec, Address, Error};
+use test_suites::{
+    assertions::assert_approx_eq_rel,
+    create_fixture_with_economy,
+    test_fixture::{TokenIndex, SCALAR_7},
+};
+
+#[test]
+fn test_portfolio_router() {
+    let fixture = create_fixture_with_economy(false);
+    let teapot = &fixture.pools[0];
+    let kettle = &fixture.pools[1];
+    let stable = &fixture.tokens[TokenIndex::STABLE];
+    let xlm = &fixture.tokens[TokenIndex::XLM];
+
+    let samwise = Address::generate(&fixture.env);
+    stable.mint(&samwise, &(10_000 * 10i128.pow(6)));
+    xlm.mint(&samwise, &(100_000 * SCALAR_7));
+
+    let router_address = fixture.env.register(
+        PortfolioRouterContract {},
+        (fixture.bombadil.clone(), 1_5000000u32),
+    );
+    let router = PortfolioRouterContractClient::new(&fixture.env, &router_address);
+    router.add_pool(&teapot.pool.address);
+    router.add_pool(&kettle.pool.address);
+    assert_eq!(
+        router.get_pools(),
+        vec![
+            &fixture.env,
+            teapot.pool.address.clone(),
+            kettle.pool.address.clone()
+        ]
+    );
+
+    // supply STABLE in the Teapot pool and borrow STABLE against XLM in the Kettle pool
+    let requests = vec![
+        &fixture.env,
+        PoolRequests {
+            pool: teapot.pool.address.clone(),
+            requests: vec![
+                &fixture.env,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: stable.address.clone(),
+                    amount: 10_000 * 10i128.pow(6),
+                },
+            ],
+        },
+        PoolRequests {
+            pool: kettle.pool.address.clone(),
+            requests: vec![
+                &fixture.env,
+                Request {
+                    request_type: RequestType::SupplyCollateral as u32,
+                    address: xlm.address.clone(),
+                    amount: 100_000 * SCALAR_7,
+                },
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: stable.address.clone(),
+                    amount: 5_000 * 10i128.pow(6),
+                },
+            ],
+        },
+    ];
+    let portfolio = router.submit(&samwise, &requests);
+    assert_eq!(stable.balance(&samwise), 5_000 * 10i128.pow(6));
+    assert_eq!(xlm.balance(&samwise), 0);
+
+    // collateral is valued at $10k in each pool
+    assert_approx_eq_rel(portfolio.collateral, 20_000 * SCALAR_7, 0_0000100);
+    assert_approx_eq_rel(portfolio.liabilities, 5_000 * SCALAR_7, 0_0000100);
+    // 10k * 0.9 + 10k * 0.75
+    assert_approx_eq_rel(portfolio.effective_collateral, 16_500 * SCALAR_7, 0_0000100);
+    // 5k / 0.95
+    assert_approx_eq_rel(portfolio.effective_liabilities, 5_263_1578947, 0_0000100);
+    assert_approx_eq_rel(portfolio.health_factor, 3_1350000, 0_0000100);
+    let loaded = router.get_portfolio(&samwise);
+    assert_eq!(loaded.health_factor, portfolio.health_factor);
+
+    // a borrow the Kettle pool allows on its own is rejected under the combined minimum
+    router.set_min_health_factor(&3_0000000);
+    let requests = vec![
+        &fixture.env,
+        PoolRequests {
+            pool: kettle.pool.address.clone(),
+            requests: vec![
+                &fixture.env,
+                Request {
+                    request_type: RequestType::Borrow as u32,
+                    address: stable.address.clone(),
+                    amount: 500 * 10i128.pow(6),
+                },
+            ],
+        },
+    ];
+    let result = router.try_submit(&samwise, &requests);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(1903))));
+
+    // requests can only be routed to registered pools
+    router.remove_pool(&kettle.pool.address);
+    let result = router.try_submit(&samwise, &requests);
+    assert_eq!(result.err(), Some(Ok(Error::from_contract_error(1901))));
+}
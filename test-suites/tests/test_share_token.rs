@@ -0,0 +1,68 @@
+#![cfg(test)]
+use sep_41_token::TokenClient;
+use soroban_sdk::{testutils::Address as _, Address, String};
+use test_suites::{create_fixture_with_data, share_token::SHARE_TOKEN_WASM, test_fixture::SCALAR_7};
+
+#[test]
+fn test_share_token() {
+    let fixture = create_fixture_with_data(false);
+    let frodo = fixture.users[0].clone();
+    let pool_fixture = &fixture.pools[0];
+    let pool_address = pool_fixture.pool.address.clone();
+
+    let samwise = Address::generate(&fixture.env);
+    let merry = Address::generate(&fixture.env);
+
+    // deploy the share token for the pool's backstop
+    let share_token_hash = fixture
+        .env
+        .deployer()
+        .upload_contract_wasm(SHARE_TOKEN_WASM);
+    fixture.backstop.set_share_token_wasm(&share_token_hash);
+    let share_token_address = pool_fixture.pool.deploy_backstop_share_token(
+        &String::from_str(&fixture.env, "Blend Backstop Share"),
+        &String::from_str(&fixture.env, "bBLP"),
+    );
+    assert_eq!(
+        fixture.backstop.share_token(&pool_address),
+        Some(share_token_address.clone())
+    );
+    let share_token = TokenClient::new(&fixture.env, &share_token_address);
+    assert_eq!(share_token.decimals(), 7);
+    assert_eq!(share_token.symbol(), String::from_str(&fixture.env, "bBLP"));
+
+    // the share token tracks backstop shares, excluding queued withdrawals
+    let frodo_shares = fixture.backstop.user_balance(&pool_address, &frodo).shares;
+    assert_eq!(share_token.balance(&frodo), frodo_shares);
+    assert_eq!(share_token.total_supply(), frodo_shares);
+    fixture
+        .backstop
+        .queue_withdrawal(&frodo, &pool_address, &(10_000 * SCALAR_7));
+    assert_eq!(share_token.balance(&frodo), frodo_shares - 10_000 * SCALAR_7);
+    assert_eq!(share_token.total_supply(), frodo_shares - 10_000 * SCALAR_7);
+
+    // transfer shares
+    share_token.transfer(&frodo, &samwise, &(5_000 * SCALAR_7));
+    assert_eq!(share_token.balance(&samwise), 5_000 * SCALAR_7);
+    assert_eq!(
+        fixture.backstop.user_balance(&pool_address, &samwise).shares,
+        5_000 * SCALAR_7
+    );
+
+    // shares queued for withdrawal can't be transferred
+    let result = share_token.try_transfer(&frodo, &samwise, &frodo_shares);
+    assert!(result.is_err());
+
+    // transferred shares can be queued for withdrawal from the backstop
+    fixture
+        .backstop
+        .queue_withdrawal(&samwise, &pool_address, &(5_000 * SCALAR_7));
+    assert_eq!(share_token.balance(&samwise), 0);
+
+    // shares can be spent with an allowance
+    let approval_ledger = fixture.env.ledger().sequence() + 17280;
+    share_token.approve(&frodo, &merry, &(1_000 * SCALAR_7), &approval_ledger);
+    share_token.transfer_from(&merry, &frodo, &merry, &(1_000 * SCALAR_7));
+    assert_eq!(share_token.allowance(&frodo, &merry), 0);
+    assert_eq!(share_token.balance(&merry), 1_000 * SCALAR_7);
+}
@@ -0,0 +1,60 @@
+#![cfg(test)]
+use soroban_sdk::{testutils::Address as _, Address};
+use test_suites::{
+    create_fixture_with_data,
+    test_fixture::{TokenIndex, SCALAR_7},
+};
+use vault::{VaultContract, VaultContractClient};
+
+#[test]
+fn test_vault() {
+    let fixture = create_fixture_with_data(false);
+    let pool_fixture = &fixture.pools[0];
+    let xlm = &fixture.tokens[TokenIndex::XLM];
+    let xlm_pool_index = pool_fixture.reserves[&TokenIndex::XLM];
+
+    let samwise = Address::generate(&fixture.env);
+    xlm.mint(&samwise, &(100 * SCALAR_7));
+
+    let vault_address = fixture.env.register(
+        VaultContract {},
+        (pool_fixture.pool.address.clone(), xlm.address.clone()),
+    );
+    let vault = VaultContractClient::new(&fixture.env, &vault_address);
+    assert_eq!(vault.asset(), xlm.address);
+    assert_eq!(vault.pool(), pool_fixture.pool.address);
+
+    // deposit mints shares 1:1 with the bTokens supplied by the vault
+    let expected_shares = vault.convert_to_shares(&(100 * SCALAR_7));
+    let shares = vault.deposit(&samwise, &(100 * SCALAR_7));
+    assert_eq!(shares, expected_shares);
+    assert_eq!(vault.balance(&samwise), shares);
+    assert_eq!(vault.total_shares(), shares);
+    assert_eq!(xlm.balance(&samwise), 0);
+    let vault_positions = pool_fixture.pool.get_positions(&vault_address);
+    assert_eq!(vault_positions.supply.get_unchecked(xlm_pool_index), shares);
+    assert_eq!(vault_positions.collateral.len(), 0);
+    let total_assets = vault.total_assets();
+    assert!(total_assets <= 100 * SCALAR_7 && total_assets >= 100 * SCALAR_7 - 1);
+
+    // shares accrue interest
+    fixture.jump(7 * 24 * 60 * 60);
+    assert!(vault.convert_to_assets(&shares) > 100 * SCALAR_7);
+
+    // withdraw by assets
+    let shares_burnt = vault.withdraw(&samwise, &(50 * SCALAR_7));
+    assert!(shares_burnt < shares / 2);
+    assert_eq!(xlm.balance(&samwise), 50 * SCALAR_7);
+    assert_eq!(vault.balance(&samwise), shares - shares_burnt);
+    assert_eq!(vault.total_shares(), shares - shares_burnt);
+
+    // redeem the remaining shares
+    let remaining_shares = vault.balance(&samwise);
+    let assets = vault.redeem(&samwise, &remaining_shares);
+    assert!(assets > 50 * SCALAR_7);
+    assert_eq!(xlm.balance(&samwise), 50 * SCALAR_7 + assets);
+    assert_eq!(vault.balance(&samwise), 0);
+    assert_eq!(vault.total_shares(), 0);
+    let vault_positions = pool_fixture.pool.get_positions(&vault_address);
+    assert_eq!(vault_positions.supply.len(), 0);
+}
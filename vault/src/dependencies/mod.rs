@@ -0,0 +1,2 @@
+mod pool;
+pub use pool::{Client as PoolClient, Request, Reserve};
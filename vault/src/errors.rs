@@ -0,0 +1,18 @@
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+/// Error codes for the vault contract. Common errors are codes that match up with the built-in
+/// contracts error reporting. Vault specific errors start at 1500.
+pub enum VaultError {
+    // Common Errors
+    InternalError = 1,
+    AlreadyInitializedError = 3,
+
+    NegativeAmountError = 8,
+    BalanceError = 10,
+
+    // Vault
+    InvalidAmount = 1500,
+}
@@ -0,0 +1,33 @@
+use soroban_sdk::{Address, Env, Symbol};
+
+pub struct VaultEvents {}
+
+impl VaultEvents {
+    /// Emitted when assets are deposited into the vault
+    ///
+    /// - topics - `["deposit", from: Address]`
+    /// - data - `[assets: i128, shares: i128]`
+    ///
+    /// ### Arguments
+    /// * `from` - The address depositing assets
+    /// * `assets` - The amount of assets deposited
+    /// * `shares` - The amount of shares minted
+    pub fn deposit(e: &Env, from: Address, assets: i128, shares: i128) {
+        let topics = (Symbol::new(e, "deposit"), from);
+        e.events().publish(topics, (assets, shares));
+    }
+
+    /// Emitted when assets are withdrawn from the vault
+    ///
+    /// - topics - `["withdraw", from: Address]`
+    /// - data - `[assets: i128, shares: i128]`
+    ///
+    /// ### Arguments
+    /// * `from` - The address withdrawing assets
+    /// * `assets` - The amount of assets withdrawn
+    /// * `shares` - The amount of shares burnt
+    pub fn withdraw(e: &Env, from: Address, assets: i128, shares: i128) {
+        let topics = (Symbol::new(e, "withdraw"), from);
+        e.events().publish(topics, (assets, shares));
+    }
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+#[cfg(any(test, feature = "testutils"))]
+extern crate std;
+
+mod dependencies;
+mod errors;
+mod events;
+mod storage;
+mod vault;
+
+pub use errors::VaultError;
+pub use storage::{VaultDataKey, VaultMeta};
+pub use vault::*;
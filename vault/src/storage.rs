@@ -0,0 +1,102 @@
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, Symbol};
+
+/********** Ledger Thresholds **********/
+
+const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5s a ledger
+
+const LEDGER_THRESHOLD_INSTANCE: u32 = ONE_DAY_LEDGERS * 30; // ~ 30 days
+const LEDGER_BUMP_INSTANCE: u32 = LEDGER_THRESHOLD_INSTANCE + ONE_DAY_LEDGERS; // ~ 31 days
+
+const LEDGER_THRESHOLD_USER: u32 = ONE_DAY_LEDGERS * 100; // ~ 100 days
+const LEDGER_BUMP_USER: u32 = LEDGER_THRESHOLD_USER + 20 * ONE_DAY_LEDGERS; // ~ 120 days
+
+const META_KEY: &str = "Meta";
+const TOTAL_SHARES_KEY: &str = "TotShares";
+
+#[derive(Clone)]
+#[contracttype]
+pub enum VaultDataKey {
+    // The share balance of a user
+    Shares(Address),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct VaultMeta {
+    pub pool: Address,  // the pool the vault supplies to
+    pub asset: Address, // the underlying asset of the reserve
+}
+
+/// Bump the instance rent for the contract
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LEDGER_THRESHOLD_INSTANCE, LEDGER_BUMP_INSTANCE);
+}
+
+/// Fetch the vault metadata
+pub fn get_meta(e: &Env) -> VaultMeta {
+    e.storage()
+        .instance()
+        .get::<Symbol, VaultMeta>(&Symbol::new(e, META_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the vault metadata
+///
+/// ### Arguments
+/// * `meta` - The metadata of the vault
+pub fn set_meta(e: &Env, meta: &VaultMeta) {
+    e.storage()
+        .instance()
+        .set::<Symbol, VaultMeta>(&Symbol::new(e, META_KEY), meta)
+}
+
+/// Fetch the total shares issued by the vault
+pub fn get_total_shares(e: &Env) -> i128 {
+    e.storage()
+        .instance()
+        .get::<Symbol, i128>(&Symbol::new(e, TOTAL_SHARES_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the total shares issued by the vault
+///
+/// ### Arguments
+/// * `shares` - The total shares issued by the vault
+pub fn set_total_shares(e: &Env, shares: i128) {
+    e.storage()
+        .instance()
+        .set::<Symbol, i128>(&Symbol::new(e, TOTAL_SHARES_KEY), &shares)
+}
+
+/// Fetch the share balance of a user
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn get_shares(e: &Env, user: &Address) -> i128 {
+    let key = VaultDataKey::Shares(user.clone());
+    if let Some(result) = e.storage().persistent().get::<VaultDataKey, i128>(&key) {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+        result
+    } else {
+        0
+    }
+}
+
+/// Set the share balance of a user
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `shares` - The share balance of the user
+pub fn set_shares(e: &Env, user: &Address, shares: i128) {
+    let key = VaultDataKey::Shares(user.clone());
+    e.storage()
+        .persistent()
+        .set::<VaultDataKey, i128>(&key, &shares);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
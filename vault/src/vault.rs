@@ -0,0 +1,247 @@
+use crate::{
+    dependencies::{PoolClient, Request, Reserve},
+    errors::VaultError,
+    events::VaultEvents,
+    storage::{self, VaultMeta},
+};
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env,
+};
+
+const SCALAR_12: i128 = 1_000_000_000_000;
+
+// pool request types used by the vault
+const REQUEST_TYPE_SUPPLY: u32 = 0;
+const REQUEST_TYPE_WITHDRAW: u32 = 1;
+
+/// ### Vault
+///
+/// A single asset vault over the supply of a pool reserve. Deposits are supplied to the pool
+/// as non-collateral supply owned by the vault, and each vault share tracks one bToken of the
+/// vault's position, so shares accrue interest at the reserve's supply rate.
+#[contract]
+pub struct VaultContract;
+
+#[contractimpl]
+impl VaultContract {
+    /// Construct the vault contract
+    ///
+    /// ### Arguments
+    /// * `pool` - The pool the vault supplies to
+    /// * `asset` - The underlying asset of the reserve
+    pub fn __constructor(e: Env, pool: Address, asset: Address) {
+        storage::set_meta(&e, &VaultMeta { pool, asset });
+    }
+
+    /// Fetch the underlying asset of the vault
+    pub fn asset(e: Env) -> Address {
+        storage::get_meta(&e).asset
+    }
+
+    /// Fetch the pool the vault supplies to
+    pub fn pool(e: Env) -> Address {
+        storage::get_meta(&e).pool
+    }
+
+    /// Fetch the share balance of "id"
+    ///
+    /// ### Arguments
+    /// * `id` - The address to fetch the balance of
+    pub fn balance(e: Env, id: Address) -> i128 {
+        storage::get_shares(&e, &id)
+    }
+
+    /// Fetch the total shares issued by the vault
+    pub fn total_shares(e: Env) -> i128 {
+        storage::get_total_shares(&e)
+    }
+
+    /// Fetch the total amount of assets held by the vault
+    pub fn total_assets(e: Env) -> i128 {
+        let reserve = load_reserve(&e, &storage::get_meta(&e));
+        to_assets(storage::get_total_shares(&e), &reserve)
+    }
+
+    /// Fetch the amount of shares a deposit of "assets" currently mints
+    ///
+    /// ### Arguments
+    /// * `assets` - The amount of assets
+    pub fn convert_to_shares(e: Env, assets: i128) -> i128 {
+        let reserve = load_reserve(&e, &storage::get_meta(&e));
+        assets
+            .fixed_div_floor(reserve.b_rate, SCALAR_12)
+            .unwrap_optimized()
+    }
+
+    /// Fetch the amount of assets "shares" can currently be redeemed for
+    ///
+    /// ### Arguments
+    /// * `shares` - The amount of shares
+    pub fn convert_to_assets(e: Env, shares: i128) -> i128 {
+        let reserve = load_reserve(&e, &storage::get_meta(&e));
+        to_assets(shares, &reserve)
+    }
+
+    /// Deposit "assets" from "from" into the vault
+    ///
+    /// Returns the amount of shares minted to "from"
+    ///
+    /// ### Arguments
+    /// * `from` - The address depositing assets
+    /// * `assets` - The amount of assets to deposit
+    ///
+    /// ### Panics
+    /// If the amount is not positive or the pool rejects the supply
+    pub fn deposit(e: Env, from: Address, assets: i128) -> i128 {
+        from.require_auth();
+        storage::extend_instance(&e);
+        require_positive(&e, assets);
+
+        let meta = storage::get_meta(&e);
+        let reserve = load_reserve(&e, &meta);
+        let shares = submit_for_vault(
+            &e,
+            &meta,
+            reserve.index,
+            &from,
+            &e.current_contract_address(),
+            REQUEST_TYPE_SUPPLY,
+            assets,
+        );
+        storage::set_shares(&e, &from, storage::get_shares(&e, &from) + shares);
+        storage::set_total_shares(&e, storage::get_total_shares(&e) + shares);
+
+        VaultEvents::deposit(&e, from, assets, shares);
+        shares
+    }
+
+    /// Withdraw "assets" from the vault to "from"
+    ///
+    /// Returns the amount of shares burnt from "from"
+    ///
+    /// ### Arguments
+    /// * `from` - The address withdrawing assets
+    /// * `assets` - The amount of assets to withdraw
+    ///
+    /// ### Panics
+    /// If the amount is not positive, "from" does not have enough shares, or the pool rejects
+    /// the withdrawal
+    pub fn withdraw(e: Env, from: Address, assets: i128) -> i128 {
+        from.require_auth();
+        storage::extend_instance(&e);
+        require_positive(&e, assets);
+
+        let meta = storage::get_meta(&e);
+        let reserve = load_reserve(&e, &meta);
+        let shares = burn_for_withdrawal(&e, &meta, &reserve, &from, assets);
+
+        VaultEvents::withdraw(&e, from, assets, shares);
+        shares
+    }
+
+    /// Redeem "shares" of "from" for assets sent to "from"
+    ///
+    /// Returns the amount of assets withdrawn
+    ///
+    /// ### Arguments
+    /// * `from` - The address redeeming shares
+    /// * `shares` - The amount of shares to redeem
+    ///
+    /// ### Panics
+    /// If the amount is not positive, "from" does not have enough shares, or the pool rejects
+    /// the withdrawal
+    pub fn redeem(e: Env, from: Address, shares: i128) -> i128 {
+        from.require_auth();
+        storage::extend_instance(&e);
+        require_positive(&e, shares);
+        if shares > storage::get_shares(&e, &from) {
+            panic_with_error!(&e, VaultError::BalanceError);
+        }
+
+        let meta = storage::get_meta(&e);
+        let reserve = load_reserve(&e, &meta);
+        let assets = to_assets(shares, &reserve);
+        let shares_burnt = burn_for_withdrawal(&e, &meta, &reserve, &from, assets);
+
+        VaultEvents::withdraw(&e, from, assets, shares_burnt);
+        assets
+    }
+}
+
+/// Withdraw "assets" from the vault's position to "from" and burn the matching shares
+///
+/// Returns the amount of shares burnt
+fn burn_for_withdrawal(
+    e: &Env,
+    meta: &VaultMeta,
+    reserve: &Reserve,
+    from: &Address,
+    assets: i128,
+) -> i128 {
+    let shares = -submit_for_vault(
+        e,
+        meta,
+        reserve.index,
+        &e.current_contract_address(),
+        from,
+        REQUEST_TYPE_WITHDRAW,
+        assets,
+    );
+    let balance = storage::get_shares(e, from);
+    if shares > balance {
+        panic_with_error!(e, VaultError::BalanceError);
+    }
+    storage::set_shares(e, from, balance - shares);
+    storage::set_total_shares(e, storage::get_total_shares(e) - shares);
+    shares
+}
+
+/// Submit a request against the vault's position in the pool
+///
+/// Returns the change in the vault's bToken balance
+fn submit_for_vault(
+    e: &Env,
+    meta: &VaultMeta,
+    reserve_index: u32,
+    spender: &Address,
+    to: &Address,
+    request_type: u32,
+    amount: i128,
+) -> i128 {
+    let pool_client = PoolClient::new(e, &meta.pool);
+    let vault = e.current_contract_address();
+    let b_tokens_before = pool_client
+        .get_positions(&vault)
+        .supply
+        .get(reserve_index)
+        .unwrap_or(0);
+    let requests = vec![
+        e,
+        Request {
+            request_type,
+            address: meta.asset.clone(),
+            amount,
+        },
+    ];
+    let positions = pool_client.submit(&vault, spender, to, &requests);
+    positions.supply.get(reserve_index).unwrap_or(0) - b_tokens_before
+}
+
+/// Load the reserve of the vault from the pool, updated to the current ledger
+fn load_reserve(e: &Env, meta: &VaultMeta) -> Reserve {
+    PoolClient::new(e, &meta.pool).get_reserve(&meta.asset)
+}
+
+/// Convert shares to assets at the reserve's bToken rate, rounding down
+fn to_assets(shares: i128, reserve: &Reserve) -> i128 {
+    shares
+        .fixed_mul_floor(reserve.b_rate, SCALAR_12)
+        .unwrap_optimized()
+}
+
+fn require_positive(e: &Env, amount: i128) {
+    if amount <= 0 {
+        panic_with_error!(e, VaultError::InvalidAmount);
+    }
+}